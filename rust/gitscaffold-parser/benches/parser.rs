@@ -0,0 +1,64 @@
+//! Parse, validate and render synthetic roadmaps of 10, 1k and 10k
+//! features. The 1k-feature parse should stay under 50ms.
+
+use std::fmt::Write;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use gitscaffold_parser::parse_markdown;
+use gitscaffold_parser::render::{render_markdown, RenderOptions};
+use gitscaffold_parser::validate::{validate, RuleConfig};
+
+const SIZES: [(&str, usize); 3] = [("small", 10), ("medium", 1_000), ("large", 10_000)];
+
+/// A roadmap with `features` features spread over ten milestones, each
+/// with metadata, a description with a link and three tasks.
+fn roadmap(features: usize) -> String {
+    let mut out = String::from("---\nrepo: acme/app\n---\n# Synthetic\n\nA generated roadmap.\n\n");
+    out.push_str("## Milestones\n\n");
+    for m in 1..=10 {
+        writeln!(out, "- **v{}** — 2025-{:02}-15", m, m).unwrap();
+    }
+    out.push_str("\n## Features\n\n");
+    for f in 1..=features {
+        writeln!(out, "### Feature {} [in-progress] (3d)\n", f).unwrap();
+        writeln!(
+            out,
+            "Lets users do thing {} without the [old flow](https://example.com/{}).\n",
+            f, f
+        )
+        .unwrap();
+        writeln!(out, "Milestone: v{}", f % 10 + 1).unwrap();
+        writeln!(out, "Labels: backend, area-{}", f % 7).unwrap();
+        writeln!(out, "Assignees: @dev{}\n", f % 5).unwrap();
+        for t in 1..=3 {
+            let mark = if t == 1 { 'x' } else { ' ' };
+            writeln!(out, "- [{}] Task {}.{}", mark, f, t).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("roadmap");
+    group.sample_size(10);
+    for (name, features) in SIZES {
+        let source = roadmap(features);
+        let parsed = parse_markdown(&source).unwrap();
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::new("parse", name), &source, |b, source| {
+            b.iter(|| parse_markdown(source).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("validate", name), &parsed, |b, roadmap| {
+            b.iter(|| validate(roadmap, &RuleConfig::default()))
+        });
+        group.bench_with_input(BenchmarkId::new("render", name), &parsed, |b, roadmap| {
+            b.iter(|| render_markdown(roadmap, RenderOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
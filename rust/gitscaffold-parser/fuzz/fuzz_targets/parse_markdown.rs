@@ -0,0 +1,19 @@
+//! Parse arbitrary text as a Markdown roadmap. The parser must not panic,
+//! and whatever it returns must serialize to JSON that reads back.
+
+#![no_main]
+
+use gitscaffold_parser::render::{render_markdown, RenderOptions};
+use gitscaffold_parser::parse_markdown;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let Ok(roadmap) = parse_markdown(source) else {
+        return;
+    };
+    let json = serde_json::to_string(&roadmap).unwrap();
+    serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    // Rendering must cope with anything the parser produces.
+    let markdown = render_markdown(&roadmap, RenderOptions::default());
+    let _ = parse_markdown(&markdown);
+});
@@ -0,0 +1,77 @@
+//! Merging a roadmap split across several files.
+
+use crate::graph;
+use crate::roadmap::Roadmap;
+
+/// Merge roadmaps parsed from several files, in order. Milestones, features
+/// and warnings are concatenated and record the file they came from, or the
+/// fragment they were included from; the name, description and each
+/// metadata key come from the first file that has them. Dependencies are
+/// resolved again, so features may depend on features in other files.
+/// Duplicates across files are left for validation to report.
+pub fn aggregate(parts: Vec<(String, Roadmap)>) -> Roadmap {
+    let mut merged = Roadmap::default();
+    for (file, part) in parts {
+        if merged.name.is_empty() {
+            merged.name = part.name;
+        }
+        if merged.description.is_empty() {
+            merged.description = part.description;
+        }
+        merged.version = merged.version.or(part.version);
+        for (key, value) in part.metadata {
+            merged.metadata.entry(key).or_insert(value);
+        }
+        merged
+            .milestones
+            .extend(part.milestones.into_iter().map(|mut milestone| {
+                milestone.file.get_or_insert_with(|| file.clone());
+                milestone
+            }));
+        merged
+            .features
+            .extend(part.features.into_iter().map(|mut feature| {
+                feature.file.get_or_insert_with(|| file.clone());
+                feature
+            }));
+        merged.extra_sections.extend(part.extra_sections);
+        merged
+            .warnings
+            .extend(part.warnings.into_iter().map(|mut warning| {
+                warning.file.get_or_insert_with(|| file.clone());
+                warning
+            }));
+    }
+    graph::resolve(&mut merged);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    #[test]
+    fn merges_files_with_provenance() {
+        let payments = parse_roadmap(
+            "# Payments\n\n## Milestones\n- Q1\n\n## Features\n\n### Checkout\nDepends on: Login\n",
+        );
+        let auth = parse_roadmap("# Auth\n\nAbout auth.\n\n## Features\n\n### Login\n");
+        let merged = aggregate(vec![
+            ("roadmap/payments.md".into(), payments),
+            ("roadmap/auth.md".into(), auth),
+        ]);
+        assert_eq!(merged.name, "Payments");
+        assert_eq!(merged.description, "About auth.");
+        assert_eq!(
+            merged.milestones[0].file.as_deref(),
+            Some("roadmap/payments.md")
+        );
+        let files: Vec<_> = merged.features.iter().map(|f| f.file.as_deref()).collect();
+        assert_eq!(
+            files,
+            [Some("roadmap/payments.md"), Some("roadmap/auth.md")]
+        );
+        assert_eq!(merged.features[0].dependencies, [1]);
+    }
+}
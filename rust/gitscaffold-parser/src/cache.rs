@@ -0,0 +1,127 @@
+//! On-disk cache of parsed roadmaps.
+//!
+//! Each roadmap file gets one entry, named after the BLAKE3 hash of its
+//! canonical path. The entry records a digest of everything that affects
+//! the parse — the file content with its includes and variables resolved,
+//! the input format, the heading conventions and the parser version — so
+//! an entry is reused only when none of them changed, and is overwritten
+//! otherwise. Roadmaps read from stdin are not cached.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::ParseError;
+use crate::format::InputFormat;
+use crate::load::{parse_prepared, prepare, read_source, STDIN};
+use crate::roadmap::Roadmap;
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    digest: String,
+    roadmap: Roadmap,
+}
+
+impl Cache {
+    /// Where the command-line tools keep their cache, relative to the
+    /// current directory.
+    pub const DEFAULT_DIR: &'static str = ".gitscaffold/cache";
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Cache { dir: dir.into() }
+    }
+
+    /// Like [`crate::load_roadmap_with`], reusing the cached roadmap when
+    /// the file is unchanged. Failing to read or write the cache is not an
+    /// error; the file is simply parsed.
+    pub fn load(
+        &self,
+        path: &str,
+        format: InputFormat,
+        config: &Config,
+    ) -> Result<Roadmap, ParseError> {
+        let content = read_source(path)?;
+        let prepared = prepare(path, &content, format, config)?;
+        let digest = digest(&prepared.text, format, config);
+        if path == STDIN {
+            return parse_prepared(path, &prepared, format, config);
+        }
+        let entry_path = self.entry_path(path);
+        if let Some(roadmap) = read_entry(&entry_path, &digest) {
+            tracing::debug!(path, "parse cache hit");
+            return Ok(roadmap);
+        }
+        tracing::debug!(path, "parse cache miss");
+        let roadmap = parse_prepared(path, &prepared, format, config)?;
+        let entry = Entry { digest, roadmap };
+        if let Ok(json) = serde_json::to_string(&entry) {
+            let _ = fs::create_dir_all(&self.dir).and_then(|()| fs::write(&entry_path, json));
+        }
+        Ok(entry.roadmap)
+    }
+
+    fn entry_path(&self, path: &str) -> PathBuf {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        let name = blake3::hash(canonical.to_string_lossy().as_bytes());
+        self.dir.join(format!("{}.json", name.to_hex()))
+    }
+}
+
+fn digest(content: &str, format: InputFormat, config: &Config) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(format!("{:?}\0{:?}\0", format, config.headings).as_bytes());
+    hasher.update(content.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn read_entry(path: &Path, digest: &str) -> Option<Roadmap> {
+    let entry: Entry = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    (entry.digest == digest).then_some(entry.roadmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_entries_until_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-cache-{}", std::process::id()));
+        let roadmap_path = dir.join("ROADMAP.md");
+        let path = roadmap_path.to_str().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        let cache = Cache::new(dir.join("cache"));
+        let config = Config::default();
+
+        fs::write(path, "# One\n").unwrap();
+        assert_eq!(
+            cache.load(path, InputFormat::Auto, &config).unwrap().name,
+            "One"
+        );
+        let entry = cache.entry_path(path);
+        assert!(entry.is_file());
+
+        // A hit returns the stored roadmap without parsing the file again.
+        let mut stored: Entry = serde_json::from_str(&fs::read_to_string(&entry).unwrap()).unwrap();
+        stored.roadmap.name = "From cache".into();
+        fs::write(&entry, serde_json::to_string(&stored).unwrap()).unwrap();
+        assert_eq!(
+            cache.load(path, InputFormat::Auto, &config).unwrap().name,
+            "From cache"
+        );
+
+        fs::write(path, "# Two\n").unwrap();
+        assert_eq!(
+            cache.load(path, InputFormat::Auto, &config).unwrap().name,
+            "Two"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
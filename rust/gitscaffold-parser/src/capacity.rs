@@ -0,0 +1,191 @@
+//! Estimated work per milestone, checked against configured capacities.
+//!
+//! A feature's work is its own estimate or, without one, the sum of its
+//! tasks' estimates; features with neither count as unestimated.
+//! Sub-features are features of their own and inherit their parent's
+//! milestone, so an epic's estimate should not repeat its children's.
+//! Durations and story points are summed separately; a milestone is over
+//! capacity when its total in the configured unit exceeds its capacity.
+
+use serde::Serialize;
+
+use crate::config::{CapacitySettings, CapacityUnit};
+use crate::roadmap::{Estimate, Feature, Roadmap};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Load {
+    pub days: f64,
+    pub points: f64,
+    pub features: usize,
+    /// Features without any valid estimate.
+    pub unestimated: usize,
+}
+
+impl Load {
+    fn add(&mut self, feature: &Feature) {
+        self.features += 1;
+        let estimates: Vec<&Estimate> = match feature.estimate.as_ref().filter(|e| e.is_valid()) {
+            Some(own) => vec![own],
+            None => feature
+                .tasks
+                .iter()
+                .filter_map(|t| t.estimate.as_ref())
+                .collect(),
+        };
+        let mut estimated = false;
+        for estimate in estimates {
+            match estimate {
+                Estimate::Days(days) => self.days += days,
+                Estimate::Points(points) => self.points += points,
+                Estimate::Other(_) => continue,
+            }
+            estimated = true;
+        }
+        if !estimated {
+            self.unestimated += 1;
+        }
+    }
+
+    /// The total in `unit`.
+    pub fn total(&self, unit: CapacityUnit) -> f64 {
+        match unit {
+            CapacityUnit::Days => self.days,
+            CapacityUnit::Points => self.points,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MilestoneLoad {
+    /// `None` for the features without a milestone.
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub load: Load,
+    /// `None` when no capacity is configured for the milestone.
+    pub capacity: Option<f64>,
+    pub over_capacity: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapacityReport {
+    pub unit: CapacityUnit,
+    /// One entry per milestone in roadmap order, then milestones features
+    /// reference but the roadmap does not define, then the features
+    /// without a milestone, if any.
+    pub milestones: Vec<MilestoneLoad>,
+    pub overall: Load,
+    /// Names of the milestones over capacity.
+    pub over_capacity: Vec<String>,
+}
+
+/// Sum the estimates of `roadmap` per milestone and compare them with the
+/// capacities in `settings`.
+pub fn capacity(roadmap: &Roadmap, settings: &CapacitySettings) -> CapacityReport {
+    let mut groups: Vec<(Option<String>, Load)> = roadmap
+        .milestones
+        .iter()
+        .map(|m| (Some(m.name.clone()), Load::default()))
+        .collect();
+    let mut unassigned = Load::default();
+    let mut overall = Load::default();
+
+    let mut pending: Vec<(&Feature, Option<&str>)> =
+        roadmap.features.iter().rev().map(|f| (f, None)).collect();
+    while let Some((feature, inherited)) = pending.pop() {
+        let milestone = feature.milestone.as_deref().or(inherited);
+        pending.extend(feature.children.iter().rev().map(|c| (c, milestone)));
+        overall.add(feature);
+        let Some(name) = milestone.map(str::trim).filter(|n| !n.is_empty()) else {
+            unassigned.add(feature);
+            continue;
+        };
+        let group = groups.iter().position(|(m, _)| {
+            m.as_deref()
+                .is_some_and(|m| m.trim().eq_ignore_ascii_case(name))
+        });
+        match group {
+            Some(i) => groups[i].1.add(feature),
+            None => {
+                let mut load = Load::default();
+                load.add(feature);
+                groups.push((Some(name.to_string()), load));
+            }
+        }
+    }
+
+    let mut milestones: Vec<MilestoneLoad> = groups
+        .into_iter()
+        .map(|(name, load)| {
+            let capacity = name.as_deref().and_then(|name| settings.of(name));
+            let over_capacity = capacity.is_some_and(|c| load.total(settings.unit) > c);
+            MilestoneLoad {
+                name,
+                load,
+                capacity,
+                over_capacity,
+            }
+        })
+        .collect();
+    if unassigned.features > 0 {
+        milestones.push(MilestoneLoad {
+            name: None,
+            load: unassigned,
+            capacity: None,
+            over_capacity: false,
+        });
+    }
+    let over_capacity = milestones
+        .iter()
+        .filter(|m| m.over_capacity)
+        .filter_map(|m| m.name.clone())
+        .collect();
+    CapacityReport {
+        unit: settings.unit,
+        milestones,
+        overall,
+        over_capacity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "# P\n\n## Milestones\n- Alpha\n- Beta\n\n## Features\n\n\
+        ### Login (3d)\nMilestone: Alpha\n\n- [ ] Form (1d)\n\n\
+        ### Search\nMilestone: Alpha\n\n- [ ] Index (1w)\n- [ ] Query (5pts)\n\n\
+        ### Export\nMilestone: Beta\n\n### Docs (2pts)\n";
+
+    #[test]
+    fn sums_feature_or_task_estimates_per_milestone() {
+        let mut settings = CapacitySettings {
+            default: Some(10.0),
+            ..CapacitySettings::default()
+        };
+        settings.milestones.insert("beta".into(), 0.0);
+        let report = capacity(&parse_roadmap(ROADMAP), &settings);
+        let alpha = &report.milestones[0];
+        // Login's own estimate wins over its task's.
+        assert_eq!((alpha.load.days, alpha.load.points), (8.0, 5.0));
+        assert_eq!(alpha.capacity, Some(10.0));
+        assert!(!alpha.over_capacity);
+        let beta = &report.milestones[1];
+        assert_eq!(beta.load.unestimated, 1);
+        assert!(!beta.over_capacity);
+        assert_eq!(report.milestones[2].name, None);
+        assert_eq!(report.overall.points, 7.0);
+        assert!(report.over_capacity.is_empty());
+    }
+
+    #[test]
+    fn flags_milestones_over_capacity_in_the_configured_unit() {
+        let settings = CapacitySettings {
+            unit: CapacityUnit::Points,
+            default: Some(4.0),
+            ..CapacitySettings::default()
+        };
+        let report = capacity(&parse_roadmap(ROADMAP), &settings);
+        assert_eq!(report.over_capacity, ["Alpha"]);
+    }
+}
@@ -0,0 +1,590 @@
+//! Project configuration read from `.gitscaffold.toml`.
+//!
+//! ```toml
+//! [headings]
+//! milestones = ["Milestones", "Phases"]
+//! feature = [{ prefix = "Epic:", level = 4 }]
+//! columns = { Feature = "title", Owner = "assignees", Target = "milestone" }
+//!
+//! [forge]
+//! kind = "gitlab"
+//! api_url = "https://gitlab.example.com/api/v4"
+//!
+//! [jira.fields]
+//! Component = "kind"
+//! Team = { value = "Platform" }
+//!
+//! [capacity]
+//! unit = "points"
+//! default = 20
+//! milestones = { "v1.0" = 30 }
+//!
+//! [sync]
+//! fuzzy_threshold = 0.9
+//!
+//! [titles]
+//! milestone_prefix = true
+//! id_prefix = "FEAT"
+//!
+//! [fields.Area]
+//! label = "area:{value}"
+//! project = "Area"
+//!
+//! [labels.define]
+//! auth = { color = "d73a4a", description = "Sign-in and accounts" }
+//!
+//! [vars]
+//! PRODUCT = "Acme"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ParseError;
+use crate::export::Column;
+use crate::extract::metadata_key;
+
+pub const FILE_NAME: &str = ".gitscaffold.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub headings: Headings,
+    pub forge: ForgeSettings,
+    pub jira: JiraSettings,
+    pub capacity: CapacitySettings,
+    pub sync: SyncSettings,
+    pub titles: TitleSettings,
+    pub labels: LabelSettings,
+    /// Custom metadata keys captured into [`Feature::fields`], by key, and
+    /// what sync does with their values.
+    ///
+    /// [`Feature::fields`]: crate::Feature::fields
+    pub fields: BTreeMap<String, FieldMapping>,
+    /// Values of the `${NAME}` placeholders in roadmaps, by name; `--var`
+    /// and `--vars-file` add to them.
+    pub vars: BTreeMap<String, String>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, ParseError> {
+        let mut config: Config = toml::from_str(&fs::read_to_string(path)?)?;
+        config.headings.fields = config.fields.keys().cloned().collect();
+        Ok(config)
+    }
+
+    /// Find [`FILE_NAME`] in `dir` or its closest ancestor that has one.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Load `path` if given, otherwise the config found from the current
+    /// directory, falling back to the defaults.
+    pub fn load_or_discover(path: Option<&Path>) -> Result<Config, ParseError> {
+        let found = match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => std::env::current_dir()
+                .ok()
+                .and_then(|dir| Config::find(&dir)),
+        };
+        match found {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+}
+
+/// Which headings the Markdown extractor treats as roadmap structure.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Headings {
+    /// Sections that list milestones.
+    pub milestones: Vec<HeadingPattern>,
+    /// Sections whose sub-headings are features.
+    pub features: Vec<HeadingPattern>,
+    /// Sections whose sub-headings are features put off for now, read
+    /// with the status `deferred`.
+    pub deferred: Vec<HeadingPattern>,
+    /// Headings that are features wherever they appear outside another
+    /// feature or milestones section. The matched prefix is dropped from
+    /// the title, also for sub-headings of a features section.
+    pub feature: Vec<HeadingPattern>,
+    /// Headings below a feature that are sub-features rather than tasks.
+    /// The matched prefix is dropped from the title.
+    pub sub_features: Vec<HeadingPattern>,
+    /// Table columns that map onto feature fields, by header. A table
+    /// directly in a features section whose header has a `title` column
+    /// lists one feature per row.
+    pub columns: BTreeMap<String, FeatureColumn>,
+    /// Metadata keys, and table columns, read into [`Feature::fields`]
+    /// rather than the description: the keys of `[fields]`.
+    ///
+    /// [`Feature::fields`]: crate::Feature::fields
+    #[serde(skip)]
+    pub fields: Vec<String>,
+}
+
+impl Headings {
+    /// The field of the table column headed `header`, ignoring case.
+    pub fn column(&self, header: &str) -> Option<FeatureColumn> {
+        let header = header.trim().to_lowercase();
+        self.columns
+            .iter()
+            .find(|(name, _)| name.trim().to_lowercase() == header)
+            .map(|(_, field)| *field)
+    }
+
+    /// The custom field whose key, normalized like metadata keys, is
+    /// `key`, as the config spells it. Built-in keys such as `Status`
+    /// always take precedence.
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|name| metadata_key(name) == key)
+            .map(String::as_str)
+    }
+}
+
+impl Default for Headings {
+    fn default() -> Self {
+        Headings {
+            milestones: vec![HeadingPattern::prefix("Milestones")],
+            features: vec![HeadingPattern::prefix("Features")],
+            deferred: vec![
+                HeadingPattern::prefix("Deferred"),
+                HeadingPattern::prefix("Icebox"),
+            ],
+            feature: Vec::new(),
+            sub_features: Vec::new(),
+            fields: Vec::new(),
+            columns: [
+                ("Feature", FeatureColumn::Title),
+                ("Title", FeatureColumn::Title),
+                ("Description", FeatureColumn::Description),
+                ("Owner", FeatureColumn::Assignees),
+                ("Assignee", FeatureColumn::Assignees),
+                ("Assignees", FeatureColumn::Assignees),
+                ("Target", FeatureColumn::Milestone),
+                ("Milestone", FeatureColumn::Milestone),
+                ("Labels", FeatureColumn::Labels),
+                ("Status", FeatureColumn::Status),
+                ("Estimate", FeatureColumn::Estimate),
+                ("Priority", FeatureColumn::Priority),
+                ("Kind", FeatureColumn::Kind),
+                ("Depends on", FeatureColumn::DependsOn),
+                ("Repo", FeatureColumn::Repo),
+            ]
+            .into_iter()
+            .map(|(name, field)| (name.to_string(), field))
+            .collect(),
+        }
+    }
+}
+
+/// The feature field a table column fills in, read like the matching
+/// metadata line of a heading-based feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureColumn {
+    /// The title; may carry a `[status]` marker and an estimate suffix.
+    Title,
+    Description,
+    Milestone,
+    /// A comma-separated list.
+    Labels,
+    /// A comma-separated list; a leading `@` is dropped.
+    Assignees,
+    Status,
+    Estimate,
+    Priority,
+    Kind,
+    /// A comma-separated list of feature titles.
+    DependsOn,
+    /// The repository, as `owner/name`.
+    Repo,
+}
+
+/// What sync does with the value of a custom field, such as `UI` for
+/// `Area: UI`. Without any of these, the value is only captured.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldMapping {
+    /// A label added to the feature's issue, with `{value}` replaced by
+    /// the value, e.g. `area:{value}`.
+    pub label: Option<String>,
+    /// The single-select field of the `--project` board set to the option
+    /// named like the value.
+    pub project: Option<String>,
+    /// The name issue templates see the value under, next to `feature`;
+    /// `feature.fields` always has it under the key.
+    pub var: Option<String>,
+}
+
+impl FieldMapping {
+    /// The label for `value`, if the field maps to one.
+    pub fn label(&self, value: &str) -> Option<String> {
+        let label = self.label.as_deref()?.replace("{value}", value.trim());
+        Some(label).filter(|label| !label.trim().is_empty())
+    }
+}
+
+/// Where sync, import and cleanup send their requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForgeSettings {
+    /// The forge to use when `--forge` is not given; GitHub by default.
+    pub kind: Option<ForgeKind>,
+    /// The forge's API base URL, for self-hosted instances.
+    pub api_url: Option<String>,
+}
+
+/// An issue tracker that roadmaps can be synced to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    #[default]
+    #[cfg_attr(feature = "clap", value(name = "github"))]
+    GitHub,
+    #[cfg_attr(feature = "clap", value(name = "gitlab"))]
+    GitLab,
+    /// Gitea or Forgejo, which share an API.
+    #[serde(alias = "forgejo")]
+    #[cfg_attr(feature = "clap", value(name = "gitea", alias = "forgejo"))]
+    Gitea,
+}
+
+/// How `scaffold export --format jira-csv` lays out issues.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JiraSettings {
+    /// Issue type of the issues made from features.
+    pub issue_type: String,
+    /// Issue type of the issues made from tasks.
+    pub subtask_type: String,
+    /// Extra columns on feature issues, by Jira field name.
+    pub fields: BTreeMap<String, JiraField>,
+}
+
+impl Default for JiraSettings {
+    fn default() -> Self {
+        JiraSettings {
+            issue_type: "Story".into(),
+            subtask_type: "Sub-task".into(),
+            fields: BTreeMap::new(),
+        }
+    }
+}
+
+/// The value of a custom Jira field: an export column such as `"kind"`,
+/// or a fixed `{ value = "..." }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum JiraField {
+    Column(Column),
+    Value { value: String },
+}
+
+/// How sync matches features to existing issues.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncSettings {
+    /// How similar, from 0 to 1, an issue's title must be to a feature's
+    /// for sync to treat the feature as renamed.
+    pub fuzzy_threshold: f64,
+    /// Append a footer linking each new issue to its feature's line in the
+    /// roadmap, at the commit checked out.
+    pub provenance: bool,
+    /// Label added to every issue sync creates, so `--prune` can tell them
+    /// apart from issues opened by hand.
+    pub marker_label: Option<String>,
+    /// Upload the local images of feature descriptions to the repository
+    /// and link to the uploaded files from the issues.
+    pub upload_images: bool,
+    /// Branch uploaded images are committed to, instead of the default
+    /// branch; it must exist.
+    pub assets_branch: Option<String>,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        SyncSettings {
+            fuzzy_threshold: 0.8,
+            provenance: false,
+            marker_label: None,
+            upload_images: false,
+            assets_branch: None,
+        }
+    }
+}
+
+/// How sync titles the issues of features. The same titles are used to
+/// match features to existing issues, so changing them renames issues.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TitleSettings {
+    /// Start titles with the feature's milestone, `[M2] Login`.
+    pub milestone_prefix: bool,
+    /// Number features in roadmap order and start titles with the number,
+    /// `FEAT-042 Login`, after the milestone prefix.
+    pub id_prefix: Option<String>,
+    /// How many digits feature numbers are padded to.
+    pub id_digits: usize,
+    /// A title template replacing the two options above. Besides what
+    /// `issue_title.j2` sees, it gets the feature's number as `id`,
+    /// prefixed like `FEAT-042` when `id_prefix` is set.
+    pub template: Option<String>,
+}
+
+impl Default for TitleSettings {
+    fn default() -> Self {
+        TitleSettings {
+            milestone_prefix: false,
+            id_prefix: None,
+            id_digits: 3,
+            template: None,
+        }
+    }
+}
+
+/// The repository's labels, as `scaffold labels sync` keeps them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelSettings {
+    /// Color of created labels that `define` gives none, as `rrggbb`.
+    pub default_color: String,
+    /// Colors and descriptions by label name. Defined labels are created
+    /// even when no feature uses them.
+    pub define: BTreeMap<String, LabelDefinition>,
+    /// Old label names and the names they became. A repository label
+    /// under an old name is renamed, and roadmap labels using one are read
+    /// as the new name.
+    pub aliases: BTreeMap<String, String>,
+    /// Bring the labels in line before every `scaffold sync`.
+    pub sync: bool,
+    /// Label each feature with a priority with this label, `{value}`
+    /// standing for the priority: `priority:{value}` gives `priority:P1`.
+    pub priority: Option<String>,
+}
+
+impl Default for LabelSettings {
+    fn default() -> Self {
+        LabelSettings {
+            default_color: "ededed".into(),
+            define: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            sync: false,
+            priority: None,
+        }
+    }
+}
+
+impl LabelSettings {
+    /// The name `label` goes by: the new name when it is an alias.
+    pub fn resolve<'a>(&'a self, label: &'a str) -> &'a str {
+        self.aliases
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(label))
+            .map_or(label, |(_, name)| name.as_str())
+    }
+}
+
+/// How a label defined in the config looks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelDefinition {
+    /// As `rrggbb`; a leading `#` is allowed.
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+/// How much work `scaffold capacity` lets each milestone hold.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CapacitySettings {
+    /// The unit capacities are given in.
+    pub unit: CapacityUnit,
+    /// Capacity of the milestones not listed in `milestones`.
+    pub default: Option<f64>,
+    /// Capacity by milestone name.
+    pub milestones: BTreeMap<String, f64>,
+}
+
+impl CapacitySettings {
+    /// The capacity of the milestone `name`, ignoring case.
+    pub fn of(&self, name: &str) -> Option<f64> {
+        self.milestones
+            .iter()
+            .find(|(milestone, _)| milestone.trim().eq_ignore_ascii_case(name.trim()))
+            .map(|(_, &capacity)| capacity)
+            .or(self.default)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapacityUnit {
+    #[default]
+    Days,
+    Points,
+}
+
+/// A case-insensitive title prefix, optionally restricted to one heading
+/// level. Written in TOML as a plain string or as
+/// `{ prefix = "...", level = N }`; `{ level = N }` matches every heading
+/// of that level.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "PatternSpec")]
+pub struct HeadingPattern {
+    pub prefix: String,
+    pub level: Option<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PatternSpec {
+    Prefix(String),
+    Full {
+        #[serde(default)]
+        prefix: String,
+        #[serde(default)]
+        level: Option<u8>,
+    },
+}
+
+impl From<PatternSpec> for HeadingPattern {
+    fn from(spec: PatternSpec) -> Self {
+        match spec {
+            PatternSpec::Prefix(prefix) => HeadingPattern::prefix(&prefix),
+            PatternSpec::Full { prefix, level } => HeadingPattern { prefix, level },
+        }
+    }
+}
+
+impl HeadingPattern {
+    pub fn prefix(prefix: &str) -> Self {
+        HeadingPattern {
+            prefix: prefix.to_string(),
+            level: None,
+        }
+    }
+
+    /// The rest of `title` after the prefix, if the heading matches.
+    pub fn strip<'t>(&self, level: u8, title: &'t str) -> Option<&'t str> {
+        if self.level.is_some_and(|l| l != level) {
+            return None;
+        }
+        let title = title.trim();
+        let head = title.get(..self.prefix.len())?;
+        (head.to_lowercase() == self.prefix.to_lowercase()).then(|| title[head.len()..].trim())
+    }
+}
+
+/// The rest of `title` after the first matching pattern's prefix.
+pub(crate) fn strip_any<'t>(
+    patterns: &[HeadingPattern],
+    level: u8,
+    title: &'t str,
+) -> Option<&'t str> {
+    patterns
+        .iter()
+        .find_map(|pattern| pattern.strip(level, title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shorthand_and_full_patterns() {
+        let config: Config = toml::from_str(
+            "[headings]\nmilestones = [\"Phases\"]\nfeature = [{ prefix = \"Epic:\", level = 4 }]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.headings.milestones,
+            [HeadingPattern::prefix("Phases")]
+        );
+        assert_eq!(config.headings.features, Headings::default().features);
+        let epic = &config.headings.feature[0];
+        assert_eq!(epic.strip(4, "EPIC: Login"), Some("Login"));
+        assert_eq!(epic.strip(3, "Epic: Login"), None);
+
+        let config: Config =
+            toml::from_str("[headings]\nsub_features = [{ level = 4 }]\n").unwrap();
+        let any = &config.headings.sub_features[0];
+        assert_eq!(any.strip(4, "Login"), Some("Login"));
+    }
+
+    #[test]
+    fn reads_forge_settings() {
+        let config: Config = toml::from_str(
+            "[forge]\nkind = \"gitlab\"\napi_url = \"https://git.example.com/api/v4\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.forge.kind, Some(ForgeKind::GitLab));
+        assert_eq!(
+            config.forge.api_url.as_deref(),
+            Some("https://git.example.com/api/v4")
+        );
+        let config: Config = toml::from_str("[forge]\nkind = \"forgejo\"\n").unwrap();
+        assert_eq!(config.forge.kind, Some(ForgeKind::Gitea));
+        assert!(toml::from_str::<Config>("[forge]\nkind = \"bitbucket\"\n").is_err());
+    }
+
+    #[test]
+    fn reads_jira_field_mapping() {
+        let config: Config = toml::from_str(
+            "[jira]\nissue_type = \"Task\"\n[jira.fields]\nComponent = \"kind\"\nTeam = { value = \"Core\" }\n",
+        )
+        .unwrap();
+        assert_eq!(config.jira.issue_type, "Task");
+        assert_eq!(config.jira.subtask_type, "Sub-task");
+        assert_eq!(
+            config.jira.fields["Component"],
+            JiraField::Column(Column::Kind)
+        );
+        assert_eq!(
+            config.jira.fields["Team"],
+            JiraField::Value {
+                value: "Core".into()
+            }
+        );
+        assert!(toml::from_str::<Config>("[jira.fields]\nTeam = \"squad\"\n").is_err());
+    }
+
+    #[test]
+    fn reads_capacities_with_a_default() {
+        let config: Config = toml::from_str(
+            "[capacity]\nunit = \"points\"\ndefault = 20\nmilestones = { \"v1.0\" = 30 }\n",
+        )
+        .unwrap();
+        assert_eq!(config.capacity.unit, CapacityUnit::Points);
+        assert_eq!(config.capacity.of("V1.0"), Some(30.0));
+        assert_eq!(config.capacity.of("v2.0"), Some(20.0));
+        assert_eq!(Config::default().capacity.of("v1.0"), None);
+    }
+
+    #[test]
+    fn reads_the_fuzzy_threshold() {
+        let config: Config = toml::from_str(
+            "[sync]\nfuzzy_threshold = 0.9\nprovenance = true\nmarker_label = \"gitscaffold\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.sync.fuzzy_threshold, 0.9);
+        assert!(config.sync.provenance);
+        assert_eq!(config.sync.marker_label.as_deref(), Some("gitscaffold"));
+        assert_eq!(Config::default().sync.fuzzy_threshold, 0.8);
+        assert!(!Config::default().sync.provenance);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("[headings]\nfeatures_x = []\n").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Due-date parsing and normalization.
+
+use chrono::NaiveDate;
+
+/// Formats accepted for due dates, tried in order. Numeric day/month
+/// formats follow the US convention (`06/30/2024`).
+const FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%Y.%m.%d",
+    "%B %d, %Y",
+    "%B %d %Y",
+    "%b %d, %Y",
+    "%b %d %Y",
+    "%d %B %Y",
+    "%d %b %Y",
+    "%m/%d/%Y",
+];
+
+/// Parse a due date written in any of the supported formats.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .trim();
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// Normalize a due date to ISO 8601 (`YYYY-MM-DD`). Values that cannot be
+/// parsed are returned unchanged so validation can report them.
+pub fn normalize_date(value: &str) -> String {
+    match parse_date(value) {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => value.trim().to_string(),
+    }
+}
+
+/// Whether `value` is a valid calendar date in `YYYY-MM-DD` form.
+pub fn is_iso_date(value: &str) -> bool {
+    value.len() == 10 && NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_common_formats() {
+        for input in [
+            "2024-06-30",
+            "2024/06/30",
+            "June 30, 2024",
+            "Jun 30 2024",
+            "30 June 2024",
+            "06/30/2024",
+            "(2024-06-30)",
+        ] {
+            assert_eq!(normalize_date(input), "2024-06-30", "{}", input);
+        }
+    }
+
+    #[test]
+    fn leaves_unparseable_dates_alone() {
+        assert_eq!(normalize_date(" next quarter "), "next quarter");
+        assert_eq!(normalize_date("2023-02-29"), "2023-02-29");
+    }
+
+    #[test]
+    fn iso_dates() {
+        assert!(is_iso_date("2024-02-29"));
+        assert!(!is_iso_date("2023-02-29"));
+        assert!(!is_iso_date("2024-6-30"));
+        assert!(!is_iso_date("June 30, 2024"));
+    }
+}
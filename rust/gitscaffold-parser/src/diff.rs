@@ -0,0 +1,632 @@
+//! Structural comparison of two roadmaps.
+//!
+//! [`diff_roadmaps`] produces a flat list of [`Change`]s that is both easy to
+//! print and stable enough to serialize as a JSON changeset. Milestones and
+//! features that disappear from one side and appear on the other with a
+//! similar title (or, for features, an identical description) are reported
+//! as renames rather than as a removal plus an addition.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::roadmap::{Feature, Milestone, Roadmap};
+
+/// Minimum title similarity for a removed/added pair to count as a rename.
+const RENAME_THRESHOLD: f64 = 0.75;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    MilestoneAdded {
+        name: String,
+    },
+    MilestoneRemoved {
+        name: String,
+    },
+    MilestoneRenamed {
+        from: String,
+        to: String,
+    },
+    MilestoneDueDateChanged {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    FeatureAdded {
+        title: String,
+    },
+    FeatureRemoved {
+        title: String,
+    },
+    FeatureRenamed {
+        from: String,
+        to: String,
+    },
+    FeatureDescriptionChanged {
+        title: String,
+        from: String,
+        to: String,
+    },
+    FeatureMilestoneChanged {
+        title: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    FeatureLabelsChanged {
+        title: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    FeatureAssigneesChanged {
+        title: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    FeatureStatusChanged {
+        title: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    FeaturePriorityChanged {
+        title: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    FeatureEstimateChanged {
+        title: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    FeatureDependenciesChanged {
+        title: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    FeatureFieldChanged {
+        title: String,
+        field: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    TaskAdded {
+        feature: String,
+        title: String,
+    },
+    TaskRemoved {
+        feature: String,
+        title: String,
+    },
+    TaskCompleted {
+        feature: String,
+        title: String,
+    },
+    TaskReopened {
+        feature: String,
+        title: String,
+    },
+}
+
+impl Change {
+    /// `+` for additions, `-` for removals and `~` for modifications.
+    pub fn marker(&self) -> char {
+        match self {
+            Change::MilestoneAdded { .. }
+            | Change::FeatureAdded { .. }
+            | Change::TaskAdded { .. } => '+',
+            Change::MilestoneRemoved { .. }
+            | Change::FeatureRemoved { .. }
+            | Change::TaskRemoved { .. } => '-',
+            _ => '~',
+        }
+    }
+}
+
+fn or_none(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("(none)")
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::MilestoneAdded { name } => write!(f, "milestone '{}' added", name),
+            Change::MilestoneRemoved { name } => write!(f, "milestone '{}' removed", name),
+            Change::MilestoneRenamed { from, to } => {
+                write!(f, "milestone '{}' renamed to '{}'", from, to)
+            }
+            Change::MilestoneDueDateChanged { name, from, to } => write!(
+                f,
+                "milestone '{}' due date {} -> {}",
+                name,
+                or_none(from),
+                or_none(to)
+            ),
+            Change::FeatureAdded { title } => write!(f, "feature '{}' added", title),
+            Change::FeatureRemoved { title } => write!(f, "feature '{}' removed", title),
+            Change::FeatureRenamed { from, to } => {
+                write!(f, "feature '{}' renamed to '{}'", from, to)
+            }
+            Change::FeatureDescriptionChanged { title, .. } => {
+                write!(f, "feature '{}' description changed", title)
+            }
+            Change::FeatureMilestoneChanged { title, from, to } => write!(
+                f,
+                "feature '{}' milestone {} -> {}",
+                title,
+                or_none(from),
+                or_none(to)
+            ),
+            Change::FeatureLabelsChanged {
+                title,
+                added,
+                removed,
+            } => write!(f, "feature '{}' labels{}", title, signed(added, removed)),
+            Change::FeatureAssigneesChanged {
+                title,
+                added,
+                removed,
+            } => write!(f, "feature '{}' assignees{}", title, signed(added, removed)),
+            Change::FeatureStatusChanged { title, from, to } => write!(
+                f,
+                "feature '{}' status {} -> {}",
+                title,
+                or_none(from),
+                or_none(to)
+            ),
+            Change::FeaturePriorityChanged { title, from, to } => write!(
+                f,
+                "feature '{}' priority {} -> {}",
+                title,
+                or_none(from),
+                or_none(to)
+            ),
+            Change::FeatureEstimateChanged { title, from, to } => write!(
+                f,
+                "feature '{}' estimate {} -> {}",
+                title,
+                or_none(from),
+                or_none(to)
+            ),
+            Change::FeatureDependenciesChanged {
+                title,
+                added,
+                removed,
+            } => write!(
+                f,
+                "feature '{}' dependencies{}",
+                title,
+                signed(added, removed)
+            ),
+            Change::FeatureFieldChanged {
+                title,
+                field,
+                from,
+                to,
+            } => write!(
+                f,
+                "feature '{}' {} {} -> {}",
+                title,
+                field,
+                or_none(from),
+                or_none(to)
+            ),
+            Change::TaskAdded { feature, title } => {
+                write!(f, "task '{}' added to '{}'", title, feature)
+            }
+            Change::TaskRemoved { feature, title } => {
+                write!(f, "task '{}' removed from '{}'", title, feature)
+            }
+            Change::TaskCompleted { feature, title } => {
+                write!(f, "task '{}' in '{}' completed", title, feature)
+            }
+            Change::TaskReopened { feature, title } => {
+                write!(f, "task '{}' in '{}' reopened", title, feature)
+            }
+        }
+    }
+}
+
+/// ` +a +b -c` for a set difference.
+fn signed(added: &[String], removed: &[String]) -> String {
+    let added = added.iter().map(|v| format!(" +{}", v));
+    let removed = removed.iter().map(|v| format!(" -{}", v));
+    added.chain(removed).collect()
+}
+
+/// Compare `old` against `new`.
+pub fn diff_roadmaps(old: &Roadmap, new: &Roadmap) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_milestones(&old.milestones, &new.milestones, &mut changes);
+    diff_features(&old.features, &new.features, &mut changes);
+    changes
+}
+
+/// Pair up entities by key, then pair the leftovers as renames. Returns the
+/// matched `(old, new)` index pairs plus the unmatched indices on each side.
+fn pair<T>(
+    old: &[T],
+    new: &[T],
+    key: impl Fn(&T) -> String,
+    rename_score: impl Fn(&T, &T) -> f64,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut pairs = Vec::new();
+    let mut unmatched_new: Vec<usize> = Vec::new();
+    let mut used_old = HashSet::new();
+    for (j, n) in new.iter().enumerate() {
+        let found = old
+            .iter()
+            .enumerate()
+            .find(|(i, o)| !used_old.contains(i) && key(o) == key(n));
+        match found {
+            Some((i, _)) => {
+                used_old.insert(i);
+                pairs.push((i, j));
+            }
+            None => unmatched_new.push(j),
+        }
+    }
+    let mut removed: Vec<usize> = (0..old.len()).filter(|i| !used_old.contains(i)).collect();
+    let mut added = Vec::new();
+    let mut renames = Vec::new();
+    for j in unmatched_new {
+        let best = removed
+            .iter()
+            .map(|&i| (i, rename_score(&old[i], &new[j])))
+            .filter(|&(_, score)| score >= RENAME_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((i, _)) => {
+                removed.retain(|&r| r != i);
+                renames.push((i, j));
+            }
+            None => added.push(j),
+        }
+    }
+    pairs.extend(renames);
+    (pairs, removed, added)
+}
+
+fn diff_milestones(old: &[Milestone], new: &[Milestone], changes: &mut Vec<Change>) {
+    let (pairs, removed, added) = pair(
+        old,
+        new,
+        |m| m.name.trim().to_lowercase(),
+        |a, b| similarity(&a.name, &b.name),
+    );
+    for (i, j) in pairs {
+        let (o, n) = (&old[i], &new[j]);
+        if o.name.trim().to_lowercase() != n.name.trim().to_lowercase() {
+            changes.push(Change::MilestoneRenamed {
+                from: o.name.clone(),
+                to: n.name.clone(),
+            });
+        }
+        if o.due_date != n.due_date {
+            changes.push(Change::MilestoneDueDateChanged {
+                name: n.name.clone(),
+                from: o.due_date.clone(),
+                to: n.due_date.clone(),
+            });
+        }
+    }
+    changes.extend(removed.into_iter().map(|i| Change::MilestoneRemoved {
+        name: old[i].name.clone(),
+    }));
+    changes.extend(added.into_iter().map(|j| Change::MilestoneAdded {
+        name: new[j].name.clone(),
+    }));
+}
+
+fn diff_features(old: &[Feature], new: &[Feature], changes: &mut Vec<Change>) {
+    let (pairs, removed, added) = pair(
+        old,
+        new,
+        |f| f.title.trim().to_lowercase(),
+        |a, b| {
+            let same_body =
+                !a.description.trim().is_empty() && a.description.trim() == b.description.trim();
+            if same_body {
+                1.0
+            } else {
+                similarity(&a.title, &b.title)
+            }
+        },
+    );
+    for (i, j) in pairs {
+        diff_feature(&old[i], &new[j], changes);
+    }
+    changes.extend(removed.into_iter().map(|i| Change::FeatureRemoved {
+        title: old[i].title.clone(),
+    }));
+    changes.extend(added.into_iter().map(|j| Change::FeatureAdded {
+        title: new[j].title.clone(),
+    }));
+}
+
+fn diff_feature(old: &Feature, new: &Feature, changes: &mut Vec<Change>) {
+    let title = new.title.clone();
+    if old.title.trim().to_lowercase() != new.title.trim().to_lowercase() {
+        changes.push(Change::FeatureRenamed {
+            from: old.title.clone(),
+            to: title.clone(),
+        });
+    }
+    if old.description.trim() != new.description.trim() {
+        changes.push(Change::FeatureDescriptionChanged {
+            title: title.clone(),
+            from: old.description.clone(),
+            to: new.description.clone(),
+        });
+    }
+    if old.milestone != new.milestone {
+        changes.push(Change::FeatureMilestoneChanged {
+            title: title.clone(),
+            from: old.milestone.clone(),
+            to: new.milestone.clone(),
+        });
+    }
+    let (added, removed) = set_diff(&old.labels, &new.labels);
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(Change::FeatureLabelsChanged {
+            title: title.clone(),
+            added,
+            removed,
+        });
+    }
+    let (added, removed) = set_diff(&old.assignees, &new.assignees);
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(Change::FeatureAssigneesChanged {
+            title: title.clone(),
+            added,
+            removed,
+        });
+    }
+
+    let status = |feature: &Feature| feature.status.as_ref().map(|s| s.name().to_string());
+    if status(old) != status(new) {
+        changes.push(Change::FeatureStatusChanged {
+            title: title.clone(),
+            from: status(old),
+            to: status(new),
+        });
+    }
+    let priority = |feature: &Feature| feature.priority.as_ref().map(|p| p.to_string());
+    if priority(old) != priority(new) {
+        changes.push(Change::FeaturePriorityChanged {
+            title: title.clone(),
+            from: priority(old),
+            to: priority(new),
+        });
+    }
+    let estimate = |feature: &Feature| feature.estimate.as_ref().map(|e| e.to_string());
+    if estimate(old) != estimate(new) {
+        changes.push(Change::FeatureEstimateChanged {
+            title: title.clone(),
+            from: estimate(old),
+            to: estimate(new),
+        });
+    }
+    let (added, removed) = set_diff(&old.depends_on, &new.depends_on);
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(Change::FeatureDependenciesChanged {
+            title: title.clone(),
+            added,
+            removed,
+        });
+    }
+    let fields: BTreeSet<&String> = old.fields.keys().chain(new.fields.keys()).collect();
+    for field in fields {
+        let (from, to) = (old.fields.get(field), new.fields.get(field));
+        if from != to {
+            changes.push(Change::FeatureFieldChanged {
+                title: title.clone(),
+                field: field.clone(),
+                from: from.cloned(),
+                to: to.cloned(),
+            });
+        }
+    }
+
+    let (pairs, removed, added) = pair(
+        &old.tasks,
+        &new.tasks,
+        |t| t.title.trim().to_lowercase(),
+        |_, _| 0.0,
+    );
+    for (i, j) in pairs {
+        let (o, n) = (&old.tasks[i], &new.tasks[j]);
+        if o.completed != n.completed {
+            let feature = title.clone();
+            let title = n.title.clone();
+            changes.push(if n.completed {
+                Change::TaskCompleted { feature, title }
+            } else {
+                Change::TaskReopened { feature, title }
+            });
+        }
+    }
+    changes.extend(removed.into_iter().map(|i| Change::TaskRemoved {
+        feature: title.clone(),
+        title: old.tasks[i].title.clone(),
+    }));
+    changes.extend(added.into_iter().map(|j| Change::TaskAdded {
+        feature: title.clone(),
+        title: new.tasks[j].title.clone(),
+    }));
+    diff_features(&old.children, &new.children, changes);
+}
+
+/// Values present only in `new` and only in `old`, in their original order.
+fn set_diff(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = new.iter().filter(|v| !old.contains(v)).cloned().collect();
+    let removed = old.iter().filter(|v| !new.contains(v)).cloned().collect();
+    (added, removed)
+}
+
+/// Case-insensitive normalized Levenshtein similarity in `0.0..=1.0`.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.trim().to_lowercase().chars().collect();
+    let b: Vec<char> = b.trim().to_lowercase().chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+    1.0 - row[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const OLD: &str = "\
+# P
+
+## Milestones
+- Alpha — 2025-01-01
+- Beta
+
+## Features
+
+### User login
+Sign in with email.
+Labels: auth
+
+- [ ] Form
+- [ ] Session
+
+### Reporting
+Weekly reports.
+
+### Legacy export
+Old CSV export.
+";
+
+    const NEW: &str = "\
+# P
+
+## Milestones
+- Alpha — 2025-02-01
+- Beta release
+
+## Features
+
+### User logins
+Sign in with email.
+Labels: auth, p1
+
+- [x] Form
+- [ ] Remember me
+
+### Summary reports
+Weekly reports.
+
+### Search
+Full-text search.
+";
+
+    #[test]
+    fn reports_structural_changes() {
+        let changes = diff_roadmaps(&parse_roadmap(OLD), &parse_roadmap(NEW));
+        assert_eq!(
+            changes,
+            vec![
+                Change::MilestoneDueDateChanged {
+                    name: "Alpha".into(),
+                    from: Some("2025-01-01".into()),
+                    to: Some("2025-02-01".into()),
+                },
+                Change::MilestoneRemoved {
+                    name: "Beta".into()
+                },
+                Change::MilestoneAdded {
+                    name: "Beta release".into()
+                },
+                Change::FeatureRenamed {
+                    from: "User login".into(),
+                    to: "User logins".into(),
+                },
+                Change::FeatureLabelsChanged {
+                    title: "User logins".into(),
+                    added: vec!["p1".into()],
+                    removed: vec![],
+                },
+                Change::TaskCompleted {
+                    feature: "User logins".into(),
+                    title: "Form".into(),
+                },
+                Change::TaskRemoved {
+                    feature: "User logins".into(),
+                    title: "Session".into(),
+                },
+                Change::TaskAdded {
+                    feature: "User logins".into(),
+                    title: "Remember me".into(),
+                },
+                Change::FeatureRenamed {
+                    from: "Reporting".into(),
+                    to: "Summary reports".into(),
+                },
+                Change::FeatureRemoved {
+                    title: "Legacy export".into()
+                },
+                Change::FeatureAdded {
+                    title: "Search".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_status_priority_estimate_and_dependency_changes() {
+        let old = parse_roadmap(
+            "# P\n\n## Features\n\n### Search [done] (3d) !!\n\n### Login\nDepends on: Search\n",
+        );
+        let new = parse_roadmap(
+            "# P\n\n## Features\n\n### Search [in-progress] (5d)\n\n### Export\n\n\
+             ### Login [blocked]\nDepends on: Export\n",
+        );
+        let changes: Vec<String> = diff_roadmaps(&old, &new)
+            .iter()
+            .map(Change::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "feature 'Search' status done -> in-progress",
+                "feature 'Search' priority P1 -> (none)",
+                "feature 'Search' estimate 3d -> 5d",
+                "feature 'Login' status (none) -> blocked",
+                "feature 'Login' dependencies +Export -Search",
+                "feature 'Export' added",
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_roadmaps_have_no_changes() {
+        assert!(diff_roadmaps(&parse_roadmap(OLD), &parse_roadmap(OLD)).is_empty());
+    }
+
+    #[test]
+    fn similarity_is_normalized() {
+        assert_eq!(similarity("Login", "login"), 1.0);
+        assert!(similarity("Beta", "Beta release") < RENAME_THRESHOLD);
+        assert!(similarity("User login", "User logins") > 0.9);
+    }
+}
@@ -0,0 +1,126 @@
+//! Milestones at risk of missing their due date: overdue ones and those
+//! due soon, with the features still unfinished in each.
+//!
+//! Milestones and features are matched as in [`crate::stats`]: by name,
+//! ignoring case, with a sub-feature without a milestone in its parent's.
+//! A milestone whose features are all done, or that has none, is never at
+//! risk.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::dates::parse_date;
+use crate::roadmap::{Feature, Roadmap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DueState {
+    /// Due within the warning window, today included.
+    DueSoon,
+    /// The due date has passed.
+    Overdue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DueAlert {
+    pub milestone: String,
+    /// As `YYYY-MM-DD`.
+    pub due_date: String,
+    pub state: DueState,
+    /// Days until the due date; negative once it has passed.
+    pub days_left: i64,
+    pub features: usize,
+    /// Titles of the features not done yet.
+    pub unfinished: Vec<String>,
+}
+
+/// The milestones of `roadmap` with unfinished features that are overdue
+/// as of `today`, or due within `within` days of it, soonest first.
+pub fn check_due(roadmap: &Roadmap, today: NaiveDate, within: u32) -> Vec<DueAlert> {
+    let mut alerts = Vec::new();
+    for milestone in &roadmap.milestones {
+        let Some(due) = milestone.due_date.as_deref().and_then(parse_date) else {
+            continue;
+        };
+        let days_left = (due - today).num_days();
+        let state = match days_left {
+            ..0 => DueState::Overdue,
+            days if days <= i64::from(within) => DueState::DueSoon,
+            _ => continue,
+        };
+        let mut features = 0;
+        let mut unfinished = Vec::new();
+        walk(&roadmap.features, None, &mut |feature, name| {
+            if name.is_some_and(|name| name.trim().eq_ignore_ascii_case(milestone.name.trim())) {
+                features += 1;
+                if !feature.is_done() {
+                    unfinished.push(feature.title.clone());
+                }
+            }
+        });
+        if unfinished.is_empty() {
+            continue;
+        }
+        alerts.push(DueAlert {
+            milestone: milestone.name.clone(),
+            due_date: due.format("%Y-%m-%d").to_string(),
+            state,
+            days_left,
+            features,
+            unfinished,
+        });
+    }
+    alerts.sort_by_key(|alert| alert.days_left);
+    alerts
+}
+
+fn walk<'a>(
+    features: &'a [Feature],
+    inherited: Option<&'a str>,
+    visit: &mut impl FnMut(&'a Feature, Option<&'a str>),
+) {
+    for feature in features {
+        let milestone = feature.milestone.as_deref().or(inherited);
+        visit(feature, milestone);
+        walk(&feature.children, milestone, visit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    #[test]
+    fn flags_overdue_and_soon_due_milestones_with_unfinished_work() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- Alpha — 2025-02-25\n- Beta — 2025-03-05\n\
+             - Gamma — 2025-04-30\n- Delta — 2025-02-01\n\n\
+             ## Features\n\n### Login [done]\nMilestone: Alpha\n\n### Search\nMilestone: alpha\n\n\
+             ### Export\nMilestone: Beta\n\n### Import\nMilestone: Gamma\n\n\
+             ### Docs [done]\nMilestone: Delta\n",
+        );
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let alerts = check_due(&roadmap, today, 7);
+        let summary: Vec<(&str, DueState, i64, &[String])> = alerts
+            .iter()
+            .map(|a| {
+                (
+                    a.milestone.as_str(),
+                    a.state,
+                    a.days_left,
+                    a.unfinished.as_slice(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Alpha", DueState::Overdue, -4, &["Search".to_string()][..]),
+                ("Beta", DueState::DueSoon, 4, &["Export".to_string()][..]),
+            ]
+        );
+        assert_eq!(alerts[0].features, 2);
+        assert_eq!(check_due(&roadmap, today, 0).len(), 1);
+    }
+}
@@ -0,0 +1,241 @@
+//! In-place updates to a Markdown roadmap that leave the rest of the
+//! source as written.
+//!
+//! Edits are located through the spans the extractor records, so they only
+//! apply to a roadmap parsed from the same source. A status is changed
+//! where it is written: on the feature's `Status:` line if it has one,
+//! otherwise as a `[status]` marker on its heading.
+
+use std::ops::Range;
+
+use crate::extract::metadata;
+use crate::roadmap::{Feature, FeatureStatus, IssueLink, Task};
+
+/// Replace `range` of the source with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// What to change about one feature.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureUpdate {
+    pub status: Option<FeatureStatus>,
+    pub issue: Option<IssueLink>,
+    /// Indices into the feature's `tasks` of the tasks to check off.
+    pub complete: Vec<usize>,
+}
+
+/// The edits that apply `update` to `feature`. Nothing is edited for a
+/// feature without a span.
+pub fn update_feature(source: &str, feature: &Feature, update: &FeatureUpdate) -> Vec<Edit> {
+    let Some(span) = feature.span else {
+        return Vec::new();
+    };
+    let mut edits = Vec::new();
+    let heading = line_at(source, span.start.offset);
+    // The status that goes into the heading's marker, if any.
+    let marker = match &update.status {
+        Some(new) => match status_line(source, heading.end, span.end.offset) {
+            Some(line) => {
+                edits.push(Edit {
+                    range: line,
+                    replacement: new.name().to_string(),
+                });
+                None
+            }
+            None => Some(new),
+        },
+        None => None,
+    };
+    if marker.is_some() || update.issue.is_some() {
+        edits.push(rewrite_heading(source, heading, feature, marker, update));
+    }
+    for &index in &update.complete {
+        if let Some(edit) = feature.tasks.get(index).and_then(|t| check_off(source, t)) {
+            edits.push(edit);
+        }
+    }
+    edits
+}
+
+/// Apply `edits` to `source`; an edit overlapping an earlier one is
+/// skipped.
+pub fn apply(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    for edit in edits {
+        if edit.range.start < copied {
+            continue;
+        }
+        out.push_str(&source[copied..edit.range.start]);
+        out.push_str(&edit.replacement);
+        copied = edit.range.end;
+    }
+    out.push_str(&source[copied..]);
+    out
+}
+
+/// The line holding `offset`, without its line break.
+fn line_at(source: &str, offset: usize) -> Range<usize> {
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let end = if source[..end].ends_with('\r') {
+        end - 1
+    } else {
+        end
+    };
+    start..end
+}
+
+/// The value of the first `Status:` line between the heading and the next
+/// heading, outside code fences.
+fn status_line(source: &str, from: usize, to: usize) -> Option<Range<usize>> {
+    let mut offset = from;
+    let mut in_fence = false;
+    for line in source[from..to].split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end();
+        let trimmed = text.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            return None;
+        }
+        match metadata(text) {
+            Some((key, value)) if key == "status" && !value.is_empty() => {
+                let at = start + text.rfind(value)?;
+                return Some(at..at + value.len());
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The heading with its status marker and issue link replaced.
+fn rewrite_heading(
+    source: &str,
+    heading: Range<usize>,
+    feature: &Feature,
+    status: Option<&FeatureStatus>,
+    update: &FeatureUpdate,
+) -> Edit {
+    let mut text = source[heading.clone()].trim_end();
+    let mut link = None;
+    if feature.issue.is_some() {
+        if let Some(at) = text.rfind("[#") {
+            link = Some(text[at..].to_string());
+            text = text[..at].trim_end();
+        }
+    }
+    let mut marker = None;
+    if let Some((rest, value)) = text.strip_suffix(']').and_then(|t| t.rsplit_once('[')) {
+        if FeatureStatus::parse(value).is_known() {
+            marker = Some(format!("[{}]", value));
+            text = rest.trim_end();
+        }
+    }
+    let mut heading_text = text.to_string();
+    if let Some(marker) = status.map(|s| format!("[{}]", s.name())).or(marker) {
+        heading_text.push(' ');
+        heading_text.push_str(&marker);
+    }
+    if let Some(link) = update.issue.as_ref().map(|l| l.to_string()).or(link) {
+        heading_text.push(' ');
+        heading_text.push_str(&link);
+    }
+    Edit {
+        range: heading,
+        replacement: heading_text,
+    }
+}
+
+/// Tick the checkbox of a checklist item or task heading, adding one to a
+/// heading without it.
+fn check_off(source: &str, task: &Task) -> Option<Edit> {
+    let line = line_at(source, task.span?.start.offset);
+    let text = &source[line.clone()];
+    let at = match text.find("[ ]") {
+        Some(at) => at,
+        None if text.trim_start().starts_with('#') => {
+            let hashes = text.find(|c: char| c != '#' && !c.is_whitespace())?;
+            return Some(Edit {
+                range: line.start + hashes..line.start + hashes,
+                replacement: "[x] ".into(),
+            });
+        }
+        None => return None,
+    };
+    Some(Edit {
+        range: line.start + at..line.start + at + 3,
+        replacement: "[x]".into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const SOURCE: &str =
+        "# P\n\n## Features\n\n### Login [in-progress]\n\n- [ ] Form\n- [x] Tokens\n\n\
+        ### Search\nStatus: planned\n\n#### Index\n\n#### [ ] Query\n";
+
+    #[test]
+    fn rewrites_markers_status_lines_and_checkboxes() {
+        let roadmap = parse_roadmap(SOURCE);
+        let link = IssueLink {
+            number: 7,
+            url: "https://github.com/o/r/issues/7".into(),
+        };
+        let mut edits = update_feature(
+            SOURCE,
+            &roadmap.features[0],
+            &FeatureUpdate {
+                status: Some(FeatureStatus::Done),
+                issue: Some(link),
+                complete: vec![0],
+            },
+        );
+        edits.extend(update_feature(
+            SOURCE,
+            &roadmap.features[1],
+            &FeatureUpdate {
+                status: Some(FeatureStatus::Blocked),
+                issue: None,
+                complete: vec![0, 1],
+            },
+        ));
+        assert_eq!(
+            apply(SOURCE, edits),
+            "# P\n\n## Features\n\n### Login [done] [#7](https://github.com/o/r/issues/7)\n\n\
+             - [x] Form\n- [x] Tokens\n\n### Search\nStatus: blocked\n\n#### [x] Index\n\n#### [x] Query\n"
+        );
+    }
+
+    #[test]
+    fn replaces_an_existing_issue_link() {
+        let source = "# P\n\n## Features\n\n### Login [#1](https://x/1)\n";
+        let roadmap = parse_roadmap(source);
+        let update = FeatureUpdate {
+            status: Some(FeatureStatus::InProgress),
+            ..FeatureUpdate::default()
+        };
+        let edits = update_feature(source, &roadmap.features[0], &update);
+        assert_eq!(
+            apply(source, edits),
+            "# P\n\n## Features\n\n### Login [in-progress] [#1](https://x/1)\n"
+        );
+    }
+}
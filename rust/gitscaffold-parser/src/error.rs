@@ -0,0 +1,87 @@
+use std::fmt;
+use std::io;
+
+use crate::roadmap::ParseWarning;
+
+/// Errors produced while reading or parsing a roadmap.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    Io(io::Error),
+    Yaml(serde_yaml::Error),
+    Config(toml::de::Error),
+    /// Strict parsing found anomalies the lenient parser would have
+    /// recorded as warnings.
+    Strict(Vec<ParseWarning>),
+    /// A `${NAME}` placeholder, at byte `offset` of the file, that has
+    /// neither a value nor a default.
+    UndefinedVariable {
+        name: String,
+        offset: usize,
+    },
+    /// An include directive in `file` that cannot be followed.
+    Include {
+        file: String,
+        message: String,
+    },
+    /// A roadmap declaring a format version newer than the parser knows.
+    FormatVersion {
+        version: u32,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::Yaml(e) => write!(f, "invalid YAML: {}", e),
+            ParseError::Config(e) => write!(f, "invalid config: {}", e),
+            ParseError::Strict(warnings) => {
+                let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+                write!(f, "strict parsing failed: {}", warnings.join("; "))
+            }
+            ParseError::UndefinedVariable { name, .. } => {
+                write!(f, "undefined variable '{}'", name)
+            }
+            ParseError::Include { file, message } => write!(f, "{}: {}", file, message),
+            ParseError::FormatVersion { version } => write!(
+                f,
+                "roadmap format version {} is newer than version {}, the latest this parser reads",
+                version,
+                crate::migrate::FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::Yaml(e) => Some(e),
+            ParseError::Config(e) => Some(e),
+            ParseError::Strict(_)
+            | ParseError::UndefinedVariable { .. }
+            | ParseError::Include { .. }
+            | ParseError::FormatVersion { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ParseError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ParseError::Yaml(e)
+    }
+}
+
+impl From<toml::de::Error> for ParseError {
+    fn from(e: toml::de::Error) -> Self {
+        ParseError::Config(e)
+    }
+}
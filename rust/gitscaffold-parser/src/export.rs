@@ -0,0 +1,209 @@
+//! Flatten a [`Roadmap`] into delimited rows for spreadsheets and BI tools.
+//!
+//! Every task becomes a row; a feature without tasks gets a row of its own
+//! so it is not lost. Sub-features are listed after their parent, their
+//! title prefixed with the parent's (`Accounts / Login`).
+
+use serde::Deserialize;
+
+use crate::roadmap::{Feature, Roadmap, Task};
+
+/// A column of the exported table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    /// The feature's milestone
+    Milestone,
+    /// The feature title, prefixed with its parents' titles
+    Feature,
+    /// The task title; empty on the row of a feature without tasks
+    Task,
+    /// `done` or `open`
+    Status,
+    /// The feature's labels followed by the task's
+    Labels,
+    /// The due date of the feature's milestone
+    #[cfg_attr(feature = "clap", value(name = "due_date"))]
+    DueDate,
+    /// The feature kind
+    Kind,
+    /// The task's assignees, or the feature's when the task has none
+    Assignees,
+    /// The file the feature was read from, for aggregated roadmaps
+    File,
+}
+
+impl Column {
+    /// The columns exported when none are selected.
+    pub const DEFAULT: [Column; 6] = [
+        Column::Milestone,
+        Column::Feature,
+        Column::Task,
+        Column::Status,
+        Column::Labels,
+        Column::DueDate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Milestone => "milestone",
+            Column::Feature => "feature",
+            Column::Task => "task",
+            Column::Status => "status",
+            Column::Labels => "labels",
+            Column::DueDate => "due_date",
+            Column::Kind => "kind",
+            Column::Assignees => "assignees",
+            Column::File => "file",
+        }
+    }
+}
+
+/// Render the roadmap as a table with a header row, separating fields with
+/// `delimiter` (`,` for CSV, `\t` for TSV). Lines end in `\n`.
+pub fn to_delimited(roadmap: &Roadmap, columns: &[Column], delimiter: char) -> String {
+    let mut out = String::new();
+    let header: Vec<&str> = columns.iter().map(|c| c.name()).collect();
+    push_row(&mut out, &header, delimiter);
+    for feature in &roadmap.features {
+        push_feature(&mut out, roadmap, feature, "", columns, delimiter);
+    }
+    out
+}
+
+fn push_feature(
+    out: &mut String,
+    roadmap: &Roadmap,
+    feature: &Feature,
+    parent: &str,
+    columns: &[Column],
+    delimiter: char,
+) {
+    let title = if parent.is_empty() {
+        feature.title.clone()
+    } else {
+        format!("{} / {}", parent, feature.title)
+    };
+    if feature.tasks.is_empty() {
+        let row = row(roadmap, feature, &title, None, columns);
+        push_row(out, &row, delimiter);
+    }
+    for task in &feature.tasks {
+        let row = row(roadmap, feature, &title, Some(task), columns);
+        push_row(out, &row, delimiter);
+    }
+    for child in &feature.children {
+        push_feature(out, roadmap, child, &title, columns, delimiter);
+    }
+}
+
+fn row(
+    roadmap: &Roadmap,
+    feature: &Feature,
+    title: &str,
+    task: Option<&Task>,
+    columns: &[Column],
+) -> Vec<String> {
+    columns
+        .iter()
+        .map(|&column| cell(roadmap, feature, title, task, column))
+        .collect()
+}
+
+/// The value of `column` on the row of `task`, or of `feature` itself.
+pub(crate) fn cell(
+    roadmap: &Roadmap,
+    feature: &Feature,
+    title: &str,
+    task: Option<&Task>,
+    column: Column,
+) -> String {
+    let milestone = feature.milestone.as_deref().unwrap_or("");
+    match column {
+        Column::Milestone => milestone.to_string(),
+        Column::Feature => title.to_string(),
+        Column::Task => task.map_or("", |t| t.title.as_str()).to_string(),
+        Column::Status => match task {
+            Some(task) if task.completed => "done".into(),
+            _ => "open".into(),
+        },
+        Column::Labels => {
+            let mut labels = feature.labels.clone();
+            for label in task.iter().flat_map(|t| &t.labels) {
+                if !labels.contains(label) {
+                    labels.push(label.clone());
+                }
+            }
+            labels.join(", ")
+        }
+        Column::DueDate => roadmap
+            .milestones
+            .iter()
+            .find(|m| !milestone.is_empty() && m.name.eq_ignore_ascii_case(milestone))
+            .and_then(|m| m.due_date.clone())
+            .unwrap_or_default(),
+        Column::Kind => feature.kind.name().to_string(),
+        Column::Assignees => match task {
+            Some(task) if !task.assignees.is_empty() => task.assignees.join(", "),
+            _ => feature.assignees.join(", "),
+        },
+        Column::File => feature.file.clone().unwrap_or_default(),
+    }
+}
+
+pub(crate) fn push_row<S: AsRef<str>>(out: &mut String, fields: &[S], delimiter: char) {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| escape(field.as_ref(), delimiter))
+        .collect();
+    out.push_str(&fields.join(&delimiter.to_string()));
+    out.push('\n');
+}
+
+/// Quote a CSV field that holds the delimiter, a quote or a line break.
+/// TSV cannot quote, so tabs and line breaks become spaces instead.
+fn escape(field: &str, delimiter: char) -> String {
+    if delimiter == '\t' {
+        return field.replace(['\t', '\r', '\n'], " ");
+    }
+    if field.contains([delimiter, '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "# Demo\n\n## Milestones\n- Beta — 2025-07-01\n\n## Features\n\n\
+        ### Search\nMilestone: Beta\nLabels: backend, search\n\n\
+        - [x] Index\n- [ ] Query \"API\"\n\n### Auth\n";
+
+    #[test]
+    fn exports_one_row_per_task() {
+        let csv = to_delimited(&parse_roadmap(ROADMAP), &Column::DEFAULT, ',');
+        assert_eq!(
+            csv,
+            "milestone,feature,task,status,labels,due_date\n\
+             Beta,Search,Index,done,\"backend, search\",2025-07-01\n\
+             Beta,Search,\"Query \"\"API\"\"\",open,\"backend, search\",2025-07-01\n\
+             ,Auth,,open,,\n"
+        );
+    }
+
+    #[test]
+    fn selects_columns_and_flattens_sub_features() {
+        let mut roadmap = parse_roadmap(ROADMAP);
+        let child = roadmap.features.remove(0);
+        roadmap.features[0].children.push(child);
+        let tsv = to_delimited(&roadmap, &[Column::Feature, Column::Labels], '\t');
+        assert_eq!(
+            tsv,
+            "feature\tlabels\nAuth\t\nAuth / Search\tbackend, search\nAuth / Search\tbackend, search\n"
+        );
+    }
+}
@@ -0,0 +1,1993 @@
+//! Roadmap extraction on top of the pulldown-cmark event stream.
+//!
+//! A leading YAML frontmatter block is split off first and becomes the
+//! roadmap's metadata. The Markdown body is then extracted in a single
+//! streaming pass:
+//!
+//! 1. the event stream is folded into top-level [`Block`]s (headings,
+//!    paragraphs, lists, tables and opaque blocks), keeping byte ranges into
+//!    the source so descriptions can be reproduced verbatim;
+//! 2. headings open and close [`Section`]s, each with a role derived from
+//!    its parent: the first H1 names the project, a `Milestones` section
+//!    lists milestones and every heading below a `Features` section becomes
+//!    a feature (with deeper headings as tasks, or as sub-features when
+//!    they say so), as does every row of a table directly below it whose
+//!    columns map onto feature fields. The section titles, headings that
+//!    are features on their own and table columns are configurable through
+//!    [`Headings`];
+//! 3. when a milestones or feature section closes it is interpreted and
+//!    yielded as an [`Entity`], so only the section being read is held in
+//!    memory.
+
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, LinkType, Options, Parser, Tag};
+
+use crate::config::{strip_any, FeatureColumn, Headings};
+use crate::dates::normalize_date;
+use crate::frontmatter;
+use crate::graph;
+use crate::migrate;
+use crate::roadmap::{
+    Anchor, Entity, Estimate, ExtraSection, Feature, FeatureKind, FeatureStatus, IssueLink, Link,
+    Metadata, Milestone, ParseWarning, Position, Priority, Roadmap, Span, Task, WarningKind,
+};
+use crate::text::{self, find_due, title_key};
+
+/// Parse Markdown roadmap source into a [`Roadmap`].
+///
+/// The name is left empty when the document has no H1 heading; callers that
+/// know the file path fall back to its stem, like the Python parser does.
+pub fn parse_roadmap(source: &str) -> Roadmap {
+    parse_roadmap_with(source, &Headings::default())
+}
+
+/// [`parse_roadmap`] with custom heading conventions.
+pub fn parse_roadmap_with(source: &str, headings: &Headings) -> Roadmap {
+    let mut roadmap: Roadmap = Entities::with_headings(source, headings.clone()).collect();
+    graph::resolve(&mut roadmap);
+    roadmap
+}
+
+pub fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES
+}
+
+/// One line of a paragraph: plain text with inline markup removed, plus the
+/// source range covering the line's markup.
+#[derive(Debug)]
+struct Line<'a> {
+    text: Cow<'a, str>,
+    range: Range<usize>,
+}
+
+#[derive(Debug)]
+struct Item<'a> {
+    text: Cow<'a, str>,
+    checked: Option<bool>,
+    depth: usize,
+    range: Range<usize>,
+}
+
+#[derive(Debug)]
+struct Row<'a> {
+    cells: Vec<Cow<'a, str>>,
+    range: Range<usize>,
+}
+
+#[derive(Debug)]
+enum Block<'a> {
+    Heading {
+        level: u8,
+        text: Cow<'a, str>,
+        range: Range<usize>,
+    },
+    Paragraph {
+        lines: Vec<Line<'a>>,
+        range: Range<usize>,
+    },
+    List {
+        items: Vec<Item<'a>>,
+        range: Range<usize>,
+    },
+    Table {
+        rows: Vec<Row<'a>>,
+        range: Range<usize>,
+    },
+    /// A fenced or indented code block, with its info string (the
+    /// language) and its text as CommonMark reads it.
+    Code {
+        info: Option<String>,
+        code: String,
+        range: Range<usize>,
+    },
+    Other {
+        range: Range<usize>,
+    },
+}
+
+impl Block<'_> {
+    fn range(&self) -> Option<&Range<usize>> {
+        match self {
+            Block::Heading { .. } => None,
+            Block::Paragraph { range, .. }
+            | Block::List { range, .. }
+            | Block::Table { range, .. }
+            | Block::Code { range, .. }
+            | Block::Other { range } => Some(range),
+        }
+    }
+}
+
+/// A link or image in the body, as pulldown-cmark resolved it, with the
+/// range of its markup.
+#[derive(Debug)]
+struct RawLink {
+    kind: LinkType,
+    image: bool,
+    link: Link,
+    range: Range<usize>,
+}
+
+impl RawLink {
+    /// The link written inline, `[text](url "title")`, when the source
+    /// refers to a definition elsewhere instead, and the end of the markup
+    /// it replaces.
+    fn inline(&self, source: &str) -> Option<(String, usize)> {
+        let open = if self.image { "![" } else { "[" };
+        let markup = source[self.range.clone()].strip_prefix(open)?;
+        let mut end = self.range.end;
+        let text = match self.kind {
+            LinkType::Reference => markup[..markup.rfind('[')?].strip_suffix(']')?,
+            // The range of `[text][]` stops before the `[]`.
+            LinkType::Collapsed if source[end..].starts_with("[]") => {
+                end += 2;
+                markup.strip_suffix(']')?
+            }
+            LinkType::Shortcut => markup.strip_suffix(']')?,
+            _ => return None,
+        };
+        let url = &self.link.url;
+        let mut inline = if url.is_empty() || url.contains([' ', '(', ')']) {
+            format!("{}{}](<{}>", open, text, url)
+        } else {
+            format!("{}{}]({}", open, text, url)
+        };
+        if let Some(title) = &self.link.title {
+            inline.push_str(&format!(" \"{}\"", title.replace('"', "\\\"")));
+        }
+        inline.push(')');
+        Some((inline, end))
+    }
+}
+
+/// The event stream of the Markdown body, with ranges shifted to be
+/// offsets into the whole source (frontmatter included). Links and
+/// images are recorded on the way, for the section of the block they are
+/// in.
+struct Events<'a> {
+    inner: pulldown_cmark::OffsetIter<'a, 'a>,
+    offset: usize,
+    links: Vec<RawLink>,
+    /// Indices into `links` of those still collecting their text, like a
+    /// link and the image inside it.
+    open: Vec<usize>,
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = (Event<'a>, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (event, range) = self.inner.next()?;
+        let range = range.start + self.offset..range.end + self.offset;
+        match &event {
+            Event::Start(tag @ (Tag::Link(kind, url, title) | Tag::Image(kind, url, title))) => {
+                let url = match kind {
+                    LinkType::Email => format!("mailto:{}", url),
+                    _ => url.to_string(),
+                };
+                let link = Link {
+                    text: String::new(),
+                    url,
+                    title: Some(title.to_string()).filter(|title| !title.is_empty()),
+                };
+                self.open.push(self.links.len());
+                self.links.push(RawLink {
+                    kind: *kind,
+                    image: matches!(tag, Tag::Image(..)),
+                    link,
+                    range: range.clone(),
+                });
+            }
+            Event::End(Tag::Link(..) | Tag::Image(..)) => {
+                self.open.pop();
+            }
+            Event::Text(text) | Event::Code(text) => {
+                for &index in &self.open {
+                    self.links[index].link.text.push_str(text);
+                }
+            }
+            _ => {}
+        }
+        Some((event, range))
+    }
+}
+
+/// Top-level blocks of a document, folded from its event stream on demand.
+struct Blocks<'a> {
+    events: Events<'a>,
+    /// An event read ahead while joining the lines of an HTML block.
+    peeked: Option<(Event<'a>, Range<usize>)>,
+}
+
+impl<'a> Blocks<'a> {
+    /// Blocks of `source` from byte `offset` on.
+    fn new(source: &'a str, offset: usize) -> Self {
+        let inner = Parser::new_ext(&source[offset..], options()).into_offset_iter();
+        Blocks {
+            events: Events {
+                inner,
+                offset,
+                links: Vec::new(),
+                open: Vec::new(),
+            },
+            peeked: None,
+        }
+    }
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = Block<'a>;
+
+    fn next(&mut self) -> Option<Block<'a>> {
+        let events = &mut self.events;
+        let (event, mut range) = self.peeked.take().or_else(|| events.next())?;
+        let block = match event {
+            Event::Start(Tag::Heading(level, ..)) => Block::Heading {
+                level: level as u8,
+                text: collect_inline(events).text,
+                range,
+            },
+            Event::Start(Tag::Paragraph) => Block::Paragraph {
+                lines: collect_lines(events),
+                range,
+            },
+            Event::Start(Tag::List(_)) => Block::List {
+                items: collect_items(events),
+                range,
+            },
+            Event::Start(Tag::Table(_)) => Block::Table {
+                rows: collect_rows(events),
+                range,
+            },
+            Event::Start(Tag::CodeBlock(kind)) => Block::Code {
+                info: match kind {
+                    CodeBlockKind::Fenced(info) => Some(info.to_string()),
+                    CodeBlockKind::Indented => None,
+                },
+                code: collect_code(events),
+                range,
+            },
+            Event::Start(_) => {
+                skip_to_end(events);
+                Block::Other { range }
+            }
+            // An HTML block comes as one event per line.
+            Event::Html(_) => {
+                for (next, next_range) in events.by_ref() {
+                    if matches!(next, Event::Html(_)) && next_range.start == range.end {
+                        range.end = next_range.end;
+                    } else {
+                        self.peeked = Some((next, next_range));
+                        break;
+                    }
+                }
+                Block::Other { range }
+            }
+            _ => Block::Other { range },
+        };
+        Some(block)
+    }
+}
+
+/// Consume events up to and including the `End` matching an already
+/// consumed `Start`.
+fn skip_to_end(events: &mut Events) {
+    let mut depth = 0usize;
+    for (event, _) in events.by_ref() {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) if depth == 0 => return,
+            Event::End(_) => depth -= 1,
+            _ => {}
+        }
+    }
+}
+
+/// Flatten inline content up to the end of the enclosing block into plain
+/// text, treating line breaks as spaces.
+fn collect_inline<'a>(events: &mut Events<'a>) -> Line<'a> {
+    let mut lines = collect_lines(events).into_iter();
+    let mut line = lines.next().unwrap_or(Line {
+        text: Cow::Borrowed(""),
+        range: 0..0,
+    });
+    for next in lines {
+        let text = line.text.to_mut();
+        text.push(' ');
+        text.push_str(&next.text);
+        line.range.end = next.range.end;
+    }
+    line
+}
+
+/// Collect inline content up to the end of the enclosing block, split into
+/// lines at soft and hard breaks.
+fn collect_lines<'a>(events: &mut Events<'a>) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    let mut current: Option<Line> = None;
+    let mut depth = 0usize;
+    for (event, range) in events.by_ref() {
+        match &event {
+            Event::End(_) if depth == 0 => break,
+            Event::SoftBreak | Event::HardBreak => {
+                lines.extend(current.take());
+                continue;
+            }
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            _ => {}
+        }
+        let line = current.get_or_insert_with(|| Line {
+            text: Cow::Borrowed(""),
+            range: range.clone(),
+        });
+        line.range.start = line.range.start.min(range.start);
+        line.range.end = line.range.end.max(range.end);
+        if let Event::Text(text) | Event::Code(text) = event {
+            append(&mut line.text, text);
+        }
+    }
+    lines.extend(current);
+    for line in &mut lines {
+        text::normalize_cow(&mut line.text);
+    }
+    lines
+}
+
+/// Add the text of an event to `buffer`, borrowing it from the source
+/// while it is the only text and needed no unescaping.
+fn append<'a>(buffer: &mut Cow<'a, str>, text: CowStr<'a>) {
+    match text {
+        CowStr::Borrowed(text) if buffer.is_empty() => *buffer = Cow::Borrowed(text),
+        text => buffer.to_mut().push_str(&text),
+    }
+}
+
+/// The text of a code block, up to its end.
+fn collect_code(events: &mut Events) -> String {
+    let mut code = String::new();
+    for (event, _) in events.by_ref() {
+        match event {
+            Event::Text(text) => code.push_str(&text),
+            Event::End(_) => break,
+            _ => {}
+        }
+    }
+    code
+}
+
+/// A code block as a description part: its source when it is a fence at
+/// the start of a line, which keeps it exactly as written, or else a fence
+/// rebuilt around its code, since indentation would not survive being
+/// moved into an issue body.
+fn code_block(source: &str, range: &Range<usize>, info: Option<&str>, code: &str) -> String {
+    let at_line_start = range.start == 0 || source[..range.start].ends_with('\n');
+    if info.is_some() && at_line_start {
+        return source[range.clone()].trim_end().to_string();
+    }
+    // The fence must be longer than any run of backticks in the code.
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}{}", fence, info.unwrap_or(""), code, fence)
+}
+
+/// Collect the items of a list (including nested lists) in document order.
+fn collect_items<'a>(events: &mut Events<'a>) -> Vec<Item<'a>> {
+    let mut items: Vec<Item> = Vec::new();
+    // Indices into `items` of the currently open items, innermost last.
+    let mut open: Vec<usize> = Vec::new();
+    let mut depth = 0usize;
+    for (event, range) in events.by_ref() {
+        match event {
+            Event::Start(Tag::List(_)) => depth += 1,
+            Event::End(Tag::List(_)) if depth == 0 => break,
+            Event::End(Tag::List(_)) => depth -= 1,
+            Event::Start(Tag::Item) => {
+                open.push(items.len());
+                items.push(Item {
+                    text: Cow::Borrowed(""),
+                    checked: None,
+                    depth,
+                    range,
+                });
+            }
+            Event::End(Tag::Item) => {
+                if let Some(index) = open.pop() {
+                    text::normalize_cow(&mut items[index].text);
+                }
+            }
+            Event::TaskListMarker(checked) => {
+                if let Some(&index) = open.last() {
+                    items[index].checked = Some(checked);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(&index) = open.last() {
+                    append(&mut items[index].text, text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak | Event::End(Tag::Paragraph) => {
+                if let Some(&index) = open.last() {
+                    items[index].text.to_mut().push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    items
+}
+
+fn collect_rows<'a>(events: &mut Events<'a>) -> Vec<Row<'a>> {
+    let mut rows: Vec<Row> = Vec::new();
+    for (event, range) in events.by_ref() {
+        match event {
+            Event::End(Tag::Table(_)) => break,
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => rows.push(Row {
+                cells: Vec::new(),
+                range,
+            }),
+            Event::Start(Tag::TableCell) => {
+                if let Some(row) = rows.last_mut() {
+                    row.cells.push(Cow::Borrowed(""));
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(cell) = rows.last_mut().and_then(|row| row.cells.last_mut()) {
+                    append(cell, text);
+                }
+            }
+            _ => {}
+        }
+    }
+    for row in &mut rows {
+        for cell in &mut row.cells {
+            text::normalize_cow(cell);
+        }
+    }
+    rows
+}
+
+/// The source text plus an index mapping byte offsets to 1-based line and
+/// column numbers.
+struct Document<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> Document<'a> {
+    fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Document { source, starts }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.starts[line];
+        Position {
+            line: line + 1,
+            column: self.source[start..offset].chars().count() + 1,
+            offset,
+        }
+    }
+
+    /// The span of `range`, excluding trailing whitespace.
+    fn span(&self, range: &Range<usize>) -> Span {
+        let end = range.start + self.source[range.clone()].trim_end().len();
+        Span {
+            start: self.position(range.start),
+            end: self.position(end),
+        }
+    }
+}
+
+/// A heading together with the blocks and sub-headings below it.
+#[derive(Debug, Default)]
+struct Section<'a> {
+    level: u8,
+    title: String,
+    range: Range<usize>,
+    blocks: Vec<Block<'a>>,
+    /// The links and images in `blocks`.
+    links: Vec<RawLink>,
+    children: Vec<Section<'a>>,
+}
+
+/// What an open section contributes to the roadmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// The first H1; its title names the roadmap and its leading blocks
+    /// describe it.
+    Title,
+    Milestones,
+    /// The `Features` heading itself, whose children are features.
+    Features,
+    /// A `Deferred` or `Icebox` heading, whose children are deferred
+    /// features.
+    Deferred,
+    Feature,
+    /// Below a milestones or feature section; kept for its parent.
+    Nested,
+    /// Anything else; its content is dropped.
+    Ignored,
+}
+
+/// Iterator over the [`Entity`]s of a Markdown roadmap, extracting each one
+/// as soon as its section ends.
+///
+/// Features and milestones are yielded in document order. The
+/// [`Entity::Roadmap`] header comes once its description is complete:
+/// right after the title's leading blocks, or last when the document has
+/// no H1.
+pub struct Entities<'a> {
+    doc: Document<'a>,
+    blocks: Blocks<'a>,
+    headings: Headings,
+    open: Vec<(Role, Section<'a>)>,
+    name: String,
+    metadata: Metadata,
+    /// Source ranges of the runs of blocks before the first heading and
+    /// directly below the title, which make up the description.
+    description: Vec<Range<usize>>,
+    /// Whether the next description block continues the last range.
+    in_description: bool,
+    header_done: bool,
+    /// Titles of the sections seen so far below the title, lowercased.
+    top_level: HashSet<String>,
+    /// The last milestones or features section opened.
+    anchor: Anchor,
+    /// The unrecognized section being read, with the range of its heading.
+    extra: Option<(ExtraSection, Range<usize>)>,
+    ready: VecDeque<Entity>,
+}
+
+impl<'a> Entities<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Entities::with_headings(source, Headings::default())
+    }
+
+    /// Extract with custom heading conventions instead of the default
+    /// `Milestones` and `Features` sections.
+    pub fn with_headings(source: &'a str, headings: Headings) -> Self {
+        let (metadata, body) = frontmatter::split(source).unwrap_or_default();
+        Entities {
+            doc: Document::new(source),
+            blocks: Blocks::new(source, body),
+            headings,
+            open: Vec::new(),
+            name: String::new(),
+            metadata,
+            description: Vec::new(),
+            in_description: false,
+            header_done: false,
+            top_level: HashSet::new(),
+            anchor: Anchor::Title,
+            extra: None,
+            ready: VecDeque::new(),
+        }
+    }
+
+    fn open_section(&mut self, level: u8, mut title: String, range: Range<usize>) {
+        while self.open.last().is_some_and(|(_, top)| top.level >= level) {
+            self.close_section();
+        }
+        let headings = &self.headings;
+        let feature = strip_any(&headings.feature, level, &title).map(str::to_string);
+        let role = match self.open.last().map(|(role, _)| *role) {
+            None if level == 1 && self.name.is_empty() => Role::Title,
+            Some(Role::Features | Role::Deferred) => Role::Feature,
+            Some(Role::Milestones | Role::Feature | Role::Nested) => Role::Nested,
+            _ if feature.is_some() => Role::Feature,
+            None | Some(Role::Title)
+                if strip_any(&headings.milestones, level, &title).is_some() =>
+            {
+                Role::Milestones
+            }
+            None | Some(Role::Title) if strip_any(&headings.features, level, &title).is_some() => {
+                Role::Features
+            }
+            None | Some(Role::Title) if strip_any(&headings.deferred, level, &title).is_some() => {
+                Role::Deferred
+            }
+            _ => Role::Ignored,
+        };
+        if let (Role::Feature, Some(stripped)) = (role, feature) {
+            title = stripped;
+        }
+        let top_level = matches!(self.open.last(), None | Some((Role::Title, _)));
+        if top_level
+            && matches!(
+                role,
+                Role::Milestones | Role::Features | Role::Deferred | Role::Ignored
+            )
+        {
+            let span = Some(self.doc.span(&range));
+            if !self.top_level.insert(title_key(&title)) {
+                let message = format!("duplicate heading '{}'", title);
+                self.warn(WarningKind::DuplicateHeading, message, span);
+            } else if role == Role::Ignored && !title.is_empty() {
+                let message = format!(
+                    "section '{}' is not a milestones or features section; it is kept as is",
+                    title
+                );
+                self.warn(WarningKind::UnknownSection, message, span);
+            }
+        }
+        // An unrecognized section runs up to the next heading of its level
+        // or above, or to a heading that is a feature on its own.
+        let ends_extra = |(extra, _): &(ExtraSection, Range<usize>)| {
+            level <= extra.level || role == Role::Feature
+        };
+        if self.extra.as_ref().is_some_and(ends_extra) {
+            self.finish_extra(range.start);
+        }
+        match role {
+            Role::Milestones => self.anchor = Anchor::Milestones,
+            Role::Features | Role::Deferred => self.anchor = Anchor::Features,
+            Role::Ignored if top_level => {
+                let extra = ExtraSection {
+                    title: title.clone(),
+                    level,
+                    after: self.anchor,
+                    ..ExtraSection::default()
+                };
+                self.extra = Some((extra, range.clone()));
+            }
+            _ => {}
+        }
+        self.in_description = false;
+        if role == Role::Title {
+            self.name = title.clone();
+        } else if !self.name.is_empty() {
+            self.finish_header();
+        }
+        let section = Section {
+            level,
+            title,
+            range,
+            ..Section::default()
+        };
+        self.open.push((role, section));
+    }
+
+    fn add_block(&mut self, block: Block<'a>, links: Vec<RawLink>) {
+        let Some(range) = block.range().cloned() else {
+            return;
+        };
+        match self.open.last_mut() {
+            None | Some((Role::Title, _)) => match self.description.last_mut() {
+                Some(last) if self.in_description => last.end = range.end,
+                _ => {
+                    self.description.push(range);
+                    self.in_description = true;
+                }
+            },
+            Some((Role::Milestones | Role::Feature | Role::Nested, section)) => {
+                section.range.end = section.range.end.max(range.end);
+                section.blocks.push(block);
+                section.links.extend(links);
+            }
+            Some((role @ (Role::Features | Role::Deferred), _)) => {
+                if let Block::Table { rows, .. } = &block {
+                    let deferred = *role == Role::Deferred;
+                    let features = features_from_table(&self.doc, &self.headings, rows);
+                    self.ready.extend(features.into_iter().map(|mut feature| {
+                        if deferred {
+                            feature.status = Some(FeatureStatus::Deferred);
+                        }
+                        Entity::Feature(feature)
+                    }));
+                }
+            }
+            Some((Role::Ignored, _)) => {}
+        }
+    }
+
+    fn close_section(&mut self) {
+        let Some((role, section)) = self.open.pop() else {
+            return;
+        };
+        match role {
+            Role::Nested => {
+                if let Some((parent_role, parent)) = self.open.last_mut() {
+                    let repeated = *parent_role != Role::Milestones
+                        && !section.title.is_empty()
+                        && parent
+                            .children
+                            .iter()
+                            .any(|child| title_key(&child.title) == title_key(&section.title));
+                    let message = format!(
+                        "duplicate heading '{}' under '{}'",
+                        section.title, parent.title
+                    );
+                    parent.range.end = parent.range.end.max(section.range.end);
+                    let span = first_line(&self.doc, &section.range);
+                    parent.children.push(section);
+                    if repeated {
+                        self.warn(WarningKind::DuplicateHeading, message, Some(span));
+                    }
+                }
+            }
+            Role::Milestones => self.ready.extend(
+                milestones_from(&self.doc, section)
+                    .into_iter()
+                    .map(Entity::Milestone),
+            ),
+            Role::Feature if !section.title.is_empty() => {
+                let mut warnings = Vec::new();
+                let mut feature = feature_from(&self.doc, &self.headings, section, &mut warnings);
+                if matches!(self.open.last(), Some((Role::Deferred, _))) {
+                    feature.status = Some(FeatureStatus::Deferred);
+                }
+                self.ready.push_back(Entity::Feature(feature));
+                self.ready.extend(warnings.into_iter().map(Entity::Warning));
+            }
+            _ => {}
+        }
+    }
+
+    /// Emit the unrecognized section being read, which ends at `end`.
+    fn finish_extra(&mut self, end: usize) {
+        let Some((mut extra, heading)) = self.extra.take() else {
+            return;
+        };
+        extra.body = self.doc.source[heading.end..end].trim().to_string();
+        extra.span = Some(self.doc.span(&(heading.start..end)));
+        self.ready.push_back(Entity::ExtraSection(extra));
+    }
+
+    fn warn(&mut self, kind: WarningKind, message: String, span: Option<Span>) {
+        self.ready.push_back(Entity::Warning(ParseWarning {
+            kind,
+            message,
+            file: None,
+            span,
+        }));
+    }
+
+    fn finish_header(&mut self) {
+        if self.header_done {
+            return;
+        }
+        self.header_done = true;
+        let source = self.doc.source;
+        let description: Vec<&str> = self
+            .description
+            .iter()
+            .map(|range| source[range.clone()].trim())
+            .filter(|part| !part.is_empty())
+            .collect();
+        let leading = source.len() - source.trim_start().len();
+        let mut metadata = std::mem::take(&mut self.metadata);
+        let version = metadata.get("version").and_then(migrate::json_version);
+        if version.is_some() {
+            metadata.remove("version");
+        }
+        let text = |key: &str| metadata.get(key).and_then(|value| value.as_str());
+        self.ready.push_back(Entity::Roadmap {
+            name: text("name").unwrap_or(&self.name).to_string(),
+            description: text("description")
+                .map(str::to_string)
+                .unwrap_or_else(|| description.join("\n\n")),
+            version,
+            metadata,
+            span: Some(self.doc.span(&(leading..source.len()))),
+        });
+        self.ready
+            .extend(migrate::check(version).map(Entity::Warning));
+    }
+}
+
+impl Iterator for Entities<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        loop {
+            if let Some(entity) = self.ready.pop_front() {
+                return Some(entity);
+            }
+            let block = self.blocks.next();
+            let links = std::mem::take(&mut self.blocks.events.links);
+            match block {
+                Some(Block::Heading { level, text, range }) => {
+                    self.open_section(level, text.into_owned(), range)
+                }
+                Some(block) => self.add_block(block, links),
+                None => {
+                    self.finish_extra(self.doc.source.len());
+                    while !self.open.is_empty() {
+                        self.close_section();
+                    }
+                    self.finish_header();
+                    return self.ready.pop_front();
+                }
+            }
+        }
+    }
+}
+
+fn milestones_from(doc: &Document, section: Section) -> Vec<Milestone> {
+    let mut milestones = Vec::new();
+    for block in &section.blocks {
+        match block {
+            Block::List { items, .. } => milestones.extend(
+                items
+                    .iter()
+                    .filter(|item| item.depth == 0 && !item.text.is_empty())
+                    .map(|item| milestone_from_text(&item.text, doc.span(&item.range))),
+            ),
+            Block::Table { rows, .. } => {
+                // The first row is always the table head.
+                for row in rows.iter().skip(1) {
+                    if let Some(name) = row.cells.first().filter(|name| !name.is_empty()) {
+                        milestones.push(Milestone {
+                            name: name.to_string(),
+                            due_date: row
+                                .cells
+                                .get(1)
+                                .filter(|due| !due.is_empty())
+                                .map(|due| normalize_date(due)),
+                            description: row
+                                .cells
+                                .get(2)
+                                .map(|cell| cell.to_string())
+                                .unwrap_or_default(),
+                            span: Some(doc.span(&row.range)),
+                            ..Milestone::default()
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    milestones.extend(
+        section
+            .children
+            .iter()
+            .filter(|child| !child.title.is_empty())
+            .map(|child| Milestone {
+                description: raw_blocks(doc.source, &child.blocks),
+                ..milestone_from_text(&child.title, doc.span(&child.range))
+            }),
+    );
+    milestones
+}
+
+/// Split `Name — due date` into its parts.
+fn milestone_from_text(text: &str, span: Span) -> Milestone {
+    let (name, due) = match find_due(text) {
+        Some((end, due)) => (&text[..end], Some(&text[due])),
+        None => (text, None),
+    };
+    Milestone {
+        name: name.trim().to_string(),
+        due_date: due.filter(|due| !due.is_empty()).map(normalize_date),
+        span: Some(span),
+        ..Milestone::default()
+    }
+}
+
+/// Split a `Key: value` metadata line, normalizing the key the way the
+/// Python parser does (case-insensitive, ignoring `*`, `-` and spaces).
+pub(crate) fn metadata(text: &str) -> Option<(String, &str)> {
+    let (key, value) = text.split_once(':')?;
+    Some((metadata_key(key), value.trim()))
+}
+
+/// `key` normalized like the keys of metadata lines.
+pub(crate) fn metadata_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| !matches!(c, '*' | '-') && !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Metadata keys of features, as normalized by [`metadata`], with how
+/// they are usually written.
+const FEATURE_KEYS: &[(&str, &str)] = &[
+    ("description", "Description"),
+    ("milestone", "Milestone"),
+    ("labels", "Labels"),
+    ("label", "Label"),
+    ("assignees", "Assignees"),
+    ("assignee", "Assignee"),
+    ("dependson", "Depends on"),
+    ("dependencies", "Dependencies"),
+    ("status", "Status"),
+    ("estimate", "Estimate"),
+    ("priority", "Priority"),
+    ("kind", "Kind"),
+    ("repo", "Repo"),
+    ("tasks", "Tasks"),
+];
+
+/// Metadata keys of tasks, like [`FEATURE_KEYS`].
+const TASK_KEYS: &[(&str, &str)] = &[
+    ("labels", "Labels"),
+    ("label", "Label"),
+    ("assignees", "Assignees"),
+    ("assignee", "Assignee"),
+    ("estimate", "Estimate"),
+    ("tests", "Tests"),
+];
+
+/// A warning when `line` is not metadata but its key is one of `known`
+/// misspelt: one edit away, or two for keys of eight letters or more.
+fn misspelt_key(doc: &Document, line: &Line, known: &[(&str, &str)]) -> Option<ParseWarning> {
+    let (key, _) = metadata(&line.text)?;
+    if key.len() < 3 || known.iter().any(|(name, _)| *name == key) {
+        return None;
+    }
+    let (_, suggestion) = known
+        .iter()
+        .map(|&(name, written)| (strsim::damerau_levenshtein(&key, name), name, written))
+        .filter(|&(distance, name, _)| distance <= if name.len() >= 8 { 2 } else { 1 })
+        .min_by_key(|&(distance, _, _)| distance)
+        .map(|(_, name, written)| (name, written))?;
+    let written = line.text.split_once(':')?.0.trim();
+    Some(ParseWarning {
+        kind: WarningKind::MetadataTypo,
+        message: format!(
+            "'{}:' looks like a misspelling of '{}:'; the line is kept in the description",
+            written, suggestion
+        ),
+        file: None,
+        span: Some(doc.span(&line.range)),
+    })
+}
+
+pub(crate) fn is_labels(key: &str) -> bool {
+    matches!(key, "labels" | "label")
+}
+
+pub(crate) fn is_assignees(key: &str) -> bool {
+    matches!(key, "assignees" | "assignee")
+}
+
+/// Split a comma-separated metadata value, dropping empty and repeated
+/// entries.
+fn split_list(value: &str) -> Vec<String> {
+    let mut list: Vec<String> = Vec::new();
+    for part in value.split(',').map(str::trim) {
+        if !part.is_empty() && !list.iter().any(|seen| seen == part) {
+            list.push(part.to_string());
+        }
+    }
+    list
+}
+
+/// Like [`split_list`], for feature titles optionally written as `#Title`.
+fn reference_list(value: &str) -> Vec<String> {
+    split_list(value)
+        .iter()
+        .map(|reference| reference.trim_start_matches('#').trim().to_string())
+        .filter(|reference| !reference.is_empty())
+        .collect()
+}
+
+/// Like [`split_list`], accepting `@login` mentions as written on GitHub.
+fn assignee_list(value: &str) -> Vec<String> {
+    split_list(&value.replace('@', ""))
+}
+
+/// Raw source covering `blocks`, trimmed.
+fn raw_blocks(source: &str, blocks: &[Block]) -> String {
+    let mut ranges = blocks.iter().filter_map(Block::range);
+    match ranges.next() {
+        Some(first) => {
+            let end = ranges.next_back().map_or(first.end, |last| last.end);
+            source[first.start..end].trim().to_string()
+        }
+        None => String::new(),
+    }
+}
+
+/// Accumulates description parts, one per block, joined by blank lines,
+/// and the links and images in them.
+#[derive(Default)]
+struct Description {
+    parts: Vec<String>,
+    links: Vec<Link>,
+    images: Vec<Link>,
+}
+
+impl Description {
+    fn push(&mut self, part: String) {
+        if !part.is_empty() {
+            self.parts.push(part);
+        }
+    }
+
+    /// The source of `range`, trimmed, with the reference-style links and
+    /// images among `links` written inline so the text stands on its own.
+    fn quote(&mut self, source: &str, range: &Range<usize>, links: &[RawLink]) -> String {
+        let mut text = String::new();
+        let mut at = range.start;
+        for raw in links
+            .iter()
+            .filter(|raw| range.start <= raw.range.start && raw.range.end <= range.end)
+        {
+            match raw.image {
+                true => self.images.push(raw.link.clone()),
+                false => self.links.push(raw.link.clone()),
+            }
+            match raw.inline(source) {
+                Some((inline, end)) if at <= raw.range.start && end <= range.end => {
+                    text.push_str(&source[at..raw.range.start]);
+                    text.push_str(&inline);
+                    at = end;
+                }
+                _ => {}
+            }
+        }
+        text.push_str(&source[at..range.end]);
+        text.trim().to_string()
+    }
+
+    fn finish(self) -> String {
+        self.parts.join("\n\n")
+    }
+}
+
+/// Parse a feature section, adding warnings about it to `warnings`.
+fn feature_from(
+    doc: &Document,
+    headings: &Headings,
+    section: Section,
+    warnings: &mut Vec<ParseWarning>,
+) -> Feature {
+    let (title, issue) = issue_link(doc, &section);
+    let (title, status, estimate, priority) = title_markers(&title);
+    let mut feature = Feature {
+        title,
+        status,
+        estimate,
+        priority,
+        issue,
+        span: Some(doc.span(&section.range)),
+        ..Feature::default()
+    };
+    let mut description = Description::default();
+    let mut explicit_description = None;
+    let mut kind = None;
+    let mut in_tasks = false;
+
+    for block in &section.blocks {
+        match block {
+            Block::Paragraph { lines, .. } => {
+                let mut kept = Vec::new();
+                for line in lines {
+                    match metadata(&line.text) {
+                        Some((key, value)) if key == "description" => {
+                            explicit_description = Some(value.to_string())
+                        }
+                        Some((key, value)) if key == "milestone" => {
+                            feature.milestone = Some(value.to_string()).filter(|v| !v.is_empty())
+                        }
+                        Some((key, value)) if is_labels(&key) => feature.labels = split_list(value),
+                        Some((key, value)) if is_assignees(&key) => {
+                            feature.assignees = assignee_list(value)
+                        }
+                        Some((key, value)) if key == "dependson" || key == "dependencies" => {
+                            feature.depends_on = reference_list(value)
+                        }
+                        Some((key, value)) if key == "status" && !value.is_empty() => {
+                            feature.status = Some(FeatureStatus::parse(value))
+                        }
+                        Some((key, value)) if key == "estimate" && !value.is_empty() => {
+                            feature.estimate = Some(Estimate::parse(value))
+                        }
+                        Some((key, value)) if key == "priority" && !value.is_empty() => {
+                            feature.priority = Some(Priority::parse(value))
+                        }
+                        Some((key, value))
+                            if key == "kind" && FeatureKind::parse(value).is_some() =>
+                        {
+                            kind = FeatureKind::parse(value)
+                        }
+                        Some((key, value)) if key == "repo" => {
+                            feature.repo = Some(value.to_string()).filter(|v| !v.is_empty())
+                        }
+                        Some((key, "")) if key == "tasks" => in_tasks = true,
+                        Some((key, value))
+                            if !value.is_empty() && headings.field(&key).is_some() =>
+                        {
+                            let name = headings.field(&key).unwrap_or_default();
+                            feature.fields.insert(name.to_string(), value.to_string());
+                        }
+                        _ => {
+                            warnings.extend(misspelt_key(doc, line, FEATURE_KEYS));
+                            kept.push(description.quote(doc.source, &line.range, &section.links))
+                        }
+                    }
+                }
+                description.push(kept.join("\n"));
+            }
+            // Lists after a `Tasks:` line and checklists anywhere in the
+            // body become tasks; in a checklist, plain items are skipped.
+            Block::List { items, .. }
+                if in_tasks || items.iter().any(|item| item.checked.is_some()) =>
+            {
+                feature.tasks.extend(
+                    items
+                        .iter()
+                        .filter(|item| in_tasks || item.checked.is_some())
+                        .filter(|item| !item.text.is_empty())
+                        .map(|item| {
+                            let (title, estimate) = estimate_suffix(&item.text);
+                            Task {
+                                title,
+                                estimate,
+                                completed: item.checked.unwrap_or(false),
+                                depth: item.depth,
+                                span: Some(doc.span(&item.range)),
+                                ..Task::default()
+                            }
+                        }),
+                );
+            }
+            Block::Code { info, code, range } => {
+                description.push(code_block(doc.source, range, info.as_deref(), code))
+            }
+            other => {
+                if let Some(range) = other.range() {
+                    let part = description.quote(doc.source, range, &section.links);
+                    description.push(part);
+                }
+            }
+        }
+    }
+
+    match explicit_description {
+        Some(text) => feature.description = text,
+        None => {
+            feature.links = std::mem::take(&mut description.links);
+            feature.images = std::mem::take(&mut description.images);
+            feature.description = description.finish();
+        }
+    }
+    for mut child in section.children {
+        if child.title.is_empty() {
+            continue;
+        }
+        match sub_feature_title(headings, &child) {
+            Some(title) => {
+                child.title = title;
+                feature
+                    .children
+                    .push(feature_from(doc, headings, child, warnings));
+            }
+            None => feature.tasks.push(task_from(doc, child, warnings)),
+        }
+    }
+    feature.kind = kind.unwrap_or(if feature.children.is_empty() {
+        FeatureKind::Feature
+    } else {
+        FeatureKind::Epic
+    });
+    feature
+}
+
+/// The features listed in a table, one per row, reading the columns
+/// whose header maps onto a feature field. A table without a title column
+/// lists no features.
+fn features_from_table(doc: &Document, headings: &Headings, rows: &[Row]) -> Vec<Feature> {
+    // The first row is always the table head.
+    let Some((head, rows)) = rows.split_first() else {
+        return Vec::new();
+    };
+    let fields: Vec<Option<FeatureColumn>> = head
+        .cells
+        .iter()
+        .map(|header| headings.column(header))
+        .collect();
+    if !fields.contains(&Some(FeatureColumn::Title)) {
+        return Vec::new();
+    }
+    let custom: Vec<Option<&str>> = head
+        .cells
+        .iter()
+        .map(|header| headings.field(&metadata_key(header)))
+        .collect();
+    let mut features = Vec::new();
+    for row in rows {
+        let mut feature = Feature {
+            span: Some(doc.span(&row.range)),
+            ..Feature::default()
+        };
+        let cells = fields.iter().zip(&custom).zip(&row.cells);
+        for ((field, custom), value) in cells.filter(|(_, value)| !value.is_empty()) {
+            match field {
+                Some(FeatureColumn::Title) => {
+                    // A status or estimate column wins over the title's.
+                    let (title, status, estimate, priority) = title_markers(value);
+                    feature.title = title;
+                    feature.status = feature.status.take().or(status);
+                    feature.estimate = feature.estimate.take().or(estimate);
+                    feature.priority = feature.priority.take().or(priority);
+                }
+                Some(FeatureColumn::Description) => feature.description = value.to_string(),
+                Some(FeatureColumn::Milestone) => feature.milestone = Some(value.to_string()),
+                Some(FeatureColumn::Labels) => feature.labels = split_list(value),
+                Some(FeatureColumn::Assignees) => feature.assignees = assignee_list(value),
+                Some(FeatureColumn::Status) => feature.status = Some(FeatureStatus::parse(value)),
+                Some(FeatureColumn::Estimate) => feature.estimate = Some(Estimate::parse(value)),
+                Some(FeatureColumn::Priority) => feature.priority = Some(Priority::parse(value)),
+                Some(FeatureColumn::Kind) => {
+                    feature.kind = FeatureKind::parse(value).unwrap_or_default()
+                }
+                Some(FeatureColumn::DependsOn) => feature.depends_on = reference_list(value),
+                Some(FeatureColumn::Repo) => feature.repo = Some(value.to_string()),
+                None => {
+                    if let Some(name) = custom {
+                        feature.fields.insert(name.to_string(), value.to_string());
+                    }
+                }
+            }
+        }
+        if !feature.title.is_empty() {
+            features.push(feature);
+        }
+    }
+    features
+}
+
+/// Split a `[#12](url)` issue link off the end of a feature heading. The
+/// link is read from the heading's source, since the title only keeps its
+/// text.
+fn issue_link(doc: &Document, section: &Section) -> (String, Option<IssueLink>) {
+    let line = doc.source[section.range.start..]
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_end();
+    let link = line
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once("]("))
+        .and_then(|(text, url)| {
+            let (_, text) = text.rsplit_once('[')?;
+            let number = text.strip_prefix('#')?.parse().ok()?;
+            let title = section.title.strip_suffix(text)?.trim_end().to_string();
+            (!title.is_empty() && !url.contains(char::is_whitespace)).then(|| {
+                let url = url.to_string();
+                (title, IssueLink { number, url })
+            })
+        });
+    match link {
+        Some((title, link)) => (title, Some(link)),
+        None => (section.title.clone(), None),
+    }
+}
+
+/// Split the status, estimate and priority markers off the end of a
+/// feature title, in any order, as in `Login [done] (3d) !!` or
+/// `Login !! [done]`. Each kind is taken once; a second marker of the same
+/// kind stays in the title.
+fn title_markers(
+    title: &str,
+) -> (
+    String,
+    Option<FeatureStatus>,
+    Option<Estimate>,
+    Option<Priority>,
+) {
+    let mut title = title.to_string();
+    let (mut status, mut estimate, mut priority) = (None, None, None);
+    loop {
+        if status.is_none() {
+            let (rest, found) = status_marker(&title);
+            if found.is_some() {
+                (title, status) = (rest, found);
+                continue;
+            }
+        }
+        if estimate.is_none() {
+            let (rest, found) = estimate_suffix(&title);
+            if found.is_some() {
+                (title, estimate) = (rest, found);
+                continue;
+            }
+        }
+        if priority.is_none() {
+            let (rest, found) = priority_marker(&title);
+            if found.is_some() {
+                (title, priority) = (rest, found);
+                continue;
+            }
+        }
+        return (title, status, estimate, priority);
+    }
+}
+
+/// Split a `[status]` marker off the end of a feature title, as in
+/// `Login [in-progress]`. Brackets holding anything but a known status are
+/// part of the title.
+fn status_marker(title: &str) -> (String, Option<FeatureStatus>) {
+    let marker = title
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once('['))
+        .map(|(title, value)| (title.trim_end(), FeatureStatus::parse(value)))
+        .filter(|(title, status)| !title.is_empty() && status.is_known());
+    match marker {
+        Some((title, status)) => (title.to_string(), Some(status)),
+        None => (title.to_string(), None),
+    }
+}
+
+/// Split a `(3d)`-style estimate off the end of a feature or task title.
+/// Parentheses holding anything but a valid estimate are part of the
+/// title.
+fn estimate_suffix(title: &str) -> (String, Option<Estimate>) {
+    let suffix = title
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once('('))
+        .map(|(title, value)| (title.trim_end(), Estimate::parse(value)))
+        .filter(|(title, estimate)| !title.is_empty() && estimate.is_valid());
+    match suffix {
+        Some((title, estimate)) => (title.to_string(), Some(estimate)),
+        None => (title.to_string(), None),
+    }
+}
+
+/// Split a `!`, `!!` or `!!!` priority marker, set off by a space, off
+/// the end of a feature title.
+fn priority_marker(title: &str) -> (String, Option<Priority>) {
+    let marker = title
+        .rsplit_once(' ')
+        .and_then(|(title, marker)| Some((title.trim_end(), Priority::from_marker(marker)?)))
+        .filter(|(title, _)| !title.is_empty());
+    match marker {
+        Some((title, priority)) => (title.to_string(), Some(priority)),
+        None => (title.to_string(), None),
+    }
+}
+
+/// The title of a heading below a feature when it is a sub-feature rather
+/// than a task: it matches a `sub_features` pattern or has a `Kind:` line.
+fn sub_feature_title(headings: &Headings, section: &Section) -> Option<String> {
+    if let Some(title) = strip_any(&headings.sub_features, section.level, &section.title) {
+        return Some(title.to_string());
+    }
+    let has_kind = section.blocks.iter().any(|block| match block {
+        Block::Paragraph { lines, .. } => lines.iter().any(|line| {
+            metadata(&line.text)
+                .is_some_and(|(key, value)| key == "kind" && FeatureKind::parse(value).is_some())
+        }),
+        _ => false,
+    });
+    has_kind.then(|| section.title.clone())
+}
+
+fn task_from(doc: &Document, section: Section, warnings: &mut Vec<ParseWarning>) -> Task {
+    let (completed, title) = match section.title.as_str() {
+        t if t.starts_with("[x] ") || t.starts_with("[X] ") => (true, &t[4..]),
+        t if t.starts_with("[ ] ") => (false, &t[4..]),
+        t => (false, t),
+    };
+    let (title, estimate) = estimate_suffix(title.trim());
+    let mut task = Task {
+        title,
+        estimate,
+        completed,
+        span: Some(doc.span(&section.range)),
+        ..Task::default()
+    };
+    let mut description = Description::default();
+    let mut in_tests = false;
+
+    for block in &section.blocks {
+        match block {
+            Block::Paragraph { lines, .. } => {
+                let mut kept = Vec::new();
+                for line in lines {
+                    match metadata(&line.text) {
+                        Some((key, value)) if is_labels(&key) => task.labels = split_list(value),
+                        Some((key, value)) if is_assignees(&key) => {
+                            task.assignees = assignee_list(value)
+                        }
+                        Some((key, value)) if key == "estimate" && !value.is_empty() => {
+                            task.estimate = Some(Estimate::parse(value))
+                        }
+                        Some((key, "")) if key == "tests" => in_tests = true,
+                        _ => {
+                            warnings.extend(misspelt_key(doc, line, TASK_KEYS));
+                            kept.push(description.quote(doc.source, &line.range, &section.links))
+                        }
+                    }
+                }
+                description.push(kept.join("\n"));
+            }
+            Block::List { items, .. } if in_tests => {
+                task.tests.extend(
+                    items
+                        .iter()
+                        .filter(|item| item.depth == 0 && !item.text.is_empty())
+                        .map(|item| item.text.to_string()),
+                );
+            }
+            Block::Code { info, code, range } => {
+                description.push(code_block(doc.source, range, info.as_deref(), code))
+            }
+            other => {
+                if let Some(range) = other.range() {
+                    let part = description.quote(doc.source, range, &section.links);
+                    description.push(part);
+                }
+            }
+        }
+    }
+    // Headings nested below a task are kept as part of its description.
+    for child in &section.children {
+        description.push(raw_section(doc, child));
+    }
+    task.description = description.finish();
+    task
+}
+
+/// The span of the first line of `range`, e.g. a section's heading.
+fn first_line(doc: &Document, range: &Range<usize>) -> Span {
+    let line = doc.source[range.clone()].lines().next().unwrap_or_default();
+    doc.span(&(range.start..range.start + line.len()))
+}
+
+/// Re-emit a section below a task as Markdown.
+fn raw_section(doc: &Document, section: &Section) -> String {
+    let mut parts = vec![format!(
+        "{} {}",
+        "#".repeat(section.level as usize),
+        section.title
+    )];
+    parts.push(raw_blocks(doc.source, &section.blocks));
+    parts.extend(section.children.iter().map(|child| raw_section(doc, child)));
+    parts.retain(|part| !part.is_empty());
+    parts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROADMAP: &str = "\
+# My Test Project
+
+This is the project description.
+It has two lines.
+
+## Milestones
+- **M1: First Milestone** — 2025-10-26
+- **M2: Second Milestone**
+
+## Features
+
+### Feature A
+This is the description for Feature A.
+Milestone: M1: First Milestone
+Labels: backend, core
+
+#### Task A1
+Description for Task A1.
+Labels: db
+Assignees: user1
+
+Tests:
+ - Test case 1 for A1.
+ - Test case 2 for A1.
+
+#### Task A2
+Description for Task A2.
+
+### Feature B
+Description for B.
+Labels: frontend
+";
+
+    #[test]
+    fn parses_python_compatible_roadmap() {
+        let roadmap = parse_roadmap(ROADMAP);
+        assert_eq!(roadmap.name, "My Test Project");
+        assert_eq!(
+            roadmap.description,
+            "This is the project description.\nIt has two lines."
+        );
+        let milestones: Vec<_> = roadmap
+            .milestones
+            .iter()
+            .map(|m| (m.name.as_str(), m.due_date.as_deref()))
+            .collect();
+        assert_eq!(
+            milestones,
+            [
+                ("M1: First Milestone", Some("2025-10-26")),
+                ("M2: Second Milestone", None),
+            ]
+        );
+
+        let a = &roadmap.features[0];
+        assert_eq!(a.title, "Feature A");
+        assert_eq!(a.description, "This is the description for Feature A.");
+        assert_eq!(a.milestone.as_deref(), Some("M1: First Milestone"));
+        assert_eq!(a.labels, ["backend", "core"]);
+        assert_eq!(a.tasks.len(), 2);
+        assert_eq!(a.tasks[0].title, "Task A1");
+        assert_eq!(a.tasks[0].description, "Description for Task A1.");
+        assert_eq!(a.tasks[0].labels, ["db"]);
+        assert_eq!(a.tasks[0].assignees, ["user1"]);
+        assert_eq!(
+            a.tasks[0].tests,
+            ["Test case 1 for A1.", "Test case 2 for A1."]
+        );
+        assert_eq!(a.tasks[1].description, "Description for Task A2.");
+
+        let b = &roadmap.features[1];
+        assert_eq!(b.title, "Feature B");
+        assert_eq!(b.description, "Description for B.");
+        assert_eq!(b.labels, ["frontend"]);
+        assert!(b.tasks.is_empty());
+    }
+
+    #[test]
+    fn strips_inline_formatting_from_titles_but_keeps_it_in_descriptions() {
+        let roadmap = parse_roadmap(
+            "# *Fancy* `Project`\n\n## Features\n\n### **Bold** feature\nUses `code` and [links](x).\n",
+        );
+        assert_eq!(roadmap.name, "Fancy Project");
+        assert_eq!(roadmap.features[0].title, "Bold feature");
+        assert_eq!(
+            roadmap.features[0].description,
+            "Uses `code` and [links](x)."
+        );
+    }
+
+    #[test]
+    fn parses_task_lists_and_milestone_tables() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n\n| Milestone | Due | About |\n|---|---|---|\n| v1 | Jan 1, 2025 | First cut |\n| v2 | | |\n\n\
+             ## Features\n\n### F\nSome text.\n\n**Tasks:**\n- [x] done\n- [ ] todo\n",
+        );
+        assert_eq!(roadmap.milestones.len(), 2);
+        assert_eq!(
+            roadmap.milestones[0].due_date.as_deref(),
+            Some("2025-01-01")
+        );
+        assert_eq!(roadmap.milestones[1].due_date, None);
+        assert_eq!(roadmap.milestones[0].description, "First cut");
+        assert_eq!(roadmap.milestones[1].description, "");
+        let tasks = &roadmap.features[0].tasks;
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks[0].completed);
+        assert_eq!(tasks[1].title, "todo");
+        assert_eq!(roadmap.features[0].description, "Some text.");
+    }
+
+    #[test]
+    fn records_entity_spans() {
+        let roadmap = parse_roadmap(ROADMAP);
+        let lines = |span: Option<Span>| span.map(|s| (s.start.line, s.end.line));
+        assert_eq!(lines(roadmap.span), Some((1, 31)));
+        assert_eq!(lines(roadmap.milestones[1].span), Some((8, 8)));
+        assert_eq!(lines(roadmap.features[0].span), Some((12, 27)));
+        assert_eq!(lines(roadmap.features[0].tasks[1].span), Some((26, 27)));
+
+        let span = roadmap.features[1].span.unwrap();
+        assert_eq!(
+            &ROADMAP[span.start.offset..span.end.offset],
+            "### Feature B\nDescription for B.\nLabels: frontend"
+        );
+        assert_eq!((span.end.line, span.end.column), (31, 17));
+    }
+
+    #[test]
+    fn checklists_anywhere_in_a_feature_become_nested_tasks() {
+        let roadmap = parse_roadmap(
+            "## Features\n\n### F\nIntro.\n\n- [x] Design\n  - [ ] Review\n    - [x] Sign-off\n- [ ] Build\n\nOutro.\n",
+        );
+        let feature = &roadmap.features[0];
+        let tasks: Vec<_> = feature
+            .tasks
+            .iter()
+            .map(|t| (t.title.as_str(), t.completed, t.depth))
+            .collect();
+        assert_eq!(
+            tasks,
+            [
+                ("Design", true, 0),
+                ("Review", false, 1),
+                ("Sign-off", true, 2),
+                ("Build", false, 0),
+            ]
+        );
+        assert_eq!(feature.description, "Intro.\n\nOutro.");
+    }
+
+    #[test]
+    fn resolves_reference_links_in_descriptions() {
+        let source = "# App\n\n## Features\n\n### Login\n\nSee [RFC 42][rfc], [the spec][] and [docs](https://docs.example.com).\nMail <ops@example.com>.\n\n![The *form*][shot]\n\n- [ ] Read [rfc]\n\n[rfc]: https://example.com/rfc/42 \"RFC 42\"\n[the spec]: <https://example.com/a spec>\n[shot]: img/login.png\n";
+        let feature = &parse_roadmap(source).features[0];
+        assert_eq!(
+            feature.description,
+            "See [RFC 42](https://example.com/rfc/42 \"RFC 42\"), [the spec](<https://example.com/a spec>) and [docs](https://docs.example.com).\nMail <ops@example.com>.\n\n![The *form*](img/login.png)"
+        );
+        let links: Vec<_> = feature
+            .links
+            .iter()
+            .map(|link| (link.text.as_str(), link.url.as_str(), link.title.as_deref()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("RFC 42", "https://example.com/rfc/42", Some("RFC 42")),
+                ("the spec", "https://example.com/a spec", None),
+                ("docs", "https://docs.example.com", None),
+                ("ops@example.com", "mailto:ops@example.com", None),
+            ]
+        );
+        assert_eq!(feature.images[0].text, "The form");
+        assert_eq!(feature.images[0].url, "img/login.png");
+        // Links in tasks stay with the tasks.
+        assert_eq!(feature.tasks[0].title, "Read rfc");
+    }
+
+    #[test]
+    fn keeps_code_blocks_with_their_language() {
+        let source = "# App\n\n## Features\n\n### Login\n\n```rust\nfn main() {}\n\n// Milestone: v2\n```\n\n    cargo run\n\n- Steps:\n\n  ```sh\n  make\n  ```\n\n#### Task\n\n   ~~~toml\n   a = 1\n   ~~~\n";
+        let feature = &parse_roadmap(source).features[0];
+        assert_eq!(feature.milestone, None);
+        assert_eq!(
+            feature.description,
+            "```rust\nfn main() {}\n\n// Milestone: v2\n```\n\n```\ncargo run\n```\n\n- Steps:\n\n  ```sh\n  make\n  ```"
+        );
+        assert_eq!(feature.tasks[0].description, "```toml\na = 1\n```");
+    }
+
+    #[test]
+    fn missing_title_leaves_name_empty() {
+        let roadmap = parse_roadmap("Intro text.\n\n## Features\n\n### Only\n");
+        assert_eq!(roadmap.name, "");
+        assert_eq!(roadmap.description, "Intro text.");
+        assert_eq!(roadmap.features[0].title, "Only");
+    }
+
+    #[test]
+    fn streams_entities_as_their_sections_end() {
+        let names: Vec<String> = Entities::new(ROADMAP)
+            .map(|entity| match entity {
+                Entity::Roadmap { name, .. } => format!("roadmap {}", name),
+                Entity::Milestone(milestone) => format!("milestone {}", milestone.name),
+                Entity::Feature(feature) => format!("feature {}", feature.title),
+                Entity::ExtraSection(section) => format!("section {}", section.title),
+                Entity::Warning(warning) => format!("warning {}", warning.message),
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "roadmap My Test Project",
+                "milestone M1: First Milestone",
+                "milestone M2: Second Milestone",
+                "feature Feature A",
+                "feature Feature B",
+            ]
+        );
+    }
+
+    #[test]
+    fn header_comes_last_without_a_title() {
+        let entities: Vec<Entity> = Entities::new("Intro.\n\n## Features\n\n### A\n").collect();
+        assert!(matches!(&entities[0], Entity::Feature(feature) if feature.title == "A"));
+        assert!(matches!(
+            &entities[1],
+            Entity::Roadmap { name, description, .. } if name.is_empty() && description == "Intro."
+        ));
+    }
+
+    #[test]
+    fn frontmatter_overrides_heading_values() {
+        let source = "---\nname: Official\nrepo: acme/app\n---\n# Draft title\n\nAbout.\n\n## Features\n\n### A\n";
+        let roadmap = parse_roadmap(source);
+        assert_eq!(roadmap.name, "Official");
+        assert_eq!(roadmap.description, "About.");
+        assert_eq!(roadmap.metadata["repo"], "acme/app");
+        // Spans still point into the whole file.
+        assert_eq!(roadmap.features[0].span.unwrap().start.line, 11);
+    }
+
+    #[test]
+    fn follows_configured_heading_conventions() {
+        let headings: Headings = toml::from_str(
+            "milestones = [\"Phases\"]\nfeature = [{ prefix = \"Epic:\", level = 4 }]\n",
+        )
+        .unwrap();
+        let source = "# P\n\n## Phases\n- Alpha\n\n## Backend\n\n### Storage\n\n\
+                      #### Epic: Sharding\nLabels: db\n\n##### Split tables\n\n\
+                      #### Not an epic\n";
+        let roadmap = parse_roadmap_with(source, &headings);
+        assert_eq!(roadmap.milestones[0].name, "Alpha");
+        assert_eq!(roadmap.features.len(), 1);
+        assert_eq!(roadmap.features[0].title, "Sharding");
+        assert_eq!(roadmap.features[0].labels, ["db"]);
+        assert_eq!(roadmap.features[0].tasks[0].title, "Split tables");
+    }
+
+    #[test]
+    fn reads_features_from_tables_next_to_headings() {
+        let source = "# P\n\n## Features\n\n\
+                      | Feature | Owner | Target | Notes |\n|---|---|---|---|\n\
+                      | Login [done] | @ana, bo | v1 | quick |\n| Search (3d) | | | |\n| | x | | |\n\n\
+                      | Name | Owner |\n|---|---|\n| Not a feature | ana |\n\n\
+                      ### Billing\nLabels: pay\n";
+        let roadmap = parse_roadmap(source);
+        let titles: Vec<_> = roadmap.features.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, ["Login", "Search", "Billing"]);
+        let login = &roadmap.features[0];
+        assert_eq!(login.assignees, ["ana", "bo"]);
+        assert_eq!(login.milestone.as_deref(), Some("v1"));
+        assert_eq!(login.status, Some(FeatureStatus::Done));
+        assert_eq!(login.span.unwrap().start.line, 7);
+        assert_eq!(roadmap.features[1].estimate, Some(Estimate::parse("3d")));
+        assert!(roadmap.features[1].assignees.is_empty());
+
+        let headings: Headings = toml::from_str("columns = { Name = \"title\" }\n").unwrap();
+        let roadmap = parse_roadmap_with(source, &headings);
+        let titles: Vec<_> = roadmap.features.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, ["Not a feature", "Billing"]);
+    }
+
+    #[test]
+    fn nests_sub_features_below_their_parent() {
+        let headings: Headings = toml::from_str("sub_features = [{ level = 4 }]\n").unwrap();
+        let source = "# P\n\n## Features\n\n### Accounts\n\n\
+                      #### Login\n- [ ] Form\n\n##### Sessions\nKind: task\n\n\
+                      ##### [x] Password reset\n\n#### Billing\n";
+        let roadmap = parse_roadmap_with(source, &headings);
+        let accounts = &roadmap.features[0];
+        assert_eq!(roadmap.features.len(), 1);
+        assert_eq!(accounts.kind, FeatureKind::Epic);
+        let children: Vec<(&str, FeatureKind)> = accounts
+            .children
+            .iter()
+            .map(|child| (child.title.as_str(), child.kind))
+            .collect();
+        assert_eq!(
+            children,
+            [
+                ("Login", FeatureKind::Epic),
+                ("Billing", FeatureKind::Feature)
+            ]
+        );
+        let login = &accounts.children[0];
+        assert_eq!(login.tasks.len(), 2);
+        assert_eq!(login.children[0].title, "Sessions");
+        assert_eq!(login.children[0].kind, FeatureKind::Task);
+
+        // Without the pattern, only the `Kind:` line makes a sub-feature.
+        let roadmap = parse_roadmap(source);
+        assert_eq!(roadmap.features[0].kind, FeatureKind::Feature);
+        assert_eq!(roadmap.features[0].tasks.len(), 2);
+    }
+
+    #[test]
+    fn reads_status_from_heading_marker_or_status_line() {
+        let source = "# P\n\n## Features\n\n### Login [In Progress]\n\n\
+                      ### Search\nStatus: blocked\n\n### Sync [beta]\nStatus: paused\n\n### Docs\n";
+        let roadmap = parse_roadmap(source);
+        let statuses: Vec<(&str, Option<FeatureStatus>)> = roadmap
+            .features
+            .iter()
+            .map(|f| (f.title.as_str(), f.status.clone()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("Login", Some(FeatureStatus::InProgress)),
+                ("Search", Some(FeatureStatus::Blocked)),
+                ("Sync [beta]", Some(FeatureStatus::Other("paused".into()))),
+                ("Docs", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_features_of_deferred_sections_as_deferred() {
+        let source = "# P\n\n## Features\n\n### Login\n\n## Icebox\n\n\
+                      | Feature | Owner |\n|---|---|\n| Plugins | ana |\n\n### Themes [planned]\n\n\
+                      ## Deferred until v2\n\n### Sync\n\n## Notes\n\n### Not a feature\n";
+        let roadmap = parse_roadmap(source);
+        let statuses: Vec<(&str, Option<FeatureStatus>)> = roadmap
+            .features
+            .iter()
+            .map(|f| (f.title.as_str(), f.status.clone()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("Login", None),
+                ("Plugins", Some(FeatureStatus::Deferred)),
+                ("Themes", Some(FeatureStatus::Deferred)),
+                ("Sync", Some(FeatureStatus::Deferred)),
+            ]
+        );
+        assert!(roadmap
+            .warnings
+            .iter()
+            .all(|w| !w.message.contains("Icebox")));
+        assert_eq!(roadmap.extra_sections.len(), 1);
+    }
+
+    #[test]
+    fn reads_issue_links_after_feature_headings() {
+        let source = "# P\n\n## Features\n\n\
+                      ### Login (2d) [done] [#12](https://github.com/o/r/issues/12)\n\n\
+                      ### Search [#3]\n";
+        let roadmap = parse_roadmap(source);
+        let login = &roadmap.features[0];
+        assert_eq!(login.title, "Login");
+        assert_eq!(login.status, Some(FeatureStatus::Done));
+        assert_eq!(
+            login.issue,
+            Some(IssueLink {
+                number: 12,
+                url: "https://github.com/o/r/issues/12".into()
+            })
+        );
+        assert_eq!(roadmap.features[1].title, "Search [#3]");
+        assert_eq!(roadmap.features[1].issue, None);
+    }
+
+    #[test]
+    fn reads_estimates_from_suffixes_and_estimate_lines() {
+        let source = "# P\n\n## Features\n\n### Login (5pts) [done]\n\n- [ ] Form (4h)\n\
+                      - [ ] Tokens (later)\n\n### Search\nEstimate: 2 weeks\n\n#### Index\nEstimate: 3d\n\n\
+                      ### Export [done] (3d)\n";
+        let roadmap = parse_roadmap(source);
+        let login = &roadmap.features[0];
+        assert_eq!(login.title, "Login");
+        assert_eq!(login.estimate, Some(Estimate::Points(5.0)));
+        assert_eq!(login.tasks[0].title, "Form");
+        assert_eq!(login.tasks[0].estimate, Some(Estimate::Days(0.5)));
+        assert_eq!(login.tasks[1].title, "Tokens (later)");
+        assert_eq!(login.tasks[1].estimate, None);
+        let search = &roadmap.features[1];
+        assert_eq!(search.estimate, Some(Estimate::Days(10.0)));
+        assert_eq!(search.tasks[0].estimate, Some(Estimate::Days(3.0)));
+        // An estimate after the status marker does not hide the status.
+        let export = &roadmap.features[2];
+        assert_eq!(export.title, "Export");
+        assert_eq!(export.status, Some(FeatureStatus::Done));
+        assert_eq!(export.estimate, Some(Estimate::Days(3.0)));
+    }
+
+    #[test]
+    fn strips_title_markers_in_any_order() {
+        let orders = [
+            "[in-progress] (2d) !!",
+            "[in-progress] !! (2d)",
+            "(2d) [in-progress] !!",
+            "(2d) !! [in-progress]",
+            "!! [in-progress] (2d)",
+            "!! (2d) [in-progress]",
+        ];
+        for markers in orders {
+            let source = format!(
+                "# P\n\n## Features\n\n### Login {}\n\n\
+                 | Feature |\n|---|\n| Search {} |\n",
+                markers, markers
+            );
+            let roadmap = parse_roadmap(&source);
+            for (feature, title) in roadmap.features.iter().zip(["Login", "Search"]) {
+                assert_eq!(feature.title, title, "{}", markers);
+                assert_eq!(feature.status, Some(FeatureStatus::InProgress));
+                assert_eq!(feature.estimate, Some(Estimate::Days(2.0)));
+                assert_eq!(feature.priority, Some(Priority::P1));
+            }
+        }
+        // Markers of one kind are taken once.
+        let roadmap = parse_roadmap("# P\n\n## Features\n\n### Login [todo] [done]\n");
+        assert_eq!(roadmap.features[0].title, "Login [todo]");
+        assert_eq!(roadmap.features[0].status, Some(FeatureStatus::Done));
+    }
+
+    #[test]
+    fn parses_label_and_assignee_metadata() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Features\n\n### A\n**Labels:** backend, p1, backend\nAssignees: @alice, @bob\nRepo: acme/api\n\n\
+             #### T\nLabel: db\nAssignee: @carol\n",
+        );
+        let feature = &roadmap.features[0];
+        assert_eq!(feature.labels, ["backend", "p1"]);
+        assert_eq!(feature.assignees, ["alice", "bob"]);
+        assert_eq!(feature.repo.as_deref(), Some("acme/api"));
+        assert_eq!(feature.tasks[0].labels, ["db"]);
+        assert_eq!(feature.tasks[0].assignees, ["carol"]);
+        assert_eq!(feature.description, "");
+    }
+
+    #[test]
+    fn captures_configured_custom_fields() {
+        let source = "# P\n\n## Features\n\n| Feature | Team |\n|---|---|\n| A | web |\n\n\
+                      ### B\nSign in.\n**Area:** Auth\nTeam: infra\nNote: soon\n";
+        let headings = Headings {
+            fields: vec!["Area".into(), "team".into()],
+            ..Headings::default()
+        };
+        let roadmap = parse_roadmap_with(source, &headings);
+        let fields = |feature: &Feature| -> Vec<(String, String)> {
+            feature.fields.clone().into_iter().collect()
+        };
+        assert_eq!(
+            fields(&roadmap.features[0]),
+            [("team".to_string(), "web".to_string())]
+        );
+        assert_eq!(
+            fields(&roadmap.features[1]),
+            [
+                ("Area".to_string(), "Auth".to_string()),
+                ("team".to_string(), "infra".to_string())
+            ]
+        );
+        assert_eq!(roadmap.features[1].description, "Sign in.\nNote: soon");
+        assert!(parse_roadmap(source).features[1].fields.is_empty());
+    }
+
+    #[test]
+    fn warns_about_anomalies_it_recovers_from() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Background\n\nWhy.\n\n## Features\n\n### A\nAsignees: @alice\nNote: keep\n\n\
+             #### T\nLables: db\n\n#### t\n\n## Features\n",
+        );
+        let warnings: Vec<(WarningKind, usize)> = roadmap
+            .warnings
+            .iter()
+            .map(|w| (w.kind, w.span.unwrap().start.line))
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                (WarningKind::UnknownSection, 3),
+                (WarningKind::DuplicateHeading, 16),
+                (WarningKind::MetadataTypo, 10),
+                (WarningKind::MetadataTypo, 14),
+                (WarningKind::DuplicateHeading, 18),
+            ]
+        );
+        assert_eq!(
+            roadmap.warnings[2].message,
+            "'Asignees:' looks like a misspelling of 'Assignees:'; the line is kept in the description"
+        );
+        let feature = &roadmap.features[0];
+        assert_eq!(feature.description, "Asignees: @alice\nNote: keep");
+        assert!(feature.assignees.is_empty());
+        assert!(matches!(
+            roadmap.deny_warnings(),
+            Err(crate::ParseError::Strict(warnings)) if warnings.len() == 5
+        ));
+        assert!(parse_roadmap(ROADMAP).warnings.is_empty());
+    }
+}
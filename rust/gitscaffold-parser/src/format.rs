@@ -0,0 +1,73 @@
+//! Input format selection and auto-detection.
+
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum InputFormat {
+    /// Pick the format from the file extension, sniffing the content when
+    /// the extension is unknown
+    Auto,
+    /// Heading-based Markdown roadmap
+    Md,
+    /// Structured YAML (or JSON) roadmap
+    Yaml,
+}
+
+impl InputFormat {
+    /// Resolve `Auto` to a concrete format for `path` with `content`.
+    pub fn resolve(self, path: &str, content: &str) -> InputFormat {
+        if self != InputFormat::Auto {
+            return self;
+        }
+        let extension = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("md" | "markdown") => InputFormat::Md,
+            Some("yml" | "yaml" | "json") => InputFormat::Yaml,
+            _ if looks_like_yaml(content) => InputFormat::Yaml,
+            _ => InputFormat::Md,
+        }
+    }
+}
+
+/// A document is treated as a YAML roadmap when it parses as a mapping with
+/// at least one of the top-level roadmap keys.
+fn looks_like_yaml(content: &str) -> bool {
+    match serde_yaml::from_str::<serde_yaml::Value>(content) {
+        Ok(serde_yaml::Value::Mapping(map)) => ["name", "milestones", "features"]
+            .iter()
+            .any(|key| map.contains_key(*key)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_wins_over_content() {
+        let yaml = "name: P\nfeatures: []\n";
+        assert_eq!(InputFormat::Auto.resolve("r.md", yaml), InputFormat::Md);
+        assert_eq!(InputFormat::Auto.resolve("r.YML", "# P"), InputFormat::Yaml);
+        assert_eq!(InputFormat::Md.resolve("r.yaml", yaml), InputFormat::Md);
+    }
+
+    #[test]
+    fn sniffs_content_without_known_extension() {
+        assert_eq!(
+            InputFormat::Auto.resolve("ROADMAP", "name: P\nfeatures: []\n"),
+            InputFormat::Yaml
+        );
+        assert_eq!(
+            InputFormat::Auto.resolve("ROADMAP", "# P\n\n## Features\n"),
+            InputFormat::Md
+        );
+        assert_eq!(
+            InputFormat::Auto.resolve("notes.txt", "Status: draft\n"),
+            InputFormat::Md
+        );
+    }
+}
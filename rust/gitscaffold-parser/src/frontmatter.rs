@@ -0,0 +1,51 @@
+//! YAML frontmatter at the top of Markdown roadmaps.
+
+use crate::roadmap::Metadata;
+
+/// Split a leading `---` delimited YAML block off `source`.
+///
+/// Returns the parsed metadata and the byte offset where the Markdown body
+/// starts. A block that is not a YAML mapping is left alone, since a leading
+/// `---` is also a valid thematic break.
+pub(crate) fn split(source: &str) -> Option<(Metadata, usize)> {
+    let (opening, _) = source.split_once('\n')?;
+    if opening.trim_end() != "---" {
+        return None;
+    }
+    let start = opening.len() + 1;
+    let mut end = start;
+    for line in source[start..].split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            let yaml = &source[start..end];
+            let metadata = if yaml.trim().is_empty() {
+                Metadata::new()
+            } else {
+                serde_yaml::from_str(yaml).ok()?
+            };
+            return Some((metadata, end + line.len()));
+        }
+        end += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_leading_yaml_mapping() {
+        let source = "---\nrepo: acme/app\nlabels: [a, b]\n---\n# Title\n";
+        let (metadata, body) = split(source).unwrap();
+        assert_eq!(&source[body..], "# Title\n");
+        assert_eq!(metadata["repo"], "acme/app");
+        assert_eq!(metadata["labels"], serde_json::json!(["a", "b"]));
+    }
+
+    #[test]
+    fn leaves_thematic_breaks_alone() {
+        assert!(split("# Title\n---\nname: x\n---\n").is_none());
+        assert!(split("---\nJust some text.\n---\n").is_none());
+        assert!(split("---\nname: unterminated\n").is_none());
+    }
+}
@@ -0,0 +1,444 @@
+//! Gantt charts of a roadmap's schedule, as Mermaid or SVG.
+//!
+//! Milestones are placed by due date, earliest first, and each milestone's
+//! features start at the previous milestone's due date (or the chart's
+//! start) and run for their estimate in days or, without one, up to their
+//! own milestone's. Sub-features are listed after their parent with
+//! its title as a prefix. Features whose milestone has no parseable due
+//! date cannot be placed and are reported as unscheduled.
+
+use std::fmt::Write;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+use crate::roadmap::{Estimate, Feature, FeatureStatus, Roadmap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum GanttFormat {
+    /// Mermaid `gantt` diagram
+    Mermaid,
+    /// Standalone SVG image
+    Svg,
+}
+
+/// The time unit of the chart's axis and grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Bucket {
+    /// Weeks starting on Monday
+    Week,
+    /// Calendar months
+    Month,
+}
+
+impl Bucket {
+    /// The start of the bucket holding `date`.
+    fn floor(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Bucket::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Bucket::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// The start of the next bucket.
+    fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Bucket::Week => start + Days::new(7),
+            Bucket::Month => start + Months::new(1),
+        }
+    }
+
+    fn label(self, start: NaiveDate) -> String {
+        match self {
+            Bucket::Week => start.format("%b %d").to_string(),
+            Bucket::Month => start.format("%b %Y").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GanttOptions {
+    /// Where the first milestone's features start.
+    pub start: NaiveDate,
+    pub bucket: Bucket,
+    /// Leave out features that are done, and milestones left empty.
+    pub exclude_done: bool,
+}
+
+/// One feature's bar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bar {
+    pub title: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub status: Option<FeatureStatus>,
+    pub done: bool,
+}
+
+/// A milestone and the bars of its features.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub milestone: String,
+    pub due: NaiveDate,
+    pub bars: Vec<Bar>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub sections: Vec<Section>,
+    /// Titles of the features that could not be placed.
+    pub unscheduled: Vec<String>,
+}
+
+/// Place the roadmap's features on a timeline.
+pub fn schedule(roadmap: &Roadmap, options: &GanttOptions) -> Schedule {
+    let mut dated: Vec<(&str, NaiveDate)> = roadmap
+        .milestones
+        .iter()
+        .filter_map(|m| {
+            let due = m.due_date.as_deref()?;
+            let due = NaiveDate::parse_from_str(due, "%Y-%m-%d").ok()?;
+            Some((m.name.as_str(), due))
+        })
+        .collect();
+    dated.sort_by_key(|&(_, due)| due);
+
+    let mut features = Vec::new();
+    for feature in &roadmap.features {
+        flatten(feature, "", None, &mut features);
+    }
+
+    let mut sections = Vec::new();
+    let mut unscheduled = Vec::new();
+    let mut start = options.start;
+    for &(name, due) in &dated {
+        let bars: Vec<Bar> = features
+            .iter()
+            .filter(|(_, feature, milestone)| {
+                milestone.is_some_and(|m| m.trim().eq_ignore_ascii_case(name.trim()))
+                    && !(options.exclude_done && feature.is_done())
+            })
+            .map(|(title, feature, _)| Bar {
+                title: title.clone(),
+                start: start.min(due),
+                end: end(feature, start.min(due), due),
+                status: feature.status.clone(),
+                done: feature.is_done(),
+            })
+            .collect();
+        start = start.max(due);
+        if bars.is_empty() && options.exclude_done {
+            continue;
+        }
+        sections.push(Section {
+            milestone: name.to_string(),
+            due,
+            bars,
+        });
+    }
+    for (title, feature, milestone) in &features {
+        if options.exclude_done && feature.is_done() {
+            continue;
+        }
+        let placed = milestone.is_some_and(|m| {
+            dated
+                .iter()
+                .any(|(name, _)| m.trim().eq_ignore_ascii_case(name.trim()))
+        });
+        if !placed {
+            unscheduled.push(title.clone());
+        }
+    }
+    Schedule {
+        sections,
+        unscheduled,
+    }
+}
+
+/// Where a feature's bar ends: after its estimate in days when it has one,
+/// otherwise at its milestone's due date.
+fn end(feature: &Feature, start: NaiveDate, due: NaiveDate) -> NaiveDate {
+    match feature.estimate {
+        Some(Estimate::Days(days)) => start + Days::new(days.ceil().max(1.0) as u64),
+        _ => due,
+    }
+}
+
+/// The feature and its sub-features, with prefixed titles and the
+/// milestone each falls under.
+fn flatten<'a>(
+    feature: &'a Feature,
+    parent: &str,
+    inherited: Option<&'a str>,
+    out: &mut Vec<(String, &'a Feature, Option<&'a str>)>,
+) {
+    let title = if parent.is_empty() {
+        feature.title.clone()
+    } else {
+        format!("{} / {}", parent, feature.title)
+    };
+    let milestone = feature.milestone.as_deref().or(inherited);
+    out.push((title.clone(), feature, milestone));
+    for child in &feature.children {
+        flatten(child, &title, milestone, out);
+    }
+}
+
+/// Render the schedule in the given format.
+pub fn render(
+    roadmap: &Roadmap,
+    schedule: &Schedule,
+    options: &GanttOptions,
+    format: GanttFormat,
+) -> String {
+    match format {
+        GanttFormat::Mermaid => render_mermaid(roadmap, schedule, options.bucket),
+        GanttFormat::Svg => render_svg(roadmap, schedule, options.bucket),
+    }
+}
+
+/// Mermaid takes everything up to a `:` as the task name, and `;` ends a
+/// statement.
+fn mermaid_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ':' => out.push_str("#58;"),
+            ';' => out.push_str("#59;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Writing to a `String` cannot fail, hence the ignored results below.
+fn render_mermaid(roadmap: &Roadmap, schedule: &Schedule, bucket: Bucket) -> String {
+    let mut out = String::from("gantt\n    dateFormat YYYY-MM-DD\n");
+    let (axis, tick) = match bucket {
+        Bucket::Week => ("%b %d", "1week"),
+        Bucket::Month => ("%b %Y", "1month"),
+    };
+    let _ = writeln!(out, "    axisFormat {}\n    tickInterval {}", axis, tick);
+    if bucket == Bucket::Week {
+        out.push_str("    weekday monday\n");
+    }
+    if !roadmap.name.is_empty() {
+        let _ = writeln!(out, "    title {}", mermaid_text(&roadmap.name));
+    }
+    let mut id = 0;
+    for section in &schedule.sections {
+        let _ = writeln!(out, "    section {}", mermaid_text(&section.milestone));
+        for bar in &section.bars {
+            id += 1;
+            let tag = if bar.done {
+                "done, "
+            } else {
+                match bar.status {
+                    Some(FeatureStatus::InProgress) => "active, ",
+                    Some(FeatureStatus::Blocked) => "crit, ",
+                    _ => "",
+                }
+            };
+            let _ = writeln!(
+                out,
+                "    {} :{}f{}, {}, {}",
+                mermaid_text(&bar.title),
+                tag,
+                id,
+                bar.start,
+                bar.end
+            );
+        }
+        let _ = writeln!(
+            out,
+            "    {} :milestone, {}, 0d",
+            mermaid_text(&section.milestone),
+            section.due
+        );
+    }
+    out
+}
+
+const LABEL_WIDTH: i64 = 240;
+const ROW_HEIGHT: i64 = 24;
+const HEADER_HEIGHT: i64 = 32;
+const BUCKET_WIDTH: i64 = 96;
+/// Room right of the grid for the last milestone's marker.
+const MARGIN: i64 = 8;
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn bar_color(bar: &Bar) -> &'static str {
+    if bar.done {
+        return "#9e9e9e";
+    }
+    match bar.status {
+        Some(FeatureStatus::InProgress) => "#2f81f7",
+        Some(FeatureStatus::Blocked) => "#d73a49",
+        _ => "#6f9bd1",
+    }
+}
+
+fn render_svg(roadmap: &Roadmap, schedule: &Schedule, bucket: Bucket) -> String {
+    let dates = schedule
+        .sections
+        .iter()
+        .flat_map(|s| s.bars.iter().map(|b| b.start).chain([s.due]));
+    let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) else {
+        return String::from(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"0\" height=\"0\"></svg>\n",
+        );
+    };
+    let mut buckets = vec![bucket.floor(first)];
+    while let Some(&start) = buckets.last().filter(|&&start| start <= last) {
+        buckets.push(bucket.next(start));
+    }
+    // `buckets` ends with the boundary after the last date.
+    let origin = buckets[0];
+    let end = buckets[buckets.len() - 1];
+    let days = (end - origin).num_days().max(1);
+    let columns = buckets.len() as i64 - 1;
+    let timeline = columns * BUCKET_WIDTH;
+    let x = |date: NaiveDate| LABEL_WIDTH + (date - origin).num_days() * timeline / days;
+    let rows: i64 = schedule
+        .sections
+        .iter()
+        .map(|s| 1 + s.bars.len() as i64)
+        .sum();
+    let width = LABEL_WIDTH + timeline + MARGIN;
+    let height = HEADER_HEIGHT + rows * ROW_HEIGHT;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"12\">",
+        w = width,
+        h = height
+    );
+    if !roadmap.name.is_empty() {
+        let _ = writeln!(out, "  <title>{}</title>", xml_escape(&roadmap.name));
+    }
+    let _ = writeln!(
+        out,
+        "  <rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>",
+        width, height
+    );
+    for pair in buckets.windows(2) {
+        let left = x(pair[0]);
+        let _ = writeln!(
+            out,
+            "  <line x1=\"{x}\" y1=\"0\" x2=\"{x}\" y2=\"{h}\" stroke=\"#e1e4e8\"/>",
+            x = left,
+            h = height
+        );
+        let _ = writeln!(
+            out,
+            "  <text x=\"{}\" y=\"20\" fill=\"#57606a\">{}</text>",
+            left + 4,
+            bucket.label(pair[0])
+        );
+    }
+    let mut y = HEADER_HEIGHT;
+    for section in &schedule.sections {
+        let _ = writeln!(
+            out,
+            "  <text x=\"8\" y=\"{}\" font-weight=\"bold\">{}</text>",
+            y + 16,
+            xml_escape(&section.milestone)
+        );
+        let (cx, cy) = (x(section.due), y + ROW_HEIGHT / 2);
+        let _ = writeln!(
+            out,
+            "  <path d=\"M{} {} l6 6 l-6 6 l-6 -6 z\" fill=\"#24292f\"><title>{} ({})</title></path>",
+            cx,
+            cy - 6,
+            xml_escape(&section.milestone),
+            section.due
+        );
+        y += ROW_HEIGHT;
+        for bar in &section.bars {
+            let _ = writeln!(
+                out,
+                "  <text x=\"20\" y=\"{}\">{}</text>",
+                y + 16,
+                xml_escape(&bar.title)
+            );
+            let left = x(bar.start);
+            let _ = writeln!(
+                out,
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"{}\"><title>{}: {} to {}</title></rect>",
+                left,
+                y + 4,
+                (x(bar.end) - left).max(2),
+                ROW_HEIGHT - 8,
+                bar_color(bar),
+                xml_escape(&bar.title),
+                bar.start,
+                bar.end
+            );
+            y += ROW_HEIGHT;
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "# Plan: 2025\n\n## Milestones\n- Beta — 2025-03-31\n- Alpha — 2025-01-31\n- Later\n\n\
+        ## Features\n\n### Login (5d) [done]\nMilestone: Alpha\n\n### Search: full text [in-progress]\nMilestone: Beta\n\n\
+        ### Export\nMilestone: Later\n";
+
+    fn options(exclude_done: bool) -> GanttOptions {
+        GanttOptions {
+            start: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            bucket: Bucket::Month,
+            exclude_done,
+        }
+    }
+
+    #[test]
+    fn renders_mermaid_sections_in_due_date_order() {
+        let roadmap = parse_roadmap(ROADMAP);
+        let schedule = schedule(&roadmap, &options(false));
+        assert_eq!(schedule.unscheduled, ["Export"]);
+        assert_eq!(
+            render(&roadmap, &schedule, &options(false), GanttFormat::Mermaid),
+            "\
+gantt
+    dateFormat YYYY-MM-DD
+    axisFormat %b %Y
+    tickInterval 1month
+    title Plan#58; 2025
+    section Alpha
+    Login :done, f1, 2025-01-01, 2025-01-06
+    Alpha :milestone, 2025-01-31, 0d
+    section Beta
+    Search#58; full text :active, f2, 2025-01-31, 2025-03-31
+    Beta :milestone, 2025-03-31, 0d
+"
+        );
+    }
+
+    #[test]
+    fn excludes_done_features_and_buckets_the_svg_grid() {
+        let roadmap = parse_roadmap(ROADMAP);
+        let schedule = schedule(&roadmap, &options(true));
+        assert_eq!(schedule.sections.len(), 1);
+        assert_eq!(schedule.sections[0].milestone, "Beta");
+        let svg = render(&roadmap, &schedule, &options(true), GanttFormat::Svg);
+        // Bars span January to March, so the grid has three month columns.
+        assert_eq!(svg.matches("<line ").count(), 3);
+        assert!(svg.contains(">Mar 2025</text>"));
+        assert!(svg.contains(">Search: full text</text>"));
+    }
+}
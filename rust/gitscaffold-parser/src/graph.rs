@@ -0,0 +1,401 @@
+//! Dependencies between features, and the milestones features belong to.
+//!
+//! Features name the features they depend on by title, with a
+//! `Depends on: #Other feature` line in Markdown or a `depends_on` list in
+//! YAML, and their milestone by name. [`resolve`] turns those titles into
+//! [`FeatureId`]s and the names into [`MilestoneId`]s; the parsers call it,
+//! so every parsed roadmap carries the resolved graph, which [`render`]
+//! draws as a Graphviz or Mermaid diagram.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::roadmap::{Feature, FeatureId, Milestone, MilestoneId, Roadmap};
+use crate::text::title_key as key;
+
+fn index(roadmap: &Roadmap) -> HashMap<String, FeatureId> {
+    let mut ids = HashMap::new();
+    for (id, feature) in roadmap.features.iter().enumerate() {
+        ids.entry(key(&feature.title)).or_insert(id);
+    }
+    ids
+}
+
+fn milestone_index(roadmap: &Roadmap) -> HashMap<String, MilestoneId> {
+    let mut ids = HashMap::new();
+    for (id, milestone) in roadmap.milestones.iter().enumerate() {
+        ids.entry(key(&milestone.name)).or_insert(id);
+    }
+    ids
+}
+
+/// Fill in every feature's `dependencies` from its `depends_on` titles, and
+/// the `milestone_id` of every feature and sub-feature from its milestone
+/// name (both case-insensitively). References to unknown features and
+/// milestones are skipped; see [`unresolved`] and [`unresolved_milestones`].
+pub fn resolve(roadmap: &mut Roadmap) {
+    fn resolve_milestones(features: &mut [Feature], ids: &HashMap<String, MilestoneId>) {
+        for feature in features {
+            feature.milestone_id = feature
+                .milestone
+                .as_deref()
+                .and_then(|name| ids.get(&key(name)).copied());
+            resolve_milestones(&mut feature.children, ids);
+        }
+    }
+    let milestones = milestone_index(roadmap);
+    resolve_milestones(&mut roadmap.features, &milestones);
+    let ids = index(roadmap);
+    for feature in &mut roadmap.features {
+        feature.dependencies = Vec::new();
+        for reference in &feature.depends_on {
+            if let Some(&id) = ids.get(&key(reference)) {
+                if !feature.dependencies.contains(&id) {
+                    feature.dependencies.push(id);
+                }
+            }
+        }
+    }
+}
+
+/// The features and sub-features whose milestone names no milestone.
+pub fn unresolved_milestones(roadmap: &Roadmap) -> Vec<&Feature> {
+    fn collect<'a>(
+        features: &'a [Feature],
+        ids: &HashMap<String, MilestoneId>,
+        found: &mut Vec<&'a Feature>,
+    ) {
+        for feature in features {
+            if let Some(name) = &feature.milestone {
+                if !ids.contains_key(&key(name)) {
+                    found.push(feature);
+                }
+            }
+            collect(&feature.children, ids, found);
+        }
+    }
+    let mut found = Vec::new();
+    collect(&roadmap.features, &milestone_index(roadmap), &mut found);
+    found
+}
+
+/// Add a milestone without a due date for each milestone name features
+/// use that no milestone has, in order of first use, and resolve the
+/// features' references to them. Returns the names added.
+pub fn add_missing_milestones(roadmap: &mut Roadmap) -> Vec<String> {
+    let mut added: Vec<String> = Vec::new();
+    for feature in unresolved_milestones(roadmap) {
+        let name = feature.milestone.as_deref().unwrap_or_default().trim();
+        if !added.iter().any(|other| key(other) == key(name)) {
+            added.push(name.to_string());
+        }
+    }
+    roadmap
+        .milestones
+        .extend(added.iter().map(|name| Milestone {
+            name: name.clone(),
+            ..Milestone::default()
+        }));
+    resolve(roadmap);
+    added
+}
+
+/// `depends_on` references that name no feature, with the feature making
+/// them.
+pub fn unresolved(roadmap: &Roadmap) -> Vec<(FeatureId, &str)> {
+    let ids = index(roadmap);
+    roadmap
+        .features
+        .iter()
+        .enumerate()
+        .flat_map(|(id, feature)| {
+            feature
+                .depends_on
+                .iter()
+                .filter(|reference| !ids.contains_key(&key(reference)))
+                .map(move |reference| (id, reference.as_str()))
+        })
+        .collect()
+}
+
+/// Features ordered so that each comes after all of its dependencies, or
+/// the features left over when the graph has a cycle.
+pub fn topological_order(roadmap: &Roadmap) -> Result<Vec<FeatureId>, Vec<FeatureId>> {
+    let features = &roadmap.features;
+    let mut pending: Vec<usize> = features.iter().map(|f| f.dependencies.len()).collect();
+    let mut dependents: Vec<Vec<FeatureId>> = vec![Vec::new(); features.len()];
+    for (id, feature) in features.iter().enumerate() {
+        for &dependency in &feature.dependencies {
+            dependents[dependency].push(id);
+        }
+    }
+    let mut order: Vec<FeatureId> = (0..features.len()).filter(|&id| pending[id] == 0).collect();
+    let mut next = 0;
+    while let Some(&id) = order.get(next) {
+        next += 1;
+        for &dependent in &dependents[id] {
+            pending[dependent] -= 1;
+            if pending[dependent] == 0 {
+                order.push(dependent);
+            }
+        }
+    }
+    if order.len() == features.len() {
+        Ok(order)
+    } else {
+        Err((0..features.len()).filter(|&id| pending[id] > 0).collect())
+    }
+}
+
+/// Dependency cycles, each as the features along it starting with the
+/// lowest id. Every strongly connected group of features is covered by at
+/// least one reported cycle.
+pub fn cycles(roadmap: &Roadmap) -> Vec<Vec<FeatureId>> {
+    let Err(stuck) = topological_order(roadmap) else {
+        return Vec::new();
+    };
+    // Every stuck feature has a stuck dependency, so following those from
+    // any stuck feature must run into a cycle.
+    let is_stuck = |id: &FeatureId| stuck.binary_search(id).is_ok();
+    let mut visited = vec![false; roadmap.features.len()];
+    let mut cycles = Vec::new();
+    for &start in &stuck {
+        let mut path = Vec::new();
+        let mut id = start;
+        while !visited[id] {
+            visited[id] = true;
+            path.push(id);
+            id = match roadmap.features[id]
+                .dependencies
+                .iter()
+                .find(|d| is_stuck(d))
+            {
+                Some(&dependency) => dependency,
+                None => break,
+            };
+        }
+        if let Some(at) = path.iter().position(|&p| p == id) {
+            let mut cycle = path.split_off(at);
+            let lowest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+            cycle.rotate_left(lowest);
+            cycles.push(cycle);
+        }
+    }
+    cycles
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Diagram {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+/// Features grouped by milestone: the roadmap's milestones in order, then
+/// milestones only named by features, then (under `None`) features without
+/// a milestone.
+fn groups(roadmap: &Roadmap) -> Vec<(Option<&str>, Vec<FeatureId>)> {
+    let mut groups: Vec<(Option<&str>, Vec<FeatureId>)> = roadmap
+        .milestones
+        .iter()
+        .map(|m| (Some(m.name.as_str()), Vec::new()))
+        .collect();
+    let mut ungrouped = Vec::new();
+    for (id, feature) in roadmap.features.iter().enumerate() {
+        let Some(milestone) = feature.milestone.as_deref() else {
+            ungrouped.push(id);
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == Some(milestone)) {
+            Some((_, members)) => members.push(id),
+            None => groups.push((Some(milestone), vec![id])),
+        }
+    }
+    groups.retain(|(_, members)| !members.is_empty());
+    if !ungrouped.is_empty() {
+        groups.push((None, ungrouped));
+    }
+    groups
+}
+
+/// Draw the dependency graph, with an edge from each dependency to the
+/// features that depend on it and one subgraph per milestone.
+pub fn render(roadmap: &Roadmap, diagram: Diagram) -> String {
+    match diagram {
+        Diagram::Dot => render_dot(roadmap),
+        Diagram::Mermaid => render_mermaid(roadmap),
+    }
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Writing to a `String` cannot fail, hence the ignored results below.
+fn render_dot(roadmap: &Roadmap) -> String {
+    let mut out = String::from("digraph roadmap {\n    rankdir=LR;\n    node [shape=box];\n");
+    if !roadmap.name.is_empty() {
+        let _ = writeln!(out, "    label={};", dot_quote(&roadmap.name));
+    }
+    for (index, (milestone, members)) in groups(roadmap).into_iter().enumerate() {
+        let indent = if milestone.is_some() {
+            "        "
+        } else {
+            "    "
+        };
+        if let Some(name) = milestone {
+            let _ = writeln!(out, "    subgraph cluster_{} {{", index);
+            let _ = writeln!(out, "        label={};", dot_quote(name));
+        }
+        for id in members {
+            let title = &roadmap.features[id].title;
+            let _ = writeln!(out, "{}f{} [label={}];", indent, id, dot_quote(title));
+        }
+        if milestone.is_some() {
+            out.push_str("    }\n");
+        }
+    }
+    for (id, feature) in roadmap.features.iter().enumerate() {
+        for dependency in &feature.dependencies {
+            let _ = writeln!(out, "    f{} -> f{};", dependency, id);
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn mermaid_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
+
+fn render_mermaid(roadmap: &Roadmap) -> String {
+    let mut out = String::from("flowchart LR\n");
+    for (index, (milestone, members)) in groups(roadmap).into_iter().enumerate() {
+        let indent = if milestone.is_some() {
+            "        "
+        } else {
+            "    "
+        };
+        if let Some(name) = milestone {
+            let _ = writeln!(out, "    subgraph m{}[{}]", index, mermaid_quote(name));
+        }
+        for id in members {
+            let title = &roadmap.features[id].title;
+            let _ = writeln!(out, "{}f{}[{}]", indent, id, mermaid_quote(title));
+        }
+        if milestone.is_some() {
+            out.push_str("    end\n");
+        }
+    }
+    for (id, feature) in roadmap.features.iter().enumerate() {
+        for dependency in &feature.dependencies {
+            let _ = writeln!(out, "    f{} --> f{}", dependency, id);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "\
+# P
+
+## Features
+
+### Schema
+### API
+Depends on: #Schema
+### UI
+Depends on: #API, #Schema, Search
+### Loop A
+Depends on: Loop B
+### Loop B
+Depends on: #loop a
+";
+
+    #[test]
+    fn resolves_references_by_title() {
+        let roadmap = parse_roadmap(ROADMAP);
+        assert_eq!(roadmap.features[1].depends_on, ["Schema"]);
+        assert_eq!(roadmap.features[1].dependencies, [0]);
+        assert_eq!(roadmap.features[2].dependencies, [1, 0]);
+        assert_eq!(unresolved(&roadmap), [(2, "Search")]);
+    }
+
+    #[test]
+    fn resolves_milestones_by_name_and_adds_missing_ones() {
+        let mut roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- M1\n- M2\n\n## Features\n\n### A\nMilestone: m2\n\n\
+             ### B\nMilestone: M3\n\n#### B1\nKind: feature\nMilestone: m3\n\n### C\n",
+        );
+        assert_eq!(roadmap.features[0].milestone_id, Some(1));
+        assert_eq!(roadmap.features[1].milestone_id, None);
+        assert_eq!(roadmap.features[2].milestone_id, None);
+        let titles: Vec<&str> = unresolved_milestones(&roadmap)
+            .iter()
+            .map(|f| f.title.as_str())
+            .collect();
+        assert_eq!(titles, ["B", "B1"]);
+
+        assert_eq!(add_missing_milestones(&mut roadmap), ["M3"]);
+        assert_eq!(roadmap.milestones[2].name, "M3");
+        assert_eq!(roadmap.features[1].milestone_id, Some(2));
+        assert_eq!(roadmap.features[1].children[0].milestone_id, Some(2));
+        assert!(unresolved_milestones(&roadmap).is_empty());
+    }
+
+    #[test]
+    fn orders_features_and_finds_cycles() {
+        let mut roadmap = parse_roadmap(ROADMAP);
+        assert_eq!(topological_order(&roadmap), Err(vec![3, 4]));
+        assert_eq!(cycles(&roadmap), [vec![3, 4]]);
+
+        roadmap.features.truncate(3);
+        assert_eq!(topological_order(&roadmap), Ok(vec![0, 1, 2]));
+        assert!(cycles(&roadmap).is_empty());
+    }
+
+    #[test]
+    fn renders_dot_and_mermaid_grouped_by_milestone() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- M1\n\n## Features\n\n### Schema\nMilestone: M1\n\n\
+             ### \"Fancy\" API\nMilestone: M1\nDepends on: Schema\n\n### Docs\nDepends on: \"Fancy\" API\n",
+        );
+        assert_eq!(
+            render(&roadmap, Diagram::Dot),
+            "\
+digraph roadmap {
+    rankdir=LR;
+    node [shape=box];
+    label=\"P\";
+    subgraph cluster_0 {
+        label=\"M1\";
+        f0 [label=\"Schema\"];
+        f1 [label=\"\\\"Fancy\\\" API\"];
+    }
+    f2 [label=\"Docs\"];
+    f0 -> f1;
+    f1 -> f2;
+}
+"
+        );
+        assert_eq!(
+            render(&roadmap, Diagram::Mermaid),
+            "\
+flowchart LR
+    subgraph m0[\"M1\"]
+        f0[\"Schema\"]
+        f1[\"#quot;Fancy#quot; API\"]
+    end
+    f2[\"Docs\"]
+    f0 --> f1
+    f1 --> f2
+"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! What happens to raw HTML and footnotes in descriptions.
+//!
+//! Descriptions keep them as written by default, which is what forges
+//! that render HTML and footnotes in issues want. [`HtmlPolicy::Strip`]
+//! drops both, with a warning for each description that lost some, and
+//! [`HtmlPolicy::Escape`] turns HTML into literal text.
+
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Deserialize;
+
+use crate::extract::options;
+use crate::roadmap::{Feature, ParseWarning, Roadmap, Span, WarningKind};
+
+/// How raw HTML and footnotes in descriptions are treated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum HtmlPolicy {
+    /// Keep HTML and footnotes as written
+    #[default]
+    Keep,
+    /// Drop HTML tags, footnote references and footnote definitions
+    Strip,
+    /// Show HTML as literal text; footnotes are kept
+    Escape,
+}
+
+/// Apply `policy` to the descriptions of `roadmap`'s features, their
+/// sub-features and their tasks, with a warning for each description that
+/// lost HTML or footnotes.
+pub fn apply(roadmap: &mut Roadmap, policy: HtmlPolicy) -> Vec<ParseWarning> {
+    let mut warnings = Vec::new();
+    for feature in &mut roadmap.features {
+        apply_to_feature(feature, policy, &mut warnings);
+    }
+    warnings
+}
+
+/// [`apply`] for one feature and its sub-features, adding the warnings to
+/// `warnings`.
+pub fn apply_to_feature(
+    feature: &mut Feature,
+    policy: HtmlPolicy,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    if policy == HtmlPolicy::Keep {
+        return;
+    }
+    let what = format!("feature '{}'", feature.title);
+    clean_into(
+        &mut feature.description,
+        policy,
+        &what,
+        feature.span,
+        warnings,
+    );
+    for task in &mut feature.tasks {
+        let what = format!("task '{}'", task.title);
+        clean_into(&mut task.description, policy, &what, task.span, warnings);
+    }
+    for child in &mut feature.children {
+        apply_to_feature(child, policy, warnings);
+    }
+}
+
+fn clean_into(
+    description: &mut String,
+    policy: HtmlPolicy,
+    what: &str,
+    span: Option<Span>,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    let (cleaned, dropped) = clean(description, policy);
+    *description = cleaned;
+    if !dropped.is_empty() {
+        warnings.push(ParseWarning {
+            kind: WarningKind::StrippedMarkup,
+            message: format!(
+                "dropped {} from the description of {}",
+                dropped.join(" and "),
+                what
+            ),
+            file: None,
+            span,
+        });
+    }
+}
+
+/// `markdown` with `policy` applied, and what was dropped from it.
+fn clean(markdown: &str, policy: HtmlPolicy) -> (String, Vec<&'static str>) {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut dropped = Vec::new();
+    let mut drop = |what| {
+        if !dropped.contains(&what) {
+            dropped.push(what);
+        }
+    };
+    let mut events = Parser::new_ext(markdown, options()).into_offset_iter();
+    while let Some((event, range)) = events.next() {
+        match (event, policy) {
+            (Event::Html(html), HtmlPolicy::Strip) => {
+                // Lines of an HTML block end in a newline; inline tags do not.
+                let range = if html.ends_with('\n') {
+                    whole_lines(markdown, range)
+                } else {
+                    range
+                };
+                edits.push((range, String::new()));
+                drop("raw HTML");
+            }
+            (Event::Html(_), HtmlPolicy::Escape) => {
+                edits.push((range.clone(), escape(&markdown[range])))
+            }
+            (Event::FootnoteReference(_), HtmlPolicy::Strip) => {
+                edits.push((range, String::new()));
+                drop("footnotes");
+            }
+            (Event::Start(Tag::FootnoteDefinition(_)), HtmlPolicy::Strip) => {
+                // Whatever the definition holds goes with it.
+                let mut depth = 0usize;
+                for (event, _) in events.by_ref() {
+                    match event {
+                        Event::Start(_) => depth += 1,
+                        Event::End(_) if depth == 0 => break,
+                        Event::End(_) => depth -= 1,
+                        _ => {}
+                    }
+                }
+                edits.push((whole_lines(markdown, range), String::new()));
+                drop("footnotes");
+            }
+            _ => {}
+        }
+    }
+    if edits.is_empty() {
+        return (markdown.to_string(), dropped);
+    }
+    let mut out = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for (range, replacement) in edits {
+        out.push_str(&markdown[last..range.start.max(last)]);
+        out.push_str(&replacement);
+        last = last.max(range.end);
+    }
+    out.push_str(&markdown[last..]);
+    (out.trim().to_string(), dropped)
+}
+
+/// `range`, a block, widened to the end of its last line and over the
+/// blank lines after it, so dropping it leaves no gap behind.
+fn whole_lines(markdown: &str, range: Range<usize>) -> Range<usize> {
+    let mut end = range.end;
+    if !markdown[..end].ends_with('\n') {
+        match markdown[end..].find('\n') {
+            Some(newline) if markdown[end..end + newline].trim().is_empty() => end += newline + 1,
+            _ => return range,
+        }
+    }
+    while let Some(newline) = markdown[end..].find('\n') {
+        if !markdown[end..end + newline].trim().is_empty() {
+            break;
+        }
+        end += newline + 1;
+    }
+    range.start..end
+}
+
+fn escape(html: &str) -> String {
+    html.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = "See <b>this</b>[^1].\n\n<details>\n<summary>More</summary>\n\
+                               </details>\n\n[^2]: Gone.\n\nHidden  \nline\n\n[^1]: A note.";
+
+    #[test]
+    fn strips_or_escapes_html_and_footnotes() {
+        let (stripped, dropped) = clean(DESCRIPTION, HtmlPolicy::Strip);
+        assert_eq!(stripped, "See this.\n\nHidden  \nline");
+        assert_eq!(dropped, ["raw HTML", "footnotes"]);
+
+        let (escaped, dropped) = clean(DESCRIPTION, HtmlPolicy::Escape);
+        assert!(escaped.starts_with("See &lt;b&gt;this&lt;/b&gt;[^1].\n\n&lt;details&gt;\n"));
+        assert!(escaped.ends_with("[^1]: A note."));
+        assert!(dropped.is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+//! `<!-- include: path/fragment.md -->` directives in Markdown roadmaps.
+//!
+//! A directive on a line of its own is replaced by the file it names,
+//! relative to the including file, before the roadmap is parsed, so shared
+//! milestone lists or feature sections can live in one fragment used by
+//! several roadmaps. Fragments may include fragments in turn; a file that
+//! ends up including itself is an error. Milestones, features and warnings
+//! read from a fragment record it as their `file`, with spans pointing
+//! into it.
+
+use std::borrow::Cow;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::config::Config;
+use crate::error::ParseError;
+use crate::roadmap::{Feature, Position, Roadmap, Span};
+use crate::vars::substitute;
+
+/// A roadmap's text with its includes spliced in, borrowed from the
+/// roadmap when there was nothing to change.
+#[derive(Debug, Clone, Default)]
+pub struct Expanded<'a> {
+    pub text: Cow<'a, str>,
+    /// Where each part of `text` was copied from.
+    regions: Vec<Region>,
+}
+
+#[derive(Debug, Clone)]
+struct Region {
+    /// Where the part is in the expanded text.
+    range: Range<usize>,
+    /// The fragment, as its path was joined onto the including file's;
+    /// `None` for the roadmap itself.
+    file: Option<String>,
+    /// Where the part starts in the file's text.
+    start: usize,
+    /// The file's text, to turn offsets into lines and columns.
+    source: Rc<str>,
+}
+
+/// `content`, read from `path`, with its include directives replaced by
+/// the fragments they name and the `${NAME}` placeholders of every file
+/// replaced by their values.
+pub fn expand<'a>(
+    path: &str,
+    content: &'a str,
+    config: &Config,
+) -> Result<Expanded<'a>, ParseError> {
+    let content = substitute(content, config)?;
+    if directives(&content).is_empty() {
+        return Ok(Expanded::plain(content));
+    }
+    let mut expanded = Expanded::default();
+    let mut stack = vec![canonical(Path::new(path))];
+    let content: Rc<str> = content.into();
+    expand_into(
+        &mut expanded,
+        Path::new(path),
+        false,
+        &content,
+        config,
+        &mut stack,
+    )?;
+    Ok(expanded)
+}
+
+fn expand_into(
+    expanded: &mut Expanded,
+    path: &Path,
+    fragment: bool,
+    text: &Rc<str>,
+    config: &Config,
+    stack: &mut Vec<PathBuf>,
+) -> Result<(), ParseError> {
+    let copy = |expanded: &mut Expanded, range: Range<usize>| {
+        if range.is_empty() {
+            return;
+        }
+        let start = expanded.text.len();
+        expanded.text.to_mut().push_str(&text[range.clone()]);
+        expanded.regions.push(Region {
+            range: start..expanded.text.len(),
+            file: fragment.then(|| path.display().to_string()),
+            start: range.start,
+            source: text.clone(),
+        });
+    };
+    let mut last = 0;
+    for (line, target) in directives(text) {
+        copy(expanded, last..line.start);
+        last = line.end;
+        let included = path.parent().unwrap_or(Path::new("")).join(target);
+        let key = canonical(&included);
+        if let Some(first) = stack.iter().position(|file| *file == key) {
+            let chain: Vec<String> = stack[first..]
+                .iter()
+                .chain([&key])
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(ParseError::Include {
+                file: path.display().to_string(),
+                message: format!("include cycle: {}", chain.join(" -> ")),
+            });
+        }
+        let content = fs::read_to_string(&included).map_err(|e| ParseError::Include {
+            file: path.display().to_string(),
+            message: format!("cannot include {}: {}", included.display(), e),
+        })?;
+        let content: Rc<str> = substitute(&content, config)?.into();
+        stack.push(key);
+        expand_into(expanded, &included, true, &content, config, stack)?;
+        stack.pop();
+        if !expanded.text.ends_with('\n') && !expanded.text.is_empty() {
+            expanded.text.to_mut().push('\n');
+        }
+    }
+    copy(expanded, last..text.len());
+    Ok(())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The lines of `text` holding an include directive, newline included,
+/// with the path each names. Directives in fenced code blocks are text.
+fn directives(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let range = offset..offset + line.len();
+        offset = range.end;
+        let trimmed = line.trim();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            _ => {}
+        }
+        if fence.is_some() {
+            continue;
+        }
+        let target = trimmed
+            .strip_prefix("<!--")
+            .and_then(|rest| rest.strip_suffix("-->"))
+            .and_then(|inner| inner.trim().strip_prefix("include:"))
+            .map(str::trim)
+            .filter(|target| !target.is_empty());
+        if let Some(target) = target {
+            found.push((range, target));
+        }
+    }
+    found
+}
+
+impl<'a> Expanded<'a> {
+    /// Text without includes.
+    pub(crate) fn plain(text: Cow<'a, str>) -> Self {
+        Expanded {
+            text,
+            regions: Vec::new(),
+        }
+    }
+
+    /// Point the spans of `roadmap`, parsed from [`Expanded::text`], back
+    /// into the file each came from, and record the fragments as the
+    /// `file` of their milestones, features and warnings.
+    pub fn attribute(&self, roadmap: &mut Roadmap) {
+        if self.regions.iter().all(|region| region.file.is_none()) {
+            return;
+        }
+        for milestone in &mut roadmap.milestones {
+            if let Some(file) = self.remap(&mut milestone.span) {
+                milestone.file = Some(file);
+            }
+        }
+        for feature in &mut roadmap.features {
+            self.attribute_feature(feature);
+        }
+        for warning in &mut roadmap.warnings {
+            if let Some(file) = self.remap(&mut warning.span) {
+                warning.file = Some(file);
+            }
+        }
+    }
+
+    fn attribute_feature(&self, feature: &mut Feature) {
+        if let Some(file) = self.remap(&mut feature.span) {
+            feature.file = Some(file);
+        }
+        for task in &mut feature.tasks {
+            self.remap(&mut task.span);
+        }
+        for child in &mut feature.children {
+            self.attribute_feature(child);
+        }
+    }
+
+    /// Move `span` into the file its start is in, and name the file if it
+    /// is a fragment. An end beyond the part is cut to the part's end.
+    fn remap(&self, span: &mut Option<Span>) -> Option<String> {
+        let old = (*span)?;
+        let region = self
+            .regions
+            .iter()
+            .find(|region| region.range.contains(&old.start.offset))?;
+        let end = old.end.offset.min(region.range.end);
+        let map = |offset: usize| {
+            let offset = region.start + offset - region.range.start;
+            position(&region.source, offset)
+        };
+        *span = Some(Span {
+            start: map(old.start.offset),
+            end: map(end),
+        });
+        region.file.clone()
+    }
+}
+
+fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        offset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splices_fragments_and_points_into_them() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(
+            dir.join("shared/milestones.md"),
+            "- **v1** — 2024-06-30\n<!-- include: more.md -->\n",
+        )
+        .unwrap();
+        fs::write(dir.join("shared/more.md"), "- **v2**").unwrap();
+        let main = dir.join("ROADMAP.md");
+        let content = "# App\n\n## Milestones\n\n<!-- include: shared/milestones.md -->\n\n\
+                       ```\n<!-- include: nowhere.md -->\n```\n";
+        let path = main.to_str().unwrap();
+        let config = Config::default();
+
+        let expanded = expand(path, content, &config).unwrap();
+        assert_eq!(
+            expanded.text,
+            "# App\n\n## Milestones\n\n- **v1** — 2024-06-30\n- **v2**\n\n\
+             ```\n<!-- include: nowhere.md -->\n```\n"
+        );
+        let mut roadmap = crate::parse_markdown_with(&expanded.text, &config.headings).unwrap();
+        expanded.attribute(&mut roadmap);
+        let v2 = &roadmap.milestones[1];
+        assert!(v2.file.as_deref().unwrap().ends_with("more.md"));
+        assert_eq!(v2.span.unwrap().start.line, 1);
+        assert_eq!(roadmap.milestones[0].span.unwrap().start.line, 1);
+
+        fs::write(
+            dir.join("shared/more.md"),
+            "<!-- include: milestones.md -->\n",
+        )
+        .unwrap();
+        let error = expand(path, content, &config).unwrap_err();
+        assert!(error.to_string().contains("include cycle"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
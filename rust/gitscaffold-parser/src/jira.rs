@@ -0,0 +1,247 @@
+//! Jira issues made from a [`Roadmap`], for Jira's CSV importer.
+//!
+//! Each milestone becomes an epic; each feature an issue of the configured
+//! type, linked to its milestone's epic; and each task a sub-task of its
+//! feature. Sub-features become issues of their own, titled with their
+//! parent's title as a prefix like in [`crate::export`]. Every row has an
+//! `Issue Id`, which sub-tasks reference as their `Parent Id`.
+
+use crate::config::{JiraField, JiraSettings};
+use crate::export::{cell, push_row};
+use crate::roadmap::{Feature, Roadmap};
+
+/// One row of the Jira import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JiraIssue {
+    /// The row's `Issue Id`, numbered from 1.
+    pub id: usize,
+    pub issue_type: String,
+    pub summary: String,
+    pub description: String,
+    /// Set on epics, which Jira links issues to by name.
+    pub epic_name: Option<String>,
+    /// The epic of the issue's milestone.
+    pub epic_link: Option<String>,
+    /// The `Issue Id` of a sub-task's feature.
+    pub parent: Option<usize>,
+    /// Labels with spaces replaced by dashes, which Jira does not allow.
+    pub labels: Vec<String>,
+    /// The first assignee; Jira issues have only one.
+    pub assignee: Option<String>,
+    pub due_date: Option<String>,
+    /// `Done` or `To Do`, for sub-tasks.
+    pub status: Option<&'static str>,
+    /// Values of the configured custom fields, in their order.
+    pub fields: Vec<String>,
+}
+
+const EPIC: &str = "Epic";
+
+/// The epics, issues and sub-tasks for `roadmap`, parents first.
+pub fn jira_issues(roadmap: &Roadmap, settings: &JiraSettings) -> Vec<JiraIssue> {
+    let mut issues = Vec::new();
+    for milestone in &roadmap.milestones {
+        issues.push(JiraIssue {
+            id: issues.len() + 1,
+            issue_type: EPIC.into(),
+            summary: milestone.name.clone(),
+            description: String::new(),
+            epic_name: Some(milestone.name.clone()),
+            epic_link: None,
+            parent: None,
+            labels: Vec::new(),
+            assignee: None,
+            due_date: milestone.due_date.clone(),
+            status: None,
+            fields: Vec::new(),
+        });
+    }
+    for feature in &roadmap.features {
+        push_feature(&mut issues, roadmap, settings, feature, "", None);
+    }
+    issues
+}
+
+fn push_feature(
+    issues: &mut Vec<JiraIssue>,
+    roadmap: &Roadmap,
+    settings: &JiraSettings,
+    feature: &Feature,
+    parent: &str,
+    parent_milestone: Option<&str>,
+) {
+    let title = if parent.is_empty() {
+        feature.title.clone()
+    } else {
+        format!("{} / {}", parent, feature.title)
+    };
+    let milestone = feature.milestone.as_deref().or(parent_milestone);
+    let epic = milestone.and_then(|name| {
+        roadmap
+            .milestones
+            .iter()
+            .find(|m| m.name.trim().eq_ignore_ascii_case(name.trim()))
+    });
+    let id = issues.len() + 1;
+    issues.push(JiraIssue {
+        id,
+        issue_type: settings.issue_type.clone(),
+        summary: title.clone(),
+        description: feature.description.trim().to_string(),
+        epic_name: None,
+        epic_link: epic.map(|m| m.name.clone()),
+        parent: None,
+        labels: jira_labels(&feature.labels),
+        assignee: feature.assignees.first().cloned(),
+        due_date: None,
+        status: None,
+        fields: settings
+            .fields
+            .values()
+            .map(|field| match field {
+                JiraField::Column(column) => cell(roadmap, feature, &title, None, *column),
+                JiraField::Value { value } => value.clone(),
+            })
+            .collect(),
+    });
+    for task in &feature.tasks {
+        issues.push(JiraIssue {
+            id: issues.len() + 1,
+            issue_type: settings.subtask_type.clone(),
+            summary: task.title.clone(),
+            description: task.description.trim().to_string(),
+            epic_name: None,
+            epic_link: None,
+            parent: Some(id),
+            labels: jira_labels(&task.labels),
+            assignee: task.assignees.first().cloned(),
+            due_date: None,
+            status: Some(if task.completed { "Done" } else { "To Do" }),
+            fields: Vec::new(),
+        });
+    }
+    for child in &feature.children {
+        push_feature(issues, roadmap, settings, child, &title, milestone);
+    }
+}
+
+fn jira_labels(labels: &[String]) -> Vec<String> {
+    labels
+        .iter()
+        .map(|label| label.split_whitespace().collect::<Vec<_>>().join("-"))
+        .filter(|label| !label.is_empty())
+        .collect()
+}
+
+/// Render `issues` as CSV for Jira's importer. Labels repeat the `Labels`
+/// column once per label, which is how the importer takes several values.
+pub fn to_jira_csv(issues: &[JiraIssue], settings: &JiraSettings) -> String {
+    let label_columns = issues
+        .iter()
+        .map(|issue| issue.labels.len())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut header = vec![
+        "Issue Id",
+        "Issue Type",
+        "Summary",
+        "Description",
+        "Epic Name",
+        "Epic Link",
+        "Parent Id",
+    ];
+    header.extend(std::iter::repeat_n("Labels", label_columns));
+    header.extend(["Assignee", "Due Date", "Status"]);
+    header.extend(settings.fields.keys().map(String::as_str));
+
+    let mut out = String::new();
+    push_row(&mut out, &header, ',');
+    for issue in issues {
+        let mut row = vec![
+            issue.id.to_string(),
+            issue.issue_type.clone(),
+            issue.summary.clone(),
+            issue.description.clone(),
+            issue.epic_name.clone().unwrap_or_default(),
+            issue.epic_link.clone().unwrap_or_default(),
+            issue.parent.map(|id| id.to_string()).unwrap_or_default(),
+        ];
+        for i in 0..label_columns {
+            row.push(issue.labels.get(i).cloned().unwrap_or_default());
+        }
+        row.push(issue.assignee.clone().unwrap_or_default());
+        row.push(issue.due_date.clone().unwrap_or_default());
+        row.push(issue.status.unwrap_or_default().to_string());
+        for i in 0..settings.fields.len() {
+            row.push(issue.fields.get(i).cloned().unwrap_or_default());
+        }
+        push_row(&mut out, &row, ',');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Column;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "# Demo\n\n## Milestones\n- Beta — 2025-07-01\n\n## Features\n\n\
+        ### Search\nMilestone: Beta\nLabels: backend, full text\nAssignees: alice, bob\n\n\
+        - [x] Index\n- [ ] Query\n\n### Auth\n";
+
+    #[test]
+    fn links_features_to_epics_and_tasks_to_features() {
+        let issues = jira_issues(&parse_roadmap(ROADMAP), &JiraSettings::default());
+        let summary: Vec<_> = issues
+            .iter()
+            .map(|i| (i.id, i.issue_type.as_str(), i.summary.as_str(), i.parent))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, "Epic", "Beta", None),
+                (2, "Story", "Search", None),
+                (3, "Sub-task", "Index", Some(2)),
+                (4, "Sub-task", "Query", Some(2)),
+                (5, "Story", "Auth", None),
+            ]
+        );
+        assert_eq!(issues[1].epic_link.as_deref(), Some("Beta"));
+        assert_eq!(issues[1].labels, ["backend", "full-text"]);
+        assert_eq!(issues[1].assignee.as_deref(), Some("alice"));
+        assert_eq!(issues[2].status, Some("Done"));
+        assert_eq!(issues[4].epic_link, None);
+    }
+
+    #[test]
+    fn writes_repeated_label_columns_and_custom_fields() {
+        let mut settings = JiraSettings::default();
+        settings
+            .fields
+            .insert("Component".into(), JiraField::Column(Column::Kind));
+        settings.fields.insert(
+            "Team".into(),
+            JiraField::Value {
+                value: "Core".into(),
+            },
+        );
+        let roadmap = parse_roadmap(ROADMAP);
+        let csv = to_jira_csv(&jira_issues(&roadmap, &settings), &settings);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "Issue Id,Issue Type,Summary,Description,Epic Name,Epic Link,Parent Id,\
+                 Labels,Labels,Assignee,Due Date,Status,Component,Team"
+            )
+        );
+        assert_eq!(lines.next(), Some("1,Epic,Beta,,Beta,,,,,,2025-07-01,,,"));
+        assert_eq!(
+            lines.next(),
+            Some("2,Story,Search,,,Beta,,backend,full-text,alice,,,feature,Core")
+        );
+        assert_eq!(lines.next(), Some("3,Sub-task,Index,,,,2,,,,,Done,,"));
+    }
+}
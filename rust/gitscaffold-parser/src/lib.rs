@@ -0,0 +1,77 @@
+//! Parse gitscaffold roadmaps written in Markdown or YAML into a common
+//! [`Roadmap`] model, and validate them.
+//!
+//! ```
+//! let roadmap = gitscaffold_parser::parse_markdown(
+//!     "# Demo\n\n## Features\n\n### Login\nLabels: auth\n",
+//! )
+//! .unwrap();
+//! assert_eq!(roadmap.features[0].labels, ["auth"]);
+//! ```
+
+pub mod aggregate;
+pub mod cache;
+pub mod capacity;
+pub mod config;
+pub mod dates;
+pub mod diff;
+pub mod due;
+pub mod edit;
+mod error;
+pub mod export;
+pub mod extract;
+pub mod format;
+mod frontmatter;
+pub mod gantt;
+pub mod graph;
+pub mod html;
+pub mod include;
+pub mod jira;
+pub mod lint;
+pub mod load;
+pub mod merge;
+pub mod migrate;
+pub mod release;
+pub mod render;
+pub mod report;
+pub mod roadmap;
+pub mod sarif;
+pub mod stats;
+pub mod text;
+pub mod validate;
+pub mod vars;
+pub mod yaml;
+
+pub use cache::Cache;
+pub use config::{
+    CapacityUnit, Config, FieldMapping, ForgeKind, Headings, LabelDefinition, LabelSettings,
+    TitleSettings,
+};
+pub use error::ParseError;
+pub use extract::Entities;
+pub use format::InputFormat;
+pub use html::HtmlPolicy;
+pub use load::{expand_paths, load_roadmap, load_roadmap_with, STDIN};
+pub use roadmap::{
+    Anchor, Entity, Estimate, ExtraSection, Feature, FeatureId, FeatureKind, FeatureStatus,
+    IssueLink, Link, Metadata, Milestone, MilestoneId, ParseWarning, Position, Priority, Roadmap,
+    Span, Task, WarningKind,
+};
+
+/// Parse a heading-based Markdown roadmap.
+///
+/// Markdown parsing itself cannot fail; the `Result` keeps the signature in
+/// line with [`parse_yaml`].
+pub fn parse_markdown(source: &str) -> Result<Roadmap, ParseError> {
+    Ok(extract::parse_roadmap(source))
+}
+
+/// [`parse_markdown`] with custom heading conventions.
+pub fn parse_markdown_with(source: &str, headings: &Headings) -> Result<Roadmap, ParseError> {
+    Ok(extract::parse_roadmap_with(source, headings))
+}
+
+/// Parse a structured YAML (or JSON) roadmap.
+pub fn parse_yaml(source: &str) -> Result<Roadmap, ParseError> {
+    Ok(yaml::parse_roadmap(source)?)
+}
@@ -0,0 +1,400 @@
+//! Style checks for Markdown roadmaps, with safe automatic fixes.
+//!
+//! Unlike [`crate::validate`], which checks the parsed roadmap, linting
+//! looks at how the source is written: a roadmap can be valid and still
+//! skip heading levels or spell its due dates three different ways. Each
+//! [`Lint`] carries a [`Fix`] when the source can be rewritten without
+//! changing what it means; [`apply_fixes`] applies them.
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::config::Headings;
+use crate::dates::parse_date;
+use crate::extract::{is_assignees, is_labels, metadata, parse_roadmap_with};
+use crate::frontmatter;
+use crate::text::find_due;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// Heading levels should only increase one at a time
+    HeadingIncrement,
+    /// Headings should not end in whitespace
+    TrailingWhitespace,
+    /// Milestone due dates should be written as YYYY-MM-DD
+    DateFormat,
+    /// Labels and assignees should be listed once
+    DuplicateLabels,
+    /// Sections should not be empty
+    EmptySection,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 5] = [
+        LintRule::HeadingIncrement,
+        LintRule::TrailingWhitespace,
+        LintRule::DateFormat,
+        LintRule::DuplicateLabels,
+        LintRule::EmptySection,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            LintRule::HeadingIncrement => "GL001",
+            LintRule::TrailingWhitespace => "GL002",
+            LintRule::DateFormat => "GL003",
+            LintRule::DuplicateLabels => "GL004",
+            LintRule::EmptySection => "GL005",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LintRule::HeadingIncrement => "heading-increment",
+            LintRule::TrailingWhitespace => "trailing-whitespace",
+            LintRule::DateFormat => "date-format",
+            LintRule::DuplicateLabels => "duplicate-labels",
+            LintRule::EmptySection => "empty-section",
+        }
+    }
+
+    /// A one-line summary of what the rule checks.
+    pub fn description(self) -> &'static str {
+        match self {
+            LintRule::HeadingIncrement => "Heading levels increase one at a time",
+            LintRule::TrailingWhitespace => "Headings do not end in whitespace",
+            LintRule::DateFormat => "Milestone due dates are written as YYYY-MM-DD",
+            LintRule::DuplicateLabels => "Labels: and Assignees: list each entry once",
+            LintRule::EmptySection => "Every heading has content or sub-headings",
+        }
+    }
+}
+
+/// Replace `range` of the source with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fix {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Lint {
+    pub code: &'static str,
+    pub rule: LintRule,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+}
+
+/// One source line, without its line ending.
+struct SourceLine<'a> {
+    number: usize,
+    start: usize,
+    text: &'a str,
+    in_fence: bool,
+}
+
+struct Heading {
+    line: usize,
+    level: usize,
+}
+
+/// Check the Markdown roadmap `source`, read with the `headings`
+/// conventions, against every lint rule. Lints are ordered by position.
+pub fn lint(source: &str, headings: &Headings) -> Vec<Lint> {
+    let body = frontmatter::split(source).map_or(0, |(_, offset)| offset);
+    let lines = source_lines(source);
+    let mut lints = Vec::new();
+    let mut push =
+        |rule: LintRule, line: &SourceLine, offset: usize, message: String, fix: Option<Fix>| {
+            lints.push(Lint {
+                code: rule.code(),
+                rule,
+                message,
+                line: line.number,
+                column: line.text[..offset - line.start].chars().count() + 1,
+                fix,
+            })
+        };
+
+    let headings_found: Vec<Heading> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.start >= body && !line.in_fence)
+        .filter_map(|(i, line)| heading_level(line.text).map(|level| Heading { line: i, level }))
+        .collect();
+    for (i, heading) in headings_found.iter().enumerate() {
+        let line = &lines[heading.line];
+        if let Some(previous) = i.checked_sub(1).map(|p| &headings_found[p]) {
+            if heading.level > previous.level + 1 {
+                push(
+                    LintRule::HeadingIncrement,
+                    line,
+                    line.start,
+                    format!(
+                        "heading level jumps from {} to {}",
+                        previous.level, heading.level
+                    ),
+                    None,
+                );
+            }
+        }
+
+        let trimmed = line.text.trim_end();
+        if trimmed.len() < line.text.len() {
+            let offset = line.start + trimmed.len();
+            push(
+                LintRule::TrailingWhitespace,
+                line,
+                offset,
+                "heading ends in whitespace".to_string(),
+                Some(Fix {
+                    range: offset..line.start + line.text.len(),
+                    replacement: String::new(),
+                }),
+            );
+        }
+
+        let next = headings_found.get(i + 1);
+        let content_end = next.map_or(lines.len(), |next| next.line);
+        let has_content = lines[heading.line + 1..content_end]
+            .iter()
+            .any(|line| !line.text.trim().is_empty());
+        let has_children = next.is_some_and(|next| next.level > heading.level);
+        if !has_content && !has_children {
+            push(
+                LintRule::EmptySection,
+                line,
+                line.start,
+                format!("section '{}' is empty", heading_title(trimmed)),
+                None,
+            );
+        }
+    }
+
+    for line in lines.iter().filter(|line| line.start >= body) {
+        if line.in_fence || heading_level(line.text).is_some() {
+            continue;
+        }
+        let Some((key, _)) = metadata(line.text) else {
+            continue;
+        };
+        if !is_labels(&key) && !is_assignees(&key) {
+            continue;
+        }
+        let range = metadata_value(line.text);
+        let value = &line.text[range.clone()];
+        let mut unique: Vec<&str> = Vec::new();
+        let mut repeated: Vec<&str> = Vec::new();
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if unique.contains(&entry) {
+                repeated.push(entry);
+            } else {
+                unique.push(entry);
+            }
+        }
+        if !repeated.is_empty() {
+            let offset = line.start + range.start;
+            push(
+                LintRule::DuplicateLabels,
+                line,
+                offset,
+                format!("{} repeats '{}'", key, repeated.join("', '")),
+                Some(Fix {
+                    range: offset..line.start + range.end,
+                    replacement: unique.join(", "),
+                }),
+            );
+        }
+    }
+
+    let roadmap = parse_roadmap_with(source, headings);
+    for milestone in &roadmap.milestones {
+        let Some(span) = milestone.span else {
+            continue;
+        };
+        let Some(line) = lines.get(span.start.line - 1) else {
+            continue;
+        };
+        let Some(range) = due_date_range(line.text) else {
+            continue;
+        };
+        let written = &line.text[range.clone()];
+        let Some(date) = parse_date(written) else {
+            continue;
+        };
+        let iso = date.format("%Y-%m-%d").to_string();
+        if written != iso {
+            let offset = line.start + range.start;
+            push(
+                LintRule::DateFormat,
+                line,
+                offset,
+                format!(
+                    "milestone '{}' due date '{}' should be written as {}",
+                    milestone.name, written, iso
+                ),
+                Some(Fix {
+                    range: offset..line.start + range.end,
+                    replacement: iso,
+                }),
+            );
+        }
+    }
+
+    lints.sort_by_key(|lint| (lint.line, lint.column));
+    lints
+}
+
+/// Apply the fixes of `lints` to `source`. A fix overlapping an earlier
+/// one is skipped; linting the result again reports what is left.
+pub fn apply_fixes(source: &str, lints: &[Lint]) -> String {
+    let mut fixes: Vec<&Fix> = lints.iter().filter_map(|lint| lint.fix.as_ref()).collect();
+    fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+    let mut fixed = String::with_capacity(source.len());
+    let mut copied = 0;
+    for fix in fixes {
+        if fix.range.start < copied {
+            continue;
+        }
+        fixed.push_str(&source[copied..fix.range.start]);
+        fixed.push_str(&fix.replacement);
+        copied = fix.range.end;
+    }
+    fixed.push_str(&source[copied..]);
+    fixed
+}
+
+fn source_lines(source: &str) -> Vec<SourceLine<'_>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut in_fence = false;
+    for (number, raw) in source.split_inclusive('\n').enumerate() {
+        let text = raw.strip_suffix('\n').unwrap_or(raw);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        let trimmed = text.trim_start();
+        let fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if fence {
+            in_fence = !in_fence;
+        }
+        lines.push(SourceLine {
+            number: number + 1,
+            start,
+            text,
+            in_fence: in_fence || fence,
+        });
+        start += raw.len();
+    }
+    lines
+}
+
+/// The level of an ATX heading line.
+fn heading_level(line: &str) -> Option<usize> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let line = &line[indent..];
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[hashes..];
+    let is_heading = indent <= 3
+        && (1..=6).contains(&hashes)
+        && (rest.is_empty() || rest.starts_with([' ', '\t']));
+    is_heading.then_some(hashes)
+}
+
+fn heading_title(line: &str) -> &str {
+    line.trim_start().trim_start_matches('#').trim()
+}
+
+/// The range of a metadata line's value, without the emphasis that may
+/// close the key (`**Labels:** a, b`) and trailing whitespace.
+fn metadata_value(line: &str) -> Range<usize> {
+    let colon = line.find(':').map_or(line.len(), |i| i + 1);
+    let value = &line[colon..];
+    let start = colon + value.len() - value.trim_start_matches(['*', '_', ' ', '\t']).len();
+    let end = start.max(line.trim_end().len());
+    start..end
+}
+
+/// The range of the due date in a milestone's first line: the second cell
+/// of a table row, or the text after the dash in a list item or heading.
+fn due_date_range(line: &str) -> Option<Range<usize>> {
+    let (start, end) = if line.trim_start().starts_with('|') {
+        let mut cells = line.match_indices('|').map(|(i, _)| i);
+        cells.next()?;
+        let start = cells.next()? + 1;
+        (start, cells.next().unwrap_or(line.len()))
+    } else {
+        let (_, due) = find_due(line)?;
+        (due.start, due.end)
+    };
+    let part = &line[start..end];
+    let markup: &[char] = &['*', '_', '`', '(', ')', ' ', '\t'];
+    let leading = part.len() - part.trim_start_matches(markup).len();
+    let inner = part.trim_matches(markup);
+    (!inner.is_empty()).then(|| start + leading..start + leading + inner.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# Demo\n\n\
+        ## Milestones\n\n\
+        - M1 — June 30, 2025\n\
+        - M2 — 2025-09-30\n\n\
+        ## Features  \n\n\
+        #### Login\n\
+        Labels: auth, api, auth\n\n\
+        ### Search\n";
+
+    fn rules(lints: &[Lint]) -> Vec<(usize, &'static str)> {
+        lints.iter().map(|l| (l.line, l.code)).collect()
+    }
+
+    #[test]
+    fn reports_style_issues_in_source_order() {
+        let lints = lint(SOURCE, &Headings::default());
+        assert_eq!(
+            rules(&lints),
+            [
+                (5, "GL003"),
+                (8, "GL002"),
+                (10, "GL001"),
+                (11, "GL004"),
+                (13, "GL005")
+            ]
+        );
+        assert_eq!((lints[0].line, lints[0].column), (5, 8));
+        assert_eq!(lints[3].message, "labels repeats 'auth'");
+    }
+
+    #[test]
+    fn fixes_what_is_safe_to_rewrite() {
+        let lints = lint(SOURCE, &Headings::default());
+        let fixed = apply_fixes(SOURCE, &lints);
+        assert!(fixed.contains("- M1 — 2025-06-30\n"));
+        assert!(fixed.contains("## Features\n"));
+        assert!(fixed.contains("Labels: auth, api\n"));
+        let left = lint(&fixed, &Headings::default());
+        assert_eq!(rules(&left), [(10, "GL001"), (13, "GL005")]);
+    }
+
+    #[test]
+    fn ignores_fenced_code_and_frontmatter() {
+        let source = "---\nlabels: [a, a]\n---\n# Demo\n\n\
+            ```\n#### not a heading  \nLabels: a, a\n```\n\n\
+            | Milestone | Due |\n|---|---|\n| M1 | **06/30/2025** |\n";
+        let headings = Headings::default();
+        let lints = lint(source, &headings);
+        assert!(lints.is_empty(), "{:?}", lints);
+
+        let source = source.replace("# Demo", "# Demo\n\n## Milestones");
+        let lints = lint(&source, &headings);
+        assert_eq!(rules(&lints), [(15, "GL003")]);
+        assert!(apply_fixes(&source, &lints).contains("| M1 | **2025-06-30** |"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Reading roadmap files from disk, or from stdin when the path is `-`.
+//!
+//! Large files are memory-mapped rather than read, and their text is
+//! parsed in place: unless includes or `${NAME}` placeholders change it,
+//! no copy of the file is made on the heap, which roughly halves the peak
+//! memory of parsing a multi-megabyte roadmap.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use memmap2::Mmap;
+
+use crate::config::Config;
+use crate::error::ParseError;
+use crate::format::InputFormat;
+use crate::include::{self, Expanded};
+use crate::roadmap::Roadmap;
+use crate::vars;
+
+/// The path that names stdin.
+pub const STDIN: &str = "-";
+
+/// Read and parse the roadmap at `path`, resolving `format` against the
+/// file's extension and content.
+pub fn load_roadmap(path: &str, format: InputFormat) -> Result<Roadmap, ParseError> {
+    load_roadmap_with(path, format, &Config::default())
+}
+
+/// [`load_roadmap`] following the conventions in `config`.
+pub fn load_roadmap_with(
+    path: &str,
+    format: InputFormat,
+    config: &Config,
+) -> Result<Roadmap, ParseError> {
+    let content = read_source(path)?;
+    parse_content(path, &content, format, config)
+}
+
+/// Files at least this large are memory-mapped instead of read.
+const MAP_THRESHOLD: u64 = 1 << 20;
+
+/// The text of a roadmap file, mapped into memory when it is large.
+pub enum Source {
+    Mapped(Mmap),
+    Read(String),
+}
+
+impl Deref for Source {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            // SAFETY: `read_source` checked that the mapped bytes are
+            // UTF-8, and the map is read-only.
+            Source::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+            Source::Read(text) => text,
+        }
+    }
+}
+
+/// Read the file at `path`, failing like [`fs::read_to_string`] when it
+/// is not UTF-8. [`STDIN`] reads stdin, once: later reads return the same
+/// text, so diagnostics can quote it.
+pub fn read_source(path: &str) -> io::Result<Source> {
+    if path == STDIN {
+        return read_stdin().map(|text| Source::Read(text.to_string()));
+    }
+    let file = File::open(path)?;
+    if file.metadata()?.len() < MAP_THRESHOLD {
+        return fs::read_to_string(path).map(Source::Read);
+    }
+    // SAFETY: the map is only read. A roadmap truncated by another process
+    // while it is being parsed can still crash the parse, as with any tool
+    // that maps its input; roadmaps are not rewritten that way in practice.
+    let map = unsafe { Mmap::map(&file)? };
+    tracing::trace!(path, bytes = map.len(), "memory-mapped roadmap");
+    std::str::from_utf8(&map).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Source::Mapped(map))
+}
+
+fn read_stdin() -> io::Result<&'static str> {
+    static TEXT: OnceLock<String> = OnceLock::new();
+    if let Some(text) = TEXT.get() {
+        return Ok(text);
+    }
+    let mut text = String::new();
+    io::stdin().lock().read_to_string(&mut text)?;
+    Ok(TEXT.get_or_init(|| text))
+}
+
+/// Parse `content` as if it had been read from `path`, which decides the
+/// format when it is [`InputFormat::Auto`], after following its include
+/// directives and replacing its `${NAME}` placeholders.
+pub fn parse_content(
+    path: &str,
+    content: &str,
+    format: InputFormat,
+    config: &Config,
+) -> Result<Roadmap, ParseError> {
+    let prepared = prepare(path, content, format, config)?;
+    parse_prepared(path, &prepared, format, config)
+}
+
+/// `content` with its includes and placeholders resolved. YAML roadmaps
+/// have no includes.
+pub(crate) fn prepare<'a>(
+    path: &str,
+    content: &'a str,
+    format: InputFormat,
+    config: &Config,
+) -> Result<Expanded<'a>, ParseError> {
+    match format.resolve(path, content) {
+        InputFormat::Yaml => Ok(Expanded::plain(vars::substitute(content, config)?)),
+        _ => include::expand(path, content, config),
+    }
+}
+
+/// Parse content made ready by [`prepare`].
+pub(crate) fn parse_prepared(
+    path: &str,
+    prepared: &Expanded,
+    format: InputFormat,
+    config: &Config,
+) -> Result<Roadmap, ParseError> {
+    match format.resolve(path, &prepared.text) {
+        InputFormat::Yaml => crate::parse_yaml(&prepared.text),
+        _ => {
+            let mut roadmap = crate::parse_markdown_with(&prepared.text, &config.headings)?;
+            prepared.attribute(&mut roadmap);
+            Ok(roadmap)
+        }
+    }
+}
+
+/// Expand glob patterns (`roadmap/*.md`) into the matching paths, sorted;
+/// arguments without glob characters are kept as they are. A pattern that
+/// matches nothing is an error, so typos do not silently drop files.
+pub fn expand_paths(patterns: &[String]) -> Result<Vec<String>, ParseError> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            paths.push(pattern.clone());
+            continue;
+        }
+        let invalid =
+            |e: &dyn std::fmt::Display| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+        let mut matched: Vec<String> = glob::glob(pattern)
+            .map_err(|e| invalid(&e))?
+            .map(|entry| entry.map(|path| path.to_string_lossy().into_owned()))
+            .collect::<Result<_, _>>()
+            .map_err(|e| invalid(&e))?;
+        if matched.is_empty() {
+            let message = format!("no files match '{}'", pattern);
+            return Err(io::Error::new(io::ErrorKind::NotFound, message).into());
+        }
+        matched.sort();
+        paths.extend(matched);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_large_files_and_rejects_invalid_utf8() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let large = dir.join("large.md");
+        let mut text = String::from("# Big\n\n## Features\n\n");
+        while (text.len() as u64) < MAP_THRESHOLD {
+            text.push_str("### Feature\n\nSome words about it.\n\n");
+        }
+        fs::write(&large, &text).unwrap();
+        let source = read_source(large.to_str().unwrap()).unwrap();
+        assert!(matches!(source, Source::Mapped(_)));
+        assert_eq!(&*source, text);
+
+        let invalid = dir.join("invalid.md");
+        let mut bytes = text.into_bytes();
+        bytes.push(0xff);
+        fs::write(&invalid, bytes).unwrap();
+        let error = read_source(invalid.to_str().unwrap()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
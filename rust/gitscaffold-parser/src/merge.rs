@@ -0,0 +1,620 @@
+//! Three-way merge of two versions of a roadmap, e.g. a feature branch's
+//! against the main branch's, with their common ancestor as the base.
+//!
+//! Milestones, features, sub-features, tasks and extra sections are matched
+//! by name or title, ignoring case, and merged field by field: a field
+//! changed on one side only takes that side's value, and labels, assignees
+//! and dependencies are merged as sets. A field both sides changed
+//! differently is a [`Conflict`], as is an entity one side changed and the
+//! other removed. Without a base, every difference between the two sides is
+//! a conflict, while entities and set members found on only one side are
+//! kept.
+//!
+//! Each conflict is resolved in favour of one [`Side`]. Merging once per
+//! side and passing both renderings to [`conflict_markers`] yields a
+//! document with git-style markers around the conflicting lines.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::graph;
+use crate::roadmap::{ExtraSection, Feature, Milestone, Roadmap, Task};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Side {
+    /// The first roadmap
+    Ours,
+    /// The second roadmap
+    Theirs,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Ours => "ours",
+            Side::Theirs => "theirs",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    /// `roadmap`, `metadata 'repo'`, `milestone 'Beta'`, `feature 'Login'`
+    /// or `task 'Form' of feature 'Login'`.
+    pub entity: String,
+    #[serde(flatten)]
+    pub kind: ConflictKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "conflict", rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both sides changed `field`, each to a different value.
+    Changed {
+        field: &'static str,
+        /// `None` without a base or when the entity is new on both sides.
+        base: Option<Value>,
+        ours: Value,
+        theirs: Value,
+    },
+    /// One side changed the entity and the other, `by`, removed it.
+    Removed { by: Side },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ConflictKind::Changed {
+                field,
+                base,
+                ours,
+                theirs,
+            } => {
+                write!(f, "{}: {} is {} in ours", self.entity, field, ours)?;
+                write!(f, " but {} in theirs", theirs)?;
+                match base {
+                    Some(base) => write!(f, " (was {})", base),
+                    None => Ok(()),
+                }
+            }
+            ConflictKind::Removed { by } => {
+                let changed = match by {
+                    Side::Ours => Side::Theirs,
+                    Side::Theirs => Side::Ours,
+                };
+                write!(
+                    f,
+                    "{}: changed in {} but removed in {}",
+                    self.entity, changed, by
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    pub roadmap: Roadmap,
+    /// The conflicts, each resolved in favour of the preferred side.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merge `ours` and `theirs`, optionally from their common `base`,
+/// resolving conflicts in favour of `prefer`. Source spans are dropped.
+pub fn merge(base: Option<&Roadmap>, ours: &Roadmap, theirs: &Roadmap, prefer: Side) -> Merge {
+    let strip = |roadmap: &Roadmap| {
+        let mut roadmap = roadmap.clone();
+        roadmap.strip_spans();
+        roadmap
+    };
+    let (base, ours, theirs) = (base.map(strip), strip(ours), strip(theirs));
+    let base = base.as_ref();
+    let mut merger = Merger {
+        prefer,
+        conflicts: Vec::new(),
+    };
+    let entity = "roadmap";
+    let mut metadata = ours.metadata.clone();
+    let mut keys: Vec<&String> = ours.metadata.keys().chain(theirs.metadata.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let base = base.map(|b| b.metadata.get(key).cloned());
+        let (o, t) = (
+            ours.metadata.get(key).cloned(),
+            theirs.metadata.get(key).cloned(),
+        );
+        let entity = format!("metadata '{}'", key);
+        match merger.value(&entity, "value", base.as_ref(), &o, &t) {
+            Some(value) => metadata.insert(key.clone(), value),
+            None => metadata.remove(key),
+        };
+    }
+    let mut roadmap = Roadmap {
+        name: merger.value(
+            entity,
+            "name",
+            base.map(|b| &b.name),
+            &ours.name,
+            &theirs.name,
+        ),
+        description: merger.value(
+            entity,
+            "description",
+            base.map(|b| &b.description),
+            &ours.description,
+            &theirs.description,
+        ),
+        version: merger.value(
+            entity,
+            "version",
+            base.map(|b| &b.version),
+            &ours.version,
+            &theirs.version,
+        ),
+        milestones: merger.list(
+            base.map(|b| b.milestones.as_slice()),
+            &ours.milestones,
+            &theirs.milestones,
+            |m| key(&m.name),
+            |m| format!("milestone '{}'", m.name),
+            Merger::milestone,
+        ),
+        features: merger.list(
+            base.map(|b| b.features.as_slice()),
+            &ours.features,
+            &theirs.features,
+            |f| key(&f.title),
+            |f| format!("feature '{}'", f.title),
+            Merger::feature,
+        ),
+        extra_sections: merger.list(
+            base.map(|b| b.extra_sections.as_slice()),
+            &ours.extra_sections,
+            &theirs.extra_sections,
+            |s| key(&s.title),
+            |s| format!("section '{}'", s.title),
+            Merger::extra_section,
+        ),
+        metadata,
+        span: None,
+        warnings: Vec::new(),
+    };
+    graph::resolve(&mut roadmap);
+    Merge {
+        roadmap,
+        conflicts: merger.conflicts,
+    }
+}
+
+fn key(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+fn json(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+struct Merger {
+    prefer: Side,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merger {
+    /// Three-way merge of a single value.
+    fn value<T: Clone + PartialEq + Serialize>(
+        &mut self,
+        entity: &str,
+        field: &'static str,
+        base: Option<&T>,
+        ours: &T,
+        theirs: &T,
+    ) -> T {
+        if ours == theirs || base == Some(theirs) {
+            return ours.clone();
+        }
+        if base == Some(ours) {
+            return theirs.clone();
+        }
+        self.conflicts.push(Conflict {
+            entity: entity.to_string(),
+            kind: ConflictKind::Changed {
+                field,
+                base: base.map(json),
+                ours: json(ours),
+                theirs: json(theirs),
+            },
+        });
+        match self.prefer {
+            Side::Ours => ours.clone(),
+            Side::Theirs => theirs.clone(),
+        }
+    }
+
+    /// Three-way merge of a list of entities matched by `key`. Entities
+    /// only `theirs` has are placed after the entity preceding them there.
+    fn list<T: Clone + PartialEq>(
+        &mut self,
+        base: Option<&[T]>,
+        ours: &[T],
+        theirs: &[T],
+        key: impl Fn(&T) -> String,
+        describe: impl Fn(&T) -> String,
+        item: impl Fn(&mut Self, Option<&T>, &T, &T) -> T,
+    ) -> Vec<T> {
+        let find = |items: &[T], k: &str| items.iter().find(|i| key(i) == k).cloned();
+        let mut out: Vec<(String, T)> = Vec::new();
+        for o in ours {
+            let k = key(o);
+            let b = base.and_then(|base| find(base, &k));
+            match (find(theirs, &k), b) {
+                (Some(t), b) => out.push((k, item(self, b.as_ref(), o, &t))),
+                (None, None) => out.push((k, o.clone())),
+                (None, Some(b)) if b == *o => {}
+                (None, Some(_)) => {
+                    if self.removed(describe(o), Side::Theirs) {
+                        out.push((k, o.clone()));
+                    }
+                }
+            }
+        }
+        let mut last = None;
+        for t in theirs {
+            let k = key(t);
+            if ours.iter().any(|o| key(o) == k) {
+                last = out.iter().position(|(key, _)| *key == k);
+                continue;
+            }
+            let keep = match base.and_then(|base| find(base, &k)) {
+                None => true,
+                Some(b) if b == *t => false,
+                Some(_) => self.removed(describe(t), Side::Ours),
+            };
+            if keep {
+                let at = last.map_or(0, |p| p + 1);
+                out.insert(at, (k, t.clone()));
+                last = Some(at);
+            }
+        }
+        out.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Record that the side `by` removed `entity` while the other changed
+    /// it, returning whether the changed entity is kept.
+    fn removed(&mut self, entity: String, by: Side) -> bool {
+        self.conflicts.push(Conflict {
+            entity,
+            kind: ConflictKind::Removed { by },
+        });
+        self.prefer != by
+    }
+
+    fn milestone(
+        &mut self,
+        base: Option<&Milestone>,
+        ours: &Milestone,
+        theirs: &Milestone,
+    ) -> Milestone {
+        let entity = format!("milestone '{}'", ours.name);
+        Milestone {
+            name: self.value(
+                &entity,
+                "name",
+                base.map(|b| &b.name),
+                &ours.name,
+                &theirs.name,
+            ),
+            due_date: self.value(
+                &entity,
+                "due_date",
+                base.map(|b| &b.due_date),
+                &ours.due_date,
+                &theirs.due_date,
+            ),
+            description: self.value(
+                &entity,
+                "description",
+                base.map(|b| &b.description),
+                &ours.description,
+                &theirs.description,
+            ),
+            file: ours.file.clone(),
+            span: None,
+        }
+    }
+
+    fn extra_section(
+        &mut self,
+        base: Option<&ExtraSection>,
+        ours: &ExtraSection,
+        theirs: &ExtraSection,
+    ) -> ExtraSection {
+        let entity = format!("section '{}'", ours.title);
+        ExtraSection {
+            title: ours.title.clone(),
+            level: self.value(
+                &entity,
+                "level",
+                base.map(|b| &b.level),
+                &ours.level,
+                &theirs.level,
+            ),
+            body: self.value(
+                &entity,
+                "body",
+                base.map(|b| &b.body),
+                &ours.body,
+                &theirs.body,
+            ),
+            after: self.value(
+                &entity,
+                "after",
+                base.map(|b| &b.after),
+                &ours.after,
+                &theirs.after,
+            ),
+            span: None,
+        }
+    }
+
+    fn feature(&mut self, base: Option<&Feature>, ours: &Feature, theirs: &Feature) -> Feature {
+        let entity = format!("feature '{}'", ours.title);
+        macro_rules! value {
+            ($field:ident) => {
+                self.value(
+                    &entity,
+                    stringify!($field),
+                    base.map(|b| &b.$field),
+                    &ours.$field,
+                    &theirs.$field,
+                )
+            };
+        }
+        let title = ours.title.clone();
+        let description = value!(description);
+        // Links and images are read from the description, so follow it.
+        let side = if description == theirs.description {
+            theirs
+        } else {
+            ours
+        };
+        Feature {
+            title: value!(title),
+            description,
+            links: side.links.clone(),
+            images: side.images.clone(),
+            milestone: value!(milestone),
+            milestone_id: None,
+            labels: set(base.map(|b| &b.labels[..]), &ours.labels, &theirs.labels),
+            assignees: set(
+                base.map(|b| &b.assignees[..]),
+                &ours.assignees,
+                &theirs.assignees,
+            ),
+            tasks: self.list(
+                base.map(|b| b.tasks.as_slice()),
+                &ours.tasks,
+                &theirs.tasks,
+                |t| key(&t.title),
+                |t| format!("task '{}' of feature '{}'", t.title, title),
+                |merger, b, o, t| merger.task(&title, b, o, t),
+            ),
+            kind: value!(kind),
+            status: value!(status),
+            estimate: value!(estimate),
+            priority: value!(priority),
+            issue: value!(issue),
+            repo: value!(repo),
+            fields: value!(fields),
+            children: self.list(
+                base.map(|b| b.children.as_slice()),
+                &ours.children,
+                &theirs.children,
+                |f| key(&f.title),
+                |f| format!("feature '{}'", f.title),
+                Merger::feature,
+            ),
+            depends_on: set(
+                base.map(|b| &b.depends_on[..]),
+                &ours.depends_on,
+                &theirs.depends_on,
+            ),
+            dependencies: Vec::new(),
+            file: ours.file.clone(),
+            span: None,
+        }
+    }
+
+    fn task(&mut self, feature: &str, base: Option<&Task>, ours: &Task, theirs: &Task) -> Task {
+        let entity = format!("task '{}' of feature '{}'", ours.title, feature);
+        macro_rules! value {
+            ($field:ident) => {
+                self.value(
+                    &entity,
+                    stringify!($field),
+                    base.map(|b| &b.$field),
+                    &ours.$field,
+                    &theirs.$field,
+                )
+            };
+        }
+        Task {
+            title: value!(title),
+            description: value!(description),
+            labels: set(base.map(|b| &b.labels[..]), &ours.labels, &theirs.labels),
+            assignees: set(
+                base.map(|b| &b.assignees[..]),
+                &ours.assignees,
+                &theirs.assignees,
+            ),
+            tests: set(base.map(|b| &b.tests[..]), &ours.tests, &theirs.tests),
+            completed: value!(completed),
+            estimate: value!(estimate),
+            depth: value!(depth),
+            span: None,
+        }
+    }
+}
+
+/// Three-way merge of a set kept as a list: members either side added are
+/// kept, members either side removed are dropped.
+fn set(base: Option<&[String]>, ours: &[String], theirs: &[String]) -> Vec<String> {
+    let in_base = |v: &String| base.is_some_and(|b| b.contains(v));
+    let mut out: Vec<String> = ours
+        .iter()
+        .filter(|v| theirs.contains(v) || !in_base(v))
+        .cloned()
+        .collect();
+    out.extend(
+        theirs
+            .iter()
+            .filter(|v| !ours.contains(v) && !in_base(v))
+            .cloned(),
+    );
+    out
+}
+
+/// Combine two renderings of a merge, one resolved in favour of each side,
+/// into one document with git-style markers around the lines that differ.
+pub fn conflict_markers(ours: &str, theirs: &str, labels: (&str, &str)) -> String {
+    let a: Vec<&str> = ours.lines().collect();
+    let b: Vec<&str> = theirs.lines().collect();
+    let width = b.len() + 1;
+    // lcs[i * width + j]: length of the longest common subsequence of
+    // a[i..] and b[j..].
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let mut out = String::new();
+    let (mut ours_hunk, mut theirs_hunk) = (Vec::new(), Vec::new());
+    let flush = |out: &mut String, ours: &mut Vec<&str>, theirs: &mut Vec<&str>| {
+        if ours.is_empty() && theirs.is_empty() {
+            return;
+        }
+        out.push_str(&format!("<<<<<<< {}\n", labels.0));
+        for line in ours.drain(..) {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push_str("=======\n");
+        for line in theirs.drain(..) {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push_str(&format!(">>>>>>> {}\n", labels.1));
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            flush(&mut out, &mut ours_hunk, &mut theirs_hunk);
+            out.push_str(a[i]);
+            out.push('\n');
+            i += 1;
+            j += 1;
+        } else if j == b.len()
+            || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            ours_hunk.push(a[i]);
+            i += 1;
+        } else {
+            theirs_hunk.push(b[j]);
+            j += 1;
+        }
+    }
+    flush(&mut out, &mut ours_hunk, &mut theirs_hunk);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const BASE: &str = "# P\n\n## Milestones\n- M1 — 2025-01-31\n\n## Features\n\n\
+        ### Login\nMilestone: M1\nLabels: auth\n\n- [ ] Form\n\n### Search\n\n### Export\n";
+
+    #[test]
+    fn merges_changes_to_different_fields_and_entities() {
+        let base = parse_roadmap(BASE);
+        let ours = parse_roadmap(
+            &BASE
+                .replace("Labels: auth", "Labels: auth, ui")
+                .replace("### Search\n", "### Search\nFind things.\n"),
+        );
+        let theirs = parse_roadmap(
+            &BASE
+                .replace("- [ ] Form", "- [x] Form")
+                .replace("### Export\n", "### Dark mode\n"),
+        );
+        let merge = merge(Some(&base), &ours, &theirs, Side::Ours);
+        assert!(merge.conflicts.is_empty(), "{:?}", merge.conflicts);
+        let titles: Vec<&str> = merge
+            .roadmap
+            .features
+            .iter()
+            .map(|f| f.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Login", "Search", "Dark mode"]);
+        let login = &merge.roadmap.features[0];
+        assert_eq!(login.labels, ["auth", "ui"]);
+        assert!(login.tasks[0].completed);
+        assert_eq!(merge.roadmap.features[1].description.trim(), "Find things.");
+    }
+
+    #[test]
+    fn reports_and_marks_conflicts() {
+        let base = parse_roadmap(BASE);
+        let ours = parse_roadmap(&BASE.replace("Milestone: M1", "Milestone: M2"));
+        let theirs = parse_roadmap(
+            &BASE
+                .replace("Milestone: M1", "Milestone: M3")
+                .replace("### Search\n\n", ""),
+        );
+        let ours = Roadmap {
+            features: ours
+                .features
+                .into_iter()
+                .map(|mut f| {
+                    if f.title == "Search" {
+                        f.description = "Changed.".into();
+                    }
+                    f
+                })
+                .collect(),
+            ..ours
+        };
+        let by_ours = merge(Some(&base), &ours, &theirs, Side::Ours);
+        let by_theirs = merge(Some(&base), &ours, &theirs, Side::Theirs);
+        let conflicts: Vec<String> = by_ours.conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            conflicts,
+            [
+                "feature 'Login': milestone is \"M2\" in ours but \"M3\" in theirs (was \"M1\")",
+                "feature 'Search': changed in ours but removed in theirs",
+            ]
+        );
+        assert_eq!(by_ours.roadmap.features.len(), 3);
+        assert_eq!(by_theirs.roadmap.features.len(), 2);
+        assert_eq!(
+            serde_json::to_value(&by_ours.conflicts[1]).unwrap(),
+            serde_json::json!({"entity": "feature 'Search'", "conflict": "removed", "by": "theirs"})
+        );
+
+        let marked = conflict_markers("a\nb\nc\n", "a\nB\nc\nd\n", ("ours", "theirs"));
+        assert_eq!(
+            marked,
+            "a\n<<<<<<< ours\nb\n=======\nB\n>>>>>>> theirs\nc\n<<<<<<< ours\n=======\nd\n>>>>>>> theirs\n"
+        );
+    }
+}
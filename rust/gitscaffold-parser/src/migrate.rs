@@ -0,0 +1,269 @@
+//! Versions of the roadmap format and upgrades between them.
+//!
+//! A roadmap declares the format it is written in with a `version:` entry,
+//! in the frontmatter of a Markdown file or at the top of a YAML file.
+//! Undeclared roadmaps are parsed as the current format, but [`migrate`]
+//! treats them as version 1, the format of the Python gitscaffold, and
+//! records the version it brings them to. Each step in [`STEPS`] upgrades
+//! a file by one version, so a file can be migrated from any older version.
+
+use serde_yaml::{Mapping, Value};
+
+use crate::error::ParseError;
+use crate::format::InputFormat;
+use crate::roadmap::{ParseWarning, WarningKind};
+
+/// The format version this parser reads and [`migrate`] writes.
+pub const FORMAT_VERSION: u32 = 2;
+
+struct Step {
+    /// The version the step upgrades from, to the next one.
+    from: u32,
+    summary: &'static str,
+    markdown: Option<fn(&str) -> String>,
+    yaml: Option<fn(&mut Value)>,
+}
+
+const STEPS: &[Step] = &[Step {
+    from: 1,
+    summary:
+        "tasks written as plain strings become task mappings and null descriptions are dropped",
+    markdown: None,
+    yaml: Some(structured_tasks),
+}];
+
+/// A roadmap file brought to [`FORMAT_VERSION`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// The version the file was in; 1 when it declared none.
+    pub from: u32,
+    /// What each step that applies to the file's format did, in order.
+    pub steps: Vec<&'static str>,
+    /// The upgraded file, identical to the input when there was nothing to
+    /// do.
+    pub text: String,
+}
+
+/// Upgrade `content`, read from `path`, to [`FORMAT_VERSION`]. Markdown is
+/// edited in place; YAML is rewritten, losing its comments, only when a
+/// step has to restructure it. A file newer than the parser is an error.
+pub fn migrate(path: &str, content: &str, format: InputFormat) -> Result<Migration, ParseError> {
+    let yaml = format.resolve(path, content) == InputFormat::Yaml;
+    let declared = if yaml {
+        serde_yaml::from_str::<Value>(content)?
+            .get("version")
+            .and_then(yaml_version)
+    } else {
+        frontmatter_version(content)
+    };
+    let from = declared.unwrap_or(1);
+    if from > FORMAT_VERSION {
+        return Err(ParseError::FormatVersion { version: from });
+    }
+    let steps: Vec<&Step> = STEPS
+        .iter()
+        .filter(|step| step.from >= from)
+        .filter(|step| {
+            if yaml {
+                step.yaml.is_some()
+            } else {
+                step.markdown.is_some()
+            }
+        })
+        .collect();
+    let mut text = content.to_string();
+    if yaml {
+        let mut value: Value = serde_yaml::from_str(content)?;
+        let original = value.clone();
+        for yaml in steps.iter().filter_map(|step| step.yaml) {
+            yaml(&mut value);
+        }
+        if value != original {
+            text = serde_yaml::to_string(&versioned(value))?;
+        }
+    } else {
+        for markdown in steps.iter().filter_map(|step| step.markdown) {
+            text = markdown(&text);
+        }
+    }
+    if declared != Some(FORMAT_VERSION) {
+        text = stamp(&text, yaml);
+    }
+    Ok(Migration {
+        from,
+        steps: steps.iter().map(|step| step.summary).collect(),
+        text,
+    })
+}
+
+/// A warning for a roadmap declaring `version` when it is not the current
+/// format.
+pub(crate) fn check(version: Option<u32>) -> Option<ParseWarning> {
+    let version = version?;
+    let message = if version > FORMAT_VERSION {
+        format!(
+            "roadmap format version {} is newer than version {}, the latest this parser reads; \
+             upgrade scaffold",
+            version, FORMAT_VERSION
+        )
+    } else if version < FORMAT_VERSION {
+        format!(
+            "roadmap format version {} is outdated; run `scaffold migrate` to upgrade it to \
+             version {}",
+            version, FORMAT_VERSION
+        )
+    } else {
+        return None;
+    };
+    Some(ParseWarning {
+        kind: WarningKind::FormatVersion,
+        message,
+        file: None,
+        span: None,
+    })
+}
+
+/// The version a `version:` frontmatter or YAML value names: a number or
+/// a string holding one.
+pub(crate) fn json_version(value: &serde_json::Value) -> Option<u32> {
+    match value {
+        serde_json::Value::Number(number) => number.as_u64()?.try_into().ok(),
+        serde_json::Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn yaml_version(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(number) => number.as_u64()?.try_into().ok(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn frontmatter_version(content: &str) -> Option<u32> {
+    let (metadata, _) = crate::frontmatter::split(content)?;
+    json_version(metadata.get("version")?)
+}
+
+/// `value` with a `version` key first, set to [`FORMAT_VERSION`].
+fn versioned(value: Value) -> Value {
+    let Value::Mapping(mapping) = value else {
+        return value;
+    };
+    let mut out = Mapping::new();
+    out.insert("version".into(), FORMAT_VERSION.into());
+    for (key, value) in mapping {
+        if key.as_str() != Some("version") {
+            out.insert(key, value);
+        }
+    }
+    Value::Mapping(out)
+}
+
+/// Record [`FORMAT_VERSION`] in `text`: replace the `version:` line of the
+/// frontmatter or YAML document, or add one at its top. Markdown without
+/// frontmatter gets a frontmatter block.
+fn stamp(text: &str, yaml: bool) -> String {
+    let line = format!("version: {}\n", FORMAT_VERSION);
+    let has_frontmatter = !yaml && crate::frontmatter::split(text).is_some();
+    // The lines that may hold the version, and where they start.
+    let (start, end) = if yaml {
+        let start = if text.starts_with("---\n") { 4 } else { 0 };
+        (start, text.len())
+    } else if has_frontmatter {
+        let (_, body) = crate::frontmatter::split(text).unwrap_or_default();
+        (text.find('\n').map_or(0, |newline| newline + 1), body)
+    } else {
+        return format!("---\n{}---\n\n{}", line, text);
+    };
+    let mut offset = start;
+    for existing in text[start..end].split_inclusive('\n') {
+        if existing.starts_with("version:") {
+            let after = offset + existing.len();
+            return format!("{}{}{}", &text[..offset], line, &text[after..]);
+        }
+        offset += existing.len();
+    }
+    format!("{}{}{}", &text[..start], line, &text[start..])
+}
+
+/// Version 1 to 2: tasks may be plain strings, as the Python gitscaffold
+/// wrote them, and descriptions may be null.
+fn structured_tasks(value: &mut Value) {
+    fn drop_null_description(mapping: &mut Mapping) {
+        if mapping.get("description").is_some_and(Value::is_null) {
+            mapping.shift_remove("description");
+        }
+    }
+    fn feature(value: &mut Value) {
+        let Value::Mapping(mapping) = value else {
+            return;
+        };
+        drop_null_description(mapping);
+        if let Some(Value::Sequence(tasks)) = mapping.get_mut("tasks") {
+            for task in tasks {
+                if let Value::String(title) = task {
+                    let mut mapping = Mapping::new();
+                    mapping.insert("title".into(), Value::String(std::mem::take(title)));
+                    *task = Value::Mapping(mapping);
+                } else if let Value::Mapping(task) = task {
+                    drop_null_description(task);
+                }
+            }
+        }
+        if let Some(Value::Sequence(children)) = mapping.get_mut("children") {
+            children.iter_mut().for_each(feature);
+        }
+    }
+    let Value::Mapping(roadmap) = value else {
+        return;
+    };
+    drop_null_description(roadmap);
+    if let Some(Value::Sequence(milestones)) = roadmap.get_mut("milestones") {
+        for milestone in milestones {
+            if let Value::Mapping(milestone) = milestone {
+                drop_null_description(milestone);
+            }
+        }
+    }
+    if let Some(Value::Sequence(features)) = roadmap.get_mut("features") {
+        features.iter_mut().for_each(feature);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_python_era_yaml_and_stamps_markdown() {
+        let yaml = "name: App\ndescription: null\nfeatures:\n- title: Login\n  tasks:\n  - Form\n";
+        let migration = migrate("roadmap.yml", yaml, InputFormat::Auto).unwrap();
+        assert_eq!(migration.from, 1);
+        assert_eq!(migration.steps.len(), 1);
+        assert_eq!(
+            migration.text,
+            "version: 2\nname: App\nfeatures:\n- title: Login\n  tasks:\n  - title: Form\n"
+        );
+        let roadmap = crate::parse_yaml(&migration.text).unwrap();
+        assert_eq!(roadmap.version, Some(FORMAT_VERSION));
+        assert_eq!(roadmap.features[0].tasks[0].title, "Form");
+
+        let markdown = "---\nrepo: acme/app\nversion: 1\n---\n# App\n";
+        let migration = migrate("ROADMAP.md", markdown, InputFormat::Auto).unwrap();
+        assert_eq!(
+            migration.text,
+            "---\nrepo: acme/app\nversion: 2\n---\n# App\n"
+        );
+        assert!(migration.steps.is_empty());
+        let migration = migrate("ROADMAP.md", "# App\n", InputFormat::Auto).unwrap();
+        assert_eq!(migration.text, "---\nversion: 2\n---\n\n# App\n");
+
+        let error = migrate("ROADMAP.md", "---\nversion: 9\n---\n", InputFormat::Auto);
+        assert!(matches!(
+            error,
+            Err(ParseError::FormatVersion { version: 9 })
+        ));
+    }
+}
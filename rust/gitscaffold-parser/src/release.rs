@@ -0,0 +1,244 @@
+//! Release notes: the features and tasks a roadmap finished, grouped by
+//! milestone and label.
+//!
+//! [`shipped_since`] compares the roadmap with an earlier version of it,
+//! such as the one at the last release's tag; [`shipped_features`] takes
+//! the features some other source says were finished, like the issues
+//! closed on the forge. A sub-feature without a milestone belongs to its
+//! parent's, and each item is listed under its first label only.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::roadmap::{Feature, IssueLink, Roadmap};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReleaseNotes {
+    /// In the roadmap's milestone order, with the features without a
+    /// milestone last.
+    pub milestones: Vec<MilestoneNotes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MilestoneNotes {
+    /// `None` for the features without a milestone.
+    pub name: Option<String>,
+    /// In the order their labels first appear, with unlabelled items last.
+    pub groups: Vec<LabelGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabelGroup {
+    /// `None` for the items without labels.
+    pub label: Option<String>,
+    pub items: Vec<Item>,
+}
+
+/// A finished feature or task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Item {
+    pub title: String,
+    /// For a task, the title of its feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    /// The issue of the feature, from its heading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue: Option<IssueLink>,
+    pub labels: Vec<String>,
+}
+
+impl ReleaseNotes {
+    pub fn is_empty(&self) -> bool {
+        self.milestones.is_empty()
+    }
+
+    /// How many features and tasks the notes list.
+    pub fn len(&self) -> usize {
+        self.milestones
+            .iter()
+            .flat_map(|milestone| &milestone.groups)
+            .map(|group| group.items.len())
+            .sum()
+    }
+}
+
+/// What `roadmap` finished since `previous`, an earlier version of it: the
+/// features done now that were not done then, and the tasks completed
+/// since in the features still under way. Without `previous` everything
+/// finished counts. Features are matched by title.
+pub fn shipped_since(roadmap: &Roadmap, previous: Option<&Roadmap>) -> ReleaseNotes {
+    let mut before = HashMap::new();
+    if let Some(previous) = previous {
+        walk(&previous.features, None, &mut |feature, _| {
+            before.insert(feature.title.trim(), feature);
+        });
+    }
+    let mut items = Vec::new();
+    walk(&roadmap.features, None, &mut |feature, milestone| {
+        let old = before.get(feature.title.trim());
+        if feature.is_done() {
+            if !old.is_some_and(|old| old.is_done()) {
+                items.push((milestone, feature_item(feature)));
+            }
+            return;
+        }
+        for task in feature.tasks.iter().filter(|task| task.completed) {
+            let done_before = old.is_some_and(|old| {
+                old.tasks
+                    .iter()
+                    .any(|old| old.completed && old.title.trim() == task.title.trim())
+            });
+            if done_before {
+                continue;
+            }
+            let labels = match task.labels.is_empty() {
+                true => feature.labels.clone(),
+                false => task.labels.clone(),
+            };
+            items.push((
+                milestone,
+                Item {
+                    title: task.title.clone(),
+                    feature: Some(feature.title.clone()),
+                    issue: feature.issue.clone(),
+                    labels,
+                },
+            ));
+        }
+    });
+    group(roadmap, items)
+}
+
+/// The features of `roadmap` that `shipped` says were finished, e.g. those
+/// whose issues were closed since the last release.
+pub fn shipped_features(roadmap: &Roadmap, shipped: impl Fn(&Feature) -> bool) -> ReleaseNotes {
+    let mut items = Vec::new();
+    walk(&roadmap.features, None, &mut |feature, milestone| {
+        if shipped(feature) {
+            items.push((milestone, feature_item(feature)));
+        }
+    });
+    group(roadmap, items)
+}
+
+fn feature_item(feature: &Feature) -> Item {
+    Item {
+        title: feature.title.clone(),
+        feature: None,
+        issue: feature.issue.clone(),
+        labels: feature.labels.clone(),
+    }
+}
+
+/// Call `visit` with every feature, sub-features included, and the
+/// milestone it belongs to.
+fn walk<'a>(
+    features: &'a [Feature],
+    inherited: Option<&'a str>,
+    visit: &mut impl FnMut(&'a Feature, Option<&'a str>),
+) {
+    for feature in features {
+        let milestone = feature.milestone.as_deref().or(inherited);
+        visit(feature, milestone);
+        walk(&feature.children, milestone, visit);
+    }
+}
+
+fn group(roadmap: &Roadmap, items: Vec<(Option<&str>, Item)>) -> ReleaseNotes {
+    let mut order: Vec<Option<&str>> = roadmap
+        .milestones
+        .iter()
+        .map(|milestone| Some(milestone.name.as_str()))
+        .collect();
+    for (milestone, _) in &items {
+        if !order.contains(milestone) {
+            order.push(*milestone);
+        }
+    }
+    // Features without a milestone go last.
+    order.retain(Option::is_some);
+    order.push(None);
+
+    let mut milestones = Vec::new();
+    for name in order {
+        let mut groups: Vec<LabelGroup> = Vec::new();
+        for (_, item) in items.iter().filter(|(milestone, _)| *milestone == name) {
+            let label = item.labels.first().cloned();
+            match groups.iter_mut().find(|group| group.label == label) {
+                Some(group) => group.items.push(item.clone()),
+                None => groups.push(LabelGroup {
+                    label,
+                    items: vec![item.clone()],
+                }),
+            }
+        }
+        if groups.is_empty() {
+            continue;
+        }
+        groups.sort_by_key(|group| group.label.is_none());
+        milestones.push(MilestoneNotes {
+            name: name.map(str::to_string),
+            groups,
+        });
+    }
+    ReleaseNotes { milestones }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_markdown;
+
+    #[test]
+    fn lists_what_was_finished_since_the_previous_version() {
+        let before = parse_markdown(
+            "# App\n\n## Milestones\n\n- **v1**\n- **v2**\n\n## Features\n\n\
+             ### Login\n\nMilestone: v1\n\n- [x] Form\n- [ ] Session\n\n\
+             ### Search\n\nMilestone: v2\n\n- [ ] Index\n- [ ] Query\n\n\
+             ### Export [done]\n",
+        )
+        .unwrap();
+        let after = parse_markdown(
+            "# App\n\n## Milestones\n\n- **v1**\n- **v2**\n\n## Features\n\n\
+             ### Login\n\nMilestone: v1\nLabels: auth\n\n- [x] Form\n- [x] Session\n\n\
+             ### Search\n\nMilestone: v2\n\n- [x] Index\n- [ ] Query\n\n\
+             ### Export [done]\n\n### Import [done]\n\nLabels: data\n",
+        )
+        .unwrap();
+
+        let notes = shipped_since(&after, Some(&before));
+        let titles: Vec<(Option<&str>, Option<&str>, &str)> = notes
+            .milestones
+            .iter()
+            .flat_map(|milestone| {
+                milestone.groups.iter().flat_map(move |group| {
+                    group.items.iter().map(move |item| {
+                        (
+                            milestone.name.as_deref(),
+                            group.label.as_deref(),
+                            item.title.as_str(),
+                        )
+                    })
+                })
+            })
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                (Some("v1"), Some("auth"), "Login"),
+                (Some("v2"), None, "Index"),
+                (None, Some("data"), "Import"),
+            ]
+        );
+        assert_eq!(
+            notes.milestones[1].groups[0].items[0].feature.as_deref(),
+            Some("Search")
+        );
+        assert_eq!(shipped_since(&after, None).len(), 4);
+
+        let notes = shipped_features(&after, |feature| feature.title == "Search");
+        assert_eq!(notes.len(), 1);
+        assert!(shipped_features(&after, |_| false).is_empty());
+    }
+}
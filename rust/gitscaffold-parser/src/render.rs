@@ -0,0 +1,391 @@
+//! Render a [`Roadmap`] back to canonical Markdown.
+//!
+//! The output uses the layout the extractor understands best — `#` for the
+//! name, `##` for the Milestones and Features sections, `###` per feature
+//! and `####` per detailed task, with sub-features one level deeper than
+//! their parent and marked by a `Kind:` line — so parsing the rendered
+//! document yields the same roadmap again (minus source spans). Metadata is
+//! written back as YAML frontmatter with sorted keys, and sections the
+//! extractor did not recognize are re-emitted verbatim where they were.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::roadmap::{Anchor, Estimate, Feature, FeatureKind, Milestone, Roadmap, Task};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    /// Sort milestones by due date (undated last) and features by title.
+    pub sort: bool,
+}
+
+pub fn render_markdown(roadmap: &Roadmap, options: RenderOptions) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut frontmatter = String::new();
+    if let Some(version) = roadmap.version {
+        frontmatter.push_str(&format!("version: {}\n", version));
+    }
+    if !roadmap.metadata.is_empty() {
+        let sorted: BTreeMap<_, _> = roadmap.metadata.iter().collect();
+        // Serializing a map of JSON values cannot fail.
+        frontmatter.push_str(&serde_yaml::to_string(&sorted).unwrap_or_default());
+    }
+    if !frontmatter.is_empty() {
+        blocks.push(format!("---\n{}---", frontmatter));
+    }
+    if !roadmap.name.is_empty() {
+        blocks.push(format!("# {}", roadmap.name));
+    }
+    push_text(&mut blocks, &roadmap.description);
+    push_extra(&mut blocks, roadmap, Anchor::Title);
+
+    let mut milestones: Vec<&Milestone> = roadmap.milestones.iter().collect();
+    let mut features: Vec<&Feature> = roadmap.features.iter().collect();
+    if options.sort {
+        milestones.sort_by(|a, b| compare_due(a, b));
+        features.sort_by_key(|f| f.title.to_lowercase());
+    }
+
+    if milestones.iter().any(|m| !m.description.is_empty()) {
+        // Descriptions need a section per milestone.
+        blocks.push("## Milestones".into());
+        for milestone in &milestones {
+            blocks.push(match &milestone.due_date {
+                Some(due) => format!("### {} — {}", milestone.name, due),
+                None => format!("### {}", milestone.name),
+            });
+            push_text(&mut blocks, &milestone.description);
+        }
+    } else if !milestones.is_empty() {
+        blocks.push("## Milestones".into());
+        let lines: Vec<String> = milestones
+            .iter()
+            .map(|m| match &m.due_date {
+                Some(due) => format!("- **{}** — {}", m.name, due),
+                None => format!("- **{}**", m.name),
+            })
+            .collect();
+        blocks.push(lines.join("\n"));
+    }
+    push_extra(&mut blocks, roadmap, Anchor::Milestones);
+
+    blocks.push("## Features".into());
+    for feature in features {
+        render_feature(&mut blocks, feature, 3);
+    }
+    push_extra(&mut blocks, roadmap, Anchor::Features);
+
+    let mut out = blocks.join("\n\n");
+    out.push('\n');
+    out
+}
+
+fn compare_due(a: &Milestone, b: &Milestone) -> Ordering {
+    match (&a.due_date, &b.due_date) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Re-emit the extra sections that go after `anchor`, in order.
+fn push_extra(blocks: &mut Vec<String>, roadmap: &Roadmap, anchor: Anchor) {
+    for section in roadmap.extra_sections.iter().filter(|s| s.after == anchor) {
+        let level = section.level.clamp(1, 6) as usize;
+        blocks.push(format!("{} {}", "#".repeat(level), section.title));
+        push_text(blocks, &section.body);
+    }
+}
+
+fn push_text(blocks: &mut Vec<String>, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        blocks.push(text.to_string());
+    }
+}
+
+fn render_feature(blocks: &mut Vec<String>, feature: &Feature, level: usize) {
+    let mut heading = format!("{} {}", "#".repeat(level), feature.title);
+    if let Some(issue) = &feature.issue {
+        heading.push_str(&format!(" {}", issue));
+    }
+    blocks.push(heading);
+    push_text(blocks, &feature.description);
+
+    let mut metadata = Vec::new();
+    // Sub-features always name their kind, since that is what tells them
+    // apart from task headings; top-level features only when it differs
+    // from the kind the extractor infers.
+    let inferred = if feature.children.is_empty() {
+        FeatureKind::Feature
+    } else {
+        FeatureKind::Epic
+    };
+    if level > 3 || feature.kind != inferred {
+        metadata.push(format!("Kind: {}", feature.kind.name()));
+    }
+    if let Some(status) = &feature.status {
+        metadata.push(format!("Status: {}", status.name()));
+    }
+    if let Some(estimate) = &feature.estimate {
+        metadata.push(format!("Estimate: {}", estimate));
+    }
+    if let Some(priority) = &feature.priority {
+        metadata.push(format!("Priority: {}", priority));
+    }
+    if let Some(milestone) = &feature.milestone {
+        metadata.push(format!("Milestone: {}", milestone));
+    }
+    if !feature.labels.is_empty() {
+        metadata.push(format!("Labels: {}", feature.labels.join(", ")));
+    }
+    if !feature.assignees.is_empty() {
+        metadata.push(format!("Assignees: {}", feature.assignees.join(", ")));
+    }
+    if !feature.depends_on.is_empty() {
+        metadata.push(format!("Depends on: {}", feature.depends_on.join(", ")));
+    }
+    if let Some(repo) = &feature.repo {
+        metadata.push(format!("Repo: {}", repo));
+    }
+    for (key, value) in &feature.fields {
+        metadata.push(format!("{}: {}", key, value));
+    }
+    if !metadata.is_empty() {
+        blocks.push(metadata.join("\n"));
+    }
+
+    // Leading tasks without details render as a checklist; the extractor
+    // reads checklists before task headings, so the rest become `####`
+    // headings to keep the task order intact.
+    let simple = feature
+        .tasks
+        .iter()
+        .take_while(|task| is_simple(task))
+        .count();
+    let (checklist, detailed) = feature.tasks.split_at(simple);
+    if !checklist.is_empty() {
+        let lines: Vec<String> = checklist
+            .iter()
+            .map(|task| {
+                let mut line = format!(
+                    "{}- [{}] {}",
+                    "  ".repeat(task.depth),
+                    checkbox(task),
+                    task.title
+                );
+                if let Some(estimate) = &task.estimate {
+                    line.push_str(&format!(" ({})", estimate));
+                }
+                line
+            })
+            .collect();
+        blocks.push(lines.join("\n"));
+    }
+    for task in detailed {
+        render_task(blocks, task, level + 1);
+    }
+    for child in &feature.children {
+        render_feature(blocks, child, level + 1);
+    }
+}
+
+fn is_simple(task: &Task) -> bool {
+    task.description.trim().is_empty()
+        && task.labels.is_empty()
+        && task.assignees.is_empty()
+        && task.tests.is_empty()
+        && task.estimate.as_ref().is_none_or(Estimate::is_valid)
+}
+
+fn checkbox(task: &Task) -> char {
+    if task.completed {
+        'x'
+    } else {
+        ' '
+    }
+}
+
+fn render_task(blocks: &mut Vec<String>, task: &Task, level: usize) {
+    blocks.push(format!(
+        "{} [{}] {}",
+        "#".repeat(level),
+        checkbox(task),
+        task.title
+    ));
+    push_text(blocks, &task.description);
+    let mut metadata = Vec::new();
+    if let Some(estimate) = &task.estimate {
+        metadata.push(format!("Estimate: {}", estimate));
+    }
+    if !task.labels.is_empty() {
+        metadata.push(format!("Labels: {}", task.labels.join(", ")));
+    }
+    if !task.assignees.is_empty() {
+        metadata.push(format!("Assignees: {}", task.assignees.join(", ")));
+    }
+    if !metadata.is_empty() {
+        blocks.push(metadata.join("\n"));
+    }
+    if !task.tests.is_empty() {
+        let mut lines = vec!["Tests:".to_string()];
+        lines.extend(task.tests.iter().map(|test| format!("- {}", test)));
+        blocks.push(lines.join("\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "\
+# Demo
+
+Intro paragraph.
+
+## Milestones
+- Beta — July 1, 2025
+-   Alpha — 2025-03-01
+
+## Features
+
+### Search
+Find things fast.
+
+Labels: backend,search
+Milestone: Beta
+
+* [x] Index
+    * [ ] Reindex job
+* [ ] Query API
+
+### Auth
+#### [x] Login form
+Has `code`.
+Assignees: alice
+
+Tests:
+- renders
+
+#### Sessions
+Kind: feature
+
+- [ ] Expiry
+";
+
+    #[test]
+    fn renders_canonical_markdown() {
+        let rendered = render_markdown(&parse_roadmap(ROADMAP), RenderOptions::default());
+        assert_eq!(
+            rendered,
+            "\
+# Demo
+
+Intro paragraph.
+
+## Milestones
+
+- **Beta** — 2025-07-01
+- **Alpha** — 2025-03-01
+
+## Features
+
+### Search
+
+Find things fast.
+
+Milestone: Beta
+Labels: backend, search
+
+- [x] Index
+  - [ ] Reindex job
+- [ ] Query API
+
+### Auth
+
+#### [x] Login form
+
+Has `code`.
+
+Assignees: alice
+
+Tests:
+- renders
+
+#### Sessions
+
+Kind: feature
+
+- [ ] Expiry
+"
+        );
+    }
+
+    #[test]
+    fn roundtrips_through_the_parser() {
+        let mut original = parse_roadmap(ROADMAP);
+        original.strip_spans();
+        let mut reparsed = parse_roadmap(&render_markdown(&original, RenderOptions::default()));
+        reparsed.strip_spans();
+        assert_eq!(reparsed, original);
+
+        // Milestone descriptions need a heading per milestone.
+        original.milestones[0].description = "Public preview.\n\nInvite only.".into();
+        let rendered = render_markdown(&original, RenderOptions::default());
+        assert!(rendered.contains("### Beta — 2025-07-01\n\nPublic preview."));
+        let mut reparsed = parse_roadmap(&rendered);
+        reparsed.strip_spans();
+        assert_eq!(reparsed, original);
+    }
+
+    #[test]
+    fn keeps_unrecognized_sections_in_place() {
+        let source = "# App\n\n## Background\n\nWhy we build it.\n\n### History\n\nLong.\n\n## Milestones\n- **v1**\n\n## Risks\n\n- Scope\n\n## Features\n\n### Login\n\n## Appendix\n\nSee the wiki.\n";
+        let mut original = parse_roadmap(source);
+        original.strip_spans();
+        let titles: Vec<_> = original
+            .extra_sections
+            .iter()
+            .map(|s| (s.title.as_str(), s.after))
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                ("Background", Anchor::Title),
+                ("Risks", Anchor::Milestones),
+                ("Appendix", Anchor::Features)
+            ]
+        );
+        assert_eq!(
+            original.extra_sections[0].body,
+            "Why we build it.\n\n### History\n\nLong."
+        );
+
+        let rendered = render_markdown(&original, RenderOptions::default());
+        assert!(rendered.contains("## Risks\n\n- Scope\n\n## Features"));
+        assert!(rendered.ends_with("## Appendix\n\nSee the wiki.\n"));
+        let mut reparsed = parse_roadmap(&rendered);
+        reparsed.strip_spans();
+        assert_eq!(reparsed, original);
+    }
+
+    #[test]
+    fn writes_metadata_as_frontmatter() {
+        let source = "---\nrepo: acme/app\nowner: jo\n---\n# Demo\n\n## Features\n\n### A\n";
+        let roadmap = parse_roadmap(source);
+        let rendered = render_markdown(&roadmap, RenderOptions::default());
+        assert!(rendered.starts_with("---\nowner: jo\nrepo: acme/app\n---\n\n# Demo\n"));
+        assert_eq!(parse_roadmap(&rendered).metadata, roadmap.metadata);
+    }
+
+    #[test]
+    fn sorts_milestones_by_due_date_and_features_by_title() {
+        let rendered = render_markdown(&parse_roadmap(ROADMAP), RenderOptions { sort: true });
+        let alpha = rendered.find("**Alpha**").unwrap();
+        let beta = rendered.find("**Beta**").unwrap();
+        assert!(alpha < beta);
+        assert!(rendered.find("### Auth").unwrap() < rendered.find("### Search").unwrap());
+    }
+}
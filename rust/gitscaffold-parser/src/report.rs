@@ -0,0 +1,295 @@
+//! A roadmap as a self-contained HTML page, for publishing on GitHub Pages
+//! or anywhere else that serves static files.
+//!
+//! The page inlines its stylesheet and loads nothing else. Milestones are
+//! collapsible sections in the order [`stats`](crate::stats::stats) lists
+//! them, each with a progress bar of its completed tasks, and every feature
+//! gets an anchor derived from its title, so that `page.html#login` links
+//! to it. Sub-features are shown below their parent. Descriptions are
+//! shown as plain text.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use chrono::NaiveDate;
+
+use crate::roadmap::{Feature, Roadmap};
+use crate::stats::{stats, Counts, MilestoneStats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum PageFormat {
+    /// Self-contained HTML page
+    Html,
+}
+
+const STYLE: &str = "
+body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; color: #1f2328; max-width: 960px; margin: 0 auto; padding: 2rem 1rem; line-height: 1.5; }
+a { color: #0969da; text-decoration: none; }
+a:hover { text-decoration: underline; }
+h1 { margin-bottom: 0.25rem; }
+details { border: 1px solid #d0d7de; border-radius: 6px; margin: 1rem 0; padding: 0 1rem; }
+summary { cursor: pointer; padding: 0.75rem 0; font-weight: 600; }
+summary h2 { display: inline; font-size: 1.25rem; margin: 0; }
+.meta { color: #59636e; font-weight: normal; font-size: 0.875rem; margin-left: 0.5rem; }
+.overdue { color: #d1242f; }
+.progress { background: #eaeef2; border-radius: 4px; height: 8px; overflow: hidden; margin: 0.25rem 0; }
+.progress > div { background: #1f883d; height: 100%; }
+.feature { border-top: 1px solid #eaeef2; padding: 0.75rem 0; }
+.feature .feature { margin-left: 1.5rem; border-top: none; padding-bottom: 0; }
+.feature h3 { font-size: 1rem; margin: 0; }
+.badge { display: inline-block; border: 1px solid #d0d7de; border-radius: 2em; padding: 0 0.5rem; font-size: 0.75rem; font-weight: 500; margin-left: 0.25rem; }
+.status-done { background: #dafbe1; border-color: #aceebb; }
+.status-in-progress { background: #ddf4ff; border-color: #b6e3ff; }
+.status-blocked { background: #ffebe9; border-color: #ffcecb; }
+ul.tasks { list-style: none; padding-left: 0; margin: 0.5rem 0 0; }
+ul.tasks input { margin-right: 0.5rem; }
+footer { color: #59636e; font-size: 0.75rem; margin-top: 2rem; }
+";
+
+/// Render `roadmap` as an HTML page, marking milestones overdue as of
+/// `today`.
+pub fn render(roadmap: &Roadmap, today: NaiveDate, format: PageFormat) -> String {
+    match format {
+        PageFormat::Html => render_html(roadmap, today),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// An anchor for `text` in the style of GitHub's heading anchors, made
+/// unique among `used` with a numeric suffix.
+fn anchor(text: &str, used: &mut HashSet<String>) -> String {
+    let base: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            ' ' => Some('-'),
+            _ => None,
+        })
+        .collect();
+    let base = if base.is_empty() {
+        "section".into()
+    } else {
+        base
+    };
+    let mut candidate = base.clone();
+    let mut n = 0;
+    while !used.insert(candidate.clone()) {
+        n += 1;
+        candidate = format!("{}-{}", base, n);
+    }
+    candidate
+}
+
+fn progress(completed: usize, total: usize) -> String {
+    let percent = if total == 0 {
+        0.0
+    } else {
+        100.0 * completed as f64 / total as f64
+    };
+    format!(
+        "<div class=\"progress\" role=\"progressbar\" aria-valuenow=\"{p:.0}\" aria-valuemin=\"0\" aria-valuemax=\"100\"><div style=\"width: {p:.1}%\"></div></div>",
+        p = percent
+    )
+}
+
+fn summary(counts: &Counts) -> String {
+    let done = counts.by_status.done;
+    format!(
+        "{} feature(s), {} done; {}/{} tasks",
+        counts.features, done, counts.completed_tasks, counts.tasks
+    )
+}
+
+fn paragraphs(out: &mut String, text: &str) {
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let _ = writeln!(out, "<p>{}</p>", escape(paragraph).replace('\n', "<br>"));
+    }
+}
+
+/// Whether `feature` belongs to the milestone of `group`.
+fn in_group(feature: &Feature, group: &MilestoneStats) -> bool {
+    let milestone = feature
+        .milestone
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty());
+    match (milestone, &group.name) {
+        (Some(milestone), Some(name)) => milestone.eq_ignore_ascii_case(name.trim()),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+// Writing to a `String` cannot fail, hence the ignored results below.
+fn render_html(roadmap: &Roadmap, today: NaiveDate) -> String {
+    let stats = stats(roadmap, today);
+    let title = if roadmap.name.is_empty() {
+        "Roadmap"
+    } else {
+        &roadmap.name
+    };
+    let mut out =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    let _ = writeln!(out, "<title>{}</title>", escape(title));
+    let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(out, "<header>\n<h1>{}</h1>", escape(title));
+    paragraphs(&mut out, &roadmap.description);
+    let _ = writeln!(
+        out,
+        "{}\n<p class=\"meta\">{}</p>\n</header>",
+        progress(stats.overall.completed_tasks, stats.overall.tasks),
+        summary(&stats.overall)
+    );
+
+    let mut used = HashSet::new();
+    for group in &stats.milestones {
+        let name = group.name.as_deref().unwrap_or("No milestone");
+        let id = anchor(&format!("milestone {}", name), &mut used);
+        let _ = writeln!(out, "<details id=\"{}\" open>\n<summary>", id);
+        let _ = write!(out, "<h2>{}</h2>", escape(name));
+        if let Some(due) = &group.due_date {
+            let class = if group.overdue {
+                "meta overdue"
+            } else {
+                "meta"
+            };
+            let overdue = if group.overdue { " (overdue)" } else { "" };
+            let _ = write!(
+                out,
+                "<span class=\"{}\">due {}{}</span>",
+                class,
+                escape(due),
+                overdue
+            );
+        }
+        let _ = writeln!(
+            out,
+            "<span class=\"meta\">{}</span>\n{}\n</summary>",
+            summary(&group.counts),
+            progress(group.counts.completed_tasks, group.counts.tasks)
+        );
+        for feature in roadmap.features.iter().filter(|f| in_group(f, group)) {
+            render_feature(&mut out, feature, &mut used);
+        }
+        out.push_str("</details>\n");
+    }
+    let _ = writeln!(
+        out,
+        "<footer>Generated by gitscaffold on {}.</footer>\n</body>\n</html>",
+        today
+    );
+    out
+}
+
+fn render_feature(out: &mut String, feature: &Feature, used: &mut HashSet<String>) {
+    let id = anchor(&feature.title, used);
+    let _ = writeln!(out, "<section class=\"feature\" id=\"{}\">", id);
+    let _ = write!(
+        out,
+        "<h3><a href=\"#{}\">{}</a>",
+        id,
+        escape(&feature.title)
+    );
+    if let Some(status) = &feature.status {
+        let name = status.name();
+        let _ = write!(
+            out,
+            "<span class=\"badge status-{}\">{}</span>",
+            escape(name),
+            escape(name)
+        );
+    }
+    if let Some(estimate) = &feature.estimate {
+        let _ = write!(
+            out,
+            "<span class=\"badge\">{}</span>",
+            escape(&estimate.to_string())
+        );
+    }
+    if let Some(issue) = &feature.issue {
+        let _ = write!(
+            out,
+            "<span class=\"meta\"><a href=\"{}\">#{}</a></span>",
+            escape(&issue.url),
+            issue.number
+        );
+    }
+    out.push_str("</h3>\n");
+    let mut meta = Vec::new();
+    if !feature.labels.is_empty() {
+        meta.push(format!("Labels: {}", escape(&feature.labels.join(", "))));
+    }
+    if !feature.assignees.is_empty() {
+        meta.push(format!(
+            "Assignees: {}",
+            escape(&feature.assignees.join(", "))
+        ));
+    }
+    if !meta.is_empty() {
+        let _ = writeln!(out, "<p class=\"meta\">{}</p>", meta.join(" · "));
+    }
+    paragraphs(out, &feature.description);
+    if !feature.tasks.is_empty() {
+        let completed = feature.tasks.iter().filter(|t| t.completed).count();
+        let _ = writeln!(
+            out,
+            "{}\n<ul class=\"tasks\">",
+            progress(completed, feature.tasks.len())
+        );
+        for task in &feature.tasks {
+            let checked = if task.completed { " checked" } else { "" };
+            let _ = writeln!(
+                out,
+                "<li style=\"margin-left: {}rem\"><input type=\"checkbox\" disabled{}>{}</li>",
+                task.depth * 2,
+                checked,
+                escape(&task.title)
+            );
+        }
+        out.push_str("</ul>\n");
+    }
+    for child in &feature.children {
+        render_feature(out, child, used);
+    }
+    out.push_str("</section>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "# Plan <2025>\n\n## Milestones\n- Alpha — 2025-01-31\n\n\
+        ## Features\n\n### Login [in-progress]\nMilestone: Alpha\n\n- [x] Form\n- [ ] Tokens\n\n\
+        ### Login\n\n### Docs & guides\n";
+
+    #[test]
+    fn renders_a_self_contained_page() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let page = render(&parse_roadmap(ROADMAP), today, PageFormat::Html);
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Plan &lt;2025&gt;</title>"));
+        assert!(!page.contains("<link") && !page.contains("<script"));
+        assert!(page.contains("<details id=\"milestone-alpha\" open>"));
+        assert!(page.contains("due 2025-01-31 (overdue)"));
+        assert!(page.contains("<div style=\"width: 50.0%\"></div>"));
+        assert!(page.contains("<section class=\"feature\" id=\"login\">"));
+        assert!(page.contains("<section class=\"feature\" id=\"login-1\">"));
+        assert!(page.contains("<a href=\"#docs--guides\">Docs &amp; guides</a>"));
+        assert!(page.contains("<span class=\"badge status-in-progress\">in-progress</span>"));
+        let alpha = page.find("milestone-alpha").unwrap();
+        let none = page.find("milestone-no-milestone").unwrap();
+        assert!(alpha < page.find("id=\"login\"").unwrap());
+        assert!(none < page.find("id=\"login-1\"").unwrap());
+    }
+}
@@ -0,0 +1,702 @@
+//! Roadmap data model.
+//!
+//! The field names and shapes mirror the dictionaries produced by
+//! `scaffold/parser.py`, so the Python layer can consume the JSON emitted by
+//! `mdparser` without any translation.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ParseError;
+
+/// Index of a feature in [`Roadmap::features`].
+pub type FeatureId = usize;
+
+/// Index of a milestone in [`Roadmap::milestones`].
+pub type MilestoneId = usize;
+
+/// Free-form project metadata, e.g. from a Markdown file's YAML frontmatter.
+pub type Metadata = HashMap<String, serde_json::Value>;
+
+/// A location in the roadmap source: 1-based line and column (counted in
+/// characters) plus the 0-based byte offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+/// The source region an entity was parsed from; `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Roadmap {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The format version the roadmap declares, from a `version:`
+    /// frontmatter entry or YAML key. See [`crate::migrate`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+    #[serde(default)]
+    pub features: Vec<Feature>,
+    /// Sections that are neither milestones nor features, kept as written.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_sections: Vec<ExtraSection>,
+    /// Project metadata such as the repository slug, owner or default
+    /// labels. `name` and `description` entries override the values
+    /// inferred from headings.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: Metadata,
+    /// Where the entity was defined; only known for Markdown input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// Anomalies the parser recovered from, in document order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl Roadmap {
+    /// Drop all source spans, e.g. before emitting JSON without them.
+    pub fn strip_spans(&mut self) {
+        self.span = None;
+        for milestone in &mut self.milestones {
+            milestone.span = None;
+        }
+        for feature in &mut self.features {
+            feature.strip_spans();
+        }
+        for section in &mut self.extra_sections {
+            section.span = None;
+        }
+        for warning in &mut self.warnings {
+            warning.span = None;
+        }
+    }
+
+    /// The roadmap, or an error listing its warnings when it has any; for
+    /// strict parsing.
+    pub fn deny_warnings(self) -> Result<Roadmap, ParseError> {
+        if self.warnings.is_empty() {
+            Ok(self)
+        } else {
+            Err(ParseError::Strict(self.warnings))
+        }
+    }
+}
+
+/// A top-level section the parser does not interpret, such as
+/// `## Background`, kept verbatim so that rendering the roadmap back to
+/// Markdown does not lose it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ExtraSection {
+    pub title: String,
+    /// The heading level, 1 to 6.
+    pub level: u8,
+    /// The Markdown below the heading, sub-headings included.
+    #[serde(default)]
+    pub body: String,
+    /// The last recognized section before it, after which it is rendered.
+    #[serde(default)]
+    pub after: Anchor,
+    /// Where the entity was defined; only known for Markdown input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+/// Where an [`ExtraSection`] goes among the recognized sections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Anchor {
+    /// After the title and description, before the milestones
+    #[default]
+    Title,
+    /// After the milestones, before the features
+    Milestones,
+    /// After the features
+    Features,
+}
+
+/// Something off in a Markdown roadmap that the parser worked around
+/// rather than reject the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ParseWarning {
+    pub kind: WarningKind,
+    pub message: String,
+    /// The file the entity was read from; only set when a roadmap was
+    /// aggregated from several files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Where the anomaly is, e.g. the offending heading or line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for ParseWarning {
+    /// The message, prefixed with the file and line when they are known.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.span) {
+            (Some(file), Some(span)) => write!(f, "{}:{}: ", file, span.start.line)?,
+            (Some(file), None) => write!(f, "{}: ", file)?,
+            (None, Some(span)) => write!(f, "line {}: ", span.start.line)?,
+            (None, None) => {}
+        }
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    /// A top-level heading that is neither the title nor a milestones or
+    /// features section; its content is ignored
+    UnknownSection,
+    /// A `Key: value` line whose key looks like a misspelt metadata key,
+    /// such as `Asignees:`; it stays part of the description
+    MetadataTypo,
+    /// A heading repeating a sibling's title
+    DuplicateHeading,
+    /// Raw HTML or footnotes dropped from a description by
+    /// [`HtmlPolicy::Strip`](crate::html::HtmlPolicy::Strip)
+    StrippedMarkup,
+    /// A roadmap declaring an older format version than the current one,
+    /// or a newer one than the parser knows
+    FormatVersion,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Milestone {
+    pub name: String,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    /// What the milestone is about, from the text below its heading or a
+    /// table's third column.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// The file the entity was read from; only set when a roadmap was
+    /// aggregated from several files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Where the entity was defined; only known for Markdown input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Feature {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub milestone: Option<String>,
+    /// `milestone` resolved to the milestone it names; filled in by the
+    /// parsers, see [`crate::graph::resolve`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub milestone_id: Option<MilestoneId>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub assignees: Vec<String>,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub kind: FeatureKind,
+    /// Where the feature is in its lifecycle, from a `[status]` marker on
+    /// the heading or a `Status:` line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<FeatureStatus>,
+    /// How much work the feature is, from an `Estimate:` line or a
+    /// `(3d)`-style suffix on the heading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    /// How urgent the feature is, from a `Priority:` line or a `!`, `!!`
+    /// or `!!!` suffix on the heading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// The forge issue tracking the feature, from a `[#12](url)` link at
+    /// the end of its heading as written by `scaffold pull`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<IssueLink>,
+    /// The repository the feature's issue belongs in, as `owner/name`, from
+    /// a `Repo:` line; `None` for the repository sync targets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Custom `Key: value` lines declared under `[fields]` in the config,
+    /// such as `Area: UI`, by the key as the config spells it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// The links in the description, in order, with reference-style links
+    /// resolved to their definitions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link>,
+    /// The images in the description, in order, with their alt text as
+    /// `text`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Link>,
+    /// Sub-features nested below this one, to any depth. Only top-level
+    /// features are listed in [`Roadmap::features`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Feature>,
+    /// Titles of the features this one depends on, as written.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// `depends_on` resolved to the features it names; filled in by the
+    /// parsers, see [`crate::graph::resolve`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<FeatureId>,
+    /// The file the entity was read from; only set when a roadmap was
+    /// aggregated from several files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Where the entity was defined; only known for Markdown input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+impl Feature {
+    /// Whether the feature is put off, with the status `deferred`.
+    pub fn is_deferred(&self) -> bool {
+        self.status == Some(FeatureStatus::Deferred)
+    }
+
+    /// Whether the feature is finished: its status is `done`, or it has no
+    /// known status and all of its tasks (at least one) are completed.
+    pub fn is_done(&self) -> bool {
+        match &self.status {
+            Some(FeatureStatus::Done) => true,
+            Some(status) if status.is_known() => false,
+            _ => !self.tasks.is_empty() && self.tasks.iter().all(|t| t.completed),
+        }
+    }
+
+    /// Where the feature goes when sorting by priority: 0 for `P0` through
+    /// 3 for `P3`, then 4 for features without a known priority.
+    pub fn priority_rank(&self) -> u8 {
+        self.priority.as_ref().and_then(Priority::rank).unwrap_or(4)
+    }
+
+    /// Drop the source spans of the feature, its tasks and its children.
+    pub fn strip_spans(&mut self) {
+        self.span = None;
+        for task in &mut self.tasks {
+            task.span = None;
+        }
+        for child in &mut self.children {
+            child.strip_spans();
+        }
+    }
+}
+
+/// A link to an issue on the forge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct IssueLink {
+    pub number: u64,
+    pub url: String,
+}
+
+impl fmt::Display for IssueLink {
+    /// The Markdown link, `[#12](url)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[#{}]({})", self.number, self.url)
+    }
+}
+
+/// A link or image in a description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Link {
+    /// The link text, without markup.
+    pub text: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Where a feature sits in the roadmap's hierarchy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum FeatureKind {
+    /// A feature grouping sub-features
+    Epic,
+    #[default]
+    Feature,
+    /// A unit of work small enough for a single issue
+    Task,
+}
+
+impl FeatureKind {
+    /// Parse a `Kind:` metadata value, ignoring case.
+    pub fn parse(value: &str) -> Option<FeatureKind> {
+        match value.trim().to_lowercase().as_str() {
+            "epic" => Some(FeatureKind::Epic),
+            "feature" => Some(FeatureKind::Feature),
+            "task" => Some(FeatureKind::Task),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FeatureKind::Epic => "epic",
+            FeatureKind::Feature => "feature",
+            FeatureKind::Task => "task",
+        }
+    }
+}
+
+/// Where a feature is in its lifecycle: `planned`, `in-progress`,
+/// `blocked`, `done` or `deferred`. Other values are kept as written and
+/// reported by validation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case", from = "String")]
+pub enum FeatureStatus {
+    Planned,
+    InProgress,
+    Blocked,
+    Done,
+    /// Put off for now, like the features of a `Deferred` or `Icebox`
+    /// section; sync leaves such features alone unless told otherwise.
+    Deferred,
+    /// Any other value, kept as written so validation can report it
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl FeatureStatus {
+    /// Parse a status, ignoring case and accepting spaces or underscores
+    /// for the dash in `in-progress`.
+    pub fn parse(value: &str) -> FeatureStatus {
+        let value = value.trim();
+        match value.to_lowercase().replace([' ', '_'], "-").as_str() {
+            "planned" => FeatureStatus::Planned,
+            "in-progress" => FeatureStatus::InProgress,
+            "blocked" => FeatureStatus::Blocked,
+            "done" => FeatureStatus::Done,
+            "deferred" => FeatureStatus::Deferred,
+            _ => FeatureStatus::Other(value.to_string()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            FeatureStatus::Planned => "planned",
+            FeatureStatus::InProgress => "in-progress",
+            FeatureStatus::Blocked => "blocked",
+            FeatureStatus::Done => "done",
+            FeatureStatus::Deferred => "deferred",
+            FeatureStatus::Other(value) => value,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, FeatureStatus::Other(_))
+    }
+}
+
+impl From<String> for FeatureStatus {
+    fn from(value: String) -> Self {
+        FeatureStatus::parse(&value)
+    }
+}
+
+/// A size estimate such as `3d`, `4h`, `2w` or `5pts`. Durations are
+/// normalized to days, at 8 hours a day and 5 days a week; story points
+/// stay points. Other values are kept as written and reported by
+/// validation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(from = "String", into = "String")]
+pub enum Estimate {
+    Days(f64),
+    Points(f64),
+    Other(String),
+}
+
+impl Estimate {
+    /// Parse an estimate: a non-negative number followed by a unit, with or
+    /// without a space. Units are `h`/`hours`, `d`/`days`, `w`/`weeks` and
+    /// `pt`/`pts`/`points`/`sp`, ignoring case.
+    pub fn parse(value: &str) -> Estimate {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let Some(number) = number.parse::<f64>().ok().filter(|n| n.is_finite()) else {
+            return Estimate::Other(value.to_string());
+        };
+        match unit.trim().to_lowercase().as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" => Estimate::Days(number / 8.0),
+            "d" | "day" | "days" => Estimate::Days(number),
+            "w" | "wk" | "wks" | "week" | "weeks" => Estimate::Days(number * 5.0),
+            "pt" | "pts" | "point" | "points" | "sp" => Estimate::Points(number),
+            _ => Estimate::Other(value.to_string()),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, Estimate::Other(_))
+    }
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Estimate::Days(days) => write!(f, "{}d", days),
+            Estimate::Points(points) => write!(f, "{}pts", points),
+            Estimate::Other(value) => f.write_str(value),
+        }
+    }
+}
+
+impl From<String> for Estimate {
+    fn from(value: String) -> Self {
+        Estimate::parse(&value)
+    }
+}
+
+impl From<Estimate> for String {
+    fn from(estimate: Estimate) -> Self {
+        estimate.to_string()
+    }
+}
+
+/// How urgent a feature is, from `P0`, the most urgent, to `P3`. Other
+/// values are kept as written and reported by validation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(from = "String", into = "String")]
+pub enum Priority {
+    P0,
+    P1,
+    P2,
+    P3,
+    Other(String),
+}
+
+impl Priority {
+    /// Parse a priority: `P0` to `P3`, or `critical`, `high`, `medium` and
+    /// `low` for the same, ignoring case; `0` to `3` and `urgent` or
+    /// `normal` work too.
+    pub fn parse(value: &str) -> Priority {
+        let value = value.trim();
+        match value.to_lowercase().as_str() {
+            "p0" | "0" | "critical" | "urgent" => Priority::P0,
+            "p1" | "1" | "high" => Priority::P1,
+            "p2" | "2" | "medium" | "normal" => Priority::P2,
+            "p3" | "3" | "low" => Priority::P3,
+            _ => Priority::Other(value.to_string()),
+        }
+    }
+
+    /// The priority of a heading suffix: `!!!` for `P0`, `!!` for `P1` and
+    /// `!` for `P2`.
+    pub fn from_marker(marker: &str) -> Option<Priority> {
+        match marker {
+            "!!!" => Some(Priority::P0),
+            "!!" => Some(Priority::P1),
+            "!" => Some(Priority::P2),
+            _ => None,
+        }
+    }
+
+    /// 0 for `P0` through 3 for `P3`; `None` for other values.
+    pub fn rank(&self) -> Option<u8> {
+        match self {
+            Priority::P0 => Some(0),
+            Priority::P1 => Some(1),
+            Priority::P2 => Some(2),
+            Priority::P3 => Some(3),
+            Priority::Other(_) => None,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.rank().is_some()
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::Other(value) => f.write_str(value),
+            known => write!(f, "P{}", known.rank().unwrap_or_default()),
+        }
+    }
+}
+
+impl From<String> for Priority {
+    fn from(value: String) -> Self {
+        Priority::parse(&value)
+    }
+}
+
+impl From<Priority> for String {
+    fn from(priority: Priority) -> Self {
+        priority.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Task {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub assignees: Vec<String>,
+    #[serde(default)]
+    pub tests: Vec<String>,
+    #[serde(default)]
+    pub completed: bool,
+    /// How much work the task is, from an `Estimate:` line or a `(3d)`-style
+    /// suffix on its title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    /// Nesting level within a checklist; 0 for top-level tasks.
+    #[serde(default)]
+    pub depth: usize,
+    /// Where the entity was defined; only known for Markdown input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+/// One top-level piece of a roadmap: its header, a milestone, a feature, an
+/// extra section or a parse warning.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+// Entities are streamed one at a time, so the size of the largest variant
+// does not add up.
+#[allow(clippy::large_enum_variant)]
+pub enum Entity {
+    /// The roadmap's name and description.
+    Roadmap {
+        name: String,
+        description: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        version: Option<u32>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        metadata: Metadata,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<Span>,
+    },
+    Milestone(Milestone),
+    Feature(Feature),
+    ExtraSection(ExtraSection),
+    Warning(ParseWarning),
+}
+
+impl Entity {
+    /// Drop all source spans, like [`Roadmap::strip_spans`].
+    pub fn strip_spans(&mut self) {
+        match self {
+            Entity::Roadmap { span, .. } => *span = None,
+            Entity::Milestone(milestone) => milestone.span = None,
+            Entity::Feature(feature) => feature.strip_spans(),
+            Entity::ExtraSection(section) => section.span = None,
+            Entity::Warning(warning) => warning.span = None,
+        }
+    }
+}
+
+impl Roadmap {
+    /// Split the roadmap into entities: the roadmap itself first, then its
+    /// milestones, features, extra sections and warnings.
+    pub fn into_entities(self) -> impl Iterator<Item = Entity> {
+        let header = Entity::Roadmap {
+            name: self.name,
+            description: self.description,
+            version: self.version,
+            metadata: self.metadata,
+            span: self.span,
+        };
+        std::iter::once(header)
+            .chain(self.milestones.into_iter().map(Entity::Milestone))
+            .chain(self.features.into_iter().map(Entity::Feature))
+            .chain(self.extra_sections.into_iter().map(Entity::ExtraSection))
+            .chain(self.warnings.into_iter().map(Entity::Warning))
+    }
+}
+
+impl FromIterator<Entity> for Roadmap {
+    fn from_iter<I: IntoIterator<Item = Entity>>(entities: I) -> Self {
+        let mut roadmap = Roadmap::default();
+        for entity in entities {
+            match entity {
+                Entity::Roadmap {
+                    name,
+                    description,
+                    version,
+                    metadata,
+                    span,
+                } => {
+                    roadmap.name = name;
+                    roadmap.description = description;
+                    roadmap.version = version;
+                    roadmap.metadata = metadata;
+                    roadmap.span = span;
+                }
+                Entity::Milestone(milestone) => roadmap.milestones.push(milestone),
+                Entity::Feature(feature) => roadmap.features.push(feature),
+                Entity::ExtraSection(section) => roadmap.extra_sections.push(section),
+                Entity::Warning(warning) => roadmap.warnings.push(warning),
+            }
+        }
+        roadmap
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl Roadmap {
+    /// JSON Schema for the roadmap as serialized by `mdparser`.
+    pub fn schema() -> schemars::Schema {
+        schemars::schema_for!(Roadmap)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl Entity {
+    /// JSON Schema for one line of `mdparser --stream` output.
+    pub fn schema() -> schemars::Schema {
+        schemars::schema_for!(Entity)
+    }
+}
+
+#[cfg(all(test, feature = "schemars"))]
+mod tests {
+    use super::*;
+
+    /// The checked-in schema is the published contract; regenerate it with
+    /// `mdparser --schema > gitscaffold-parser/roadmap.schema.json` after
+    /// changing the model.
+    #[test]
+    fn checked_in_schema_is_up_to_date() {
+        let schema = serde_json::to_string_pretty(&Roadmap::schema()).unwrap();
+        assert_eq!(schema.trim(), include_str!("../roadmap.schema.json").trim());
+    }
+}
@@ -0,0 +1,270 @@
+//! Validation and lint results as a [SARIF 2.1.0] log, the format GitHub
+//! code scanning and many editors read.
+//!
+//! Each log has one run whose tool lists every rule, so rule indices stay
+//! stable whichever rules fired. File paths are used as given, with
+//! forward slashes, so they should be relative to the repository root for
+//! code scanning to place the results.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use serde::Serialize;
+
+use crate::lint::{Lint, LintRule};
+use crate::validate::{Diagnostic, Rule, Severity};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const INFORMATION_URI: &str = "https://github.com/josephedward/gitscaffold";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Log {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Run {
+    pub tool: Tool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub name: &'static str,
+    pub information_uri: &'static str,
+    pub version: &'static str,
+    pub rules: Vec<RuleDescriptor>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDescriptor {
+    /// The rule's code, e.g. `GS003`.
+    pub id: &'static str,
+    /// The rule's name, e.g. `unique-feature-titles`.
+    pub name: &'static str,
+    pub short_description: Message,
+    pub default_configuration: Configuration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Configuration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+/// One diagnostic or lint; named so as not to shadow [`Result`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: &'static str,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<usize>,
+}
+
+impl Log {
+    fn new(rules: Vec<RuleDescriptor>, results: Vec<SarifResult>) -> Self {
+        Log {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "gitscaffold",
+                        information_uri: INFORMATION_URI,
+                        version: env!("CARGO_PKG_VERSION"),
+                        rules,
+                    },
+                },
+                results,
+            }],
+        }
+    }
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// `file` as a URI: relative paths stay relative, absolute ones become
+/// `file:` URIs.
+fn uri(file: &str) -> String {
+    let path = file.replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else if path.as_bytes().get(1) == Some(&b':') {
+        format!("file:///{}", path)
+    } else {
+        path.strip_prefix("./").unwrap_or(&path).to_string()
+    }
+}
+
+fn location(file: &str, line: Option<usize>, column: Option<usize>) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation { uri: uri(file) },
+            region: line.map(|start_line| Region {
+                start_line,
+                start_column: column,
+            }),
+        },
+    }
+}
+
+/// The log of a validation run. Diagnostics without a `file` of their own
+/// are located in `file`, or nowhere when it is `None`.
+pub fn validation_log(diagnostics: &[Diagnostic], file: Option<&str>) -> Log {
+    let rules = Rule::ALL
+        .iter()
+        .map(|rule| RuleDescriptor {
+            id: rule.code(),
+            name: rule.name(),
+            short_description: Message {
+                text: rule.description().into(),
+            },
+            default_configuration: Configuration {
+                level: level(rule.default_severity()),
+            },
+        })
+        .collect();
+    let results = diagnostics
+        .iter()
+        .map(|d| SarifResult {
+            rule_id: d.code,
+            rule_index: Rule::ALL.iter().position(|&r| r == d.rule).unwrap_or(0),
+            level: level(d.severity),
+            message: Message {
+                text: d.message.clone(),
+            },
+            locations: d
+                .file
+                .as_deref()
+                .or(file)
+                .map(|file| location(file, d.line, d.column))
+                .into_iter()
+                .collect(),
+        })
+        .collect();
+    Log::new(rules, results)
+}
+
+/// The log of a lint run over `files`, each with the lints left in it.
+/// Lints are reported as warnings.
+pub fn lint_log(files: &[(String, Vec<Lint>)]) -> Log {
+    let rules = LintRule::ALL
+        .iter()
+        .map(|rule| RuleDescriptor {
+            id: rule.code(),
+            name: rule.name(),
+            short_description: Message {
+                text: rule.description().into(),
+            },
+            default_configuration: Configuration { level: "warning" },
+        })
+        .collect();
+    let results = files
+        .iter()
+        .flat_map(|(file, lints)| lints.iter().map(move |lint| (file, lint)))
+        .map(|(file, lint)| SarifResult {
+            rule_id: lint.code,
+            rule_index: LintRule::ALL
+                .iter()
+                .position(|&r| r == lint.rule)
+                .unwrap_or(0),
+            level: "warning",
+            message: Message {
+                text: lint.message.clone(),
+            },
+            locations: vec![location(file, Some(lint.line), Some(lint.column))],
+        })
+        .collect();
+    Log::new(rules, results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+    use crate::validate::{validate, RuleConfig};
+
+    #[test]
+    fn locates_diagnostics_by_rule_and_line() {
+        let roadmap = parse_roadmap("# P\n\n## Features\n\n### Login\n\n### login\n");
+        let diagnostics = validate(&roadmap, &RuleConfig::default());
+        let log =
+            serde_json::to_value(validation_log(&diagnostics, Some("./docs\\ROADMAP.md"))).unwrap();
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"][2]["id"], "GS003");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][1]["defaultConfiguration"]["level"],
+            "warning"
+        );
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["ruleId"], "GS002");
+        assert!(results[0]["locations"][0]["physicalLocation"]["region"].is_null());
+        assert_eq!(results[1]["ruleId"], "GS003");
+        assert_eq!(results[1]["ruleIndex"], 2);
+        assert_eq!(results[1]["level"], "error");
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "docs/ROADMAP.md");
+        assert_eq!(location["region"]["startLine"], 7);
+    }
+
+    #[test]
+    fn reports_lints_as_warnings_with_file_uris() {
+        let lints = crate::lint::lint("# P\n\n### Login \n\nText.\n", &Default::default());
+        let log = lint_log(&[("/work/ROADMAP.md".into(), lints)]);
+        let result = &log.runs[0].results[0];
+        assert_eq!((result.rule_id, result.level), ("GL001", "warning"));
+        assert_eq!(
+            result.locations[0].physical_location.artifact_location.uri,
+            "file:///work/ROADMAP.md"
+        );
+    }
+}
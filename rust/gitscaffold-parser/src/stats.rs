@@ -0,0 +1,226 @@
+//! Completion metrics for a [`Roadmap`], per milestone and overall.
+//!
+//! Every feature counts, sub-features included; a sub-feature without a
+//! milestone belongs to its parent's. A feature is done as defined by
+//! [`Feature::is_done`]. Features are also counted by priority.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::roadmap::{Feature, FeatureStatus, Roadmap};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatusCounts {
+    pub planned: usize,
+    pub in_progress: usize,
+    pub blocked: usize,
+    pub done: usize,
+    pub deferred: usize,
+    /// Features without a (known) status.
+    pub unset: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PriorityCounts {
+    pub p0: usize,
+    pub p1: usize,
+    pub p2: usize,
+    pub p3: usize,
+    /// Features without a (known) priority.
+    pub unset: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Counts {
+    pub features: usize,
+    pub by_status: StatusCounts,
+    pub by_priority: PriorityCounts,
+    pub tasks: usize,
+    pub completed_tasks: usize,
+    /// Percentage of completed tasks; `None` without tasks.
+    pub completion: Option<f64>,
+    /// `None` without features.
+    pub tasks_per_feature: Option<f64>,
+    /// Features that are done.
+    #[serde(skip)]
+    done_features: usize,
+}
+
+impl Counts {
+    fn add(&mut self, feature: &Feature) {
+        self.features += 1;
+        let by_status = &mut self.by_status;
+        match &feature.status {
+            Some(FeatureStatus::Planned) => by_status.planned += 1,
+            Some(FeatureStatus::InProgress) => by_status.in_progress += 1,
+            Some(FeatureStatus::Blocked) => by_status.blocked += 1,
+            Some(FeatureStatus::Done) => by_status.done += 1,
+            Some(FeatureStatus::Deferred) => by_status.deferred += 1,
+            Some(FeatureStatus::Other(_)) | None => by_status.unset += 1,
+        }
+        let by_priority = &mut self.by_priority;
+        match feature.priority_rank() {
+            0 => by_priority.p0 += 1,
+            1 => by_priority.p1 += 1,
+            2 => by_priority.p2 += 1,
+            3 => by_priority.p3 += 1,
+            _ => by_priority.unset += 1,
+        }
+        self.tasks += feature.tasks.len();
+        self.completed_tasks += feature.tasks.iter().filter(|t| t.completed).count();
+        if feature.is_done() {
+            self.done_features += 1;
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.completion =
+            (self.tasks > 0).then(|| 100.0 * self.completed_tasks as f64 / self.tasks as f64);
+        self.tasks_per_feature =
+            (self.features > 0).then(|| self.tasks as f64 / self.features as f64);
+        self
+    }
+
+    /// Whether every feature is done.
+    pub fn is_complete(&self) -> bool {
+        self.done_features == self.features
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MilestoneStats {
+    /// `None` for the features without a milestone.
+    pub name: Option<String>,
+    pub due_date: Option<String>,
+    /// The due date has passed and some feature is not done.
+    pub overdue: bool,
+    #[serde(flatten)]
+    pub counts: Counts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    /// One entry per milestone in roadmap order, then milestones features
+    /// reference but the roadmap does not define, then the features
+    /// without a milestone, if any.
+    pub milestones: Vec<MilestoneStats>,
+    pub overall: Counts,
+    /// Names of the overdue milestones.
+    pub overdue: Vec<String>,
+}
+
+/// Compute the statistics of `roadmap` as of `today`.
+pub fn stats(roadmap: &Roadmap, today: NaiveDate) -> Stats {
+    let mut groups: Vec<(Option<String>, Option<String>, Counts)> = roadmap
+        .milestones
+        .iter()
+        .map(|m| (Some(m.name.clone()), m.due_date.clone(), Counts::default()))
+        .collect();
+    let mut unassigned = Counts::default();
+    let mut overall = Counts::default();
+
+    let mut pending: Vec<(&Feature, Option<&str>)> =
+        roadmap.features.iter().rev().map(|f| (f, None)).collect();
+    while let Some((feature, inherited)) = pending.pop() {
+        let milestone = feature.milestone.as_deref().or(inherited);
+        pending.extend(feature.children.iter().rev().map(|c| (c, milestone)));
+        overall.add(feature);
+        let Some(name) = milestone.map(str::trim).filter(|n| !n.is_empty()) else {
+            unassigned.add(feature);
+            continue;
+        };
+        let group = groups.iter().position(|(m, _, _)| {
+            m.as_deref()
+                .is_some_and(|m| m.trim().eq_ignore_ascii_case(name))
+        });
+        match group {
+            Some(i) => groups[i].2.add(feature),
+            None => {
+                let mut counts = Counts::default();
+                counts.add(feature);
+                groups.push((Some(name.to_string()), None, counts));
+            }
+        }
+    }
+
+    let mut milestones: Vec<MilestoneStats> = groups
+        .into_iter()
+        .map(|(name, due_date, counts)| {
+            let counts = counts.finish();
+            let overdue = due_date
+                .as_deref()
+                .and_then(|due| NaiveDate::parse_from_str(due, "%Y-%m-%d").ok())
+                .is_some_and(|due| due < today && !counts.is_complete());
+            MilestoneStats {
+                name,
+                due_date,
+                overdue,
+                counts,
+            }
+        })
+        .collect();
+    if unassigned.features > 0 {
+        milestones.push(MilestoneStats {
+            name: None,
+            due_date: None,
+            overdue: false,
+            counts: unassigned.finish(),
+        });
+    }
+    let overdue = milestones
+        .iter()
+        .filter(|m| m.overdue)
+        .filter_map(|m| m.name.clone())
+        .collect();
+    Stats {
+        milestones,
+        overall: overall.finish(),
+        overdue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "# P\n\n## Milestones\n- Alpha — 2025-01-31\n- Beta — 2025-06-30\n\n\
+        ## Features\n\n### Login [done]\nMilestone: Alpha\nPriority: P1\n\n- [x] Form\n- [ ] Tokens\n\n\
+        ### Search !!! [blocked]\nMilestone: Alpha\n\n### Export\nMilestone: Beta\n\n- [x] CSV\n\n\
+        ### Docs\n- [ ] Guide\n";
+
+    #[test]
+    fn counts_statuses_and_tasks_per_milestone() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let stats = stats(&parse_roadmap(ROADMAP), today);
+        let alpha = &stats.milestones[0];
+        assert_eq!(alpha.counts.features, 2);
+        assert_eq!(alpha.counts.by_status.done, 1);
+        assert_eq!(alpha.counts.by_status.blocked, 1);
+        assert_eq!(alpha.counts.completion, Some(50.0));
+        assert_eq!(
+            (alpha.counts.by_priority.p0, alpha.counts.by_priority.p1),
+            (1, 1)
+        );
+        assert_eq!(stats.overall.by_priority.unset, 2);
+        assert!(alpha.overdue);
+        let beta = &stats.milestones[1];
+        assert_eq!(beta.counts.by_status.unset, 1);
+        assert!(!beta.overdue);
+        assert_eq!(stats.milestones[2].name, None);
+        assert_eq!(stats.overdue, ["Alpha"]);
+        assert_eq!(stats.overall.features, 4);
+        assert_eq!(stats.overall.tasks, 4);
+        assert_eq!(stats.overall.completed_tasks, 2);
+        assert_eq!(stats.overall.tasks_per_feature, Some(1.0));
+    }
+
+    #[test]
+    fn finished_milestones_are_not_overdue() {
+        let today = NaiveDate::from_ymd_opt(2025, 12, 1).unwrap();
+        let source = ROADMAP.replace("[blocked]", "[done]");
+        let stats = stats(&parse_roadmap(&source), today);
+        // Beta's only feature has all its tasks completed.
+        assert!(stats.overdue.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! Unicode handling for titles and names.
+//!
+//! Titles are NFC-normalized when parsed, so a heading typed with a
+//! combining accent matches the same title typed precomposed, and the
+//! `Name — due date` split accepts any dash rather than only `—`.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::dates::parse_date;
+
+/// Dashes other than `—` that separate a milestone from its due date:
+/// en dash, figure dash, horizontal bar and minus sign.
+const DASHES: &[char] = &['–', '‒', '―', '−'];
+
+/// `text` trimmed and in Unicode normalization form C.
+pub fn normalize(text: &str) -> String {
+    nfc(text.trim()).into_owned()
+}
+
+/// `text` in Unicode normalization form C, borrowed when it already is,
+/// as nearly all text is.
+pub fn nfc(text: &str) -> Cow<'_, str> {
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(text),
+        _ => Cow::Owned(text.nfc().collect()),
+    }
+}
+
+/// [`normalize`] `text` in place, allocating only when it has to change.
+pub(crate) fn normalize_cow(text: &mut Cow<'_, str>) {
+    match text {
+        Cow::Borrowed(borrowed) => *text = nfc(borrowed.trim()),
+        Cow::Owned(owned) => {
+            if let Cow::Owned(normalized) = nfc(owned.trim()) {
+                *owned = normalized;
+            } else {
+                owned.truncate(owned.trim_end().len());
+                owned.drain(..owned.len() - owned.trim_start().len());
+            }
+        }
+    }
+}
+
+/// The key titles are compared by: normalized and lowercased.
+pub fn title_key(title: &str) -> String {
+    normalize(title).to_lowercase()
+}
+
+/// Where `text`, such as `v1.0 — June 30, 2024`, splits into a name and a
+/// due date: the end of the name and the range of the due date, which may
+/// be empty. An em dash always starts the due date; any other dash, or a
+/// hyphen between spaces, only when a date follows. A trailing
+/// `(due: June 30, 2024)` works too.
+pub fn find_due(text: &str) -> Option<(usize, Range<usize>)> {
+    let trimmed = text.trim_end();
+    if let Some(open) = trimmed.strip_suffix(')').and_then(|rest| rest.rfind('(')) {
+        let inner = trimmed[open + 1..trimmed.len() - 1].trim_start();
+        let value = inner
+            .get(..3)
+            .filter(|due| due.eq_ignore_ascii_case("due"))
+            .and_then(|_| inner[3..].trim_start().strip_prefix(':'));
+        if let Some(value) = value {
+            return Some((open, range_in(text, value.trim())));
+        }
+    }
+    for (index, c) in text.char_indices() {
+        let rest = &text[index + c.len_utf8()..];
+        let hyphen = c == '-' && text[..index].ends_with(' ') && rest.starts_with(' ');
+        if c == '—' || ((DASHES.contains(&c) || hyphen) && parse_date(rest).is_some()) {
+            return Some((index, range_in(text, rest.trim())));
+        }
+    }
+    None
+}
+
+/// The byte range of `part`, a slice of `text`, within `text`.
+fn range_in(text: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - text.as_ptr() as usize;
+    start..start + part.len()
+}
+
+/// `text` cut to at most `max` characters, with `…` marking the cut.
+/// Only whole grapheme clusters are kept, so an emoji or an accented
+/// letter is never split.
+pub fn truncate(text: &str, max: usize) -> Cow<'_, str> {
+    if text.chars().count() <= max {
+        return Cow::Borrowed(text);
+    }
+    let mut cut = String::new();
+    let mut count = 0;
+    for grapheme in text.graphemes(true) {
+        count += grapheme.chars().count();
+        if count + 1 > max {
+            break;
+        }
+        cut.push_str(grapheme);
+    }
+    cut.truncate(cut.trim_end().len());
+    cut.push('…');
+    Cow::Owned(cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn due(text: &str) -> Option<(&str, &str)> {
+        find_due(text).map(|(end, due)| (&text[..end], &text[due]))
+    }
+
+    #[test]
+    fn splits_due_dates_after_any_dash() {
+        assert_eq!(due("v1 — soon"), Some(("v1 ", "soon")));
+        assert_eq!(due("v1 – 2024-06-30"), Some(("v1 ", "2024-06-30")));
+        assert_eq!(due("v1 - June 30, 2024"), Some(("v1 ", "June 30, 2024")));
+        assert_eq!(due("v1 (due: 2024-06-30)"), Some(("v1 ", "2024-06-30")));
+        assert_eq!(due("Phase 1–2 – the rest"), None);
+        assert_eq!(due("Sign-in (beta)"), None);
+    }
+
+    #[test]
+    fn normalizes_and_truncates_by_grapheme() {
+        assert_eq!(normalize(" Cafe\u{301} "), "Caf\u{e9}");
+        assert_eq!(title_key("CAFE\u{301}"), title_key("café"));
+        assert_eq!(truncate("Ship 🚀🚀", 10), "Ship 🚀🚀");
+        assert_eq!(truncate("Ship it 👩‍👩‍👧 now", 9), "Ship it…");
+        assert_eq!(truncate("Cafe\u{301} au lait", 6), "Cafe\u{301}…");
+    }
+}
@@ -0,0 +1,593 @@
+//! Rule-based roadmap validation.
+//!
+//! Every check is a [`Rule`] with a stable machine-readable code and a
+//! default [`Severity`]. A [`RuleConfig`] can disable rules or change their
+//! severity; [`validate`] runs the enabled rules and returns one
+//! [`Diagnostic`] per violation, located at the offending entity when the
+//! roadmap was parsed from Markdown.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::dates::is_iso_date;
+use crate::graph;
+use crate::roadmap::{Feature, Roadmap, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// The roadmap must have a name (an H1 heading or `name` key)
+    RequiredName,
+    /// The roadmap should define at least one milestone
+    NonEmptyMilestones,
+    /// Feature titles must be unique (case-insensitively)
+    UniqueFeatureTitles,
+    /// Milestone names must be unique (case-insensitively)
+    UniqueMilestoneNames,
+    /// Milestone due dates must be parseable dates
+    ValidDueDates,
+    /// Feature milestones must name a defined milestone
+    MilestoneReferences,
+    /// Feature dependencies must name a defined feature
+    DependencyReferences,
+    /// Feature dependencies must not form a cycle
+    DependencyCycles,
+    /// Feature statuses must be `planned`, `in-progress`, `blocked`, `done`
+    /// or `deferred`
+    ValidStatuses,
+    /// Feature and task estimates must be a number with a known unit
+    ValidEstimates,
+    /// Feature priorities must be `P0` to `P3` or a word for one
+    ValidPriorities,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 11] = [
+        Rule::RequiredName,
+        Rule::NonEmptyMilestones,
+        Rule::UniqueFeatureTitles,
+        Rule::UniqueMilestoneNames,
+        Rule::ValidDueDates,
+        Rule::MilestoneReferences,
+        Rule::DependencyReferences,
+        Rule::DependencyCycles,
+        Rule::ValidStatuses,
+        Rule::ValidEstimates,
+        Rule::ValidPriorities,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Rule::RequiredName => "GS001",
+            Rule::NonEmptyMilestones => "GS002",
+            Rule::UniqueFeatureTitles => "GS003",
+            Rule::ValidDueDates => "GS004",
+            Rule::MilestoneReferences => "GS005",
+            Rule::DependencyReferences => "GS006",
+            Rule::DependencyCycles => "GS007",
+            Rule::UniqueMilestoneNames => "GS008",
+            Rule::ValidStatuses => "GS009",
+            Rule::ValidEstimates => "GS010",
+            Rule::ValidPriorities => "GS011",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::RequiredName => "required-name",
+            Rule::NonEmptyMilestones => "non-empty-milestones",
+            Rule::UniqueFeatureTitles => "unique-feature-titles",
+            Rule::UniqueMilestoneNames => "unique-milestone-names",
+            Rule::ValidDueDates => "valid-due-dates",
+            Rule::MilestoneReferences => "milestone-references",
+            Rule::DependencyReferences => "dependency-references",
+            Rule::DependencyCycles => "dependency-cycles",
+            Rule::ValidStatuses => "valid-statuses",
+            Rule::ValidEstimates => "valid-estimates",
+            Rule::ValidPriorities => "valid-priorities",
+        }
+    }
+
+    /// A one-line summary of what the rule checks.
+    pub fn description(self) -> &'static str {
+        match self {
+            Rule::RequiredName => "The roadmap has a name",
+            Rule::NonEmptyMilestones => "At least one milestone is defined",
+            Rule::UniqueFeatureTitles => "Feature titles are unique (case-insensitive)",
+            Rule::UniqueMilestoneNames => "Milestone names are unique (case-insensitive)",
+            Rule::ValidDueDates => "Milestone due dates can be parsed",
+            Rule::MilestoneReferences => "Features reference defined milestones",
+            Rule::DependencyReferences => "Feature dependencies name defined features",
+            Rule::DependencyCycles => "Feature dependencies do not form a cycle",
+            Rule::ValidStatuses => "Feature statuses are known values",
+            Rule::ValidEstimates => "Feature and task estimates have a known unit",
+            Rule::ValidPriorities => "Feature priorities are known values",
+        }
+    }
+
+    /// What a label pointing at the offending text says.
+    pub fn label(self) -> &'static str {
+        match self {
+            Rule::RequiredName => "roadmap name missing",
+            Rule::NonEmptyMilestones => "no milestones",
+            Rule::UniqueFeatureTitles => "feature title used again here",
+            Rule::UniqueMilestoneNames => "milestone name used again here",
+            Rule::ValidDueDates => "not a date",
+            Rule::MilestoneReferences => "milestone referenced here was never defined",
+            Rule::DependencyReferences => "feature referenced here was never defined",
+            Rule::DependencyCycles => "cycle starts here",
+            Rule::ValidStatuses => "unknown status",
+            Rule::ValidEstimates => "estimate without a known unit",
+            Rule::ValidPriorities => "unknown priority",
+        }
+    }
+
+    /// How to fix a violation.
+    pub fn help(self) -> &'static str {
+        match self {
+            Rule::RequiredName => "start the roadmap with an H1 heading or set `name`",
+            Rule::NonEmptyMilestones => "add a milestones section listing at least one milestone",
+            Rule::UniqueFeatureTitles => "rename one of the features; issues are matched by title",
+            Rule::UniqueMilestoneNames => "rename or merge the milestones",
+            Rule::ValidDueDates => "write due dates as YYYY-MM-DD",
+            Rule::MilestoneReferences => {
+                "define the milestone, or reference one of the defined milestones by its name"
+            }
+            Rule::DependencyReferences => "depend on features by their exact title",
+            Rule::DependencyCycles => "remove one of the dependencies to break the cycle",
+            Rule::ValidStatuses => "use `planned`, `in-progress`, `blocked`, `done` or `deferred`",
+            Rule::ValidEstimates => {
+                "write a number and a unit: hours (`4h`), days (`3d`), weeks (`2w`) or points (`5pts`)"
+            }
+            Rule::ValidPriorities => {
+                "use `P0` to `P3`, or `critical`, `high`, `medium` or `low` for the same"
+            }
+        }
+    }
+
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Rule::NonEmptyMilestones => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Run the rule, reporting each violation with the location of the
+    /// offending entity (its span and, for aggregated roadmaps, its file).
+    fn check(self, roadmap: &Roadmap, report: &mut Reporter) {
+        match self {
+            Rule::RequiredName => {
+                if roadmap.name.trim().is_empty() {
+                    report(None, None, None, "roadmap has no name".into());
+                }
+            }
+            Rule::NonEmptyMilestones => {
+                if roadmap.milestones.is_empty() {
+                    report(None, None, None, "roadmap defines no milestones".into());
+                }
+            }
+            Rule::UniqueFeatureTitles => {
+                let mut seen = HashMap::new();
+                for feature in &roadmap.features {
+                    let file = feature.file.as_deref();
+                    let key = feature.title.trim().to_lowercase();
+                    if let Some(&first) = seen.get(&key) {
+                        let message = format!("duplicate feature title '{}'", feature.title);
+                        report(
+                            feature.span,
+                            file,
+                            Some(&feature.title),
+                            also_in(message, first, file),
+                        );
+                    } else {
+                        seen.insert(key, file);
+                    }
+                }
+            }
+            Rule::UniqueMilestoneNames => {
+                let mut seen = HashMap::new();
+                for milestone in &roadmap.milestones {
+                    let file = milestone.file.as_deref();
+                    let key = milestone.name.trim().to_lowercase();
+                    if let Some(&first) = seen.get(&key) {
+                        let message = format!("duplicate milestone name '{}'", milestone.name);
+                        report(
+                            milestone.span,
+                            file,
+                            Some(&milestone.name),
+                            also_in(message, first, file),
+                        );
+                    } else {
+                        seen.insert(key, file);
+                    }
+                }
+            }
+            Rule::ValidDueDates => {
+                for milestone in &roadmap.milestones {
+                    if let Some(due) = &milestone.due_date {
+                        if !is_iso_date(due) {
+                            report(
+                                milestone.span,
+                                milestone.file.as_deref(),
+                                Some(due),
+                                format!(
+                                    "milestone '{}' has unparseable due date '{}'",
+                                    milestone.name, due
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+            Rule::MilestoneReferences => {
+                for feature in graph::unresolved_milestones(roadmap) {
+                    let milestone = feature.milestone.as_deref().unwrap_or_default();
+                    report(
+                        feature.span,
+                        feature.file.as_deref(),
+                        Some(milestone),
+                        format!(
+                            "feature '{}' references undefined milestone '{}'",
+                            feature.title, milestone
+                        ),
+                    );
+                }
+            }
+            Rule::DependencyReferences => {
+                for (id, reference) in graph::unresolved(roadmap) {
+                    let feature = &roadmap.features[id];
+                    report(
+                        feature.span,
+                        feature.file.as_deref(),
+                        Some(reference),
+                        format!(
+                            "feature '{}' depends on undefined feature '{}'",
+                            feature.title, reference
+                        ),
+                    );
+                }
+            }
+            Rule::DependencyCycles => {
+                for cycle in graph::cycles(roadmap) {
+                    let titles: Vec<&str> = cycle
+                        .iter()
+                        .chain(cycle.first())
+                        .map(|&id| roadmap.features[id].title.as_str())
+                        .collect();
+                    let first = &roadmap.features[cycle[0]];
+                    report(
+                        first.span,
+                        first.file.as_deref(),
+                        None,
+                        format!("dependency cycle: {}", titles.join(" → ")),
+                    );
+                }
+            }
+            Rule::ValidStatuses => {
+                // Depth first, in document order.
+                let mut pending: Vec<&Feature> = roadmap.features.iter().rev().collect();
+                while let Some(feature) = pending.pop() {
+                    pending.extend(feature.children.iter().rev());
+                    match &feature.status {
+                        Some(status) if !status.is_known() => report(
+                            feature.span,
+                            feature.file.as_deref(),
+                            Some(status.name()),
+                            format!(
+                                "feature '{}' has unknown status '{}'",
+                                feature.title,
+                                status.name()
+                            ),
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+            Rule::ValidPriorities => {
+                let mut pending: Vec<&Feature> = roadmap.features.iter().rev().collect();
+                while let Some(feature) = pending.pop() {
+                    pending.extend(feature.children.iter().rev());
+                    if let Some(priority) = feature.priority.as_ref().filter(|p| !p.is_known()) {
+                        report(
+                            feature.span,
+                            feature.file.as_deref(),
+                            Some(&priority.to_string()),
+                            format!(
+                                "feature '{}' has unknown priority '{}'",
+                                feature.title, priority
+                            ),
+                        );
+                    }
+                }
+            }
+            Rule::ValidEstimates => {
+                let mut pending: Vec<&Feature> = roadmap.features.iter().rev().collect();
+                while let Some(feature) = pending.pop() {
+                    pending.extend(feature.children.iter().rev());
+                    let file = feature.file.as_deref();
+                    if let Some(estimate) = feature.estimate.as_ref().filter(|e| !e.is_valid()) {
+                        report(
+                            feature.span,
+                            file,
+                            Some(&estimate.to_string()),
+                            format!(
+                                "feature '{}' has invalid estimate '{}'",
+                                feature.title, estimate
+                            ),
+                        );
+                    }
+                    for task in &feature.tasks {
+                        if let Some(estimate) = task.estimate.as_ref().filter(|e| !e.is_valid()) {
+                            report(
+                                task.span,
+                                file,
+                                Some(&estimate.to_string()),
+                                format!(
+                                    "task '{}' has invalid estimate '{}'",
+                                    task.title, estimate
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Receives each violation with the span and file of the offending entity
+/// and the text in it the violation is about, such as an undefined
+/// milestone's name.
+type Reporter<'a> = dyn FnMut(Option<Span>, Option<&str>, Option<&str>, String) + 'a;
+
+/// Point at the first definition of a duplicate when it is in another file.
+fn also_in(message: String, first: Option<&str>, file: Option<&str>) -> String {
+    match first {
+        Some(first) if Some(first) != file => format!("{}, also defined in {}", message, first),
+        _ => message,
+    }
+}
+
+/// Which rules run and at what severity. Rules not mentioned keep their
+/// default severity.
+#[derive(Debug, Clone, Default)]
+pub struct RuleConfig {
+    overrides: HashMap<Rule, Option<Severity>>,
+}
+
+impl RuleConfig {
+    pub fn disable(&mut self, rule: Rule) {
+        self.overrides.insert(rule, None);
+    }
+
+    pub fn set_severity(&mut self, rule: Rule, severity: Severity) {
+        self.overrides.insert(rule, Some(severity));
+    }
+
+    /// The effective severity of `rule`, or `None` when it is disabled.
+    pub fn severity(&self, rule: Rule) -> Option<Severity> {
+        match self.overrides.get(&rule) {
+            Some(severity) => *severity,
+            None => Some(rule.default_severity()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    /// The file of the offending entity, when the roadmap was aggregated
+    /// from several files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// The source region of the offending entity, for pointing at it.
+    #[serde(skip)]
+    pub span: Option<Span>,
+    /// The text within `span` the violation is about, e.g. the undefined
+    /// milestone a feature references.
+    #[serde(skip)]
+    pub subject: Option<String>,
+}
+
+/// Run every enabled rule against `roadmap`.
+pub fn validate(roadmap: &Roadmap, config: &RuleConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for rule in Rule::ALL {
+        let Some(severity) = config.severity(rule) else {
+            continue;
+        };
+        rule.check(roadmap, &mut |span, file, subject, message| {
+            diagnostics.push(Diagnostic {
+                code: rule.code(),
+                rule,
+                severity,
+                message,
+                file: file.map(str::to_string),
+                line: span.map(|s| s.start.line),
+                column: span.map(|s| s.start.column),
+                span,
+                subject: subject.map(str::to_string),
+            })
+        });
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::parse_roadmap;
+
+    const ROADMAP: &str = "\
+# Project
+
+## Milestones
+- M1 — 2025-02-30
+- M2 — 2025-03-01
+
+## Features
+
+### Login
+Milestone: M3
+
+### login
+";
+
+    #[test]
+    fn reports_each_violation_with_code_and_location() {
+        let diagnostics = validate(&parse_roadmap(ROADMAP), &RuleConfig::default());
+        let summary: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code, d.severity, d.line))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("GS003", Severity::Error, Some(12)),
+                ("GS004", Severity::Error, Some(4)),
+                ("GS005", Severity::Error, Some(9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn config_disables_and_reclassifies_rules() {
+        let roadmap = parse_roadmap("Just text.\n");
+        let mut config = RuleConfig::default();
+        config.disable(Rule::RequiredName);
+        config.set_severity(Rule::NonEmptyMilestones, Severity::Error);
+        let diagnostics = validate(&roadmap, &config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::NonEmptyMilestones);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn reports_unknown_and_cyclic_dependencies() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- M\n\n## Features\n\n### A\nDepends on: B, Z\n\n### B\nDepends on: A\n",
+        );
+        let messages: Vec<_> = validate(&roadmap, &RuleConfig::default())
+            .into_iter()
+            .map(|d| format!("{} {}", d.code, d.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "GS006 feature 'A' depends on undefined feature 'Z'",
+                "GS007 dependency cycle: A → B → A",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_statuses_of_nested_features() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- M\n\n## Features\n\n### A [done]\n\n\
+             #### A1\nKind: task\nStatus: someday\n",
+        );
+        let diagnostics = validate(&roadmap, &RuleConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "GS009");
+        assert_eq!(
+            diagnostics[0].message,
+            "feature 'A1' has unknown status 'someday'"
+        );
+        assert_eq!(diagnostics[0].line, Some(10));
+    }
+
+    #[test]
+    fn reports_invalid_feature_and_task_estimates() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- M\n\n## Features\n\n### A\nEstimate: 3 parsecs\n\n\
+             #### Form\nEstimate: 2d\n\n#### Tokens\nEstimate: soon\n",
+        );
+        let messages: Vec<_> = validate(&roadmap, &RuleConfig::default())
+            .into_iter()
+            .map(|d| format!("{} {}", d.code, d.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "GS010 feature 'A' has invalid estimate '3 parsecs'",
+                "GS010 task 'Tokens' has invalid estimate 'soon'",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_priorities() {
+        let roadmap = parse_roadmap(
+            "# P\n\n## Milestones\n- M\n\n## Features\n\n### A !!\n\n### B\nPriority: asap\n\n\
+             ### C\nPriority: low\n",
+        );
+        let diagnostics = validate(&roadmap, &RuleConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "GS011");
+        assert_eq!(
+            diagnostics[0].message,
+            "feature 'B' has unknown priority 'asap'"
+        );
+    }
+
+    #[test]
+    fn points_duplicates_across_files_at_their_files() {
+        let part = |source| parse_roadmap(source);
+        let roadmap = crate::aggregate::aggregate(vec![
+            (
+                "a.md".into(),
+                part("# P\n\n## Milestones\n- M\n\n## Features\n\n### X\n"),
+            ),
+            (
+                "b.md".into(),
+                part("# Q\n\n## Milestones\n- m\n\n## Features\n\n### X\n"),
+            ),
+        ]);
+        let diagnostics = validate(&roadmap, &RuleConfig::default());
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code, d.file.as_deref(), d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "GS003",
+                    Some("b.md"),
+                    Some(8),
+                    "duplicate feature title 'X', also defined in a.md"
+                ),
+                (
+                    "GS008",
+                    Some("b.md"),
+                    Some(4),
+                    "duplicate milestone name 'm', also defined in a.md"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! `${NAME}` placeholders in roadmap files.
+//!
+//! One roadmap can serve as a template for several products or releases:
+//! placeholders are replaced before the file is parsed, by the variables
+//! in [`Config::vars`], then by environment variables. `${NAME:-text}`
+//! falls back to `text` when `NAME` is defined nowhere, and `$${` stands
+//! for a literal `${`.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::error::ParseError;
+
+/// `source` with its placeholders replaced by the values in `config` or
+/// the environment. A placeholder without a value or default is an error.
+pub fn substitute<'a>(source: &'a str, config: &Config) -> Result<Cow<'a, str>, ParseError> {
+    substitute_with(source, |name| {
+        config
+            .vars
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+    })
+}
+
+fn substitute_with(
+    source: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Cow<'_, str>, ParseError> {
+    if !source.contains("${") {
+        return Ok(Cow::Borrowed(source));
+    }
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some((name, default, end)) = placeholder(after) else {
+            // Not a placeholder, like `${ x }`; keep it as written.
+            out.push_str("${");
+            rest = after;
+            continue;
+        };
+        match lookup(name).or(default.map(str::to_string)) {
+            Some(value) => out.push_str(&value),
+            None => {
+                return Err(ParseError::UndefinedVariable {
+                    name: name.to_string(),
+                    offset: source.len() - rest.len() + start,
+                })
+            }
+        }
+        rest = &after[end..];
+    }
+    out.push_str(rest);
+    Ok(Cow::Owned(out))
+}
+
+/// The name and default of the placeholder `text` starts with, just after
+/// its `${`, and where the placeholder ends in `text`.
+fn placeholder(text: &str) -> Option<(&str, Option<&str>, usize)> {
+    let close = text.find('}')?;
+    let inner = &text[..close];
+    let (name, default) = match inner.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (inner, None),
+    };
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some((name, default, close + 1))
+}
+
+/// Split a `KEY=VALUE` assignment, as given to `--var`.
+pub fn assignment(text: &str) -> Option<(String, String)> {
+    let (key, value) = text.split_once('=')?;
+    let key = key.trim();
+    (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+}
+
+/// Read a vars file: one `KEY=VALUE` per line, with blank lines and lines
+/// starting with `#` skipped, and quotes around a value dropped.
+pub fn load_file(path: &Path) -> Result<BTreeMap<String, String>, ParseError> {
+    let mut vars = BTreeMap::new();
+    for (index, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = assignment(line).ok_or_else(|| {
+            let message = format!("line {} is not KEY=VALUE", index + 1);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|&quote| value.strip_prefix(quote)?.strip_suffix(quote))
+            .map_or(value.clone(), str::to_string);
+        vars.insert(key, value);
+    }
+    Ok(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_placeholders_with_values_or_defaults() {
+        let lookup = |name: &str| (name == "VERSION").then(|| "2.1".to_string());
+        let source =
+            "# App ${VERSION}\n\n- **v${VERSION}** — ${DUE:-TBD}\n\nCost: $$5, $${VERSION}, ${ x }";
+        assert_eq!(
+            substitute_with(source, lookup).unwrap(),
+            "# App 2.1\n\n- **v2.1** — TBD\n\nCost: $$5, ${VERSION}, ${ x }"
+        );
+
+        let error = substitute_with("# A\n${RELEASE}", lookup).unwrap_err();
+        assert!(matches!(
+            error,
+            ParseError::UndefinedVariable { ref name, offset: 4 } if name == "RELEASE"
+        ));
+    }
+}
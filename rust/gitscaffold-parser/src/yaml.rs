@@ -0,0 +1,112 @@
+//! Structured YAML roadmap input.
+//!
+//! YAML roadmaps use the same keys as the JSON output, so they deserialize
+//! straight into [`Roadmap`] and produce identical JSON to the equivalent
+//! Markdown document.
+
+use crate::dates::normalize_date;
+use crate::graph;
+use crate::migrate;
+use crate::roadmap::{Feature, Roadmap};
+use crate::text::normalize;
+
+/// Parse a YAML (or JSON) roadmap document.
+pub fn parse_roadmap(source: &str) -> Result<Roadmap, serde_yaml::Error> {
+    let mut roadmap: Roadmap = serde_yaml::from_str(source)?;
+    for milestone in &mut roadmap.milestones {
+        milestone.name = normalize(&milestone.name);
+        milestone.due_date = milestone.due_date.as_deref().map(normalize_date);
+    }
+    // Accept `@login` mentions, like the Markdown metadata lines do.
+    for feature in &mut roadmap.features {
+        strip_mentions(&mut feature.assignees);
+        for task in &mut feature.tasks {
+            strip_mentions(&mut task.assignees);
+        }
+    }
+    normalize_titles(&mut roadmap.features);
+    graph::resolve(&mut roadmap);
+    roadmap.warnings.extend(migrate::check(roadmap.version));
+    Ok(roadmap)
+}
+
+/// Normalize titles and the names they refer to, as the Markdown parser
+/// does for the text it reads.
+fn normalize_titles(features: &mut [Feature]) {
+    for feature in features {
+        feature.title = normalize(&feature.title);
+        feature.milestone = feature.milestone.as_deref().map(normalize);
+        for title in &mut feature.depends_on {
+            *title = normalize(title);
+        }
+        for task in &mut feature.tasks {
+            task.title = normalize(&task.title);
+        }
+        normalize_titles(&mut feature.children);
+    }
+}
+
+fn strip_mentions(assignees: &mut [String]) {
+    for assignee in assignees {
+        if let Some(login) = assignee.strip_prefix('@') {
+            *assignee = login.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract;
+
+    #[test]
+    fn yaml_and_markdown_produce_the_same_roadmap() {
+        let yaml = "\
+name: Demo
+description: A demo roadmap.
+milestones:
+  - name: M1
+    due_date: January 31, 2025
+features:
+  - title: Login
+    description: Let users sign in.
+    milestone: M1
+    labels: [auth]
+    tasks:
+      - title: Form
+        completed: true
+";
+        let markdown = "\
+# Demo
+
+A demo roadmap.
+
+## Milestones
+- M1 — 2025-01-31
+
+## Features
+
+### Login
+Let users sign in.
+Milestone: M1
+Labels: auth
+
+#### [x] Form
+";
+        let mut from_markdown = extract::parse_roadmap(markdown);
+        from_markdown.strip_spans();
+        assert_eq!(parse_roadmap(yaml).unwrap(), from_markdown);
+    }
+
+    #[test]
+    fn missing_name_defaults_to_empty() {
+        let roadmap = parse_roadmap("features:\n  - title: Only\n").unwrap();
+        assert_eq!(roadmap.name, "");
+        assert_eq!(roadmap.features[0].title, "Only");
+    }
+
+    #[test]
+    fn rejects_non_mapping_documents() {
+        assert!(parse_roadmap("- just\n- a list\n").is_err());
+    }
+}
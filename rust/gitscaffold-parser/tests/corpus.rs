@@ -0,0 +1,40 @@
+//! Real-world roadmaps in `tests/corpus/`: each parses, serializes to
+//! JSON, and settles after one render → parse round trip.
+
+use std::fs;
+use std::path::Path;
+
+use gitscaffold_parser::render::{render_markdown, RenderOptions};
+use gitscaffold_parser::{parse_markdown, Roadmap};
+
+fn canonical(roadmap: &Roadmap) -> Roadmap {
+    let markdown = render_markdown(roadmap, RenderOptions::default());
+    let mut parsed = parse_markdown(&markdown).unwrap();
+    parsed.strip_spans();
+    parsed.warnings.clear();
+    parsed
+}
+
+#[test]
+fn corpus_roadmaps_parse_and_round_trip() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut files: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "no roadmaps in {}", dir.display());
+    for path in files {
+        let source = fs::read_to_string(&path).unwrap();
+        let roadmap =
+            parse_markdown(&source).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let json = serde_json::to_string(&roadmap).unwrap();
+        let back: Roadmap = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, roadmap, "{}: JSON round trip", path.display());
+
+        let once = canonical(&roadmap);
+        let twice = canonical(&once);
+        assert_eq!(twice, once, "{}: render round trip", path.display());
+    }
+}
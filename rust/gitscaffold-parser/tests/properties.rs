@@ -0,0 +1,136 @@
+//! Property tests: the parser never panics on arbitrary Markdown, its JSON
+//! is always valid, and canonical documents survive a render → parse
+//! round trip unchanged.
+
+use gitscaffold_parser::render::{render_markdown, RenderOptions};
+use gitscaffold_parser::{parse_markdown, Feature, Milestone, Roadmap, Task};
+use proptest::prelude::*;
+
+/// Lines that look like roadmap Markdown, to reach deeper into the parser
+/// than random text would.
+fn markdown_line() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "#{1,6} [^\n]{0,20}",
+        "- \\*\\*[a-zA-Z0-9 ]{1,10}\\*\\* — [0-9-]{0,10}",
+        "- \\[[ xX]\\] [^\n]{0,20}",
+        " {0,6}[-*+] [^\n]{0,20}",
+        "(Labels|Assignees|Milestone|Status|Estimate|Depends on|Repo|Kind): [^\n]{0,20}",
+        "\\|[^\n|]{0,8}\\|[^\n|]{0,8}\\|",
+        "(```|~~~)[a-z]{0,4}",
+        "---",
+        "<!-- [^\n]{0,20} -->",
+        "[^\n]{0,40}",
+    ]
+}
+
+fn markdown() -> impl Strategy<Value = String> {
+    prop::collection::vec(markdown_line(), 0..40).prop_map(|lines| lines.join("\n"))
+}
+
+fn words() -> impl Strategy<Value = String> {
+    "[A-Za-z][a-z0-9]{0,7}( [A-Za-z0-9][a-z0-9]{0,7}){0,3}"
+}
+
+fn names() -> impl Strategy<Value = Vec<String>> {
+    prop::collection::btree_set("[a-z][a-z0-9-]{0,7}", 0..3)
+        .prop_map(|set| set.into_iter().collect())
+}
+
+fn task() -> impl Strategy<Value = Task> {
+    (words(), names(), any::<bool>()).prop_map(|(title, labels, completed)| Task {
+        title,
+        labels,
+        completed,
+        ..Task::default()
+    })
+}
+
+/// A roadmap as `render_markdown` writes it: every field it renders set to
+/// a value the parser reads back, and nothing it does not render.
+fn canonical_roadmap() -> impl Strategy<Value = Roadmap> {
+    let milestones = prop::collection::vec(
+        prop::option::of("20[0-9]{2}-(0[1-9]|1[0-2])-(0[1-9]|1[0-9]|2[0-8])"),
+        0..4,
+    );
+    let features = prop::collection::vec(
+        (
+            words(),
+            prop::option::of(words()),
+            any::<prop::sample::Index>(),
+            names(),
+            names(),
+            prop::collection::vec(task(), 0..4),
+        ),
+        0..5,
+    );
+    (words(), prop::option::of(words()), milestones, features).prop_map(
+        |(name, description, dates, features)| {
+            let milestones: Vec<Milestone> = dates
+                .into_iter()
+                .enumerate()
+                .map(|(i, due_date)| Milestone {
+                    name: format!("M{}", i + 1),
+                    due_date,
+                    ..Milestone::default()
+                })
+                .collect();
+            let features = features
+                .into_iter()
+                .enumerate()
+                .map(
+                    |(i, (title, description, milestone, labels, assignees, tasks))| {
+                        let milestone_id =
+                            (!milestones.is_empty()).then(|| milestone.index(milestones.len()));
+                        Feature {
+                            // Numbered, so no two features share a heading.
+                            title: format!("{} {}", title, i + 1),
+                            description: description.unwrap_or_default(),
+                            milestone: milestone_id.map(|id| milestones[id].name.clone()),
+                            milestone_id,
+                            labels,
+                            assignees,
+                            // Numbered too, as tasks with labels are headings.
+                            tasks: tasks
+                                .into_iter()
+                                .enumerate()
+                                .map(|(j, task)| Task {
+                                    title: format!("{} {}", task.title, j + 1),
+                                    ..task
+                                })
+                                .collect(),
+                            ..Feature::default()
+                        }
+                    },
+                )
+                .collect();
+            Roadmap {
+                name,
+                description: description.unwrap_or_default(),
+                milestones,
+                features,
+                ..Roadmap::default()
+            }
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn parses_any_markdown_into_valid_json(source in markdown()) {
+        let Ok(roadmap) = parse_markdown(&source) else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec(&roadmap).unwrap();
+        let text = String::from_utf8(json).unwrap();
+        serde_json::from_str::<serde_json::Value>(&text).unwrap();
+    }
+
+    #[test]
+    fn canonical_documents_round_trip(roadmap in canonical_roadmap()) {
+        let markdown = render_markdown(&roadmap, RenderOptions::default());
+        let mut parsed = parse_markdown(&markdown).unwrap();
+        parsed.strip_spans();
+        prop_assert_eq!(&parsed, &roadmap, "rendered as:\n{}", markdown);
+        prop_assert_eq!(render_markdown(&parsed, RenderOptions::default()), markdown);
+    }
+}
@@ -0,0 +1,159 @@
+//! Step outputs and annotations for GitHub Actions, written by
+//! `scaffold --github-output`.
+//!
+//! Outputs are appended to the file `$GITHUB_OUTPUT` names, as `name=value`
+//! lines or, for values spanning lines, as `name<<DELIMITER` blocks.
+//! Annotations are workflow commands printed on stdout, such as
+//! `::error file=ROADMAP.md,line=3::message`, which the runner turns into
+//! messages on the workflow run and the pull request's diff.
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use gitscaffold_parser::validate::{Diagnostic, Severity};
+
+/// The severity of an annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Notice,
+}
+
+/// A workflow command annotating the run, printed on stderr.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub level: Level,
+    pub title: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Annotation {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Annotation {
+            level,
+            title: None,
+            file: None,
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    /// The annotation of a validation diagnostic about the roadmap `file`.
+    pub fn diagnostic(diagnostic: &Diagnostic, file: &str) -> Self {
+        let level = match diagnostic.severity {
+            Severity::Error => Level::Error,
+            Severity::Warning => Level::Warning,
+        };
+        Annotation {
+            title: Some(diagnostic.code.to_string()),
+            file: Some(diagnostic.file.as_deref().unwrap_or(file).to_string()),
+            line: diagnostic.line,
+            column: diagnostic.column,
+            ..Annotation::new(level, diagnostic.message.clone())
+        }
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let command = match self.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Notice => "notice",
+        };
+        let mut properties = Vec::new();
+        if let Some(file) = &self.file {
+            properties.push(format!("file={}", escape_property(file)));
+        }
+        if let Some(line) = self.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(column) = self.column {
+            properties.push(format!("col={}", column));
+        }
+        if let Some(title) = &self.title {
+            properties.push(format!("title={}", escape_property(title)));
+        }
+        write!(f, "::{}", command)?;
+        if !properties.is_empty() {
+            write!(f, " {}", properties.join(","))?;
+        }
+        write!(f, "::{}", escape_data(&self.message))
+    }
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Append `outputs` to the step output file at `path`.
+pub fn write_outputs(path: &Path, outputs: &[(&str, String)]) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format_outputs(outputs).as_bytes())
+}
+
+fn format_outputs(outputs: &[(&str, String)]) -> String {
+    let mut text = String::new();
+    for (name, value) in outputs {
+        if value.contains('\n') || value.contains('\r') {
+            // A delimiter that cannot occur in the value.
+            let mut delimiter = String::from("EOF");
+            while value.lines().any(|line| line == delimiter) {
+                delimiter.push('_');
+            }
+            text.push_str(&format!(
+                "{}<<{}\n{}\n{}\n",
+                name, delimiter, value, delimiter
+            ));
+        } else {
+            text.push_str(&format!("{}={}\n", name, value));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_outputs_and_escapes_annotations() {
+        let outputs = [
+            ("created_count", "2".to_string()),
+            ("plan", "+ create issue 'A'\nEOF\n".to_string()),
+        ];
+        assert_eq!(
+            format_outputs(&outputs),
+            "created_count=2\nplan<<EOF_\n+ create issue 'A'\nEOF\n\nEOF_\n"
+        );
+
+        let annotation = Annotation {
+            title: Some("E001".into()),
+            file: Some("docs/a,b.md".into()),
+            line: Some(3),
+            column: Some(1),
+            ..Annotation::new(Level::Error, "50% done\nsee: below")
+        };
+        assert_eq!(
+            annotation.to_string(),
+            "::error file=docs/a%2Cb.md,line=3,col=1,title=E001::50%25 done%0Asee: below"
+        );
+        assert_eq!(
+            Annotation::new(Level::Notice, "ok").to_string(),
+            "::notice::ok"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::status;
+use mdparser::sync::{apply, Plan, Synced, PLAN_VERSION};
+
+use super::sync::{failed, print_progress, print_report, renamed, report_outputs};
+use super::{read_input, Context, ForgeArgs, JobsArgs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Plan written by `scaffold sync --plan-out`, or `-` for stdin
+    plan: String,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    #[command(flatten)]
+    jobs: JobsArgs,
+}
+
+/// Apply a plan saved by `scaffold sync --plan-out`.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let content = read_input(&args.plan)?;
+    let plan: Plan =
+        serde_json::from_str(&content).map_err(|e| ScaffoldError::parse(Some(&args.plan), e))?;
+    if plan.version != PLAN_VERSION {
+        return Err(ScaffoldError::parse(
+            Some(&args.plan),
+            format!("unsupported plan version {}", plan.version),
+        ));
+    }
+
+    let forge = args.forge.client(&context.config)?;
+    if plan.repo != forge.repo() {
+        return Err(ScaffoldError::Usage(format!(
+            "{} was planned for {}, not {}",
+            args.plan,
+            plan.repo,
+            forge.repo()
+        )));
+    }
+    let total = plan.operations.len();
+    let execution = args.jobs.execution(context);
+    let report = apply(&plan, &forge, execution, |step, operation| {
+        print_progress(step, total, operation)
+    })
+    .await
+    .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    print_report(&report);
+    context.record(forge.repo(), &report)?;
+    report_outputs(context, &report)?;
+    let created = |items: &[Synced]| items.iter().filter(|item| item.created).count();
+    status!(
+        "Applied {} to {}: created {} milestone(s) and {} issue(s), renamed {} issue(s), \
+         updated {} issue(s), closed {} issue(s).",
+        args.plan,
+        forge.repo(),
+        created(&report.milestones),
+        created(&report.issues),
+        renamed(&report),
+        report.updated.len(),
+        report.closed.len()
+    );
+    failed(&forge, report)
+}
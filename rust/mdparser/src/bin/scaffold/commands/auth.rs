@@ -0,0 +1,144 @@
+use std::io::{self, BufRead, IsTerminal};
+
+use gitscaffold_parser::ForgeKind;
+use mdparser::credentials::{self, KeyringError};
+use mdparser::error::ScaffoldError;
+use mdparser::status;
+
+use super::{api_url, Context};
+use crate::style::{paint, use_color, GREEN, YELLOW};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: AuthCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum AuthCommand {
+    /// Store a token in the OS keyring
+    Login(LoginArgs),
+    /// Remove a token stored by `login`
+    Logout(HostArgs),
+    /// Show where the token for a forge comes from
+    Status(StatusArgs),
+}
+
+/// Which forge the token is for.
+#[derive(clap::Args)]
+struct HostArgs {
+    /// Forge the token is for; defaults to `kind` in the `[forge]` config,
+    /// then GitHub
+    #[arg(long, value_enum)]
+    forge: Option<ForgeKind>,
+    /// Host the token is for, e.g. `github.com`; defaults to the host of
+    /// the forge's API URL
+    #[arg(long)]
+    hostname: Option<String>,
+    /// Base URL of the forge's REST API
+    #[arg(long)]
+    api_url: Option<String>,
+}
+
+impl HostArgs {
+    fn resolve(&self, context: &Context) -> (ForgeKind, String) {
+        let kind = self.forge.or(context.config.forge.kind).unwrap_or_default();
+        let host = match &self.hostname {
+            Some(host) => host.to_lowercase(),
+            None => credentials::host(&api_url(kind, self.api_url.as_deref(), &context.config)),
+        };
+        (kind, host)
+    }
+}
+
+#[derive(clap::Args)]
+struct LoginArgs {
+    #[command(flatten)]
+    host: HostArgs,
+    /// Read the token from stdin instead of prompting for it
+    #[arg(long)]
+    with_token: bool,
+}
+
+#[derive(clap::Args)]
+struct StatusArgs {
+    #[command(flatten)]
+    host: HostArgs,
+    /// Token that would be passed to other commands, to check it is picked
+    #[arg(long)]
+    token: Option<String>,
+}
+
+/// Manage the tokens used to talk to forges.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    match args.command {
+        AuthCommand::Login(args) => login(args, context),
+        AuthCommand::Logout(args) => logout(args, context),
+        AuthCommand::Status(args) => status(args, context),
+    }
+}
+
+fn login(args: LoginArgs, context: &Context) -> Result<(), ScaffoldError> {
+    let (_, host) = args.host.resolve(context);
+    let token = if args.with_token || !io::stdin().is_terminal() {
+        let mut line = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| ScaffoldError::io("<stdin>", e))?;
+        line
+    } else {
+        rpassword::prompt_password(format!("Token for {}: ", host))
+            .map_err(|e| ScaffoldError::io("<terminal>", e))?
+    };
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(ScaffoldError::Usage("no token given".into()));
+    }
+    credentials::store(&host, token).map_err(|e| keyring_error(&host, e))?;
+    status!("Stored the token for {} in the OS keyring.", host);
+    Ok(())
+}
+
+fn logout(args: HostArgs, context: &Context) -> Result<(), ScaffoldError> {
+    let (_, host) = args.resolve(context);
+    if credentials::delete(&host).map_err(|e| keyring_error(&host, e))? {
+        status!("Removed the token for {} from the OS keyring.", host);
+    } else {
+        status!("The OS keyring holds no token for {}.", host);
+    }
+    Ok(())
+}
+
+fn status(args: StatusArgs, context: &Context) -> Result<(), ScaffoldError> {
+    let (kind, host) = args.host.resolve(context);
+    let color = use_color();
+    match credentials::resolve(kind, &host, args.token.as_deref()) {
+        Some(credential) => {
+            let line = format!(
+                "{}: token {} from {}",
+                host,
+                credential.masked(),
+                credential.source
+            );
+            println!("{}", paint(&line, GREEN, color));
+            Ok(())
+        }
+        None => {
+            let line = format!("{}: no token found", host);
+            println!("{}", paint(&line, YELLOW, color));
+            Err(ScaffoldError::Usage(format!(
+                "no token for {}: pass --token, set {}, or run `scaffold auth login`",
+                host,
+                credentials::env_vars(kind)[0]
+            )))
+        }
+    }
+}
+
+fn keyring_error(host: &str, error: KeyringError) -> ScaffoldError {
+    ScaffoldError::io(
+        &format!("keyring entry for {}", host),
+        io::Error::other(error),
+    )
+}
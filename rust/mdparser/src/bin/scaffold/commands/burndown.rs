@@ -0,0 +1,91 @@
+use chrono::{NaiveDate, Utc};
+use clap::ValueEnum;
+use futures::{stream, StreamExt, TryStreamExt};
+
+use mdparser::burndown::Burndown;
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+
+use super::{write_output, Context, ForgeArgs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BurndownFormat {
+    /// The milestone, its due date and one object per day
+    Json,
+    /// One `date,open,closed` row per day
+    Csv,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The milestone to chart, by title
+    #[arg(long, value_name = "NAME")]
+    milestone: String,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// First day of the series, as YYYY-MM-DD; defaults to the day the
+    /// first issue joined the milestone
+    #[arg(long, value_name = "DATE")]
+    from: Option<NaiveDate>,
+    /// Last day of the series, as YYYY-MM-DD; defaults to today (UTC)
+    #[arg(long, value_name = "DATE")]
+    to: Option<NaiveDate>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = BurndownFormat::Json)]
+    format: BurndownFormat,
+    /// How many issues' event logs to fetch at once
+    #[arg(short, long, value_name = "N", default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
+    /// Write the series to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Print the daily open and closed issue counts of a milestone.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let forge = args.forge.client(&context.config)?;
+    let github = |e| ScaffoldError::github(forge.repo(), e);
+    let milestones = forge.list_milestones().await.map_err(github)?;
+    let milestone = milestones
+        .iter()
+        .find(|milestone| milestone.title.trim() == args.milestone.trim())
+        .ok_or_else(|| {
+            ScaffoldError::Usage(format!(
+                "{} has no milestone titled '{}'",
+                forge.repo(),
+                args.milestone
+            ))
+        })?;
+    let issues = forge.list_issues().await.map_err(github)?;
+    let issues: Vec<_> = issues
+        .iter()
+        .filter(|issue| {
+            issue
+                .milestone
+                .as_ref()
+                .is_some_and(|m| m.number == milestone.number)
+        })
+        .collect();
+    tracing::info!(
+        milestone = milestone.title,
+        issues = issues.len(),
+        "reading issue events"
+    );
+    let histories: Vec<_> = stream::iter(&issues)
+        .map(|issue| forge.issue_events(issue))
+        .buffered(args.jobs.into())
+        .try_collect()
+        .await
+        .map_err(github)?;
+
+    let to = args.to.unwrap_or_else(|| Utc::now().date_naive());
+    let burndown = Burndown {
+        due_on: milestone.due_on.clone(),
+        ..Burndown::new(&milestone.title, &histories, args.from, to)
+    };
+    let text = match args.format {
+        BurndownFormat::Json => format!("{}\n", context.json.to_string(&burndown)?),
+        BurndownFormat::Csv => burndown.to_csv(),
+    };
+    write_output(args.output.as_deref(), &text)
+}
@@ -0,0 +1,104 @@
+use gitscaffold_parser::capacity::{capacity, CapacityReport, Load};
+use gitscaffold_parser::{CapacityUnit, InputFormat};
+use mdparser::error::ScaffoldError;
+
+use super::{Context, Inputs, ReportFormat};
+use crate::style::{paint, use_color, RED};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// How to print the report
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+/// Print the estimated work per milestone against the `[capacity]` config.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let report = capacity(&roadmap, &context.config.capacity);
+    match args.report_format {
+        ReportFormat::Human => print_table(&report),
+        ReportFormat::Json => println!("{}", context.json.to_string(&report)?),
+    }
+    Ok(())
+}
+
+const HEADER: [&str; 6] = [
+    "Milestone",
+    "Features",
+    "Unestimated",
+    "Days",
+    "Points",
+    "Capacity",
+];
+
+fn print_table(report: &CapacityReport) {
+    let unit = match report.unit {
+        CapacityUnit::Days => "d",
+        CapacityUnit::Points => "pts",
+    };
+    let mut rows: Vec<(Vec<String>, bool)> = report
+        .milestones
+        .iter()
+        .map(|m| {
+            let name = m.name.as_deref().unwrap_or("(no milestone)");
+            let capacity = m
+                .capacity
+                .map_or("-".into(), |c| format!("{}{}", number(c), unit));
+            (row(name, &m.load, capacity), m.over_capacity)
+        })
+        .collect();
+    rows.push((row("Total", &report.overall, String::new()), false));
+
+    let mut widths: Vec<usize> = HEADER.iter().map(|h| h.len()).collect();
+    for (cells, _) in &rows {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    let color = use_color();
+    println!("{}", line(&HEADER.map(String::from)));
+    for (cells, over) in &rows {
+        if *over {
+            println!(
+                "{}",
+                paint(&format!("{}  over capacity", line(cells)), RED, color)
+            );
+        } else {
+            println!("{}", line(cells));
+        }
+    }
+    if !report.over_capacity.is_empty() {
+        println!("Over capacity: {}", report.over_capacity.join(", "));
+    }
+}
+
+fn row(name: &str, load: &Load, capacity: String) -> Vec<String> {
+    vec![
+        name.to_string(),
+        load.features.to_string(),
+        load.unestimated.to_string(),
+        number(load.days),
+        number(load.points),
+        capacity,
+    ]
+}
+
+/// `f64` without a trailing `.0`, rounded to one decimal.
+fn number(value: f64) -> String {
+    let rounded = (value * 10.0).round() / 10.0;
+    format!("{}", rounded)
+}
@@ -0,0 +1,125 @@
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+
+use gitscaffold_parser::due::{check_due, DueAlert, DueState};
+use gitscaffold_parser::InputFormat;
+use mdparser::actions::{Annotation, Level};
+use mdparser::error::ScaffoldError;
+
+use super::{Context, Inputs, ReportFormat};
+use crate::style::{paint, use_color, RED, YELLOW};
+
+/// Which milestones make the check fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FailOn {
+    /// Milestones due soon or overdue
+    DueSoon,
+    /// Overdue milestones only
+    Overdue,
+    /// None; only report
+    Never,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// Warn about milestones due within this many days
+    #[arg(long, value_name = "DAYS", default_value_t = 7)]
+    within: u32,
+    /// Check as of this date, as YYYY-MM-DD, instead of today
+    #[arg(long, value_name = "DATE")]
+    today: Option<NaiveDate>,
+    /// Exit with status 1 when milestones like these are flagged
+    #[arg(long, value_enum, default_value_t = FailOn::DueSoon)]
+    fail_on: FailOn,
+    /// How to print the flagged milestones
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+/// Flag milestones with unfinished features that are overdue or due soon.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let today = args.today.unwrap_or_else(|| Local::now().date_naive());
+    let alerts = check_due(&roadmap, today, args.within);
+    match args.report_format {
+        ReportFormat::Human => print_alerts(&alerts, args.within),
+        ReportFormat::Json => println!("{}", context.json.to_string(&alerts)?),
+    }
+
+    for alert in &alerts {
+        let level = match alert.state {
+            DueState::Overdue => Level::Error,
+            DueState::DueSoon => Level::Warning,
+        };
+        context.annotate(Annotation {
+            title: Some("Milestone due".into()),
+            ..Annotation::new(level, describe(alert))
+        });
+    }
+    let overdue = alerts
+        .iter()
+        .filter(|alert| alert.state == DueState::Overdue)
+        .count();
+    let due_soon = alerts.len() - overdue;
+    context.outputs(&[
+        ("overdue_count", overdue.to_string()),
+        ("due_soon_count", due_soon.to_string()),
+    ])?;
+
+    let failing = match args.fail_on {
+        FailOn::DueSoon => alerts.len(),
+        FailOn::Overdue => overdue,
+        FailOn::Never => 0,
+    };
+    if failing == 0 {
+        return Ok(());
+    }
+    Err(ScaffoldError::Validation(format!(
+        "{} milestone(s) overdue and {} due within {} day(s) have unfinished features",
+        overdue, due_soon, args.within
+    )))
+}
+
+/// One line about `alert`, e.g. `Beta is due in 4 day(s), on 2025-03-05:
+/// 1 of 2 feature(s) unfinished`.
+fn describe(alert: &DueAlert) -> String {
+    let when = match alert.days_left {
+        ..0 => format!("was due {} day(s) ago", -alert.days_left),
+        0 => "is due today".to_string(),
+        days => format!("is due in {} day(s)", days),
+    };
+    format!(
+        "{} {}, on {}: {} of {} feature(s) unfinished",
+        alert.milestone,
+        when,
+        alert.due_date,
+        alert.unfinished.len(),
+        alert.features
+    )
+}
+
+fn print_alerts(alerts: &[DueAlert], within: u32) {
+    if alerts.is_empty() {
+        println!(
+            "No milestone with unfinished features is overdue or due within {} day(s).",
+            within
+        );
+        return;
+    }
+    let color = use_color();
+    for alert in alerts {
+        let tint = match alert.state {
+            DueState::Overdue => RED,
+            DueState::DueSoon => YELLOW,
+        };
+        println!("{}", paint(&describe(alert), tint, color));
+        for title in &alert.unfinished {
+            println!("  - {}", title);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::{self, Write};
+
+use clap_complete::Shell;
+
+use mdparser::error::ScaffoldError;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Shell to write the completion script for
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+/// Write the completion script for `command`, the whole CLI, to stdout.
+/// Subcommands and flags complete in every shell; bash, zsh and fish also
+/// complete the values of enum flags such as `--format` and `--forge`.
+pub fn run(args: Args, mut command: clap::Command) -> Result<(), ScaffoldError> {
+    let mut script = Vec::new();
+    clap_complete::generate(args.shell, &mut command, "scaffold", &mut script);
+    io::stdout()
+        .write_all(&script)
+        .map_err(|e| ScaffoldError::io("stdout", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, ValueEnum};
+    use clap_complete::Shell;
+
+    #[test]
+    fn completes_subcommands_flags_and_values() {
+        for &shell in Shell::value_variants() {
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut crate::Cli::command(), "scaffold", &mut script);
+            let script = String::from_utf8(script).unwrap();
+            let mut words = vec!["delete-closed", "max-task-depth"];
+            if matches!(shell, Shell::Bash | Shell::Zsh | Shell::Fish) {
+                words.extend(["gitlab", "mock"]);
+            }
+            for word in words {
+                assert!(script.contains(word), "{} lacks {}", shell, word);
+            }
+        }
+    }
+}
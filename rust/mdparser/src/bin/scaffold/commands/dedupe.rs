@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use gitscaffold_parser::InputFormat;
+use mdparser::dedupe::{dedupe, Cluster};
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::template::IssueTemplates;
+
+use super::{Context, ForgeArgs, Inputs, ReportFormat};
+use crate::style::{paint, use_color, YELLOW};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Only compare the roadmap's features with each other, without
+    /// fetching the repository's issues
+    #[arg(long)]
+    roadmap_only: bool,
+    /// How alike two titles must be to be flagged, from 0 to 1; defaults to
+    /// `fuzzy_threshold` under `[sync]`
+    #[arg(long, value_name = "SCORE")]
+    threshold: Option<f64>,
+    /// How to print the suspected duplicates
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+    /// Directory with the `issue_title.j2` template used for sync, to
+    /// compare issues with the titles sync gives features
+    #[arg(long, value_name = "DIR")]
+    template_dir: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    file: &'a str,
+    repo: Option<&'a str>,
+    threshold: f64,
+    clusters: &'a [Cluster],
+}
+
+/// Print clusters of features, and issues no feature has, whose titles are
+/// suspiciously alike.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let threshold = args
+        .threshold
+        .unwrap_or(context.config.sync.fuzzy_threshold);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(ScaffoldError::Usage(format!(
+            "--threshold must be between 0 and 1, not {}",
+            threshold
+        )));
+    }
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let templates = match &args.template_dir {
+        Some(dir) => IssueTemplates::from_dir(dir)?,
+        None => IssueTemplates::default(),
+    };
+    let texts = templates
+        .with_titles(&context.config.titles)?
+        .with_fields(&context.config.fields)
+        .render(&roadmap)
+        .map_err(|e| ScaffoldError::parse(None, e))?;
+    let (repo, issues) = match args.roadmap_only {
+        true => (None, Vec::new()),
+        false => {
+            let forge = args.forge.client(&context.config)?;
+            let issues = forge
+                .list_issues()
+                .await
+                .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+            (Some(forge.repo().to_string()), issues)
+        }
+    };
+    let clusters = dedupe(&roadmap, &texts, &issues, threshold);
+
+    let file = args.input.to_string();
+    match args.report_format {
+        ReportFormat::Human => {
+            let color = use_color();
+            for (n, cluster) in clusters.iter().enumerate() {
+                let entries: Vec<String> = cluster.entries.iter().map(|e| e.to_string()).collect();
+                println!("{}. {}", n + 1, paint(&entries.join(", "), YELLOW, color));
+                for pair in &cluster.pairs {
+                    println!(
+                        "   {:>3.0}%  {} ~ {}",
+                        pair.score * 100.0,
+                        entries[pair.a],
+                        entries[pair.b]
+                    );
+                }
+            }
+            let scope = match &repo {
+                Some(repo) => format!("{} and {}", file, repo),
+                None => file.clone(),
+            };
+            match clusters.len() {
+                0 => println!("No suspected duplicates in {}.", scope),
+                n => println!("{} cluster(s) of suspected duplicates in {}.", n, scope),
+            }
+        }
+        ReportFormat::Json => {
+            let report = Report {
+                file: &file,
+                repo: repo.as_deref(),
+                threshold,
+                clusters: &clusters,
+            };
+            println!("{}", context.json.to_string(&report)?);
+        }
+    }
+    Ok(())
+}
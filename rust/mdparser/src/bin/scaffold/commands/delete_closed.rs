@@ -0,0 +1,210 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::github::RemoteIssue;
+use mdparser::status;
+
+use super::{Context, ForgeArgs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Action {
+    /// Permanently delete the issues (needs admin access)
+    Delete,
+    /// Lock the issues' conversations and keep them
+    Lock,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// What to do with the closed issues
+    #[arg(long, value_enum, default_value_t = Action::Delete)]
+    action: Action,
+    /// Only issues closed longer ago than this, e.g. `90d` or `12w`
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    older_than: Option<Duration>,
+    /// List the issues without touching them
+    #[arg(long)]
+    dry_run: bool,
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Parse an age such as `90d` or `12w`.
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (count, unit) = value.split_at(value.len().saturating_sub(1));
+    let count: i64 = count.parse().map_err(|_| {
+        format!(
+            "expected a number of days or weeks such as 90d, got '{}'",
+            value
+        )
+    })?;
+    match unit {
+        "d" => Ok(Duration::days(count)),
+        "w" => Ok(Duration::weeks(count)),
+        _ => Err(format!(
+            "unknown unit in '{}'; use d (days) or w (weeks)",
+            value
+        )),
+    }
+}
+
+/// Whether `issue` was closed before `cutoff`. Issues without a known close
+/// time are kept.
+fn closed_before(issue: &RemoteIssue, cutoff: DateTime<Utc>) -> bool {
+    issue
+        .closed_at
+        .as_deref()
+        .and_then(|closed| DateTime::parse_from_rfc3339(closed).ok())
+        .is_some_and(|closed| closed < cutoff)
+}
+
+/// Delete or lock the repository's closed issues.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let forge = args.forge.client(&context.config)?;
+    let mut issues = forge
+        .list_issues()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    issues.retain(|issue| issue.state == "closed");
+    if let Some(age) = args.older_than {
+        let cutoff = Utc::now() - age;
+        issues.retain(|issue| closed_before(issue, cutoff));
+    }
+    if issues.is_empty() {
+        status!(
+            "No closed issues to {} in {}.",
+            verb(args.action),
+            forge.repo()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Found {} closed issue(s) in {}:",
+        issues.len(),
+        forge.repo()
+    );
+    for issue in &issues {
+        println!("  #{} {}", issue.number, issue.title);
+    }
+    if args.dry_run {
+        status!("Dry run: no issues were changed.");
+        return Ok(());
+    }
+    if !args.yes {
+        if !io::stdin().is_terminal() {
+            return Err(ScaffoldError::Usage(
+                "refusing to continue without confirmation; pass --yes".into(),
+            ));
+        }
+        let warning = match args.action {
+            Action::Delete => " This cannot be undone.",
+            Action::Lock => "",
+        };
+        eprint!(
+            "{} {} issue(s) in {}?{} [y/N] ",
+            capitalize(verb(args.action)),
+            issues.len(),
+            forge.repo(),
+            warning
+        );
+        io::stderr().flush().ok();
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|e| ScaffoldError::io("<stdin>", e))?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            status!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let mut failures = Vec::new();
+    for issue in &issues {
+        let result = match args.action {
+            Action::Delete => forge.delete_issue(issue).await,
+            Action::Lock => forge.lock_issue(issue.number).await,
+        };
+        match result {
+            Ok(()) => status!("{} #{} {}", past(args.action), issue.number, issue.title),
+            Err(e) => {
+                tracing::error!("Failed to {} #{}: {}", verb(args.action), issue.number, e);
+                failures.push(e);
+            }
+        }
+    }
+    status!(
+        "{} {} of {} issue(s) in {}.",
+        past(args.action),
+        issues.len() - failures.len(),
+        issues.len(),
+        forge.repo()
+    );
+    match failures.into_iter().next() {
+        Some(e) => Err(ScaffoldError::github(forge.repo(), e)),
+        None => Ok(()),
+    }
+}
+
+fn verb(action: Action) -> &'static str {
+    match action {
+        Action::Delete => "delete",
+        Action::Lock => "lock",
+    }
+}
+
+fn past(action: Action) -> &'static str {
+    match action {
+        Action::Delete => "Deleted",
+        Action::Lock => "Locked",
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ages_and_filters_by_close_time() {
+        assert_eq!(parse_age("90d"), Ok(Duration::days(90)));
+        assert_eq!(parse_age("2w"), Ok(Duration::days(14)));
+        assert!(parse_age("3 months").is_err());
+        assert!(parse_age("d").is_err());
+
+        let cutoff = "2025-03-01T00:00:00Z".parse().unwrap();
+        let issue = |closed_at: Option<&str>| RemoteIssue {
+            number: 1,
+            node_id: String::new(),
+            title: "Old".into(),
+            state: "closed".into(),
+            created_at: None,
+            closed_at: closed_at.map(str::to_string),
+            body: None,
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
+            html_url: None,
+            pull_request: None,
+        };
+        assert!(closed_before(&issue(Some("2025-01-15T10:00:00Z")), cutoff));
+        assert!(!closed_before(&issue(Some("2025-03-02T10:00:00Z")), cutoff));
+        assert!(!closed_before(&issue(None), cutoff));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+use gitscaffold_parser::diff::{diff_roadmaps, Change};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{Context, ReportFormat};
+use crate::style::{paint, use_color, GREEN, RED, YELLOW};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The original roadmap
+    old: String,
+    /// The updated roadmap
+    new: String,
+    /// Input format of both files; `auto` detects it per file
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// How to print the changes
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+#[derive(Serialize)]
+struct Changeset<'a> {
+    old: &'a str,
+    new: &'a str,
+    changes: &'a [Change],
+}
+
+/// Print the changes between two roadmap files.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let old = context.load(&args.old, args.format)?;
+    let new = context.load(&args.new, args.format)?;
+    let changes = diff_roadmaps(&old, &new);
+
+    match args.report_format {
+        ReportFormat::Human => print_changes(&changes),
+        ReportFormat::Json => {
+            let changeset = Changeset {
+                old: &args.old,
+                new: &args.new,
+                changes: &changes,
+            };
+            println!("{}", context.json.to_string(&changeset)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print `changes` one per line, marked and colored by kind, and count them.
+pub fn print_changes(changes: &[Change]) {
+    let color = use_color();
+    for change in changes {
+        let marker = change.marker();
+        let tint = match marker {
+            '+' => GREEN,
+            '-' => RED,
+            _ => YELLOW,
+        };
+        println!("{}", paint(&format!("{} {}", marker, change), tint, color));
+    }
+    println!("{} change(s)", changes.len());
+}
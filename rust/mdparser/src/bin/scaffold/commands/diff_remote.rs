@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::labels::{add_field_labels, add_priority_labels};
+use mdparser::remote_diff::{diff_remote, Drift};
+use mdparser::template::IssueTemplates;
+
+use super::{Context, ForgeArgs, Inputs, ReportFormat};
+use crate::style::{paint, use_color, GREEN, RED, YELLOW};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// How to print the drift
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+    /// Directory with the `issue_title.j2` template used for sync, to match
+    /// features to issues by their rendered titles
+    #[arg(long, value_name = "DIR")]
+    template_dir: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    file: &'a str,
+    repo: &'a str,
+    drift: &'a [Drift],
+}
+
+/// Print how the repository's milestones and issues differ from the roadmap.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let mut roadmap = context.load_all(&args.input, args.format)?;
+    add_field_labels(&mut roadmap, &context.config.fields);
+    add_priority_labels(&mut roadmap, &context.config.labels);
+    let templates = match &args.template_dir {
+        Some(dir) => IssueTemplates::from_dir(dir)?,
+        None => IssueTemplates::default(),
+    };
+    let texts = templates
+        .with_titles(&context.config.titles)?
+        .with_fields(&context.config.fields)
+        .render(&roadmap)
+        .map_err(|e| ScaffoldError::parse(None, e))?;
+    let forge = args.forge.client(&context.config)?;
+    let milestones = forge
+        .list_milestones()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let issues = forge
+        .list_issues()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let drift = diff_remote(&roadmap, &texts, &milestones, &issues);
+
+    match args.report_format {
+        ReportFormat::Human => {
+            let color = use_color();
+            for item in &drift {
+                let marker = item.marker();
+                let tint = match marker {
+                    '+' => GREEN,
+                    '-' => RED,
+                    _ => YELLOW,
+                };
+                println!("{}", paint(&format!("{} {}", marker, item), tint, color));
+            }
+            println!("{} difference(s) with {}", drift.len(), forge.repo());
+        }
+        ReportFormat::Json => {
+            let report = Report {
+                file: &args.input.to_string(),
+                repo: forge.repo(),
+                drift: &drift,
+            };
+            println!("{}", context.json.to_string(&report)?);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use clap::ValueEnum;
+
+use gitscaffold_parser::export::{to_delimited, Column};
+use gitscaffold_parser::jira::{jira_issues, to_jira_csv, JiraIssue};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{write_output, Context, Inputs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One row per task, with the default columns
+    Csv,
+    /// Like `csv`, separated by tabs
+    Tsv,
+    /// Epics, issues and sub-tasks for Jira's CSV importer
+    JiraCsv,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Export format
+    #[arg(long, value_enum)]
+    format: ExportFormat,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+    /// Write the export to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Print what would be exported instead of the export itself
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Export the roadmap for another tool.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.input_format)?;
+    let export = match args.format {
+        ExportFormat::Csv | ExportFormat::Tsv if args.dry_run => {
+            return Err(ScaffoldError::Usage(
+                "--dry-run is only supported with --format jira-csv".into(),
+            ));
+        }
+        ExportFormat::Csv => to_delimited(&roadmap, &Column::DEFAULT, ','),
+        ExportFormat::Tsv => to_delimited(&roadmap, &Column::DEFAULT, '\t'),
+        ExportFormat::JiraCsv => {
+            let settings = &context.config.jira;
+            let issues = jira_issues(&roadmap, settings);
+            if args.dry_run {
+                print_preview(&issues);
+                return Ok(());
+            }
+            to_jira_csv(&issues, settings)
+        }
+    };
+    write_output(args.output.as_deref(), &export)?;
+    Ok(())
+}
+
+/// List the Jira issues as a tree: epics, then each issue with its
+/// sub-tasks below it.
+fn print_preview(issues: &[JiraIssue]) {
+    for issue in issues {
+        let indent = if issue.parent.is_some() { "    " } else { "" };
+        let mut details = Vec::new();
+        if let Some(epic) = &issue.epic_link {
+            details.push(format!("epic '{}'", epic));
+        }
+        if let Some(due) = &issue.due_date {
+            details.push(format!("due {}", due));
+        }
+        if !issue.labels.is_empty() {
+            details.push(format!("labels {}", issue.labels.join(", ")));
+        }
+        if let Some(assignee) = &issue.assignee {
+            details.push(format!("assignee {}", assignee));
+        }
+        if let Some(status) = issue.status {
+            details.push(status.to_string());
+        }
+        let details = if details.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", details.join("; "))
+        };
+        println!(
+            "{}{} '{}'{}",
+            indent, issue.issue_type, issue.summary, details
+        );
+    }
+    let epics = issues.iter().filter(|i| i.epic_name.is_some()).count();
+    let subtasks = issues.iter().filter(|i| i.parent.is_some()).count();
+    println!(
+        "Dry run: would export {} epic(s), {} issue(s) and {} sub-task(s).",
+        epics,
+        issues.len() - epics - subtasks,
+        subtasks
+    );
+}
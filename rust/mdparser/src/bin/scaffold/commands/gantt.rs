@@ -0,0 +1,49 @@
+use chrono::{Local, NaiveDate};
+
+use gitscaffold_parser::gantt::{render, schedule, Bucket, GanttFormat, GanttOptions};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{write_output, Context, Inputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Chart format
+    #[arg(long, value_enum, default_value_t = GanttFormat::Mermaid)]
+    format: GanttFormat,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+    /// Time unit of the axis and grid
+    #[arg(long, value_enum, default_value_t = Bucket::Week)]
+    bucket: Bucket,
+    /// Start of the first milestone's features, as YYYY-MM-DD; defaults to
+    /// today
+    #[arg(long, value_name = "DATE")]
+    start: Option<NaiveDate>,
+    /// Leave out done features and the milestones they leave empty
+    #[arg(long)]
+    exclude_done: bool,
+    /// Write the chart to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Draw the roadmap's milestones and features on a timeline.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.input_format)?;
+    let options = GanttOptions {
+        start: args.start.unwrap_or_else(|| Local::now().date_naive()),
+        bucket: args.bucket,
+        exclude_done: args.exclude_done,
+    };
+    let schedule = schedule(&roadmap, &options);
+    for title in &schedule.unscheduled {
+        tracing::warn!("skipping '{}': no milestone with a due date", title);
+    }
+    let chart = render(&roadmap, &schedule, &options, args.format);
+    write_output(args.output.as_deref(), &chart)?;
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use clap::{Arg, Command};
+use clap_mangen::Man;
+
+use mdparser::error::ScaffoldError;
+use mdparser::status;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Directory to write `scaffold.1` and a page per subcommand
+    /// (`scaffold-sync.1`, `scaffold-hook-install.1`, ...) to; prints
+    /// `scaffold.1` to stdout when not given
+    #[arg(short, long, value_name = "DIR")]
+    out: Option<PathBuf>,
+}
+
+/// Write the man pages of `command`, the whole CLI.
+pub fn run(args: Args, command: Command) -> Result<(), ScaffoldError> {
+    let mut command = without_help(command);
+    command.build();
+    let Some(dir) = args.out else {
+        let mut page = Vec::new();
+        render(&command, &mut page).map_err(|e| ScaffoldError::io("stdout", e))?;
+        return io::stdout()
+            .write_all(&page)
+            .map_err(|e| ScaffoldError::io("stdout", e));
+    };
+    let path = dir.display().to_string();
+    fs::create_dir_all(&dir).map_err(|e| ScaffoldError::io(&path, e))?;
+    clap_mangen::generate_to(command, &dir).map_err(|e| ScaffoldError::io(&path, e))?;
+    status!("Wrote man pages to {}", dir.display());
+    Ok(())
+}
+
+/// `command` without the `help` subcommands clap adds, which would get
+/// pages of their own.
+fn without_help(command: Command) -> Command {
+    command
+        .disable_help_subcommand(true)
+        .mut_subcommands(without_help)
+}
+
+fn render(command: &Command, out: &mut Vec<u8>) -> io::Result<()> {
+    Man::new(command.clone()).render(out)
+}
+
+/// The whole CLI reference as Markdown, from the same definitions the
+/// parser uses: a section per command with its usage, subcommands,
+/// arguments and options. Global options are only listed once, on
+/// `scaffold` itself.
+pub fn markdown(command: Command) -> String {
+    let mut command = without_help(command);
+    command.build();
+    let mut out = String::new();
+    write_command(&mut out, &command, "scaffold", 1);
+    out
+}
+
+fn write_command(out: &mut String, command: &Command, path: &str, level: usize) {
+    let _ = writeln!(out, "{} `{}`\n", "#".repeat(level.min(6)), path);
+    if let Some(about) = command.get_long_about().or(command.get_about()) {
+        let _ = writeln!(out, "{}\n", about);
+    }
+    let usage = command.clone().render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage:").trim();
+    let _ = writeln!(out, "```text\n{}\n```\n", usage);
+
+    let subcommands: Vec<&Command> = command
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .collect();
+    if !subcommands.is_empty() {
+        out.push_str("**Commands:**\n\n");
+        for sub in &subcommands {
+            let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+            let _ = writeln!(out, "- `{}`: {}", sub.get_name(), about);
+        }
+        out.push('\n');
+    }
+
+    let shown = |arg: &&Arg| {
+        !arg.is_hide_set()
+            && !matches!(arg.get_id().as_str(), "help" | "version")
+            && (level == 1 || !arg.is_global_set())
+    };
+    let positionals: Vec<&Arg> = command.get_positionals().filter(shown).collect();
+    if !positionals.is_empty() {
+        out.push_str("**Arguments:**\n\n");
+        for arg in positionals {
+            write_arg(out, arg);
+        }
+        out.push('\n');
+    }
+    let options: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .filter(shown)
+        .collect();
+    if !options.is_empty() {
+        out.push_str("**Options:**\n\n");
+        for arg in options {
+            write_arg(out, arg);
+        }
+        out.push('\n');
+    }
+
+    for sub in subcommands {
+        let path = format!("{} {}", path, sub.get_name());
+        write_command(out, sub, &path, level + 1);
+    }
+}
+
+fn write_arg(out: &mut String, arg: &Arg) {
+    let values: Vec<String> = arg
+        .get_value_names()
+        .map(|names| names.iter().map(|name| format!("<{}>", name)).collect())
+        .unwrap_or_else(|| vec![format!("<{}>", arg.get_id().as_str().to_uppercase())]);
+    let takes_value = arg.get_num_args().is_some_and(|n| n.takes_values());
+    let mut name = match (arg.get_short(), arg.get_long()) {
+        (Some(short), Some(long)) => format!("-{}, --{}", short, long),
+        (None, Some(long)) => format!("--{}", long),
+        (Some(short), None) => format!("-{}", short),
+        (None, None) => values.join(" "),
+    };
+    if takes_value && !arg.is_positional() {
+        name = format!("{} {}", name, values.join(" "));
+    }
+    let help = arg
+        .get_long_help()
+        .or(arg.get_help())
+        .map(|help| help.to_string().replace('\n', " "))
+        .unwrap_or_default();
+    let _ = write!(out, "- `{}`: {}", name, help);
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| format!("`{}`", value.get_name()))
+        .collect();
+    if !possible.is_empty() && takes_value {
+        let _ = write!(out, " (one of {})", possible.join(", "));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect();
+    if !defaults.is_empty() && takes_value {
+        let _ = write!(out, " [default: `{}`]", defaults.join(", "));
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn documents_every_command_once() {
+        let reference = markdown(crate::Cli::command());
+        assert!(reference.starts_with("# `scaffold`\n"));
+        assert!(reference.contains("\n## `scaffold sync`\n"));
+        assert!(reference.contains("\n### `scaffold hook install`\n"));
+        assert!(reference.contains("- `--forge <FORGE>`: "));
+        assert!(reference.contains("(one of `github`, `gitlab`, `gitea`, `mock`)"));
+        assert_eq!(reference.matches("- `--no-cache`").count(), 1);
+
+        let mut page = Vec::new();
+        render(&crate::Cli::command(), &mut page).unwrap();
+        assert!(String::from_utf8(page).unwrap().contains(".TH scaffold 1"));
+    }
+}
@@ -0,0 +1,28 @@
+use gitscaffold_parser::graph::{render, Diagram};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{write_output, Context, Inputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Diagram language
+    #[arg(long, value_enum, default_value_t = Diagram::Dot)]
+    format: Diagram,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+    /// Write the diagram to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Render the feature dependency graph as a diagram.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.input_format)?;
+    let diagram = render(&roadmap, args.format);
+    write_output(args.output.as_deref(), &diagram)?;
+    Ok(())
+}
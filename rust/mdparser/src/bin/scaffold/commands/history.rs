@@ -0,0 +1,153 @@
+use serde::Serialize;
+
+use gitscaffold_parser::diff::{diff_roadmaps, Change};
+use mdparser::error::ScaffoldError;
+use mdparser::snapshot::{find, Snapshot};
+
+use super::diff::print_changes;
+use super::{Context, ReportFormat};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum HistoryCommand {
+    /// List the snapshots `scaffold snapshot` took, oldest first
+    List(ListArgs),
+    /// Print the changes to the roadmap between two snapshots
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args)]
+struct ListArgs {
+    /// How to print the snapshots
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// The earlier snapshot: `latest`, an id or the start of one, or a
+    /// `YYYY-MM-DD` date for the last snapshot taken before that day
+    from: String,
+    /// The later snapshot, named the same ways
+    #[arg(default_value = "latest")]
+    to: String,
+    /// How to print the changes
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+#[derive(Serialize)]
+struct Listed<'a> {
+    id: &'a str,
+    taken: &'a str,
+    source: &'a str,
+    milestones: usize,
+    features: usize,
+}
+
+#[derive(Serialize)]
+struct Changeset<'a> {
+    from: &'a str,
+    to: &'a str,
+    changes: &'a [Change],
+}
+
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let dir = context.state_dir().join("snapshots");
+    let snapshots = Snapshot::list(&dir)?;
+    match args.command {
+        HistoryCommand::List(args) => {
+            let listed: Vec<Listed> = snapshots
+                .iter()
+                .map(|snapshot| Listed {
+                    id: &snapshot.id,
+                    taken: &snapshot.taken,
+                    source: &snapshot.source,
+                    milestones: snapshot.roadmap.milestones.len(),
+                    features: snapshot.roadmap.features.len(),
+                })
+                .collect();
+            match args.report_format {
+                ReportFormat::Human if listed.is_empty() => {
+                    println!("No snapshots in {}.", dir.display());
+                }
+                ReportFormat::Human => {
+                    for item in &listed {
+                        println!(
+                            "{}  {}  {} milestone(s), {} feature(s)",
+                            item.id, item.source, item.milestones, item.features
+                        );
+                    }
+                }
+                ReportFormat::Json => println!("{}", context.json.to_string(&listed)?),
+            }
+            Ok(())
+        }
+        HistoryCommand::Diff(args) => {
+            let (from, to, changes) = compare(&snapshots, &args.from, &args.to).map_err(|e| {
+                ScaffoldError::Usage(format!("{}; snapshots are kept in {}", e, dir.display()))
+            })?;
+            match args.report_format {
+                ReportFormat::Human => {
+                    println!("Changes from {} to {}:", from.id, to.id);
+                    print_changes(&changes);
+                }
+                ReportFormat::Json => {
+                    let changeset = Changeset {
+                        from: &from.id,
+                        to: &to.id,
+                        changes: &changes,
+                    };
+                    println!("{}", context.json.to_string(&changeset)?);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The snapshots among `snapshots` that `from` and `to` name, see [`find`],
+/// and the changes to the roadmap between them.
+fn compare<'a>(
+    snapshots: &'a [Snapshot],
+    from: &str,
+    to: &str,
+) -> Result<(&'a Snapshot, &'a Snapshot, Vec<Change>), String> {
+    let (from, to) = (find(snapshots, from)?, find(snapshots, to)?);
+    let changes = diff_roadmaps(&from.roadmap, &to.roadmap);
+    Ok((from, to, changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use gitscaffold_parser::parse_markdown;
+
+    #[test]
+    fn reports_status_changes_between_snapshots() {
+        let snapshot = |day, source: &str| {
+            let taken = Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap();
+            Snapshot::new("ROADMAP.md", parse_markdown(source).unwrap(), taken)
+        };
+        let snapshots = [
+            snapshot(1, "# P\n\n## Features\n\n### Login [done]\n"),
+            snapshot(20, "# P\n\n## Features\n\n### Login [in-progress]\n"),
+        ];
+        let (from, to, changes) = compare(&snapshots, "2025-06-02", "latest").unwrap();
+        assert_eq!((from, to), (&snapshots[0], &snapshots[1]));
+        assert_eq!(
+            changes,
+            [Change::FeatureStatusChanged {
+                title: "Login".into(),
+                from: Some("done".into()),
+                to: Some("in-progress".into()),
+            }]
+        );
+    }
+}
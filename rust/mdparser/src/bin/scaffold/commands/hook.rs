@@ -0,0 +1,254 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use glob::{MatchOptions, Pattern};
+
+use gitscaffold_parser::load::parse_content;
+use gitscaffold_parser::validate::{validate, RuleConfig, Severity};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+use mdparser::status;
+
+use super::validate::print_human;
+use super::Context;
+
+/// Marks hooks written by `scaffold hook install`, so they are replaced and
+/// removed without touching anyone else's.
+const MARKER: &str = "# Installed by `scaffold hook install`";
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: HookCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum HookCommand {
+    /// Write a git pre-commit hook that validates staged roadmaps
+    Install(InstallArgs),
+    /// Remove the pre-commit hook written by `install`
+    Uninstall,
+    /// Validate roadmap files one by one, as the hook does; the entry point
+    /// for the pre-commit framework
+    Run(RunArgs),
+}
+
+#[derive(clap::Args)]
+struct Patterns {
+    /// Which staged files are roadmaps, as a glob matched against the path
+    /// or the file name, ignoring case (repeatable)
+    #[arg(
+        long = "pattern",
+        value_name = "GLOB",
+        default_values = ["ROADMAP.md", "roadmap.yml", "roadmap.yaml"]
+    )]
+    patterns: Vec<String>,
+}
+
+#[derive(clap::Args)]
+struct InstallArgs {
+    #[command(flatten)]
+    patterns: Patterns,
+    /// Replace an existing pre-commit hook that scaffold did not write
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(clap::Args)]
+struct RunArgs {
+    /// Roadmap files to validate, each on its own
+    files: Vec<String>,
+    /// Validate the staged version of the staged files matching the
+    /// patterns instead
+    #[arg(long, conflicts_with = "files")]
+    staged: bool,
+    #[command(flatten)]
+    patterns: Patterns,
+}
+
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    match args.command {
+        HookCommand::Install(args) => install(args),
+        HookCommand::Uninstall => uninstall(),
+        HookCommand::Run(args) => run_hook(args, context),
+    }
+}
+
+fn install(args: InstallArgs) -> Result<(), ScaffoldError> {
+    let path = hook_path()?;
+    let display = path.display().to_string();
+    match fs::read_to_string(&path) {
+        Ok(existing) if !existing.contains(MARKER) && !args.force => {
+            return Err(ScaffoldError::Usage(format!(
+                "{} already exists; pass --force to replace it",
+                display
+            )));
+        }
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            return Err(ScaffoldError::io(&display, e));
+        }
+        _ => {}
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| ScaffoldError::io(&display, e))?;
+    }
+    fs::write(&path, script(&args.patterns.patterns))
+        .map_err(|e| ScaffoldError::io(&display, e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| ScaffoldError::io(&display, e))?;
+    }
+    status!("Installed the pre-commit hook in {}.", path.display());
+    Ok(())
+}
+
+fn uninstall() -> Result<(), ScaffoldError> {
+    let path = hook_path()?;
+    let display = path.display().to_string();
+    let existing = match fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            status!("No pre-commit hook is installed.");
+            return Ok(());
+        }
+        Err(e) => return Err(ScaffoldError::io(&display, e)),
+    };
+    if !existing.contains(MARKER) {
+        return Err(ScaffoldError::Usage(format!(
+            "{} was not installed by scaffold; leaving it in place",
+            display
+        )));
+    }
+    fs::remove_file(&path).map_err(|e| ScaffoldError::io(&display, e))?;
+    status!("Removed the pre-commit hook from {}.", path.display());
+    Ok(())
+}
+
+/// Validate each file, printing the diagnostics of those that have any,
+/// and fail when there are errors.
+fn run_hook(args: RunArgs, context: &Context) -> Result<(), ScaffoldError> {
+    let files = if args.staged {
+        let patterns = compile(&args.patterns.patterns)?;
+        let staged = git(&["diff", "--cached", "--name-only", "--diff-filter=ACMR"])?;
+        staged
+            .lines()
+            .filter(|path| matches(&patterns, path))
+            .map(|path| Ok((path.to_string(), git(&["show", &format!(":{}", path)])?)))
+            .collect::<Result<Vec<_>, ScaffoldError>>()?
+    } else {
+        args.files
+            .iter()
+            .map(|path| {
+                let content = fs::read_to_string(path).map_err(|e| ScaffoldError::io(path, e))?;
+                Ok((path.clone(), content))
+            })
+            .collect::<Result<Vec<_>, ScaffoldError>>()?
+    };
+
+    let counts = context
+        .failure
+        .map(&files, "roadmap files", |(path, content)| {
+            let roadmap = parse_content(path, content, InputFormat::Auto, &context.config)
+                .map_err(|e| ScaffoldError::load(path, e))?;
+            let diagnostics = validate(&roadmap, &RuleConfig::default());
+            if !diagnostics.is_empty() {
+                print_human(path, Some(content), &diagnostics);
+            }
+            Ok(diagnostics
+                .iter()
+                .filter(|d| d.severity == Severity::Error)
+                .count())
+        })?;
+    let errors: usize = counts.iter().sum();
+    if errors == 0 {
+        return Ok(());
+    }
+    Err(ScaffoldError::Validation(format!(
+        "{} validation error(s) in {} roadmap file(s)",
+        errors,
+        files.len()
+    )))
+}
+
+/// The hook script, running `scaffold hook run --staged` with `patterns`.
+fn script(patterns: &[String]) -> String {
+    let quoted: Vec<String> = patterns
+        .iter()
+        .map(|pattern| format!("--pattern '{}'", pattern.replace('\'', r"'\''")))
+        .collect();
+    format!(
+        "#!/bin/sh\n{}; remove it with `scaffold hook uninstall`.\n\
+         exec scaffold hook run --staged {}\n",
+        MARKER,
+        quoted.join(" ")
+    )
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Pattern>, ScaffoldError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern)
+                .map_err(|e| ScaffoldError::Usage(format!("invalid pattern '{}': {}", pattern, e)))
+        })
+        .collect()
+}
+
+/// Whether `path` or its file name matches any of `patterns`.
+fn matches(patterns: &[Pattern], path: &str) -> bool {
+    let options = MatchOptions {
+        case_sensitive: false,
+        ..MatchOptions::new()
+    };
+    let name = Path::new(path)
+        .file_name()
+        .map_or(path.into(), |name| name.to_string_lossy());
+    patterns
+        .iter()
+        .any(|p| p.matches_with(path, options) || p.matches_with(&name, options))
+}
+
+/// Where git looks for the pre-commit hook, honouring `core.hooksPath`.
+fn hook_path() -> Result<std::path::PathBuf, ScaffoldError> {
+    let path = git(&["rev-parse", "--git-path", "hooks/pre-commit"])?;
+    Ok(path.trim_end().into())
+}
+
+/// Run git with `args` and return its output.
+fn git(args: &[&str]) -> Result<String, ScaffoldError> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| ScaffoldError::io("git", e))?;
+    if !output.status.success() {
+        return Err(ScaffoldError::Usage(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_paths_and_file_names_ignoring_case() {
+        let patterns = compile(&["ROADMAP.md".into(), "plans/*.yml".into()]).unwrap();
+        assert!(matches(&patterns, "ROADMAP.md"));
+        assert!(matches(&patterns, "docs/roadmap.md"));
+        assert!(matches(&patterns, "plans/q3.yml"));
+        assert!(!matches(&patterns, "README.md"));
+        assert!(!matches(&patterns, "q3.yml"));
+        assert_eq!(
+            script(&["it's.md".into()]).lines().last(),
+            Some(r"exec scaffold hook run --staged --pattern 'it'\''s.md'")
+        );
+    }
+}
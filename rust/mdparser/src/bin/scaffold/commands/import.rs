@@ -0,0 +1,85 @@
+use gitscaffold_parser::validate::{validate, RuleConfig, Severity};
+use gitscaffold_parser::STDIN;
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::import::{import_roadmap, ImportFilter, IssueState};
+use mdparser::status;
+
+use super::{write_output, Context, ForgeArgs, RoadmapFormat};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Only import issues with this label (repeatable; all must match)
+    #[arg(long, value_name = "LABEL")]
+    label: Vec<String>,
+    /// Only import issues in this milestone
+    #[arg(long)]
+    milestone: Option<String>,
+    /// Which issues to import
+    #[arg(long, value_enum, default_value_t = IssueState::Open)]
+    state: IssueState,
+    /// Roadmap name; defaults to the repository name
+    #[arg(long)]
+    name: Option<String>,
+    /// Roadmap format to write
+    #[arg(long, value_enum, default_value_t = RoadmapFormat::Md)]
+    format: RoadmapFormat,
+    /// Write the roadmap to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Generate a roadmap from the repository's existing milestones and issues.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let forge = args.forge.client(&context.config)?;
+    let milestones = forge
+        .list_milestones()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let issues = forge
+        .list_issues()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let filter = ImportFilter {
+        labels: args.label,
+        milestone: args.milestone,
+        state: args.state,
+    };
+    let name = args.name.unwrap_or_else(|| {
+        let repo = forge.repo();
+        repo.rsplit('/').next().unwrap_or(repo).to_string()
+    });
+    let mut roadmap = import_roadmap(&name, &milestones, &issues, &filter);
+    roadmap.metadata.insert("repo".into(), forge.repo().into());
+
+    // Issue titles need not be unique on the forge; point out what would trip
+    // up validation or a later sync.
+    for d in validate(&roadmap, &RuleConfig::default()) {
+        let line = format!(
+            "{}[{}] {} ({})",
+            d.severity,
+            d.code,
+            d.message,
+            d.rule.name()
+        );
+        match d.severity {
+            Severity::Error => tracing::error!("{}", line),
+            _ => tracing::warn!("{}", line),
+        }
+    }
+
+    let content = args.format.serialize(&roadmap)?;
+    write_output(args.output.as_deref(), &content)?;
+    if let Some(path) = args.output.as_deref().filter(|path| *path != STDIN) {
+        status!(
+            "Imported {} milestone(s) and {} issue(s) from {} into {}",
+            roadmap.milestones.len(),
+            roadmap.features.len(),
+            forge.repo(),
+            path
+        );
+    }
+    Ok(())
+}
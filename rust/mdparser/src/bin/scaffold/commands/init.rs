@@ -0,0 +1,235 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use chrono::{Days, Local, Months, NaiveDate};
+use clap::ValueEnum;
+
+use gitscaffold_parser::validate::{validate, RuleConfig, Severity};
+use gitscaffold_parser::{Feature, Milestone, Roadmap, Task, STDIN};
+use mdparser::error::ScaffoldError;
+use mdparser::status;
+
+use super::{write_output, RoadmapFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Cadence {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Quarterly,
+    /// Milestones without due dates
+    None,
+}
+
+impl Cadence {
+    /// The due date of the `n`th milestone (1-based) starting from `start`.
+    fn due(self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        match self {
+            Cadence::Weekly => start.checked_add_days(Days::new(7 * u64::from(n))),
+            Cadence::Biweekly => start.checked_add_days(Days::new(14 * u64::from(n))),
+            Cadence::Monthly => start.checked_add_months(Months::new(n)),
+            Cadence::Quarterly => start.checked_add_months(Months::new(3 * n)),
+            Cadence::None => None,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// File to write, or `-` for stdout; defaults to ROADMAP.md, or
+    /// roadmap.yml with `--format yaml`
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Roadmap format to write
+    #[arg(long, value_enum, default_value_t = RoadmapFormat::Md)]
+    format: RoadmapFormat,
+    /// Project name; asked for when omitted
+    #[arg(long)]
+    name: Option<String>,
+    /// How often milestones are due; asked for when omitted
+    #[arg(long, value_enum)]
+    cadence: Option<Cadence>,
+    /// Number of milestones; asked for when omitted
+    #[arg(long)]
+    milestones: Option<u32>,
+    /// Comma-separated feature titles; asked for when omitted
+    #[arg(long)]
+    features: Option<String>,
+    /// Use defaults instead of asking for missing values
+    #[arg(short, long)]
+    yes: bool,
+    /// Overwrite the output file if it exists
+    #[arg(long)]
+    force: bool,
+}
+
+/// Ask for what the flags did not provide and write a starter roadmap.
+pub fn run(args: Args) -> Result<(), ScaffoldError> {
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| args.format.default_file().into());
+    if output != STDIN && Path::new(&output).exists() && !args.force {
+        return Err(ScaffoldError::Usage(format!(
+            "{} already exists; pass --force to overwrite it",
+            output
+        )));
+    }
+
+    let mut prompter = Prompter {
+        interactive: !args.yes && io::stdin().is_terminal(),
+        input: io::stdin().lock(),
+    };
+    let default_name = std::env::current_dir()
+        .ok()
+        .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "My Project".into());
+    let name = args
+        .name
+        .clone()
+        .unwrap_or_else(|| prompter.ask("Project name", &default_name));
+    let cadence = args.cadence.unwrap_or_else(|| loop {
+        let answer = prompter.ask(
+            "Milestone cadence (weekly, biweekly, monthly, quarterly, none)",
+            "monthly",
+        );
+        match Cadence::from_str(&answer, true) {
+            Ok(cadence) => break cadence,
+            Err(_) => eprintln!("Please answer one of: weekly, biweekly, monthly, quarterly, none"),
+        }
+    });
+    let milestones = args.milestones.unwrap_or_else(|| loop {
+        match prompter.ask("Number of milestones", "3").parse() {
+            Ok(count) => break count,
+            Err(_) => eprintln!("Please answer with a number"),
+        }
+    });
+    let features = args.features.clone().unwrap_or_else(|| {
+        prompter.ask("Feature sections (comma-separated)", "Core, Documentation")
+    });
+    let features: Vec<&str> = features
+        .split(',')
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .collect();
+
+    let roadmap = starter(
+        &name,
+        cadence,
+        milestones,
+        &features,
+        Local::now().date_naive(),
+    );
+    let diagnostics = validate(&roadmap, &RuleConfig::default());
+    if let Some(error) = diagnostics.iter().find(|d| d.severity == Severity::Error) {
+        return Err(ScaffoldError::Validation(format!(
+            "the starter roadmap would not validate: {}",
+            error.message
+        )));
+    }
+    let content = args.format.serialize(&roadmap)?;
+    write_output(Some(&output), &content)?;
+    if output != STDIN {
+        status!("Wrote {}", output);
+    }
+    Ok(())
+}
+
+struct Prompter<R> {
+    interactive: bool,
+    input: R,
+}
+
+impl<R: BufRead> Prompter<R> {
+    /// Ask `question` on stderr, returning `default` for an empty answer or
+    /// when not running interactively.
+    fn ask(&mut self, question: &str, default: &str) -> String {
+        if !self.interactive {
+            return default.to_string();
+        }
+        eprint!("{} [{}]: ", question, default);
+        let _ = io::stderr().flush();
+        let mut answer = String::new();
+        match self.input.read_line(&mut answer) {
+            Ok(_) if !answer.trim().is_empty() => answer.trim().to_string(),
+            _ => default.to_string(),
+        }
+    }
+}
+
+/// A roadmap with `milestones` milestones due every `cadence` from `start`
+/// and one placeholder feature per title, spread over the milestones.
+fn starter(
+    name: &str,
+    cadence: Cadence,
+    milestones: u32,
+    features: &[&str],
+    start: NaiveDate,
+) -> Roadmap {
+    let milestones: Vec<Milestone> = (1..=milestones)
+        .map(|n| Milestone {
+            name: format!("M{}", n),
+            due_date: cadence
+                .due(start, n)
+                .map(|due| due.format("%Y-%m-%d").to_string()),
+            ..Milestone::default()
+        })
+        .collect();
+    let features = features
+        .iter()
+        .enumerate()
+        .map(|(i, title)| {
+            let milestone_id = (!milestones.is_empty()).then(|| i % milestones.len());
+            Feature {
+                title: title.to_string(),
+                description: format!("Describe what {} delivers and why it matters.", title),
+                milestone: milestone_id.map(|id| milestones[id].name.clone()),
+                milestone_id,
+                tasks: ["Define scope", "Implement", "Document"]
+                    .iter()
+                    .map(|task| Task {
+                        title: task.to_string(),
+                        ..Task::default()
+                    })
+                    .collect(),
+                ..Feature::default()
+            }
+        })
+        .collect();
+    Roadmap {
+        name: name.to_string(),
+        description: "What this project is about.".into(),
+        milestones,
+        features,
+        ..Roadmap::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starter_roadmap_round_trips_through_the_parser() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let roadmap = starter("Demo", Cadence::Monthly, 2, &["Core", "Docs", "CLI"], start);
+        let due: Vec<_> = roadmap
+            .milestones
+            .iter()
+            .map(|m| m.due_date.as_deref())
+            .collect();
+        assert_eq!(due, [Some("2025-02-28"), Some("2025-03-31")]);
+        let milestones: Vec<_> = roadmap
+            .features
+            .iter()
+            .map(|f| f.milestone.as_deref())
+            .collect();
+        assert_eq!(milestones, [Some("M1"), Some("M2"), Some("M1")]);
+
+        let markdown = RoadmapFormat::Md.serialize(&roadmap).unwrap();
+        let mut parsed = gitscaffold_parser::parse_markdown(&markdown).unwrap();
+        parsed.strip_spans();
+        assert_eq!(parsed, roadmap);
+        assert!(validate(&parsed, &RuleConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+use serde::Serialize;
+
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::labels::{
+    add_field_labels, add_priority_labels, apply_labels, plan_labels, used_labels, LabelPlan,
+};
+
+use super::{Context, ForgeArgs, Inputs, ReportFormat};
+use crate::style::{paint, use_color, GREEN, YELLOW};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: LabelsCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum LabelsCommand {
+    /// Create the labels the roadmap uses or `[labels]` defines, rename
+    /// aliased labels and report the labels nothing uses
+    Sync(SyncArgs),
+}
+
+#[derive(clap::Args)]
+struct SyncArgs {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Print the changes without making them
+    #[arg(long)]
+    dry_run: bool,
+    /// How to print the changes and unused labels
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    repo: &'a str,
+    #[serde(flatten)]
+    plan: &'a LabelPlan,
+}
+
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    match args.command {
+        LabelsCommand::Sync(args) => sync(args, context).await,
+    }
+}
+
+async fn sync(args: SyncArgs, context: &Context) -> Result<(), ScaffoldError> {
+    let mut roadmap = context.load_all(&args.input, args.format)?;
+    add_field_labels(&mut roadmap, &context.config.fields);
+    add_priority_labels(&mut roadmap, &context.config.labels);
+    let forge = args.forge.client(&context.config)?;
+    let mut wanted = used_labels(&roadmap, &context.config.labels);
+    wanted.extend(context.config.sync.marker_label.clone());
+    let plan = sync_labels(&forge, &wanted, context, args.dry_run).await?;
+    match args.report_format {
+        ReportFormat::Human => {
+            print_plan(&plan);
+            for name in &plan.unused {
+                println!("  unused label '{}'", name);
+            }
+            println!(
+                "{} label change(s) {} {}, {} unused label(s).",
+                plan.changes.len(),
+                if args.dry_run {
+                    "planned for"
+                } else {
+                    "made in"
+                },
+                forge.repo(),
+                plan.unused.len()
+            );
+        }
+        ReportFormat::Json => {
+            let report = Report {
+                repo: forge.repo(),
+                plan: &plan,
+            };
+            println!("{}", context.json.to_string(&report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Plan the label changes that bring `forge` in line with `wanted` and the
+/// config, and make them unless `dry_run`.
+pub async fn sync_labels(
+    forge: &impl Forge,
+    wanted: &[String],
+    context: &Context,
+    dry_run: bool,
+) -> Result<LabelPlan, ScaffoldError> {
+    let remote = forge
+        .list_labels()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let plan = plan_labels(wanted, &context.config.labels, &remote);
+    if !dry_run {
+        apply_labels(forge, &plan)
+            .await
+            .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    }
+    Ok(plan)
+}
+
+/// Print the label changes, as sync prints its plan.
+pub fn print_plan(plan: &LabelPlan) {
+    let color = use_color();
+    for change in &plan.changes {
+        let tint = match change.marker() {
+            '+' => GREEN,
+            _ => YELLOW,
+        };
+        let line = format!("{} {}", change.marker(), change);
+        println!("{}", paint(&line, tint, color));
+    }
+}
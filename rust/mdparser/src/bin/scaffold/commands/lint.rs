@@ -0,0 +1,138 @@
+use serde::Serialize;
+
+use gitscaffold_parser::lint::{apply_fixes, lint, Lint};
+use gitscaffold_parser::sarif::lint_log;
+use gitscaffold_parser::{expand_paths, InputFormat, STDIN};
+use mdparser::error::ScaffoldError;
+
+use super::{read_input, write_output, Context, DiagnosticsFormat, Inputs, Status};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Rewrite each file, applying the fixes that are safe to make
+    #[arg(long)]
+    fix: bool,
+    /// How to print lints
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+    report_format: DiagnosticsFormat,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    file: &'a str,
+    /// Lints whose fixes were applied, with `--fix`.
+    fixed: &'a [Lint],
+    /// Lints still present in the file.
+    lints: &'a [Lint],
+}
+
+/// Lint each Markdown roadmap, fixing it in place with `--fix`; a roadmap
+/// read from stdin is fixed onto stdout, and its lints reported on stderr.
+/// Fails when any lint is left.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let paths = expand_paths(&args.input.paths)
+        .map_err(|e| ScaffoldError::load(&args.input.to_string(), e))?;
+    let mut left = 0;
+    // With `--report-format sarif`, the lints left in each file for one
+    // log covering them all.
+    let mut remaining = Vec::new();
+    let status = Status {
+        stderr: args.fix && paths.iter().any(|path| path == STDIN),
+    };
+    for path in &paths {
+        let source = read_input(path)?;
+        if InputFormat::Auto.resolve(path, &source) != InputFormat::Md {
+            return Err(ScaffoldError::Usage(format!(
+                "{}: only Markdown roadmaps can be linted",
+                path
+            )));
+        }
+        let headings = &context.config.headings;
+        let mut lints = lint(&source, headings);
+        let mut fixed = Vec::new();
+        if args.fix && lints.iter().any(|l| l.fix.is_some()) {
+            let rewritten = apply_fixes(&source, &lints);
+            write_output(Some(path), &rewritten)?;
+            let remaining = lint(&rewritten, headings);
+            fixed = lints
+                .into_iter()
+                .filter(|l| l.fix.is_some())
+                .filter(|l| {
+                    !remaining
+                        .iter()
+                        .any(|r| r.rule == l.rule && r.message == l.message)
+                })
+                .collect();
+            lints = remaining;
+        } else if args.fix && path == STDIN {
+            print!("{}", source);
+        }
+        left += lints.len();
+
+        match args.report_format {
+            DiagnosticsFormat::Human => print_human(status, path, &fixed, &lints),
+            DiagnosticsFormat::Json => {
+                let report = Report {
+                    file: path,
+                    fixed: &fixed,
+                    lints: &lints,
+                };
+                status.print(context.json.to_string(&report)?);
+            }
+            DiagnosticsFormat::Sarif => remaining.push((path.clone(), lints)),
+        }
+    }
+    if args.report_format == DiagnosticsFormat::Sarif {
+        status.print(context.json.to_string(&lint_log(&remaining))?);
+    }
+    if left == 0 {
+        return Ok(());
+    }
+    Err(ScaffoldError::Validation(format!(
+        "{} has {} lint(s)",
+        args.input, left
+    )))
+}
+
+fn print_human(status: Status, file: &str, fixed: &[Lint], lints: &[Lint]) {
+    for l in fixed {
+        status.print(format_args!(
+            "{}:{}:{}: fixed[{}] {} ({})",
+            file,
+            l.line,
+            l.column,
+            l.code,
+            l.message,
+            l.rule.name()
+        ));
+    }
+    for l in lints {
+        status.print(format_args!(
+            "{}:{}:{}: lint[{}] {} ({})",
+            file,
+            l.line,
+            l.column,
+            l.code,
+            l.message,
+            l.rule.name()
+        ));
+    }
+    let fixable = lints.iter().filter(|l| l.fix.is_some()).count();
+    if fixed.is_empty() {
+        status.print(format_args!(
+            "{}: {} lint(s), {} fixable with --fix",
+            file,
+            lints.len(),
+            fixable
+        ));
+    } else {
+        status.print(format_args!(
+            "{}: fixed {} lint(s), {} left",
+            file,
+            fixed.len(),
+            lints.len()
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs;
+
+use gitscaffold_parser::merge::{conflict_markers, merge, Side};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{write_output, Context, RoadmapFormat};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Our version of the roadmap, e.g. the main branch's
+    ours: String,
+    /// Their version of the roadmap, e.g. a feature branch's
+    theirs: String,
+    /// The version both were derived from; without it, every difference is
+    /// a conflict
+    #[arg(long, value_name = "FILE")]
+    base: Option<String>,
+    /// Input format of the files; `auto` detects it per file
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+    /// Format of the merged roadmap
+    #[arg(long, value_enum, default_value_t = RoadmapFormat::Md)]
+    format: RoadmapFormat,
+    /// Resolve conflicts in favour of this side instead of writing
+    /// conflict markers
+    #[arg(long, value_enum)]
+    resolve: Option<Side>,
+    /// Write the merged roadmap to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Write the conflicts to this file as JSON
+    #[arg(long, value_name = "FILE")]
+    report: Option<String>,
+}
+
+/// Merge two versions of a roadmap, writing conflict markers (or a
+/// resolution) where both changed the same thing.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let ours = context.load(&args.ours, args.input_format)?;
+    let theirs = context.load(&args.theirs, args.input_format)?;
+    let base = match &args.base {
+        Some(path) => Some(context.load(path, args.input_format)?),
+        None => None,
+    };
+    let prefer = args.resolve.unwrap_or(Side::Ours);
+    let merged = merge(base.as_ref(), &ours, &theirs, prefer);
+
+    for conflict in &merged.conflicts {
+        match args.resolve {
+            Some(side) => tracing::warn!("Resolved in favour of {}: {}", side, conflict),
+            None => tracing::warn!("CONFLICT {}", conflict),
+        }
+    }
+    if let Some(path) = &args.report {
+        let json = context.json.to_string(&merged.conflicts)?;
+        fs::write(path, json + "\n").map_err(|e| ScaffoldError::io(path, e))?;
+    }
+
+    let unresolved = args.resolve.is_none() && !merged.conflicts.is_empty();
+    let output = if unresolved {
+        if args.format != RoadmapFormat::Md {
+            return Err(ScaffoldError::Validation(format!(
+                "{} conflict(s); YAML cannot hold conflict markers, pass --resolve ours or --resolve theirs",
+                merged.conflicts.len()
+            )));
+        }
+        let by_theirs = merge(base.as_ref(), &ours, &theirs, Side::Theirs);
+        conflict_markers(
+            &args.format.serialize(&merged.roadmap)?,
+            &args.format.serialize(&by_theirs.roadmap)?,
+            (&args.ours, &args.theirs),
+        )
+    } else {
+        args.format.serialize(&merged.roadmap)?
+    };
+    write_output(args.output.as_deref(), &output)?;
+    if unresolved {
+        return Err(ScaffoldError::Validation(format!(
+            "merge left {} conflict(s) to resolve",
+            merged.conflicts.len()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use gitscaffold_parser::migrate::{migrate, FORMAT_VERSION};
+use gitscaffold_parser::{InputFormat, STDIN};
+use mdparser::error::ScaffoldError;
+use mdparser::status;
+
+use super::{read_input, write_output, Inputs, Status};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// Only report the files that need migrating, failing if any does
+    #[arg(long)]
+    check: bool,
+}
+
+/// Upgrade each roadmap file in place to the current format version. A
+/// roadmap read from stdin is written, upgraded or not, to stdout.
+pub fn run(args: Args) -> Result<(), ScaffoldError> {
+    let mut outdated = 0;
+    for path in args.input.expand()? {
+        let content = read_input(&path)?;
+        let migration =
+            migrate(&path, &content, args.format).map_err(|e| ScaffoldError::load(&path, e))?;
+        let status = Status::for_output(&path);
+        if migration.text == content {
+            status.print(format_args!(
+                "{}: already at version {}",
+                path, FORMAT_VERSION
+            ));
+            // A pipeline still gets the roadmap.
+            if path == STDIN && !args.check {
+                print!("{}", content);
+            }
+            continue;
+        }
+        outdated += 1;
+        if args.check {
+            status.print(format_args!(
+                "{}: needs migrating from version {} to {}",
+                path, migration.from, FORMAT_VERSION
+            ));
+            continue;
+        }
+        write_output(Some(&path), &migration.text)?;
+        status!(
+            "{}: migrated from version {} to {}",
+            path,
+            migration.from,
+            FORMAT_VERSION
+        );
+        for step in &migration.steps {
+            status!("  - {}", step);
+        }
+    }
+    if args.check && outdated > 0 {
+        return Err(ScaffoldError::Validation(format!(
+            "{} file(s) need `scaffold migrate`",
+            outdated
+        )));
+    }
+    Ok(())
+}
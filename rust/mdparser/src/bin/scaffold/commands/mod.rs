@@ -0,0 +1,597 @@
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+use chrono::Utc;
+use clap::ValueEnum;
+
+use gitscaffold_parser::aggregate::aggregate;
+use gitscaffold_parser::load::read_source;
+use gitscaffold_parser::render::{render_markdown, RenderOptions};
+use gitscaffold_parser::{
+    expand_paths, load_roadmap_with, Cache, Config, ForgeKind, InputFormat, Roadmap, STDIN,
+};
+use mdparser::actions::{self, Annotation};
+use mdparser::credentials;
+use mdparser::error::{FailurePolicy, ScaffoldError};
+use mdparser::fixtures::{self, Fixtures, Recorded};
+use mdparser::forge::AnyForge;
+use mdparser::gitea::{self, Gitea};
+use mdparser::github::{self, GitHub, RetryPolicy, Wait};
+use mdparser::github_app::GitHubApp;
+use mdparser::gitlab::{self, GitLab};
+use mdparser::output::JsonStyle;
+use mdparser::runs::Run;
+use mdparser::state::State;
+use mdparser::status;
+use mdparser::sync::{Execution, SyncReport};
+
+pub mod apply;
+pub mod auth;
+pub mod burndown;
+pub mod capacity;
+pub mod check_due;
+pub mod completions;
+pub mod dedupe;
+pub mod delete_closed;
+pub mod diff;
+pub mod diff_remote;
+pub mod export;
+pub mod gantt;
+pub mod gen_man;
+pub mod graph;
+pub mod history;
+pub mod hook;
+pub mod import;
+pub mod init;
+pub mod labels;
+pub mod lint;
+pub mod merge;
+pub mod migrate;
+pub mod pull;
+pub mod release_notes;
+pub mod render_md;
+pub mod report;
+pub mod rollback;
+pub mod rollup;
+pub mod scan_commits;
+pub mod serve;
+pub mod snapshot;
+pub mod stats;
+pub mod sync;
+pub mod validate;
+
+/// What commands need to read roadmaps: the project config, unless
+/// disabled the parse cache, and whether parse warnings are errors; plus
+/// where the sync state is kept, how to print JSON, whether to carry on
+/// after errors, whether to report to GitHub Actions and the global
+/// options to run other commands with.
+pub struct Context {
+    pub config: Config,
+    pub cache: Option<Cache>,
+    pub strict: bool,
+    pub state: PathBuf,
+    pub json: JsonStyle,
+    pub failure: FailurePolicy,
+    pub github_output: bool,
+    pub globals: Vec<OsString>,
+}
+
+impl Context {
+    /// Load the roadmap at `path`, failing on parse warnings when strict.
+    pub fn load(&self, path: &str, format: InputFormat) -> Result<Roadmap, ScaffoldError> {
+        tracing::info!(path, "loading roadmap");
+        let loaded = match &self.cache {
+            Some(cache) => cache.load(path, format, &self.config),
+            None => load_roadmap_with(path, format, &self.config),
+        };
+        loaded
+            .and_then(|roadmap| {
+                if self.strict {
+                    roadmap.deny_warnings()
+                } else {
+                    Ok(roadmap)
+                }
+            })
+            .map_err(|e| ScaffoldError::load(path, e))
+    }
+
+    /// Load every file named by `inputs`, merging them into one roadmap
+    /// when there are several.
+    pub fn load_all(&self, inputs: &Inputs, format: InputFormat) -> Result<Roadmap, ScaffoldError> {
+        let paths = inputs.expand()?;
+        if let [path] = paths.as_slice() {
+            return self.load(path, format);
+        }
+        let parts = self.failure.map(paths, "roadmap files", |path| {
+            let roadmap = self.load(&path, format)?;
+            Ok((path, roadmap))
+        })?;
+        Ok(aggregate(parts))
+    }
+
+    /// With `--github-output`, append `outputs` to the step outputs of the
+    /// GitHub Actions step running the command.
+    pub fn outputs(&self, outputs: &[(&str, String)]) -> Result<(), ScaffoldError> {
+        if !self.github_output {
+            return Ok(());
+        }
+        let Some(path) = env::var_os("GITHUB_OUTPUT") else {
+            tracing::warn!("--github-output: GITHUB_OUTPUT is not set outside GitHub Actions");
+            return Ok(());
+        };
+        let path = PathBuf::from(path);
+        actions::write_outputs(&path, outputs)
+            .map_err(|e| ScaffoldError::io(&path.display().to_string(), e))
+    }
+
+    /// With `--github-output`, print `annotation` for the runner. It goes
+    /// to stderr, which the runner reads workflow commands from as well, to
+    /// keep reports on stdout parseable.
+    pub fn annotate(&self, annotation: Annotation) {
+        if self.github_output {
+            eprintln!("{}", annotation);
+        }
+    }
+
+    /// The directory of the state file, which also holds the journals and
+    /// runs of syncs and the roadmap snapshots.
+    pub fn state_dir(&self) -> &Path {
+        self.state.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Record the milestones and issues of a sync run in the state file,
+    /// and what it created in the runs `scaffold rollback` undoes.
+    pub fn record(&self, repo: &str, report: &SyncReport) -> Result<(), ScaffoldError> {
+        let mut state = State::load(&self.state)?;
+        state.repo_mut(repo).record(report);
+        state.save(&self.state)?;
+        if let Some(mut run) = Run::new(repo, report, Utc::now()) {
+            run.save(&self.state_dir().join("runs"))?;
+            status!(
+                "Recorded the run as {}; `scaffold rollback {}` undoes what it created.",
+                run.id,
+                run.id
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The roadmap a command reads, possibly split across several files.
+#[derive(clap::Args)]
+pub struct Inputs {
+    /// Roadmap files or glob patterns such as `roadmap/*.md`, or `-` for
+    /// stdin; several files are merged into one roadmap
+    #[arg(value_name = "INPUT", required = true)]
+    paths: Vec<String>,
+}
+
+impl Inputs {
+    /// The files named, with glob patterns expanded.
+    pub fn expand(&self) -> Result<Vec<String>, ScaffoldError> {
+        if self.paths.iter().filter(|path| *path == STDIN).count() > 1 {
+            return Err(ScaffoldError::Usage(
+                "`-` (stdin) can only be given once".into(),
+            ));
+        }
+        expand_paths(&self.paths).map_err(|e| ScaffoldError::load(&self.to_string(), e))
+    }
+}
+
+impl fmt::Display for Inputs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.paths.join(", "))
+    }
+}
+
+/// The text of the file at `path`; `-` reads stdin.
+pub fn read_input(path: &str) -> Result<String, ScaffoldError> {
+    read_source(path)
+        .map(|source| source.to_string())
+        .map_err(|e| ScaffoldError::io(path, e))
+}
+
+/// Write a command's document to `path`, or to stdout when no path or `-`
+/// is given.
+pub fn write_output(path: Option<&str>, text: &str) -> Result<(), ScaffoldError> {
+    match path {
+        Some(path) if path != STDIN => {
+            fs::write(path, text).map_err(|e| ScaffoldError::io(path, e))
+        }
+        _ => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Where a command prints its report, such as the changes it found:
+/// stdout, unless the document it writes goes there, so that the document
+/// can be piped on. What it then did is logged with `status!`.
+#[derive(Debug, Clone, Copy)]
+pub struct Status {
+    pub stderr: bool,
+}
+
+impl Status {
+    /// Report on stderr when `target`, the file a command writes, is `-`.
+    pub fn for_output(target: &str) -> Self {
+        Status {
+            stderr: target == STDIN,
+        }
+    }
+
+    pub fn print(self, line: impl fmt::Display) {
+        if self.stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Output format shared by commands that print reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Human,
+    Json,
+}
+
+/// Output format of the commands that report diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiagnosticsFormat {
+    Human,
+    Json,
+    /// A SARIF 2.1.0 log, for GitHub code scanning and editors
+    Sarif,
+}
+
+/// Concurrency flag shared by commands that create issues.
+#[derive(clap::Args)]
+pub struct JobsArgs {
+    /// How many issues to create at once; milestones are always created
+    /// one at a time, before any issue
+    #[arg(short, long, value_name = "N", default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: u16,
+    /// Create up to N issues per request on GitHub, over GraphQL; other
+    /// forges create them one by one
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..=50))]
+    pub batch: u16,
+}
+
+impl JobsArgs {
+    /// How to run the operations of a sync: `jobs` at a time, following
+    /// the failure policy, and stopping on Ctrl-C.
+    pub fn execution(&self, context: &Context) -> Execution<'static> {
+        Execution {
+            jobs: self.jobs.into(),
+            batch: self.batch.into(),
+            keep_going: context.failure.keep_going(),
+            interrupted: Some(interrupt_flag()),
+            journal: None,
+        }
+    }
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// A flag set by the first Ctrl-C, so that a sync starts no new operations
+/// but still reports and records those done. A second Ctrl-C exits at once.
+fn interrupt_flag() -> &'static AtomicBool {
+    static LISTEN: Once = Once::new();
+    LISTEN.call_once(|| {
+        tokio::spawn(async {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            tracing::warn!(
+                "Interrupted; waiting for the requests under way (Ctrl-C again to abort)."
+            );
+            if tokio::signal::ctrl_c().await.is_ok() {
+                process::exit(130);
+            }
+        });
+    });
+    &INTERRUPTED
+}
+
+/// Whether the run was interrupted by Ctrl-C.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Format of a roadmap written by a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RoadmapFormat {
+    Md,
+    Yaml,
+}
+
+impl RoadmapFormat {
+    /// The conventional file name for a roadmap in this format.
+    pub fn default_file(self) -> &'static str {
+        match self {
+            RoadmapFormat::Md => "ROADMAP.md",
+            RoadmapFormat::Yaml => "roadmap.yml",
+        }
+    }
+
+    pub fn serialize(self, roadmap: &Roadmap) -> Result<String, ScaffoldError> {
+        match self {
+            RoadmapFormat::Md => Ok(render_markdown(roadmap, RenderOptions::default())),
+            RoadmapFormat::Yaml => Ok(serde_yaml::to_string(roadmap)?),
+        }
+    }
+}
+
+/// Connection flags shared by commands that talk to a forge.
+///
+/// Unset flags fall back to the `[forge]` config, then to the forge's CI
+/// variables: the repository to `GITHUB_REPOSITORY` (`CI_PROJECT_PATH` on
+/// GitLab) and the API URL to `api_url` in the config, then
+/// `GITHUB_API_URL` (`CI_API_V4_URL` on GitLab, `GITEA_API_URL` on Gitea).
+/// The token is resolved by [`credentials::resolve`].
+#[derive(clap::Args, Clone)]
+pub struct ForgeArgs {
+    /// Forge to talk to; defaults to `kind` in the `[forge]` config, then
+    /// GitHub
+    #[arg(long, value_enum)]
+    forge: Option<ForgeArg>,
+    /// Target repository as owner/name, or the GitLab project path
+    #[arg(long)]
+    repo: Option<String>,
+    /// Token with issues and milestones access
+    #[arg(long)]
+    token: Option<String>,
+    /// Base URL of the forge's REST API
+    #[arg(long)]
+    api_url: Option<String>,
+    /// Retries for rate-limited or transiently failing requests
+    #[arg(long, value_name = "N", default_value_t = RetryPolicy::default().max_retries)]
+    max_retries: u32,
+    /// Authenticate with a token or as a GitHub App
+    #[arg(long, value_enum, default_value_t = AuthMethod::Token)]
+    auth: AuthMethod,
+    /// The GitHub App's id, for `--auth app`
+    #[arg(long, env = "GITHUB_APP_ID", value_name = "ID")]
+    app_id: Option<String>,
+    /// PEM file with the GitHub App's private key, for `--auth app`
+    #[arg(long, env = "GITHUB_APP_PRIVATE_KEY_PATH", value_name = "FILE")]
+    app_private_key: Option<PathBuf>,
+    /// The app's installation to act as; looked up on the repository when
+    /// not given
+    #[arg(long, env = "GITHUB_APP_INSTALLATION_ID", value_name = "ID")]
+    installation_id: Option<u64>,
+    /// Record the forge's responses into this directory; with
+    /// `--forge mock`, replay them instead of talking to the forge
+    #[arg(long, value_name = "DIR")]
+    fixtures: Option<PathBuf>,
+}
+
+/// The forges `--forge` offers: the real ones, and `mock` for replaying
+/// recorded fixtures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ForgeArg {
+    #[value(name = "github")]
+    GitHub,
+    #[value(name = "gitlab")]
+    GitLab,
+    /// Gitea or Forgejo
+    #[value(name = "gitea", alias = "forgejo")]
+    Gitea,
+    /// Replay the responses recorded in `--fixtures`, without network or
+    /// token
+    Mock,
+}
+
+/// How `ForgeArgs` clients authenticate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuthMethod {
+    /// A personal or CI token, found as `scaffold auth status` shows
+    Token,
+    /// Installation tokens of a GitHub App, refreshed as they expire
+    App,
+}
+
+impl ForgeArgs {
+    /// A client for the selected forge that reports on stderr when it
+    /// waits out a rate limit or retries a request.
+    pub fn client(&self, config: &Config) -> Result<AnyForge, ScaffoldError> {
+        if self.forge == Some(ForgeArg::Mock) {
+            return self.replay();
+        }
+        let kind = self.kind(config);
+        let repo = self.repo(config).ok_or_else(|| {
+            ScaffoldError::Usage(format!("pass --repo or set {}", repo_var(kind)))
+        })?;
+        let api_url = api_url(kind, self.api_url.as_deref(), config);
+        let retry = RetryPolicy {
+            max_retries: self.max_retries,
+            ..RetryPolicy::default()
+        };
+        let fixtures = match &self.fixtures {
+            Some(dir) => {
+                let recorded = Recorded {
+                    forge: kind,
+                    repo: repo.clone(),
+                    api_url: api_url.clone(),
+                };
+                let path = dir.join(fixtures::FILE).display().to_string();
+                Some(Fixtures::record(dir, recorded).map_err(|e| ScaffoldError::io(&path, e))?)
+            }
+            None => None,
+        };
+        if self.auth == AuthMethod::App {
+            if fixtures.is_some() {
+                // The recording would hold the installation tokens.
+                return Err(ScaffoldError::Usage(
+                    "--fixtures cannot record with --auth app".into(),
+                ));
+            }
+            if kind != ForgeKind::GitHub {
+                return Err(ScaffoldError::Usage(
+                    "--auth app only works with GitHub".into(),
+                ));
+            }
+            let app = self.app()?;
+            return GitHub::for_app(&api_url, app, &repo)
+                .map(|client| AnyForge::GitHub(client.with_retry(retry).on_wait(print_wait)))
+                .map_err(|e| ScaffoldError::github(&repo, e));
+        }
+        let host = credentials::host(&api_url);
+        let token = credentials::resolve(kind, &host, self.token.as_deref())
+            .ok_or_else(|| {
+                ScaffoldError::Usage(format!(
+                    "no token for {}: pass --token, set {}, or run `scaffold auth login`",
+                    host,
+                    credentials::env_vars(kind)[0]
+                ))
+            })?
+            .token;
+        let forge = match kind {
+            ForgeKind::GitHub => GitHub::new(&api_url, &token, &repo)
+                .map(|client| AnyForge::GitHub(client.with_retry(retry).on_wait(print_wait))),
+            ForgeKind::GitLab => GitLab::new(&api_url, &token, &repo)
+                .map(|client| AnyForge::GitLab(client.with_retry(retry).on_wait(print_wait))),
+            ForgeKind::Gitea => Gitea::new(&api_url, &token, &repo)
+                .map(|client| AnyForge::Gitea(client.with_retry(retry).on_wait(print_wait))),
+        };
+        let forge = forge.map_err(|e| ScaffoldError::github(&repo, e))?;
+        Ok(match fixtures {
+            Some(fixtures) => forge.with_fixtures(fixtures),
+            None => forge,
+        })
+    }
+
+    /// A client like [`ForgeArgs::client`] for `repo` instead of `--repo`.
+    pub fn client_for(&self, config: &Config, repo: &str) -> Result<AnyForge, ScaffoldError> {
+        let args = ForgeArgs {
+            repo: Some(repo.to_string()),
+            ..self.clone()
+        };
+        args.client(config)
+    }
+
+    /// The target repository: `--repo`, or the forge's CI variable.
+    pub fn repo(&self, config: &Config) -> Option<String> {
+        or_env(&self.repo, repo_var(self.kind(config)))
+    }
+
+    fn kind(&self, config: &Config) -> ForgeKind {
+        match self.forge {
+            Some(ForgeArg::GitHub) => ForgeKind::GitHub,
+            Some(ForgeArg::GitLab) => ForgeKind::GitLab,
+            Some(ForgeArg::Gitea) => ForgeKind::Gitea,
+            Some(ForgeArg::Mock) | None => config.forge.kind.unwrap_or_default(),
+        }
+    }
+
+    /// A client answering from the fixtures in `--fixtures`, for the forge
+    /// and repository they were recorded from.
+    fn replay(&self) -> Result<AnyForge, ScaffoldError> {
+        let dir = self.fixtures.as_ref().ok_or_else(|| {
+            ScaffoldError::Usage("--forge mock replays the recording in --fixtures DIR".into())
+        })?;
+        let path = dir.join(fixtures::FILE).display().to_string();
+        let fixtures = Fixtures::replay(dir).map_err(|e| ScaffoldError::io(&path, e))?;
+        let Recorded {
+            forge,
+            repo,
+            api_url,
+        } = fixtures.recorded();
+        // The token is never sent, since no request leaves the process.
+        let client = match forge {
+            ForgeKind::GitHub => GitHub::new(&api_url, "", &repo).map(AnyForge::GitHub),
+            ForgeKind::GitLab => GitLab::new(&api_url, "", &repo).map(AnyForge::GitLab),
+            ForgeKind::Gitea => Gitea::new(&api_url, "", &repo).map(AnyForge::Gitea),
+        };
+        client
+            .map(|client| client.with_fixtures(fixtures))
+            .map_err(|e| ScaffoldError::github(&repo, e))
+    }
+
+    /// The GitHub App given by `--app-id` and `--app-private-key`.
+    fn app(&self) -> Result<GitHubApp, ScaffoldError> {
+        let (Some(app_id), Some(key)) = (&self.app_id, &self.app_private_key) else {
+            return Err(ScaffoldError::Usage(
+                "--auth app needs --app-id and --app-private-key".into(),
+            ));
+        };
+        let path = key.display().to_string();
+        let pem = fs::read(key).map_err(|e| ScaffoldError::io(&path, e))?;
+        GitHubApp::new(app_id, &pem, self.installation_id)
+            .map_err(|e| ScaffoldError::parse(Some(&path), e))
+    }
+}
+
+/// The CI variable naming the repository on a `kind` forge.
+fn repo_var(kind: ForgeKind) -> &'static str {
+    match kind {
+        ForgeKind::GitHub | ForgeKind::Gitea => "GITHUB_REPOSITORY",
+        ForgeKind::GitLab => "CI_PROJECT_PATH",
+    }
+}
+
+/// The API URL of a `kind` forge: `flag` if given, then `api_url` in the
+/// `[forge]` config, the forge's CI variable and its public instance.
+pub fn api_url(kind: ForgeKind, flag: Option<&str>, config: &Config) -> String {
+    let (var, default) = match kind {
+        ForgeKind::GitHub => ("GITHUB_API_URL", github::DEFAULT_API_URL),
+        ForgeKind::GitLab => ("CI_API_V4_URL", gitlab::DEFAULT_API_URL),
+        ForgeKind::Gitea => ("GITEA_API_URL", gitea::DEFAULT_API_URL),
+    };
+    flag.map(str::to_string)
+        .or_else(|| config.forge.api_url.clone())
+        .or_else(|| or_env(&None, var))
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// `flag` if given, otherwise the non-empty environment variable `var`.
+fn or_env(flag: &Option<String>, var: &str) -> Option<String> {
+    flag.clone()
+        .or_else(|| std::env::var(var).ok().filter(|value| !value.is_empty()))
+}
+
+fn print_wait(wait: &Wait) {
+    let delay = wait.delay.as_secs_f64();
+    if wait.attempt == 0 {
+        tracing::warn!(delay, "{}; waiting {}s", wait.reason, wait.delay.as_secs());
+    } else {
+        tracing::warn!(
+            delay,
+            attempt = wait.attempt,
+            "{}; retry {} in {:.1}s",
+            wait.reason,
+            wait.attempt,
+            delay
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_url_prefers_the_flag_then_the_config_then_the_ci_variable() {
+        let mut config = Config::default();
+        let url = |config: &Config, flag| api_url(ForgeKind::Gitea, flag, config);
+        assert_eq!(url(&config, None), gitea::DEFAULT_API_URL);
+        env::set_var("GITEA_API_URL", "https://ci.example.com/api/v1");
+        assert_eq!(url(&config, None), "https://ci.example.com/api/v1");
+        config.forge.api_url = Some("https://config.example.com/api/v1".into());
+        assert_eq!(url(&config, None), "https://config.example.com/api/v1");
+        assert_eq!(
+            url(&config, Some("https://flag.example.com/api/v1")),
+            "https://flag.example.com/api/v1"
+        );
+        env::remove_var("GITEA_API_URL");
+    }
+}
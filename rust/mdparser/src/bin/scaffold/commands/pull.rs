@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use gitscaffold_parser::{parse_markdown_with, InputFormat, STDIN};
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::pull::{apply, plan};
+use mdparser::state::State;
+use mdparser::status;
+use mdparser::template::IssueTemplates;
+
+use super::{read_input, write_output, Context, ForgeArgs, Status};
+use crate::style::{paint, use_color, GREEN};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Markdown roadmap to update; `-` reads it from stdin and writes the
+    /// update to stdout
+    input: String,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Print the changes pull would make without writing anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Write the updated roadmap to this file instead of in place
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Directory with the `issue_title.j2` template used for sync, to match
+    /// features to issues by their rendered titles
+    #[arg(long, value_name = "DIR")]
+    template_dir: Option<PathBuf>,
+}
+
+/// Update the roadmap from its issues: link them, update statuses and
+/// check off completed tasks.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let path = &args.input;
+    let source = read_input(path)?;
+    if InputFormat::Auto.resolve(path, &source) != InputFormat::Md {
+        return Err(ScaffoldError::Usage(format!(
+            "{}: only Markdown roadmaps can be pulled into",
+            path
+        )));
+    }
+    let roadmap = parse_markdown_with(&source, &context.config.headings)
+        .map_err(|e| ScaffoldError::load(path, e))?;
+    let templates = match &args.template_dir {
+        Some(dir) => IssueTemplates::from_dir(dir)?,
+        None => IssueTemplates::default(),
+    };
+    let texts = templates
+        .with_titles(&context.config.titles)?
+        .with_fields(&context.config.fields)
+        .render(&roadmap)
+        .map_err(|e| ScaffoldError::parse(None, e))?;
+
+    let forge = args.forge.client(&context.config)?;
+    let issues = forge
+        .list_issues()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let state = State::load(&context.state)?;
+    let pull = plan(&roadmap, &texts, &issues, state.repo(forge.repo()));
+
+    let target = args.output.as_deref().unwrap_or(path);
+    let status = Status {
+        stderr: target == STDIN && !args.dry_run,
+    };
+    let color = use_color();
+    for change in &pull.changes {
+        status.print(paint(&format!("~ {}", change), GREEN, color));
+    }
+    for title in &pull.unmatched {
+        tracing::warn!("No issue found for '{}'", title);
+    }
+    if args.dry_run || pull.changes.is_empty() {
+        status.print(format_args!(
+            "{} change(s) to pull from {}.",
+            pull.changes.len(),
+            forge.repo()
+        ));
+        if status.stderr {
+            print!("{}", source);
+        }
+        return Ok(());
+    }
+    let updated = apply(&source, &roadmap, &pull.changes);
+    write_output(Some(target), &updated)?;
+    status!(
+        "Pulled {} change(s) from {} into {}.",
+        pull.changes.len(),
+        forge.repo(),
+        target
+    );
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, Local, Utc};
+
+use gitscaffold_parser::load::parse_content;
+use gitscaffold_parser::release::{shipped_features, shipped_since, ReleaseNotes};
+use gitscaffold_parser::{InputFormat, IssueLink, Roadmap, STDIN};
+use mdparser::changelog::{insert, render, Release};
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::revision::{read_at, resolve};
+use mdparser::state::State;
+use mdparser::status;
+
+use super::{read_input, write_output, Context, ForgeArgs, ReportFormat};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The roadmap, in a git checkout unless `--issues` is given
+    input: String,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// List what was finished after this tag or other revision, or after
+    /// the start of this `YYYY-MM-DD` date; everything finished when left
+    /// out
+    #[arg(long, value_name = "REV|DATE")]
+    since: Option<String>,
+    /// List the features whose issues were closed on the forge since then,
+    /// instead of comparing the roadmap with its earlier version
+    #[arg(long)]
+    issues: bool,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Name of the release the section is headed with
+    #[arg(long, value_name = "NAME", default_value = "Unreleased")]
+    version: String,
+    /// Release date for the heading; defaults to today, or none for an
+    /// unreleased section
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    date: Option<String>,
+    /// minijinja template to render the section with instead of the
+    /// built-in one
+    #[arg(long, value_name = "FILE")]
+    template: Option<String>,
+    /// Add the section to this changelog, above its newest release
+    #[arg(long, value_name = "FILE", conflicts_with = "output")]
+    changelog: Option<String>,
+    /// Write the section to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Print the section, or the notes as JSON
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+}
+
+/// Render a changelog section from the features and tasks finished since
+/// a tag or date.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let path = &args.input;
+    let roadmap = context.load(path, args.format)?;
+    let notes = match args.issues {
+        true => closed_since(&args, context, &roadmap).await?,
+        false => finished_since(&args, context, &roadmap)?,
+    };
+    if args.report_format == ReportFormat::Json {
+        return write_output(
+            args.output.as_deref(),
+            &format!("{}\n", context.json.to_string(&notes)?),
+        );
+    }
+
+    let template = args.template.as_deref().map(read_input).transpose()?;
+    let date = match &args.date {
+        Some(date) => Some(date.clone()),
+        None if args.version == "Unreleased" => None,
+        None => Some(Local::now().format("%Y-%m-%d").to_string()),
+    };
+    let release = Release {
+        version: &args.version,
+        date,
+        since: args.since.as_deref(),
+    };
+    let section = render(&notes, &release, template.as_deref()).map_err(|e| {
+        ScaffoldError::parse(
+            Some(args.template.as_deref().unwrap_or("release_notes.j2")),
+            e,
+        )
+    })?;
+    context.outputs(&[
+        ("item_count", notes.len().to_string()),
+        ("release_notes", section.clone()),
+    ])?;
+
+    let Some(changelog) = &args.changelog else {
+        return write_output(args.output.as_deref(), &section);
+    };
+    if notes.is_empty() {
+        status!(
+            "Nothing finished since {}; {} is unchanged.",
+            args.since.as_deref().unwrap_or("the start"),
+            changelog
+        );
+        return Ok(());
+    }
+    let existing = match fs::read_to_string(changelog) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(ScaffoldError::io(changelog, e)),
+    };
+    write_output(Some(changelog), &insert(&existing, &section))?;
+    status!(
+        "Added {} to {} ({} item(s)).",
+        args.version,
+        changelog,
+        notes.len()
+    );
+    Ok(())
+}
+
+/// What the roadmap finished since its version at `--since` in git.
+fn finished_since(
+    args: &Args,
+    context: &Context,
+    roadmap: &Roadmap,
+) -> Result<ReleaseNotes, ScaffoldError> {
+    let path = &args.input;
+    let Some(since) = &args.since else {
+        return Ok(shipped_since(roadmap, None));
+    };
+    if path == STDIN {
+        return Err(ScaffoldError::Usage(
+            "--since compares the roadmap file with its history; pass a file, not stdin".into(),
+        ));
+    }
+    let git = |e: git2::Error| ScaffoldError::parse(Some(path), e);
+    let absolute = Path::new(path)
+        .canonicalize()
+        .map_err(|e| ScaffoldError::io(path, e))?;
+    let resolved = resolve(absolute.parent().unwrap_or(&absolute), since).map_err(git)?;
+    let previous = match &resolved.commit {
+        Some(commit) => read_at(&absolute, commit).map_err(git)?,
+        // The date is before the first commit.
+        None => None,
+    };
+    let previous = previous
+        .map(|text| parse_content(path, &text, args.format, &context.config))
+        .transpose()
+        .map_err(|e| ScaffoldError::load(&format!("{} at {}", path, since), e))?;
+    Ok(shipped_since(roadmap, previous.as_ref()))
+}
+
+/// The features whose issues were closed on the forge since `--since`.
+/// Features are matched to issues by their issue link, then the state
+/// file, then their title.
+async fn closed_since(
+    args: &Args,
+    context: &Context,
+    roadmap: &Roadmap,
+) -> Result<ReleaseNotes, ScaffoldError> {
+    let after = match &args.since {
+        Some(since) => {
+            let dir = Path::new(&args.input)
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            let resolved = resolve(dir, since)
+                .map_err(|e| ScaffoldError::parse(Some(&dir.display().to_string()), e))?;
+            Some(resolved.time)
+        }
+        None => None,
+    };
+    let forge = args.forge.client(&context.config)?;
+    let issues = forge
+        .list_issues()
+        .await
+        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    let closed: HashMap<u64, IssueLink> = issues
+        .iter()
+        .filter(|issue| issue.state == "closed" && issue.pull_request.is_none())
+        .filter(|issue| {
+            let closed_at = issue
+                .closed_at
+                .as_deref()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| at.with_timezone(&Utc));
+            match (after, closed_at) {
+                (Some(after), Some(closed_at)) => closed_at >= after,
+                (Some(_), None) => false,
+                (None, _) => true,
+            }
+        })
+        .map(|issue| {
+            let link = IssueLink {
+                number: issue.number,
+                url: issue.html_url.clone().unwrap_or_default(),
+            };
+            (issue.number, link)
+        })
+        .collect();
+    let titles: HashMap<&str, u64> = issues
+        .iter()
+        .map(|issue| (issue.title.trim(), issue.number))
+        .collect();
+    let state = State::load(&context.state)?;
+    let recorded = state.repo(forge.repo());
+    let number = |title: &str, link: Option<&IssueLink>| {
+        link.map(|link| link.number)
+            .or_else(|| recorded.and_then(|recorded| recorded.feature(title)))
+            .or_else(|| titles.get(title.trim()).copied())
+    };
+
+    let mut notes = shipped_features(roadmap, |feature| {
+        number(&feature.title, feature.issue.as_ref()).is_some_and(|n| closed.contains_key(&n))
+    });
+    // Link the features whose heading has no issue link to their issue.
+    for item in notes
+        .milestones
+        .iter_mut()
+        .flat_map(|milestone| &mut milestone.groups)
+        .flat_map(|group| &mut group.items)
+    {
+        if item.issue.is_none() {
+            item.issue = number(&item.title, None).and_then(|n| closed.get(&n).cloned());
+        }
+    }
+    Ok(notes)
+}
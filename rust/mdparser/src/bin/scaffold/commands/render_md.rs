@@ -0,0 +1,28 @@
+use gitscaffold_parser::render::{render_markdown, RenderOptions};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{write_output, Context, Inputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// Sort milestones by due date and features by title
+    #[arg(long)]
+    sort: bool,
+    /// Write the Markdown to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Render the roadmap as canonical Markdown.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let markdown = render_markdown(&roadmap, RenderOptions { sort: args.sort });
+    write_output(args.output.as_deref(), &markdown)?;
+    Ok(())
+}
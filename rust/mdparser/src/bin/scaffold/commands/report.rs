@@ -0,0 +1,30 @@
+use chrono::Local;
+
+use gitscaffold_parser::report::{render, PageFormat};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{write_output, Context, Inputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Page format
+    #[arg(long, value_enum, default_value_t = PageFormat::Html)]
+    format: PageFormat,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+    /// Write the page to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Render the roadmap as a page for publishing.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.input_format)?;
+    let page = render(&roadmap, Local::now().date_naive(), args.format);
+    write_output(args.output.as_deref(), &page)?;
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::github::{GitHubError, MilestoneUpdate, RemoteIssue, RemoteMilestone};
+use mdparser::runs::Run;
+use mdparser::state::State;
+use mdparser::status;
+
+use super::{Context, ForgeArgs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The run to undo, as printed by `sync`; lists the recorded runs when
+    /// left out
+    #[arg(value_name = "SYNC_ID")]
+    id: Option<String>,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Permanently delete the run's issues instead of closing them (needs
+    /// admin access)
+    #[arg(long)]
+    delete: bool,
+    /// List what would be closed or deleted without touching it
+    #[arg(long)]
+    dry_run: bool,
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// What rolling back does to one of the run's milestones or issues.
+enum Undo<'a> {
+    CloseIssue(&'a RemoteIssue),
+    DeleteIssue(&'a RemoteIssue),
+    CloseMilestone(&'a RemoteMilestone),
+}
+
+impl Undo<'_> {
+    fn describe(&self) -> String {
+        match self {
+            Undo::CloseIssue(issue) => format!("close issue #{} {}", issue.number, issue.title),
+            Undo::DeleteIssue(issue) => format!("delete issue #{} {}", issue.number, issue.title),
+            Undo::CloseMilestone(milestone) => format!("close milestone {}", milestone.title),
+        }
+    }
+}
+
+/// Close or delete the issues and close the milestones a sync run created.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let dir = context.state_dir().join("runs");
+    let Some(id) = &args.id else {
+        return list(&Run::list(&dir)?);
+    };
+    let run = Run::load(&dir, id)?;
+    let forge = args.forge.client_for(&context.config, &run.repo)?;
+    let github = |e| ScaffoldError::github(forge.repo(), e);
+    let issues = forge.list_issues().await.map_err(github)?;
+    let milestones = forge.list_milestones().await.map_err(github)?;
+
+    // What was closed or deleted since, by an earlier rollback or by hand,
+    // is left alone.
+    let mut undo = Vec::new();
+    for created in &run.issues {
+        let Some(issue) = issues.iter().find(|issue| issue.number == created.number) else {
+            continue;
+        };
+        if args.delete {
+            undo.push(Undo::DeleteIssue(issue));
+        } else if issue.state == "open" {
+            undo.push(Undo::CloseIssue(issue));
+        }
+    }
+    for created in &run.milestones {
+        let open = milestones
+            .iter()
+            .find(|milestone| milestone.number == created.number && milestone.state == "open");
+        undo.extend(open.map(Undo::CloseMilestone));
+    }
+    if undo.is_empty() {
+        status!(
+            "Nothing left to roll back of sync run {} in {}.",
+            run.id,
+            run.repo
+        );
+        if args.dry_run {
+            return Ok(());
+        }
+        return forget(context, &dir, &run);
+    }
+
+    println!("Rolling back sync run {} in {}:", run.id, run.repo);
+    for step in &undo {
+        println!("  {}", step.describe());
+    }
+    if args.dry_run {
+        status!("Dry run: nothing was changed.");
+        return Ok(());
+    }
+    if !args.yes {
+        if !io::stdin().is_terminal() {
+            return Err(ScaffoldError::Usage(
+                "refusing to continue without confirmation; pass --yes".into(),
+            ));
+        }
+        let warning = if args.delete {
+            " Deleted issues cannot be restored."
+        } else {
+            ""
+        };
+        eprint!(
+            "Undo {} change(s) in {}?{} [y/N] ",
+            undo.len(),
+            run.repo,
+            warning
+        );
+        io::stderr().flush().ok();
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|e| ScaffoldError::io("<stdin>", e))?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            status!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    let comment = format!(
+        "Closed by `scaffold rollback {}`, which undoes the sync that created this issue.",
+        run.id
+    );
+    let mut failures: Vec<GitHubError> = Vec::new();
+    for step in &undo {
+        let result = match step {
+            Undo::CloseIssue(issue) => forge.close_issue(issue.number, &comment).await,
+            Undo::DeleteIssue(issue) => forge.delete_issue(issue).await,
+            Undo::CloseMilestone(milestone) => {
+                let update = MilestoneUpdate {
+                    state: Some("closed"),
+                    ..MilestoneUpdate::default()
+                };
+                forge.update_milestone(milestone.number, &update).await
+            }
+        };
+        match result {
+            Ok(()) => status!("Done: {}", step.describe()),
+            Err(e) => {
+                tracing::error!("Failed to {}: {}", step.describe(), e);
+                failures.push(e);
+            }
+        }
+    }
+    status!(
+        "Undid {} of {} change(s) in {}.",
+        undo.len() - failures.len(),
+        undo.len(),
+        run.repo
+    );
+    match failures.into_iter().next() {
+        // The run stays recorded, so running rollback again retries.
+        Some(e) => Err(github(e)),
+        None => forget(context, &dir, &run),
+    }
+}
+
+/// Drop the rolled-back run from the state file and the recorded runs.
+fn forget(context: &Context, dir: &Path, run: &Run) -> Result<(), ScaffoldError> {
+    let mut state = State::load(&context.state)?;
+    run.forget(state.repo_mut(&run.repo));
+    state.save(&context.state)?;
+    run.remove(dir)
+}
+
+fn list(runs: &[Run]) -> Result<(), ScaffoldError> {
+    if runs.is_empty() {
+        println!("No sync runs are recorded.");
+        return Ok(());
+    }
+    for run in runs {
+        println!(
+            "{}  {}  {} milestone(s), {} issue(s)",
+            run.id,
+            run.repo,
+            run.milestones.len(),
+            run.issues.len()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use mdparser::error::ScaffoldError;
+use mdparser::forge::Forge;
+use mdparser::rollup::{find_comment, rollups};
+use mdparser::status;
+
+use super::{Context, ForgeArgs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    forge: ForgeArgs,
+    /// Only roll up this parent issue, open or closed; repeat for several.
+    /// Defaults to every open issue whose task list references issues
+    #[arg(long, value_name = "NUMBER")]
+    issue: Vec<u64>,
+    /// Print the progress without commenting
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Post or update a progress comment on each parent issue listing its
+/// sub-issues.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let forge = args.forge.client(&context.config)?;
+    let github = |e| ScaffoldError::github(forge.repo(), e);
+    let issues = forge.list_issues().await.map_err(github)?;
+    let mut rollups = rollups(&issues);
+    match args.issue.is_empty() {
+        true => rollups.retain(|rollup| rollup.parent.state == "open"),
+        false => {
+            if let Some(number) = args
+                .issue
+                .iter()
+                .find(|&&number| !rollups.iter().any(|rollup| rollup.parent.number == number))
+            {
+                return Err(ScaffoldError::Usage(format!(
+                    "issue #{} in {} does not reference any sub-issues",
+                    number,
+                    forge.repo()
+                )));
+            }
+            rollups.retain(|rollup| args.issue.contains(&rollup.parent.number));
+        }
+    }
+    if rollups.is_empty() {
+        status!("No open issues with sub-issues in {}.", forge.repo());
+        return Ok(());
+    }
+
+    let (mut changed, mut failures) = (0, Vec::new());
+    for rollup in &rollups {
+        let parent = rollup.parent;
+        let comment = rollup.comment();
+        let comments = forge.list_comments(parent.number).await.map_err(github)?;
+        let existing = find_comment(&comments);
+        let (verb, result) = match existing {
+            Some(existing) if existing.body.trim_end() == comment => ("Unchanged", None),
+            Some(_) if args.dry_run => ("Would update", None),
+            None if args.dry_run => ("Would post", None),
+            Some(existing) => (
+                "Updated",
+                Some(
+                    forge
+                        .update_comment(parent.number, existing.id, &comment)
+                        .await,
+                ),
+            ),
+            None => (
+                "Posted",
+                Some(forge.comment_on_issue(parent.number, &comment).await),
+            ),
+        };
+        match result {
+            Some(Err(e)) => {
+                tracing::error!("Failed to comment on #{}: {}", parent.number, e);
+                failures.push(e);
+                continue;
+            }
+            Some(Ok(())) => changed += 1,
+            None => {}
+        }
+        let line = format!(
+            "{} #{} {}: {}",
+            verb,
+            parent.number,
+            parent.title,
+            rollup.summary()
+        );
+        // A dry run's output is what it would change.
+        match args.dry_run {
+            true => println!("{}", line),
+            false => status!("{}", line),
+        }
+    }
+    match args.dry_run {
+        true => status!("Dry run: no comments were changed."),
+        false => status!(
+            "{} of {} rollup comment(s) changed in {}.",
+            changed,
+            rollups.len(),
+            forge.repo()
+        ),
+    }
+    match failures.into_iter().next() {
+        Some(e) => Err(github(e)),
+        None => Ok(()),
+    }
+}
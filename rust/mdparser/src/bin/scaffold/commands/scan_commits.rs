@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use gitscaffold_parser::{parse_markdown_with, InputFormat, STDIN};
+use mdparser::error::ScaffoldError;
+use mdparser::pull::{apply, Change};
+use mdparser::scan::{commits, plan};
+use mdparser::state::State;
+use mdparser::status;
+
+use super::{read_input, write_output, Context, Status};
+use crate::style::{paint, use_color, GREEN};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Markdown roadmap to update; `-` reads it from stdin and writes the
+    /// update to stdout
+    input: String,
+    /// Only scan the commits after this revision, e.g. a tag or `HEAD~20`
+    #[arg(long, value_name = "REV")]
+    since: Option<String>,
+    /// Scan at most this many commits back from HEAD
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    max_count: usize,
+    /// Repository to scan; defaults to the one holding the roadmap
+    #[arg(long, value_name = "DIR")]
+    git_dir: Option<PathBuf>,
+    /// Repository whose issue numbers the state file records, to match
+    /// `closes #12` against features that have no issue link
+    #[arg(long, env = "GITHUB_REPOSITORY")]
+    repo: Option<String>,
+    /// Write the changes into the roadmap instead of only listing them
+    #[arg(long)]
+    write: bool,
+    /// Write the updated roadmap to this file; implies `--write`
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Mark features and tasks done from commit messages that close them.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let path = &args.input;
+    let source = read_input(path)?;
+    if InputFormat::Auto.resolve(path, &source) != InputFormat::Md {
+        return Err(ScaffoldError::Usage(format!(
+            "{}: only Markdown roadmaps can be updated from commits",
+            path
+        )));
+    }
+    let roadmap = parse_markdown_with(&source, &context.config.headings)
+        .map_err(|e| ScaffoldError::load(path, e))?;
+
+    let dir = match &args.git_dir {
+        Some(dir) => dir.clone(),
+        None => Path::new(path)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf(),
+    };
+    let history = commits(&dir, args.since.as_deref(), args.max_count)
+        .map_err(|e| ScaffoldError::parse(Some(&dir.display().to_string()), e))?;
+    let state = State::load(&context.state)?;
+    let recorded = args.repo.as_deref().and_then(|repo| state.repo(repo));
+    let found = plan(&roadmap, &history, recorded);
+
+    let write = args.write || args.output.is_some();
+    let target = args.output.as_deref().unwrap_or(path);
+    let status = Status {
+        stderr: write && target == STDIN,
+    };
+    let color = use_color();
+    for found in &found {
+        let line = format!(
+            "~ {} ({} {})",
+            found.change,
+            found.commit.short_id(),
+            found.commit.summary()
+        );
+        status.print(paint(&line, GREEN, color));
+    }
+    if !write || found.is_empty() {
+        status.print(format_args!(
+            "{} change(s) from {} commit(s).",
+            found.len(),
+            history.len()
+        ));
+        if status.stderr {
+            print!("{}", source);
+        }
+        return Ok(());
+    }
+    let changes: Vec<Change> = found.into_iter().map(|found| found.change).collect();
+    let updated = apply(&source, &roadmap, &changes);
+    write_output(Some(target), &updated)?;
+    status!(
+        "Applied {} change(s) from {} commit(s) to {}.",
+        changes.len(),
+        history.len(),
+        target
+    );
+    Ok(())
+}
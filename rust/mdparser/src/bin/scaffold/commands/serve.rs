@@ -0,0 +1,354 @@
+use std::env;
+use std::ffi::OsString;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use git2::Repository;
+use glob::Pattern;
+use tokio::sync::Notify;
+
+use gitscaffold_parser::STDIN;
+use mdparser::error::ScaffoldError;
+use mdparser::status;
+use mdparser::webhook::{verify, Push};
+
+use super::{Context, Inputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Address to listen on for webhook deliveries
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+    /// The webhook's secret, to check the signature of every delivery
+    #[arg(
+        long,
+        env = "GITSCAFFOLD_WEBHOOK_SECRET",
+        hide_env_values = true,
+        value_name = "SECRET"
+    )]
+    secret: String,
+    /// Only sync after pushes to this branch; defaults to the repository's
+    /// default branch. The checkout must be on the pushed branch: pushes
+    /// to any other are not synced
+    #[arg(long, value_name = "BRANCH")]
+    branch: Vec<String>,
+    /// Also sync when files matching this pattern change, e.g. fragments
+    /// the roadmap includes; relative to the repository root
+    #[arg(long, value_name = "GLOB")]
+    watch: Vec<String>,
+    /// Sync the checkout as it is instead of pulling the pushed commits
+    /// into it first
+    #[arg(long)]
+    no_pull: bool,
+    /// Options passed on to `scaffold sync`, after `--`
+    #[arg(last = true, value_name = "SYNC_OPTIONS")]
+    sync: Vec<String>,
+}
+
+/// What the webhook handler shares with the task running the syncs.
+struct Server {
+    secret: Vec<u8>,
+    branches: Vec<String>,
+    /// The roadmap files and `--watch` patterns, relative to the
+    /// repository root.
+    watched: Vec<Pattern>,
+    /// The branches with pushes waiting to be synced, each once.
+    pushed: Mutex<Vec<String>>,
+    /// Woken for every push that changes the roadmap; pushes arriving
+    /// during a sync add up to one more.
+    pending: Notify,
+}
+
+/// Run an HTTP server that syncs the roadmap whenever a push to GitHub
+/// changes it.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let paths = args.input.expand()?;
+    if paths.iter().any(|path| path == STDIN) {
+        return Err(ScaffoldError::Usage(
+            "serve syncs roadmap files from a checkout, not stdin".into(),
+        ));
+    }
+    let root = checkout(Path::new(&paths[0]))?;
+    let mut watched = Vec::new();
+    for path in &paths {
+        let absolute = Path::new(path)
+            .canonicalize()
+            .map_err(|e| ScaffoldError::io(path, e))?;
+        let relative = absolute
+            .strip_prefix(&root)
+            .map_err(|_| ScaffoldError::Usage(format!("{} is not in {}", path, root.display())))?;
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        watched.push(Pattern::new(&Pattern::escape(&relative)).expect("escaped patterns parse"));
+    }
+    for pattern in &args.watch {
+        let pattern = Pattern::new(pattern)
+            .map_err(|e| ScaffoldError::Usage(format!("invalid --watch '{}': {}", pattern, e)))?;
+        watched.push(pattern);
+    }
+    let server = Arc::new(Server {
+        secret: args.secret.clone().into_bytes(),
+        branches: args.branch.clone(),
+        watched,
+        pushed: Mutex::new(Vec::new()),
+        pending: Notify::new(),
+    });
+
+    let sync = sync_command(&args, context, &paths)?;
+    let worker = server.clone();
+    let pull = !args.no_pull;
+    tokio::spawn(async move {
+        loop {
+            worker.pending.notified().await;
+            for branch in worker.take_pushed() {
+                let (root, sync) = (root.clone(), sync.clone());
+                let result =
+                    tokio::task::spawn_blocking(move || resync(&root, &branch, pull, &sync)).await;
+                match result {
+                    Ok(Ok(())) => tracing::info!("sync finished"),
+                    Ok(Err(e)) => tracing::error!("sync failed: {}", e),
+                    Err(e) => tracing::error!("sync panicked: {}", e),
+                }
+            }
+        }
+    });
+
+    let app = Router::new().route("/", post(webhook)).with_state(server);
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .map_err(|e| ScaffoldError::io(&args.listen.to_string(), e))?;
+    status!(
+        "Listening for GitHub push webhooks on http://{}/",
+        args.listen
+    );
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await
+        .map_err(|e| ScaffoldError::io(&args.listen.to_string(), e))
+}
+
+/// The root of the git checkout holding the roadmap at `path`.
+fn checkout(path: &Path) -> Result<PathBuf, ScaffoldError> {
+    let absolute = path
+        .canonicalize()
+        .map_err(|e| ScaffoldError::io(&path.display().to_string(), e))?;
+    Repository::discover(absolute.parent().unwrap_or(&absolute))
+        .ok()
+        .and_then(|repository| repository.workdir()?.canonicalize().ok())
+        .ok_or_else(|| {
+            ScaffoldError::Usage(format!(
+                "{} is not in a git checkout, which serve pulls pushes into",
+                path.display()
+            ))
+        })
+}
+
+/// The command line of `scaffold sync` of `paths`, with the global options
+/// of this run, such as its config, variables and state file, and the
+/// options after `--`.
+fn sync_command(
+    args: &Args,
+    context: &Context,
+    paths: &[String],
+) -> Result<Vec<OsString>, ScaffoldError> {
+    let exe = env::current_exe().map_err(|e| ScaffoldError::io("scaffold", e))?;
+    let mut command = vec![exe.into()];
+    command.extend(context.globals.iter().cloned());
+    command.push("sync".into());
+    command.extend(paths.iter().chain(&args.sync).map(OsString::from));
+    Ok(command)
+}
+
+/// The branch the checkout at `root` is on; `None` for a detached head.
+fn checked_out(root: &Path) -> Result<Option<String>, String> {
+    let repository =
+        Repository::open(root).map_err(|e| format!("cannot open the checkout: {}", e))?;
+    let head = repository
+        .head()
+        .map_err(|e| format!("cannot read the checkout's HEAD: {}", e))?;
+    match head.is_branch() {
+        true => Ok(head.shorthand().map(str::to_string)),
+        false => Ok(None),
+    }
+}
+
+/// Sync a push to `branch`, which the checkout at `root` must be on since
+/// `git pull` updates the branch checked out: pull the pushed commits into
+/// it unless told not to, then run the `sync` command line.
+fn resync(root: &Path, branch: &str, pull: bool, sync: &[OsString]) -> Result<(), String> {
+    let current = checked_out(root)?;
+    if current.as_deref() != Some(branch) {
+        return Err(format!(
+            "the push is to {} but the checkout is on {}; check out {} to sync its pushes",
+            branch,
+            current.as_deref().unwrap_or("a detached HEAD"),
+            branch
+        ));
+    }
+    if pull {
+        let status = Command::new("git")
+            .args(["pull", "--ff-only"])
+            .current_dir(root)
+            .status()
+            .map_err(|e| format!("cannot run git: {}", e))?;
+        if !status.success() {
+            return Err(format!("git pull failed ({})", status));
+        }
+    }
+    let status = Command::new(&sync[0])
+        .args(&sync[1..])
+        .status()
+        .map_err(|e| format!("cannot run scaffold sync: {}", e))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(format!("scaffold sync exited with {}", status)),
+    }
+}
+
+/// Handle one webhook delivery: queue a sync for a verified push that
+/// changes the roadmap on a watched branch.
+async fn webhook(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, &'static str) {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if !verify(&server.secret, &body, header("x-hub-signature-256")) {
+        tracing::warn!("rejected a delivery with a missing or invalid signature");
+        return (StatusCode::UNAUTHORIZED, "invalid signature");
+    }
+    match header("x-github-event") {
+        Some("ping") => return (StatusCode::OK, "pong"),
+        Some("push") => {}
+        _ => return (StatusCode::ACCEPTED, "ignored: not a push"),
+    }
+    let push: Push = match serde_json::from_slice(&body) {
+        Ok(push) => push,
+        Err(e) => {
+            tracing::warn!("unreadable push event: {}", e);
+            return (StatusCode::BAD_REQUEST, "unreadable push event");
+        }
+    };
+    if !server.wants(&push) {
+        tracing::debug!(reference = push.reference, "push leaves the roadmap alone");
+        return (StatusCode::OK, "ignored: roadmap unchanged");
+    }
+    tracing::info!(
+        repository = push.repository.full_name,
+        reference = push.reference,
+        "roadmap changed; queueing a sync"
+    );
+    server.push(push.branch().unwrap_or_default());
+    (StatusCode::ACCEPTED, "sync queued")
+}
+
+impl Server {
+    /// Queue a sync of `branch`, unless one is queued already.
+    fn push(&self, branch: &str) {
+        let mut pushed = self.pushed.lock().unwrap_or_else(|e| e.into_inner());
+        if !pushed.iter().any(|queued| queued == branch) {
+            pushed.push(branch.to_string());
+        }
+        drop(pushed);
+        self.pending.notify_one();
+    }
+
+    /// The branches queued since the last call, in the order they were
+    /// pushed.
+    fn take_pushed(&self) -> Vec<String> {
+        std::mem::take(&mut *self.pushed.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Whether `push` is to a watched branch and changes a watched file.
+    fn wants(&self, push: &Push) -> bool {
+        if push.deleted {
+            return false;
+        }
+        let Some(branch) = push.branch() else {
+            return false;
+        };
+        let watched_branch = match self.branches.as_slice() {
+            [] => push.repository.default_branch.as_deref() == Some(branch),
+            branches => branches.iter().any(|wanted| wanted == branch),
+        };
+        watched_branch
+            && push
+                .changed()
+                .any(|path| self.watched.iter().any(|pattern| pattern.matches(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wants_roadmap_changes_on_watched_branches() {
+        let server = |branches: &[&str]| Server {
+            secret: Vec::new(),
+            branches: branches.iter().map(|b| b.to_string()).collect(),
+            watched: vec![
+                Pattern::new("ROADMAP.md").unwrap(),
+                Pattern::new("roadmap/*.md").unwrap(),
+            ],
+            pushed: Mutex::new(Vec::new()),
+            pending: Notify::new(),
+        };
+        let push = |reference: &str, path: &str| -> Push {
+            serde_json::from_value(serde_json::json!({
+                "ref": reference,
+                "repository": {"full_name": "acme/app", "default_branch": "main"},
+                "commits": [{"modified": [path]}],
+            }))
+            .unwrap()
+        };
+        let default = server(&[]);
+        assert!(default.wants(&push("refs/heads/main", "ROADMAP.md")));
+        assert!(default.wants(&push("refs/heads/main", "roadmap/q3.md")));
+        assert!(!default.wants(&push("refs/heads/main", "src/main.rs")));
+        assert!(!default.wants(&push("refs/heads/dev", "ROADMAP.md")));
+        assert!(!default.wants(&push("refs/tags/v1", "ROADMAP.md")));
+        assert!(server(&["dev"]).wants(&push("refs/heads/dev", "ROADMAP.md")));
+    }
+
+    #[test]
+    fn syncs_only_pushes_to_the_checked_out_branch() {
+        let root = env::temp_dir().join(format!("gitscaffold-serve-{}", std::process::id()));
+        let repository = Repository::init(&root).unwrap();
+        let signature = git2::Signature::now("Dev", "dev@example.com").unwrap();
+        let tree = repository
+            .find_tree(repository.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repository
+            .commit(
+                Some("refs/heads/main"),
+                &signature,
+                &signature,
+                "init",
+                &tree,
+                &[],
+            )
+            .unwrap();
+        repository.set_head("refs/heads/main").unwrap();
+
+        let succeed = [OsString::from("true")];
+        let refused = resync(&root, "dev", false, &succeed);
+        let synced = resync(&root, "main", false, &succeed);
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            refused.unwrap_err(),
+            "the push is to dev but the checkout is on main; check out dev to sync its pushes"
+        );
+        assert_eq!(synced, Ok(()));
+    }
+}
@@ -0,0 +1,43 @@
+use chrono::Utc;
+
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+use mdparser::snapshot::Snapshot;
+use mdparser::status;
+
+use super::{Context, Inputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+}
+
+/// Keep a copy of the parsed roadmap for `scaffold history`, unless it is
+/// the same as the latest one.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let dir = context.state_dir().join("snapshots");
+    let snapshot = Snapshot::new(&args.input.to_string(), roadmap, Utc::now());
+    if let Some(latest) = Snapshot::list(&dir)?.last() {
+        if latest.hash == snapshot.hash {
+            status!(
+                "The roadmap is unchanged since snapshot {}; nothing to save.",
+                latest.id
+            );
+            return Ok(());
+        }
+    }
+    snapshot.save(&dir)?;
+    status!(
+        "Saved snapshot {} of {}: {} milestone(s), {} feature(s).",
+        snapshot.id,
+        snapshot.source,
+        snapshot.roadmap.milestones.len(),
+        snapshot.roadmap.features.len()
+    );
+    Ok(())
+}
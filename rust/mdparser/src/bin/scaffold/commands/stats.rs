@@ -0,0 +1,121 @@
+use chrono::Local;
+
+use gitscaffold_parser::stats::{stats, Counts, Stats};
+use gitscaffold_parser::InputFormat;
+use mdparser::error::ScaffoldError;
+
+use super::{Context, Inputs, ReportFormat};
+use crate::style::{paint, use_color, RED};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// How to print the statistics
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+    /// Print the statistics as JSON; short for `--report-format json`
+    #[arg(long, conflicts_with = "report_format")]
+    json: bool,
+}
+
+/// Print completion statistics per milestone and for the whole roadmap.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let stats = stats(&roadmap, Local::now().date_naive());
+    let format = match args.json {
+        true => ReportFormat::Json,
+        false => args.report_format,
+    };
+    match format {
+        ReportFormat::Human => print_table(&stats),
+        ReportFormat::Json => println!("{}", context.json.to_string(&stats)?),
+    }
+    Ok(())
+}
+
+const HEADER: [&str; 11] = [
+    "Milestone",
+    "Due",
+    "Features",
+    "Done",
+    "In progress",
+    "Blocked",
+    "Planned",
+    "Deferred",
+    "P0/P1/P2/P3",
+    "Tasks",
+    "Complete",
+];
+
+fn print_table(stats: &Stats) {
+    let mut rows: Vec<(Vec<String>, bool)> = stats
+        .milestones
+        .iter()
+        .map(|m| {
+            let name = m.name.as_deref().unwrap_or("(no milestone)");
+            let due = m.due_date.as_deref().unwrap_or("-");
+            (row(name, due, &m.counts), m.overdue)
+        })
+        .collect();
+    rows.push((row("Total", "", &stats.overall), false));
+
+    let mut widths: Vec<usize> = HEADER.iter().map(|h| h.len()).collect();
+    for (cells, _) in &rows {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    let color = use_color();
+    println!("{}", line(&HEADER.map(String::from)));
+    for (cells, overdue) in &rows {
+        if *overdue {
+            println!(
+                "{}",
+                paint(&format!("{}  overdue", line(cells)), RED, color)
+            );
+        } else {
+            println!("{}", line(cells));
+        }
+    }
+    if let Some(average) = stats.overall.tasks_per_feature {
+        println!("Average tasks per feature: {:.1}", average);
+    }
+    if !stats.overdue.is_empty() {
+        println!("Overdue milestones: {}", stats.overdue.join(", "));
+    }
+}
+
+fn row(name: &str, due: &str, counts: &Counts) -> Vec<String> {
+    let status = &counts.by_status;
+    let priority = &counts.by_priority;
+    vec![
+        name.to_string(),
+        due.to_string(),
+        counts.features.to_string(),
+        status.done.to_string(),
+        status.in_progress.to_string(),
+        status.blocked.to_string(),
+        status.planned.to_string(),
+        status.deferred.to_string(),
+        format!(
+            "{}/{}/{}/{}",
+            priority.p0, priority.p1, priority.p2, priority.p3
+        ),
+        format!("{}/{}", counts.completed_tasks, counts.tasks),
+        counts
+            .completion
+            .map_or("-".into(), |percent| format!("{:.0}%", percent)),
+    ]
+}
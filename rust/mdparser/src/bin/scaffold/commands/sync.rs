@@ -0,0 +1,815 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use gitscaffold_parser::graph::add_missing_milestones;
+use gitscaffold_parser::validate::{validate, RuleConfig, Severity};
+use gitscaffold_parser::{html, Feature, HtmlPolicy, InputFormat, Roadmap};
+use mdparser::actions::{Annotation, Level};
+use mdparser::error::ScaffoldError;
+use mdparser::forge::{AnyForge, Forge};
+use mdparser::github::GitHub;
+use mdparser::images::{local_images, upload_images};
+use mdparser::journal::Journal;
+use mdparser::labels::{add_field_labels, add_priority_labels, resolve_aliases, used_labels};
+use mdparser::notify::Notify;
+use mdparser::project::Board;
+use mdparser::provenance::Provenance;
+use mdparser::state::State;
+use mdparser::status;
+use mdparser::sync::{
+    apply, check_assignees, plan, split_by_repo, sync, Execution, Field, Matching, Operation, Plan,
+    Reconcile, SyncReport, Synced,
+};
+use mdparser::template::IssueTemplates;
+
+use super::labels::{print_plan as print_labels, sync_labels};
+use super::validate::{annotate, check, print_human};
+use super::{interrupted, Context, ForgeArgs, Inputs, JobsArgs};
+use crate::style::{paint, use_color, GREEN, RED, YELLOW};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    #[command(flatten)]
+    forge: ForgeArgs,
+    #[command(flatten)]
+    jobs: JobsArgs,
+    /// Print the operations sync would perform without changing anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Write the plan to this file for `scaffold apply` instead of syncing;
+    /// implies `--dry-run`
+    #[arg(long, value_name = "FILE")]
+    plan_out: Option<String>,
+    /// Directory with `issue_title.j2` and/or `issue_body.j2` templates
+    /// replacing the built-in ones
+    #[arg(long, value_name = "DIR")]
+    template_dir: Option<PathBuf>,
+    /// Append a footer linking each issue to its feature in the roadmap at
+    /// the current commit; `provenance` under `[sync]` in the config turns
+    /// it on for every run
+    #[arg(long)]
+    provenance: bool,
+    /// Branch, tag or commit that relative links in descriptions, like
+    /// `./docs/design.md`, point at in issues; defaults to the remote's
+    /// default branch
+    #[arg(long = "ref", value_name = "REF")]
+    git_ref: Option<String>,
+    /// Upload the local images in descriptions to the repository and show
+    /// the uploaded files in issues; `upload_images` under `[sync]` turns
+    /// it on for every run
+    #[arg(long)]
+    upload_images: bool,
+    /// Branch to commit uploaded images to instead of the default branch;
+    /// defaults to `assets_branch` under `[sync]`
+    #[arg(long, value_name = "BRANCH")]
+    assets_branch: Option<String>,
+    /// What to do with raw HTML and footnotes in descriptions before they
+    /// become issue bodies
+    #[arg(long, value_enum, default_value_t = HtmlPolicy::Keep)]
+    html: HtmlPolicy,
+    /// Only treat a feature as renamed when the state file records its
+    /// issue, instead of also matching similar issue titles
+    #[arg(long)]
+    no_fuzzy: bool,
+    /// Also update existing issues whose body, labels, milestone or
+    /// assignees drifted from the roadmap
+    #[arg(long)]
+    update: bool,
+    /// Fields `--update` leaves alone, e.g. ones edited by hand on the forge
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FIELD",
+        value_delimiter = ',',
+        requires = "update"
+    )]
+    protect: Vec<Field>,
+    /// Whether `--update` leaves a comment on each issue it updates,
+    /// summarizing what changed, so the issue's followers hear of it
+    #[arg(long, value_enum, default_value_t = Notify::Silent, requires = "update")]
+    notify: Notify,
+    /// Also retitle renamed milestones, update changed due dates and
+    /// descriptions, and close milestones whose features are all done
+    #[arg(long)]
+    milestones: bool,
+    /// Create the milestones features name but the roadmap does not
+    /// define, without a due date, instead of refusing to sync
+    #[arg(long)]
+    create_missing_milestones: bool,
+    /// Also create and update the issues of deferred features, such as
+    /// those of a `Deferred` or `Icebox` section
+    #[arg(long)]
+    include_deferred: bool,
+    /// Close the open issues sync made for features since deleted from the
+    /// roadmap, with a comment saying why
+    #[arg(long)]
+    prune: bool,
+    /// Label every new issue, so `--prune` also recognises issues the state
+    /// file does not record; defaults to `marker_label` under `[sync]`
+    #[arg(long, value_name = "LABEL")]
+    marker_label: Option<String>,
+    /// Give tasks issues of their own, below their feature's issue and
+    /// referred to from its task list
+    #[arg(long)]
+    tasks_as_issues: bool,
+    /// How deeply nested tasks still get issues with `--tasks-as-issues`;
+    /// 0 for top-level tasks only
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        requires = "tasks_as_issues"
+    )]
+    max_task_depth: usize,
+    /// Create and rename labels with `scaffold labels sync` before the
+    /// issues; `sync` under `[labels]` turns it on for every run
+    #[arg(long)]
+    sync_labels: bool,
+    /// Do not ask for confirmation before `--prune` closes issues
+    #[arg(short, long)]
+    yes: bool,
+    /// Add the issues to this GitHub project (v2) of the repository's
+    /// owner, setting their Status, iteration and mapped fields
+    #[arg(long, value_name = "NUMBER")]
+    project: Option<u64>,
+    /// Apply only the operations a failed or interrupted sync left undone,
+    /// as its journal records them, instead of planning again
+    #[arg(long, conflicts_with = "plan_out")]
+    resume: bool,
+    /// Directory with the journals of syncs under way; defaults to
+    /// `journal` next to the state file
+    #[arg(long, value_name = "DIR")]
+    journal: Option<PathBuf>,
+}
+
+impl Args {
+    /// The journal of the sync to `repo`.
+    fn journal(&self, context: &Context, repo: &str) -> Journal {
+        let dir = match &self.journal {
+            Some(dir) => dir.clone(),
+            None => context.state_dir().join("journal"),
+        };
+        Journal::new(&dir, repo)
+    }
+}
+
+/// Create the roadmap's missing milestones and issues in the repository.
+pub async fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let mut roadmap = context.load_all(&args.input, args.format)?;
+    add_field_labels(&mut roadmap, &context.config.fields);
+    add_priority_labels(&mut roadmap, &context.config.labels);
+    resolve_aliases(&mut roadmap, &context.config.labels);
+    for warning in html::apply(&mut roadmap, args.html) {
+        tracing::warn!("{}", warning);
+    }
+    if args.create_missing_milestones {
+        for name in add_missing_milestones(&mut roadmap) {
+            status!(
+                "Adding milestone '{}', which features use but the roadmap does not define.",
+                name
+            );
+        }
+    }
+    let deferred = roadmap.features.iter().filter(|f| f.is_deferred()).count();
+    if deferred > 0 && !args.include_deferred {
+        status!(
+            "Leaving {} deferred feature(s) alone; pass --include-deferred to sync them.",
+            deferred
+        );
+    }
+    let diagnostics = validate(&roadmap, &RuleConfig::default());
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        print_human(&args.input.to_string(), None, &diagnostics);
+        annotate(context, &args.input.to_string(), &diagnostics);
+        tracing::error!("Refusing to sync an invalid roadmap.");
+        return check(&args.input.to_string(), &diagnostics);
+    }
+
+    let default = args.forge.repo(&context.config);
+    let parts = split_by_repo(&roadmap, default.as_deref());
+    if let [(None, _)] = parts.as_slice() {
+        let forge = args.forge.client(&context.config)?;
+        return sync_repo(&args, context, forge, roadmap).await;
+    }
+    if args.plan_out.is_some() {
+        return Err(ScaffoldError::Usage(format!(
+            "--plan-out saves the plan of one repository, but the features belong in {}",
+            parts.len()
+        )));
+    }
+
+    // One repository failing does not stop the others.
+    let mut results = Vec::new();
+    for (repo, part) in parts {
+        if interrupted() {
+            break;
+        }
+        let name = repo
+            .clone()
+            .or(default.clone())
+            .unwrap_or_else(|| "the default repository".into());
+        status!("== {} ==", name);
+        let forge = match &repo {
+            Some(repo) => args.forge.client_for(&context.config, repo),
+            None => args.forge.client(&context.config),
+        };
+        let result = match forge {
+            Ok(forge) => sync_repo(&args, context, forge, part).await,
+            Err(e) => Err(e),
+        };
+        results.push((name, result));
+    }
+    status!("Summary:");
+    for (name, result) in &results {
+        match result {
+            Ok(()) => status!("  {}: synced", name),
+            Err(e) => status!("  {}: failed: {}", name, e),
+        }
+    }
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    status!(
+        "Synced {} of {} repositories.",
+        results.len() - failed,
+        results.len()
+    );
+    match results.into_iter().find_map(|(_, result)| result.err()) {
+        Some(e) => Err(e),
+        None if interrupted() => Err(ScaffoldError::Interrupted),
+        None => Ok(()),
+    }
+}
+
+/// Sync `roadmap`, or the part of it that belongs in one repository, to
+/// `forge`.
+async fn sync_repo(
+    args: &Args,
+    context: &Context,
+    forge: AnyForge,
+    mut roadmap: Roadmap,
+) -> Result<(), ScaffoldError> {
+    let board = match args.project {
+        Some(number) => {
+            let AnyForge::GitHub(github) = &forge else {
+                return Err(ScaffoldError::Usage(
+                    "--project only works with GitHub repositories".into(),
+                ));
+            };
+            let board = Board::load(github, number)
+                .await
+                .map_err(|e| ScaffoldError::github(forge.repo(), e))?
+                .with_fields(&context.config.fields);
+            Some((board, github))
+        }
+        None => None,
+    };
+    let project = board.as_ref().map(|(board, github)| (board, *github));
+    if args.resume {
+        return resume(args, context, &forge, &roadmap, project).await;
+    }
+    let mut templates = match &args.template_dir {
+        Some(dir) => IssueTemplates::from_dir(dir)?,
+        None => IssueTemplates::default(),
+    }
+    .with_titles(&context.config.titles)?
+    .with_fields(&context.config.fields);
+    let paths = args.input.expand()?;
+    let repository = Provenance::discover(Path::new(&paths[0]), forge.kind());
+    if let Some(repository) = &repository {
+        // `HEAD` makes the forge pick its default branch itself.
+        let reference = args
+            .git_ref
+            .as_deref()
+            .or(repository.default_branch())
+            .unwrap_or("HEAD");
+        templates = templates.with_absolute_links(repository.clone(), reference.to_string());
+    }
+    if args.provenance || context.config.sync.provenance {
+        match repository {
+            Some(provenance) => templates = templates.with_provenance(provenance),
+            None => tracing::warn!(
+                "{} is not in a git repository; issues get no provenance footer.",
+                paths[0]
+            ),
+        }
+    }
+    if args.upload_images || context.config.sync.upload_images {
+        let roadmap_path = Path::new(&paths[0]);
+        if args.dry_run || args.plan_out.is_some() {
+            for image in local_images(&roadmap, roadmap_path) {
+                println!("- upload image {}", image.path.display());
+            }
+        } else {
+            let branch =
+                args.assets_branch
+                    .as_deref()
+                    .or(context.config.sync.assets_branch.as_deref());
+            let uploads = upload_images(&forge, &mut roadmap, roadmap_path, branch).await?;
+            for (path, url) in &uploads.uploaded {
+                status!("Uploaded {} to {}", path.display(), url);
+            }
+            for path in &uploads.missing {
+                tracing::warn!(
+                    "{} does not exist; its image is not uploaded.",
+                    path.display()
+                );
+            }
+        }
+    }
+    let texts = templates
+        .render(&roadmap)
+        .map_err(|e| ScaffoldError::parse(None, e))?;
+
+    let state = State::load(&context.state)?;
+    let update: Vec<Field> = match args.update {
+        true => Field::ALL
+            .into_iter()
+            .filter(|field| !args.protect.contains(field))
+            .collect(),
+        false => Vec::new(),
+    };
+    let matching = Matching {
+        state: state.repo(forge.repo()),
+        fuzzy_threshold: (!args.no_fuzzy).then_some(context.config.sync.fuzzy_threshold),
+    };
+    let marker_label = args
+        .marker_label
+        .as_deref()
+        .or(context.config.sync.marker_label.as_deref());
+    let reconcile = Reconcile {
+        update: &update,
+        prune: args.prune,
+        milestones: args.milestones,
+        marker_label,
+        tasks_as_issues: args.tasks_as_issues.then_some(args.max_task_depth),
+        notify: args.notify,
+        include_deferred: args.include_deferred,
+    };
+    if args.sync_labels || context.config.labels.sync {
+        let mut wanted = used_labels(&roadmap, &context.config.labels);
+        wanted.extend(marker_label.map(str::to_string));
+        let dry_run = args.dry_run || args.plan_out.is_some();
+        let plan = sync_labels(&forge, &wanted, context, dry_run).await?;
+        print_labels(&plan);
+    }
+    let confirm = args.prune && !args.yes;
+    if args.dry_run || args.plan_out.is_some() || confirm {
+        let milestones = forge
+            .list_milestones()
+            .await
+            .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+        let issues = forge
+            .list_issues()
+            .await
+            .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+        let mut plan = plan(
+            &roadmap,
+            &texts,
+            forge.repo(),
+            &milestones,
+            &issues,
+            matching,
+            reconcile,
+        );
+        check_assignees(&mut plan, &forge)
+            .await
+            .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+        if confirm && !(args.dry_run || args.plan_out.is_some()) {
+            return apply_confirmed(args, &forge, &plan, &roadmap, project, context).await;
+        }
+        print_plan(&plan);
+        plan_outputs(context, &plan)?;
+        if let Some(path) = &args.plan_out {
+            let json = context.json.to_string(&plan)?;
+            fs::write(path, json + "\n").map_err(|e| ScaffoldError::io(path, e))?;
+            status!(
+                "Saved the plan to {}; run `scaffold apply {}` to apply it.",
+                path,
+                path
+            );
+        }
+        return Ok(());
+    }
+
+    let journal = args.journal(context, forge.repo());
+    let execution = Execution {
+        journal: Some(&journal),
+        ..args.jobs.execution(context)
+    };
+    let report = sync(
+        &roadmap,
+        &texts,
+        &forge,
+        matching,
+        reconcile,
+        execution,
+        print_progress,
+    )
+    .await
+    .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    finish(args, &forge, report, journal, &roadmap, project, context).await
+}
+
+/// Apply what the journaled sync to `forge` left undone.
+async fn resume(
+    args: &Args,
+    context: &Context,
+    forge: &impl Forge,
+    roadmap: &Roadmap,
+    project: Project<'_>,
+) -> Result<(), ScaffoldError> {
+    let journal = args.journal(context, forge.repo());
+    let Some(unfinished) = journal.load()? else {
+        status!("No unfinished sync of {} to resume.", forge.repo());
+        return Ok(());
+    };
+    if unfinished.plan.repo != forge.repo() {
+        return Err(ScaffoldError::Usage(format!(
+            "{} journals a sync to {}, not {}",
+            journal.path().display(),
+            unfinished.plan.repo,
+            forge.repo()
+        )));
+    }
+    let done = unfinished.done.len();
+    let plan = unfinished.remaining();
+    status!(
+        "Resuming the sync to {}: {} operation(s) done, {} left.",
+        forge.repo(),
+        done,
+        plan.operations.len()
+    );
+    if args.dry_run {
+        print_plan(&plan);
+        return plan_outputs(context, &plan);
+    }
+    let total = plan.operations.len();
+    let execution = Execution {
+        journal: Some(&journal),
+        ..args.jobs.execution(context)
+    };
+    let report = apply(&plan, forge, execution, |step, operation| {
+        print_progress(step, total, operation)
+    })
+    .await
+    .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    finish(args, forge, report, journal, roadmap, project, context).await
+}
+
+/// The project board issues are added to, and the client to reach it.
+type Project<'a> = Option<(&'a Board, &'a GitHub)>;
+
+/// Apply `plan` once the user agreed to the issues it closes, if any.
+async fn apply_confirmed(
+    args: &Args,
+    forge: &impl Forge,
+    plan: &Plan,
+    roadmap: &Roadmap,
+    project: Project<'_>,
+    context: &Context,
+) -> Result<(), ScaffoldError> {
+    for warning in &plan.warnings {
+        tracing::warn!("{}", warning);
+    }
+    let closes: Vec<&Operation> = plan
+        .operations
+        .iter()
+        .filter(|operation| matches!(operation, Operation::CloseIssue { .. }))
+        .collect();
+    if !closes.is_empty() {
+        if !io::stdin().is_terminal() {
+            return Err(ScaffoldError::Usage(
+                "refusing to close issues without confirmation; pass --yes".into(),
+            ));
+        }
+        for operation in &closes {
+            eprintln!("  {}", operation);
+        }
+        eprint!(
+            "Close {} issue(s) in {} whose features left the roadmap? [y/N] ",
+            closes.len(),
+            forge.repo()
+        );
+        io::stderr().flush().ok();
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|e| ScaffoldError::io("<stdin>", e))?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            status!("Aborted.");
+            return Ok(());
+        }
+    }
+    let total = plan.operations.len();
+    let journal = args.journal(context, forge.repo());
+    let execution = Execution {
+        journal: Some(&journal),
+        ..args.jobs.execution(context)
+    };
+    let report = apply(plan, forge, execution, |step, operation| {
+        print_progress(step, total, operation)
+    })
+    .await
+    .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+    finish(args, forge, report, journal, roadmap, project, context).await
+}
+
+/// Report and record the run, close its journal and put its issues on the
+/// project board, then fail if any of it did.
+async fn finish(
+    args: &Args,
+    forge: &impl Forge,
+    report: SyncReport,
+    journal: Journal,
+    roadmap: &Roadmap,
+    project: Project<'_>,
+    context: &Context,
+) -> Result<(), ScaffoldError> {
+    print_report(&report);
+    context.record(forge.repo(), &report)?;
+    report_outputs(context, &report)?;
+    if journal.finish(&report)? {
+        tracing::warn!("Run `scaffold sync --resume` to apply the operations left undone.");
+    }
+    if let Some((board, github)) = project {
+        if !interrupted() {
+            place(board, github, roadmap, &report).await?;
+        }
+    }
+    let created = |items: &[Synced]| items.iter().filter(|item| item.created).count();
+    status!(
+        "Synced {} to {}: created {} milestone(s) and {} issue(s), renamed {} issue(s), \
+         updated {} issue(s), closed {} issue(s).",
+        args.input,
+        forge.repo(),
+        created(&report.milestones),
+        created(&report.issues),
+        renamed(&report),
+        report.updated.len(),
+        report.closed.len()
+    );
+    failed(forge, report)
+}
+
+/// Add the issues of the run's features to `board`.
+async fn place(
+    board: &Board,
+    github: &GitHub,
+    roadmap: &Roadmap,
+    report: &SyncReport,
+) -> Result<(), ScaffoldError> {
+    let issues = github
+        .list_issues()
+        .await
+        .map_err(|e| ScaffoldError::github(github.repo(), e))?;
+    let nodes: HashMap<u64, &str> = issues
+        .iter()
+        .map(|issue| (issue.number, issue.node_id.as_str()))
+        .collect();
+    let features: HashMap<&str, &Feature> = roadmap
+        .features
+        .iter()
+        .map(|feature| (feature.title.trim(), feature))
+        .collect();
+    let mut placed = 0;
+    let mut error = None;
+    for issue in &report.issues {
+        let feature = issue
+            .feature
+            .as_deref()
+            .and_then(|title| features.get(title.trim()));
+        let (Some(feature), Some(node)) = (feature, nodes.get(&issue.number)) else {
+            continue;
+        };
+        match board.place(github, node, feature).await {
+            Ok(()) => placed += 1,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to add issue #{} to project '{}': {}",
+                    issue.number,
+                    board.title,
+                    e
+                );
+                error.get_or_insert(e);
+            }
+        }
+    }
+    status!("Added {} issue(s) to project '{}'.", placed, board.title);
+    match error {
+        Some(e) => Err(ScaffoldError::github(github.repo(), e)),
+        None => Ok(()),
+    }
+}
+
+/// With `--github-output`, the counts of what the run did as step outputs,
+/// and its failures as annotations.
+pub fn report_outputs(context: &Context, report: &SyncReport) -> Result<(), ScaffoldError> {
+    for failure in &report.failures {
+        let message = format!(
+            "Failed to {} '{}': {}",
+            failure.action, failure.title, failure.error
+        );
+        context.annotate(Annotation::new(Level::Error, message));
+    }
+    let created = |items: &[Synced]| items.iter().filter(|item| item.created).count();
+    let (milestones, issues) = (created(&report.milestones), created(&report.issues));
+    context.outputs(&[
+        ("created_count", (milestones + issues).to_string()),
+        ("created_milestones", milestones.to_string()),
+        ("created_issues", issues.to_string()),
+        ("renamed_count", renamed(report).to_string()),
+        ("updated_count", report.updated.len().to_string()),
+        ("closed_count", report.closed.len().to_string()),
+        ("failed_count", report.failures.len().to_string()),
+        ("skipped_count", report.skipped.to_string()),
+    ])
+}
+
+/// With `--github-output`, the number of operations of `plan` and their
+/// summary, one per line, as step outputs.
+fn plan_outputs(context: &Context, plan: &Plan) -> Result<(), ScaffoldError> {
+    let summary: Vec<String> = plan
+        .operations
+        .iter()
+        .map(|operation| format!("{} {}", operation.marker(), operation))
+        .collect();
+    context.outputs(&[
+        ("operation_count", plan.operations.len().to_string()),
+        ("plan", summary.join("\n")),
+    ])
+}
+
+/// How many issues the run renamed.
+pub fn renamed(report: &SyncReport) -> usize {
+    report
+        .issues
+        .iter()
+        .filter(|issue| issue.renamed_from.is_some())
+        .count()
+}
+
+/// Fail when the run was interrupted, or when any operation failed: as a
+/// partial sync when others changed the forge, with the first error
+/// otherwise. The failures were already listed by [`print_report`].
+pub fn failed(forge: &impl Forge, report: SyncReport) -> Result<(), ScaffoldError> {
+    if interrupted() {
+        return Err(ScaffoldError::Interrupted);
+    }
+    let created = |items: &[Synced]| items.iter().filter(|item| item.created).count();
+    let applied = created(&report.milestones)
+        + created(&report.issues)
+        + renamed(&report)
+        + report.updated.len()
+        + report.closed.len()
+        + report.updated_milestones.len()
+        + report.closed_milestones.len();
+    let failures = report.failures.len();
+    let Some(failure) = report.failures.into_iter().next() else {
+        return Ok(());
+    };
+    if applied == 0 {
+        return Err(ScaffoldError::github(forge.repo(), failure.error));
+    }
+    Err(ScaffoldError::PartialSync(format!(
+        "{}: {} operation(s) failed after {} were applied; run sync again to finish",
+        forge.repo(),
+        failures,
+        applied
+    )))
+}
+
+/// Print the plan's operations, terraform-style, and a summary line.
+pub fn print_plan(plan: &Plan) {
+    let color = use_color();
+    for operation in &plan.operations {
+        let line = format!("{} {}", operation.marker(), operation);
+        let style = match operation.marker() {
+            '+' => GREEN,
+            '-' => RED,
+            _ => YELLOW,
+        };
+        println!("{}", paint(&line, style, color));
+    }
+    for warning in &plan.warnings {
+        tracing::warn!("{}", warning);
+    }
+    let existing = plan.existing_milestones.len() + plan.existing_issues.len();
+    let count =
+        |matches: fn(&Operation) -> bool| plan.operations.iter().filter(|o| matches(o)).count();
+    let renames = count(|operation| {
+        matches!(
+            operation,
+            Operation::RenameIssue { .. } | Operation::RenameMilestone { .. }
+        )
+    });
+    let updates = count(|operation| {
+        matches!(
+            operation,
+            Operation::UpdateIssue { .. } | Operation::UpdateMilestone { .. }
+        )
+    });
+    let closes = count(|operation| {
+        matches!(
+            operation,
+            Operation::CloseIssue { .. } | Operation::CloseMilestone { .. }
+        )
+    });
+    println!(
+        "Plan for {}: {} to create, {} to rename, {} to update, {} to close, {} already exist.",
+        plan.repo,
+        plan.operations.len() - renames - updates - closes,
+        renames,
+        updates,
+        closes,
+        existing
+    );
+}
+
+/// Show which operation is running, when stderr is a terminal.
+pub fn print_progress(step: usize, total: usize, operation: &Operation) {
+    if io::stderr().is_terminal() {
+        status!("[{}/{}] {}", step, total, operation);
+    }
+}
+
+/// Log what the run did, and what it failed to do.
+pub fn print_report(report: &SyncReport) {
+    for milestone in &report.milestones {
+        print_synced("milestone", milestone);
+    }
+    for milestone in &report.updated_milestones {
+        status!(
+            "Updated milestone #{} '{}'",
+            milestone.number,
+            milestone.title
+        );
+    }
+    for milestone in &report.closed_milestones {
+        status!(
+            "Closed milestone #{} '{}'",
+            milestone.number,
+            milestone.title
+        );
+    }
+    for issue in &report.issues {
+        print_synced("issue", issue);
+    }
+    for updated in &report.updated {
+        let fields: Vec<&str> = updated.fields.iter().map(|field| field.name()).collect();
+        status!(
+            "Updated issue #{} '{}': {}",
+            updated.number,
+            updated.title,
+            fields.join(", ")
+        );
+    }
+    for closed in &report.closed {
+        status!("Closed issue #{} '{}'", closed.number, closed.title);
+    }
+    for failure in &report.failures {
+        tracing::error!(
+            "Failed to {} '{}': {}",
+            failure.action,
+            failure.title,
+            failure.error
+        );
+    }
+    if !report.failures.is_empty() {
+        tracing::error!("{} operation(s) failed.", report.failures.len());
+    }
+    if report.skipped > 0 && interrupted() {
+        tracing::warn!(
+            "Interrupted; {} operation(s) were not attempted.",
+            report.skipped
+        );
+    } else if report.skipped > 0 {
+        tracing::warn!(
+            "Stopped after the first failure; {} operation(s) were not attempted. \
+             Pass --keep-going to attempt them anyway.",
+            report.skipped
+        );
+    }
+}
+
+fn print_synced(kind: &str, item: &Synced) {
+    if let Some(from) = &item.renamed_from {
+        status!(
+            "Renamed {} #{} '{}' to '{}'",
+            kind,
+            item.number,
+            from,
+            item.title
+        );
+    } else if item.created {
+        status!("Created {} #{} '{}'", kind, item.number, item.title);
+    } else {
+        status!("Found existing {} #{} '{}'", kind, item.number, item.title);
+    }
+}
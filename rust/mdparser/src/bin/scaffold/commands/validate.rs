@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use gitscaffold_parser::load::read_source;
+use gitscaffold_parser::sarif::validation_log;
+use gitscaffold_parser::validate::{validate, Diagnostic, Rule, RuleConfig, Severity};
+use gitscaffold_parser::{expand_paths, InputFormat};
+use mdparser::actions::Annotation;
+use mdparser::diagnostics::Snippet;
+use mdparser::error::ScaffoldError;
+
+use super::{Context, DiagnosticsFormat, Inputs};
+use crate::style::use_color;
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    input: Inputs,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// How to print diagnostics
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+    report_format: DiagnosticsFormat,
+    /// Skip a rule (repeatable)
+    #[arg(long, value_enum, value_name = "RULE")]
+    disable: Vec<Rule>,
+    /// Report a rule's violations as warnings (repeatable)
+    #[arg(long, value_enum, value_name = "RULE")]
+    warn: Vec<Rule>,
+    /// Report a rule's violations as errors (repeatable)
+    #[arg(long, value_enum, value_name = "RULE")]
+    deny: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    file: &'a str,
+    diagnostics: &'a [Diagnostic],
+}
+
+/// Validate the roadmap and print its diagnostics. Fails when any
+/// error-level diagnostic was reported.
+pub fn run(args: Args, context: &Context) -> Result<(), ScaffoldError> {
+    let roadmap = context.load_all(&args.input, args.format)?;
+    let mut config = RuleConfig::default();
+    for &rule in &args.warn {
+        config.set_severity(rule, Severity::Warning);
+    }
+    for &rule in &args.deny {
+        config.set_severity(rule, Severity::Error);
+    }
+    for &rule in &args.disable {
+        config.disable(rule);
+    }
+
+    let diagnostics = validate(&roadmap, &config);
+    match args.report_format {
+        DiagnosticsFormat::Human => print_human(&args.input.to_string(), None, &diagnostics),
+        DiagnosticsFormat::Json => {
+            let report = Report {
+                file: &args.input.to_string(),
+                diagnostics: &diagnostics,
+            };
+            println!("{}", context.json.to_string(&report)?);
+        }
+        DiagnosticsFormat::Sarif => {
+            // Several files have no single one to locate roadmap-wide
+            // diagnostics in.
+            let paths = expand_paths(&args.input.paths)
+                .map_err(|e| ScaffoldError::load(&args.input.to_string(), e))?;
+            let file = match paths.as_slice() {
+                [path] => Some(path.as_str()),
+                _ => None,
+            };
+            let log = validation_log(&diagnostics, file);
+            println!("{}", context.json.to_string(&log)?);
+        }
+    }
+    annotate(context, &args.input.to_string(), &diagnostics);
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    context.outputs(&[
+        ("valid", (errors == 0).to_string()),
+        ("error_count", errors.to_string()),
+        ("warning_count", (diagnostics.len() - errors).to_string()),
+    ])?;
+    check(&args.input.to_string(), &diagnostics)
+}
+
+/// With `--github-output`, annotate the run with `diagnostics` about the
+/// roadmap `file`.
+pub fn annotate(context: &Context, file: &str, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        context.annotate(Annotation::diagnostic(diagnostic, file));
+    }
+}
+
+/// Fail with a validation error when `diagnostics` contains errors.
+pub fn check(file: &str, diagnostics: &[Diagnostic]) -> Result<(), ScaffoldError> {
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors == 0 {
+        return Ok(());
+    }
+    Err(ScaffoldError::Validation(format!(
+        "{} has {} validation error(s)",
+        file, errors
+    )))
+}
+
+/// Print `diagnostics` about the roadmap `file` followed by their counts.
+/// Located diagnostics quote the roadmap: `content` when given, the
+/// file's current text otherwise.
+pub fn print_human(file: &str, content: Option<&str>, diagnostics: &[Diagnostic]) {
+    let color = use_color();
+    let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+    for d in diagnostics {
+        let path = d.file.as_deref().unwrap_or(file);
+        let source = match (content, &d.file, d.span) {
+            (_, _, None) => None,
+            (Some(content), None, _) => Some(content.to_string()),
+            _ => sources
+                .entry(path)
+                .or_insert_with(|| read_source(path).ok().map(|source| source.to_string()))
+                .clone(),
+        };
+        print!("{}", Snippet::validation(d, path, source).render(color));
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    println!("{}: {} error(s), {} warning(s)", file, errors, warnings);
+}
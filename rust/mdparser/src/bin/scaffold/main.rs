@@ -0,0 +1,364 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process;
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+
+use gitscaffold_parser::{Cache, Config};
+use mdparser::actions::{Annotation, Level};
+use mdparser::diagnostics::ColorChoice;
+use mdparser::error::{exit_on_usage, ErrorFormat, FailurePolicy, ScaffoldError};
+use mdparser::logging::LogArgs;
+use mdparser::output::JsonArgs;
+use mdparser::state::State;
+use mdparser::vars::VarArgs;
+
+mod commands;
+mod style;
+
+/// Roadmap tooling for gitscaffold.
+#[derive(Parser)]
+#[command(name = "scaffold", author, version, about, long_about = None)]
+struct Cli {
+    /// Config file; defaults to the nearest `.gitscaffold.toml` in the
+    /// current directory or its ancestors
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Always parse roadmaps instead of reusing results cached in
+    /// `.gitscaffold/cache/`
+    #[arg(long, global = true)]
+    no_cache: bool,
+    /// Fail on roadmap anomalies such as unknown sections or misspelt
+    /// metadata keys instead of ignoring them
+    #[arg(long, global = true)]
+    strict: bool,
+    /// File mapping roadmap milestones and features to their numbers on
+    /// the forge
+    #[arg(long, global = true, value_name = "FILE", default_value = State::DEFAULT_PATH)]
+    state: PathBuf,
+    /// How to print errors on stderr
+    #[arg(long, value_enum, global = true, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+    /// When to color output and errors
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    #[command(flatten)]
+    json: JsonArgs,
+    #[command(flatten)]
+    failure: FailurePolicy,
+    #[command(flatten)]
+    vars: VarArgs,
+    #[command(flatten)]
+    log: LogArgs,
+    /// In GitHub Actions, write the results of `sync`, `apply` and
+    /// `validate` to the step outputs in `$GITHUB_OUTPUT` and annotate the
+    /// run with their problems
+    #[arg(long, global = true)]
+    github_output: bool,
+    /// Print the reference of every command and option as Markdown
+    #[arg(long, exclusive = true)]
+    help_markdown: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Cli {
+    /// The global options as given, for the `scaffold` commands a command
+    /// runs in turn, such as `serve` does `sync`.
+    fn global_args(&self) -> Vec<OsString> {
+        fn name(value: impl ValueEnum) -> OsString {
+            value
+                .to_possible_value()
+                .map_or_else(OsString::new, |value| value.get_name().into())
+        }
+        let mut args: Vec<OsString> = Vec::new();
+        if let Some(config) = &self.config {
+            args.extend(["--config".into(), config.into()]);
+        }
+        for (key, value) in &self.vars.vars {
+            args.extend(["--var".into(), format!("{}={}", key, value).into()]);
+        }
+        if let Some(vars_file) = &self.vars.vars_file {
+            args.extend(["--vars-file".into(), vars_file.into()]);
+        }
+        let flags = [
+            (self.no_cache, "--no-cache"),
+            (self.strict, "--strict"),
+            (self.failure.keep_going(), "--keep-going"),
+            (self.github_output, "--github-output"),
+            (self.json.pretty, "--pretty"),
+            (self.json.compact, "--compact"),
+            (self.json.sort_keys, "--sort-keys"),
+            (self.log.quiet, "--quiet"),
+        ];
+        args.extend(
+            flags
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, flag)| flag.into()),
+        );
+        if let Some(indent) = self.json.indent {
+            args.extend(["--indent".into(), indent.to_string().into()]);
+        }
+        if self.log.verbose > 0 {
+            args.push(format!("-{}", "v".repeat(self.log.verbose.into())).into());
+        }
+        args.extend([
+            "--state".into(),
+            self.state.clone().into(),
+            "--log-format".into(),
+            name(self.log.log_format),
+            "--error-format".into(),
+            name(self.error_format),
+            "--color".into(),
+            name(self.color),
+        ]);
+        args
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a roadmap against the validation rules
+    Validate(commands::validate::Args),
+    /// Check a Markdown roadmap for style issues, fixing them with `--fix`
+    Lint(commands::lint::Args),
+    /// Install or remove a git pre-commit hook that validates roadmaps
+    Hook(commands::hook::Args),
+    /// Store, remove or check the tokens used to talk to forges
+    Auth(commands::auth::Args),
+    /// Create, rename and report the repository's labels
+    Labels(commands::labels::Args),
+    /// Create GitHub or GitLab milestones and issues from a roadmap
+    Sync(commands::sync::Args),
+    /// Apply a plan saved by `sync --plan-out`
+    Apply(commands::apply::Args),
+    /// Close or delete the milestones and issues a sync run created
+    Rollback(commands::rollback::Args),
+    /// Sync the roadmap whenever a push to GitHub changes it, as a
+    /// webhook server
+    Serve(commands::serve::Args),
+    /// Update a roadmap from the state of its issues
+    Pull(commands::pull::Args),
+    /// Mark features and tasks done from the commits that close them
+    ScanCommits(commands::scan_commits::Args),
+    /// Render a roadmap as canonical Markdown
+    RenderMd(commands::render_md::Args),
+    /// Compare two roadmap files
+    Diff(commands::diff::Args),
+    /// Compare a roadmap against the issues and milestones on the forge
+    DiffRemote(commands::diff_remote::Args),
+    /// Flag features, and issues no feature has, with near-duplicate
+    /// titles
+    Dedupe(commands::dedupe::Args),
+    /// Keep a timestamped copy of the parsed roadmap for `history`
+    Snapshot(commands::snapshot::Args),
+    /// List roadmap snapshots and compare two of them
+    History(commands::history::Args),
+    /// Merge two versions of a roadmap, marking conflicting changes
+    Merge(commands::merge::Args),
+    /// Upgrade roadmap files to the current format version
+    Migrate(commands::migrate::Args),
+    /// Export a roadmap as CSV, TSV or a Jira import file
+    Export(commands::export::Args),
+    /// Show completion statistics per milestone
+    Stats(commands::stats::Args),
+    /// Export the daily open and closed issue counts of a milestone for
+    /// burndown charts
+    Burndown(commands::burndown::Args),
+    /// Flag milestones with unfinished features that are overdue or due
+    /// soon
+    CheckDue(commands::check_due::Args),
+    /// Sum estimates per milestone and flag milestones over capacity
+    Capacity(commands::capacity::Args),
+    /// Draw the feature dependency graph as DOT or Mermaid
+    Graph(commands::graph::Args),
+    /// Draw milestones and features on a timeline as Mermaid or SVG
+    Gantt(commands::gantt::Args),
+    /// Publish a roadmap as a self-contained HTML page
+    Report(commands::report::Args),
+    /// Write a changelog section listing what the roadmap finished since
+    /// a tag or date
+    ReleaseNotes(commands::release_notes::Args),
+    /// Write a starter roadmap, asking for the project's basics
+    Init(commands::init::Args),
+    /// Generate a roadmap from a repository's existing issues and milestones
+    Import(commands::import::Args),
+    /// Keep a comment on each parent issue tracking its sub-issues'
+    /// progress
+    Rollup(commands::rollup::Args),
+    /// Delete or lock a repository's closed issues
+    DeleteClosed(commands::delete_closed::Args),
+    /// Print a shell completion script for bash, zsh, fish, elvish or
+    /// PowerShell
+    Completions(commands::completions::Args),
+    /// Write man pages for scaffold and its subcommands
+    GenMan(commands::gen_man::Args),
+}
+
+#[tokio::main]
+async fn main() {
+    // Exit quietly when a pipe such as `| head` closes early, as other
+    // Unix tools do, rather than panicking on the failed write.
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+    let mut cli = Cli::try_parse().unwrap_or_else(|e| exit_on_usage(e));
+    cli.log.init(cli.color.enabled(&std::io::stderr()));
+    if cli.help_markdown {
+        print!("{}", commands::gen_man::markdown(Cli::command()));
+        return;
+    }
+    let Some(command) = cli.command.take() else {
+        exit_on_usage(Cli::command().error(
+            clap::error::ErrorKind::MissingSubcommand,
+            "a subcommand is required",
+        ));
+    };
+    style::set_color(cli.color);
+    let result = run(command, &cli).await;
+    if let Err(e) = result {
+        e.report(cli.error_format, cli.color.enabled(&std::io::stderr()));
+        if cli.github_output {
+            eprintln!("{}", Annotation::new(Level::Error, e.to_string()));
+        }
+        process::exit(e.exit_code());
+    }
+}
+
+/// Run `command` with the global options in `cli`.
+async fn run(command: Command, cli: &Cli) -> Result<(), ScaffoldError> {
+    // Completions and man pages do not depend on the project, so a broken
+    // config must not get in the way.
+    match command {
+        Command::Completions(args) => return commands::completions::run(args, Cli::command()),
+        Command::GenMan(args) => return commands::gen_man::run(args, Cli::command()),
+        _ => {}
+    }
+    let mut config =
+        Config::load_or_discover(cli.config.as_deref()).map_err(|e| match &cli.config {
+            Some(path) => ScaffoldError::load(&path.display().to_string(), e),
+            None => ScaffoldError::parse(None, e),
+        })?;
+    cli.vars.apply(&mut config)?;
+    let context = commands::Context {
+        config,
+        cache: (!cli.no_cache).then(|| Cache::new(Cache::DEFAULT_DIR)),
+        strict: cli.strict,
+        state: cli.state.clone(),
+        json: cli.json.style(true),
+        failure: cli.failure,
+        github_output: cli.github_output,
+        globals: cli.global_args(),
+    };
+    match command {
+        Command::Validate(args) => commands::validate::run(args, &context),
+        Command::Lint(args) => commands::lint::run(args, &context),
+        Command::Hook(args) => commands::hook::run(args, &context),
+        Command::Auth(args) => commands::auth::run(args, &context),
+        Command::Labels(args) => commands::labels::run(args, &context).await,
+        Command::Sync(args) => commands::sync::run(args, &context).await,
+        Command::Apply(args) => commands::apply::run(args, &context).await,
+        Command::Rollback(args) => commands::rollback::run(args, &context).await,
+        Command::Serve(args) => commands::serve::run(args, &context).await,
+        Command::Pull(args) => commands::pull::run(args, &context).await,
+        Command::ScanCommits(args) => commands::scan_commits::run(args, &context),
+        Command::RenderMd(args) => commands::render_md::run(args, &context),
+        Command::Diff(args) => commands::diff::run(args, &context),
+        Command::DiffRemote(args) => commands::diff_remote::run(args, &context).await,
+        Command::Dedupe(args) => commands::dedupe::run(args, &context).await,
+        Command::Snapshot(args) => commands::snapshot::run(args, &context),
+        Command::History(args) => commands::history::run(args, &context),
+        Command::Merge(args) => commands::merge::run(args, &context),
+        Command::Migrate(args) => commands::migrate::run(args),
+        Command::Export(args) => commands::export::run(args, &context),
+        Command::Stats(args) => commands::stats::run(args, &context),
+        Command::Burndown(args) => commands::burndown::run(args, &context).await,
+        Command::CheckDue(args) => commands::check_due::run(args, &context),
+        Command::Capacity(args) => commands::capacity::run(args, &context),
+        Command::Graph(args) => commands::graph::run(args, &context),
+        Command::Gantt(args) => commands::gantt::run(args, &context),
+        Command::Report(args) => commands::report::run(args, &context),
+        Command::ReleaseNotes(args) => commands::release_notes::run(args, &context).await,
+        Command::Init(args) => commands::init::run(args),
+        Command::Import(args) => commands::import::run(args, &context).await,
+        Command::Rollup(args) => commands::rollup::run(args, &context).await,
+        Command::DeleteClosed(args) => commands::delete_closed::run(args, &context).await,
+        Command::Completions(_) | Command::GenMan(_) => {
+            unreachable!("handled before loading the config")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_global_options_on() {
+        let cli = Cli::try_parse_from([
+            "scaffold",
+            "--config",
+            "other.toml",
+            "--var",
+            "V=1",
+            "--strict",
+            "-vv",
+            "serve",
+            "--secret",
+            "s",
+            "ROADMAP.md",
+        ])
+        .unwrap();
+        let args: Vec<String> = cli
+            .global_args()
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "--config",
+                "other.toml",
+                "--var",
+                "V=1",
+                "--strict",
+                "-vv",
+                "--state",
+                ".gitscaffold/state.json",
+                "--log-format",
+                "text",
+                "--error-format",
+                "human",
+                "--color",
+                "auto",
+            ]
+        );
+        // Parsed again, they give the same options.
+        let again = Cli::try_parse_from(
+            std::iter::once(OsString::from("scaffold"))
+                .chain(cli.global_args())
+                .chain(["validate".into(), "ROADMAP.md".into()]),
+        )
+        .unwrap();
+        assert_eq!(again.global_args(), cli.global_args());
+    }
+
+    #[test]
+    fn notify_needs_update() {
+        let parse =
+            |args: &[&str]| Cli::try_parse_from(["scaffold", "sync", "R.md"].iter().chain(args));
+        assert!(parse(&[]).is_ok());
+        assert!(parse(&["--notify", "comment"]).is_err());
+        assert!(parse(&["--update", "--notify", "comment"]).is_ok());
+    }
+
+    #[test]
+    fn stats_takes_json() {
+        let parse =
+            |args: &[&str]| Cli::try_parse_from(["scaffold", "stats", "R.md"].iter().chain(args));
+        assert!(parse(&["--json"]).is_ok());
+        assert!(parse(&["--json", "--report-format", "human"]).is_err());
+    }
+}
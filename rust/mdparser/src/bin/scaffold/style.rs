@@ -0,0 +1,35 @@
+//! Terminal colors for human-readable output.
+
+use std::sync::OnceLock;
+
+use mdparser::diagnostics::ColorChoice;
+
+static CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+const RESET: &str = "\x1b[0m";
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+
+/// Apply `--color`; called once, before anything is printed.
+pub fn set_color(choice: ColorChoice) {
+    let _ = CHOICE.set(choice);
+}
+
+/// Whether to color stdout: as `--color` says, by default when it is a
+/// terminal and `NO_COLOR` is unset.
+pub fn use_color() -> bool {
+    CHOICE
+        .get()
+        .copied()
+        .unwrap_or_default()
+        .enabled(&std::io::stdout())
+}
+
+pub fn paint(text: &str, color: &str, enabled: bool) -> String {
+    if enabled {
+        format!("{}{}{}", color, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,175 @@
+//! Burndown series: how many of a milestone's issues were open and closed
+//! at the end of each day, replayed from the issues' event logs.
+//!
+//! An issue counts from the day it joins the milestone, whether it was
+//! opened in it or moved there later, until it leaves. Days end at
+//! midnight UTC.
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::github::{IssueEvent, IssueEventKind};
+
+/// The issues of one milestone, day by day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Burndown {
+    pub milestone: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<String>,
+    pub days: Vec<Day>,
+}
+
+/// The milestone's issues at the end of `date`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Day {
+    /// As `YYYY-MM-DD`.
+    pub date: String,
+    pub open: usize,
+    pub closed: usize,
+}
+
+impl Burndown {
+    /// The series of `milestone` from `from` to `to`, inclusive, given the
+    /// event history of each issue that was ever in it. `from` defaults to
+    /// the day the first issue joined the milestone; without any, the
+    /// series is empty.
+    pub fn new(
+        milestone: &str,
+        histories: &[Vec<IssueEvent>],
+        from: Option<NaiveDate>,
+        to: NaiveDate,
+    ) -> Self {
+        let histories: Vec<Vec<(DateTime<Utc>, &IssueEventKind)>> = histories
+            .iter()
+            .map(|events| {
+                let mut timed: Vec<_> = events
+                    .iter()
+                    .filter_map(|event| {
+                        let at = DateTime::parse_from_rfc3339(&event.at).ok()?;
+                        Some((at.with_timezone(&Utc), &event.kind))
+                    })
+                    .collect();
+                timed.sort_by_key(|(at, _)| *at);
+                timed
+            })
+            .collect();
+        let from = from.or_else(|| {
+            histories
+                .iter()
+                .flatten()
+                .filter_map(|(at, kind)| match kind {
+                    IssueEventKind::Milestoned(title) if title == milestone => {
+                        Some(at.date_naive())
+                    }
+                    _ => None,
+                })
+                .min()
+        });
+
+        let mut days = Vec::new();
+        let mut date = from.unwrap_or(to + Days::new(1));
+        while date <= to {
+            let end = (date + Days::new(1))
+                .and_hms_opt(0, 0, 0)
+                .unwrap_or_default()
+                .and_utc();
+            let mut day = Day {
+                date: date.format("%Y-%m-%d").to_string(),
+                open: 0,
+                closed: 0,
+            };
+            for events in &histories {
+                match state_at(events, milestone, end) {
+                    Some(true) => day.open += 1,
+                    Some(false) => day.closed += 1,
+                    None => {}
+                }
+            }
+            days.push(day);
+            date = date + Days::new(1);
+        }
+        Burndown {
+            milestone: milestone.to_string(),
+            due_on: None,
+            days,
+        }
+    }
+
+    /// The series as CSV with a `date,open,closed` header.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,open,closed\n");
+        for day in &self.days {
+            csv.push_str(&format!("{},{},{}\n", day.date, day.open, day.closed));
+        }
+        csv
+    }
+}
+
+/// Whether the issue with `events` was open just before `end`; `None` when
+/// it was not in `milestone` then.
+fn state_at(
+    events: &[(DateTime<Utc>, &IssueEventKind)],
+    milestone: &str,
+    end: DateTime<Utc>,
+) -> Option<bool> {
+    let (mut open, mut member) = (true, false);
+    for (_, kind) in events.iter().take_while(|(at, _)| *at < end) {
+        match kind {
+            IssueEventKind::Opened | IssueEventKind::Reopened => open = true,
+            IssueEventKind::Closed => open = false,
+            // An issue is in one milestone at a time.
+            IssueEventKind::Milestoned(title) => member = title == milestone,
+            IssueEventKind::Demilestoned(title) if title == milestone => member = false,
+            IssueEventKind::Demilestoned(_) => {}
+        }
+    }
+    member.then_some(open)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(at: &str, kind: IssueEventKind) -> IssueEvent {
+        IssueEvent {
+            at: format!("{}T12:00:00Z", at),
+            kind,
+        }
+    }
+
+    #[test]
+    fn counts_open_and_closed_issues_per_day() {
+        let m2 = || IssueEventKind::Milestoned("M2".into());
+        let histories = vec![
+            vec![
+                event("2024-06-01", IssueEventKind::Opened),
+                event("2024-06-01", m2()),
+                event("2024-06-02", IssueEventKind::Closed),
+            ],
+            // Moved into the milestone later, closed, then reopened.
+            vec![
+                event("2024-05-20", IssueEventKind::Opened),
+                event("2024-06-02", m2()),
+                event("2024-06-03", IssueEventKind::Closed),
+                event("2024-06-04", IssueEventKind::Reopened),
+            ],
+            // Moved out again.
+            vec![
+                event("2024-06-01", IssueEventKind::Opened),
+                event("2024-06-01", m2()),
+                event("2024-06-03", IssueEventKind::Demilestoned("M2".into())),
+            ],
+        ];
+        let to = NaiveDate::from_ymd_opt(2024, 6, 4).unwrap();
+        let burndown = Burndown::new("M2", &histories, None, to);
+        assert_eq!(
+            burndown.to_csv(),
+            "date,open,closed\n\
+             2024-06-01,2,0\n\
+             2024-06-02,2,1\n\
+             2024-06-03,0,2\n\
+             2024-06-04,1,1\n"
+        );
+        assert!(Burndown::new("M9", &histories, None, to).days.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+//! Changelog sections rendered from release notes.
+//!
+//! `scaffold release-notes` renders [`ReleaseNotes`] through the minijinja
+//! template `release_notes.j2`, or one of the user's, which sees:
+//!
+//! - `version`: the release's name, e.g. `v1.3.0` or `Unreleased`;
+//! - `date`: the release date as `YYYY-MM-DD`, if any;
+//! - `since`: the tag or date the notes start at;
+//! - `milestones`: the notes, each with a `name` and `groups` of `items`
+//!   sharing a `label`; an item has a `title`, the `feature` a task belongs
+//!   to and the feature's `issue` (`number`, `url`).
+//!
+//! [`insert`] adds the section to a `CHANGELOG.md`, above the newest
+//! release's.
+
+use minijinja::{context, Environment};
+
+use gitscaffold_parser::release::ReleaseNotes;
+
+use crate::template::TemplateError;
+
+const DEFAULT_TEMPLATE: &str = include_str!("../templates/release_notes.j2");
+
+/// What the changelog section is about.
+#[derive(Debug, Clone, Default)]
+pub struct Release<'a> {
+    pub version: &'a str,
+    pub date: Option<String>,
+    pub since: Option<&'a str>,
+}
+
+/// Render the changelog section of `notes` through `template`, or the
+/// built-in one. The section ends with exactly one newline.
+pub fn render(
+    notes: &ReleaseNotes,
+    release: &Release,
+    template: Option<&str>,
+) -> Result<String, TemplateError> {
+    let mut env = Environment::new();
+    env.add_template("release_notes.j2", template.unwrap_or(DEFAULT_TEMPLATE))?;
+    let text = env.get_template("release_notes.j2")?.render(context! {
+        version => release.version,
+        date => release.date,
+        since => release.since,
+        milestones => notes.milestones,
+    })?;
+    Ok(format!("{}\n", text.trim_end()))
+}
+
+/// `changelog` with `section` added above its first `## ` heading, the
+/// newest release's, or at its end when there is none. An empty changelog
+/// starts with a `# Changelog` title.
+pub fn insert(changelog: &str, section: &str) -> String {
+    if changelog.trim().is_empty() {
+        return format!("# Changelog\n\n{}", section);
+    }
+    let mut offset = 0;
+    for line in changelog.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            return format!(
+                "{}{}\n{}",
+                &changelog[..offset],
+                section,
+                &changelog[offset..]
+            );
+        }
+        offset += line.len();
+    }
+    format!("{}\n\n{}", changelog.trim_end(), section)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::parse_markdown;
+    use gitscaffold_parser::release::shipped_since;
+
+    #[test]
+    fn renders_sections_and_inserts_them_above_the_last_release() {
+        let roadmap = parse_markdown(
+            "# App\n\n## Milestones\n\n- **v1**\n\n## Features\n\n\
+             ### Login [done] [#4](https://example.com/4)\n\nMilestone: v1\nLabels: auth\n\n\
+             ### Search\n\nMilestone: v1\n\n- [x] Index\n- [ ] Query\n\n\
+             ### Import [done]\n",
+        )
+        .unwrap();
+        let release = Release {
+            version: "v1.1.0",
+            date: Some("2026-10-15".into()),
+            since: Some("v1.0.0"),
+        };
+        let section = render(&shipped_since(&roadmap, None), &release, None).unwrap();
+        assert_eq!(
+            section,
+            "## v1.1.0 - 2026-10-15\n\n\
+             ### v1\n\n\
+             #### auth\n\n\
+             - Login ([#4](https://example.com/4))\n\n\
+             #### Other\n\n\
+             - Search: Index\n\n\
+             ### Unscheduled\n\n\
+             - Import\n"
+        );
+        assert_eq!(
+            render(
+                &ReleaseNotes::default(),
+                &release,
+                Some("{{ version }} since {{ since }}")
+            )
+            .unwrap(),
+            "v1.1.0 since v1.0.0\n"
+        );
+
+        let changelog = "# Changelog\n\nAll notable changes.\n\n## v1.0.0\n\n- First\n";
+        assert_eq!(
+            insert(changelog, "## v1.1.0\n\n- Next\n"),
+            "# Changelog\n\nAll notable changes.\n\n## v1.1.0\n\n- Next\n\n## v1.0.0\n\n- First\n"
+        );
+        assert_eq!(insert("# Changelog\n", "## v1\n"), "# Changelog\n\n## v1\n");
+        assert_eq!(insert("", "## v1\n"), "# Changelog\n\n## v1\n");
+    }
+}
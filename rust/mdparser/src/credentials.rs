@@ -0,0 +1,172 @@
+//! Where forge tokens come from.
+//!
+//! [`resolve`] tries, in order: the `--token` flag; the forge's environment
+//! variables ([`env_vars`]); for GitHub, the token the `gh` CLI keeps in
+//! its `hosts.yml`; and last the OS keyring (macOS Keychain, Windows
+//! Credential Manager, the Secret Service on Linux), where
+//! `scaffold auth login` stores tokens under the service
+//! [`KEYRING_SERVICE`] and the forge's host name.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use gitscaffold_parser::ForgeKind;
+
+pub use keyring::Error as KeyringError;
+
+/// The keyring service tokens are stored under.
+pub const KEYRING_SERVICE: &str = "gitscaffold";
+
+/// Where a token was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Flag,
+    Env(&'static str),
+    /// The `hosts.yml` of the `gh` CLI.
+    GhCli(PathBuf),
+    Keyring,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Flag => f.write_str("--token"),
+            Source::Env(var) => write!(f, "the {} environment variable", var),
+            Source::GhCli(path) => write!(f, "the gh CLI config ({})", path.display()),
+            Source::Keyring => f.write_str("the OS keyring"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credential {
+    pub token: String,
+    pub source: Source,
+}
+
+impl Credential {
+    /// The token with all but its last four characters hidden, for display.
+    pub fn masked(&self) -> String {
+        let chars: Vec<char> = self.token.chars().collect();
+        let shown = if chars.len() > 8 { 4 } else { 0 };
+        let tail: String = chars[chars.len() - shown..].iter().collect();
+        format!("{}{}", "*".repeat(chars.len().min(12) - shown), tail)
+    }
+}
+
+/// The environment variables holding a token for `kind`, in the order
+/// they are tried.
+pub fn env_vars(kind: ForgeKind) -> &'static [&'static str] {
+    match kind {
+        ForgeKind::GitHub => &["GITHUB_TOKEN", "GH_TOKEN"],
+        ForgeKind::GitLab => &["GITLAB_TOKEN"],
+        ForgeKind::Gitea => &["GITEA_TOKEN"],
+    }
+}
+
+/// The host a token is for, from the forge's API URL: `github.com` for
+/// `https://api.github.com`, otherwise the URL's host.
+pub fn host(api_url: &str) -> String {
+    let rest = api_url.split_once("://").map_or(api_url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = host.rsplit('@').next().unwrap_or(host).to_lowercase();
+    match host.as_str() {
+        "api.github.com" => "github.com".into(),
+        _ => host,
+    }
+}
+
+/// The first token found for `host` on a `kind` forge, given the `--token`
+/// flag.
+pub fn resolve(kind: ForgeKind, host: &str, flag: Option<&str>) -> Option<Credential> {
+    if let Some(token) = flag.filter(|token| !token.is_empty()) {
+        return Some(Credential {
+            token: token.to_string(),
+            source: Source::Flag,
+        });
+    }
+    for var in env_vars(kind) {
+        if let Some(token) = std::env::var(var).ok().filter(|token| !token.is_empty()) {
+            return Some(Credential {
+                token,
+                source: Source::Env(var),
+            });
+        }
+    }
+    if kind == ForgeKind::GitHub {
+        if let Some((token, path)) = gh_cli(host) {
+            return Some(Credential {
+                token,
+                source: Source::GhCli(path),
+            });
+        }
+    }
+    // A missing or locked keyring is no different from an empty one here.
+    let token = keyring::Entry::new(KEYRING_SERVICE, host)
+        .and_then(|entry| entry.get_password())
+        .ok()?;
+    Some(Credential {
+        token,
+        source: Source::Keyring,
+    })
+}
+
+/// Store `token` for `host` in the OS keyring.
+pub fn store(host: &str, token: &str) -> Result<(), KeyringError> {
+    keyring::Entry::new(KEYRING_SERVICE, host)?.set_password(token)
+}
+
+/// Remove the token for `host` from the OS keyring; `false` when there was
+/// none.
+pub fn delete(host: &str) -> Result<bool, KeyringError> {
+    match keyring::Entry::new(KEYRING_SERVICE, host)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(KeyringError::NoEntry) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct GhHost {
+    oauth_token: Option<String>,
+}
+
+/// The token the `gh` CLI keeps for `host`, and the file it is in. Newer
+/// versions of `gh` keep tokens in the keyring instead, under their own
+/// service.
+fn gh_cli(host: &str) -> Option<(String, PathBuf)> {
+    let dir = match std::env::var_os("GH_CONFIG_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(config) => PathBuf::from(config).join("gh"),
+            None if cfg!(windows) => PathBuf::from(std::env::var_os("APPDATA")?).join("GitHub CLI"),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config/gh"),
+        },
+    };
+    let path = dir.join("hosts.yml");
+    let hosts: std::collections::BTreeMap<String, GhHost> =
+        serde_yaml::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+    let token = hosts.get(host)?.oauth_token.clone()?;
+    (!token.is_empty()).then_some((token, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_come_from_api_urls() {
+        assert_eq!(host("https://api.github.com"), "github.com");
+        assert_eq!(host("https://GHE.example.com/api/v3"), "ghe.example.com");
+        assert_eq!(host("https://gitlab.com/api/v4"), "gitlab.com");
+        assert_eq!(host("http://localhost:3000/api/v1"), "localhost:3000");
+        let credential = Credential {
+            token: "ghp_abcdefghijkl".into(),
+            source: Source::Flag,
+        };
+        assert_eq!(credential.masked(), "********ijkl");
+    }
+}
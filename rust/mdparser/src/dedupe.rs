@@ -0,0 +1,227 @@
+//! Suspected duplicates among a roadmap's features and a repository's
+//! issues.
+//!
+//! Sync gives each feature the issue titled exactly like it, or takes a
+//! similar title for a renamed feature. Titles that are alike but not
+//! equal can also be the same work written down twice: two features, or a
+//! feature and an issue opened by hand. [`dedupe`] scores every such pair
+//! with [`similarity`] and groups the pairs that score at least the
+//! threshold into clusters, to review before syncing.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::Serialize;
+
+use gitscaffold_parser::text::normalize;
+use gitscaffold_parser::Roadmap;
+
+use crate::fuzzy::similarity;
+use crate::github::RemoteIssue;
+use crate::template::IssueText;
+
+/// A roadmap feature or a repository issue in a [`Cluster`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    Feature {
+        title: String,
+    },
+    Issue {
+        number: u64,
+        title: String,
+        state: String,
+    },
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Feature { title } => write!(f, "feature '{}'", title),
+            Entry::Issue {
+                number,
+                title,
+                state,
+            } => {
+                write!(f, "issue #{} '{}'", number, title)?;
+                match state.as_str() {
+                    "closed" => write!(f, " (closed)"),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Two entries of a cluster, by their index in it, and how alike their
+/// titles are.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pair {
+    pub a: usize,
+    pub b: usize,
+    pub score: f64,
+}
+
+/// Entries linked by pairs scoring at least the threshold, directly or
+/// through other entries: features first in roadmap order, then issues by
+/// number. Pairs are listed from the most alike.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cluster {
+    pub entries: Vec<Entry>,
+    pub pairs: Vec<Pair>,
+}
+
+impl Cluster {
+    /// The score of the most alike pair.
+    pub fn score(&self) -> f64 {
+        self.pairs.first().map_or(0.0, |pair| pair.score)
+    }
+}
+
+/// The clusters of suspected duplicates among the top-level features of
+/// `roadmap` and the `issues` no feature has, most alike first. `texts`
+/// holds each feature's rendered issue title, as for [`crate::sync::plan`];
+/// an issue is a feature's own when it is titled exactly like that, and is
+/// then left out. Features are compared by title, and with issues by the
+/// better of title and rendered title, as sync matches renamed features.
+pub fn dedupe(
+    roadmap: &Roadmap,
+    texts: &[IssueText],
+    issues: &[RemoteIssue],
+    threshold: f64,
+) -> Vec<Cluster> {
+    let titles: HashSet<String> = texts.iter().map(|text| normalize(&text.title)).collect();
+    let mut issues: Vec<&RemoteIssue> = issues
+        .iter()
+        .filter(|issue| issue.pull_request.is_none() && !titles.contains(&normalize(&issue.title)))
+        .collect();
+    issues.sort_by_key(|issue| issue.number);
+    let features: Vec<(&str, &str)> = roadmap
+        .features
+        .iter()
+        .zip(texts)
+        .map(|(feature, text)| (feature.title.as_str(), text.title.as_str()))
+        .collect();
+
+    // Entries are features, then issues; pairs index into them.
+    let mut pairs = Vec::new();
+    for (i, (title, _)) in features.iter().enumerate() {
+        for (j, (other, _)) in features.iter().enumerate().skip(i + 1) {
+            pairs.push((i, j, similarity(title, other)));
+        }
+    }
+    for (i, (title, rendered)) in features.iter().enumerate() {
+        for (j, issue) in issues.iter().enumerate() {
+            let score = similarity(rendered, &issue.title).max(similarity(title, &issue.title));
+            pairs.push((i, features.len() + j, score));
+        }
+    }
+    pairs.retain(|&(_, _, score)| score >= threshold);
+
+    let mut group: Vec<usize> = (0..features.len() + issues.len()).collect();
+    fn root(group: &mut [usize], mut entry: usize) -> usize {
+        while group[entry] != entry {
+            group[entry] = group[group[entry]];
+            entry = group[entry];
+        }
+        entry
+    }
+    for &(a, b, _) in &pairs {
+        let (a, b) = (root(&mut group, a), root(&mut group, b));
+        group[a.max(b)] = a.min(b);
+    }
+
+    let entry = |index: usize| match index.checked_sub(features.len()) {
+        None => Entry::Feature {
+            title: features[index].0.to_string(),
+        },
+        Some(j) => Entry::Issue {
+            number: issues[j].number,
+            title: issues[j].title.clone(),
+            state: issues[j].state.clone(),
+        },
+    };
+    let mut clusters: Vec<(usize, Vec<usize>, Cluster)> = Vec::new();
+    pairs.sort_by(|x, y| y.2.total_cmp(&x.2));
+    for (a, b, score) in pairs {
+        let id = root(&mut group, a);
+        let position = match clusters.iter().position(|(other, ..)| *other == id) {
+            Some(position) => position,
+            None => {
+                let members: Vec<usize> = (0..group.len())
+                    .filter(|&index| root(&mut group, index) == id)
+                    .collect();
+                let cluster = Cluster {
+                    entries: members.iter().map(|&index| entry(index)).collect(),
+                    pairs: Vec::new(),
+                };
+                clusters.push((id, members, cluster));
+                clusters.len() - 1
+            }
+        };
+        let (_, members, cluster) = &mut clusters[position];
+        let at = |index| members.iter().position(|&m| m == index).unwrap_or(0);
+        cluster.pairs.push(Pair {
+            a: at(a),
+            b: at(b),
+            score,
+        });
+    }
+    clusters.into_iter().map(|(.., cluster)| cluster).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::parse_markdown;
+
+    #[test]
+    fn clusters_similar_features_and_unclaimed_issues() {
+        let roadmap = parse_markdown(
+            "# P\n\n## Features\n\n### Login\n\n### OAuth login\n\n### Export to CSV\n\n\
+             ### CSV: export to\n\n### Search\n",
+        )
+        .unwrap();
+        let texts: Vec<IssueText> = roadmap
+            .features
+            .iter()
+            .map(|feature| IssueText {
+                title: feature.title.clone(),
+                body: String::new(),
+            })
+            .collect();
+        let issues: Vec<RemoteIssue> = serde_json::from_value(serde_json::json!([
+            {"number": 1, "title": "Search", "state": "open"},
+            {"number": 2, "title": "OAuth2 login", "state": "closed"},
+            {"number": 3, "title": "Dark mode", "state": "open"},
+        ]))
+        .unwrap();
+        let clusters = dedupe(&roadmap, &texts, &issues, 0.8);
+        assert_eq!(clusters.len(), 2);
+
+        assert_eq!(clusters[0].score(), 1.0);
+        assert_eq!(
+            clusters[0].entries,
+            [
+                Entry::Feature {
+                    title: "Export to CSV".into()
+                },
+                Entry::Feature {
+                    title: "CSV: export to".into()
+                },
+            ]
+        );
+
+        let cluster = &clusters[1];
+        assert_eq!(
+            cluster.entries[1],
+            Entry::Issue {
+                number: 2,
+                title: "OAuth2 login".into(),
+                state: "closed".into(),
+            }
+        );
+        assert_eq!((cluster.pairs[0].a, cluster.pairs[0].b), (0, 1));
+        assert!(cluster.score() > 0.8 && cluster.score() < 1.0);
+    }
+}
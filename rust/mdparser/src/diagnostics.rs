@@ -0,0 +1,207 @@
+//! How the binaries show errors and validation diagnostics on terminals.
+//!
+//! A [`Snippet`] is rendered with miette: a validation diagnostic or a
+//! parse error quotes the roadmap lines it is about, underlines the
+//! offending text with a label saying what is wrong there, and ends with
+//! help on fixing it. Colors follow [`ColorChoice`].
+
+use std::fmt;
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+use miette::{GraphicalReportHandler, GraphicalTheme, LabeledSpan, NamedSource, SourceSpan};
+
+use gitscaffold_parser::validate::{Diagnostic, Severity};
+use gitscaffold_parser::Span;
+
+/// When to color terminal output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// When writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color what is written to `stream`.
+    pub fn enabled(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none() && stream.is_terminal(),
+        }
+    }
+}
+
+/// An error or diagnostic, possibly pointing into the file it is about.
+#[derive(Debug)]
+pub struct Snippet {
+    message: String,
+    code: Option<String>,
+    severity: miette::Severity,
+    help: Option<String>,
+    source: Option<NamedSource<String>>,
+    labels: Vec<LabeledSpan>,
+}
+
+impl Snippet {
+    /// An error `message` without a location.
+    pub fn error(message: impl Into<String>) -> Snippet {
+        Snippet {
+            message: message.into(),
+            code: None,
+            severity: miette::Severity::Error,
+            help: None,
+            source: None,
+            labels: Vec::new(),
+        }
+    }
+
+    /// The validation `diagnostic` about the roadmap at `path`, quoting
+    /// `source`, the file's text, when it is known and the diagnostic has
+    /// a location.
+    pub fn validation(diagnostic: &Diagnostic, path: &str, source: Option<String>) -> Snippet {
+        let mut snippet = Snippet::error(format!(
+            "{}: {}",
+            location(path, diagnostic.line, diagnostic.column),
+            diagnostic.message
+        ));
+        snippet.code = Some(format!("{}({})", diagnostic.code, diagnostic.rule.name()));
+        snippet.severity = match diagnostic.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+        };
+        snippet.help = Some(diagnostic.rule.help().to_string());
+        if let (Some(source), Some(span)) = (source, diagnostic.span) {
+            if let Some(range) = offending(&source, span, diagnostic.subject.as_deref()) {
+                let label = diagnostic.rule.label().to_string();
+                snippet
+                    .labels
+                    .push(LabeledSpan::new_with_span(Some(label), range));
+                snippet.source = Some(NamedSource::new(path, source));
+            }
+        }
+        snippet
+    }
+
+    /// Point at `offset` in `source`, the text of the file at `path`,
+    /// with `label`.
+    pub fn at(mut self, path: &str, source: String, offset: usize, label: &str) -> Snippet {
+        if offset <= source.len() && source.is_char_boundary(offset) {
+            let len = source[offset..].chars().next().map_or(0, char::len_utf8);
+            let label = LabeledSpan::new_with_span(Some(label.to_string()), (offset, len));
+            self.labels.push(label);
+            self.source = Some(NamedSource::new(path, source));
+        }
+        self
+    }
+
+    /// The snippet as printed on a terminal, colored when `color` is set.
+    pub fn render(&self, color: bool) -> String {
+        let theme = if color {
+            GraphicalTheme::unicode()
+        } else {
+            GraphicalTheme::unicode_nocolor()
+        };
+        let mut out = String::new();
+        // Rendering into a String cannot fail.
+        let _ = GraphicalReportHandler::new_themed(theme).render_report(&mut out, self);
+        out
+    }
+}
+
+/// `path:line:column`, or as much of it as is known.
+fn location(path: &str, line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path, line, column),
+        _ => path.to_string(),
+    }
+}
+
+/// The byte range to underline in `source` for an entity spanning `span`:
+/// the first occurrence of `subject` in it, or else the entity's first
+/// line. `None` when `span` does not fit `source`, e.g. because the file
+/// changed since it was parsed.
+fn offending(source: &str, span: Span, subject: Option<&str>) -> Option<SourceSpan> {
+    let text = source.get(span.start.offset..span.end.offset)?;
+    let line = source[..span.start.offset].matches('\n').count() + 1;
+    if line != span.start.line {
+        return None;
+    }
+    let (start, len) = match subject.filter(|s| !s.is_empty()).and_then(|s| text.find(s)) {
+        Some(start) => (start, subject.map_or(0, str::len)),
+        None => (0, text.lines().next().unwrap_or("").trim_end().len()),
+    };
+    Some((span.start.offset + start, len).into())
+}
+
+impl fmt::Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Snippet {}
+
+impl miette::Diagnostic for Snippet {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.code
+            .as_ref()
+            .map(|code| Box::new(code) as Box<dyn fmt::Display>)
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(self.severity)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|help| Box::new(help) as Box<dyn fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.source
+            .as_ref()
+            .map(|source| source as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            return None;
+        }
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::parse_markdown;
+    use gitscaffold_parser::validate::{validate, RuleConfig};
+
+    #[test]
+    fn underlines_the_offending_text() {
+        let source = "# App\n\n## Milestones\n- **v1**\n\n## Features\n\n### Login\nSign in.\nMilestone: v2\n";
+        let roadmap = parse_markdown(source).unwrap();
+        let diagnostic = validate(&roadmap, &RuleConfig::default())
+            .into_iter()
+            .find(|d| d.code == "GS005")
+            .unwrap();
+        let snippet = Snippet::validation(&diagnostic, "ROADMAP.md", Some(source.to_string()));
+        let rendered = snippet.render(false);
+        assert!(rendered.contains("GS005(milestone-references)"));
+        assert!(rendered.contains("ROADMAP.md:8:1: feature 'Login' references undefined"));
+        assert!(rendered.contains("Milestone: v2"));
+        assert!(rendered.contains("milestone referenced here was never defined"));
+        assert!(rendered.contains("help: define the milestone"));
+        assert!(!rendered.contains("\x1b["));
+        assert!(snippet.render(true).contains("\x1b["));
+
+        // A file that changed since it was parsed is not quoted.
+        let stale = Snippet::validation(&diagnostic, "ROADMAP.md", Some("# App\n".into()));
+        assert!(!stale.render(false).contains("never defined"));
+    }
+}
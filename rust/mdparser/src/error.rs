@@ -0,0 +1,382 @@
+//! The error type of the `mdparser` and `scaffold` binaries and how it is
+//! reported: each category exits with its own status so scripts can tell a
+//! missing file from an invalid roadmap or a GitHub outage.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::process;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use gitscaffold_parser::ParseError;
+
+use crate::diagnostics::Snippet;
+use crate::github::GitHubError;
+
+/// How errors are printed on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// The message, quoting the offending lines of a malformed file
+    #[default]
+    Human,
+    /// One JSON object per error
+    Json,
+}
+
+/// The exit status of usage errors, whether clap or a command found them.
+pub const USAGE_EXIT_CODE: i32 = 6;
+
+/// Print a command-line parsing error and exit like [`clap::Error::exit`]
+/// does, but with [`USAGE_EXIT_CODE`]: clap's own status 2 means a parse
+/// error here. `--help` and `--version` still exit with 0.
+pub fn exit_on_usage(error: clap::Error) -> ! {
+    let _ = error.print();
+    process::exit(match error.use_stderr() {
+        true => USAGE_EXIT_CODE,
+        false => 0,
+    })
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ScaffoldError {
+    /// A file could not be read or written.
+    Io { path: String, source: io::Error },
+    /// A roadmap or config file could not be parsed. The path is unknown
+    /// for a discovered config file.
+    Parse {
+        path: Option<String>,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// The roadmap has error-level diagnostics.
+    Validation(String),
+    /// Output could not be serialized.
+    Serialization(Box<dyn std::error::Error + Send + Sync>),
+    /// A GitHub API request failed.
+    GitHub { repo: String, source: GitHubError },
+    /// The arguments cannot be used together.
+    Usage(String),
+    /// Several inputs or operations failed under `--keep-going`; reported
+    /// one by one, then with `summary`.
+    Multiple {
+        summary: String,
+        errors: Vec<ScaffoldError>,
+    },
+    /// A sync applied some operations before others failed, leaving the
+    /// forge partly updated.
+    PartialSync(String),
+    /// The run was stopped by Ctrl-C or SIGINT.
+    Interrupted,
+}
+
+impl ScaffoldError {
+    /// Wrap an error from loading the roadmap at `path`, keeping I/O
+    /// failures apart from malformed content.
+    pub fn load(path: &str, error: ParseError) -> Self {
+        match error {
+            ParseError::Io(source) => ScaffoldError::Io {
+                path: path.to_string(),
+                source,
+            },
+            source => ScaffoldError::parse(Some(path), source),
+        }
+    }
+
+    pub fn parse(
+        path: Option<&str>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        ScaffoldError::Parse {
+            path: path.map(str::to_string),
+            source: source.into(),
+        }
+    }
+
+    pub fn io(path: &str, source: io::Error) -> Self {
+        ScaffoldError::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    pub fn github(repo: &str, source: GitHubError) -> Self {
+        ScaffoldError::GitHub {
+            repo: repo.to_string(),
+            source,
+        }
+    }
+
+    /// The error category, as printed by `--error-format json`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScaffoldError::Io { .. } => "io",
+            ScaffoldError::Parse { .. } => "parse",
+            ScaffoldError::Validation(_) => "validation",
+            ScaffoldError::Serialization(_) => "serialization",
+            ScaffoldError::GitHub { .. } => "github",
+            ScaffoldError::Usage(_) => "usage",
+            ScaffoldError::Multiple { .. } => "multiple",
+            ScaffoldError::PartialSync(_) => "partial_sync",
+            ScaffoldError::Interrupted => "interrupted",
+        }
+    }
+
+    /// The process exit status for this error: 1 for validation failures,
+    /// 2 for parse errors, 3 for I/O, 4 for API failures and 5 for a sync
+    /// that got part way, then 6 for usage errors, clap's own included
+    /// (see [`exit_on_usage`]), and 7 for serialization. Several errors
+    /// exit with the highest of their statuses, and an interrupted run with
+    /// the shell's 130.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ScaffoldError::Validation(_) => 1,
+            ScaffoldError::Parse { .. } => 2,
+            ScaffoldError::Io { .. } => 3,
+            ScaffoldError::GitHub { .. } => 4,
+            ScaffoldError::PartialSync(_) => 5,
+            ScaffoldError::Usage(_) => USAGE_EXIT_CODE,
+            ScaffoldError::Serialization(_) => 7,
+            ScaffoldError::Multiple { errors, .. } => errors
+                .iter()
+                .map(ScaffoldError::exit_code)
+                .max()
+                .unwrap_or(1),
+            ScaffoldError::Interrupted => 130,
+        }
+    }
+
+    /// Print the error on stderr in `format`, in color when `color` is
+    /// set and the format is human-readable.
+    pub fn report(&self, format: ErrorFormat, color: bool) {
+        if let ScaffoldError::Multiple { errors, .. } = self {
+            for error in errors {
+                error.report(format, color);
+            }
+        }
+        match format {
+            ErrorFormat::Human => eprint!("{}", self.snippet().render(color)),
+            ErrorFormat::Json => {
+                let path = match self {
+                    ScaffoldError::Io { path, .. } => Some(path.as_str()),
+                    ScaffoldError::Parse { path, .. } => path.as_deref(),
+                    _ => None,
+                };
+                let report = Report {
+                    kind: self.kind(),
+                    exit_code: self.exit_code(),
+                    message: self.to_string(),
+                    path,
+                };
+                // Serializing plain strings and numbers cannot fail.
+                eprintln!("{}", serde_json::to_string(&report).unwrap_or_default());
+            }
+        }
+    }
+
+    /// The error for rendering; a file that failed to parse at a known
+    /// place is quoted there.
+    fn snippet(&self) -> Snippet {
+        let snippet = Snippet::error(self.to_string());
+        let ScaffoldError::Parse {
+            path: Some(path),
+            source,
+        } = self
+        else {
+            return snippet;
+        };
+        let yaml = match source.downcast_ref::<ParseError>() {
+            Some(ParseError::Yaml(e)) => Some(e),
+            Some(_) => None,
+            None => source.downcast_ref::<serde_yaml::Error>(),
+        };
+        let (offset, line) = match source.downcast_ref::<ParseError>() {
+            Some(ParseError::Config(e)) => (e.span().map(|span| span.start), None),
+            Some(ParseError::UndefinedVariable { offset, .. }) => (Some(*offset), None),
+            Some(ParseError::Strict(warnings)) => {
+                let span = warnings.first().and_then(|warning| warning.span);
+                (span.map(|span| span.start.offset), None)
+            }
+            _ => match yaml.and_then(serde_yaml::Error::location) {
+                Some(location) => (Some(location.index()), Some(location.line())),
+                None => (None, None),
+            },
+        };
+        let (Some(offset), Ok(text)) = (offset, fs::read_to_string(path)) else {
+            return snippet;
+        };
+        // YAML frontmatter is parsed apart from the Markdown around it, so
+        // its locations are only good when they land on the line reported.
+        let at = text
+            .get(..offset)
+            .map(|before| before.matches('\n').count() + 1);
+        if line.is_some() && at != line {
+            return snippet;
+        }
+        snippet.at(path, text, offset, "here")
+    }
+}
+
+/// What to do about an error while there are more files to read or
+/// issues to create.
+#[derive(Debug, Clone, Copy, Default, clap::Args)]
+pub struct FailurePolicy {
+    /// Stop at the first failing file or operation (the default)
+    #[arg(long, global = true, overrides_with = "keep_going")]
+    fail_fast: bool,
+    /// Carry on past failing files and operations, then report them all
+    /// and exit with an error
+    #[arg(long, global = true, overrides_with = "fail_fast")]
+    keep_going: bool,
+}
+
+impl FailurePolicy {
+    pub fn keep_going(&self) -> bool {
+        self.keep_going
+    }
+
+    /// Apply `f` to each item in order. Failing fast, the first error is
+    /// returned as is; keeping going, every item is tried and the errors
+    /// are returned together, summarized as failed `what` (e.g. `files`).
+    pub fn map<T, U>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        what: &str,
+        mut f: impl FnMut(T) -> Result<U, ScaffoldError>,
+    ) -> Result<Vec<U>, ScaffoldError> {
+        let mut done = Vec::new();
+        let mut errors = Vec::new();
+        let mut total = 0;
+        for item in items {
+            total += 1;
+            match f(item) {
+                Ok(value) => done.push(value),
+                Err(error) if !self.keep_going => return Err(error),
+                Err(error) => errors.push(error),
+            }
+        }
+        match errors.len() {
+            0 => Ok(done),
+            1 => Err(errors.remove(0)),
+            n => Err(ScaffoldError::Multiple {
+                summary: format!("{} of {} {} failed", n, total, what),
+                errors,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    kind: &'a str,
+    exit_code: i32,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaffoldError::Io { path, source } => write!(f, "{}: {}", path, source),
+            ScaffoldError::Parse {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {}", path, source),
+            ScaffoldError::Parse { path: None, source } => write!(f, "{}", source),
+            ScaffoldError::Validation(message) => f.write_str(message),
+            ScaffoldError::Serialization(e) => write!(f, "cannot serialize output: {}", e),
+            ScaffoldError::GitHub { repo, source } => write!(f, "{}: {}", repo, source),
+            ScaffoldError::Usage(message) | ScaffoldError::PartialSync(message) => {
+                f.write_str(message)
+            }
+            ScaffoldError::Multiple { summary, .. } => f.write_str(summary),
+            ScaffoldError::Interrupted => f.write_str("interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for ScaffoldError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScaffoldError::Io { source, .. } => Some(source),
+            ScaffoldError::Parse { source, .. } => Some(source.as_ref()),
+            ScaffoldError::Serialization(e) => Some(e.as_ref()),
+            ScaffoldError::GitHub { source, .. } => Some(source),
+            ScaffoldError::Validation(_)
+            | ScaffoldError::Usage(_)
+            | ScaffoldError::Multiple { .. }
+            | ScaffoldError::PartialSync(_)
+            | ScaffoldError::Interrupted => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ScaffoldError {
+    fn from(e: serde_json::Error) -> Self {
+        ScaffoldError::Serialization(Box::new(e))
+    }
+}
+
+impl From<serde_yaml::Error> for ScaffoldError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ScaffoldError::Serialization(Box::new(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_load_errors_into_io_and_parse() {
+        let missing = io::Error::new(io::ErrorKind::NotFound, "missing");
+        let error = ScaffoldError::load("a.md", ParseError::Io(missing));
+        assert_eq!((error.kind(), error.exit_code()), ("io", 3));
+        assert_eq!(error.to_string(), "a.md: missing");
+
+        let yaml = serde_yaml::from_str::<u32>("[").unwrap_err();
+        let error = ScaffoldError::load("a.yml", ParseError::Yaml(yaml));
+        assert_eq!((error.kind(), error.exit_code()), ("parse", 2));
+
+        let partial = ScaffoldError::PartialSync("acme/app: 1 operation(s) failed".into());
+        assert_eq!((partial.kind(), partial.exit_code()), ("partial_sync", 5));
+    }
+
+    #[test]
+    fn keeping_going_collects_every_error() {
+        let parse = |n: &&str| {
+            n.parse::<u32>()
+                .map_err(|e| ScaffoldError::parse(Some(n), e))
+        };
+        let items = ["1", "x", "2", "y"];
+        let fail_fast = FailurePolicy::default();
+        let error = fail_fast.map(&items, "files", parse).unwrap_err();
+        assert_eq!(error.to_string(), "x: invalid digit found in string");
+
+        let keep_going = FailurePolicy {
+            keep_going: true,
+            ..FailurePolicy::default()
+        };
+        let error = keep_going.map(&items, "files", parse).unwrap_err();
+        assert_eq!(error.to_string(), "2 of 4 files failed");
+        assert_eq!((error.kind(), error.exit_code()), ("multiple", 2));
+        assert_eq!(keep_going.map(&items[..1], "files", parse).unwrap(), [1]);
+    }
+
+    #[test]
+    fn json_report_carries_kind_and_exit_code() {
+        let error = ScaffoldError::Usage("--stream takes a single file".into());
+        let report = Report {
+            kind: error.kind(),
+            exit_code: error.exit_code(),
+            message: error.to_string(),
+            path: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"kind":"usage","exit_code":6,"message":"--stream takes a single file"}"#
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! The Markdown event stream of a roadmap, printed by `mdparser --events`.
+//!
+//! Events are a typed model of pulldown-cmark's, independent of its
+//! version: `{"type":"start","tag":{"kind":"heading","level":2}}`. Their
+//! shape is part of the output contract, versioned by [`EVENTS_VERSION`]
+//! and pinned by a checked-in snapshot. Consecutive text is merged into one
+//! event, and a code block comes as a single `code_block` event holding its
+//! code.
+//!
+//! Events are produced as the parser goes, so `--ndjson` can print a large
+//! roadmap line by line.
+
+use std::mem;
+
+use pulldown_cmark as md;
+use serde::Serialize;
+
+use gitscaffold_parser::extract;
+
+/// The version of the event model; raised whenever an event or tag
+/// changes shape.
+pub const EVENTS_VERSION: u32 = 1;
+
+/// The `--events` array, with the version of the events it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Document {
+    pub version: u32,
+    pub events: Vec<Event>,
+}
+
+/// The first line of `--events --ndjson`, before the events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename = "stream")]
+pub struct Header {
+    pub version: u32,
+}
+
+/// One Markdown event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Start {
+        tag: Tag,
+    },
+    End {
+        tag: Tag,
+    },
+    Text {
+        text: String,
+    },
+    /// Inline code.
+    Code {
+        text: String,
+    },
+    Html {
+        text: String,
+    },
+    CodeBlock {
+        /// The info string of a fenced code block, e.g. `rust`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        text: String,
+    },
+    FootnoteReference {
+        label: String,
+    },
+    SoftBreak,
+    HardBreak,
+    Rule,
+    TaskListMarker {
+        checked: bool,
+    },
+}
+
+/// The element a `start` or `end` event opens or closes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Tag {
+    Paragraph,
+    Heading {
+        level: u8,
+        /// The heading's `{#id}`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    BlockQuote,
+    List {
+        ordered: bool,
+        /// The first number of a numbered list.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        start: Option<u64>,
+    },
+    Item,
+    FootnoteDefinition {
+        label: String,
+    },
+    Table,
+    TableHead,
+    TableRow,
+    TableCell,
+    Emphasis,
+    Strong,
+    Strikethrough,
+    Link {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
+    Image {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
+}
+
+/// The events of `content`, parsed with the options the roadmap extractor
+/// uses.
+pub fn events(content: &str) -> Events<'_> {
+    Events {
+        parser: md::Parser::new_ext(content, extract::options()),
+        text: String::new(),
+        queued: None,
+    }
+}
+
+/// Iterator returned by [`events`].
+pub struct Events<'a> {
+    parser: md::Parser<'a, 'a>,
+    /// Text read but not yet emitted, to merge with the text that follows.
+    text: String,
+    /// The event after the merged text.
+    queued: Option<Event>,
+}
+
+impl Iterator for Events<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if let Some(event) = self.queued.take() {
+            return Some(event);
+        }
+        loop {
+            let Some(event) = self.parser.next() else {
+                return (!self.text.is_empty()).then(|| self.take_text());
+            };
+            if let md::Event::Text(text) = &event {
+                self.text.push_str(text);
+                continue;
+            }
+            let event = self.convert(event);
+            if self.text.is_empty() {
+                return Some(event);
+            }
+            self.queued = Some(event);
+            return Some(self.take_text());
+        }
+    }
+}
+
+impl Events<'_> {
+    fn take_text(&mut self) -> Event {
+        Event::Text {
+            text: mem::take(&mut self.text),
+        }
+    }
+
+    fn convert(&mut self, event: md::Event) -> Event {
+        match event {
+            md::Event::Start(md::Tag::CodeBlock(kind)) => {
+                let mut text = String::new();
+                for event in self.parser.by_ref() {
+                    match event {
+                        md::Event::Text(code) => text.push_str(&code),
+                        md::Event::End(md::Tag::CodeBlock(_)) => break,
+                        _ => {}
+                    }
+                }
+                let language = match kind {
+                    md::CodeBlockKind::Fenced(info) if !info.is_empty() => Some(info.to_string()),
+                    _ => None,
+                };
+                Event::CodeBlock { language, text }
+            }
+            md::Event::Start(tag) => Event::Start { tag: tag.into() },
+            md::Event::End(tag) => Event::End { tag: tag.into() },
+            md::Event::Text(text) => Event::Text {
+                text: text.to_string(),
+            },
+            md::Event::Code(text) => Event::Code {
+                text: text.to_string(),
+            },
+            md::Event::Html(html) => Event::Html {
+                text: html.to_string(),
+            },
+            md::Event::FootnoteReference(label) => Event::FootnoteReference {
+                label: label.to_string(),
+            },
+            md::Event::SoftBreak => Event::SoftBreak,
+            md::Event::HardBreak => Event::HardBreak,
+            md::Event::Rule => Event::Rule,
+            md::Event::TaskListMarker(checked) => Event::TaskListMarker { checked },
+        }
+    }
+}
+
+impl From<md::Tag<'_>> for Tag {
+    fn from(tag: md::Tag) -> Self {
+        let title = |title: md::CowStr| (!title.is_empty()).then(|| title.to_string());
+        match tag {
+            md::Tag::Paragraph => Tag::Paragraph,
+            md::Tag::Heading(level, id, _) => Tag::Heading {
+                level: level as u8,
+                id: id.map(str::to_string),
+            },
+            md::Tag::BlockQuote => Tag::BlockQuote,
+            md::Tag::CodeBlock(_) => unreachable!("code blocks are folded into one event"),
+            md::Tag::List(start) => Tag::List {
+                ordered: start.is_some(),
+                start,
+            },
+            md::Tag::Item => Tag::Item,
+            md::Tag::FootnoteDefinition(label) => Tag::FootnoteDefinition {
+                label: label.to_string(),
+            },
+            md::Tag::Table(_) => Tag::Table,
+            md::Tag::TableHead => Tag::TableHead,
+            md::Tag::TableRow => Tag::TableRow,
+            md::Tag::TableCell => Tag::TableCell,
+            md::Tag::Emphasis => Tag::Emphasis,
+            md::Tag::Strong => Tag::Strong,
+            md::Tag::Strikethrough => Tag::Strikethrough,
+            md::Tag::Link(_, url, text) => Tag::Link {
+                url: url.to_string(),
+                title: title(text),
+            },
+            md::Tag::Image(_, url, text) => Tag::Image {
+                url: url.to_string(),
+                title: title(text),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The checked-in snapshot pins the shape of every event and tag;
+    /// after a deliberate change, raise [`EVENTS_VERSION`] and regenerate
+    /// it with `mdparser --events --pretty snapshots/events.md >
+    /// snapshots/events.json` from the `mdparser` directory.
+    #[test]
+    fn events_match_the_checked_in_snapshot() {
+        let document = Document {
+            version: EVENTS_VERSION,
+            events: events(include_str!("../snapshots/events.md")).collect(),
+        };
+        let json = serde_json::to_string_pretty(&document).unwrap();
+        assert_eq!(json.trim(), include_str!("../snapshots/events.json").trim());
+    }
+}
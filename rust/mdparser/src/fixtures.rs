@@ -0,0 +1,290 @@
+//! Recording forge API responses to disk and replaying them.
+//!
+//! A [`Fixtures`] attached to a client's transport either records: every
+//! response the forge sends is written to `fixtures.json` in its
+//! directory, along with the request it answers; or replays: requests are
+//! answered from that file and never reach the network. Requests match a
+//! recorded one by method, path with query and JSON body, each recording
+//! being used once, so concurrent requests replay fine in any order. The
+//! host is not part of the match and credentials are never recorded.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+use gitscaffold_parser::ForgeKind;
+
+use crate::github::GitHubError;
+
+/// The file in the fixtures directory holding the recording.
+pub const FILE: &str = "fixtures.json";
+
+/// Response headers worth replaying: pagination and rate limits.
+const HEADERS: &[&str] = &[
+    "link",
+    "x-next-page",
+    "x-total-pages",
+    "x-total-count",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+];
+
+/// What the recording was made against, so a replay can talk to it the
+/// same way.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recorded {
+    pub forge: ForgeKind,
+    pub repo: String,
+    pub api_url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Recording {
+    #[serde(flatten)]
+    recorded: Recorded,
+    interactions: Vec<Interaction>,
+}
+
+/// A request and the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    /// The URL's path and query.
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<serde_json::Value>,
+    status: u16,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    headers: BTreeMap<String, String>,
+    /// The response body, `null` when it was empty.
+    #[serde(default)]
+    response: serde_json::Value,
+}
+
+/// The parts of a request that pick its recording.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Key {
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+}
+
+impl Key {
+    /// The key of `request`; `None` for requests that cannot be copied,
+    /// which the clients never send.
+    pub(crate) fn of(request: &RequestBuilder) -> Option<Key> {
+        let request = request.try_clone()?.build().ok()?;
+        let url = request.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .and_then(|bytes| serde_json::from_slice(bytes).ok());
+        Some(Key {
+            method: request.method().to_string(),
+            path,
+            body,
+        })
+    }
+
+    fn matches(&self, interaction: &Interaction) -> bool {
+        self.method == interaction.method
+            && self.path == interaction.path
+            && self.body == interaction.body
+    }
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record,
+    /// Which recorded interactions were replayed already.
+    Replay(Vec<bool>),
+}
+
+/// A fixtures directory being recorded into or replayed from.
+#[derive(Debug)]
+pub struct Fixtures {
+    file: PathBuf,
+    state: Mutex<(Recording, Mode)>,
+}
+
+impl Fixtures {
+    /// Start recording into `dir`, replacing any earlier recording.
+    pub fn record(dir: &Path, recorded: Recorded) -> std::io::Result<Fixtures> {
+        fs::create_dir_all(dir)?;
+        let fixtures = Fixtures {
+            file: dir.join(FILE),
+            state: Mutex::new((
+                Recording {
+                    recorded,
+                    interactions: Vec::new(),
+                },
+                Mode::Record,
+            )),
+        };
+        fixtures.save()?;
+        Ok(fixtures)
+    }
+
+    /// Replay the recording in `dir`.
+    pub fn replay(dir: &Path) -> std::io::Result<Fixtures> {
+        let file = dir.join(FILE);
+        let recording: Recording = serde_json::from_str(&fs::read_to_string(&file)?)?;
+        let used = vec![false; recording.interactions.len()];
+        Ok(Fixtures {
+            file,
+            state: Mutex::new((recording, Mode::Replay(used))),
+        })
+    }
+
+    /// The forge, repository and API URL the recording was made against.
+    pub fn recorded(&self) -> Recorded {
+        self.lock().0.recorded.clone()
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.lock().1, Mode::Replay(_))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, (Recording, Mode)> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.lock().0)?;
+        fs::write(&self.file, json + "\n")
+    }
+
+    /// The recorded response to the request with `key`.
+    pub(crate) fn answer(&self, key: &Key) -> Result<Response, GitHubError> {
+        let mut state = self.lock();
+        let (recording, Mode::Replay(used)) = &mut *state else {
+            unreachable!("only replays answer requests");
+        };
+        let index = recording
+            .interactions
+            .iter()
+            .zip(used.iter())
+            .position(|(interaction, used)| !used && key.matches(interaction))
+            .ok_or_else(|| {
+                GitHubError::Fixture(format!(
+                    "no recorded response to {} {} in {}",
+                    key.method,
+                    key.path,
+                    self.file.display()
+                ))
+            })?;
+        used[index] = true;
+        response(&recording.interactions[index])
+    }
+
+    /// Record `response` as the answer to the request with `key`, and hand
+    /// it on.
+    pub(crate) async fn store(
+        &self,
+        key: Key,
+        response: Response,
+    ) -> Result<Response, GitHubError> {
+        let status = response.status().as_u16();
+        let headers = HEADERS
+            .iter()
+            .filter_map(|&name| {
+                let value = response.headers().get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+        let bytes = response.bytes().await?;
+        let interaction = Interaction {
+            method: key.method,
+            path: key.path,
+            body: key.body,
+            status,
+            headers,
+            response: serde_json::from_slice(&bytes).unwrap_or_default(),
+        };
+        let replayed = self::response(&interaction)?;
+        self.lock().0.interactions.push(interaction);
+        self.save().map_err(|e| {
+            GitHubError::Fixture(format!("cannot write {}: {}", self.file.display(), e))
+        })?;
+        Ok(replayed)
+    }
+}
+
+/// The response `interaction` recorded.
+fn response(interaction: &Interaction) -> Result<Response, GitHubError> {
+    let mut builder = http::Response::builder().status(interaction.status);
+    for (name, value) in &interaction.headers {
+        builder = builder.header(name, value);
+    }
+    let body = match &interaction.response {
+        serde_json::Value::Null => String::new(),
+        json => json.to_string(),
+    };
+    builder
+        .body(body)
+        .map(Response::from)
+        .map_err(|e| GitHubError::Fixture(format!("invalid recorded response: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_recorded_responses_once_each() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-fixtures-{}", std::process::id()));
+        let recorded = Recorded {
+            forge: ForgeKind::GitHub,
+            repo: "acme/app".into(),
+            api_url: "https://api.github.com".into(),
+        };
+        let client = reqwest::Client::new();
+        let request = client
+            .post("https://api.github.com/repos/acme/app/issues?x=1")
+            .bearer_auth("secret")
+            .json(&serde_json::json!({ "title": "Login" }));
+        let key = Key::of(&request).unwrap();
+        let fixtures = Fixtures::record(&dir, recorded.clone()).unwrap();
+        let answer = http::Response::builder()
+            .status(201)
+            .header("link", "<next>")
+            .header("set-cookie", "session")
+            .body(r#"{"number": 7}"#)
+            .unwrap();
+        let stored = futures::executor::block_on(fixtures.store(key.clone(), answer.into()));
+        assert_eq!(stored.unwrap().status(), 201);
+        assert!(!fs::read_to_string(dir.join(FILE))
+            .unwrap()
+            .contains("secret"));
+
+        let fixtures = Fixtures::replay(&dir).unwrap();
+        assert!(fixtures.is_replaying());
+        assert_eq!(fixtures.recorded(), recorded);
+        let other = Key::of(
+            &client
+                .post("http://localhost/repos/acme/app/issues?x=1")
+                .json(&serde_json::json!({ "title": "Login" })),
+        )
+        .unwrap();
+        let response = fixtures.answer(&other).unwrap();
+        assert_eq!(response.status(), 201);
+        assert_eq!(response.headers()["link"], "<next>");
+        assert!(response.headers().get("set-cookie").is_none());
+        let body = futures::executor::block_on(response.json::<serde_json::Value>()).unwrap();
+        assert_eq!(body["number"], 7);
+        assert!(matches!(
+            fixtures.answer(&key),
+            Err(GitHubError::Fixture(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
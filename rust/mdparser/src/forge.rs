@@ -0,0 +1,393 @@
+//! The issue trackers ("forges") sync, import and cleanup talk to.
+//!
+//! [`Forge`] covers the milestone and issue operations the commands need,
+//! in GitHub's terms: issues are numbered per repository and milestones
+//! carry a `due_on` timestamp. Each client maps its own API onto that, so
+//! planning and reporting stay the same whichever forge is used.
+
+use std::future::Future;
+
+use gitscaffold_parser::ForgeKind;
+
+use crate::fixtures::Fixtures;
+use crate::gitea::Gitea;
+use crate::github::{
+    GitHub, GitHubError, IssueEvent, IssueUpdate, LabelUpdate, MilestoneUpdate, NewIssue, NewLabel,
+    NewMilestone, RemoteComment, RemoteIssue, RemoteLabel, RemoteMilestone, RemoteUser,
+};
+use crate::gitlab::GitLab;
+
+pub trait Forge: Send + Sync {
+    /// The forge's name for messages, e.g. `GitHub`.
+    fn name(&self) -> &'static str;
+
+    /// The repository (or project) path the client works on.
+    fn repo(&self) -> &str;
+
+    /// All milestones, open and closed.
+    fn list_milestones(
+        &self,
+    ) -> impl Future<Output = Result<Vec<RemoteMilestone>, GitHubError>> + Send;
+
+    /// All issues, open and closed, excluding pull or merge requests.
+    fn list_issues(&self) -> impl Future<Output = Result<Vec<RemoteIssue>, GitHubError>> + Send;
+
+    fn create_milestone(
+        &self,
+        milestone: &NewMilestone<'_>,
+    ) -> impl Future<Output = Result<RemoteMilestone, GitHubError>> + Send;
+
+    /// Change the fields of an existing milestone that `update` sets.
+    fn update_milestone(
+        &self,
+        number: u64,
+        update: &MilestoneUpdate<'_>,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    fn create_issue(
+        &self,
+        issue: &NewIssue<'_>,
+    ) -> impl Future<Output = Result<RemoteIssue, GitHubError>> + Send;
+
+    /// Create several issues, returning the result of each in order. Forges
+    /// that can create issues in bulk do so in as few requests as they can;
+    /// the others create them one by one.
+    fn create_issues(
+        &self,
+        issues: &[NewIssue<'_>],
+    ) -> impl Future<Output = Vec<Result<RemoteIssue, GitHubError>>> + Send {
+        async move {
+            let mut created = Vec::with_capacity(issues.len());
+            for issue in issues {
+                created.push(self.create_issue(issue).await);
+            }
+            created
+        }
+    }
+
+    /// Change the title of an issue.
+    fn rename_issue(
+        &self,
+        number: u64,
+        title: &str,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Change the body, labels, assignees or milestone of an issue.
+    fn update_issue(
+        &self,
+        number: u64,
+        update: &IssueUpdate<'_>,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Make issue `child` a sub-issue of issue `parent`, where the forge
+    /// has sub-issues.
+    fn add_sub_issue(
+        &self,
+        parent: u64,
+        child: u64,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Leave a comment on an issue.
+    fn comment_on_issue(
+        &self,
+        number: u64,
+        comment: &str,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// All comments on an issue, oldest first.
+    fn list_comments(
+        &self,
+        number: u64,
+    ) -> impl Future<Output = Result<Vec<RemoteComment>, GitHubError>> + Send;
+
+    /// Replace the text of comment `id` on issue `number`.
+    fn update_comment(
+        &self,
+        number: u64,
+        id: u64,
+        body: &str,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Close an issue, leaving `comment` on it first.
+    fn close_issue(
+        &self,
+        number: u64,
+        comment: &str,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Permanently delete an issue; needs admin rights on the repository.
+    fn delete_issue(
+        &self,
+        issue: &RemoteIssue,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Lock an issue's conversation so only members can comment.
+    fn lock_issue(&self, number: u64) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Commit `content` to the repository as the file at `path`, on
+    /// `branch` or the default branch, unless a file is there already, and
+    /// return the URL it is served at for issues to link to.
+    fn upload_file(
+        &self,
+        path: &str,
+        content: &[u8],
+        branch: Option<&str>,
+    ) -> impl Future<Output = Result<String, GitHubError>> + Send;
+
+    /// All labels of the repository, with their colors and descriptions.
+    fn list_labels(&self) -> impl Future<Output = Result<Vec<RemoteLabel>, GitHubError>> + Send;
+
+    fn create_label(
+        &self,
+        label: &NewLabel<'_>,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// Rename the label called `name` or change its color or description.
+    fn update_label(
+        &self,
+        name: &str,
+        update: &LabelUpdate<'_>,
+    ) -> impl Future<Output = Result<(), GitHubError>> + Send;
+
+    /// The users that issues in the repository can be assigned to.
+    fn list_assignees(&self) -> impl Future<Output = Result<Vec<RemoteUser>, GitHubError>> + Send;
+
+    /// When `issue` was opened, closed, reopened and moved between
+    /// milestones, oldest first. Forges whose event log is not read fall
+    /// back to [`RemoteIssue::inferred_events`].
+    fn issue_events(
+        &self,
+        issue: &RemoteIssue,
+    ) -> impl Future<Output = Result<Vec<IssueEvent>, GitHubError>> + Send {
+        let events = issue.inferred_events();
+        async move { Ok(events) }
+    }
+}
+
+/// A forge chosen at runtime, e.g. with `--forge`.
+pub enum AnyForge {
+    GitHub(GitHub),
+    GitLab(GitLab),
+    Gitea(Gitea),
+}
+
+impl AnyForge {
+    pub fn kind(&self) -> ForgeKind {
+        match self {
+            AnyForge::GitHub(_) => ForgeKind::GitHub,
+            AnyForge::GitLab(_) => ForgeKind::GitLab,
+            AnyForge::Gitea(_) => ForgeKind::Gitea,
+        }
+    }
+
+    /// Record the client's requests into `fixtures`, or answer them from
+    /// it.
+    pub fn with_fixtures(self, fixtures: Fixtures) -> AnyForge {
+        match self {
+            AnyForge::GitHub(forge) => AnyForge::GitHub(forge.with_fixtures(fixtures)),
+            AnyForge::GitLab(forge) => AnyForge::GitLab(forge.with_fixtures(fixtures)),
+            AnyForge::Gitea(forge) => AnyForge::Gitea(forge.with_fixtures(fixtures)),
+        }
+    }
+}
+
+impl Forge for AnyForge {
+    fn name(&self) -> &'static str {
+        match self {
+            AnyForge::GitHub(forge) => forge.name(),
+            AnyForge::GitLab(forge) => forge.name(),
+            AnyForge::Gitea(forge) => forge.name(),
+        }
+    }
+
+    fn repo(&self) -> &str {
+        match self {
+            AnyForge::GitHub(forge) => forge.repo(),
+            AnyForge::GitLab(forge) => forge.repo(),
+            AnyForge::Gitea(forge) => forge.repo(),
+        }
+    }
+
+    async fn list_milestones(&self) -> Result<Vec<RemoteMilestone>, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.list_milestones().await,
+            AnyForge::GitLab(forge) => forge.list_milestones().await,
+            AnyForge::Gitea(forge) => forge.list_milestones().await,
+        }
+    }
+
+    async fn list_issues(&self) -> Result<Vec<RemoteIssue>, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.list_issues().await,
+            AnyForge::GitLab(forge) => forge.list_issues().await,
+            AnyForge::Gitea(forge) => forge.list_issues().await,
+        }
+    }
+
+    async fn create_milestone(
+        &self,
+        milestone: &NewMilestone<'_>,
+    ) -> Result<RemoteMilestone, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.create_milestone(milestone).await,
+            AnyForge::GitLab(forge) => forge.create_milestone(milestone).await,
+            AnyForge::Gitea(forge) => forge.create_milestone(milestone).await,
+        }
+    }
+
+    async fn update_milestone(
+        &self,
+        number: u64,
+        update: &MilestoneUpdate<'_>,
+    ) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.update_milestone(number, update).await,
+            AnyForge::GitLab(forge) => forge.update_milestone(number, update).await,
+            AnyForge::Gitea(forge) => forge.update_milestone(number, update).await,
+        }
+    }
+
+    async fn create_issue(&self, issue: &NewIssue<'_>) -> Result<RemoteIssue, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.create_issue(issue).await,
+            AnyForge::GitLab(forge) => forge.create_issue(issue).await,
+            AnyForge::Gitea(forge) => forge.create_issue(issue).await,
+        }
+    }
+
+    async fn create_issues(
+        &self,
+        issues: &[NewIssue<'_>],
+    ) -> Vec<Result<RemoteIssue, GitHubError>> {
+        match self {
+            AnyForge::GitHub(forge) => forge.create_issues(issues).await,
+            AnyForge::GitLab(forge) => forge.create_issues(issues).await,
+            AnyForge::Gitea(forge) => forge.create_issues(issues).await,
+        }
+    }
+
+    async fn rename_issue(&self, number: u64, title: &str) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.rename_issue(number, title).await,
+            AnyForge::GitLab(forge) => forge.rename_issue(number, title).await,
+            AnyForge::Gitea(forge) => forge.rename_issue(number, title).await,
+        }
+    }
+
+    async fn update_issue(&self, number: u64, update: &IssueUpdate<'_>) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.update_issue(number, update).await,
+            AnyForge::GitLab(forge) => forge.update_issue(number, update).await,
+            AnyForge::Gitea(forge) => forge.update_issue(number, update).await,
+        }
+    }
+
+    async fn add_sub_issue(&self, parent: u64, child: u64) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.add_sub_issue(parent, child).await,
+            AnyForge::GitLab(forge) => forge.add_sub_issue(parent, child).await,
+            AnyForge::Gitea(forge) => forge.add_sub_issue(parent, child).await,
+        }
+    }
+
+    async fn comment_on_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.comment_on_issue(number, comment).await,
+            AnyForge::GitLab(forge) => forge.comment_on_issue(number, comment).await,
+            AnyForge::Gitea(forge) => forge.comment_on_issue(number, comment).await,
+        }
+    }
+
+    async fn list_comments(&self, number: u64) -> Result<Vec<RemoteComment>, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.list_comments(number).await,
+            AnyForge::GitLab(forge) => forge.list_comments(number).await,
+            AnyForge::Gitea(forge) => forge.list_comments(number).await,
+        }
+    }
+
+    async fn update_comment(&self, number: u64, id: u64, body: &str) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.update_comment(number, id, body).await,
+            AnyForge::GitLab(forge) => forge.update_comment(number, id, body).await,
+            AnyForge::Gitea(forge) => forge.update_comment(number, id, body).await,
+        }
+    }
+
+    async fn close_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.close_issue(number, comment).await,
+            AnyForge::GitLab(forge) => forge.close_issue(number, comment).await,
+            AnyForge::Gitea(forge) => forge.close_issue(number, comment).await,
+        }
+    }
+
+    async fn delete_issue(&self, issue: &RemoteIssue) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.delete_issue(issue).await,
+            AnyForge::GitLab(forge) => forge.delete_issue(issue).await,
+            AnyForge::Gitea(forge) => forge.delete_issue(issue).await,
+        }
+    }
+
+    async fn lock_issue(&self, number: u64) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.lock_issue(number).await,
+            AnyForge::GitLab(forge) => forge.lock_issue(number).await,
+            AnyForge::Gitea(forge) => forge.lock_issue(number).await,
+        }
+    }
+
+    async fn upload_file(
+        &self,
+        path: &str,
+        content: &[u8],
+        branch: Option<&str>,
+    ) -> Result<String, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.upload_file(path, content, branch).await,
+            AnyForge::GitLab(forge) => forge.upload_file(path, content, branch).await,
+            AnyForge::Gitea(forge) => forge.upload_file(path, content, branch).await,
+        }
+    }
+
+    async fn list_labels(&self) -> Result<Vec<RemoteLabel>, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.list_labels().await,
+            AnyForge::GitLab(forge) => forge.list_labels().await,
+            AnyForge::Gitea(forge) => forge.list_labels().await,
+        }
+    }
+
+    async fn create_label(&self, label: &NewLabel<'_>) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.create_label(label).await,
+            AnyForge::GitLab(forge) => forge.create_label(label).await,
+            AnyForge::Gitea(forge) => forge.create_label(label).await,
+        }
+    }
+
+    async fn update_label(&self, name: &str, update: &LabelUpdate<'_>) -> Result<(), GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.update_label(name, update).await,
+            AnyForge::GitLab(forge) => forge.update_label(name, update).await,
+            AnyForge::Gitea(forge) => forge.update_label(name, update).await,
+        }
+    }
+
+    async fn list_assignees(&self) -> Result<Vec<RemoteUser>, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.list_assignees().await,
+            AnyForge::GitLab(forge) => forge.list_assignees().await,
+            AnyForge::Gitea(forge) => forge.list_assignees().await,
+        }
+    }
+
+    async fn issue_events(&self, issue: &RemoteIssue) -> Result<Vec<IssueEvent>, GitHubError> {
+        match self {
+            AnyForge::GitHub(forge) => forge.issue_events(issue).await,
+            AnyForge::GitLab(forge) => forge.issue_events(issue).await,
+            AnyForge::Gitea(forge) => forge.issue_events(issue).await,
+        }
+    }
+}
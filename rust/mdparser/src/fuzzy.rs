@@ -0,0 +1,42 @@
+//! How alike two titles are, for telling a renamed feature from a new one.
+
+use std::collections::BTreeSet;
+
+/// Similarity of `a` and `b` from 0 (nothing in common) to 1 (equal,
+/// ignoring case and spacing): the better of their normalized Levenshtein
+/// similarity, which forgives typos and small edits, and the overlap of
+/// their word sets, which forgives reordered or added words.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let edits = strsim::normalized_levenshtein(&a.join(" "), &b.join(" "));
+    let (a, b): (BTreeSet<&String>, BTreeSet<&String>) = (a.iter().collect(), b.iter().collect());
+    let tokens = a.intersection(&b).count() as f64 / a.union(&b).count() as f64;
+    edits.max(tokens)
+}
+
+/// The lowercased words of `title`, split on anything but letters and
+/// digits.
+fn words(title: &str) -> Vec<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_edits_and_reordered_words() {
+        assert_eq!(similarity("Login form", " login  FORM"), 1.0);
+        assert!(similarity("Add OAuth login", "Add OAuth2 login") > 0.9);
+        assert_eq!(similarity("Export to CSV", "CSV: export to"), 1.0);
+        assert!(similarity("Search", "Export") < 0.5);
+        assert!(similarity("Dark mode", "Dark mode for the settings page") < 0.8);
+    }
+}
@@ -0,0 +1,501 @@
+//! Gitea and Forgejo REST client (API v1) behind the [`Forge`] trait.
+//!
+//! Gitea's API is modelled on GitHub's, so issues and milestones map over
+//! almost directly. The differences: new issues take label IDs rather than
+//! names, so labels are looked up (and created when missing) first; list
+//! endpoints page with `limit`; empty lists may come back as `null`; and
+//! the API has no way to lock an issue.
+
+use std::collections::HashMap;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use futures::lock::Mutex;
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::fixtures::Fixtures;
+use crate::forge::Forge;
+use crate::github::{
+    GitHubError, IssueUpdate, LabelUpdate, MilestoneUpdate, NewIssue, NewLabel, NewMilestone,
+    RemoteComment, RemoteIssue, RemoteLabel, RemoteMilestone, RemoteUser, RetryPolicy, Transport,
+    Wait,
+};
+
+pub const DEFAULT_API_URL: &str = "https://gitea.com/api/v1";
+
+/// Page size; Gitea's default cap on list responses.
+const LIMIT: usize = 50;
+
+/// The color given to labels created by sync.
+const LABEL_COLOR: &str = "#ededed";
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    id: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    due_on: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+impl From<Milestone> for RemoteMilestone {
+    fn from(milestone: Milestone) -> Self {
+        RemoteMilestone {
+            number: milestone.id,
+            title: milestone.title,
+            state: milestone.state,
+            due_on: milestone.due_on,
+            description: milestone.description,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    id: u64,
+    number: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    closed_at: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    labels: Option<Vec<Label>>,
+    #[serde(default)]
+    assignees: Option<Vec<RemoteUser>>,
+    #[serde(default)]
+    milestone: Option<Milestone>,
+    #[serde(default)]
+    html_url: Option<String>,
+}
+
+impl From<Issue> for RemoteIssue {
+    fn from(issue: Issue) -> Self {
+        RemoteIssue {
+            number: issue.number,
+            node_id: issue.id.to_string(),
+            title: issue.title,
+            state: issue.state,
+            created_at: issue.created_at,
+            closed_at: issue.closed_at,
+            body: issue.body,
+            labels: issue
+                .labels
+                .unwrap_or_default()
+                .into_iter()
+                .map(|label| RemoteLabel {
+                    name: label.name,
+                    ..RemoteLabel::default()
+                })
+                .collect(),
+            assignees: issue.assignees.unwrap_or_default(),
+            milestone: issue.milestone.map(RemoteMilestone::from),
+            html_url: issue.html_url,
+            pull_request: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    id: u64,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct CreateIssue<'a> {
+    title: &'a str,
+    body: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<u64>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    assignees: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct UpdateIssue<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assignees: Option<&'a [String]>,
+    /// 0 removes the milestone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone: Option<u64>,
+}
+
+pub struct Gitea {
+    http: Transport,
+    api_url: String,
+    token: String,
+    repo: String,
+    /// Label IDs by lowercased name, loaded on first use. The async lock
+    /// also keeps concurrent issue creations from creating a label twice.
+    labels: Mutex<Option<HashMap<String, u64>>>,
+}
+
+impl Gitea {
+    /// Create a client for `repo` (`owner/name`) on the instance whose API
+    /// lives at `api_url`, e.g. `https://codeberg.org/api/v1`.
+    pub fn new(api_url: &str, token: &str, repo: &str) -> Result<Self, GitHubError> {
+        Ok(Gitea {
+            http: Transport::new("Gitea")?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            repo: repo.to_string(),
+            labels: Mutex::new(None),
+        })
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.http.set_retry(retry);
+        self
+    }
+
+    /// Record the client's requests into `fixtures`, or answer them from
+    /// it.
+    pub fn with_fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.http.set_fixtures(fixtures);
+        self
+    }
+
+    /// Call `observer` whenever the client sleeps before a request.
+    pub fn on_wait(mut self, observer: impl Fn(&Wait) + Send + Sync + 'static) -> Self {
+        self.http.set_on_wait(Box::new(observer));
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.http
+            .client
+            .request(
+                method,
+                format!("{}/repos/{}/{}", self.api_url, self.repo, path),
+            )
+            .header("Authorization", format!("token {}", self.token))
+    }
+
+    /// Fetch every page of a list endpoint.
+    async fn list<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>, GitHubError> {
+        let mut all = Vec::new();
+        for page in 1.. {
+            let request = self
+                .request(reqwest::Method::GET, path)
+                .query(query)
+                .query(&[("limit", LIMIT.to_string()), ("page", page.to_string())]);
+            let batch: Option<Vec<T>> = self.http.send(request).await?;
+            let batch = batch.unwrap_or_default();
+            let done = batch.len() < LIMIT;
+            all.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(all)
+    }
+
+    /// The IDs of the labels named `names`, creating the missing ones.
+    async fn label_ids(&self, names: &[String]) -> Result<Vec<u64>, GitHubError> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut cached = self.labels.lock().await;
+        if cached.is_none() {
+            let labels: Vec<Label> = self.list("labels", &[]).await?;
+            let by_name = labels
+                .into_iter()
+                .map(|label| (label.name.to_lowercase(), label.id))
+                .collect();
+            *cached = Some(by_name);
+        }
+        let by_name = cached.get_or_insert_with(HashMap::new);
+        let mut ids = Vec::new();
+        for name in names {
+            let key = name.to_lowercase();
+            let id = match by_name.get(&key) {
+                Some(&id) => id,
+                None => {
+                    let body = serde_json::json!({ "name": name, "color": LABEL_COLOR });
+                    let request = self.request(reqwest::Method::POST, "labels").json(&body);
+                    let created: Label = self.http.send(request).await?;
+                    by_name.insert(key, created.id);
+                    created.id
+                }
+            };
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+}
+
+impl Forge for Gitea {
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    async fn list_milestones(&self) -> Result<Vec<RemoteMilestone>, GitHubError> {
+        let milestones: Vec<Milestone> = self.list("milestones", &[("state", "all")]).await?;
+        Ok(milestones.into_iter().map(RemoteMilestone::from).collect())
+    }
+
+    async fn list_issues(&self) -> Result<Vec<RemoteIssue>, GitHubError> {
+        let issues: Vec<Issue> = self
+            .list("issues", &[("state", "all"), ("type", "issues")])
+            .await?;
+        Ok(issues.into_iter().map(RemoteIssue::from).collect())
+    }
+
+    async fn create_milestone(
+        &self,
+        milestone: &NewMilestone<'_>,
+    ) -> Result<RemoteMilestone, GitHubError> {
+        let created: Milestone = self
+            .http
+            .send(
+                self.request(reqwest::Method::POST, "milestones")
+                    .json(milestone),
+            )
+            .await?;
+        Ok(created.into())
+    }
+
+    async fn update_milestone(
+        &self,
+        number: u64,
+        update: &MilestoneUpdate<'_>,
+    ) -> Result<(), GitHubError> {
+        let path = format!("milestones/{}", number);
+        self.http
+            .send_checked(self.request(reqwest::Method::PATCH, &path).json(update))
+            .await?;
+        Ok(())
+    }
+
+    async fn create_issue(&self, issue: &NewIssue<'_>) -> Result<RemoteIssue, GitHubError> {
+        let body = CreateIssue {
+            title: issue.title,
+            body: &issue.body,
+            labels: self.label_ids(&issue.labels).await?,
+            assignees: &issue.assignees,
+            milestone: issue.milestone,
+        };
+        let created: Issue = self
+            .http
+            .send(self.request(reqwest::Method::POST, "issues").json(&body))
+            .await?;
+        Ok(created.into())
+    }
+
+    async fn rename_issue(&self, number: u64, title: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .json(&serde_json::json!({ "title": title })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Labels are replaced through their own endpoint, which takes ids.
+    async fn update_issue(&self, number: u64, update: &IssueUpdate<'_>) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", number);
+        let body = UpdateIssue {
+            body: update.body,
+            assignees: update.assignees,
+            milestone: update.milestone.map(|id| id.unwrap_or(0)),
+        };
+        if body.body.is_some() || body.assignees.is_some() || body.milestone.is_some() {
+            self.http
+                .send_checked(self.request(reqwest::Method::PATCH, &path).json(&body))
+                .await?;
+        }
+        if let Some(labels) = update.labels {
+            let labels = serde_json::json!({ "labels": self.label_ids(labels).await? });
+            let path = format!("{}/labels", path);
+            self.http
+                .send_checked(self.request(reqwest::Method::PUT, &path).json(&labels))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Issues here have no sub-issues; the references in the parent's task
+    /// list are the only link.
+    async fn add_sub_issue(&self, _: u64, _: u64) -> Result<(), GitHubError> {
+        Ok(())
+    }
+
+    async fn comment_on_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}/comments", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::POST, &path)
+                    .json(&serde_json::json!({ "body": comment })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_comments(&self, number: u64) -> Result<Vec<RemoteComment>, GitHubError> {
+        self.list(&format!("issues/{}/comments", number), &[]).await
+    }
+
+    async fn update_comment(&self, _: u64, id: u64, body: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/comments/{}", id);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .json(&serde_json::json!({ "body": body })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn close_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        self.comment_on_issue(number, comment).await?;
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .json(&serde_json::json!({ "state": "closed" })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Permanently delete an issue; needs admin access to the repository.
+    async fn delete_issue(&self, issue: &RemoteIssue) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", issue.number);
+        self.http
+            .send_checked(self.request(reqwest::Method::DELETE, &path))
+            .await?;
+        Ok(())
+    }
+
+    async fn lock_issue(&self, _number: u64) -> Result<(), GitHubError> {
+        Err(GitHubError::Unsupported("Gitea's API cannot lock issues"))
+    }
+
+    /// Files go through the contents API and are linked to by their raw
+    /// download URL.
+    async fn upload_file(
+        &self,
+        path: &str,
+        content: &[u8],
+        branch: Option<&str>,
+    ) -> Result<String, GitHubError> {
+        #[derive(Deserialize)]
+        struct File {
+            download_url: String,
+        }
+        #[derive(Deserialize)]
+        struct Created {
+            content: File,
+        }
+        let contents = format!("contents/{}", path);
+        let mut request = self.request(reqwest::Method::GET, &contents);
+        if let Some(branch) = branch {
+            request = request.query(&[("ref", branch)]);
+        }
+        let file = match self.http.send::<File>(request).await {
+            Ok(file) => file,
+            Err(GitHubError::Api { status: 404, .. }) => {
+                let mut body = serde_json::json!({
+                    "message": format!("Add {}", path),
+                    "content": BASE64_STANDARD.encode(content),
+                });
+                if let Some(branch) = branch {
+                    body["branch"] = branch.into();
+                }
+                let request = self.request(reqwest::Method::POST, &contents).json(&body);
+                let created: Created = self.http.send(request).await?;
+                created.content
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(file.download_url)
+    }
+
+    async fn list_labels(&self) -> Result<Vec<RemoteLabel>, GitHubError> {
+        let labels: Vec<RemoteLabel> = self.list("labels", &[]).await?;
+        Ok(labels
+            .into_iter()
+            .map(|mut label| {
+                label.color = label.color.trim_start_matches('#').to_string();
+                label
+            })
+            .collect())
+    }
+
+    async fn create_label(&self, label: &NewLabel<'_>) -> Result<(), GitHubError> {
+        let request = self.request(reqwest::Method::POST, "labels").json(label);
+        self.http.send_checked(request).await?;
+        // The label IDs cached for new issues are out of date.
+        *self.labels.lock().await = None;
+        Ok(())
+    }
+
+    /// Labels are edited by ID, and renamed through `name`.
+    async fn update_label(&self, name: &str, update: &LabelUpdate<'_>) -> Result<(), GitHubError> {
+        let labels: Vec<Label> = self.list("labels", &[]).await?;
+        let label = labels
+            .into_iter()
+            .find(|label| label.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| GitHubError::Api {
+                status: 404,
+                message: format!("no Gitea label '{}'", name),
+            })?;
+        let body = serde_json::json!({
+            "name": update.new_name.unwrap_or(&label.name),
+            "color": update.color,
+            "description": update.description,
+        });
+        let path = format!("labels/{}", label.id);
+        let request = self.request(reqwest::Method::PATCH, &path).json(&body);
+        self.http.send_checked(request).await?;
+        *self.labels.lock().await = None;
+        Ok(())
+    }
+
+    async fn list_assignees(&self) -> Result<Vec<RemoteUser>, GitHubError> {
+        self.list("assignees", &[]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_gitea_issues_with_null_lists() {
+        let issue: Issue = serde_json::from_str(
+            r#"{"id": 901, "number": 7, "title": "Login", "state": "open",
+                "body": "", "labels": null, "assignees": null,
+                "milestone": {"id": 55, "title": "M1", "state": "open",
+                              "due_on": "2025-06-30T23:59:59Z"}}"#,
+        )
+        .unwrap();
+        let issue = RemoteIssue::from(issue);
+        assert_eq!((issue.number, issue.node_id.as_str()), (7, "901"));
+        assert!(issue.labels.is_empty() && issue.assignees.is_empty());
+        let milestone = issue.milestone.unwrap();
+        assert_eq!(milestone.number, 55);
+        assert_eq!(milestone.due_on.as_deref(), Some("2025-06-30T23:59:59Z"));
+    }
+}
@@ -0,0 +1,1127 @@
+//! Minimal GitHub REST client covering the milestone and issue endpoints
+//! used by `scaffold sync`, and the HTTP transport it shares with the
+//! GitLab and Gitea clients.
+//!
+//! Requests are retried when GitHub rate limits them (403/429) or fails
+//! transiently (502/503/504, connection errors), waiting for as long as the
+//! `Retry-After` or `X-RateLimit-Reset` headers ask, or backing off
+//! exponentially with jitter when they give no hint. When the primary rate
+//! limit is used up, the next request waits for it to reset instead of
+//! failing.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::fixtures::{Fixtures, Key};
+use crate::forge::Forge;
+use crate::github_app::GitHubApp;
+
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+const PER_PAGE: usize = 100;
+
+/// A failed forge API request; GitLab and Gitea requests fail the same
+/// ways.
+#[derive(Debug)]
+pub enum GitHubError {
+    Http(reqwest::Error),
+    Api {
+        status: u16,
+        message: String,
+    },
+    GraphQl(String),
+    /// The forge's API has no way to do what was asked.
+    Unsupported(&'static str),
+    /// No token could be obtained, e.g. for a GitHub App.
+    Auth(String),
+    /// A replayed request was not recorded, or recording failed.
+    Fixture(String),
+}
+
+impl fmt::Display for GitHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitHubError::Http(e) => write!(f, "request failed: {}", e),
+            GitHubError::Api { status, message } => {
+                write!(f, "API returned {}: {}", status, message)
+            }
+            GitHubError::GraphQl(message) => write!(f, "GraphQL query failed: {}", message),
+            GitHubError::Unsupported(what) => write!(f, "not supported: {}", what),
+            GitHubError::Auth(message) | GitHubError::Fixture(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for GitHubError {}
+
+impl From<reqwest::Error> for GitHubError {
+    fn from(e: reqwest::Error) -> Self {
+        GitHubError::Http(e)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteMilestone {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    #[serde(default)]
+    pub due_on: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteIssue {
+    pub number: u64,
+    /// GraphQL node ID, needed for mutations without a REST equivalent.
+    #[serde(default)]
+    pub node_id: String,
+    pub title: String,
+    pub state: String,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub closed_at: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<RemoteLabel>,
+    #[serde(default)]
+    pub assignees: Vec<RemoteUser>,
+    #[serde(default)]
+    pub milestone: Option<RemoteMilestone>,
+    /// The issue's web page.
+    #[serde(default)]
+    pub html_url: Option<String>,
+    /// Present when the "issue" is actually a pull request.
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+impl RemoteIssue {
+    /// The issue's history as far as its fields tell: opened in its
+    /// current milestone, then closed if it is. Reopenings and moves
+    /// between milestones need the forge's event log.
+    pub fn inferred_events(&self) -> Vec<IssueEvent> {
+        let Some(created_at) = &self.created_at else {
+            return Vec::new();
+        };
+        let mut events = vec![IssueEvent {
+            at: created_at.clone(),
+            kind: IssueEventKind::Opened,
+        }];
+        if let Some(milestone) = &self.milestone {
+            events.push(IssueEvent {
+                at: created_at.clone(),
+                kind: IssueEventKind::Milestoned(milestone.title.clone()),
+            });
+        }
+        if let (Some(closed_at), "closed") = (&self.closed_at, self.state.as_str()) {
+            events.push(IssueEvent {
+                at: closed_at.clone(),
+                kind: IssueEventKind::Closed,
+            });
+        }
+        events
+    }
+}
+
+/// Something that happened to an issue, at an RFC 3339 timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueEvent {
+    pub at: String,
+    pub kind: IssueEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueEventKind {
+    Opened,
+    Closed,
+    Reopened,
+    /// Added to the milestone with this title.
+    Milestoned(String),
+    /// Taken out of the milestone with this title.
+    Demilestoned(String),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RemoteLabel {
+    pub name: String,
+    /// As `rrggbb`; only filled in by [`crate::forge::Forge::list_labels`].
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteUser {
+    pub login: String,
+}
+
+/// A comment on an issue.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteComment {
+    pub id: u64,
+    #[serde(default)]
+    pub body: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewMilestone<'a> {
+    pub title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+}
+
+/// Fields of an existing milestone to change; `None` leaves a field alone.
+#[derive(Debug, Default, Serialize)]
+pub struct MilestoneUpdate<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<&'a str>,
+    /// `Some(None)` removes the due date.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<Option<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    /// `closed` to close the milestone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<&'static str>,
+}
+
+/// A label to create; `color` is `rrggbb`.
+#[derive(Debug, Serialize)]
+pub struct NewLabel<'a> {
+    pub name: &'a str,
+    pub color: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+}
+
+/// Fields of an existing label to change; `None` leaves a field alone.
+#[derive(Debug, Default, Serialize)]
+pub struct LabelUpdate<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewIssue<'a> {
+    pub title: &'a str,
+    pub body: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub assignees: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<u64>,
+}
+
+/// Fields of an existing issue to change; `None` leaves a field alone.
+#[derive(Debug, Default, Serialize)]
+pub struct IssueUpdate<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignees: Option<&'a [String]>,
+    /// `Some(None)` removes the issue from its milestone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone: Option<Option<u64>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GraphQlError {
+    pub message: String,
+    /// The field the error belongs to, starting with its top-level alias.
+    #[serde(default)]
+    pub path: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct GraphQlResponse {
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+    #[serde(default)]
+    pub errors: Vec<GraphQlError>,
+}
+
+/// How often and how long to retry rate-limited or failed requests.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for each further one.
+    pub base_delay: Duration,
+    /// Upper bound for any single wait, including rate limit resets.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff for the `attempt`th retry (1-based), with up to
+    /// 50% jitter so parallel clients do not retry in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16));
+        let jitter = exponential.mul_f64(jitter_fraction() * 0.5);
+        (exponential + jitter).min(self.max_delay)
+    }
+}
+
+/// A fraction in `[0, 1)` that varies between calls; good enough for
+/// spreading retries without pulling in a random number generator.
+fn jitter_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    f64::from(nanos % 1000) / 1000.0
+}
+
+/// The primary rate limit as reported by the last response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Unix time at which the limit resets.
+    pub reset: u64,
+}
+
+impl RateLimit {
+    /// Read GitHub's `X-RateLimit-*` headers or GitLab's `RateLimit-*`.
+    fn from_headers(headers: &HeaderMap) -> Option<RateLimit> {
+        Some(RateLimit {
+            limit: rate_limit_header(headers, "limit")?,
+            remaining: rate_limit_header(headers, "remaining")?,
+            reset: rate_limit_header(headers, "reset")?,
+        })
+    }
+
+    /// Time left until the limit resets, counted from `now` (Unix time).
+    fn until_reset(&self, now: u64) -> Duration {
+        Duration::from_secs(self.reset.saturating_sub(now) + 1)
+    }
+}
+
+fn header_number(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn rate_limit_header(headers: &HeaderMap, field: &str) -> Option<u64> {
+    header_number(headers, &format!("x-ratelimit-{}", field))
+        .or_else(|| header_number(headers, &format!("ratelimit-{}", field)))
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// How long to wait before retrying a request that got `status`, or `None`
+/// when the response should not be retried.
+fn retry_delay(
+    status: StatusCode,
+    headers: &HeaderMap,
+    policy: &RetryPolicy,
+    attempt: u32,
+    now: u64,
+) -> Option<Duration> {
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN
+            && (headers.contains_key("retry-after")
+                || rate_limit_header(headers, "remaining") == Some(0)));
+    let transient = matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    );
+    if !rate_limited && !transient {
+        return None;
+    }
+    let hinted = header_number(headers, "retry-after")
+        .map(Duration::from_secs)
+        .or_else(|| {
+            RateLimit::from_headers(headers)
+                .filter(|limit| limit.remaining == 0)
+                .map(|limit| limit.until_reset(now))
+        });
+    Some(
+        hinted
+            .unwrap_or_else(|| policy.backoff(attempt))
+            .min(policy.max_delay),
+    )
+}
+
+/// Why the client is about to wait, passed to the observer set with
+/// [`GitHub::on_wait`].
+#[derive(Debug, Clone)]
+pub struct Wait {
+    pub delay: Duration,
+    /// The retry about to be made (1-based), or 0 when waiting for the
+    /// rate limit to reset before a request.
+    pub attempt: u32,
+    pub reason: String,
+}
+
+type WaitObserver = Box<dyn Fn(&Wait) + Send + Sync>;
+
+/// The HTTP side shared by the forge clients: retries, rate limit tracking
+/// and error decoding as described in the module docs.
+pub(crate) struct Transport {
+    pub(crate) client: Client,
+    /// The forge's name, for wait reasons like "GitHub returned 502".
+    name: &'static str,
+    retry: RetryPolicy,
+    rate_limit: Mutex<Option<RateLimit>>,
+    on_wait: Option<WaitObserver>,
+    fixtures: Option<Fixtures>,
+}
+
+impl Transport {
+    pub(crate) fn new(name: &'static str) -> Result<Self, GitHubError> {
+        let client = Client::builder()
+            .user_agent(concat!("gitscaffold/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Transport {
+            client,
+            name,
+            retry: RetryPolicy::default(),
+            rate_limit: Mutex::new(None),
+            on_wait: None,
+            fixtures: None,
+        })
+    }
+
+    pub(crate) fn set_retry(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    pub(crate) fn set_on_wait(&mut self, observer: WaitObserver) {
+        self.on_wait = Some(observer);
+    }
+
+    pub(crate) fn set_fixtures(&mut self, fixtures: Fixtures) {
+        self.fixtures = Some(fixtures);
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn wait(&self, delay: Duration, attempt: u32, reason: String) {
+        if let Some(observer) = &self.on_wait {
+            observer(&Wait {
+                delay,
+                attempt,
+                reason,
+            });
+        }
+        tokio::time::sleep(delay).await;
+    }
+
+    /// Send `request`, retrying as described in the module docs, or answer
+    /// it from the fixtures being replayed.
+    async fn execute(&self, request: RequestBuilder) -> Result<Response, GitHubError> {
+        let Some(fixtures) = &self.fixtures else {
+            return self.send_retrying(request).await;
+        };
+        let key = Key::of(&request)
+            .ok_or_else(|| GitHubError::Fixture("cannot record a streamed request".into()))?;
+        if fixtures.is_replaying() {
+            return fixtures.answer(&key);
+        }
+        let response = self.send_retrying(request).await?;
+        fixtures.store(key, response).await
+    }
+
+    async fn send_retrying(&self, request: RequestBuilder) -> Result<Response, GitHubError> {
+        if let Some(limit) = self.rate_limit().filter(|l| l.remaining == 0) {
+            let now = unix_now();
+            if limit.reset >= now {
+                let delay = limit.until_reset(now).min(self.retry.max_delay);
+                self.wait(delay, 0, "rate limit exhausted".into()).await;
+            }
+        }
+        let mut pending = Some(request);
+        let mut attempt = 0;
+        while let Some(request) = pending.take() {
+            attempt += 1;
+            // Bodies are always JSON, so cloning only fails for requests
+            // that could not be retried anyway.
+            if attempt <= self.retry.max_retries {
+                pending = request.try_clone();
+            }
+            let retryable = pending.is_some();
+            match request.send().await {
+                Ok(response) => {
+                    if let Some(limit) = RateLimit::from_headers(response.headers()) {
+                        *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner()) = Some(limit);
+                    }
+                    let status = response.status();
+                    tracing::debug!(
+                        status = status.as_u16(),
+                        url = %response.url(),
+                        attempt,
+                        "{} responded",
+                        self.name
+                    );
+                    let delay = retryable
+                        .then(|| {
+                            retry_delay(
+                                status,
+                                response.headers(),
+                                &self.retry,
+                                attempt,
+                                unix_now(),
+                            )
+                        })
+                        .flatten();
+                    match delay {
+                        Some(delay) => {
+                            let reason = format!("{} returned {}", self.name, status);
+                            self.wait(delay, attempt, reason).await
+                        }
+                        None => return Ok(response),
+                    }
+                }
+                Err(e) if retryable && (e.is_timeout() || e.is_connect()) => {
+                    self.wait(self.retry.backoff(attempt), attempt, e.to_string())
+                        .await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        unreachable!("the last attempt always returns")
+    }
+
+    /// Send `request` and turn an unsuccessful status into an error.
+    pub(crate) async fn send_checked(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Response, GitHubError> {
+        let response = self.execute(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|body| api_message(&body))
+                .unwrap_or_else(|| status.canonical_reason().unwrap_or("").to_string());
+            return Err(GitHubError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+        Ok(response)
+    }
+
+    pub(crate) async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, GitHubError> {
+        Ok(self.send_checked(request).await?.json().await?)
+    }
+}
+
+/// The message of an error response: GitHub's `message`, or GitLab's
+/// `message` (which may be an object of field errors) or `error`.
+fn api_message(body: &serde_json::Value) -> Option<String> {
+    match body.get("message").or_else(|| body.get("error"))? {
+        serde_json::Value::String(message) => Some(message.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// How the client authenticates.
+enum Auth {
+    Token(String),
+    App(GitHubApp),
+}
+
+pub struct GitHub {
+    http: Transport,
+    api_url: String,
+    auth: Auth,
+    repo: String,
+}
+
+impl GitHub {
+    /// Create a client for `repo` (`owner/name`) authenticated with `token`.
+    pub fn new(api_url: &str, token: &str, repo: &str) -> Result<Self, GitHubError> {
+        Ok(GitHub {
+            http: Transport::new("GitHub")?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            auth: Auth::Token(token.to_string()),
+            repo: repo.to_string(),
+        })
+    }
+
+    /// Create a client for `repo` authenticated as an installation of
+    /// `app`.
+    pub fn for_app(api_url: &str, app: GitHubApp, repo: &str) -> Result<Self, GitHubError> {
+        Ok(GitHub {
+            http: Transport::new("GitHub")?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            auth: Auth::App(app),
+            repo: repo.to_string(),
+        })
+    }
+
+    /// The account owning the repository.
+    pub fn owner(&self) -> &str {
+        self.repo.split('/').next().unwrap_or(&self.repo)
+    }
+
+    /// The repository's name without its owner.
+    pub fn repo_name(&self) -> &str {
+        self.repo
+            .split_once('/')
+            .map_or(&self.repo, |(_, name)| name)
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.http.set_retry(retry);
+        self
+    }
+
+    /// Record the client's requests into `fixtures`, or answer them from
+    /// it.
+    pub fn with_fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.http.set_fixtures(fixtures);
+        self
+    }
+
+    /// Call `observer` whenever the client sleeps before a request, e.g. to
+    /// tell the user why a long sync stalls.
+    pub fn on_wait(mut self, observer: impl Fn(&Wait) + Send + Sync + 'static) -> Self {
+        self.http.set_on_wait(Box::new(observer));
+        self
+    }
+
+    /// The primary rate limit reported by the last response, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.http.rate_limit()
+    }
+
+    async fn token(&self) -> Result<String, GitHubError> {
+        match &self.auth {
+            Auth::Token(token) => Ok(token.clone()),
+            Auth::App(app) => app.token(&self.http, &self.api_url, &self.repo).await,
+        }
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> Result<RequestBuilder, GitHubError> {
+        Ok(self
+            .http
+            .client
+            .request(
+                method,
+                format!("{}/repos/{}/{}", self.api_url, self.repo, path),
+            )
+            .bearer_auth(self.token().await?)
+            .header("Accept", "application/vnd.github+json"))
+    }
+
+    /// Run a GraphQL query or mutation. The GraphQL endpoint sits next to
+    /// the REST API: `/graphql` on github.com, `/api/graphql` on GitHub
+    /// Enterprise Server.
+    pub(crate) async fn graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value, GitHubError> {
+        let mut response = self.graphql_response(query, variables).await?;
+        if let Some(error) = response.errors.first() {
+            return Err(GitHubError::GraphQl(error.message.clone()));
+        }
+        Ok(response.data.take().unwrap_or_default())
+    }
+
+    /// Run a GraphQL query or mutation whose fields may fail one by one,
+    /// returning the data that did come back along with the errors.
+    pub(crate) async fn graphql_response(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<GraphQlResponse, GitHubError> {
+        let url = match self.api_url.strip_suffix("/v3") {
+            Some(api) => format!("{}/graphql", api),
+            None => format!("{}/graphql", self.api_url),
+        };
+        let request = self
+            .http
+            .client
+            .post(url)
+            .bearer_auth(self.token().await?)
+            .json(&serde_json::json!({ "query": query, "variables": variables }));
+        self.http.send(request).await
+    }
+
+    /// Fetch every page of a list endpoint.
+    async fn list<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, GitHubError> {
+        let mut all = Vec::new();
+        for page in 1.. {
+            let request = self.request(reqwest::Method::GET, path).await?.query(&[
+                ("state", "all".to_string()),
+                ("per_page", PER_PAGE.to_string()),
+                ("page", page.to_string()),
+            ]);
+            let batch: Vec<T> = self.http.send(request).await?;
+            let done = batch.len() < PER_PAGE;
+            all.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(all)
+    }
+}
+
+impl Forge for GitHub {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    /// All milestones, open and closed.
+    async fn list_milestones(&self) -> Result<Vec<RemoteMilestone>, GitHubError> {
+        self.list("milestones").await
+    }
+
+    /// All issues, open and closed, excluding pull requests.
+    async fn list_issues(&self) -> Result<Vec<RemoteIssue>, GitHubError> {
+        let mut issues: Vec<RemoteIssue> = self.list("issues").await?;
+        issues.retain(|issue| issue.pull_request.is_none());
+        Ok(issues)
+    }
+
+    async fn create_milestone(
+        &self,
+        milestone: &NewMilestone<'_>,
+    ) -> Result<RemoteMilestone, GitHubError> {
+        self.http
+            .send(
+                self.request(reqwest::Method::POST, "milestones")
+                    .await?
+                    .json(milestone),
+            )
+            .await
+    }
+
+    async fn update_milestone(
+        &self,
+        number: u64,
+        update: &MilestoneUpdate<'_>,
+    ) -> Result<(), GitHubError> {
+        let path = format!("milestones/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .await?
+                    .json(update),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn create_issue(&self, issue: &NewIssue<'_>) -> Result<RemoteIssue, GitHubError> {
+        self.http
+            .send(
+                self.request(reqwest::Method::POST, "issues")
+                    .await?
+                    .json(issue),
+            )
+            .await
+    }
+
+    /// Batched over GraphQL; see [`crate::github_batch`].
+    async fn create_issues(
+        &self,
+        issues: &[NewIssue<'_>],
+    ) -> Vec<Result<RemoteIssue, GitHubError>> {
+        crate::github_batch::create_issues(self, issues).await
+    }
+
+    async fn rename_issue(&self, number: u64, title: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .await?
+                    .json(&serde_json::json!({ "title": title })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_issue(&self, number: u64, update: &IssueUpdate<'_>) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .await?
+                    .json(update),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Sub-issues are linked by the child's REST id, not its number.
+    async fn add_sub_issue(&self, parent: u64, child: u64) -> Result<(), GitHubError> {
+        #[derive(Deserialize)]
+        struct Id {
+            id: u64,
+        }
+        let path = format!("issues/{}", child);
+        let child: Id = self
+            .http
+            .send(self.request(reqwest::Method::GET, &path).await?)
+            .await?;
+        let path = format!("issues/{}/sub_issues", parent);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::POST, &path)
+                    .await?
+                    .json(&serde_json::json!({ "sub_issue_id": child.id })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn comment_on_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}/comments", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::POST, &path)
+                    .await?
+                    .json(&serde_json::json!({ "body": comment })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_comments(&self, number: u64) -> Result<Vec<RemoteComment>, GitHubError> {
+        self.list(&format!("issues/{}/comments", number)).await
+    }
+
+    async fn update_comment(&self, _: u64, id: u64, body: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/comments/{}", id);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .await?
+                    .json(&serde_json::json!({ "body": body })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn close_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        self.comment_on_issue(number, comment).await?;
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .await?
+                    .json(&serde_json::json!({ "state": "closed", "state_reason": "not_planned" })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Permanently delete an issue; needs admin access to the repository.
+    async fn delete_issue(&self, issue: &RemoteIssue) -> Result<(), GitHubError> {
+        self.graphql(
+            "mutation($id: ID!) { deleteIssue(input: {issueId: $id}) { clientMutationId } }",
+            serde_json::json!({ "id": issue.node_id }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn lock_issue(&self, number: u64) -> Result<(), GitHubError> {
+        let path = format!("issues/{}/lock", number);
+        self.http
+            .send_checked(self.request(reqwest::Method::PUT, &path).await?)
+            .await?;
+        Ok(())
+    }
+
+    /// Files go through the contents API and are linked to by their web
+    /// page with `?raw=true`, which also works in private repositories.
+    async fn upload_file(
+        &self,
+        path: &str,
+        content: &[u8],
+        branch: Option<&str>,
+    ) -> Result<String, GitHubError> {
+        #[derive(Deserialize)]
+        struct File {
+            html_url: String,
+        }
+        #[derive(Deserialize)]
+        struct Created {
+            content: File,
+        }
+        let contents = format!("contents/{}", path);
+        let mut request = self.request(reqwest::Method::GET, &contents).await?;
+        if let Some(branch) = branch {
+            request = request.query(&[("ref", branch)]);
+        }
+        let file = match self.http.send::<File>(request).await {
+            Ok(file) => file,
+            Err(GitHubError::Api { status: 404, .. }) => {
+                let mut body = serde_json::json!({
+                    "message": format!("Add {}", path),
+                    "content": BASE64_STANDARD.encode(content),
+                });
+                if let Some(branch) = branch {
+                    body["branch"] = branch.into();
+                }
+                let request = self.request(reqwest::Method::PUT, &contents).await?;
+                let created: Created = self.http.send(request.json(&body)).await?;
+                created.content
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(format!("{}?raw=true", file.html_url))
+    }
+
+    async fn list_labels(&self) -> Result<Vec<RemoteLabel>, GitHubError> {
+        self.list("labels").await
+    }
+
+    async fn create_label(&self, label: &NewLabel<'_>) -> Result<(), GitHubError> {
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::POST, "labels")
+                    .await?
+                    .json(label),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_label(&self, name: &str, update: &LabelUpdate<'_>) -> Result<(), GitHubError> {
+        let path = format!("labels/{}", encode_segment(name));
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PATCH, &path)
+                    .await?
+                    .json(update),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_assignees(&self) -> Result<Vec<RemoteUser>, GitHubError> {
+        self.list("assignees").await
+    }
+
+    /// The issue's event log, keeping the events that change its state or
+    /// milestone.
+    async fn issue_events(&self, issue: &RemoteIssue) -> Result<Vec<IssueEvent>, GitHubError> {
+        let log: Vec<LoggedEvent> = self
+            .list(&format!("issues/{}/events", issue.number))
+            .await?;
+        Ok(complete_events(issue, log))
+    }
+}
+
+/// An entry of an issue's event log.
+#[derive(Debug, Deserialize)]
+struct LoggedEvent {
+    event: String,
+    created_at: String,
+    #[serde(default)]
+    milestone: Option<LoggedMilestone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoggedMilestone {
+    title: String,
+}
+
+/// The history of `issue` from its event log. The log has no entry for
+/// opening the issue, and none for a milestone set when it was opened,
+/// which only shows in the issue itself or in a later removal.
+fn complete_events(issue: &RemoteIssue, log: Vec<LoggedEvent>) -> Vec<IssueEvent> {
+    let mut events: Vec<IssueEvent> = log
+        .into_iter()
+        .filter_map(|logged| {
+            let milestone = || logged.milestone.as_ref().map(|m| m.title.clone());
+            let kind = match logged.event.as_str() {
+                "closed" => IssueEventKind::Closed,
+                "reopened" => IssueEventKind::Reopened,
+                "milestoned" => IssueEventKind::Milestoned(milestone()?),
+                "demilestoned" => IssueEventKind::Demilestoned(milestone()?),
+                _ => return None,
+            };
+            Some(IssueEvent {
+                at: logged.created_at,
+                kind,
+            })
+        })
+        .collect();
+    let Some(created_at) = &issue.created_at else {
+        return events;
+    };
+    // The milestone the issue was opened in: the one its first move took
+    // it out of, or without moves the one it is in.
+    let first_move = events.iter().find_map(|event| match &event.kind {
+        IssueEventKind::Milestoned(_) => Some(None),
+        IssueEventKind::Demilestoned(title) => Some(Some(title.clone())),
+        _ => None,
+    });
+    let initial = match first_move {
+        Some(initial) => initial,
+        None => issue.milestone.as_ref().map(|m| m.title.clone()),
+    };
+    let mut opened = vec![IssueEvent {
+        at: created_at.clone(),
+        kind: IssueEventKind::Opened,
+    }];
+    if let Some(title) = initial {
+        opened.push(IssueEvent {
+            at: created_at.clone(),
+            kind: IssueEventKind::Milestoned(title),
+        });
+    }
+    opened.append(&mut events);
+    opened
+}
+
+/// `segment` percent-encoded for use as one segment of a URL path, such as
+/// a label name with spaces or a `/` in it.
+pub(crate) fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn waits_as_long_as_rate_limit_headers_ask() {
+        let policy = RetryPolicy::default();
+        let delay = |status, pairs: &[(&'static str, &str)]| {
+            retry_delay(status, &headers(pairs), &policy, 1, 1_000)
+        };
+        assert_eq!(
+            delay(StatusCode::TOO_MANY_REQUESTS, &[("retry-after", "30")]),
+            Some(Duration::from_secs(30))
+        );
+        let exhausted = [
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1060"),
+        ];
+        assert_eq!(
+            delay(StatusCode::FORBIDDEN, &exhausted),
+            Some(Duration::from_secs(61))
+        );
+        // A plain 403 is a permission problem, not a rate limit.
+        assert_eq!(delay(StatusCode::FORBIDDEN, &[]), None);
+        assert_eq!(delay(StatusCode::NOT_FOUND, &[]), None);
+        let backoff = delay(StatusCode::BAD_GATEWAY, &[]).unwrap();
+        assert!(backoff >= Duration::from_secs(1) && backoff < Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+        };
+        assert!(policy.backoff(3) >= Duration::from_secs(4));
+        assert!(policy.backoff(3) < Duration::from_secs(6));
+        assert_eq!(policy.backoff(9), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn completes_event_logs_with_the_opening_milestone() {
+        let issue: RemoteIssue = serde_json::from_value(serde_json::json!({
+            "number": 1, "title": "Login", "state": "open",
+            "created_at": "2024-06-01T09:00:00Z",
+            "milestone": {"number": 2, "title": "M2", "state": "open"},
+        }))
+        .unwrap();
+        let log: Vec<LoggedEvent> = serde_json::from_value(serde_json::json!([
+            {"event": "labeled", "created_at": "2024-06-01T09:00:01Z"},
+            {"event": "demilestoned", "created_at": "2024-06-02T09:00:00Z", "milestone": {"title": "M1"}},
+            {"event": "milestoned", "created_at": "2024-06-02T09:00:00Z", "milestone": {"title": "M2"}},
+            {"event": "closed", "created_at": "2024-06-03T09:00:00Z"},
+            {"event": "reopened", "created_at": "2024-06-04T09:00:00Z"},
+        ]))
+        .unwrap();
+        let kinds: Vec<IssueEventKind> = complete_events(&issue, log)
+            .into_iter()
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                IssueEventKind::Opened,
+                IssueEventKind::Milestoned("M1".into()),
+                IssueEventKind::Demilestoned("M1".into()),
+                IssueEventKind::Milestoned("M2".into()),
+                IssueEventKind::Closed,
+                IssueEventKind::Reopened,
+            ]
+        );
+        // Without moves, the issue was in its milestone from the start.
+        assert_eq!(complete_events(&issue, Vec::new()), issue.inferred_events());
+    }
+}
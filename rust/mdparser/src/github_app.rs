@@ -0,0 +1,182 @@
+//! Authenticating as a GitHub App instead of with a personal token.
+//!
+//! The app signs a short-lived JWT with its private key, looks up its
+//! installation on the repository (unless the installation id is given)
+//! and exchanges the JWT for an installation token. Installation tokens
+//! expire after an hour; [`GitHubApp::token`] hands out the current one
+//! and fetches a new one five minutes before it runs out, so long syncs
+//! carry on without interruption.
+
+use std::fmt;
+
+use chrono::DateTime;
+use futures::lock::Mutex;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+
+use crate::github::{unix_now, GitHubError, Transport};
+
+/// How long before it expires an installation token is replaced.
+const REFRESH_MARGIN: u64 = 5 * 60;
+
+pub use jsonwebtoken::errors::Error as KeyError;
+
+/// A GitHub App and the installation tokens it has been given.
+pub struct GitHubApp {
+    app_id: String,
+    key: EncodingKey,
+    installation_id: Mutex<Option<u64>>,
+    current: Mutex<Option<InstallationToken>>,
+}
+
+impl fmt::Debug for GitHubApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitHubApp")
+            .field("app_id", &self.app_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InstallationToken {
+    token: String,
+    /// Unix time.
+    expires_at: u64,
+}
+
+impl InstallationToken {
+    fn is_fresh(&self, now: u64) -> bool {
+        now + REFRESH_MARGIN < self.expires_at
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Claims {
+    iat: u64,
+    exp: u64,
+    iss: String,
+}
+
+impl Claims {
+    /// Claims valid from a minute ago, allowing for clock drift, for the
+    /// nine minutes after; GitHub accepts at most ten.
+    fn new(app_id: &str, now: u64) -> Claims {
+        Claims {
+            iat: now.saturating_sub(60),
+            exp: now + 9 * 60,
+            iss: app_id.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Installation {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct AccessToken {
+    token: String,
+    expires_at: String,
+}
+
+impl GitHubApp {
+    /// The app `app_id` with its private key in PEM form. Without
+    /// `installation_id`, the installation is looked up on the repository.
+    pub fn new(
+        app_id: &str,
+        private_key: &[u8],
+        installation_id: Option<u64>,
+    ) -> Result<GitHubApp, KeyError> {
+        Ok(GitHubApp {
+            app_id: app_id.to_string(),
+            key: EncodingKey::from_rsa_pem(private_key)?,
+            installation_id: Mutex::new(installation_id),
+            current: Mutex::new(None),
+        })
+    }
+
+    fn jwt(&self) -> Result<String, GitHubError> {
+        let claims = Claims::new(&self.app_id, unix_now());
+        jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &self.key)
+            .map_err(|e| GitHubError::Auth(format!("cannot sign the app's JWT: {}", e)))
+    }
+
+    /// An installation token for `repo`, fetched from `api_url` when there
+    /// is none yet or the last one is about to expire.
+    pub(crate) async fn token(
+        &self,
+        http: &Transport,
+        api_url: &str,
+        repo: &str,
+    ) -> Result<String, GitHubError> {
+        // Holding the lock while refreshing makes concurrent requests wait
+        // for one new token instead of each fetching their own.
+        let mut current = self.current.lock().await;
+        if let Some(token) = current.as_ref().filter(|t| t.is_fresh(unix_now())) {
+            return Ok(token.token.clone());
+        }
+        let jwt = self.jwt()?;
+        let installation = {
+            let mut id = self.installation_id.lock().await;
+            match *id {
+                Some(id) => id,
+                None => {
+                    let request = http
+                        .client
+                        .get(format!("{}/repos/{}/installation", api_url, repo))
+                        .bearer_auth(&jwt)
+                        .header("Accept", "application/vnd.github+json");
+                    let found: Installation = http.send(request).await.map_err(|e| {
+                        GitHubError::Auth(format!("app is not installed on {}: {}", repo, e))
+                    })?;
+                    *id = Some(found.id);
+                    found.id
+                }
+            }
+        };
+        let request = http
+            .client
+            .post(format!(
+                "{}/app/installations/{}/access_tokens",
+                api_url, installation
+            ))
+            .bearer_auth(&jwt)
+            .header("Accept", "application/vnd.github+json");
+        let issued: AccessToken = http
+            .send(request)
+            .await
+            .map_err(|e| GitHubError::Auth(format!("cannot get an installation token: {}", e)))?;
+        let expires_at = DateTime::parse_from_rfc3339(&issued.expires_at)
+            .map(|at| at.timestamp().max(0) as u64)
+            // An hour is what GitHub grants.
+            .unwrap_or_else(|_| unix_now() + 60 * 60);
+        let token = issued.token.clone();
+        *current = Some(InstallationToken {
+            token: issued.token,
+            expires_at,
+        });
+        Ok(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_replaced_before_they_expire() {
+        let claims = Claims::new("123", 1_000_000);
+        assert_eq!((claims.iat, claims.exp), (999_940, 1_000_540));
+        assert_eq!(claims.iss, "123");
+
+        let token = InstallationToken {
+            token: "ghs_x".into(),
+            expires_at: 1_000_000 + 3600,
+        };
+        assert!(token.is_fresh(1_000_000));
+        assert!(token.is_fresh(1_000_000 + 3600 - 301));
+        assert!(!token.is_fresh(1_000_000 + 3600 - 300));
+        assert!(GitHubApp::new("123", b"not a key", None).is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! Creating many GitHub issues in a few GraphQL requests.
+//!
+//! The REST API takes one request per issue. GraphQL takes several
+//! `createIssue` mutations in one request, but wants node ids where REST
+//! takes names: [`create_issues`] first looks up the repository, labels,
+//! milestones and assignees of the whole batch in a single query, then
+//! sends one mutation per batch with a field per issue. GraphQL cannot
+//! create labels on the fly the way REST does, so issues with a label the
+//! repository lacks, or an assignee or milestone that could not be
+//! resolved, are created over REST instead, as are all issues of a batch
+//! whose lookup fails.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde_json::{json, Value};
+
+use crate::forge::Forge;
+use crate::github::{GitHub, GitHubError, NewIssue, RemoteIssue};
+
+/// The node ids the issues of a batch refer to.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Ids {
+    repository: String,
+    labels: HashMap<String, String>,
+    users: HashMap<String, String>,
+    milestones: HashMap<u64, String>,
+}
+
+impl Ids {
+    /// The `createIssue` input for `issue`, or `None` when something it
+    /// refers to has no id.
+    fn input(&self, issue: &NewIssue<'_>) -> Option<Value> {
+        let labels: Option<Vec<&String>> = issue
+            .labels
+            .iter()
+            .map(|name| self.labels.get(name))
+            .collect();
+        let users: Option<Vec<&String>> = issue
+            .assignees
+            .iter()
+            .map(|login| self.users.get(login))
+            .collect();
+        let milestone = match issue.milestone {
+            Some(number) => Some(self.milestones.get(&number)?),
+            None => None,
+        };
+        Some(json!({
+            "repositoryId": self.repository,
+            "title": issue.title,
+            "body": issue.body,
+            "labelIds": labels?,
+            "assigneeIds": users?,
+            "milestoneId": milestone,
+        }))
+    }
+}
+
+/// The query looking up everything `issues` refer to; fields are aliased
+/// `l0`, `u0`, `m0`... in the order of [`names`].
+fn lookup(github: &GitHub, issues: &[NewIssue<'_>]) -> (String, Value) {
+    let (labels, users, milestones) = names(issues);
+    let mut params = vec!["$owner: String!".to_string(), "$name: String!".to_string()];
+    let mut repository = vec!["id".to_string()];
+    let mut top = Vec::new();
+    let mut variables = json!({ "owner": github.owner(), "name": github.repo_name() });
+    for (i, label) in labels.iter().enumerate() {
+        params.push(format!("$l{}: String!", i));
+        repository.push(format!("l{i}: label(name: $l{i}) {{ id }}"));
+        variables[format!("l{}", i)] = json!(label);
+    }
+    for (i, number) in milestones.iter().enumerate() {
+        params.push(format!("$m{}: Int!", i));
+        repository.push(format!("m{i}: milestone(number: $m{i}) {{ id }}"));
+        variables[format!("m{}", i)] = json!(number);
+    }
+    for (i, login) in users.iter().enumerate() {
+        params.push(format!("$u{}: String!", i));
+        top.push(format!("u{i}: user(login: $u{i}) {{ id }}"));
+        variables[format!("u{}", i)] = json!(login);
+    }
+    let query = format!(
+        "query({}) {{ repository(owner: $owner, name: $name) {{ {} }} {} }}",
+        params.join(", "),
+        repository.join(" "),
+        top.join(" ")
+    );
+    (query, variables)
+}
+
+/// The distinct labels, assignees and milestones of `issues`, sorted.
+fn names(issues: &[NewIssue<'_>]) -> (Vec<String>, Vec<String>, Vec<u64>) {
+    let labels: BTreeSet<&String> = issues.iter().flat_map(|i| &i.labels).collect();
+    let users: BTreeSet<&String> = issues.iter().flat_map(|i| &i.assignees).collect();
+    let milestones: BTreeSet<u64> = issues.iter().filter_map(|i| i.milestone).collect();
+    (
+        labels.into_iter().cloned().collect(),
+        users.into_iter().cloned().collect(),
+        milestones.into_iter().collect(),
+    )
+}
+
+/// The ids in the answer to [`lookup`]. Unknown labels and users come
+/// back as `null` (with an error for users), and are left out.
+fn ids(issues: &[NewIssue<'_>], data: &Value) -> Option<Ids> {
+    let (labels, users, milestones) = names(issues);
+    let repository = &data["repository"];
+    let id = |field: &Value| field["id"].as_str().map(str::to_string);
+    let mut ids = Ids {
+        repository: id(repository)?,
+        ..Ids::default()
+    };
+    for (i, label) in labels.into_iter().enumerate() {
+        if let Some(id) = id(&repository[format!("l{}", i)]) {
+            ids.labels.insert(label, id);
+        }
+    }
+    for (i, number) in milestones.into_iter().enumerate() {
+        if let Some(id) = id(&repository[format!("m{}", i)]) {
+            ids.milestones.insert(number, id);
+        }
+    }
+    for (i, login) in users.into_iter().enumerate() {
+        if let Some(id) = id(&data[format!("u{}", i)]) {
+            ids.users.insert(login, id);
+        }
+    }
+    Some(ids)
+}
+
+/// The mutation creating an issue for each of `inputs`, aliased `i0`,
+/// `i1`...
+fn mutation(inputs: &[Value]) -> (String, Value) {
+    let mut params = Vec::new();
+    let mut fields = Vec::new();
+    let mut variables = json!({});
+    for (i, input) in inputs.iter().enumerate() {
+        params.push(format!("$i{}: CreateIssueInput!", i));
+        fields.push(format!(
+            "i{i}: createIssue(input: $i{i}) {{ issue {{ id number title state url }} }}"
+        ));
+        variables[format!("i{}", i)] = input.clone();
+    }
+    let query = format!("mutation({}) {{ {} }}", params.join(", "), fields.join(" "));
+    (query, variables)
+}
+
+/// The issue GraphQL returned for a `createIssue` field.
+fn created(field: &Value) -> Option<RemoteIssue> {
+    let issue = &field["issue"];
+    serde_json::from_value(json!({
+        "number": issue["number"].as_u64()?,
+        "node_id": issue["id"],
+        "title": issue["title"],
+        "state": issue["state"].as_str()?.to_lowercase(),
+        "html_url": issue["url"],
+    }))
+    .ok()
+}
+
+/// Create `issues`, returning the result of each in order. See the module
+/// documentation for when REST is used instead of GraphQL.
+pub(crate) async fn create_issues(
+    github: &GitHub,
+    issues: &[NewIssue<'_>],
+) -> Vec<Result<RemoteIssue, GitHubError>> {
+    let (query, variables) = lookup(github, issues);
+    let ids = match github.graphql_response(&query, variables).await {
+        Ok(response) => response.data.as_ref().and_then(|data| ids(issues, data)),
+        Err(_) => None,
+    };
+    let inputs: Vec<Option<Value>> = issues
+        .iter()
+        .map(|issue| ids.as_ref()?.input(issue))
+        .collect();
+    let batched: Vec<Value> = inputs.iter().flatten().cloned().collect();
+
+    let mut results: Vec<Result<RemoteIssue, GitHubError>> = Vec::new();
+    if !batched.is_empty() {
+        let (query, variables) = mutation(&batched);
+        match github.graphql_response(&query, variables).await {
+            Ok(response) => {
+                let data = response.data.unwrap_or_default();
+                for i in 0..batched.len() {
+                    let alias = format!("i{}", i);
+                    let error = response
+                        .errors
+                        .iter()
+                        .find(|error| error.path.first().and_then(Value::as_str) == Some(&alias));
+                    results.push(match (created(&data[&alias]), error) {
+                        (Some(issue), _) => Ok(issue),
+                        (None, Some(error)) => Err(GitHubError::GraphQl(error.message.clone())),
+                        (None, None) => Err(GitHubError::GraphQl("no issue returned".into())),
+                    });
+                }
+            }
+            Err(error) => {
+                // The request failed as a whole, so every issue of the batch
+                // did; retrying them over REST could create them twice.
+                let message = error.to_string();
+                results
+                    .extend((0..batched.len()).map(|_| Err(GitHubError::GraphQl(message.clone()))));
+            }
+        }
+    }
+
+    let mut results = results.into_iter();
+    let mut outcomes = Vec::with_capacity(issues.len());
+    for (issue, input) in issues.iter().zip(&inputs) {
+        outcomes.push(match input.as_ref().and_then(|_| results.next()) {
+            Some(outcome) => outcome,
+            None => github.create_issue(issue).await,
+        });
+    }
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue<'a>(title: &'a str, labels: &[&str], milestone: Option<u64>) -> NewIssue<'a> {
+        NewIssue {
+            title,
+            body: String::new(),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            assignees: vec!["alice".into()],
+            milestone,
+        }
+    }
+
+    #[test]
+    fn resolves_ids_and_leaves_unknown_labels_to_rest() {
+        let github = GitHub::new("https://api.github.com", "t", "acme/app").unwrap();
+        let issues = [
+            issue("Login", &["auth"], Some(3)),
+            issue("Search", &["auth", "new"], None),
+        ];
+        let (query, variables) = lookup(&github, &issues);
+        assert!(query.contains("l1: label(name: $l1) { id }"));
+        assert!(query.contains("u0: user(login: $u0) { id }"));
+        assert_eq!(variables["l1"], "new");
+        assert_eq!(variables["m0"], 3);
+
+        let data = json!({
+            "repository": {"id": "R", "l0": {"id": "L0"}, "l1": null, "m0": {"id": "M3"}},
+            "u0": {"id": "U"},
+        });
+        let ids = ids(&issues, &data).unwrap();
+        assert_eq!(
+            ids.input(&issues[0]).unwrap(),
+            json!({
+                "repositoryId": "R",
+                "title": "Login",
+                "body": "",
+                "labelIds": ["L0"],
+                "assigneeIds": ["U"],
+                "milestoneId": "M3",
+            })
+        );
+        assert_eq!(ids.input(&issues[1]), None);
+
+        let (query, _) = mutation(&[json!({}), json!({})]);
+        assert!(query.starts_with("mutation($i0: CreateIssueInput!, $i1: CreateIssueInput!)"));
+        let issue = created(&json!({
+            "issue": {"id": "I", "number": 9, "title": "Login", "state": "OPEN", "url": "u"},
+        }))
+        .unwrap();
+        assert_eq!((issue.number, issue.state.as_str()), (9, "open"));
+    }
+}
@@ -0,0 +1,566 @@
+//! GitLab REST client (API v4) behind the [`Forge`] trait.
+//!
+//! GitLab's model differs from GitHub's in a few places the client smooths
+//! over: issues are addressed by their project-scoped `iid`, which becomes
+//! the issue number; states are `opened`/`active` rather than `open`;
+//! milestones have a plain `due_date`; labels are sent as one
+//! comma-separated string; and assignees are set by user ID, so usernames
+//! are looked up first.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::fixtures::Fixtures;
+use crate::forge::Forge;
+use crate::github::{
+    encode_segment, GitHubError, IssueUpdate, LabelUpdate, MilestoneUpdate, NewIssue, NewLabel,
+    NewMilestone, RemoteComment, RemoteIssue, RemoteLabel, RemoteMilestone, RemoteUser,
+    RetryPolicy, Transport, Wait,
+};
+
+pub const DEFAULT_API_URL: &str = "https://gitlab.com/api/v4";
+
+const PER_PAGE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    id: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    due_date: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+impl From<Milestone> for RemoteMilestone {
+    fn from(milestone: Milestone) -> Self {
+        RemoteMilestone {
+            number: milestone.id,
+            title: milestone.title,
+            state: open_state(milestone.state),
+            due_on: milestone.due_date.map(|due| format!("{}T00:00:00Z", due)),
+            description: milestone.description,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    id: u64,
+    iid: u64,
+    title: String,
+    state: String,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    closed_at: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    assignees: Vec<User>,
+    #[serde(default)]
+    milestone: Option<Milestone>,
+    #[serde(default)]
+    web_url: Option<String>,
+}
+
+impl From<Issue> for RemoteIssue {
+    fn from(issue: Issue) -> Self {
+        RemoteIssue {
+            number: issue.iid,
+            node_id: issue.id.to_string(),
+            title: issue.title,
+            state: open_state(issue.state),
+            created_at: issue.created_at,
+            closed_at: issue.closed_at,
+            body: issue.description,
+            labels: issue
+                .labels
+                .into_iter()
+                .map(|name| RemoteLabel {
+                    name,
+                    ..RemoteLabel::default()
+                })
+                .collect(),
+            assignees: issue
+                .assignees
+                .into_iter()
+                .map(|user| RemoteUser {
+                    login: user.username,
+                })
+                .collect(),
+            milestone: issue.milestone.map(RemoteMilestone::from),
+            html_url: issue.web_url,
+            pull_request: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: u64,
+    username: String,
+}
+
+#[derive(Serialize)]
+struct CreateMilestone<'a> {
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due_date: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct UpdateMilestone<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due_date: Option<Option<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_event: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct CreateIssue<'a> {
+    title: &'a str,
+    description: &'a str,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    labels: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    assignee_ids: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone_id: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct UpdateIssue<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    labels: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assignee_ids: Option<Vec<u64>>,
+    /// 0 removes the milestone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone_id: Option<u64>,
+}
+
+/// GitLab says `opened` for issues and `active` for milestones.
+fn open_state(state: String) -> String {
+    match state.as_str() {
+        "opened" | "active" => "open".to_string(),
+        _ => state,
+    }
+}
+
+pub struct GitLab {
+    http: Transport,
+    api_url: String,
+    token: String,
+    project: String,
+    /// User IDs by username, looked up once per run.
+    users: Mutex<HashMap<String, u64>>,
+}
+
+impl GitLab {
+    /// Create a client for `project` (`group/name`, subgroups allowed)
+    /// authenticated with a personal, project or group access `token`.
+    pub fn new(api_url: &str, token: &str, project: &str) -> Result<Self, GitHubError> {
+        Ok(GitLab {
+            http: Transport::new("GitLab")?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            project: project.to_string(),
+            users: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.http.set_retry(retry);
+        self
+    }
+
+    /// Record the client's requests into `fixtures`, or answer them from
+    /// it.
+    pub fn with_fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.http.set_fixtures(fixtures);
+        self
+    }
+
+    /// Call `observer` whenever the client sleeps before a request.
+    pub fn on_wait(mut self, observer: impl Fn(&Wait) + Send + Sync + 'static) -> Self {
+        self.http.set_on_wait(Box::new(observer));
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.http
+            .client
+            .request(
+                method,
+                format!(
+                    "{}/projects/{}/{}",
+                    self.api_url,
+                    self.project.replace('/', "%2F"),
+                    path
+                ),
+            )
+            .header("PRIVATE-TOKEN", &self.token)
+    }
+
+    /// Fetch every page of a list endpoint.
+    async fn list<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, GitHubError> {
+        let mut all = Vec::new();
+        for page in 1.. {
+            let request = self.request(reqwest::Method::GET, path).query(&[
+                ("per_page", PER_PAGE.to_string()),
+                ("page", page.to_string()),
+            ]);
+            let batch: Vec<T> = self.http.send(request).await?;
+            let done = batch.len() < PER_PAGE;
+            all.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(all)
+    }
+
+    async fn user_id(&self, username: &str) -> Result<u64, GitHubError> {
+        if let Some(&id) = self.lock_users().get(username) {
+            return Ok(id);
+        }
+        let request = self
+            .http
+            .client
+            .get(format!("{}/users", self.api_url))
+            .query(&[("username", username)])
+            .header("PRIVATE-TOKEN", &self.token);
+        let users: Vec<User> = self.http.send(request).await?;
+        let user = users
+            .into_iter()
+            .find(|user| user.username.eq_ignore_ascii_case(username))
+            .ok_or_else(|| GitHubError::Api {
+                status: 404,
+                message: format!("no GitLab user '{}'", username),
+            })?;
+        self.lock_users().insert(username.to_string(), user.id);
+        Ok(user.id)
+    }
+
+    fn lock_users(&self) -> std::sync::MutexGuard<'_, HashMap<String, u64>> {
+        self.users.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Forge for GitLab {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn repo(&self) -> &str {
+        &self.project
+    }
+
+    async fn list_milestones(&self) -> Result<Vec<RemoteMilestone>, GitHubError> {
+        let milestones: Vec<Milestone> = self.list("milestones").await?;
+        Ok(milestones.into_iter().map(RemoteMilestone::from).collect())
+    }
+
+    async fn list_issues(&self) -> Result<Vec<RemoteIssue>, GitHubError> {
+        let issues: Vec<Issue> = self.list("issues").await?;
+        Ok(issues.into_iter().map(RemoteIssue::from).collect())
+    }
+
+    async fn create_milestone(
+        &self,
+        milestone: &NewMilestone<'_>,
+    ) -> Result<RemoteMilestone, GitHubError> {
+        let body = CreateMilestone {
+            title: milestone.title,
+            due_date: milestone
+                .due_on
+                .as_deref()
+                .map(|due| &due[..due.len().min(10)]),
+            description: milestone.description,
+        };
+        let created: Milestone = self
+            .http
+            .send(
+                self.request(reqwest::Method::POST, "milestones")
+                    .json(&body),
+            )
+            .await?;
+        Ok(created.into())
+    }
+
+    async fn update_milestone(
+        &self,
+        number: u64,
+        update: &MilestoneUpdate<'_>,
+    ) -> Result<(), GitHubError> {
+        let body = UpdateMilestone {
+            title: update.title,
+            due_date: update
+                .due_on
+                .map(|due| due.map(|due| &due[..due.len().min(10)])),
+            description: update.description,
+            state_event: update.state.map(|_| "close"),
+        };
+        let path = format!("milestones/{}", number);
+        self.http
+            .send_checked(self.request(reqwest::Method::PUT, &path).json(&body))
+            .await?;
+        Ok(())
+    }
+
+    async fn create_issue(&self, issue: &NewIssue<'_>) -> Result<RemoteIssue, GitHubError> {
+        let mut assignee_ids = Vec::new();
+        for username in &issue.assignees {
+            assignee_ids.push(self.user_id(username).await?);
+        }
+        let body = CreateIssue {
+            title: issue.title,
+            description: &issue.body,
+            labels: issue.labels.join(","),
+            assignee_ids,
+            milestone_id: issue.milestone,
+        };
+        let created: Issue = self
+            .http
+            .send(self.request(reqwest::Method::POST, "issues").json(&body))
+            .await?;
+        Ok(created.into())
+    }
+
+    async fn rename_issue(&self, number: u64, title: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PUT, &path)
+                    .json(&serde_json::json!({ "title": title })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_issue(&self, number: u64, update: &IssueUpdate<'_>) -> Result<(), GitHubError> {
+        let assignee_ids = match update.assignees {
+            Some(usernames) => {
+                let mut ids = Vec::new();
+                for username in usernames {
+                    ids.push(self.user_id(username).await?);
+                }
+                Some(ids)
+            }
+            None => None,
+        };
+        let body = UpdateIssue {
+            description: update.body,
+            labels: update.labels.map(|labels| labels.join(",")),
+            assignee_ids,
+            milestone_id: update.milestone.map(|id| id.unwrap_or(0)),
+        };
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(self.request(reqwest::Method::PUT, &path).json(&body))
+            .await?;
+        Ok(())
+    }
+
+    /// Issues here have no sub-issues; the references in the parent's task
+    /// list are the only link.
+    async fn add_sub_issue(&self, _: u64, _: u64) -> Result<(), GitHubError> {
+        Ok(())
+    }
+
+    async fn comment_on_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}/notes", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::POST, &path)
+                    .json(&serde_json::json!({ "body": comment })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_comments(&self, number: u64) -> Result<Vec<RemoteComment>, GitHubError> {
+        self.list(&format!("issues/{}/notes", number)).await
+    }
+
+    async fn update_comment(&self, number: u64, id: u64, body: &str) -> Result<(), GitHubError> {
+        let path = format!("issues/{}/notes/{}", number, id);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PUT, &path)
+                    .json(&serde_json::json!({ "body": body })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn close_issue(&self, number: u64, comment: &str) -> Result<(), GitHubError> {
+        self.comment_on_issue(number, comment).await?;
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PUT, &path)
+                    .json(&serde_json::json!({ "state_event": "close" })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_issue(&self, issue: &RemoteIssue) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", issue.number);
+        self.http
+            .send_checked(self.request(reqwest::Method::DELETE, &path))
+            .await?;
+        Ok(())
+    }
+
+    async fn lock_issue(&self, number: u64) -> Result<(), GitHubError> {
+        let path = format!("issues/{}", number);
+        self.http
+            .send_checked(
+                self.request(reqwest::Method::PUT, &path)
+                    .json(&serde_json::json!({ "discussion_locked": true })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Files go through the repository files API, which needs a branch, so
+    /// the project is looked up first for its default branch and web
+    /// address.
+    async fn upload_file(
+        &self,
+        path: &str,
+        content: &[u8],
+        branch: Option<&str>,
+    ) -> Result<String, GitHubError> {
+        #[derive(Deserialize)]
+        struct Project {
+            web_url: String,
+            #[serde(default)]
+            default_branch: Option<String>,
+        }
+        let request = self
+            .http
+            .client
+            .get(format!(
+                "{}/projects/{}",
+                self.api_url,
+                self.project.replace('/', "%2F")
+            ))
+            .header("PRIVATE-TOKEN", &self.token);
+        let project: Project = self.http.send(request).await?;
+        let branch = branch
+            .or(project.default_branch.as_deref())
+            .unwrap_or("main");
+        let file = format!("repository/files/{}", path.replace('/', "%2F"));
+        let request = self
+            .request(reqwest::Method::GET, &file)
+            .query(&[("ref", branch)]);
+        match self.http.send_checked(request).await {
+            Ok(_) => {}
+            Err(GitHubError::Api { status: 404, .. }) => {
+                let body = serde_json::json!({
+                    "branch": branch,
+                    "content": BASE64_STANDARD.encode(content),
+                    "encoding": "base64",
+                    "commit_message": format!("Add {}", path),
+                });
+                let request = self.request(reqwest::Method::POST, &file).json(&body);
+                self.http.send_checked(request).await?;
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(format!("{}/-/raw/{}/{}", project.web_url, branch, path))
+    }
+
+    async fn list_labels(&self) -> Result<Vec<RemoteLabel>, GitHubError> {
+        let labels: Vec<RemoteLabel> = self.list("labels").await?;
+        Ok(labels.into_iter().map(without_hash).collect())
+    }
+
+    /// GitLab writes colors with a leading `#`.
+    async fn create_label(&self, label: &NewLabel<'_>) -> Result<(), GitHubError> {
+        let body = serde_json::json!({
+            "name": label.name,
+            "color": format!("#{}", label.color),
+            "description": label.description,
+        });
+        let request = self.request(reqwest::Method::POST, "labels").json(&body);
+        self.http.send_checked(request).await?;
+        Ok(())
+    }
+
+    async fn update_label(&self, name: &str, update: &LabelUpdate<'_>) -> Result<(), GitHubError> {
+        let mut body = serde_json::to_value(update).unwrap_or_default();
+        if let Some(color) = update.color {
+            body["color"] = format!("#{}", color).into();
+        }
+        let path = format!("labels/{}", encode_segment(name));
+        let request = self.request(reqwest::Method::PUT, &path).json(&body);
+        self.http.send_checked(request).await?;
+        Ok(())
+    }
+
+    /// The project's members, including those inherited from its groups;
+    /// their IDs are kept for assigning issues.
+    async fn list_assignees(&self) -> Result<Vec<RemoteUser>, GitHubError> {
+        let members: Vec<User> = self.list("members/all").await?;
+        let mut users = self.lock_users();
+        Ok(members
+            .into_iter()
+            .map(|member| {
+                users.insert(member.username.clone(), member.id);
+                RemoteUser {
+                    login: member.username,
+                }
+            })
+            .collect())
+    }
+}
+
+fn without_hash(mut label: RemoteLabel) -> RemoteLabel {
+    label.color = label.color.trim_start_matches('#').to_string();
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_gitlab_issues_onto_the_shared_model() {
+        let issue: Issue = serde_json::from_str(
+            r#"{"id": 901, "iid": 7, "title": "Login", "state": "opened",
+                "description": "Sign in.", "labels": ["auth", "p1"],
+                "assignees": [{"id": 3, "username": "alice"}],
+                "milestone": {"id": 55, "iid": 2, "title": "M1", "state": "active",
+                              "due_date": "2025-06-30"}}"#,
+        )
+        .unwrap();
+        let issue = RemoteIssue::from(issue);
+        assert_eq!((issue.number, issue.state.as_str()), (7, "open"));
+        assert_eq!(issue.labels[1].name, "p1");
+        assert_eq!(issue.assignees[0].login, "alice");
+        let milestone = issue.milestone.unwrap();
+        assert_eq!((milestone.number, milestone.state.as_str()), (55, "open"));
+        assert_eq!(milestone.due_on.as_deref(), Some("2025-06-30T00:00:00Z"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Local images in feature descriptions, uploaded for sync.
+//!
+//! An issue cannot show `![form](img/login.png)`: the path means nothing
+//! on the forge. [`upload_images`] commits each local image a description
+//! refers to into the repository, under [`ASSETS_DIR`] and named after a
+//! hash of its content so an unchanged image is only uploaded once, and
+//! points the descriptions at the uploaded files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use gitscaffold_parser::{Feature, Roadmap};
+
+use crate::error::ScaffoldError;
+use crate::forge::Forge;
+use crate::provenance::has_scheme;
+use crate::template::rewrite_links;
+
+/// Where in the repository uploaded images go.
+pub const ASSETS_DIR: &str = ".gitscaffold/assets";
+
+/// A description image whose source is a local file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalImage {
+    /// The source as written in the description.
+    pub url: String,
+    /// The file it names.
+    pub path: PathBuf,
+}
+
+/// What [`upload_images`] did.
+#[derive(Debug, Default)]
+pub struct Uploads {
+    /// The URL of each uploaded image, by its file.
+    pub uploaded: Vec<(PathBuf, String)>,
+    /// Images whose file does not exist; their links are left alone.
+    pub missing: Vec<PathBuf>,
+}
+
+/// The local images of `roadmap`'s features and their sub-features, read
+/// from the file at `roadmap_path`. Sources are relative to the file each
+/// feature comes from; URLs, absolute paths and anchors are not local.
+pub fn local_images(roadmap: &Roadmap, roadmap_path: &Path) -> Vec<LocalImage> {
+    let mut images = Vec::new();
+    // A stack of the features left, in document order from the top.
+    let mut features: Vec<&Feature> = roadmap.features.iter().rev().collect();
+    while let Some(feature) = features.pop() {
+        images.extend(feature_images(feature, roadmap_path));
+        features.extend(feature.children.iter().rev());
+    }
+    images
+}
+
+fn feature_images(feature: &Feature, roadmap_path: &Path) -> Vec<LocalImage> {
+    let file = feature.file.as_deref().map_or(roadmap_path, Path::new);
+    let dir = file.parent().unwrap_or(Path::new(""));
+    feature
+        .images
+        .iter()
+        .filter(|image| is_local(&image.url))
+        .map(|image| {
+            let end = image.url.find(['?', '#']).unwrap_or(image.url.len());
+            LocalImage {
+                url: image.url.clone(),
+                path: dir.join(&image.url[..end]),
+            }
+        })
+        .collect()
+}
+
+fn is_local(url: &str) -> bool {
+    !(url.is_empty() || url.starts_with(['#', '/', '?']) || has_scheme(url))
+}
+
+/// Upload the local images of `roadmap`, read from `roadmap_path`, through
+/// `forge`, to `branch` or the default branch, and rewrite the features'
+/// descriptions and `images` to point at the uploaded files.
+pub async fn upload_images(
+    forge: &impl Forge,
+    roadmap: &mut Roadmap,
+    roadmap_path: &Path,
+    branch: Option<&str>,
+) -> Result<Uploads, ScaffoldError> {
+    let mut uploads = Uploads::default();
+    let mut urls: HashMap<PathBuf, String> = HashMap::new();
+    let mut features: Vec<&mut Feature> = roadmap.features.iter_mut().rev().collect();
+    while let Some(feature) = features.pop() {
+        let mut rewritten = HashMap::new();
+        for image in feature_images(feature, roadmap_path) {
+            let url = match urls.get(&image.path) {
+                Some(url) => url.clone(),
+                None => {
+                    let Ok(content) = fs::read(&image.path) else {
+                        if !uploads.missing.contains(&image.path) {
+                            uploads.missing.push(image.path);
+                        }
+                        continue;
+                    };
+                    let url = forge
+                        .upload_file(&asset_path(&image.path, &content), &content, branch)
+                        .await
+                        .map_err(|e| ScaffoldError::github(forge.repo(), e))?;
+                    uploads.uploaded.push((image.path.clone(), url.clone()));
+                    urls.insert(image.path, url.clone());
+                    url
+                }
+            };
+            rewritten.insert(image.url, url);
+        }
+        if !rewritten.is_empty() {
+            let rewrite = |url: &str| rewritten.get(url).cloned();
+            feature.description = rewrite_links(&feature.description, rewrite);
+            for image in &mut feature.images {
+                if let Some(url) = rewrite(&image.url) {
+                    image.url = url;
+                }
+            }
+        }
+        features.extend(feature.children.iter_mut().rev());
+    }
+    Ok(uploads)
+}
+
+/// Where the image at `path` goes in the repository: [`ASSETS_DIR`], with
+/// its file name prefixed by a hash of `content` and reduced to characters
+/// that need no escaping in URLs.
+fn asset_path(path: &Path, content: &[u8]) -> String {
+    let hash: String = Sha256::digest(content)[..6]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let name: String = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => c,
+            _ => '-',
+        })
+        .collect();
+    format!("{}/{}-{}", ASSETS_DIR, hash, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::parse_markdown;
+
+    #[test]
+    fn finds_local_images_next_to_their_roadmap() {
+        let source = "# App\n\n## Features\n\n### Login\n\n![form](<img/login form.png#top>) \
+                      ![logo](https://example.com/logo.png) ![root](/logo.png)\n";
+        let roadmap = parse_markdown(source).unwrap();
+        let images = local_images(&roadmap, Path::new("docs/ROADMAP.md"));
+        assert_eq!(
+            images,
+            [LocalImage {
+                url: "img/login form.png#top".into(),
+                path: PathBuf::from("docs/img/login form.png"),
+            }]
+        );
+        assert_eq!(
+            asset_path(&images[0].path, b"png"),
+            ".gitscaffold/assets/8f8cbb7dcf46-login-form.png"
+        );
+    }
+}
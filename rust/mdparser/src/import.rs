@@ -0,0 +1,317 @@
+//! Build a roadmap from the milestones and issues already in a GitHub repo,
+//! the reverse of [`crate::sync`].
+//!
+//! Every selected issue becomes a feature. Features are ordered by
+//! milestone (by due date, undated last) and then by issue number, so the
+//! rendered roadmap groups them by milestone. A `## Tasks` checklist at the
+//! end of an issue body, as written by sync, is read back into tasks.
+
+use std::cmp::Ordering;
+
+use clap::ValueEnum;
+
+use gitscaffold_parser::{Feature, Milestone, Roadmap, Task};
+
+use crate::github::{RemoteIssue, RemoteMilestone};
+use crate::marker;
+
+/// Which issues to import, by their state on GitHub.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum IssueState {
+    #[default]
+    Open,
+    Closed,
+    All,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportFilter {
+    /// Only issues carrying all of these labels (case-insensitive).
+    pub labels: Vec<String>,
+    /// Only issues in this milestone (case-insensitive).
+    pub milestone: Option<String>,
+    pub state: IssueState,
+}
+
+impl ImportFilter {
+    fn matches(&self, issue: &RemoteIssue) -> bool {
+        let state = match self.state {
+            IssueState::Open => issue.state == "open",
+            IssueState::Closed => issue.state == "closed",
+            IssueState::All => true,
+        };
+        let labels = self.labels.iter().all(|wanted| {
+            issue
+                .labels
+                .iter()
+                .any(|label| label.name.eq_ignore_ascii_case(wanted))
+        });
+        let milestone = match &self.milestone {
+            Some(wanted) => issue
+                .milestone
+                .as_ref()
+                .is_some_and(|m| m.title.trim().eq_ignore_ascii_case(wanted.trim())),
+            None => true,
+        };
+        state && labels && milestone
+    }
+}
+
+/// Synthesize a roadmap named `name` from the repository's milestones and
+/// the issues selected by `filter`. Milestones are kept when they hold a
+/// selected issue or, without a milestone filter, when they are open.
+pub fn import_roadmap(
+    name: &str,
+    milestones: &[RemoteMilestone],
+    issues: &[RemoteIssue],
+    filter: &ImportFilter,
+) -> Roadmap {
+    let mut issues: Vec<&RemoteIssue> = issues.iter().filter(|i| filter.matches(i)).collect();
+    let mut milestones: Vec<&RemoteMilestone> = milestones
+        .iter()
+        .filter(|m| {
+            let used = issues
+                .iter()
+                .any(|i| i.milestone.as_ref().is_some_and(|im| im.number == m.number));
+            used || (filter.milestone.is_none() && m.state == "open")
+        })
+        .collect();
+    milestones.sort_by(|a, b| compare_due(a, b).then(a.number.cmp(&b.number)));
+
+    let rank = |issue: &RemoteIssue| {
+        issue
+            .milestone
+            .as_ref()
+            .and_then(|im| milestones.iter().position(|m| m.number == im.number))
+            .unwrap_or(milestones.len())
+    };
+    issues.sort_by_key(|issue| (rank(issue), issue.number));
+
+    Roadmap {
+        name: name.to_string(),
+        milestones: milestones
+            .iter()
+            .map(|m| Milestone {
+                name: m.title.trim().to_string(),
+                due_date: due_date(m),
+                description: m.description.as_deref().unwrap_or("").trim().to_string(),
+                ..Milestone::default()
+            })
+            .collect(),
+        features: issues.into_iter().map(feature).collect(),
+        ..Roadmap::default()
+    }
+}
+
+fn due_date(milestone: &RemoteMilestone) -> Option<String> {
+    milestone
+        .due_on
+        .as_ref()
+        .map(|due| due.chars().take(10).collect())
+}
+
+fn compare_due(a: &RemoteMilestone, b: &RemoteMilestone) -> Ordering {
+    match (&a.due_on, &b.due_on) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn feature(issue: &RemoteIssue) -> Feature {
+    let (description, tasks) = split_body(issue.body.as_deref().unwrap_or(""));
+    Feature {
+        title: issue.title.trim().to_string(),
+        description,
+        milestone: issue.milestone.as_ref().map(|m| m.title.trim().to_string()),
+        labels: issue.labels.iter().map(|l| l.name.clone()).collect(),
+        assignees: issue.assignees.iter().map(|a| a.login.clone()).collect(),
+        tasks,
+        ..Feature::default()
+    }
+}
+
+/// Split an issue body into the feature description and the tasks of a
+/// trailing `Tasks` checklist. Headings left in the description are
+/// turned into bold lines, since they would otherwise end the feature's
+/// section in the rendered roadmap.
+pub(crate) fn split_body(body: &str) -> (String, Vec<Task>) {
+    let body = marker::strip(body);
+    let lines: Vec<&str> = body.lines().collect();
+    let heading = lines
+        .iter()
+        .rposition(|line| line.trim().trim_start_matches('#').trim() == "Tasks")
+        .filter(|&i| lines[i].trim_start().starts_with('#'));
+    let mut tasks = Vec::new();
+    let mut end = lines.len();
+    if let Some(i) = heading {
+        let items: Option<Vec<Task>> = lines[i + 1..]
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| checklist_item(line))
+            .collect();
+        if let Some(items) = items {
+            tasks = items;
+            end = i;
+        }
+    }
+
+    let mut in_fence = false;
+    let description: Vec<String> = lines[..end]
+        .iter()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            match heading_text(trimmed) {
+                Some(text) if !in_fence => format!("**{}**", text),
+                _ => line.to_string(),
+            }
+        })
+        .collect();
+    (description.join("\n").trim().to_string(), tasks)
+}
+
+fn heading_text(line: &str) -> Option<&str> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[hashes..];
+    let is_heading = (1..=6).contains(&hashes) && (rest.is_empty() || rest.starts_with(' '));
+    is_heading
+        .then(|| rest.trim().trim_end_matches('#').trim())
+        .filter(|text| !text.is_empty())
+}
+
+fn checklist_item(line: &str) -> Option<Task> {
+    let indent = line.len() - line.trim_start().len();
+    let item = line
+        .trim_start()
+        .strip_prefix(['-', '*', '+'])?
+        .trim_start();
+    let (completed, title) = if let Some(title) = item.strip_prefix("[ ]") {
+        (false, title)
+    } else if let Some(title) = item
+        .strip_prefix("[x]")
+        .or_else(|| item.strip_prefix("[X]"))
+    {
+        (true, title)
+    } else {
+        return None;
+    };
+    Some(Task {
+        title: title.trim().to_string(),
+        completed,
+        depth: indent / 2,
+        ..Task::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{RemoteLabel, RemoteUser};
+    use crate::sync::issue_body;
+
+    fn milestone(number: u64, title: &str, state: &str, due_on: Option<&str>) -> RemoteMilestone {
+        RemoteMilestone {
+            number,
+            title: title.into(),
+            state: state.into(),
+            due_on: due_on.map(str::to_string),
+            description: None,
+        }
+    }
+
+    fn issue(
+        number: u64,
+        title: &str,
+        state: &str,
+        milestone: Option<&RemoteMilestone>,
+    ) -> RemoteIssue {
+        RemoteIssue {
+            number,
+            title: title.into(),
+            state: state.into(),
+            body: None,
+            labels: vec![RemoteLabel {
+                name: "api".into(),
+                ..RemoteLabel::default()
+            }],
+            assignees: vec![RemoteUser {
+                login: "alice".into(),
+            }],
+            milestone: milestone.cloned(),
+            html_url: None,
+            pull_request: None,
+            node_id: String::new(),
+            created_at: None,
+            closed_at: None,
+        }
+    }
+
+    #[test]
+    fn groups_issues_by_milestone_due_date() {
+        let late = milestone(1, "Late", "open", Some("2025-09-30T07:00:00Z"));
+        let early = milestone(2, "Early", "open", Some("2025-03-31T07:00:00Z"));
+        let done = milestone(3, "Done", "closed", None);
+        let issues = vec![
+            issue(5, "Loose", "open", None),
+            issue(6, "Second", "open", Some(&late)),
+            issue(7, "First", "open", Some(&early)),
+            issue(8, "Shipped", "closed", Some(&done)),
+        ];
+        let roadmap = import_roadmap(
+            "Demo",
+            &[late.clone(), early.clone(), done],
+            &issues,
+            &ImportFilter::default(),
+        );
+        let names: Vec<&str> = roadmap.milestones.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Early", "Late"]);
+        assert_eq!(
+            roadmap.milestones[0].due_date.as_deref(),
+            Some("2025-03-31")
+        );
+        let titles: Vec<&str> = roadmap.features.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(titles, ["First", "Second", "Loose"]);
+        assert_eq!(roadmap.features[0].milestone.as_deref(), Some("Early"));
+        assert_eq!(roadmap.features[0].assignees, ["alice"]);
+
+        let filter = ImportFilter {
+            milestone: Some("done".into()),
+            state: IssueState::All,
+            ..ImportFilter::default()
+        };
+        let roadmap = import_roadmap("Demo", &[late, early], &issues, &filter);
+        assert_eq!(roadmap.features.len(), 1);
+        assert!(roadmap.milestones.is_empty());
+    }
+
+    #[test]
+    fn reads_back_the_task_checklist_written_by_sync() {
+        let feature = Feature {
+            title: "Login".into(),
+            description: "Let users sign in.\n\n## Notes\n\nSee the spec.".into(),
+            tasks: vec![
+                Task {
+                    title: "Form".into(),
+                    completed: true,
+                    ..Task::default()
+                },
+                Task {
+                    title: "Expiry".into(),
+                    depth: 1,
+                    ..Task::default()
+                },
+            ],
+            ..Feature::default()
+        };
+        let (description, tasks) = split_body(&issue_body(&feature));
+        assert_eq!(
+            description,
+            "Let users sign in.\n\n**Notes**\n\nSee the spec."
+        );
+        assert_eq!(tasks, feature.tasks);
+    }
+}
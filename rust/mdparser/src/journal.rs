@@ -0,0 +1,218 @@
+//! The journal of a sync under way, kept so that `scaffold sync --resume`
+//! can finish a run that failed or was interrupted partway.
+//!
+//! A journal is a JSON Lines file per repository: the plan being applied,
+//! then one line for each of its operations as it completes. Resuming
+//! applies the operations with no line of their own and nothing else, so
+//! updates and closes done before the failure are not sent again, and no
+//! feature is planned afresh against a half-synced repository. A run that
+//! completes every operation removes its journal.
+
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ScaffoldError;
+use crate::sync::{Plan, SyncReport};
+
+/// One line of a journal.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Entry<P> {
+    Plan { plan: P },
+    Done { done: usize },
+}
+
+/// The journal a run writes to, created when the run starts applying its
+/// plan.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: Mutex<Writer>,
+}
+
+#[derive(Debug, Default)]
+struct Writer {
+    file: Option<File>,
+    /// The first write that failed; the run goes on without its journal.
+    error: Option<io::Error>,
+}
+
+/// What an earlier run left to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unfinished {
+    pub plan: Plan,
+    /// Indexes into the plan's operations of those that completed.
+    pub done: BTreeSet<usize>,
+}
+
+impl Journal {
+    /// Where the command-line tools keep journals, relative to the current
+    /// directory.
+    pub const DEFAULT_DIR: &'static str = ".gitscaffold/journal";
+
+    /// The journal of `repo` in `dir`.
+    pub fn new(dir: &Path, repo: &str) -> Self {
+        Journal {
+            path: dir.join(format!("{}.jsonl", repo.replace('/', "__"))),
+            file: Mutex::new(Writer::default()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read what the run journaled at this path left to do; `None` when
+    /// there is no journal.
+    pub fn load(&self) -> Result<Option<Unfinished>, ScaffoldError> {
+        let display = self.path.display().to_string();
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ScaffoldError::io(&display, e)),
+        };
+        let mut lines = content.lines();
+        let Some(first) = lines.next() else {
+            return Ok(None);
+        };
+        let Entry::Plan { plan } = serde_json::from_str::<Entry<Plan>>(first)
+            .map_err(|e| ScaffoldError::parse(Some(&display), e))?
+        else {
+            return Err(ScaffoldError::parse(
+                Some(&display),
+                "the journal does not start with a plan",
+            ));
+        };
+        // A line cut short by a crash is an operation that did not finish.
+        let done = lines
+            .filter_map(|line| match serde_json::from_str::<Entry<Plan>>(line) {
+                Ok(Entry::Done { done }) => Some(done),
+                _ => None,
+            })
+            .collect();
+        Ok(Some(Unfinished { plan, done }))
+    }
+
+    /// Start journaling `plan`, replacing the journal of an earlier run.
+    pub(crate) fn begin(&self, plan: &Plan) {
+        let mut writer = self.file.lock().unwrap();
+        let file = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| File::create(&self.path));
+        match file {
+            Ok(file) => writer.file = Some(file),
+            Err(e) => writer.error = Some(e),
+        }
+        writer.write(&Entry::Plan { plan });
+    }
+
+    /// Note that the operation at `index` in the plan completed.
+    pub(crate) fn record(&self, index: usize) {
+        self.file
+            .lock()
+            .unwrap()
+            .write(&Entry::<()>::Done { done: index });
+    }
+
+    /// Close the journal after the run made `report`: removed when every
+    /// operation completed, kept for `--resume` otherwise. Returns whether
+    /// it was kept, and fails when it could not be written.
+    pub fn finish(self, report: &SyncReport) -> Result<bool, ScaffoldError> {
+        let display = self.path.display().to_string();
+        let writer = self.file.into_inner().unwrap();
+        if let Some(e) = writer.error {
+            return Err(ScaffoldError::io(&display, e));
+        }
+        if writer.file.is_none() {
+            return Ok(false);
+        }
+        if report.failures.is_empty() && report.skipped == 0 {
+            fs::remove_file(&self.path).map_err(|e| ScaffoldError::io(&display, e))?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+impl Writer {
+    fn write<P: Serialize>(&mut self, entry: &Entry<P>) {
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let mut line = serde_json::to_vec(entry).expect("journal entries serialize");
+        line.push(b'\n');
+        if let Err(e) = file.write_all(&line).and_then(|()| file.sync_data()) {
+            self.error.get_or_insert(e);
+            self.file = None;
+        }
+    }
+}
+
+impl Unfinished {
+    /// The plan of the operations left to do.
+    pub fn remaining(self) -> Plan {
+        let operations = self
+            .plan
+            .operations
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !self.done.contains(index))
+            .map(|(_, operation)| operation)
+            .collect();
+        Plan {
+            operations,
+            ..self.plan
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::{Operation, PLAN_VERSION};
+
+    #[test]
+    fn resumes_after_the_last_complete_line() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-journal-{}", std::process::id()));
+        let close = |number| Operation::CloseIssue {
+            number,
+            title: format!("#{}", number),
+            comment: String::new(),
+            feature: None,
+        };
+        let plan = Plan {
+            version: PLAN_VERSION,
+            repo: "acme/app".into(),
+            existing_milestones: vec![],
+            existing_issues: vec![],
+            operations: vec![close(1), close(2), close(3)],
+            warnings: vec![],
+        };
+        let journal = Journal::new(&dir, "acme/app");
+        assert!(journal.path().ends_with("acme__app.jsonl"));
+        assert_eq!(journal.load().unwrap(), None);
+        journal.begin(&plan);
+        journal.record(2);
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        file.write_all(b"{\"done\":").unwrap();
+
+        let unfinished = journal.load().unwrap().unwrap();
+        assert_eq!(unfinished.done, BTreeSet::from([2]));
+        assert_eq!(unfinished.remaining().operations, vec![close(1), close(2)]);
+
+        let path = journal.path().to_path_buf();
+        assert!(!journal.finish(&SyncReport::default()).unwrap());
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,384 @@
+//! Bring a repository's labels in line with the roadmap and the `[labels]`
+//! config section: create the labels features use or the config defines,
+//! rename labels listed as aliases, fix colors and descriptions that drifted
+//! from their definitions, and report the labels nothing uses.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use gitscaffold_parser::{Feature, FieldMapping, LabelSettings, Roadmap};
+
+use crate::forge::Forge;
+use crate::github::{GitHubError, LabelUpdate, NewLabel, RemoteLabel};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LabelChange {
+    Create {
+        name: String,
+        color: String,
+        description: Option<String>,
+    },
+    /// A repository label under an alias takes its new name, and the
+    /// defined color and description if they differ.
+    Rename {
+        from: String,
+        to: String,
+        color: Option<String>,
+        description: Option<String>,
+    },
+    Update {
+        name: String,
+        color: Option<String>,
+        description: Option<String>,
+    },
+}
+
+impl LabelChange {
+    /// `+` for labels to create and `~` for labels to change.
+    pub fn marker(&self) -> char {
+        match self {
+            LabelChange::Create { .. } => '+',
+            _ => '~',
+        }
+    }
+}
+
+impl fmt::Display for LabelChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelChange::Create { name, color, .. } => {
+                write!(f, "create label '{}' (#{})", name, color)
+            }
+            LabelChange::Rename { from, to, .. } => {
+                write!(f, "rename label '{}' to '{}'", from, to)
+            }
+            LabelChange::Update {
+                name,
+                color,
+                description,
+            } => {
+                let mut fields = Vec::new();
+                if let Some(color) = color {
+                    fields.push(format!("color #{}", color));
+                }
+                if description.is_some() {
+                    fields.push("description".to_string());
+                }
+                write!(f, "update label '{}': {}", name, fields.join(", "))
+            }
+        }
+    }
+}
+
+/// What [`apply_labels`] changes, and the repository labels left that
+/// neither the roadmap nor the config uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LabelPlan {
+    pub changes: Vec<LabelChange>,
+    pub unused: Vec<String>,
+}
+
+/// Replace the aliases among the labels of `roadmap`'s features, their
+/// sub-features and their tasks by the names they stand for.
+pub fn resolve_aliases(roadmap: &mut Roadmap, settings: &LabelSettings) {
+    fn resolve(feature: &mut Feature, settings: &LabelSettings) {
+        let labels = feature.labels.iter_mut();
+        let task_labels = feature.tasks.iter_mut().flat_map(|task| &mut task.labels);
+        for label in labels.chain(task_labels) {
+            *label = settings.resolve(label).to_string();
+        }
+        for child in &mut feature.children {
+            resolve(child, settings);
+        }
+    }
+    if settings.aliases.is_empty() {
+        return;
+    }
+    for feature in &mut roadmap.features {
+        resolve(feature, settings);
+    }
+}
+
+/// Add the labels that custom field values map to, such as `area:ui` for
+/// `Area: ui`, to the features of `roadmap` and their sub-features.
+pub fn add_field_labels(roadmap: &mut Roadmap, fields: &BTreeMap<String, FieldMapping>) {
+    add_labels(roadmap, &|feature| {
+        feature
+            .fields
+            .iter()
+            .filter_map(|(key, value)| fields.get(key)?.label(value))
+            .collect()
+    });
+}
+
+/// Add the `[labels] priority` label, such as `priority:P1`, to the
+/// features of `roadmap` and their sub-features that have a priority.
+pub fn add_priority_labels(roadmap: &mut Roadmap, settings: &LabelSettings) {
+    let Some(pattern) = settings.priority.as_deref() else {
+        return;
+    };
+    add_labels(roadmap, &|feature| {
+        feature
+            .priority
+            .iter()
+            .map(|priority| pattern.replace("{value}", &priority.to_string()))
+            .filter(|label| !label.trim().is_empty())
+            .collect()
+    });
+}
+
+fn add_labels(roadmap: &mut Roadmap, labels_of: &dyn Fn(&Feature) -> Vec<String>) {
+    fn add(feature: &mut Feature, labels_of: &dyn Fn(&Feature) -> Vec<String>) {
+        for label in labels_of(feature) {
+            if !feature
+                .labels
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(&label))
+            {
+                feature.labels.push(label);
+            }
+        }
+        for child in &mut feature.children {
+            add(child, labels_of);
+        }
+    }
+    for feature in &mut roadmap.features {
+        add(feature, labels_of);
+    }
+}
+
+/// The labels `roadmap`'s features, sub-features and tasks use, with
+/// aliases resolved, in order of first use and without duplicates that
+/// only differ in case.
+pub fn used_labels(roadmap: &Roadmap, settings: &LabelSettings) -> Vec<String> {
+    fn collect<'a>(feature: &'a Feature, labels: &mut Vec<&'a str>) {
+        labels.extend(feature.labels.iter().map(String::as_str));
+        for task in &feature.tasks {
+            labels.extend(task.labels.iter().map(String::as_str));
+        }
+        for child in &feature.children {
+            collect(child, labels);
+        }
+    }
+    let mut labels = Vec::new();
+    for feature in &roadmap.features {
+        collect(feature, &mut labels);
+    }
+    let mut seen = HashSet::new();
+    labels
+        .into_iter()
+        .map(|label| settings.resolve(label.trim()))
+        .filter(|label| !label.is_empty() && seen.insert(label.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// How to bring `remote`, the repository's labels, in line with `wanted`,
+/// the labels in use, and the labels `settings` defines. Labels are
+/// compared ignoring case, as forges do.
+pub fn plan_labels(
+    wanted: &[String],
+    settings: &LabelSettings,
+    remote: &[RemoteLabel],
+) -> LabelPlan {
+    let find = |name: &str| {
+        remote
+            .iter()
+            .find(|label| label.name.eq_ignore_ascii_case(name))
+    };
+    let definition = |name: &str| {
+        settings
+            .define
+            .iter()
+            .find(|(defined, _)| defined.eq_ignore_ascii_case(name))
+            .map(|(_, definition)| definition)
+    };
+    // What a defined label should change to look like its definition.
+    let drift = |name: &str, label: Option<&RemoteLabel>| {
+        let Some(definition) = definition(name) else {
+            return (None, None);
+        };
+        let color = definition
+            .color
+            .as_deref()
+            .map(color)
+            .filter(|color| label.is_none_or(|label| !label.color.eq_ignore_ascii_case(color)));
+        let description = definition.description.clone().filter(|description| {
+            label.is_none_or(|label| label.description.as_ref() != Some(description))
+        });
+        (color, description)
+    };
+
+    let mut plan = LabelPlan::default();
+    let mut seen = HashSet::new();
+    let mut renamed = HashSet::new();
+    let names = wanted
+        .iter()
+        .map(String::as_str)
+        .chain(settings.define.keys().map(String::as_str));
+    for name in names {
+        if !seen.insert(name.to_lowercase()) {
+            continue;
+        }
+        if let Some(label) = find(name) {
+            let (color, description) = drift(name, Some(label));
+            if color.is_some() || description.is_some() {
+                plan.changes.push(LabelChange::Update {
+                    name: label.name.clone(),
+                    color,
+                    description,
+                });
+            }
+            continue;
+        }
+        let old = settings
+            .aliases
+            .iter()
+            .filter(|(_, new)| new.eq_ignore_ascii_case(name))
+            .find_map(|(alias, _)| find(alias));
+        if let Some(old) = old {
+            renamed.insert(old.name.to_lowercase());
+            let (color, description) = drift(name, Some(old));
+            plan.changes.push(LabelChange::Rename {
+                from: old.name.clone(),
+                to: name.to_string(),
+                color,
+                description,
+            });
+            continue;
+        }
+        let (color, description) = drift(name, None);
+        plan.changes.push(LabelChange::Create {
+            name: name.to_string(),
+            color: color.unwrap_or_else(|| self::color(&settings.default_color)),
+            description,
+        });
+    }
+    plan.unused = remote
+        .iter()
+        .filter(|label| {
+            let key = label.name.to_lowercase();
+            !seen.contains(&key) && !renamed.contains(&key)
+        })
+        .map(|label| label.name.clone())
+        .collect();
+    plan
+}
+
+/// `rrggbb` from a color written with or without a leading `#`.
+fn color(value: &str) -> String {
+    value.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Make the changes of `plan`, in order, stopping at the first that fails.
+pub async fn apply_labels(forge: &impl Forge, plan: &LabelPlan) -> Result<(), GitHubError> {
+    for change in &plan.changes {
+        match change {
+            LabelChange::Create {
+                name,
+                color,
+                description,
+            } => {
+                let label = NewLabel {
+                    name,
+                    color,
+                    description: description.as_deref(),
+                };
+                forge.create_label(&label).await?;
+            }
+            LabelChange::Rename {
+                from,
+                to,
+                color,
+                description,
+            } => {
+                let update = LabelUpdate {
+                    new_name: Some(to),
+                    color: color.as_deref(),
+                    description: description.as_deref(),
+                };
+                forge.update_label(from, &update).await?;
+            }
+            LabelChange::Update {
+                name,
+                color,
+                description,
+            } => {
+                let update = LabelUpdate {
+                    new_name: None,
+                    color: color.as_deref(),
+                    description: description.as_deref(),
+                };
+                forge.update_label(name, &update).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::LabelDefinition;
+
+    fn label(name: &str, color: &str) -> RemoteLabel {
+        RemoteLabel {
+            name: name.into(),
+            color: color.into(),
+            ..RemoteLabel::default()
+        }
+    }
+
+    #[test]
+    fn plans_creates_renames_updates_and_unused_labels() {
+        let mut settings = LabelSettings::default();
+        settings.define.insert(
+            "auth".into(),
+            LabelDefinition {
+                color: Some("#D93F0B".into()),
+                description: Some("Sign-in and sessions".into()),
+            },
+        );
+        settings.define.insert(
+            "ui".into(),
+            LabelDefinition {
+                color: Some("1d76db".into()),
+                description: None,
+            },
+        );
+        settings.aliases.insert("bug".into(), "defect".into());
+        let remote = [
+            label("Bug", "d73a4a"),
+            label("UI", "1D76DB"),
+            label("wontfix", "ffffff"),
+        ];
+        let wanted = ["defect".to_string(), "api".to_string(), "auth".to_string()];
+
+        let plan = plan_labels(&wanted, &settings, &remote);
+        assert_eq!(
+            plan.changes,
+            [
+                LabelChange::Rename {
+                    from: "Bug".into(),
+                    to: "defect".into(),
+                    color: None,
+                    description: None,
+                },
+                LabelChange::Create {
+                    name: "api".into(),
+                    color: "ededed".into(),
+                    description: None,
+                },
+                LabelChange::Create {
+                    name: "auth".into(),
+                    color: "d93f0b".into(),
+                    description: Some("Sign-in and sessions".into()),
+                },
+            ]
+        );
+        assert_eq!(plan.unused, ["wontfix"]);
+    }
+}
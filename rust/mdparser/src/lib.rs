@@ -0,0 +1,42 @@
+//! GitHub, GitLab and Gitea sync and error reporting shared by the
+//! `mdparser` and `scaffold` binaries. Parsing lives in the
+//! `gitscaffold-parser` crate.
+
+pub mod actions;
+pub mod burndown;
+pub mod changelog;
+pub mod credentials;
+pub mod dedupe;
+pub mod diagnostics;
+pub mod error;
+pub mod events;
+pub mod fixtures;
+pub mod forge;
+pub mod fuzzy;
+pub mod gitea;
+pub mod github;
+pub mod github_app;
+pub mod github_batch;
+pub mod gitlab;
+pub mod images;
+pub mod import;
+pub mod journal;
+pub mod labels;
+pub mod logging;
+pub mod marker;
+pub mod notify;
+pub mod output;
+pub mod project;
+pub mod provenance;
+pub mod pull;
+pub mod remote_diff;
+pub mod revision;
+pub mod rollup;
+pub mod runs;
+pub mod scan;
+pub mod snapshot;
+pub mod state;
+pub mod sync;
+pub mod template;
+pub mod vars;
+pub mod webhook;
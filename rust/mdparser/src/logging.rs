@@ -0,0 +1,154 @@
+//! Diagnostic logs of the binaries, written to stderr through `tracing`.
+//!
+//! Logs are separate from a command's output and its error report: by
+//! default only warnings are logged, `-v` adds what a command is doing,
+//! `-vv` each forge request and cache lookup, and `-vvv` everything. `-q`
+//! leaves only errors. `RUST_LOG`, when set, takes precedence over both,
+//! with the usual `tracing` directives such as `mdparser::github=debug`.
+//!
+//! What a command did, such as the issues a sync created, is logged with
+//! [`status!`](crate::status) under the [`STATUS`] target. Those lines show
+//! unless `-q` is given, as bare lines in text logs, so stdout only holds a
+//! command's primary output.
+
+use clap::{ArgAction, ValueEnum};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// Target of the status lines logged with [`status!`](crate::status).
+pub const STATUS: &str = "status";
+
+/// Log a status line saying what a command did, at `info` under the
+/// [`STATUS`] target, which is shown unless `-q` is given.
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)+) => {
+        ::tracing::info!(target: "status", $($arg)+)
+    };
+}
+
+/// How log lines are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// One line of text per event
+    #[default]
+    Text,
+    /// One JSON object per event, for CI log collectors
+    Json,
+}
+
+/// Logging flags shared by the binaries.
+#[derive(Debug, Clone, Copy, Default, clap::Args)]
+pub struct LogArgs {
+    /// Log more: `-v` for progress, `-vv` for requests and cache lookups,
+    /// `-vvv` for everything
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+    /// Log only errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    /// How to write log lines on stderr
+    #[arg(long, value_enum, global = true, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+}
+
+impl LogArgs {
+    /// The most detailed level the flags let through.
+    pub fn level(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::ERROR,
+            (false, 0) => LevelFilter::WARN,
+            (false, 1) => LevelFilter::INFO,
+            (false, 2) => LevelFilter::DEBUG,
+            (false, _) => LevelFilter::TRACE,
+        }
+    }
+
+    /// The most detailed level of status lines the flags let through:
+    /// `info` unless quiet.
+    pub fn status_level(&self) -> LevelFilter {
+        match self.quiet {
+            true => LevelFilter::ERROR,
+            false => self.level().max(LevelFilter::INFO),
+        }
+    }
+
+    /// Install the global subscriber, coloring levels when `color` is set.
+    /// Call once, before logging anything.
+    pub fn init(&self, color: bool) {
+        let mut filter = EnvFilter::builder()
+            .with_default_directive(self.level().into())
+            .from_env_lossy();
+        if let Ok(directive) = format!("{}={}", STATUS, self.status_level()).parse() {
+            filter = filter.add_directive(directive);
+        }
+        let _ = match self.log_format {
+            LogFormat::Text => {
+                let status = fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_ansi(color)
+                    .without_time()
+                    .with_level(false)
+                    .with_target(false)
+                    .with_filter(filter_fn(|metadata| metadata.target() == STATUS));
+                let logs = fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_ansi(color)
+                    .without_time()
+                    .with_filter(filter_fn(|metadata| metadata.target() != STATUS));
+                tracing_subscriber::registry()
+                    .with(filter)
+                    .with(status)
+                    .with(logs)
+                    .try_init()
+            }
+            LogFormat::Json => tracing_subscriber::registry()
+                .with(filter)
+                .with(
+                    fmt::layer()
+                        .json()
+                        .flatten_event(true)
+                        .with_writer(std::io::stderr),
+                )
+                .try_init(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_maps_to_levels() {
+        let level = |quiet, verbose| {
+            LogArgs {
+                verbose,
+                quiet,
+                ..LogArgs::default()
+            }
+            .level()
+        };
+        assert_eq!(level(false, 0), LevelFilter::WARN);
+        assert_eq!(level(false, 2), LevelFilter::DEBUG);
+        assert_eq!(level(false, 5), LevelFilter::TRACE);
+        assert_eq!(level(true, 0), LevelFilter::ERROR);
+    }
+
+    #[test]
+    fn status_lines_show_unless_quiet() {
+        let level = |quiet, verbose| {
+            LogArgs {
+                verbose,
+                quiet,
+                ..LogArgs::default()
+            }
+            .status_level()
+        };
+        assert_eq!(level(false, 0), LevelFilter::INFO);
+        assert_eq!(level(false, 2), LevelFilter::DEBUG);
+        assert_eq!(level(true, 0), LevelFilter::ERROR);
+    }
+}
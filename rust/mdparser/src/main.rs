@@ -1,73 +1,281 @@
-use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 
-use clap::Parser;
-use pulldown_cmark::{Event, Parser as MdParser};
-use serde::Serialize;
+use clap::{Parser, ValueEnum};
+
+use gitscaffold_parser::aggregate::aggregate;
+use gitscaffold_parser::export::{to_delimited, Column};
+use gitscaffold_parser::include;
+use gitscaffold_parser::load::read_source;
+use gitscaffold_parser::vars::substitute;
+use gitscaffold_parser::{
+    expand_paths, html, load_roadmap_with, parse_yaml, Cache, Config, Entities, Entity, HtmlPolicy,
+    InputFormat, ParseError, Roadmap, STDIN,
+};
+use mdparser::diagnostics::ColorChoice;
+use mdparser::error::{exit_on_usage, ErrorFormat, FailurePolicy, ScaffoldError};
+use mdparser::events::{self, EVENTS_VERSION};
+use mdparser::logging::LogArgs;
+use mdparser::output::{JsonArgs, JsonStyle};
+use mdparser::vars::VarArgs;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Path to Markdown file to parse
-    input: String,
+    /// Roadmap files or glob patterns such as `roadmap/*.md`, or `-` for
+    /// stdin; several files are merged into one roadmap
+    #[arg(value_name = "INPUT", required_unless_present = "schema")]
+    inputs: Vec<String>,
+    /// Input format; `auto` detects it from the extension and content
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    format: InputFormat,
+    /// Dump the raw Markdown event stream instead of the extracted roadmap
+    #[arg(long)]
+    events: bool,
+    /// Print the `--events` stream as NDJSON, one structured event per
+    /// line, as it is parsed
+    #[arg(long, requires = "events")]
+    ndjson: bool,
+    /// Include source spans (line, column and byte offset) for each entity
+    #[arg(long)]
+    with_spans: bool,
+    /// Emit one JSON object per roadmap entity (NDJSON) as it is parsed
+    #[arg(long, conflicts_with = "events")]
+    stream: bool,
+    /// Fail on anomalies such as unknown sections or misspelt metadata
+    /// keys instead of listing them in the output's `warnings`
+    #[arg(long)]
+    strict: bool,
+    /// What to do with raw HTML and footnotes in descriptions; `strip`
+    /// warns about each description it changes
+    #[arg(long, value_enum, default_value_t = HtmlPolicy::Keep, conflicts_with = "events")]
+    html: HtmlPolicy,
+    /// Output format; `csv` and `tsv` flatten the roadmap into one row per
+    /// task
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json, conflicts_with_all = ["events", "stream"])]
+    output_format: OutputFormat,
+    /// Columns of the `csv` and `tsv` output, in order
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = Column::DEFAULT)]
+    columns: Vec<Column>,
+    /// Config file; defaults to the nearest `.gitscaffold.toml` in the
+    /// current directory or its ancestors
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Always parse the file instead of reusing a result cached in
+    /// `.gitscaffold/cache/`
+    #[arg(long)]
+    no_cache: bool,
+    /// Print the JSON Schema of the output (of one line with `--stream`)
+    /// instead of parsing a file
+    #[arg(long)]
+    schema: bool,
+    /// How to print errors on stderr
+    #[arg(long, value_enum, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+    /// When to color errors
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    #[command(flatten)]
+    json: JsonArgs,
+    #[command(flatten)]
+    failure: FailurePolicy,
+    #[command(flatten)]
+    vars: VarArgs,
+    #[command(flatten)]
+    log: LogArgs,
 }
 
-#[derive(Serialize)]
-struct Node {
-    event: String,
-    text: Option<String>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Csv,
+    Tsv,
 }
 
 fn main() {
-    let args = Args::parse();
-    let content = match fs::read_to_string(&args.input) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error reading file {}: {}", args.input, e);
-            process::exit(1);
+    // Exit quietly when a pipe such as `| head` closes early.
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+    let args = Args::try_parse().unwrap_or_else(|e| exit_on_usage(e));
+    args.log.init(args.color.enabled(&io::stderr()));
+    if let Err(e) = run(&args) {
+        e.report(args.error_format, args.color.enabled(&io::stderr()));
+        process::exit(e.exit_code());
+    }
+}
+
+fn run(args: &Args) -> Result<(), ScaffoldError> {
+    if args.schema {
+        let schema = if args.stream {
+            Entity::schema()
+        } else {
+            Roadmap::schema()
+        };
+        println!("{}", args.json.style(true).to_string(&schema)?);
+        return Ok(());
+    }
+    let mut config =
+        Config::load_or_discover(args.config.as_deref()).map_err(|e| match &args.config {
+            Some(path) => ScaffoldError::load(&path.display().to_string(), e),
+            None => ScaffoldError::parse(None, e),
+        })?;
+    args.vars.apply(&mut config)?;
+    let paths =
+        expand_paths(&args.inputs).map_err(|e| ScaffoldError::load(&args.inputs.join(", "), e))?;
+    let input = paths[0].as_str();
+    if (args.stream || args.events) && paths.len() > 1 {
+        return Err(ScaffoldError::Usage(
+            "--stream and --events take a single file".into(),
+        ));
+    }
+    let style = args.json.style(false);
+    if args.stream {
+        return stream(input, args, &config);
+    }
+    if args.ndjson {
+        return event_lines(input, args);
+    }
+    let json = if args.events {
+        let content = read_source(input).map_err(|e| ScaffoldError::io(input, e))?;
+        let document = events::Document {
+            version: EVENTS_VERSION,
+            events: events::events(&content).collect(),
+        };
+        style.to_string(&document)?
+    } else {
+        let cache = (!args.no_cache).then(|| Cache::new(Cache::DEFAULT_DIR));
+        let mut parts = args.failure.map(&paths, "roadmap files", |path| {
+            let loaded = match &cache {
+                Some(cache) => cache.load(path, args.format, &config),
+                None => load_roadmap_with(path, args.format, &config),
+            };
+            let roadmap = loaded
+                .map(|mut roadmap| {
+                    let stripped = html::apply(&mut roadmap, args.html);
+                    roadmap.warnings.extend(stripped);
+                    roadmap
+                })
+                .and_then(|roadmap| {
+                    if args.strict {
+                        roadmap.deny_warnings()
+                    } else {
+                        Ok(roadmap)
+                    }
+                })
+                .map_err(|e| ScaffoldError::load(path, e))?;
+            Ok((path.clone(), roadmap))
+        })?;
+        let mut roadmap = if parts.len() == 1 {
+            parts.remove(0).1
+        } else {
+            aggregate(parts)
+        };
+        if roadmap.name.is_empty() {
+            roadmap.name = file_stem(input);
+        }
+        match args.output_format {
+            OutputFormat::Csv => {
+                print!("{}", to_delimited(&roadmap, &args.columns, ','));
+                return Ok(());
+            }
+            OutputFormat::Tsv => {
+                print!("{}", to_delimited(&roadmap, &args.columns, '\t'));
+                return Ok(());
+            }
+            OutputFormat::Json => {}
         }
+        if !args.with_spans {
+            roadmap.strip_spans();
+        }
+        style.to_string(&roadmap)?
     };
-    let parser = MdParser::new(&content);
-    let mut nodes = Vec::new();
-    let mut buffer = String::new();
-    for event in parser {
-        match &event {
-            Event::Text(text) => buffer.push_str(text),
-            _ => {
-                if !buffer.is_empty() {
-                    nodes.push(Node {
-                        event: "Text".into(),
-                        text: Some(buffer.clone()),
-                    });
-                    buffer.clear();
-                }
-                let ev = match &event {
-                    Event::Start(tag) => format!("Start {:?}", tag),
-                    Event::End(tag) => format!("End {:?}", tag),
-                    Event::Code(text) => format!("Code({:?})", text),
-                    Event::Html(html) => format!("Html({:?})", html),
-                    Event::SoftBreak => "SoftBreak".into(),
-                    Event::HardBreak => "HardBreak".into(),
-                    Event::Rule => "Rule".into(),
-                    Event::FootnoteReference(name) => format!("FootnoteReference({:?})", name),
-                    _ => format!("{:?}", event),
-                };
-                nodes.push(Node { event: ev, text: None });
+    // Emit JSON
+    println!("{}", json);
+    Ok(())
+}
+
+/// Write the roadmap as NDJSON, one entity per line. Markdown is extracted
+/// incrementally so memory use does not grow with the number of features;
+/// YAML has to be loaded as a whole first. Lines are never indented.
+fn stream(input: &str, args: &Args, config: &Config) -> Result<(), ScaffoldError> {
+    let style = JsonStyle {
+        indent: None,
+        ..args.json.style(false)
+    };
+    let raw = read_source(input).map_err(|e| ScaffoldError::io(input, e))?;
+    // Spans of streamed entities refer to the text with includes spliced in.
+    let content = match args.format.resolve(input, &raw) {
+        InputFormat::Yaml => substitute(&raw, config),
+        _ => include::expand(input, &raw, config).map(|expanded| expanded.text),
+    }
+    .map_err(|e| ScaffoldError::load(input, e))?;
+    let entities: Box<dyn Iterator<Item = Entity>> = match args.format.resolve(input, &content) {
+        InputFormat::Yaml => Box::new(
+            parse_yaml(&content)
+                .map_err(|e| ScaffoldError::load(input, e))?
+                .into_entities(),
+        ),
+        _ => Box::new(Entities::with_headings(&content, config.headings.clone())),
+    };
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut stripped = Vec::new();
+    let entities = entities.flat_map(|mut entity| {
+        if let Entity::Feature(feature) = &mut entity {
+            html::apply_to_feature(feature, args.html, &mut stripped);
+        }
+        std::iter::once(entity).chain(stripped.drain(..).map(Entity::Warning).collect::<Vec<_>>())
+    });
+    for mut entity in entities {
+        match &mut entity {
+            Entity::Roadmap { name, .. } if name.is_empty() => *name = file_stem(input),
+            Entity::Warning(warning) if args.strict => {
+                let error = ParseError::Strict(vec![warning.clone()]);
+                return Err(ScaffoldError::load(input, error));
             }
+            _ => {}
+        }
+        if !args.with_spans {
+            entity.strip_spans();
         }
+        style.to_writer(&mut out, &entity)?;
+        out.write_all(b"\n")
+            .map_err(|e| ScaffoldError::io("<stdout>", e))?;
     }
-    if !buffer.is_empty() {
-        nodes.push(Node {
-            event: "Text".into(),
-            text: Some(buffer.clone()),
-        });
+    out.flush().map_err(|e| ScaffoldError::io("<stdout>", e))
+}
+
+/// Write the Markdown events of `input` as NDJSON, one per line, without
+/// collecting them first.
+fn event_lines(input: &str, args: &Args) -> Result<(), ScaffoldError> {
+    let style = JsonStyle {
+        indent: None,
+        ..args.json.style(false)
+    };
+    let content = read_source(input).map_err(|e| ScaffoldError::io(input, e))?;
+    let mut out = BufWriter::new(io::stdout().lock());
+    let header = events::Header {
+        version: EVENTS_VERSION,
+    };
+    style.to_writer(&mut out, &header)?;
+    out.write_all(b"\n")
+        .map_err(|e| ScaffoldError::io("<stdout>", e))?;
+    for event in events::events(&content) {
+        style.to_writer(&mut out, &event)?;
+        out.write_all(b"\n")
+            .map_err(|e| ScaffoldError::io("<stdout>", e))?;
     }
-    // Emit JSON
-    match serde_json::to_string(&nodes) {
-        Ok(json) => println!("{}", json),
-        Err(e) => {
-            eprintln!("Error serializing JSON: {}", e);
-            process::exit(1);
-        }
+    out.flush().map_err(|e| ScaffoldError::io("<stdout>", e))
+}
+
+fn file_stem(path: &str) -> String {
+    if path == STDIN {
+        return String::new();
     }
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
 }
@@ -0,0 +1,122 @@
+//! The hidden marker sync ends issue bodies with.
+//!
+//! `<!-- gitscaffold:id=login,hash=3f2a9c0d81be -->` names the feature the
+//! issue was made for and fingerprints the body above it. GitHub, GitLab
+//! and Gitea all hide HTML comments when rendering Markdown, so the marker
+//! only shows when editing the issue. With it, sync recognises its own
+//! issues without the state file: an issue whose title no longer matches
+//! is claimed by its id, an issue whose id is gone from the roadmap can be
+//! pruned, and the hash tells whether the body still says what sync wrote.
+
+use sha2::{Digest, Sha256};
+
+const PREFIX: &str = "<!-- gitscaffold:";
+const SUFFIX: &str = "-->";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    /// The feature's id, see [`feature_id`].
+    pub id: String,
+    /// The start of the SHA-256 of the body without the marker.
+    pub hash: String,
+}
+
+impl Marker {
+    /// The marker for `body`, written for the feature with id `id`.
+    pub fn new(id: &str, body: &str) -> Marker {
+        Marker {
+            id: id.to_string(),
+            hash: hash(&strip(body)),
+        }
+    }
+
+    /// The marker at the end of `body`, if it has one.
+    pub fn find(body: &str) -> Option<Marker> {
+        let start = body.rfind(PREFIX)?;
+        let rest = &body[start + PREFIX.len()..];
+        let fields = rest[..rest.find(SUFFIX)?].trim();
+        let mut marker = Marker {
+            id: String::new(),
+            hash: String::new(),
+        };
+        for field in fields.split(',') {
+            match field.split_once('=') {
+                Some(("id", id)) => marker.id = id.trim().to_string(),
+                Some(("hash", hash)) => marker.hash = hash.trim().to_string(),
+                _ => {}
+            }
+        }
+        (!marker.id.is_empty()).then_some(marker)
+    }
+
+    /// Whether the body the marker ends still hashes to it, i.e. nobody
+    /// edited it since sync wrote it.
+    pub fn matches(&self, body: &str) -> bool {
+        self.hash == hash(&strip(body))
+    }
+}
+
+/// The id of the feature titled `title`: its title lowercased, with runs
+/// of anything but letters and digits turned into single dashes.
+pub fn feature_id(title: &str) -> String {
+    let mut id = String::new();
+    for c in title.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// `body` ending with the marker for the feature with id `id`, replacing
+/// any marker it has.
+pub fn stamp(body: &str, id: &str) -> String {
+    let body = strip(body);
+    let marker = Marker::new(id, &body);
+    let comment = format!("{}id={},hash={} {}", PREFIX, marker.id, marker.hash, SUFFIX);
+    if body.is_empty() {
+        comment
+    } else {
+        format!("{}\n\n{}", body, comment)
+    }
+}
+
+/// `body` without its marker, with line endings normalised and the ends
+/// trimmed.
+pub fn strip(body: &str) -> String {
+    let body = body.replace("\r\n", "\n");
+    let end = match body.rfind(PREFIX) {
+        Some(start) if body[start..].contains(SUFFIX) => start,
+        _ => body.len(),
+    };
+    body[..end].trim().to_string()
+}
+
+fn hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())[..6]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_finds_and_strips_markers() {
+        assert_eq!(feature_id("  Login & Sign-up (v2) "), "login-sign-up-v2");
+        let body = stamp("Sign in.\r\n\r\n- [ ] Form\n", "login");
+        assert!(body.starts_with("Sign in.\n\n- [ ] Form\n\n<!-- gitscaffold:id=login,hash="));
+        let marker = Marker::find(&body).unwrap();
+        assert_eq!(marker, Marker::new("login", "Sign in.\n\n- [ ] Form"));
+        assert_eq!(marker.hash.len(), 12);
+        assert!(marker.matches(&body));
+        assert!(!marker.matches(&body.replace("Form", "Forms")));
+        assert_eq!(stamp(&body, "login"), body);
+        assert_eq!(strip(&body), "Sign in.\n\n- [ ] Form");
+        assert_eq!(Marker::find("<!-- note -->"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Comments telling an issue's followers how sync changed it.
+//!
+//! Forges notify followers of new comments but not, or only faintly, of
+//! edits to an issue. With [`Notify::Comment`], each update sync makes to
+//! an issue comes with a comment listing the labels, assignees and
+//! milestone it changed and, folded away, a diff of the body.
+
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+
+use crate::github::RemoteIssue;
+use crate::marker::strip;
+
+/// Whether sync comments on the issues it updates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Notify {
+    /// Edit issues without a word
+    #[default]
+    Silent,
+    /// Leave a comment summarizing each update
+    Comment,
+}
+
+/// Body diffs longer than this many lines are cut short; forges limit the
+/// length of comments.
+const MAX_DIFF_LINES: usize = 200;
+
+/// What an update changes; the fields left as `None` stay as they are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Changes<'a> {
+    pub body: Option<&'a str>,
+    pub labels: Option<&'a [String]>,
+    pub assignees: Option<&'a [String]>,
+    /// `Some(None)` takes the issue out of its milestone.
+    pub milestone: Option<Option<&'a str>>,
+}
+
+/// The comment announcing `changes` to `issue`, as it is before them.
+pub fn change_comment(issue: &RemoteIssue, changes: Changes) -> String {
+    let mut lines = vec![
+        "The roadmap changed, so this issue was updated:".to_string(),
+        String::new(),
+    ];
+    if let Some(labels) = changes.labels {
+        let before: Vec<&str> = issue.labels.iter().map(|l| l.name.as_str()).collect();
+        lines.push(format!(
+            "- **Labels:** {}",
+            set_change(&before, labels, |l| format!("`{}`", l))
+        ));
+    }
+    if let Some(assignees) = changes.assignees {
+        let before: Vec<&str> = issue.assignees.iter().map(|u| u.login.as_str()).collect();
+        lines.push(format!(
+            "- **Assignees:** {}",
+            set_change(&before, assignees, |login| format!("@{}", login))
+        ));
+    }
+    if let Some(milestone) = changes.milestone {
+        let before = issue.milestone.as_ref().map(|m| m.title.as_str());
+        let change = match (before, milestone) {
+            (Some(before), Some(after)) => format!("{} → {}", before, after),
+            (None, Some(after)) => format!("added to {}", after),
+            (Some(before), None) => format!("removed from {}", before),
+            (None, None) => "unchanged".to_string(),
+        };
+        lines.push(format!("- **Milestone:** {}", change));
+    }
+    if let Some(body) = changes.body {
+        lines.push("- **Description:** changed".to_string());
+        lines.push(String::new());
+        lines.push("<details><summary>Changes to the description</summary>".to_string());
+        lines.push(String::new());
+        lines.push(body_diff(
+            &strip(issue.body.as_deref().unwrap_or("")),
+            &strip(body),
+        ));
+        lines.push(String::new());
+        lines.push("</details>".to_string());
+    }
+    lines.join("\n")
+}
+
+/// `added …; removed …` between the names `before` and `after`, compared
+/// ignoring case.
+fn set_change(before: &[&str], after: &[String], show: impl Fn(&str) -> String) -> String {
+    let after: Vec<&str> = after.iter().map(String::as_str).collect();
+    let missing = |names: &[&str], from: &[&str]| -> Vec<String> {
+        names
+            .iter()
+            .filter(|name| {
+                !from
+                    .iter()
+                    .any(|other| other.trim().eq_ignore_ascii_case(name.trim()))
+            })
+            .map(|name| show(name))
+            .collect()
+    };
+    let added = missing(&after, before);
+    let removed = missing(before, &after);
+    let mut parts = Vec::new();
+    if !added.is_empty() {
+        parts.push(format!("added {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        parts.push(format!("removed {}", removed.join(", ")));
+    }
+    match parts.is_empty() {
+        true => "reordered".to_string(),
+        false => parts.join("; "),
+    }
+}
+
+/// A unified diff of the bodies in a `diff` code block, fenced with more
+/// backticks than the bodies use in a row.
+fn body_diff(before: &str, after: &str) -> String {
+    let diff = TextDiff::from_lines(before, after)
+        .unified_diff()
+        .context_radius(2)
+        .to_string();
+    let mut lines: Vec<&str> = diff.lines().collect();
+    let cut = lines.len().saturating_sub(MAX_DIFF_LINES);
+    lines.truncate(MAX_DIFF_LINES);
+    let mut diff = lines.join("\n");
+    if cut > 0 {
+        diff.push_str(&format!("\n… {} more line(s)", cut));
+    }
+    let longest = diff.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}diff\n{}\n{}", fence, diff, fence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_changed_fields_with_a_body_diff() {
+        let issue: RemoteIssue = serde_json::from_value(serde_json::json!({
+            "number": 3, "title": "Login", "state": "open",
+            "body": "Sign in.\n\n```sh\nlogin\n```\n",
+            "labels": [{"name": "ui"}, {"name": "Auth"}],
+            "milestone": {"number": 1, "title": "v1", "state": "open"},
+        }))
+        .unwrap();
+        let labels = ["auth".to_string(), "security".to_string()];
+        let comment = change_comment(
+            &issue,
+            Changes {
+                body: Some("Sign in with SSO.\n\n```sh\nlogin\n```\n"),
+                labels: Some(&labels),
+                milestone: Some(Some("v2")),
+                ..Changes::default()
+            },
+        );
+        assert_eq!(
+            comment,
+            "The roadmap changed, so this issue was updated:\n\n\
+             - **Labels:** added `security`; removed `ui`\n\
+             - **Milestone:** v1 → v2\n\
+             - **Description:** changed\n\n\
+             <details><summary>Changes to the description</summary>\n\n\
+             ````diff\n\
+             @@ -1,3 +1,3 @@\n\
+             -Sign in.\n\
+             +Sign in with SSO.\n \n \
+             ```sh\n\
+             ````\n\n\
+             </details>"
+        );
+    }
+}
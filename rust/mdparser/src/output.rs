@@ -0,0 +1,143 @@
+//! How the binaries print JSON.
+//!
+//! `mdparser` prints compact JSON and `scaffold` indented JSON by default;
+//! [`JsonArgs`] gives both the same flags to choose otherwise, and to sort
+//! object keys so output diffs cleanly whatever order fields are emitted
+//! in.
+
+use serde::Serialize;
+use serde_json::ser::{PrettyFormatter, Serializer};
+use serde_json::{Map, Value};
+
+/// JSON formatting flags shared by the binaries.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct JsonArgs {
+    /// Indent JSON output
+    #[arg(long, global = true, conflicts_with = "compact")]
+    pub pretty: bool,
+    /// Print JSON output on a single line
+    #[arg(long, global = true)]
+    pub compact: bool,
+    /// Sort the keys of JSON objects alphabetically
+    #[arg(long, global = true)]
+    pub sort_keys: bool,
+    /// Spaces per indentation level; implies `--pretty`
+    #[arg(long, global = true, value_name = "N", conflicts_with = "compact")]
+    pub indent: Option<usize>,
+}
+
+impl JsonArgs {
+    /// The style the flags select, indenting unless told otherwise when
+    /// `pretty` is the binary's default.
+    pub fn style(&self, pretty: bool) -> JsonStyle {
+        let pretty = (pretty || self.pretty || self.indent.is_some()) && !self.compact;
+        JsonStyle {
+            indent: pretty.then_some(self.indent.unwrap_or(2)),
+            sort_keys: self.sort_keys,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonStyle {
+    /// Spaces per indentation level, or `None` for a single line.
+    pub indent: Option<usize>,
+    pub sort_keys: bool,
+}
+
+impl JsonStyle {
+    pub fn to_string(&self, value: &impl Serialize) -> Result<String, serde_json::Error> {
+        let mut out = Vec::new();
+        self.to_writer(&mut out, value)?;
+        // serde_json only writes valid UTF-8.
+        Ok(String::from_utf8(out).expect("JSON is UTF-8"))
+    }
+
+    pub fn to_writer(
+        &self,
+        writer: impl std::io::Write,
+        value: &impl Serialize,
+    ) -> Result<(), serde_json::Error> {
+        if self.sort_keys {
+            let sorted = sort_keys(serde_json::to_value(value)?);
+            return self.write(writer, &sorted);
+        }
+        self.write(writer, value)
+    }
+
+    fn write(
+        &self,
+        writer: impl std::io::Write,
+        value: &impl Serialize,
+    ) -> Result<(), serde_json::Error> {
+        match self.indent {
+            Some(indent) => {
+                let indent = " ".repeat(indent);
+                let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+                value.serialize(&mut Serializer::with_formatter(writer, formatter))
+            }
+            None => value.serialize(&mut Serializer::new(writer)),
+        }
+    }
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            let map: Map<String, Value> = entries
+                .into_iter()
+                .map(|(key, value)| (key, sort_keys(value)))
+                .collect();
+            Value::Object(map)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Item {
+        name: &'static str,
+        extra: Vec<(u8, u8)>,
+        #[serde(flatten)]
+        more: std::collections::HashMap<&'static str, u8>,
+    }
+
+    #[test]
+    fn indents_and_sorts_as_asked() {
+        let item = Item {
+            name: "a",
+            extra: vec![(1, 2)],
+            more: [("b", 1)].into(),
+        };
+        let args = |pretty, compact, sort_keys, indent| JsonArgs {
+            pretty,
+            compact,
+            sort_keys,
+            indent,
+        };
+        let compact = args(false, false, false, None).style(false);
+        assert_eq!(
+            compact.to_string(&item).unwrap(),
+            r#"{"name":"a","extra":[[1,2]],"b":1}"#
+        );
+        let sorted = args(false, false, true, None).style(false);
+        assert_eq!(
+            sorted.to_string(&item).unwrap(),
+            r#"{"b":1,"extra":[[1,2]],"name":"a"}"#
+        );
+        let indented = args(false, false, true, Some(4)).style(false);
+        assert!(indented
+            .to_string(&item)
+            .unwrap()
+            .starts_with("{\n    \"b\": 1,\n    \"extra\": [\n        [\n"));
+        assert_eq!(args(false, true, false, None).style(true).indent, None);
+        assert_eq!(args(false, false, false, None).style(true).indent, Some(2));
+    }
+}
@@ -0,0 +1,329 @@
+//! Adding synced issues to a GitHub Projects (v2) board.
+//!
+//! Projects v2 only has a GraphQL API. [`Board::load`] reads the project
+//! and its fields; [`Board::place`] adds an issue to it, sets the
+//! single-select `Status` field from the feature's status and the
+//! project's iteration field to the iteration titled like the feature's
+//! milestone, and the single-select fields custom fields are mapped to,
+//! see [`Board::with_fields`]. Adding an issue that is already on the
+//! board returns its existing item, so placing issues again on every sync
+//! is harmless.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use gitscaffold_parser::{Feature, FeatureStatus, FieldMapping};
+
+use crate::github::{GitHub, GitHubError};
+
+/// The name of the field statuses are written to.
+pub const STATUS_FIELD: &str = "Status";
+
+const LOAD: &str = "query($owner: String!, $number: Int!) {
+  repositoryOwner(login: $owner) {
+    ... on ProjectV2Owner {
+      projectV2(number: $number) {
+        id
+        title
+        fields(first: 100) {
+          nodes {
+            ... on ProjectV2SingleSelectField { id name options { id name } }
+            ... on ProjectV2IterationField {
+              id
+              name
+              configuration {
+                iterations { id title }
+                completedIterations { id title }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}";
+
+const ADD: &str = "mutation($project: ID!, $content: ID!) {
+  addProjectV2ItemById(input: {projectId: $project, contentId: $content}) { item { id } }
+}";
+
+const SET: &str = "mutation($project: ID!, $item: ID!, $field: ID!, $value: ProjectV2FieldValue!) {
+  updateProjectV2ItemFieldValue(
+    input: {projectId: $project, itemId: $item, fieldId: $field, value: $value}
+  ) { projectV2Item { id } }
+}";
+
+/// A named choice of a single-select field, or an iteration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+struct Choice {
+    id: String,
+    #[serde(alias = "title")]
+    name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Field {
+    id: String,
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct RawField {
+    id: Option<String>,
+    name: Option<String>,
+    options: Option<Vec<Choice>>,
+    configuration: Option<Configuration>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Configuration {
+    iterations: Vec<Choice>,
+    completed_iterations: Vec<Choice>,
+}
+
+/// A project board and the fields sync fills in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    id: String,
+    pub title: String,
+    status: Option<Field>,
+    iteration: Option<Field>,
+    /// The single-select fields, by name.
+    selects: Vec<(String, Field)>,
+    /// Custom field keys and the single-select fields they fill in.
+    mapped: Vec<(String, String)>,
+}
+
+impl Board {
+    /// The project numbered `number` of the owner of `github`'s repository.
+    pub async fn load(github: &GitHub, number: u64) -> Result<Board, GitHubError> {
+        let owner = github.owner().to_string();
+        let data = github
+            .graphql(
+                LOAD,
+                serde_json::json!({ "owner": owner, "number": number }),
+            )
+            .await?;
+        let project = &data["repositoryOwner"]["projectV2"];
+        if project.is_null() {
+            return Err(GitHubError::GraphQl(format!(
+                "{} has no project number {}",
+                owner, number
+            )));
+        }
+        let fields: Vec<RawField> =
+            serde_json::from_value(project["fields"]["nodes"].clone()).unwrap_or_default();
+        Ok(Board::new(
+            project["id"].as_str().unwrap_or_default(),
+            project["title"].as_str().unwrap_or_default(),
+            fields,
+        ))
+    }
+
+    fn new(id: &str, title: &str, fields: Vec<RawField>) -> Board {
+        let mut board = Board {
+            id: id.to_string(),
+            title: title.to_string(),
+            status: None,
+            iteration: None,
+            selects: Vec::new(),
+            mapped: Vec::new(),
+        };
+        for field in fields {
+            let Some(id) = field.id else { continue };
+            if let Some(options) = field.options {
+                let name = field.name.unwrap_or_default();
+                let field = Field {
+                    id,
+                    choices: options,
+                };
+                if name.eq_ignore_ascii_case(STATUS_FIELD) {
+                    board.status = Some(field.clone());
+                }
+                board.selects.push((name, field));
+            } else if let Some(configuration) = field.configuration {
+                // Boards rarely have more than one; the first one wins.
+                if board.iteration.is_none() {
+                    let mut choices = configuration.iterations;
+                    choices.extend(configuration.completed_iterations);
+                    board.iteration = Some(Field { id, choices });
+                }
+            }
+        }
+        board
+    }
+
+    /// The option of the `Status` field for `feature`: the one named like
+    /// its status, ignoring case and punctuation, with `Todo` standing in
+    /// for `planned`. Features without a status only get one when done.
+    fn status(&self, feature: &Feature) -> Option<&Choice> {
+        let names: &[&str] = match &feature.status {
+            Some(FeatureStatus::Planned) => &["planned", "todo", "backlog"],
+            Some(FeatureStatus::InProgress) => &["inprogress", "doing"],
+            Some(FeatureStatus::Deferred) => &["deferred", "icebox"],
+            Some(status) => &[status.name()],
+            None if feature.is_done() => &["done"],
+            None => &[],
+        };
+        let choices = &self.status.as_ref()?.choices;
+        names
+            .iter()
+            .find_map(|name| choices.iter().find(|c| key(&c.name) == key(name)))
+    }
+
+    /// Also set the single-select fields that `fields` maps custom fields
+    /// to, to the option named like the feature's value, ignoring case and
+    /// punctuation. Values without a matching option are left unset.
+    pub fn with_fields(mut self, fields: &BTreeMap<String, FieldMapping>) -> Self {
+        self.mapped = fields
+            .iter()
+            .filter_map(|(key, mapping)| Some((key.clone(), mapping.project.clone()?)))
+            .collect();
+        self
+    }
+
+    /// The single-select fields and options the custom fields of `feature`
+    /// fill in.
+    fn custom(&self, feature: &Feature) -> Vec<(&Field, &Choice)> {
+        self.mapped
+            .iter()
+            .filter_map(|(name, project)| {
+                let value = feature.fields.get(name)?;
+                let (_, field) = self
+                    .selects
+                    .iter()
+                    .find(|(name, _)| key(name) == key(project))?;
+                let choice = field.choices.iter().find(|c| key(&c.name) == key(value))?;
+                Some((field, choice))
+            })
+            .collect()
+    }
+
+    /// The iteration titled like `milestone`, ignoring case.
+    fn iteration(&self, milestone: &str) -> Option<&Choice> {
+        let choices = &self.iteration.as_ref()?.choices;
+        choices.iter().find(|c| key(&c.name) == key(milestone))
+    }
+
+    /// Add the issue with GraphQL node id `issue` to the board and fill in
+    /// its fields from `feature`.
+    pub async fn place(
+        &self,
+        github: &GitHub,
+        issue: &str,
+        feature: &Feature,
+    ) -> Result<(), GitHubError> {
+        let added = github
+            .graphql(
+                ADD,
+                serde_json::json!({ "project": self.id, "content": issue }),
+            )
+            .await?;
+        let item = added["addProjectV2ItemById"]["item"]["id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        if let (Some(field), Some(choice)) = (&self.status, self.status(feature)) {
+            self.set(github, &item, field, "singleSelectOptionId", choice)
+                .await?;
+        }
+        let iteration = feature
+            .milestone
+            .as_deref()
+            .and_then(|milestone| self.iteration(milestone));
+        if let (Some(field), Some(choice)) = (&self.iteration, iteration) {
+            self.set(github, &item, field, "iterationId", choice)
+                .await?;
+        }
+        for (field, choice) in self.custom(feature) {
+            self.set(github, &item, field, "singleSelectOptionId", choice)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn set(
+        &self,
+        github: &GitHub,
+        item: &str,
+        field: &Field,
+        kind: &str,
+        choice: &Choice,
+    ) -> Result<(), GitHubError> {
+        let variables = serde_json::json!({
+            "project": self.id,
+            "item": item,
+            "field": field.id,
+            "value": { kind: choice.id },
+        });
+        github.graphql(SET, variables).await?;
+        Ok(())
+    }
+}
+
+/// `name` lowercased with only its letters and digits.
+fn key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_statuses_and_milestones_to_field_values() {
+        let fields: Vec<RawField> = serde_json::from_value(serde_json::json!([
+            {},
+            {"id": "F1", "name": "Priority", "options": [{"id": "p", "name": "Done"}]},
+            {"id": "F2", "name": "Status", "options": [
+                {"id": "a", "name": "Todo"},
+                {"id": "b", "name": "In Progress"},
+                {"id": "c", "name": "Done"},
+            ]},
+            {"id": "F3", "name": "Sprint", "configuration": {
+                "iterations": [{"id": "i2", "title": "M2"}],
+                "completedIterations": [{"id": "i1", "title": "m1"}],
+            }},
+        ]))
+        .unwrap();
+        let board = Board::new("P", "Roadmap", fields);
+        let status = |status: Option<&str>| {
+            let feature = Feature {
+                status: status.map(FeatureStatus::parse),
+                ..Feature::default()
+            };
+            board.status(&feature).map(|choice| choice.id.clone())
+        };
+        assert_eq!(status(Some("planned")).as_deref(), Some("a"));
+        assert_eq!(status(Some("in progress")).as_deref(), Some("b"));
+        assert_eq!(status(Some("done")).as_deref(), Some("c"));
+        assert_eq!(status(Some("blocked")), None);
+        assert_eq!(status(None), None);
+        assert_eq!(board.iteration("M1").map(|c| c.id.as_str()), Some("i1"));
+        assert_eq!(board.iteration("M3"), None);
+
+        let mapping = FieldMapping {
+            project: Some("priority".into()),
+            ..FieldMapping::default()
+        };
+        let board = board.with_fields(&[("Priority".to_string(), mapping)].into_iter().collect());
+        let feature = |value: &str| Feature {
+            fields: [("Priority".to_string(), value.to_string())]
+                .into_iter()
+                .collect(),
+            ..Feature::default()
+        };
+        let custom = board.custom(&feature("DONE"));
+        assert_eq!(custom.len(), 1);
+        assert_eq!(
+            (custom[0].0.id.as_str(), custom[0].1.id.as_str()),
+            ("F1", "p")
+        );
+        assert!(board.custom(&feature("P9")).is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! Where in the repository a feature comes from, for the provenance footer
+//! of the issues sync creates.
+//!
+//! [`Provenance`] reads the git repository holding the roadmap: the
+//! commit checked out and, from the `origin` remote, the web address of
+//! the repository and its default branch, so each feature's [`Source`] can
+//! link to its heading at that commit, and relative links in descriptions
+//! can be made absolute.
+
+use std::path::{Path, PathBuf};
+
+use git2::{Repository, Status};
+use serde::Serialize;
+
+use gitscaffold_parser::{Feature, ForgeKind};
+
+/// A feature's place in the repository, as templates see it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Source {
+    /// The roadmap file, relative to the repository root.
+    pub file: String,
+    /// The line of the feature's heading; unknown for YAML roadmaps.
+    pub line: Option<usize>,
+    /// The full id of the commit checked out; `None` before the first.
+    pub commit: Option<String>,
+    pub short_commit: Option<String>,
+    /// The roadmap file differs from the commit.
+    pub modified: bool,
+    /// The feature's lines at the commit on the forge's website.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    repo: PathBuf,
+    /// The roadmap of features that were not aggregated from several files.
+    file: String,
+    commit: Option<String>,
+    modified: Vec<String>,
+    web_url: Option<String>,
+    /// The branch `origin/HEAD` points at.
+    default_branch: Option<String>,
+    forge: ForgeKind,
+}
+
+impl Provenance {
+    /// Read the repository holding the roadmap at `path`; `None` when it
+    /// is not in a git work tree.
+    pub fn discover(path: &Path, forge: ForgeKind) -> Option<Provenance> {
+        let absolute = path.canonicalize().ok()?;
+        let repository = Repository::discover(absolute.parent()?).ok()?;
+        let root = repository.workdir()?.canonicalize().ok()?;
+        let commit = repository
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .map(|commit| commit.id().to_string());
+        let modified = repository
+            .statuses(None)
+            .map(|statuses| {
+                statuses
+                    .iter()
+                    .filter(|entry| entry.status() != Status::CURRENT)
+                    .filter_map(|entry| entry.path().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let web_url = repository
+            .find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().and_then(web_url));
+        let default_branch = repository
+            .find_reference("refs/remotes/origin/HEAD")
+            .ok()
+            .and_then(|head| {
+                let target = head.symbolic_target()?;
+                Some(target.strip_prefix("refs/remotes/origin/")?.to_string())
+            });
+        let mut provenance = Provenance {
+            repo: root,
+            file: String::new(),
+            commit,
+            modified,
+            web_url,
+            default_branch,
+            forge,
+        };
+        provenance.file = provenance.relative(&absolute)?;
+        Some(provenance)
+    }
+
+    /// Where `feature` comes from, see [`Source`].
+    pub fn source(&self, feature: &Feature) -> Source {
+        let file = self.file_of(feature);
+        let line = feature.span.map(|span| span.start.line);
+        let url = self.commit.as_ref().and_then(|commit| {
+            let anchor = line.map(|line| format!("#L{}", line)).unwrap_or_default();
+            Some(self.blob_url(commit, true, &file)? + &anchor)
+        });
+        Source {
+            modified: self.modified.contains(&file),
+            line,
+            commit: self.commit.clone(),
+            short_commit: self
+                .commit
+                .as_ref()
+                .map(|c| c[..c.len().min(7)].to_string()),
+            url,
+            file,
+        }
+    }
+
+    /// The branch the `origin` remote defaults to, such as `main`, when the
+    /// clone knows it.
+    pub fn default_branch(&self) -> Option<&str> {
+        self.default_branch.as_deref()
+    }
+
+    /// `url`, a link in `feature`'s description, as a link to the file it
+    /// names on the forge's website at `reference`, a branch, tag or
+    /// commit. Paths are relative to the feature's roadmap file, or to the
+    /// repository root when they start with `/`. `None` for links that are
+    /// not relative paths, such as external links and `#anchors`, and for
+    /// paths outside the repository.
+    pub fn absolute_link(&self, feature: &Feature, url: &str, reference: &str) -> Option<String> {
+        if url.is_empty() || url.starts_with('#') || url.starts_with("//") || has_scheme(url) {
+            return None;
+        }
+        let (path, suffix) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+        let file = self.file_of(feature);
+        let mut parts: Vec<&str> = Vec::new();
+        if !path.starts_with('/') {
+            parts.extend(file.split('/'));
+            parts.pop();
+        }
+        for part in path.split('/') {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop()?;
+                }
+                part => parts.push(part),
+            }
+        }
+        let mut path = parts.join("/");
+        if url.ends_with('/') && !path.is_empty() {
+            path.push('/');
+        }
+        Some(self.blob_url(reference, false, &path)? + suffix)
+    }
+
+    /// The forge's web page for `path` in the repository at `reference`,
+    /// a commit when `commit` is set and a branch or tag otherwise.
+    fn blob_url(&self, reference: &str, commit: bool, path: &str) -> Option<String> {
+        let blob = match (self.forge, commit) {
+            (ForgeKind::GitHub, _) => "blob",
+            (ForgeKind::GitLab, _) => "-/blob",
+            (ForgeKind::Gitea, true) => "src/commit",
+            (ForgeKind::Gitea, false) => "src/branch",
+        };
+        let web = self.web_url.as_ref()?;
+        Some(format!("{}/{}/{}/{}", web, blob, reference, path))
+    }
+
+    /// The roadmap file `feature` comes from: its own file when it was
+    /// aggregated from several, otherwise the roadmap's.
+    fn file_of(&self, feature: &Feature) -> String {
+        feature
+            .file
+            .as_deref()
+            .and_then(|file| Path::new(file).canonicalize().ok())
+            .and_then(|file| self.relative(&file))
+            .unwrap_or_else(|| self.file.clone())
+    }
+
+    /// `path` relative to the repository root, with forward slashes.
+    fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.repo).ok()?;
+        let parts: Vec<String> = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Some(parts.join("/"))
+    }
+}
+
+/// Whether `url` starts with a scheme such as `https:` or `mailto:`.
+pub(crate) fn has_scheme(url: &str) -> bool {
+    url.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// The web address of the repository a remote URL points to:
+/// `https://host/owner/name` for HTTP(S), `git@host:owner/name` and
+/// `ssh://` remotes.
+fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    let (host, path) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+    {
+        rest.split_once('/')?
+    } else {
+        remote.split_once(':')?
+    };
+    // Drop credentials or the ssh user, and an ssh port.
+    let host = host.rsplit('@').next()?;
+    let host = match remote.starts_with("ssh://") {
+        true => host.split(':').next()?,
+        false => host,
+    };
+    if host.is_empty() || path.is_empty() || host.contains('/') {
+        return None;
+    }
+    Some(format!("https://{}/{}", host, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_web_urls_from_remotes() {
+        let expected = Some("https://github.com/acme/app".to_string());
+        assert_eq!(web_url("https://github.com/acme/app.git"), expected);
+        assert_eq!(web_url("https://token@github.com/acme/app"), expected);
+        assert_eq!(web_url("git@github.com:acme/app.git"), expected);
+        assert_eq!(web_url("ssh://git@github.com:22/acme/app.git"), expected);
+        assert_eq!(
+            web_url("https://gitlab.com/group/sub/app/"),
+            Some("https://gitlab.com/group/sub/app".into())
+        );
+        assert_eq!(web_url("/srv/git/app.git"), None);
+    }
+
+    #[test]
+    fn makes_relative_links_absolute() {
+        use crate::template::IssueTemplates;
+        use gitscaffold_parser::{Link, Roadmap};
+
+        let provenance = Provenance {
+            repo: PathBuf::from("/src/app"),
+            file: "plans/ROADMAP.md".into(),
+            commit: None,
+            modified: vec![],
+            web_url: Some("https://github.com/acme/app".into()),
+            default_branch: Some("main".into()),
+            forge: ForgeKind::GitHub,
+        };
+        let roadmap = Roadmap {
+            features: vec![Feature {
+                title: "Login".into(),
+                description: "See [design](./design.md#goals), [spec](../SPEC.md), \
+                              ![flow](/img/flow.png), [site](https://example.com), \
+                              [top](#top) and [out](../../x.md)."
+                    .into(),
+                links: vec![Link {
+                    text: "spec".into(),
+                    url: "../SPEC.md".into(),
+                    title: None,
+                }],
+                ..Feature::default()
+            }],
+            ..Roadmap::default()
+        };
+        let templates = IssueTemplates::default().with_absolute_links(provenance, "v2".into());
+        let texts = templates.render(&roadmap).unwrap();
+        assert_eq!(
+            texts[0].body,
+            "See [design](https://github.com/acme/app/blob/v2/plans/design.md#goals), \
+             [spec](https://github.com/acme/app/blob/v2/SPEC.md), \
+             ![flow](https://github.com/acme/app/blob/v2/img/flow.png), \
+             [site](https://example.com), [top](#top) and [out](../../x.md)."
+        );
+    }
+
+    #[test]
+    fn footer_links_the_feature_at_the_commit() {
+        use crate::template::IssueTemplates;
+        use gitscaffold_parser::{Position, Roadmap, Span};
+
+        let provenance = Provenance {
+            repo: PathBuf::from("/src/app"),
+            file: "docs/ROADMAP.md".into(),
+            commit: Some("abcdef0123456789".into()),
+            modified: vec![],
+            web_url: Some("https://gitlab.com/acme/app".into()),
+            default_branch: Some("main".into()),
+            forge: ForgeKind::GitLab,
+        };
+        let at = |line| Position {
+            line,
+            column: 1,
+            offset: 0,
+        };
+        let roadmap = Roadmap {
+            features: vec![Feature {
+                title: "Login".into(),
+                description: "Sign in.".into(),
+                span: Some(Span {
+                    start: at(120),
+                    end: at(124),
+                }),
+                ..Feature::default()
+            }],
+            ..Roadmap::default()
+        };
+        let texts = IssueTemplates::default()
+            .with_provenance(provenance)
+            .render(&roadmap)
+            .unwrap();
+        assert_eq!(
+            texts[0].body,
+            "Sign in.\n\n---\n<sub>Generated by gitscaffold from \
+             [docs/ROADMAP.md#L120](https://gitlab.com/acme/app/-/blob/abcdef0123456789/docs/ROADMAP.md#L120) \
+             at commit abcdef0</sub>"
+        );
+    }
+}
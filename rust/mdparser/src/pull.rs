@@ -0,0 +1,253 @@
+//! Carry the state of a roadmap's issues back into its Markdown, the
+//! reverse direction of [`crate::sync`].
+//!
+//! Each top-level feature is matched to its issue by the `[#12](url)` link
+//! after its heading, which pull adds the first time, then by the number
+//! recorded in the [`crate::state`], and last by the title sync gives its
+//! issue. A closed issue marks its feature `done`; an open
+//! one takes its status from a label naming one (`blocked`,
+//! `in-progress`, ...) and otherwise moves a `done` feature back to
+//! `in-progress`. Tasks checked off in the issue's `## Tasks` checklist are
+//! checked off in the roadmap; tasks are never unchecked.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use gitscaffold_parser::edit::{self, FeatureUpdate};
+use gitscaffold_parser::{Feature, FeatureStatus, IssueLink, Roadmap};
+
+use crate::github::RemoteIssue;
+use crate::import::split_body;
+use crate::state::RepoState;
+use crate::template::IssueText;
+
+/// One update to the roadmap.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Link {
+        /// Index into the roadmap's `features`.
+        feature: usize,
+        title: String,
+        issue: IssueLink,
+    },
+    Status {
+        feature: usize,
+        title: String,
+        from: Option<FeatureStatus>,
+        to: FeatureStatus,
+    },
+    CompleteTask {
+        feature: usize,
+        /// Index into the feature's `tasks`.
+        task: usize,
+        title: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Link { title, issue, .. } => {
+                write!(f, "link '{}' to #{}", title, issue.number)
+            }
+            Change::Status {
+                title, from, to, ..
+            } => {
+                write!(f, "set the status of '{}' to {}", title, to.name())?;
+                if let Some(from) = from {
+                    write!(f, " (was {})", from.name())?;
+                }
+                Ok(())
+            }
+            Change::CompleteTask { title, .. } => write!(f, "check off task '{}'", title),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Pull {
+    pub changes: Vec<Change>,
+    /// Titles of the features no issue was found for.
+    pub unmatched: Vec<String>,
+}
+
+/// Work out the changes that bring `roadmap` in line with `issues`.
+/// `texts` holds the issue title and body of each feature, in order, as
+/// for [`crate::sync::plan`], and `state` the repository's recorded issue
+/// numbers, if any.
+pub fn plan(
+    roadmap: &Roadmap,
+    texts: &[IssueText],
+    issues: &[RemoteIssue],
+    state: Option<&RepoState>,
+) -> Pull {
+    let issues: Vec<&RemoteIssue> = issues
+        .iter()
+        .filter(|issue| issue.pull_request.is_none())
+        .collect();
+    let by_number: HashMap<u64, &RemoteIssue> = issues.iter().map(|i| (i.number, *i)).collect();
+    let by_title: HashMap<&str, &RemoteIssue> =
+        issues.iter().map(|i| (i.title.trim(), *i)).collect();
+
+    let mut pull = Pull::default();
+    for (index, (feature, text)) in roadmap.features.iter().zip(texts).enumerate() {
+        let recorded = state.and_then(|state| state.feature(&feature.title));
+        let issue = match (&feature.issue, recorded) {
+            (Some(link), _) => by_number.get(&link.number),
+            (None, Some(number)) => by_number.get(&number),
+            (None, None) => by_title.get(text.title.trim()),
+        };
+        let Some(issue) = issue else {
+            pull.unmatched.push(feature.title.clone());
+            continue;
+        };
+        if feature.issue.is_none() {
+            if let Some(url) = &issue.html_url {
+                pull.changes.push(Change::Link {
+                    feature: index,
+                    title: feature.title.clone(),
+                    issue: IssueLink {
+                        number: issue.number,
+                        url: url.clone(),
+                    },
+                });
+            }
+        }
+        let to = status(feature, issue).filter(|to| Some(to) != feature.status.as_ref());
+        if let Some(to) = to {
+            pull.changes.push(Change::Status {
+                feature: index,
+                title: feature.title.clone(),
+                from: feature.status.clone(),
+                to,
+            });
+        }
+        let (_, checklist) = split_body(issue.body.as_deref().unwrap_or(""));
+        for (task_index, task) in feature.tasks.iter().enumerate() {
+            let checked = checklist
+                .iter()
+                .any(|item| item.completed && item.title == task.title.trim());
+            if checked && !task.completed {
+                pull.changes.push(Change::CompleteTask {
+                    feature: index,
+                    task: task_index,
+                    title: task.title.clone(),
+                });
+            }
+        }
+    }
+    pull
+}
+
+/// The status `issue` gives `feature`, if any.
+fn status(feature: &Feature, issue: &RemoteIssue) -> Option<FeatureStatus> {
+    if issue.state == "closed" {
+        return Some(FeatureStatus::Done);
+    }
+    let labeled = issue
+        .labels
+        .iter()
+        .map(|label| FeatureStatus::parse(&label.name))
+        .find(|status| status.is_known() && *status != FeatureStatus::Done);
+    match labeled {
+        Some(status) => Some(status),
+        None if feature.status == Some(FeatureStatus::Done) => Some(FeatureStatus::InProgress),
+        None => None,
+    }
+}
+
+/// Apply `changes` to the Markdown `source` that `roadmap` was parsed from.
+pub fn apply(source: &str, roadmap: &Roadmap, changes: &[Change]) -> String {
+    let mut updates: Vec<FeatureUpdate> = vec![FeatureUpdate::default(); roadmap.features.len()];
+    for change in changes {
+        match change {
+            Change::Link { feature, issue, .. } => updates[*feature].issue = Some(issue.clone()),
+            Change::Status { feature, to, .. } => updates[*feature].status = Some(to.clone()),
+            Change::CompleteTask { feature, task, .. } => updates[*feature].complete.push(*task),
+        }
+    }
+    let edits = roadmap
+        .features
+        .iter()
+        .zip(&updates)
+        .filter(|(_, update)| **update != FeatureUpdate::default())
+        .flat_map(|(feature, update)| edit::update_feature(source, feature, update))
+        .collect();
+    edit::apply(source, edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::RemoteLabel;
+    use crate::template::IssueTemplates;
+    use gitscaffold_parser::parse_markdown;
+
+    fn issue(number: u64, title: &str, state: &str, labels: &[&str], body: &str) -> RemoteIssue {
+        RemoteIssue {
+            number,
+            node_id: String::new(),
+            title: title.into(),
+            state: state.into(),
+            created_at: None,
+            closed_at: None,
+            body: Some(body.into()),
+            labels: labels
+                .iter()
+                .map(|name| RemoteLabel {
+                    name: name.to_string(),
+                    ..RemoteLabel::default()
+                })
+                .collect(),
+            assignees: Vec::new(),
+            milestone: None,
+            html_url: Some(format!("https://github.com/o/r/issues/{}", number)),
+            pull_request: None,
+        }
+    }
+
+    #[test]
+    fn links_issues_and_pulls_back_their_state() {
+        let source = "# P\n\n## Features\n\n### Login\n\n- [ ] Form\n- [ ] Tokens\n\n\
+                      ### Search [done] [#9](https://github.com/o/r/issues/9)\n\n### Docs\n\n\
+                      ### Export\n";
+        let roadmap = parse_markdown(source).unwrap();
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let issues = [
+            issue(
+                3,
+                "Login",
+                "open",
+                &["Blocked"],
+                "## Tasks\n\n- [x] Form\n- [ ] Tokens",
+            ),
+            // Renamed on the forge, but still linked by number.
+            issue(9, "Full-text search", "open", &[], ""),
+            // Renamed too, and recorded in the state.
+            issue(12, "CSV export", "closed", &[], ""),
+        ];
+        let mut state = RepoState::default();
+        state.features.insert("Export".into(), 12);
+        let pull = plan(&roadmap, &texts, &issues, Some(&state));
+        let summary: Vec<String> = pull.changes.iter().map(Change::to_string).collect();
+        assert_eq!(
+            summary,
+            [
+                "link 'Login' to #3",
+                "set the status of 'Login' to blocked",
+                "check off task 'Form'",
+                "set the status of 'Search' to in-progress (was done)",
+                "link 'Export' to #12",
+                "set the status of 'Export' to done",
+            ]
+        );
+        assert_eq!(pull.unmatched, ["Docs"]);
+        assert_eq!(
+            apply(source, &roadmap, &pull.changes),
+            "# P\n\n## Features\n\n### Login [blocked] [#3](https://github.com/o/r/issues/3)\n\n\
+             - [x] Form\n- [ ] Tokens\n\n\
+             ### Search [in-progress] [#9](https://github.com/o/r/issues/9)\n\n### Docs\n\n\
+             ### Export [done] [#12](https://github.com/o/r/issues/12)\n"
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Compare a roadmap against the milestones and issues in a GitHub repo.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::Serialize;
+
+use gitscaffold_parser::diff::similarity;
+use gitscaffold_parser::text::title_key as key;
+use gitscaffold_parser::Roadmap;
+
+use crate::github::{RemoteIssue, RemoteMilestone};
+use crate::marker::Marker;
+use crate::template::IssueText;
+
+/// Minimum similarity for an unmatched feature and issue to be reported as
+/// title drift instead of a missing and an untracked issue.
+const TITLE_DRIFT_THRESHOLD: f64 = 0.75;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Drift {
+    /// In the roadmap but not on GitHub.
+    MilestoneMissing {
+        name: String,
+    },
+    /// On GitHub but not in the roadmap.
+    MilestoneUntracked {
+        title: String,
+        number: u64,
+    },
+    MilestoneDueDate {
+        name: String,
+        number: u64,
+        local: Option<String>,
+        remote: Option<String>,
+    },
+    IssueMissing {
+        title: String,
+    },
+    IssueUntracked {
+        title: String,
+        number: u64,
+        state: String,
+    },
+    IssueTitle {
+        local: String,
+        remote: String,
+        number: u64,
+    },
+    IssueLabels {
+        title: String,
+        number: u64,
+        missing: Vec<String>,
+        extra: Vec<String>,
+    },
+    IssueMilestone {
+        title: String,
+        number: u64,
+        local: Option<String>,
+        remote: Option<String>,
+    },
+    /// The body no longer hashes to the marker sync ended it with.
+    IssueEdited {
+        title: String,
+        number: u64,
+    },
+}
+
+impl Drift {
+    /// `+` for what sync would create, `-` for what only exists remotely
+    /// and `~` for drift on matched entities.
+    pub fn marker(&self) -> char {
+        match self {
+            Drift::MilestoneMissing { .. } | Drift::IssueMissing { .. } => '+',
+            Drift::MilestoneUntracked { .. } | Drift::IssueUntracked { .. } => '-',
+            _ => '~',
+        }
+    }
+}
+
+fn or_none(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("(none)")
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::MilestoneMissing { name } => {
+                write!(f, "milestone '{}' is missing on GitHub", name)
+            }
+            Drift::MilestoneUntracked { title, number } => {
+                write!(f, "milestone #{} '{}' is not in the roadmap", number, title)
+            }
+            Drift::MilestoneDueDate {
+                name,
+                number,
+                local,
+                remote,
+            } => write!(
+                f,
+                "milestone #{} '{}' due date: roadmap {}, GitHub {}",
+                number,
+                name,
+                or_none(local),
+                or_none(remote)
+            ),
+            Drift::IssueMissing { title } => write!(f, "issue '{}' is missing on GitHub", title),
+            Drift::IssueUntracked {
+                title,
+                number,
+                state,
+            } => write!(
+                f,
+                "{} issue #{} '{}' is not in the roadmap",
+                state, number, title
+            ),
+            Drift::IssueTitle {
+                local,
+                remote,
+                number,
+            } => write!(
+                f,
+                "issue #{} title: roadmap '{}', GitHub '{}'",
+                number, local, remote
+            ),
+            Drift::IssueLabels {
+                title,
+                number,
+                missing,
+                extra,
+            } => write!(
+                f,
+                "issue #{} '{}' labels: missing [{}], extra [{}]",
+                number,
+                title,
+                missing.join(", "),
+                extra.join(", ")
+            ),
+            Drift::IssueMilestone {
+                title,
+                number,
+                local,
+                remote,
+            } => write!(
+                f,
+                "issue #{} '{}' milestone: roadmap {}, GitHub {}",
+                number,
+                title,
+                or_none(local),
+                or_none(remote)
+            ),
+            Drift::IssueEdited { title, number } => write!(
+                f,
+                "issue #{} '{}' body was edited since sync wrote it",
+                number, title
+            ),
+        }
+    }
+}
+
+/// How the roadmap differs from the repository. Features are matched to
+/// issues by `texts`, their rendered issue titles and bodies, in order, so
+/// configured title prefixes are not reported as drift.
+pub fn diff_remote(
+    roadmap: &Roadmap,
+    texts: &[IssueText],
+    milestones: &[RemoteMilestone],
+    issues: &[RemoteIssue],
+) -> Vec<Drift> {
+    let mut drift = Vec::new();
+
+    let mut seen_milestones = HashSet::new();
+    for milestone in &roadmap.milestones {
+        match milestones
+            .iter()
+            .find(|m| key(&m.title) == key(&milestone.name))
+        {
+            Some(remote) => {
+                seen_milestones.insert(remote.number);
+                let remote_due = remote.due_on.as_ref().map(|d| d.chars().take(10).collect());
+                if milestone.due_date.is_some() && milestone.due_date != remote_due {
+                    drift.push(Drift::MilestoneDueDate {
+                        name: milestone.name.clone(),
+                        number: remote.number,
+                        local: milestone.due_date.clone(),
+                        remote: remote_due,
+                    });
+                }
+            }
+            None => drift.push(Drift::MilestoneMissing {
+                name: milestone.name.clone(),
+            }),
+        }
+    }
+    drift.extend(
+        milestones
+            .iter()
+            .filter(|m| !seen_milestones.contains(&m.number))
+            .map(|m| Drift::MilestoneUntracked {
+                title: m.title.clone(),
+                number: m.number,
+            }),
+    );
+
+    // Issues created for tasks (e.g. by the Python sync) are tracked too.
+    let task_titles: HashSet<String> = roadmap
+        .features
+        .iter()
+        .flat_map(|f| f.tasks.iter().map(|t| key(&t.title)))
+        .collect();
+    let mut seen_issues = HashSet::new();
+    let mut unmatched = Vec::new();
+    for (feature, text) in roadmap.features.iter().zip(texts) {
+        match issues.iter().find(|i| key(&i.title) == key(&text.title)) {
+            Some(issue) => {
+                seen_issues.insert(issue.number);
+                compare_issue(feature, issue, &mut drift);
+            }
+            None => unmatched.push((feature, text)),
+        }
+    }
+    for (feature, text) in unmatched {
+        let closest = issues
+            .iter()
+            .filter(|i| !seen_issues.contains(&i.number) && !task_titles.contains(&key(&i.title)))
+            .map(|i| (i, similarity(&text.title, &i.title)))
+            .filter(|&(_, score)| score >= TITLE_DRIFT_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match closest {
+            Some((issue, _)) => {
+                seen_issues.insert(issue.number);
+                drift.push(Drift::IssueTitle {
+                    local: text.title.clone(),
+                    remote: issue.title.clone(),
+                    number: issue.number,
+                });
+                compare_issue(feature, issue, &mut drift);
+            }
+            None => drift.push(Drift::IssueMissing {
+                title: text.title.clone(),
+            }),
+        }
+    }
+    drift.extend(
+        issues
+            .iter()
+            .filter(|i| !seen_issues.contains(&i.number) && !task_titles.contains(&key(&i.title)))
+            .map(|i| Drift::IssueUntracked {
+                title: i.title.clone(),
+                number: i.number,
+                state: i.state.clone(),
+            }),
+    );
+    drift
+}
+
+fn compare_issue(
+    feature: &gitscaffold_parser::Feature,
+    issue: &RemoteIssue,
+    drift: &mut Vec<Drift>,
+) {
+    let remote_labels: Vec<String> = issue.labels.iter().map(|l| l.name.clone()).collect();
+    let missing: Vec<String> = feature
+        .labels
+        .iter()
+        .filter(|l| !remote_labels.contains(l))
+        .cloned()
+        .collect();
+    let extra: Vec<String> = remote_labels
+        .iter()
+        .filter(|l| !feature.labels.contains(l))
+        .cloned()
+        .collect();
+    if !missing.is_empty() || !extra.is_empty() {
+        drift.push(Drift::IssueLabels {
+            title: feature.title.clone(),
+            number: issue.number,
+            missing,
+            extra,
+        });
+    }
+    let remote_milestone = issue.milestone.as_ref().map(|m| m.title.clone());
+    if feature.milestone.as_deref().map(key) != remote_milestone.as_deref().map(key) {
+        drift.push(Drift::IssueMilestone {
+            title: feature.title.clone(),
+            number: issue.number,
+            local: feature.milestone.clone(),
+            remote: remote_milestone,
+        });
+    }
+    let body = issue.body.as_deref().unwrap_or("");
+    if Marker::find(body).is_some_and(|marker| !marker.matches(body)) {
+        drift.push(Drift::IssueEdited {
+            title: feature.title.clone(),
+            number: issue.number,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::RemoteLabel;
+    use crate::marker::stamp;
+    use crate::template::IssueTemplates;
+
+    fn milestone(number: u64, title: &str, due_on: Option<&str>) -> RemoteMilestone {
+        RemoteMilestone {
+            number,
+            title: title.into(),
+            state: "open".into(),
+            due_on: due_on.map(str::to_string),
+            description: None,
+        }
+    }
+
+    fn issue(
+        number: u64,
+        title: &str,
+        labels: &[&str],
+        milestone: Option<RemoteMilestone>,
+    ) -> RemoteIssue {
+        RemoteIssue {
+            number,
+            title: title.into(),
+            state: "open".into(),
+            body: None,
+            labels: labels
+                .iter()
+                .map(|name| RemoteLabel {
+                    name: name.to_string(),
+                    ..RemoteLabel::default()
+                })
+                .collect(),
+            assignees: Vec::new(),
+            milestone,
+            html_url: None,
+            pull_request: None,
+            node_id: String::new(),
+            created_at: None,
+            closed_at: None,
+        }
+    }
+
+    #[test]
+    fn reports_missing_untracked_and_drifted_entities() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Milestones\n- M1 — 2025-01-31\n- M2\n\n## Features\n\n\
+             ### Login page\nMilestone: M1\nLabels: auth\n\n- [ ] Form\n\n### Search\n\n### Export\n",
+        )
+        .unwrap();
+        let m1 = milestone(1, "M1", Some("2025-02-28T08:00:00Z"));
+        let remote_milestones = vec![m1.clone(), milestone(7, "Old", None)];
+        let search = RemoteIssue {
+            body: Some(stamp("Find things.", "search").replace("things", "stuff")),
+            ..issue(11, "Search", &[], None)
+        };
+        let remote_issues = vec![
+            issue(10, "Login Pages", &["auth", "ui"], Some(m1)),
+            search,
+            issue(12, "Form", &[], None),
+            issue(13, "Unrelated", &[], None),
+        ];
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let drift = diff_remote(&roadmap, &texts, &remote_milestones, &remote_issues);
+        assert_eq!(
+            drift,
+            vec![
+                Drift::MilestoneDueDate {
+                    name: "M1".into(),
+                    number: 1,
+                    local: Some("2025-01-31".into()),
+                    remote: Some("2025-02-28".into()),
+                },
+                Drift::MilestoneMissing { name: "M2".into() },
+                Drift::MilestoneUntracked {
+                    title: "Old".into(),
+                    number: 7
+                },
+                Drift::IssueEdited {
+                    title: "Search".into(),
+                    number: 11,
+                },
+                Drift::IssueTitle {
+                    local: "Login page".into(),
+                    remote: "Login Pages".into(),
+                    number: 10,
+                },
+                Drift::IssueLabels {
+                    title: "Login page".into(),
+                    number: 10,
+                    missing: vec![],
+                    extra: vec!["ui".into()],
+                },
+                Drift::IssueMissing {
+                    title: "Export".into()
+                },
+                Drift::IssueUntracked {
+                    title: "Unrelated".into(),
+                    number: 13,
+                    state: "open".into(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Files as they were at an earlier point in their git history.
+//!
+//! A point is named by a revision, such as a tag, or by a date, meaning
+//! the last commit on `HEAD`'s first-parent history made before that day.
+
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use git2::{Oid, Repository};
+
+/// An earlier point in the history of the repository holding a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Since {
+    /// The commit at that point; `None` for a date before the first
+    /// commit.
+    pub commit: Option<String>,
+    /// The time of the commit, or the start of the date.
+    pub time: DateTime<Utc>,
+}
+
+/// The point `since`, a revision or a `YYYY-MM-DD` date, in the history of
+/// the repository holding `dir`.
+pub fn resolve(dir: &Path, since: &str) -> Result<Since, git2::Error> {
+    let repository = Repository::discover(dir)?;
+    let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") else {
+        let commit = repository.revparse_single(since)?.peel_to_commit()?;
+        let time = DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default();
+        return Ok(Since {
+            commit: Some(commit.id().to_string()),
+            time,
+        });
+    };
+    let time = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let mut commit = repository.head()?.peel_to_commit()?;
+    loop {
+        if commit.time().seconds() < time.timestamp() {
+            return Ok(Since {
+                commit: Some(commit.id().to_string()),
+                time,
+            });
+        }
+        match commit.parent(0) {
+            Ok(parent) => commit = parent,
+            Err(_) => return Ok(Since { commit: None, time }),
+        }
+    }
+}
+
+/// The text of the file at `path` in `commit`; `None` when the commit does
+/// not have it.
+pub fn read_at(path: &Path, commit: &str) -> Result<Option<String>, git2::Error> {
+    let absolute = path
+        .canonicalize()
+        .map_err(|e| git2::Error::from_str(&format!("{}: {}", path.display(), e)))?;
+    let repository = Repository::discover(absolute.parent().unwrap_or(&absolute))?;
+    let root = repository
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("the repository has no work tree"))?
+        .canonicalize()
+        .map_err(|e| git2::Error::from_str(&e.to_string()))?;
+    let relative = absolute
+        .strip_prefix(&root)
+        .map_err(|_| git2::Error::from_str("the file is outside the repository"))?;
+    let tree = repository.find_commit(Oid::from_str(commit)?)?.tree()?;
+    let entry = match tree.get_path(relative) {
+        Ok(entry) => entry,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let blob = entry.to_object(&repository)?.peel_to_blob()?;
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reads_files_at_tags_and_dates() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-revision-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let repository = Repository::init(&dir).unwrap();
+        let signature = git2::Signature::new(
+            "Test",
+            "test@example.com",
+            &git2::Time::new(1_719_748_800, 0), // 2024-06-30T12:00:00Z
+        )
+        .unwrap();
+        let path = dir.join("ROADMAP.md");
+        fs::write(&path, "# v1\n").unwrap();
+        let mut index = repository.index().unwrap();
+        index.add_path(Path::new("ROADMAP.md")).unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let first = repository
+            .commit(Some("HEAD"), &signature, &signature, "First", &tree, &[])
+            .unwrap();
+        repository
+            .tag_lightweight("v1", &repository.find_object(first, None).unwrap(), false)
+            .unwrap();
+
+        let since = resolve(&dir, "v1").unwrap();
+        assert_eq!(since.commit, Some(first.to_string()));
+        assert_eq!(
+            read_at(&path, &first.to_string()).unwrap().unwrap(),
+            "# v1\n"
+        );
+        fs::write(dir.join("NEW.md"), "# New\n").unwrap();
+        assert_eq!(
+            read_at(&dir.join("NEW.md"), &first.to_string()).unwrap(),
+            None
+        );
+        assert_eq!(resolve(&dir, "2024-07-01").unwrap().commit, since.commit);
+        assert_eq!(resolve(&dir, "2024-06-30").unwrap().commit, None);
+        assert!(resolve(&dir, "nope").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+//! Progress comments on parent issues.
+//!
+//! Sync links a feature's task issues from the parent's task list, one
+//! `- [ ] #12` line each. A rollup is one comment on the parent listing
+//! those children with their state and how many are closed, kept up to
+//! date in place: it starts with a hidden [`MARKER`] that finds it again.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::github::{RemoteComment, RemoteIssue};
+
+/// The hidden first line of every rollup comment.
+pub const MARKER: &str = "<!-- gitscaffold:rollup -->";
+
+/// A parent issue and the child issues its task list references.
+#[derive(Debug, Clone)]
+pub struct Rollup<'a> {
+    pub parent: &'a RemoteIssue,
+    pub children: Vec<&'a RemoteIssue>,
+}
+
+impl Rollup<'_> {
+    /// How many children are closed.
+    pub fn closed(&self) -> usize {
+        self.children
+            .iter()
+            .filter(|child| child.state == "closed")
+            .count()
+    }
+
+    /// The share of children closed, rounded down so that 100 means all.
+    pub fn percent(&self) -> usize {
+        match self.children.len() {
+            0 => 0,
+            total => self.closed() * 100 / total,
+        }
+    }
+
+    /// `2 of 3 sub-issues closed (66%)`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} of {} sub-issues closed ({}%)",
+            self.closed(),
+            self.children.len(),
+            self.percent()
+        )
+    }
+
+    /// The comment's text: the marker, the summary and a checklist of the
+    /// children in the order the parent lists them.
+    pub fn comment(&self) -> String {
+        let mut lines = vec![
+            MARKER.to_string(),
+            format!("**Progress:** {}", self.summary()),
+            String::new(),
+        ];
+        for child in &self.children {
+            let check = if child.state == "closed" { 'x' } else { ' ' };
+            lines.push(format!("- [{}] #{} {}", check, child.number, child.title));
+        }
+        lines.join("\n")
+    }
+}
+
+/// A rollup for each issue among `issues` whose task list references other
+/// issues among them, by issue number.
+pub fn rollups(issues: &[RemoteIssue]) -> Vec<Rollup<'_>> {
+    let by_number: HashMap<u64, &RemoteIssue> =
+        issues.iter().map(|issue| (issue.number, issue)).collect();
+    let mut rollups: Vec<Rollup> = issues
+        .iter()
+        .filter_map(|parent| {
+            let children: Vec<&RemoteIssue> = references(parent.body.as_deref().unwrap_or(""))
+                .into_iter()
+                .filter(|&number| number != parent.number)
+                .filter_map(|number| by_number.get(&number).copied())
+                .collect();
+            (!children.is_empty()).then_some(Rollup { parent, children })
+        })
+        .collect();
+    rollups.sort_by_key(|rollup| rollup.parent.number);
+    rollups
+}
+
+/// The rollup comment among `comments`, if one was posted.
+pub fn find_comment(comments: &[RemoteComment]) -> Option<&RemoteComment> {
+    comments
+        .iter()
+        .find(|comment| comment.body.trim_start().starts_with(MARKER))
+}
+
+/// The issue numbers of task-list items that are nothing but a reference,
+/// `- [ ] #12` or `- [x] #12`, each once and in order.
+fn references(body: &str) -> Vec<u64> {
+    let mut seen = BTreeSet::new();
+    body.lines()
+        .filter_map(|line| {
+            let item = line.trim();
+            let text = ["- [ ] ", "- [x] ", "- [X] ", "* [ ] ", "* [x] ", "* [X] "]
+                .iter()
+                .find_map(|prefix| item.strip_prefix(prefix))?;
+            text.trim().strip_prefix('#')?.parse().ok()
+        })
+        .filter(|&number| seen.insert(number))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_children_from_the_parent_task_list() {
+        let issue = |number: u64, title: &str, state: &str, body: &str| -> RemoteIssue {
+            serde_json::from_value(serde_json::json!({
+                "number": number, "title": title, "state": state, "body": body,
+            }))
+            .unwrap()
+        };
+        let issues = vec![
+            issue(
+                7,
+                "Login",
+                "open",
+                "Sign in.\n\n- [x] #9\n- [ ] #8\n- [ ] Remember me\n- [ ] #99\n- [ ] #8\n",
+            ),
+            issue(8, "Login / Form", "open", "See #7."),
+            issue(9, "Login / Session", "closed", ""),
+            issue(10, "Search", "open", "- [ ] Index\n"),
+        ];
+        let rollups = rollups(&issues);
+        assert_eq!(rollups.len(), 1);
+        assert_eq!(rollups[0].parent.number, 7);
+        assert_eq!(
+            rollups[0].comment(),
+            "<!-- gitscaffold:rollup -->\n\
+             **Progress:** 1 of 2 sub-issues closed (50%)\n\n\
+             - [x] #9 Login / Session\n\
+             - [ ] #8 Login / Form"
+        );
+
+        let comments: Vec<RemoteComment> = serde_json::from_value(serde_json::json!([
+            {"id": 1, "body": "Looks good"},
+            {"id": 2, "body": rollups[0].comment()},
+        ]))
+        .unwrap();
+        assert_eq!(find_comment(&comments).map(|c| c.id), Some(2));
+    }
+}
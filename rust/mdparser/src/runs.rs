@@ -0,0 +1,199 @@
+//! The milestones and issues each sync run created, kept in
+//! `.gitscaffold/runs/` so that `scaffold rollback` can undo a run.
+//!
+//! Every run that creates something is recorded in a file of its own,
+//! named by its id: the time it finished, in UTC, such as
+//! `20240630T120000Z`. Runs that only found, renamed, updated or closed
+//! issues have nothing to roll back and are not recorded.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ScaffoldError;
+use crate::state::RepoState;
+use crate::sync::{SyncReport, Synced};
+
+/// Version of the run file format.
+pub const RUN_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Run {
+    pub version: u32,
+    pub id: String,
+    pub repo: String,
+    /// When the run finished, in RFC 3339.
+    pub finished: String,
+    /// The milestones the run created.
+    #[serde(default)]
+    pub milestones: Vec<Synced>,
+    /// The issues the run created.
+    #[serde(default)]
+    pub issues: Vec<Synced>,
+}
+
+impl Run {
+    /// Where the command-line tools keep runs, relative to the current
+    /// directory.
+    pub const DEFAULT_DIR: &'static str = ".gitscaffold/runs";
+
+    /// The run to `repo` that made `report`, finished at `finished`; `None`
+    /// when it created nothing.
+    pub fn new(repo: &str, report: &SyncReport, finished: DateTime<Utc>) -> Option<Run> {
+        let created = |items: &[Synced]| -> Vec<Synced> {
+            items.iter().filter(|item| item.created).cloned().collect()
+        };
+        let run = Run {
+            version: RUN_VERSION,
+            id: finished.format("%Y%m%dT%H%M%SZ").to_string(),
+            repo: repo.to_string(),
+            finished: finished.to_rfc3339_opts(SecondsFormat::Secs, true),
+            milestones: created(&report.milestones),
+            issues: created(&report.issues),
+        };
+        (!run.milestones.is_empty() || !run.issues.is_empty()).then_some(run)
+    }
+
+    fn path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.json", id))
+    }
+
+    /// Write the run to `dir`, creating it. A run finished in the same
+    /// second as one already there gets a numbered id, e.g.
+    /// `20240630T120000Z-2`.
+    pub fn save(&mut self, dir: &Path) -> Result<(), ScaffoldError> {
+        let display = dir.display().to_string();
+        fs::create_dir_all(dir).map_err(|e| ScaffoldError::io(&display, e))?;
+        let base = self.id.clone();
+        let mut n = 1;
+        while Run::path(dir, &self.id).exists() {
+            n += 1;
+            self.id = format!("{}-{}", base, n);
+        }
+        let path = Run::path(dir, &self.id);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json + "\n").map_err(|e| ScaffoldError::io(&path.display().to_string(), e))
+    }
+
+    /// Read the run `id` from `dir`.
+    pub fn load(dir: &Path, id: &str) -> Result<Run, ScaffoldError> {
+        let path = Run::path(dir, id);
+        let display = path.display().to_string();
+        let content = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => ScaffoldError::Usage(format!(
+                "no sync run '{}' is recorded in {}",
+                id,
+                dir.display()
+            )),
+            _ => ScaffoldError::io(&display, e),
+        })?;
+        let run: Run =
+            serde_json::from_str(&content).map_err(|e| ScaffoldError::parse(Some(&display), e))?;
+        if run.version != RUN_VERSION {
+            return Err(ScaffoldError::parse(
+                Some(&display),
+                format!("unsupported run version {}", run.version),
+            ));
+        }
+        Ok(run)
+    }
+
+    /// The runs recorded in `dir`, oldest first; none when it does not
+    /// exist.
+    pub fn list(dir: &Path) -> Result<Vec<Run>, ScaffoldError> {
+        let display = dir.display().to_string();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(ScaffoldError::io(&display, e)),
+        };
+        let mut runs = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| ScaffoldError::io(&display, e))?.path();
+            let Some(id) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            runs.push(Run::load(dir, id)?);
+        }
+        // `-10` comes after `-9`.
+        runs.sort_by(|a, b| {
+            (&a.finished, a.id.len(), &a.id).cmp(&(&b.finished, b.id.len(), &b.id))
+        });
+        Ok(runs)
+    }
+
+    /// Remove the run from `dir` once it has been rolled back.
+    pub fn remove(&self, dir: &Path) -> Result<(), ScaffoldError> {
+        let path = Run::path(dir, &self.id);
+        fs::remove_file(&path).map_err(|e| ScaffoldError::io(&path.display().to_string(), e))
+    }
+
+    /// Forget the milestones and issues of the run in the repository's
+    /// state, so the next sync creates them afresh.
+    pub fn forget(&self, state: &mut RepoState) {
+        state
+            .milestones
+            .retain(|_, number| !self.milestones.iter().any(|m| m.number == *number));
+        state
+            .features
+            .retain(|_, number| !self.issues.iter().any(|i| i.number == *number));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synced(title: &str, number: u64, created: bool) -> Synced {
+        Synced {
+            title: title.into(),
+            number,
+            created,
+            feature: None,
+            renamed_from: None,
+            parent: None,
+        }
+    }
+
+    #[test]
+    fn records_only_what_a_run_created() {
+        let finished = "2024-06-30T12:00:00Z".parse().unwrap();
+        assert_eq!(Run::new("o/r", &SyncReport::default(), finished), None);
+        let report = SyncReport {
+            milestones: vec![synced("v1", 1, false)],
+            issues: vec![synced("Login", 7, true), synced("Search", 3, false)],
+            ..SyncReport::default()
+        };
+        let dir = std::env::temp_dir().join(format!("gitscaffold-runs-{}", std::process::id()));
+        let mut first = Run::new("o/r", &report, finished).unwrap();
+        assert_eq!(first.issues, vec![synced("Login", 7, true)]);
+        first.save(&dir).unwrap();
+        let mut second = first.clone();
+        second.save(&dir).unwrap();
+        assert_eq!(
+            (first.id.as_str(), second.id.as_str()),
+            ("20240630T120000Z", "20240630T120000Z-2")
+        );
+        assert_eq!(Run::load(&dir, &second.id).unwrap(), second);
+        assert_eq!(Run::list(&dir).unwrap(), vec![first.clone(), second]);
+        assert!(matches!(
+            Run::load(&dir, "nope"),
+            Err(ScaffoldError::Usage(_))
+        ));
+
+        let mut state = RepoState::default();
+        state.features.insert("Login".into(), 7);
+        state.features.insert("Search".into(), 3);
+        first.forget(&mut state);
+        assert_eq!(state.feature("Login"), None);
+        assert_eq!(state.feature("Search"), Some(3));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,274 @@
+//! Mark roadmap work done from the commits that finished it.
+//!
+//! [`commits`] reads recent history with git2, and [`plan`] looks through
+//! each message for the closing keywords GitHub understands (`closes`,
+//! `fixes`, `resolves` and their other forms). A keyword followed by an
+//! issue number (`Fixes #12`) marks the feature linked to, or recorded in
+//! the [`crate::state`] for, that issue `done`; one followed by a colon
+//! and text (`Closes: Login form`) or a quoted title marks the feature, or
+//! else the task, with that title. The changes are the same as
+//! [`crate::pull`]'s, so [`crate::pull::apply`] writes them into the
+//! Markdown.
+
+use std::path::Path;
+
+use git2::{Repository, Sort};
+
+use gitscaffold_parser::{FeatureStatus, Roadmap};
+
+use crate::pull::Change;
+use crate::state::RepoState;
+
+const KEYWORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+/// A commit, as far as scanning is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub id: String,
+    pub message: String,
+}
+
+impl Commit {
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(7)]
+    }
+
+    /// The first line of the message.
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or("").trim()
+    }
+}
+
+/// A change and the commit it comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Found {
+    pub change: Change,
+    pub commit: Commit,
+}
+
+/// What a closing keyword refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reference {
+    Issue(u64),
+    Title(String),
+}
+
+/// Up to `limit` commits reachable from `HEAD` in the repository holding
+/// `dir`, newest first, stopping at `since` (a revision such as a tag or
+/// `HEAD~20`) when given.
+pub fn commits(dir: &Path, since: Option<&str>, limit: usize) -> Result<Vec<Commit>, git2::Error> {
+    let repository = Repository::discover(dir)?;
+    let mut walk = repository.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.push_head()?;
+    if let Some(since) = since {
+        walk.hide(repository.revparse_single(since)?.peel_to_commit()?.id())?;
+    }
+    let mut commits = Vec::new();
+    for id in walk.take(limit) {
+        let commit = repository.find_commit(id?)?;
+        commits.push(Commit {
+            id: commit.id().to_string(),
+            message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+        });
+    }
+    Ok(commits)
+}
+
+/// The changes `commits` make to `roadmap`, each once, attributed to the
+/// oldest commit that makes it. `state` supplies issue numbers that are not
+/// linked in the roadmap.
+pub fn plan(roadmap: &Roadmap, commits: &[Commit], state: Option<&RepoState>) -> Vec<Found> {
+    let mut found: Vec<Found> = Vec::new();
+    for commit in commits.iter().rev() {
+        for reference in references(&commit.message) {
+            let Some(change) = resolve(roadmap, state, &reference) else {
+                continue;
+            };
+            if !found.iter().any(|f| f.change == change) {
+                found.push(Found {
+                    change,
+                    commit: commit.clone(),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// The change that closing `reference` makes, if it names unfinished work.
+fn resolve(roadmap: &Roadmap, state: Option<&RepoState>, reference: &Reference) -> Option<Change> {
+    let features = &roadmap.features;
+    let feature = match reference {
+        Reference::Issue(number) => features.iter().position(|feature| {
+            let recorded = state.and_then(|state| state.feature(&feature.title));
+            feature.issue.as_ref().map(|link| link.number).or(recorded) == Some(*number)
+        }),
+        Reference::Title(title) => features
+            .iter()
+            .position(|feature| feature.title.trim().eq_ignore_ascii_case(title)),
+    };
+    if let Some(index) = feature {
+        let feature = &features[index];
+        if feature.status == Some(FeatureStatus::Done) {
+            return None;
+        }
+        return Some(Change::Status {
+            feature: index,
+            title: feature.title.clone(),
+            from: feature.status.clone(),
+            to: FeatureStatus::Done,
+        });
+    }
+    let Reference::Title(title) = reference else {
+        return None;
+    };
+    features.iter().enumerate().find_map(|(index, feature)| {
+        let task = feature
+            .tasks
+            .iter()
+            .position(|task| task.title.trim().eq_ignore_ascii_case(title))?;
+        (!feature.tasks[task].completed).then(|| Change::CompleteTask {
+            feature: index,
+            task,
+            title: feature.tasks[task].title.clone(),
+        })
+    })
+}
+
+/// What the closing keywords in `message` refer to: `#12` after a keyword
+/// is an issue, a quoted or backticked title after one is that title, and
+/// so is the rest of the line after a colon (`Closes: Login form`). Other
+/// text after a keyword, as in `Fix login`, is an ordinary sentence, not a
+/// reference.
+fn references(message: &str) -> Vec<Reference> {
+    let mut found = Vec::new();
+    for line in message.lines() {
+        let mut rest = line;
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[start..];
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+            if !KEYWORDS
+                .iter()
+                .any(|k| word.trim_end_matches(':').eq_ignore_ascii_case(k))
+            {
+                continue;
+            }
+            let mut target = rest.trim_start();
+            let colon = word.ends_with(':') || target.starts_with(':');
+            target = target.trim_start_matches(':').trim_start();
+            if let Some(number) = target.strip_prefix('#') {
+                let digits: String = number.chars().take_while(char::is_ascii_digit).collect();
+                if let Ok(number) = digits.parse() {
+                    found.push(Reference::Issue(number));
+                }
+                continue;
+            }
+            if let Some((title, after)) = quoted(target) {
+                found.push(Reference::Title(title.to_string()));
+                rest = after;
+                continue;
+            }
+            if colon {
+                let title = target.trim_end_matches('.').trim();
+                if !title.is_empty() {
+                    found.push(Reference::Title(title.to_string()));
+                }
+                break;
+            }
+        }
+    }
+    found
+}
+
+/// The non-empty title `text` starts with in double quotes, single quotes
+/// or backticks, and the text after it.
+fn quoted(text: &str) -> Option<(&str, &str)> {
+    let quote = text
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let (title, after) = text[1..].split_once(quote)?;
+    let title = title.trim();
+    (!title.is_empty()).then_some((title, after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::extract::parse_roadmap;
+
+    fn commit(id: &str, message: &str) -> Commit {
+        Commit {
+            id: id.into(),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn finds_closing_keywords() {
+        assert_eq!(
+            references("Add form\n\nCloses #12 and fixes #3.\nResolves: \"Login form\"."),
+            [
+                Reference::Issue(12),
+                Reference::Issue(3),
+                Reference::Title("Login form".into())
+            ]
+        );
+        assert_eq!(
+            references("Fixes `Search` and closes 'Export', fixes #7"),
+            [
+                Reference::Title("Search".into()),
+                Reference::Title("Export".into()),
+                Reference::Issue(7)
+            ]
+        );
+        assert!(references("Close the dialog on escape").is_empty());
+        assert!(references("Fix login").is_empty());
+        assert!(references("Disclose the fixture").is_empty());
+    }
+
+    #[test]
+    fn marks_features_and_tasks_done_once() {
+        let roadmap = parse_roadmap(
+            "# Plan\n\n## Features\n\n### Login [#12](https://example.com/12)\n\n\
+             - [ ] Form\n- [x] Tokens\n\n### Search\nStatus: done\n",
+        );
+        let commits = [
+            commit("bbbbbbbbb", "Fix #12 for good"),
+            commit(
+                "aaaaaaaaa",
+                "Build the form\n\nCloses: form\nFixes #12\nfixes Search",
+            ),
+        ];
+        let found = plan(&roadmap, &commits, None);
+        let summary: Vec<(String, &str)> = found
+            .iter()
+            .map(|f| (f.change.to_string(), f.commit.short_id()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("check off task 'Form'".to_string(), "aaaaaaa"),
+                ("set the status of 'Login' to done".to_string(), "aaaaaaa"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ordinary_messages_change_nothing() {
+        let roadmap = parse_roadmap("# Plan\n\n## Features\n\n### Login\n\n- [ ] Form\n");
+        let commits = [
+            commit("aaaaaaaaa", "Fix login"),
+            commit(
+                "bbbbbbbbb",
+                "fixes form validation\n\nResolves the flaky test",
+            ),
+        ];
+        assert!(plan(&roadmap, &commits, None).is_empty());
+    }
+}
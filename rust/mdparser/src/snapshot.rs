@@ -0,0 +1,196 @@
+//! Copies of the parsed roadmap kept in `.gitscaffold/snapshots/`, so that
+//! `scaffold history diff` can tell what changed in the plan between two
+//! points in time, whether or not the roadmap lives in git.
+//!
+//! Each snapshot is a file of its own, named by its id: the time it was
+//! taken, in UTC, and a hash of the roadmap it holds, such as
+//! `20240630T120000Z-8f8cbb7dcf46`. Source spans are left out of the copy
+//! and of the hash, so moving text around without changing it does not
+//! make a new snapshot.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use gitscaffold_parser::Roadmap;
+
+use crate::error::ScaffoldError;
+
+/// Version of the snapshot file format.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub id: String,
+    /// When the snapshot was taken, in RFC 3339.
+    pub taken: String,
+    /// The start of the SHA-256 hash of the roadmap's JSON, in hex.
+    pub hash: String,
+    /// The files the roadmap was read from.
+    pub source: String,
+    pub roadmap: Roadmap,
+}
+
+impl Snapshot {
+    /// Where the command-line tools keep snapshots, relative to the current
+    /// directory.
+    pub const DEFAULT_DIR: &'static str = ".gitscaffold/snapshots";
+
+    /// A snapshot of `roadmap`, read from `source`, taken at `taken`.
+    pub fn new(source: &str, mut roadmap: Roadmap, taken: DateTime<Utc>) -> Snapshot {
+        roadmap.strip_spans();
+        let hash = hash(&roadmap);
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            id: format!("{}-{}", taken.format("%Y%m%dT%H%M%SZ"), hash),
+            taken: taken.to_rfc3339_opts(SecondsFormat::Secs, true),
+            hash,
+            source: source.to_string(),
+            roadmap,
+        }
+    }
+
+    fn path(dir: &Path, id: &str) -> PathBuf {
+        dir.join(format!("{}.json", id))
+    }
+
+    /// Write the snapshot to `dir`, creating it.
+    pub fn save(&self, dir: &Path) -> Result<(), ScaffoldError> {
+        let display = dir.display().to_string();
+        fs::create_dir_all(dir).map_err(|e| ScaffoldError::io(&display, e))?;
+        let path = Snapshot::path(dir, &self.id);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json + "\n").map_err(|e| ScaffoldError::io(&path.display().to_string(), e))
+    }
+
+    fn load(path: &Path) -> Result<Snapshot, ScaffoldError> {
+        let display = path.display().to_string();
+        let content = fs::read_to_string(path).map_err(|e| ScaffoldError::io(&display, e))?;
+        let snapshot: Snapshot =
+            serde_json::from_str(&content).map_err(|e| ScaffoldError::parse(Some(&display), e))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(ScaffoldError::parse(
+                Some(&display),
+                format!("unsupported snapshot version {}", snapshot.version),
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    /// The snapshots kept in `dir`, oldest first; none when it does not
+    /// exist.
+    pub fn list(dir: &Path) -> Result<Vec<Snapshot>, ScaffoldError> {
+        let display = dir.display().to_string();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(ScaffoldError::io(&display, e)),
+        };
+        let mut snapshots = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| ScaffoldError::io(&display, e))?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                snapshots.push(Snapshot::load(&path)?);
+            }
+        }
+        snapshots.sort_by(|a, b| (&a.taken, &a.id).cmp(&(&b.taken, &b.id)));
+        Ok(snapshots)
+    }
+}
+
+/// The snapshot among `snapshots`, oldest first, that `reference` names:
+/// `latest`, an id or the start of one, or a `YYYY-MM-DD` date, meaning
+/// the last snapshot taken before that day.
+pub fn find<'a>(snapshots: &'a [Snapshot], reference: &str) -> Result<&'a Snapshot, String> {
+    let reference = reference.trim();
+    if reference == "latest" {
+        return snapshots
+            .last()
+            .ok_or_else(|| "no snapshots have been taken".to_string());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(reference, "%Y-%m-%d") {
+        let day = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        return snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| {
+                DateTime::parse_from_rfc3339(&snapshot.taken).is_ok_and(|taken| taken < day)
+            })
+            .ok_or_else(|| format!("no snapshot was taken before {}", reference));
+    }
+    let mut matching = snapshots.iter().filter(|s| s.id.starts_with(reference));
+    match (matching.next(), matching.next()) {
+        (Some(snapshot), None) => Ok(snapshot),
+        (Some(_), Some(_)) => Err(format!(
+            "'{}' matches several snapshots; give more of the id",
+            reference
+        )),
+        (None, _) => Err(format!("no snapshot '{}'", reference)),
+    }
+}
+
+fn hash(roadmap: &Roadmap) -> String {
+    let json = serde_json::to_vec(roadmap).unwrap_or_default();
+    Sha256::digest(&json)[..6]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitscaffold_parser::parse_markdown;
+
+    #[test]
+    fn saves_snapshots_and_finds_them_by_id_or_date() {
+        let dir =
+            std::env::temp_dir().join(format!("gitscaffold-snapshots-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let taken = |day: u32| {
+            NaiveDate::from_ymd_opt(2025, 6, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let first = Snapshot::new(
+            "ROADMAP.md",
+            parse_markdown("# P\n\n## Features\n\n### Login\n").unwrap(),
+            taken(1),
+        );
+        // Only moved text: the same roadmap, so the same hash.
+        let moved = Snapshot::new(
+            "ROADMAP.md",
+            parse_markdown("# P\n\n\n## Features\n\n### Login\n").unwrap(),
+            taken(2),
+        );
+        assert_eq!(first.hash, moved.hash);
+        let second = Snapshot::new(
+            "ROADMAP.md",
+            parse_markdown("# P\n\n## Features\n\n### Login\n\n### Search\n").unwrap(),
+            taken(20),
+        );
+        assert_ne!(first.hash, second.hash);
+        assert_eq!(second.id, format!("20250620T120000Z-{}", second.hash));
+        second.save(&dir).unwrap();
+        first.save(&dir).unwrap();
+
+        let snapshots = Snapshot::list(&dir).unwrap();
+        assert_eq!(snapshots, [first.clone(), second.clone()]);
+        assert_eq!(find(&snapshots, "latest").unwrap().id, second.id);
+        assert_eq!(find(&snapshots, "20250601").unwrap().id, first.id);
+        assert_eq!(find(&snapshots, "2025-06-15").unwrap().id, first.id);
+        assert!(find(&snapshots, "2025-06-01").is_err());
+        assert!(find(&snapshots, "2025").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Which milestones and issues the roadmap's entities were synced to, kept
+//! in `.gitscaffold/state.json`.
+//!
+//! Sync matches milestones and issues by title, which breaks as soon as
+//! either side is renamed. The state records the numbers instead, per
+//! repository: milestones by name and issues by the title of the feature
+//! they were created from. Titles are stored trimmed.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ScaffoldError;
+use crate::sync::SyncReport;
+
+/// Version of the state file format.
+pub const STATE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    pub version: u32,
+    /// Keyed by repository, as `owner/name` or the GitLab project path.
+    #[serde(default)]
+    pub repos: BTreeMap<String, RepoState>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoState {
+    /// Milestone numbers by milestone name.
+    #[serde(default)]
+    pub milestones: BTreeMap<String, u64>,
+    /// Issue numbers by feature title.
+    #[serde(default)]
+    pub features: BTreeMap<String, u64>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            version: STATE_VERSION,
+            repos: BTreeMap::new(),
+        }
+    }
+}
+
+impl State {
+    /// Where the command-line tools keep the state, relative to the current
+    /// directory.
+    pub const DEFAULT_PATH: &'static str = ".gitscaffold/state.json";
+
+    /// Read the state at `path`; a missing file is an empty state.
+    pub fn load(path: &Path) -> Result<State, ScaffoldError> {
+        let display = path.display().to_string();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(State::default()),
+            Err(e) => return Err(ScaffoldError::io(&display, e)),
+        };
+        let state: State =
+            serde_json::from_str(&content).map_err(|e| ScaffoldError::parse(Some(&display), e))?;
+        if state.version != STATE_VERSION {
+            return Err(ScaffoldError::parse(
+                Some(&display),
+                format!("unsupported state version {}", state.version),
+            ));
+        }
+        Ok(state)
+    }
+
+    /// Write the state to `path`, creating its directory.
+    pub fn save(&self, path: &Path) -> Result<(), ScaffoldError> {
+        let display = path.display().to_string();
+        let json = serde_json::to_string_pretty(self)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| ScaffoldError::io(&display, e))?;
+        }
+        fs::write(path, json + "\n").map_err(|e| ScaffoldError::io(&display, e))
+    }
+
+    pub fn repo(&self, repo: &str) -> Option<&RepoState> {
+        self.repos.get(repo)
+    }
+
+    /// The state of `repo`, added empty if it has none.
+    pub fn repo_mut(&mut self, repo: &str) -> &mut RepoState {
+        self.repos.entry(repo.to_string()).or_default()
+    }
+
+    /// Add the entries of `other`, which win over the existing ones.
+    pub fn merge(&mut self, other: State) {
+        for (repo, state) in other.repos {
+            self.repo_mut(&repo).merge(state);
+        }
+    }
+}
+
+impl RepoState {
+    /// The issue number recorded for the feature titled `title`.
+    pub fn feature(&self, title: &str) -> Option<u64> {
+        self.features.get(title.trim()).copied()
+    }
+
+    /// The milestone number recorded for the milestone named `name`.
+    pub fn milestone(&self, name: &str) -> Option<u64> {
+        self.milestones.get(name.trim()).copied()
+    }
+
+    /// Record the milestones and issues of a sync run, whether they were
+    /// created or found, and forget the issues it closed.
+    pub fn record(&mut self, report: &SyncReport) {
+        for milestone in &report.milestones {
+            self.milestones
+                .insert(milestone.title.trim().to_string(), milestone.number);
+        }
+        // Task issues are found by title; only features are recorded.
+        for issue in report.issues.iter().filter(|issue| issue.parent.is_none()) {
+            let title = issue.feature.as_deref().unwrap_or(&issue.title);
+            self.features.insert(title.trim().to_string(), issue.number);
+        }
+        for issue in &report.closed {
+            self.features.retain(|_, number| *number != issue.number);
+        }
+    }
+
+    /// Add the entries of `other`, which win over the existing ones.
+    pub fn merge(&mut self, other: RepoState) {
+        self.milestones.extend(other.milestones);
+        self.features.extend(other.features);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::Synced;
+
+    #[test]
+    fn records_sync_reports_by_feature_title() {
+        let report = SyncReport {
+            milestones: vec![Synced {
+                title: "v1 ".into(),
+                number: 2,
+                created: true,
+                feature: None,
+                renamed_from: None,
+                parent: None,
+            }],
+            issues: vec![
+                Synced {
+                    title: "[core] Login".into(),
+                    number: 7,
+                    created: false,
+                    feature: Some("Login".into()),
+                    renamed_from: None,
+                    parent: None,
+                },
+                Synced {
+                    title: "[core] Login / Expiry".into(),
+                    number: 8,
+                    created: true,
+                    feature: None,
+                    renamed_from: None,
+                    parent: Some("[core] Login".into()),
+                },
+            ],
+            failures: Vec::new(),
+            updated: Vec::new(),
+            closed: Vec::new(),
+            updated_milestones: Vec::new(),
+            closed_milestones: Vec::new(),
+            skipped: 0,
+        };
+        let mut state = State::default();
+        state.repo_mut("o/r").record(&report);
+        let repo = state.repo("o/r").unwrap();
+        assert_eq!(repo.milestone("v1"), Some(2));
+        assert_eq!(repo.feature(" Login"), Some(7));
+        assert_eq!(repo.feature("[core] Login / Expiry"), None);
+        assert_eq!(state.repo("o/other"), None);
+    }
+
+    #[test]
+    fn saves_loads_and_merges() {
+        let dir = std::env::temp_dir().join(format!("gitscaffold-state-{}", std::process::id()));
+        let path = dir.join("nested").join("state.json");
+        assert_eq!(State::load(&path).unwrap(), State::default());
+
+        let mut state = State::default();
+        state.repo_mut("o/r").features.insert("Login".into(), 1);
+        state.repo_mut("o/r").features.insert("Search".into(), 2);
+        state.save(&path).unwrap();
+        let mut loaded = State::load(&path).unwrap();
+        assert_eq!(loaded, state);
+
+        let mut other = State::default();
+        other.repo_mut("o/r").features.insert("Search".into(), 5);
+        other.repo_mut("o/s").milestones.insert("v1".into(), 1);
+        loaded.merge(other);
+        let repo = loaded.repo("o/r").unwrap();
+        assert_eq!(
+            (repo.feature("Login"), repo.feature("Search")),
+            (Some(1), Some(5))
+        );
+        assert_eq!(loaded.repo("o/s").unwrap().milestone("v1"), Some(1));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,2586 @@
+//! Create milestones and issues from a roadmap on GitHub or GitLab.
+//!
+//! Sync runs in two phases. [`plan`] compares the roadmap against what
+//! already exists in the repository, matching milestones and issues by
+//! title, and lists the operations needed to bring the repository in line.
+//! [`apply`] carries them out. Plans serialize to JSON so they can be
+//! reviewed before they are applied, and re-running sync only creates the
+//! entities that are missing.
+//!
+//! A feature whose title no issue has may have been renamed. Its issue is
+//! then the one the [`crate::state`] records for it or, failing that, the
+//! issue whose title is most similar to the feature's, and sync renames
+//! that issue instead of creating another one.
+//!
+//! Sync normally leaves existing issues alone. Given [`Field`]s to update,
+//! it also compares the body, labels, milestone and assignees of each
+//! feature's issue with what the roadmap says and plans an update of the
+//! fields that drifted. With [`crate::notify::Notify::Comment`], each
+//! update also leaves a comment on the issue saying what changed.
+//!
+//! Sync can also give tasks issues of their own, each below the issue of
+//! its feature or of the task it is nested in. Their titles follow the
+//! parent's (`Login / Session expiry`), and the parent's task list refers
+//! to them by number (`- [ ] #12`) so GitHub shows their state; on GitHub
+//! they also become sub-issues of the parent.
+//!
+//! Managing milestones, sync also keeps existing milestones in line with
+//! the roadmap: it retitles the milestone of a renamed roadmap milestone,
+//! found like renamed features by title similarity, updates due dates and
+//! descriptions that changed, and closes open milestones whose features
+//! are all done.
+//!
+//! When pruning, sync also closes the open issues it made for features
+//! that have since been deleted from the roadmap: those the state records
+//! under a feature title the roadmap no longer has, and those carrying the
+//! configured marker label that no feature claims. Each gets a comment
+//! saying why before it is closed.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use gitscaffold_parser::dates::is_iso_date;
+use gitscaffold_parser::graph;
+use gitscaffold_parser::text::{self, normalize, truncate};
+use gitscaffold_parser::{Feature, Milestone, Roadmap, Task};
+
+use crate::forge::Forge;
+use crate::fuzzy::similarity;
+use crate::github::{
+    GitHubError, IssueUpdate, MilestoneUpdate, NewIssue, NewMilestone, RemoteIssue,
+    RemoteMilestone, RemoteUser,
+};
+use crate::journal::Journal;
+use crate::marker::{feature_id, stamp, strip, Marker};
+use crate::notify::{change_comment, Changes, Notify};
+use crate::state::RepoState;
+use crate::template::{IssueText, MAX_TITLE_CHARS};
+
+/// Version of the plan file format written by `--plan-out`.
+pub const PLAN_VERSION: u32 = 1;
+
+/// A milestone or issue handled by a sync run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Synced {
+    pub title: String,
+    pub number: u64,
+    pub created: bool,
+    /// For an issue, the title of the feature it was created from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    /// The issue's title before this run renamed it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    /// For the issue of a task, the title of its parent issue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// A milestone or issue that could not be created or renamed.
+#[derive(Debug)]
+pub struct Failure {
+    pub title: String,
+    /// What was attempted, e.g. `create milestone` or `close issue`.
+    pub action: &'static str,
+    pub error: GitHubError,
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub milestones: Vec<Synced>,
+    pub issues: Vec<Synced>,
+    pub failures: Vec<Failure>,
+    /// Issues whose fields were brought in line with the roadmap.
+    pub updated: Vec<Updated>,
+    /// Issues closed because their features left the roadmap.
+    pub closed: Vec<Synced>,
+    /// Milestones whose due date or description was updated.
+    pub updated_milestones: Vec<Synced>,
+    /// Milestones closed because their features are all done.
+    pub closed_milestones: Vec<Synced>,
+    /// Operations not attempted because an earlier one failed or the run
+    /// was interrupted.
+    pub skipped: usize,
+}
+
+/// An existing issue sync updated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Updated {
+    pub title: String,
+    pub number: u64,
+    pub fields: Vec<Field>,
+}
+
+/// A field of an issue sync can update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Body,
+    Labels,
+    Milestone,
+    Assignees,
+}
+
+impl Field {
+    pub const ALL: [Field; 4] = [
+        Field::Body,
+        Field::Labels,
+        Field::Milestone,
+        Field::Assignees,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Body => "body",
+            Field::Labels => "labels",
+            Field::Milestone => "milestone",
+            Field::Assignees => "assignees",
+        }
+    }
+}
+
+/// How a sync run carries out its operations.
+#[derive(Debug, Clone, Copy)]
+pub struct Execution<'a> {
+    /// How many issues to create or rename at once.
+    pub jobs: usize,
+    /// How many issues to create per request where the forge can create
+    /// them in bulk; 1 creates each with a request of its own.
+    pub batch: usize,
+    /// Whether to carry on after an operation fails. Otherwise no
+    /// operation starts after the first failure, though those already
+    /// under way finish.
+    pub keep_going: bool,
+    /// Set, e.g. by a signal handler, to start no further operations.
+    pub interrupted: Option<&'a AtomicBool>,
+    /// Where to record the plan and each operation as it completes.
+    pub journal: Option<&'a Journal>,
+}
+
+impl Default for Execution<'_> {
+    fn default() -> Self {
+        Execution {
+            jobs: 1,
+            batch: 1,
+            keep_going: false,
+            interrupted: None,
+            journal: None,
+        }
+    }
+}
+
+/// One change to make in the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    CreateMilestone {
+        title: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        due_on: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// Give the milestone of a renamed roadmap milestone its new title.
+    RenameMilestone {
+        number: u64,
+        from: String,
+        to: String,
+    },
+    /// Bring the due date and description of an existing milestone in line
+    /// with the roadmap; the fields left out are not changed.
+    UpdateMilestone {
+        number: u64,
+        title: String,
+        /// `null` removes the due date.
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "present"
+        )]
+        due_on: Option<Option<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    /// Close a milestone whose features are all done.
+    CloseMilestone { number: u64, title: String },
+    CreateIssue {
+        title: String,
+        body: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        labels: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        assignees: Vec<String>,
+        /// Milestone title, resolved to its number when the plan is applied
+        /// since the milestone may only be created by the same plan.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        milestone: Option<String>,
+        /// Title of the feature the issue is created from.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        feature: Option<String>,
+        /// For the issue of a task, the title of its parent issue.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        parent: Option<String>,
+    },
+    /// Give the issue of a renamed feature the feature's new title.
+    RenameIssue {
+        number: u64,
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        feature: Option<String>,
+    },
+    /// Bring the fields of an existing issue in line with its feature; the
+    /// fields left out are not changed.
+    UpdateIssue {
+        number: u64,
+        title: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        labels: Option<Vec<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        assignees: Option<Vec<String>>,
+        /// Milestone title; `null` removes the issue from its milestone.
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "present"
+        )]
+        milestone: Option<Option<String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        feature: Option<String>,
+        /// Left on the issue once it is updated, summarizing the changes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        comment: Option<String>,
+    },
+    /// Close an issue whose feature was deleted from the roadmap, leaving
+    /// `comment` on it.
+    CloseIssue {
+        number: u64,
+        title: String,
+        comment: String,
+        /// Title of the feature the issue was created from, when known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        feature: Option<String>,
+    },
+}
+
+/// Deserialize a field that is present, even as `null`, to `Some`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl Operation {
+    /// `+` for creations, `~` for changes and `-` for closures, in the
+    /// style of `scaffold diff`.
+    pub fn marker(&self) -> char {
+        match self {
+            Operation::CreateMilestone { .. } | Operation::CreateIssue { .. } => '+',
+            Operation::RenameMilestone { .. }
+            | Operation::UpdateMilestone { .. }
+            | Operation::RenameIssue { .. }
+            | Operation::UpdateIssue { .. } => '~',
+            Operation::CloseMilestone { .. } | Operation::CloseIssue { .. } => '-',
+        }
+    }
+
+    /// Whether the operation changes a milestone rather than an issue.
+    pub fn is_milestone(&self) -> bool {
+        matches!(
+            self,
+            Operation::CreateMilestone { .. }
+                | Operation::RenameMilestone { .. }
+                | Operation::UpdateMilestone { .. }
+                | Operation::CloseMilestone { .. }
+        )
+    }
+
+    /// The fields an `UpdateIssue` changes; none for other operations.
+    pub fn fields(&self) -> Vec<Field> {
+        let Operation::UpdateIssue {
+            body,
+            labels,
+            assignees,
+            milestone,
+            ..
+        } = self
+        else {
+            return Vec::new();
+        };
+        let changed = [
+            body.is_some(),
+            labels.is_some(),
+            milestone.is_some(),
+            assignees.is_some(),
+        ];
+        Field::ALL
+            .into_iter()
+            .zip(changed)
+            .filter_map(|(field, changed)| changed.then_some(field))
+            .collect()
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::CreateMilestone { title, due_on, .. } => {
+                write!(f, "create milestone '{}'", title)?;
+                if let Some(due) = due_on {
+                    write!(f, " due {}", &due[..due.len().min(10)])?;
+                }
+                Ok(())
+            }
+            Operation::CreateIssue {
+                title,
+                labels,
+                assignees,
+                milestone,
+                ..
+            } => {
+                write!(f, "create issue '{}'", title)?;
+                if let Some(milestone) = milestone {
+                    write!(f, " in milestone '{}'", milestone)?;
+                }
+                if !labels.is_empty() {
+                    write!(f, " labeled {}", labels.join(", "))?;
+                }
+                if !assignees.is_empty() {
+                    write!(f, " assigned to {}", assignees.join(", "))?;
+                }
+                Ok(())
+            }
+            Operation::RenameMilestone { number, from, to } => {
+                write!(f, "rename milestone #{} '{}' to '{}'", number, from, to)
+            }
+            Operation::UpdateMilestone {
+                number,
+                title,
+                due_on,
+                description,
+            } => {
+                let mut changes = Vec::new();
+                match due_on {
+                    Some(Some(due)) => changes.push(format!("due {}", &due[..due.len().min(10)])),
+                    Some(None) => changes.push("no due date".to_string()),
+                    None => {}
+                }
+                if description.is_some() {
+                    changes.push("description".to_string());
+                }
+                write!(
+                    f,
+                    "update milestone #{} '{}': {}",
+                    number,
+                    title,
+                    changes.join(", ")
+                )
+            }
+            Operation::CloseMilestone { number, title } => {
+                write!(f, "close milestone #{} '{}'", number, title)
+            }
+            Operation::RenameIssue {
+                number, from, to, ..
+            } => write!(f, "rename issue #{} '{}' to '{}'", number, from, to),
+            Operation::UpdateIssue {
+                number,
+                title,
+                labels,
+                assignees,
+                milestone,
+                comment,
+                ..
+            } => {
+                let changes: Vec<String> = self
+                    .fields()
+                    .into_iter()
+                    .map(|field| match field {
+                        Field::Body => "body".to_string(),
+                        Field::Labels => {
+                            format!(
+                                "labels [{}]",
+                                labels.as_deref().unwrap_or_default().join(", ")
+                            )
+                        }
+                        Field::Milestone => match milestone.as_ref().and_then(Option::as_ref) {
+                            Some(milestone) => format!("milestone '{}'", milestone),
+                            None => "no milestone".to_string(),
+                        },
+                        Field::Assignees => format!(
+                            "assignees [{}]",
+                            assignees.as_deref().unwrap_or_default().join(", ")
+                        ),
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "update issue #{} '{}': {}",
+                    number,
+                    title,
+                    changes.join(", ")
+                )?;
+                if comment.is_some() {
+                    write!(f, " (with a comment)")?;
+                }
+                Ok(())
+            }
+            Operation::CloseIssue { number, title, .. } => {
+                write!(f, "close issue #{} '{}'", number, title)
+            }
+        }
+    }
+}
+
+/// The operations a sync would perform, plus the roadmap entities that
+/// already exist and are left alone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub version: u32,
+    pub repo: String,
+    pub existing_milestones: Vec<Synced>,
+    pub existing_issues: Vec<Synced>,
+    pub operations: Vec<Operation>,
+    /// Problems found while planning that the operations work around.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// How [`plan`] finds the issue of a feature whose title no issue has.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Matching<'a> {
+    /// Issue numbers recorded by earlier runs.
+    pub state: Option<&'a RepoState>,
+    /// How similar an issue's title must be for the feature to count as
+    /// renamed, as scored by [`similarity`]; `None` disables fuzzy
+    /// matching.
+    pub fuzzy_threshold: Option<f64>,
+}
+
+/// What [`plan`] does to existing issues besides matching them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reconcile<'a> {
+    /// Fields of the issues found to bring in line with their features.
+    pub update: &'a [Field],
+    /// Close the open issues made for features no longer in the roadmap.
+    pub prune: bool,
+    /// Retitle, update and close existing milestones, not only create the
+    /// missing ones.
+    pub milestones: bool,
+    /// Give the tasks nested at most this deep (0 for top-level tasks only)
+    /// issues of their own; `None` leaves tasks in their feature's task
+    /// list.
+    pub tasks_as_issues: Option<usize>,
+    /// Label added to every issue sync creates; when pruning, issues with
+    /// it count as made by sync even if the state does not record them.
+    pub marker_label: Option<&'a str>,
+    /// Whether updates come with a comment on the issue saying what
+    /// changed.
+    pub notify: Notify,
+    /// Also create and update the issues of deferred features, which are
+    /// otherwise left alone; their issues are never pruned either way.
+    pub include_deferred: bool,
+}
+
+/// Titles are compared after trimming, like the Python client does, and
+/// in Unicode normalization form C, so an issue title that a forge or an
+/// editor stored decomposed still matches.
+fn title_key(title: &str) -> String {
+    normalize(title)
+}
+
+/// The milestone `feature` belongs to, spelled as the roadmap's milestone
+/// is: like [`graph::resolve`], a feature's `Milestone:` line matches the
+/// milestone's name ignoring case. A name no roadmap milestone has is kept
+/// as written.
+fn milestone_of<'a>(roadmap: &'a Roadmap, feature: &'a Feature) -> Option<&'a str> {
+    let name = feature.milestone.as_deref()?;
+    let key = text::title_key(name);
+    let milestone = roadmap
+        .milestones
+        .iter()
+        .find(|milestone| text::title_key(&milestone.name) == key);
+    Some(milestone.map_or(name, |milestone| milestone.name.as_str()))
+}
+
+/// Convert a `YYYY-MM-DD` due date into the timestamp GitHub expects.
+/// Anything else is dropped rather than sent.
+pub fn due_on(due_date: Option<&str>) -> Option<String> {
+    due_date
+        .filter(|due| is_iso_date(due))
+        .map(|due| format!("{}T00:00:00Z", due))
+}
+
+/// The issue body for a feature: its description followed by a checklist
+/// of its tasks.
+pub fn issue_body(feature: &Feature) -> String {
+    let mut body = feature.description.trim().to_string();
+    if !feature.tasks.is_empty() {
+        if !body.is_empty() {
+            body.push_str("\n\n");
+        }
+        body.push_str("## Tasks\n");
+        for task in &feature.tasks {
+            let mark = if task.completed { 'x' } else { ' ' };
+            let indent = "  ".repeat(task.depth);
+            body.push_str(&format!("\n{}- [{}] {}", indent, mark, task.title));
+        }
+    }
+    body
+}
+
+/// Plan the operations that create the roadmap's missing milestones and
+/// issues in `repo`, given what already exists there. `texts` holds the
+/// issue title and body of each feature, in order, as rendered by
+/// [`crate::template::IssueTemplates::render`]; issues are matched by the
+/// rendered title, then as set out by `matching`. What happens to the
+/// issues found, and to those no feature claims, is set out by `reconcile`.
+pub fn plan(
+    roadmap: &Roadmap,
+    texts: &[IssueText],
+    repo: &str,
+    milestones: &[RemoteMilestone],
+    issues: &[RemoteIssue],
+    matching: Matching<'_>,
+    reconcile: Reconcile<'_>,
+) -> Plan {
+    let mut plan = Plan {
+        version: PLAN_VERSION,
+        repo: repo.to_string(),
+        existing_milestones: Vec::new(),
+        existing_issues: Vec::new(),
+        operations: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let mut milestone_numbers: HashMap<String, Option<u64>> = milestones
+        .iter()
+        .map(|m| (title_key(&m.title), Some(m.number)))
+        .collect();
+    let names: HashSet<String> = roadmap
+        .milestones
+        .iter()
+        .map(|m| title_key(&m.name))
+        .collect();
+    let mut unclaimed_milestones: Vec<&RemoteMilestone> = milestones
+        .iter()
+        .filter(|m| !names.contains(&title_key(&m.title)))
+        .collect();
+    // The milestones in the repository for each roadmap milestone, to
+    // close once their features are done.
+    let mut found: Vec<(&Milestone, &RemoteMilestone)> = Vec::new();
+    for milestone in &roadmap.milestones {
+        let key = title_key(&milestone.name);
+        match milestone_numbers.get(&key) {
+            Some(Some(number)) => {
+                plan.existing_milestones.push(Synced {
+                    title: milestone.name.clone(),
+                    number: *number,
+                    created: false,
+                    feature: None,
+                    renamed_from: None,
+                    parent: None,
+                });
+                let number = *number;
+                if let Some(remote) = milestones.iter().find(|m| m.number == number) {
+                    found.push((milestone, remote));
+                }
+            }
+            Some(None) => {}
+            None => {
+                let renamed = reconcile
+                    .milestones
+                    .then(|| claim_milestone(&mut unclaimed_milestones, milestone, matching))
+                    .flatten();
+                if let Some(remote) = renamed {
+                    plan.operations.push(Operation::RenameMilestone {
+                        number: remote.number,
+                        from: remote.title.clone(),
+                        to: milestone.name.clone(),
+                    });
+                    milestone_numbers.insert(key, Some(remote.number));
+                    found.push((milestone, remote));
+                    continue;
+                }
+                plan.operations.push(Operation::CreateMilestone {
+                    title: milestone.name.clone(),
+                    due_on: due_on(milestone.due_date.as_deref()),
+                    description: Some(milestone.description.trim().to_string())
+                        .filter(|description| !description.is_empty()),
+                });
+                milestone_numbers.insert(key, None);
+            }
+        }
+    }
+    if reconcile.milestones {
+        plan.operations.extend(
+            found
+                .iter()
+                .filter_map(|(milestone, remote)| milestone_drift(remote, milestone)),
+        );
+    }
+
+    let mut issue_numbers: HashMap<String, Option<u64>> = issues
+        .iter()
+        .map(|issue| (title_key(&issue.title), Some(issue.number)))
+        .collect();
+    let tasks: Vec<Vec<TaskIssue>> = roadmap
+        .features
+        .iter()
+        .zip(texts)
+        .map(|(feature, text)| match reconcile.tasks_as_issues {
+            Some(depth) => task_issues(feature, &text.title, depth, reconcile.marker_label),
+            None => Vec::new(),
+        })
+        .collect();
+    let titles: HashSet<String> = texts
+        .iter()
+        .map(|text| &text.title)
+        .chain(tasks.iter().flatten().map(|task| &task.title))
+        .map(|title| title_key(title))
+        .collect();
+    let mut unclaimed: Vec<&RemoteIssue> = issues
+        .iter()
+        .filter(|issue| !titles.contains(&title_key(&issue.title)))
+        .collect();
+    // The titles the state records for features no longer in the roadmap.
+    let features: HashSet<String> = roadmap
+        .features
+        .iter()
+        .map(|f| title_key(&f.title))
+        .collect();
+    let former: HashMap<u64, &str> = matching
+        .state
+        .iter()
+        .flat_map(|state| &state.features)
+        .filter(|(title, _)| !features.contains(*title))
+        .map(|(title, &number)| (number, title.as_str()))
+        .collect();
+    let by_number: HashMap<u64, &RemoteIssue> =
+        issues.iter().map(|issue| (issue.number, issue)).collect();
+    // The most urgent features come first, so their issues are created
+    // first; features of the same priority keep the roadmap's order.
+    let mut ordered: Vec<_> = roadmap.features.iter().zip(texts).zip(&tasks).collect();
+    ordered.sort_by_key(|((feature, _), _)| feature.priority_rank());
+    for ((feature, text), tasks) in ordered {
+        if feature.is_deferred() && !reconcile.include_deferred {
+            continue;
+        }
+        let body = link_known(&text.body, &text.title, tasks, &issue_numbers);
+        let text = &IssueText {
+            title: text.title.clone(),
+            body: stamp(&body, &feature_id(&feature.title)),
+        };
+        let key = title_key(&text.title);
+        // Features naming an unknown milestone go without one.
+        let milestone = milestone_of(roadmap, feature)
+            .filter(|name| milestone_numbers.contains_key(&title_key(name)));
+        match issue_numbers.get(&key) {
+            Some(Some(number)) => {
+                plan.existing_issues.push(Synced {
+                    title: text.title.clone(),
+                    number: *number,
+                    created: false,
+                    feature: Some(feature.title.clone()),
+                    renamed_from: None,
+                    parent: None,
+                });
+                if let Some(issue) = by_number.get(number) {
+                    plan.operations
+                        .extend(drift(issue, feature, text, milestone, reconcile));
+                }
+            }
+            Some(None) => {}
+            None => {
+                issue_numbers.insert(key, None);
+                if let Some(issue) = claim(&mut unclaimed, feature, text, &former, matching) {
+                    plan.operations.push(Operation::RenameIssue {
+                        number: issue.number,
+                        from: issue.title.clone(),
+                        to: text.title.clone(),
+                        feature: Some(feature.title.clone()),
+                    });
+                    plan.operations
+                        .extend(drift(issue, feature, text, milestone, reconcile));
+                } else {
+                    plan.operations.push(Operation::CreateIssue {
+                        title: text.title.clone(),
+                        body: text.body.clone(),
+                        labels: labels(feature, reconcile.marker_label),
+                        assignees: feature.assignees.clone(),
+                        milestone: milestone.map(str::to_string),
+                        feature: Some(feature.title.clone()),
+                        parent: None,
+                    });
+                }
+            }
+        }
+        for task in tasks {
+            let key = title_key(&task.title);
+            match issue_numbers.get(&key) {
+                Some(Some(number)) => plan.existing_issues.push(Synced {
+                    title: task.title.clone(),
+                    number: *number,
+                    created: false,
+                    feature: None,
+                    renamed_from: None,
+                    parent: Some(task.parent.clone()),
+                }),
+                Some(None) => {}
+                None => {
+                    plan.operations.push(Operation::CreateIssue {
+                        title: task.title.clone(),
+                        body: stamp(
+                            &link_known(&task.body, &task.title, tasks, &issue_numbers),
+                            &task.id,
+                        ),
+                        labels: task.labels.clone(),
+                        assignees: task.assignees.clone(),
+                        milestone: milestone.map(str::to_string),
+                        feature: None,
+                        parent: Some(task.parent.clone()),
+                    });
+                    issue_numbers.insert(key, None);
+                }
+            }
+        }
+    }
+    if reconcile.prune {
+        // The feature ids of this roadmap, now or as the state recorded it;
+        // a marker with another id is another roadmap's.
+        let ids: HashSet<String> = roadmap
+            .features
+            .iter()
+            .map(|feature| feature_id(&feature.title))
+            .chain(
+                matching
+                    .state
+                    .iter()
+                    .flat_map(|state| state.features.keys())
+                    .map(|title| feature_id(title)),
+            )
+            .collect();
+        let owner = Owner {
+            ids: &ids,
+            tasks: reconcile.tasks_as_issues.is_some(),
+        };
+        plan.operations
+            .extend(orphans(&unclaimed, &former, reconcile.marker_label, owner));
+    }
+    if reconcile.milestones {
+        plan.operations.extend(
+            found
+                .iter()
+                .filter(|(milestone, remote)| {
+                    remote.state == "open" && finished(roadmap, milestone)
+                })
+                .map(|(milestone, remote)| Operation::CloseMilestone {
+                    number: remote.number,
+                    title: milestone.name.clone(),
+                }),
+        );
+    }
+    plan
+}
+
+/// Whether `milestone` has features that are not deferred and all of them
+/// are done.
+fn finished(roadmap: &Roadmap, milestone: &Milestone) -> bool {
+    let mut features = roadmap
+        .features
+        .iter()
+        .filter(|feature| !feature.is_deferred())
+        .filter(|feature| milestone_of(roadmap, feature) == Some(&milestone.name))
+        .peekable();
+    features.peek().is_some() && features.all(Feature::is_done)
+}
+
+/// The update that brings the due date and description of `remote` in line
+/// with `milestone`; `None` when neither changed. Due dates are compared by
+/// day.
+fn milestone_drift(remote: &RemoteMilestone, milestone: &Milestone) -> Option<Operation> {
+    let wanted = due_on(milestone.due_date.as_deref());
+    let day = |due: Option<&str>| due.map(|due| due[..due.len().min(10)].to_string());
+    let due_changed = day(remote.due_on.as_deref()) != day(wanted.as_deref());
+    let normalized = |text: &str| text.replace("\r\n", "\n").trim().to_string();
+    let description = normalized(&milestone.description);
+    let description_changed =
+        normalized(remote.description.as_deref().unwrap_or("")) != description;
+    (due_changed || description_changed).then(|| Operation::UpdateMilestone {
+        number: remote.number,
+        title: milestone.name.clone(),
+        due_on: due_changed.then_some(wanted),
+        description: description_changed.then_some(description),
+    })
+}
+
+/// Take the milestone most similar to the renamed `milestone` out of
+/// `unclaimed`, as long as fuzzy matching is on.
+fn claim_milestone<'a>(
+    unclaimed: &mut Vec<&'a RemoteMilestone>,
+    milestone: &Milestone,
+    matching: Matching<'_>,
+) -> Option<&'a RemoteMilestone> {
+    let threshold = matching.fuzzy_threshold?;
+    let mut best: Option<(usize, f64)> = None;
+    for (index, remote) in unclaimed.iter().enumerate() {
+        let score = similarity(&milestone.name, &remote.title);
+        if score >= threshold && best.is_none_or(|(_, best)| score > best) {
+            best = Some((index, score));
+        }
+    }
+    best.map(|(index, _)| unclaimed.remove(index))
+}
+
+/// The labels of `feature`'s issue: its own plus the marker label.
+fn labels(feature: &Feature, marker_label: Option<&str>) -> Vec<String> {
+    let mut labels = feature.labels.clone();
+    if let Some(marker) = marker_label {
+        if !labels
+            .iter()
+            .any(|label| label.eq_ignore_ascii_case(marker))
+        {
+            labels.push(marker.to_string());
+        }
+    }
+    labels
+}
+
+/// A task that gets an issue of its own, below the issue of its feature or
+/// of the task it is nested in.
+struct TaskIssue {
+    /// The task's title, as its line in the parent's task list reads.
+    name: String,
+    /// The id in the issue's marker: its parent's and its own, `login/form`.
+    id: String,
+    title: String,
+    parent: String,
+    body: String,
+    labels: Vec<String>,
+    assignees: Vec<String>,
+}
+
+/// The tasks of `feature`, whose issue is titled `title`, nested at most
+/// `max_depth` deep. Each issue's body is the task's description and a
+/// task list of the tasks nested below it; it has the feature's labels
+/// plus its own, and the feature's assignees unless it has some.
+fn task_issues(
+    feature: &Feature,
+    title: &str,
+    max_depth: usize,
+    marker_label: Option<&str>,
+) -> Vec<TaskIssue> {
+    // The depth and index in `issues` of the tasks enclosing this one.
+    let mut parents: Vec<(usize, usize)> = Vec::new();
+    let mut issues: Vec<TaskIssue> = Vec::new();
+    let id = feature_id(&feature.title);
+    for (index, task) in feature.tasks.iter().enumerate() {
+        if task.depth > max_depth {
+            continue;
+        }
+        while parents
+            .last()
+            .is_some_and(|(depth, _)| *depth >= task.depth)
+        {
+            parents.pop();
+        }
+        let (parent, parent_id) = match parents.last() {
+            Some(&(_, index)) => (issues[index].title.as_str(), issues[index].id.as_str()),
+            None => (title, id.as_str()),
+        };
+        let nested: Vec<Task> = feature.tasks[index + 1..]
+            .iter()
+            .take_while(|nested| nested.depth > task.depth)
+            .map(|nested| Task {
+                depth: nested.depth - task.depth - 1,
+                ..nested.clone()
+            })
+            .collect();
+        let body = issue_body(&Feature {
+            description: task.description.clone(),
+            tasks: nested,
+            ..Feature::default()
+        });
+        let mut labels = labels(feature, marker_label);
+        for label in &task.labels {
+            if !labels.iter().any(|l| l.eq_ignore_ascii_case(label)) {
+                labels.push(label.clone());
+            }
+        }
+        let assignees = match task.assignees.is_empty() {
+            true => feature.assignees.clone(),
+            false => task.assignees.clone(),
+        };
+        let issue = TaskIssue {
+            name: task.title.clone(),
+            id: format!("{}/{}", parent_id, feature_id(&task.title)),
+            title: truncate(&format!("{} / {}", parent, task.title), MAX_TITLE_CHARS).into_owned(),
+            parent: parent.to_string(),
+            body,
+            labels,
+            assignees,
+        };
+        parents.push((task.depth, issues.len()));
+        issues.push(issue);
+    }
+    issues
+}
+
+/// `body`, of the issue titled `parent`, with the task-list line of each
+/// of its `tasks` that already has an issue referring to that issue.
+fn link_known(
+    body: &str,
+    parent: &str,
+    tasks: &[TaskIssue],
+    numbers: &HashMap<String, Option<u64>>,
+) -> String {
+    tasks
+        .iter()
+        .filter(|task| task.parent == parent)
+        .filter_map(|task| Some((task, (*numbers.get(&title_key(&task.title))?)?)))
+        .fold(body.to_string(), |body, (task, number)| {
+            link_task(&body, &task.name, number)
+        })
+}
+
+/// `body` with the first task-list line reading `name` replaced by a
+/// reference to issue `number`, `- [ ] #12`, which GitHub shows with the
+/// issue's title and state.
+pub fn link_task(body: &str, name: &str, number: u64) -> String {
+    let mut linked = false;
+    let lines: Vec<String> = body
+        .split('\n')
+        .map(|line| {
+            let item = line.trim_start();
+            let text = item
+                .strip_prefix("- [ ] ")
+                .or_else(|| item.strip_prefix("- [x] "));
+            if linked || text.map(str::trim_end) != Some(name.trim()) {
+                return line.to_string();
+            }
+            linked = true;
+            let prefix = &line[..line.len() - item.len() + "- [ ] ".len()];
+            format!("{}#{}", prefix, number)
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The feature ids whose [`Marker`] makes an issue this roadmap's, and
+/// whether the ids of their tasks' issues count too.
+#[derive(Clone, Copy)]
+struct Owner<'a> {
+    ids: &'a HashSet<String>,
+    tasks: bool,
+}
+
+impl Owner<'_> {
+    fn owns(&self, marker: &Marker) -> bool {
+        match marker.id.split_once('/') {
+            Some((feature, _)) => self.tasks && self.ids.contains(feature),
+            None => self.ids.contains(&marker.id),
+        }
+    }
+}
+
+/// The closures of the open issues among `unclaimed` that sync made for a
+/// feature since deleted: those recorded under a `former` feature title,
+/// carrying the marker label or ending with a [`Marker`] `owner` owns.
+/// Task issues only count when tasks are synced as issues.
+fn orphans(
+    unclaimed: &[&RemoteIssue],
+    former: &HashMap<u64, &str>,
+    marker_label: Option<&str>,
+    owner: Owner<'_>,
+) -> Vec<Operation> {
+    unclaimed
+        .iter()
+        .filter(|issue| issue.state == "open" && issue.pull_request.is_none())
+        .filter_map(|issue| {
+            let feature = former.get(&issue.number).map(|title| title.to_string());
+            let marked = marker_label.is_some_and(|marker| {
+                issue
+                    .labels
+                    .iter()
+                    .any(|label| label.name.eq_ignore_ascii_case(marker))
+            });
+            let owned = issue
+                .body
+                .as_deref()
+                .and_then(Marker::find)
+                .is_some_and(|marker| owner.owns(&marker));
+            if feature.is_none() && !marked && !owned {
+                return None;
+            }
+            let comment = format!(
+                "Closing: the feature '{}' this issue was created for is no longer \
+                 in the roadmap.",
+                feature.as_deref().unwrap_or(&issue.title)
+            );
+            Some(Operation::CloseIssue {
+                number: issue.number,
+                title: issue.title.clone(),
+                comment,
+                feature,
+            })
+        })
+        .collect()
+}
+
+/// The update of `issue` that brings the fields `reconcile` updates in line
+/// with `feature`, whose issue has `text` and belongs in `milestone`; `None`
+/// when none of them drifted. Labels and assignees are compared ignoring
+/// order and case.
+fn drift(
+    issue: &RemoteIssue,
+    feature: &Feature,
+    text: &IssueText,
+    milestone: Option<&str>,
+    reconcile: Reconcile<'_>,
+) -> Option<Operation> {
+    let fields = reconcile.update;
+    let labels = labels(feature, reconcile.marker_label);
+    let set = |names: &mut dyn Iterator<Item = &str>| -> Vec<String> {
+        let mut names: Vec<String> = names.map(|name| name.trim().to_lowercase()).collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+    let changed = |field: Field| -> bool {
+        if !fields.contains(&field) {
+            return false;
+        }
+        match field {
+            // Only the content counts: issues made before bodies had a
+            // marker are not rewritten just to add one.
+            Field::Body => strip(issue.body.as_deref().unwrap_or("")) != strip(&text.body),
+            Field::Labels => {
+                set(&mut issue.labels.iter().map(|label| label.name.as_str()))
+                    != set(&mut labels.iter().map(String::as_str))
+            }
+            Field::Milestone => {
+                issue.milestone.as_ref().map(|m| title_key(&m.title)) != milestone.map(title_key)
+            }
+            Field::Assignees => {
+                set(&mut issue.assignees.iter().map(|user| user.login.as_str()))
+                    != set(&mut feature.assignees.iter().map(String::as_str))
+            }
+        }
+    };
+    let body = changed(Field::Body).then(|| text.body.clone());
+    let labels = changed(Field::Labels).then(|| labels.clone());
+    let assignees = changed(Field::Assignees).then(|| feature.assignees.clone());
+    let milestone = changed(Field::Milestone).then(|| milestone.map(str::to_string));
+    if body.is_none() && labels.is_none() && assignees.is_none() && milestone.is_none() {
+        return None;
+    }
+    let comment = (reconcile.notify == Notify::Comment).then(|| {
+        let changes = Changes {
+            body: body.as_deref(),
+            labels: labels.as_deref(),
+            assignees: assignees.as_deref(),
+            milestone: milestone.as_ref().map(Option::as_deref),
+        };
+        change_comment(issue, changes)
+    });
+    Some(Operation::UpdateIssue {
+        number: issue.number,
+        title: text.title.clone(),
+        body,
+        labels,
+        assignees,
+        milestone,
+        feature: Some(feature.title.clone()),
+        comment,
+    })
+}
+
+/// Take the issue `feature` had before it was renamed out of `unclaimed`:
+/// the one the state records for its title, the one whose marker has its
+/// id, or else the one most similar to it, comparing issue titles with its
+/// rendered title and `former` feature titles with its own.
+fn claim<'a>(
+    unclaimed: &mut Vec<&'a RemoteIssue>,
+    feature: &Feature,
+    text: &IssueText,
+    former: &HashMap<u64, &str>,
+    matching: Matching<'_>,
+) -> Option<&'a RemoteIssue> {
+    let recorded = matching
+        .state
+        .and_then(|state| state.feature(&feature.title));
+    if let Some(index) = unclaimed.iter().position(|i| Some(i.number) == recorded) {
+        return Some(unclaimed.remove(index));
+    }
+    let id = feature_id(&feature.title);
+    let marked = unclaimed.iter().position(|issue| {
+        issue
+            .body
+            .as_deref()
+            .and_then(Marker::find)
+            .is_some_and(|marker| marker.id == id)
+    });
+    if let Some(index) = marked {
+        return Some(unclaimed.remove(index));
+    }
+    let threshold = matching.fuzzy_threshold?;
+    let mut best: Option<(usize, f64)> = None;
+    for (index, issue) in unclaimed.iter().enumerate() {
+        let mut score = similarity(&text.title, &issue.title);
+        if let Some(title) = former.get(&issue.number) {
+            score = score.max(similarity(&feature.title, title));
+        }
+        if score >= threshold && best.is_none_or(|(_, best)| score > best) {
+            best = Some((index, score));
+        }
+    }
+    best.map(|(index, _)| unclaimed.remove(index))
+}
+
+/// Carry out `plan` in its repository. The repository is read again first:
+/// a milestone or issue created since the plan was made is reported as
+/// existing instead of being created twice.
+///
+/// Milestones are created one after the other, then issues as `execution`
+/// allows. `progress` is called with the 1-based step number as each
+/// operation starts. An operation that fails is recorded in the report's
+/// `failures`; see [`Execution`] for what happens to the others.
+pub async fn apply(
+    plan: &Plan,
+    forge: &impl Forge,
+    execution: Execution<'_>,
+    progress: impl FnMut(usize, &Operation),
+) -> Result<SyncReport, GitHubError> {
+    let milestones = forge.list_milestones().await?;
+    let issues = forge.list_issues().await?;
+    Ok(execute(plan, forge, &milestones, &issues, execution, progress).await)
+}
+
+async fn execute(
+    plan: &Plan,
+    forge: &impl Forge,
+    milestones: &[RemoteMilestone],
+    issues: &[RemoteIssue],
+    execution: Execution<'_>,
+    mut progress: impl FnMut(usize, &Operation),
+) -> SyncReport {
+    let mut report = SyncReport {
+        milestones: plan.existing_milestones.clone(),
+        issues: plan.existing_issues.clone(),
+        ..SyncReport::default()
+    };
+    if let Some(journal) = execution.journal {
+        journal.begin(plan);
+    }
+    let journaled = |index: usize| {
+        if let Some(journal) = execution.journal {
+            journal.record(index);
+        }
+    };
+    let failed = AtomicBool::new(false);
+    let stop = || {
+        execution
+            .interrupted
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+            || (!execution.keep_going && failed.load(Ordering::SeqCst))
+    };
+    let mut milestones: HashMap<String, u64> = milestones
+        .iter()
+        .map(|m| (title_key(&m.title), m.number))
+        .collect();
+    // The bodies issues have once the operations are done, for linking
+    // task issues into their parents' task lists.
+    let mut bodies: HashMap<String, String> = issues
+        .iter()
+        .filter_map(|issue| Some((title_key(&issue.title), issue.body.clone()?)))
+        .collect();
+    for operation in &plan.operations {
+        match operation {
+            Operation::CreateIssue { title, body, .. }
+            | Operation::UpdateIssue {
+                title,
+                body: Some(body),
+                ..
+            } => {
+                bodies.insert(title_key(title), body.clone());
+            }
+            _ => {}
+        }
+    }
+    let issues: HashMap<String, u64> = issues
+        .iter()
+        .map(|issue| (title_key(&issue.title), issue.number))
+        .collect();
+    let mut step = 0;
+
+    let synced = |title: &String, number: u64| Synced {
+        title: title.clone(),
+        number,
+        created: false,
+        feature: None,
+        renamed_from: None,
+        parent: None,
+    };
+    let indexed = || plan.operations.iter().enumerate();
+    for (index, operation) in indexed().filter(|(_, o)| o.is_milestone()) {
+        if stop() {
+            report.skipped += 1;
+            continue;
+        }
+        step += 1;
+        progress(step, operation);
+        let (title, action, result) = match operation {
+            Operation::CreateMilestone {
+                title,
+                due_on,
+                description,
+            } => {
+                let key = title_key(title);
+                if let Some(&number) = milestones.get(&key) {
+                    report.milestones.push(synced(title, number));
+                    journaled(index);
+                    continue;
+                }
+                let milestone = NewMilestone {
+                    title,
+                    due_on: due_on.clone(),
+                    description: description.as_deref(),
+                };
+                let result = forge.create_milestone(&milestone).await.map(|created| {
+                    milestones.insert(key, created.number);
+                    report.milestones.push(Synced {
+                        created: true,
+                        ..synced(title, created.number)
+                    });
+                });
+                (title, "create milestone", result)
+            }
+            Operation::RenameMilestone { number, from, to } => {
+                // Renamed since the plan was made, by us or by hand.
+                if let Some(&number) = milestones.get(&title_key(to)) {
+                    report.milestones.push(synced(to, number));
+                    journaled(index);
+                    continue;
+                }
+                let update = MilestoneUpdate {
+                    title: Some(to),
+                    ..MilestoneUpdate::default()
+                };
+                let result = forge.update_milestone(*number, &update).await.map(|()| {
+                    milestones.insert(title_key(to), *number);
+                    report.milestones.push(Synced {
+                        renamed_from: Some(from.clone()),
+                        ..synced(to, *number)
+                    });
+                });
+                (to, "rename milestone", result)
+            }
+            Operation::UpdateMilestone {
+                number,
+                title,
+                due_on,
+                description,
+            } => {
+                let update = MilestoneUpdate {
+                    due_on: due_on.as_ref().map(Option::as_deref),
+                    description: description.as_deref(),
+                    ..MilestoneUpdate::default()
+                };
+                let result = forge
+                    .update_milestone(*number, &update)
+                    .await
+                    .map(|()| report.updated_milestones.push(synced(title, *number)));
+                (title, "update milestone", result)
+            }
+            Operation::CloseMilestone { number, title } => {
+                let update = MilestoneUpdate {
+                    state: Some("closed"),
+                    ..MilestoneUpdate::default()
+                };
+                let result = forge
+                    .update_milestone(*number, &update)
+                    .await
+                    .map(|()| report.closed_milestones.push(synced(title, *number)));
+                (title, "close milestone", result)
+            }
+            _ => unreachable!("only milestone operations are left"),
+        };
+        match result {
+            Ok(()) => journaled(index),
+            Err(error) => {
+                failed.store(true, Ordering::SeqCst);
+                report.failures.push(Failure {
+                    title: title.clone(),
+                    action,
+                    error,
+                });
+            }
+        }
+    }
+
+    // With batching, the issues to create go out `batch` at a time before
+    // the other issue operations start, which then pick up their results.
+    let mut batched: HashMap<&str, Result<RemoteIssue, GitHubError>> = HashMap::new();
+    if execution.batch > 1 {
+        let pending: Vec<NewIssue> = plan
+            .operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::CreateIssue {
+                    title,
+                    body,
+                    labels,
+                    assignees,
+                    milestone,
+                    ..
+                } if !issues.contains_key(&title_key(title)) => Some(NewIssue {
+                    title,
+                    body: body.clone(),
+                    labels: labels.clone(),
+                    assignees: assignees.clone(),
+                    milestone: milestone
+                        .as_deref()
+                        .and_then(|name| milestones.get(&title_key(name)).copied()),
+                }),
+                _ => None,
+            })
+            .collect();
+        for chunk in pending.chunks(execution.batch) {
+            if stop() {
+                break;
+            }
+            let results = forge.create_issues(chunk).await;
+            for (issue, result) in chunk.iter().zip(results) {
+                if result.is_err() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                batched.insert(issue.title, result);
+            }
+        }
+    }
+    let batched = std::sync::Mutex::new(batched);
+
+    // Issues are independent of each other; `buffered` keeps the results in
+    // plan order however the requests interleave. Operations are started
+    // lazily, so once `stop` holds no new request goes out, while those in
+    // flight still finish and are reported.
+    let (milestones, issues, failed, stop, journaled) =
+        (&milestones, &issues, &failed, &stop, &journaled);
+    let batched = &batched;
+    enum Done {
+        Issue(Synced),
+        Updated(Updated),
+        Closed(Synced),
+    }
+    type Outcome<'a> = Option<(&'a String, &'static str, Result<Done, GitHubError>)>;
+    let results: Vec<Outcome> = stream::iter(indexed())
+        .filter(|(_, operation)| futures::future::ready(!operation.is_milestone()))
+        .map(|(index, operation)| {
+            // Batched results are reported even once `stop` holds.
+            let done = matches!(operation, Operation::CreateIssue { title, .. }
+                if batched.lock().unwrap().contains_key(title.as_str()));
+            let skip = !done && stop();
+            if !skip {
+                step += 1;
+                progress(step, operation);
+            }
+            async move {
+                if skip {
+                    return None;
+                }
+                let (title, action, result) = match operation {
+                    Operation::CreateIssue {
+                        title,
+                        body,
+                        labels,
+                        assignees,
+                        milestone,
+                        feature,
+                        parent,
+                    } => {
+                        let issue = NewIssue {
+                            title,
+                            body: body.clone(),
+                            labels: labels.clone(),
+                            assignees: assignees.clone(),
+                            milestone: milestone
+                                .as_deref()
+                                .and_then(|name| milestones.get(&title_key(name)).copied()),
+                        };
+                        let synced = match issues.get(&title_key(title)) {
+                            Some(&number) => Ok(Synced {
+                                title: title.clone(),
+                                number,
+                                created: false,
+                                feature: feature.clone(),
+                                renamed_from: None,
+                                parent: parent.clone(),
+                            }),
+                            None => {
+                                let result = batched.lock().unwrap().remove(title.as_str());
+                                match result {
+                                    Some(result) => result,
+                                    None => forge.create_issue(&issue).await,
+                                }
+                            }
+                            .map(|created| Synced {
+                                title: title.clone(),
+                                number: created.number,
+                                created: true,
+                                feature: feature.clone(),
+                                renamed_from: None,
+                                parent: parent.clone(),
+                            }),
+                        };
+                        (title, "create issue", synced.map(Done::Issue))
+                    }
+                    Operation::RenameIssue {
+                        number,
+                        from,
+                        to,
+                        feature,
+                    } => {
+                        // Renamed since the plan was made, by us or by hand.
+                        let synced = match issues.get(&title_key(to)) {
+                            Some(&number) => Ok(Synced {
+                                title: to.clone(),
+                                number,
+                                created: false,
+                                feature: feature.clone(),
+                                renamed_from: None,
+                                parent: None,
+                            }),
+                            None => forge.rename_issue(*number, to).await.map(|()| Synced {
+                                title: to.clone(),
+                                number: *number,
+                                created: false,
+                                feature: feature.clone(),
+                                renamed_from: Some(from.clone()),
+                                parent: None,
+                            }),
+                        };
+                        (to, "rename issue", synced.map(Done::Issue))
+                    }
+                    Operation::UpdateIssue {
+                        number,
+                        title,
+                        body,
+                        labels,
+                        assignees,
+                        milestone,
+                        comment,
+                        ..
+                    } => {
+                        let update = IssueUpdate {
+                            body: body.as_deref(),
+                            labels: labels.as_deref(),
+                            assignees: assignees.as_deref(),
+                            // A milestone that could not be created is left
+                            // as it is.
+                            milestone: milestone.as_ref().and_then(|name| match name {
+                                Some(name) => milestones.get(&title_key(name)).map(|&n| Some(n)),
+                                None => Some(None),
+                            }),
+                        };
+                        let mut updated = forge.update_issue(*number, &update).await;
+                        if let (Ok(()), Some(comment)) = (&updated, comment) {
+                            updated = forge.comment_on_issue(*number, comment).await;
+                        }
+                        let updated = updated.map(|()| {
+                            Done::Updated(Updated {
+                                title: title.clone(),
+                                number: *number,
+                                fields: operation.fields(),
+                            })
+                        });
+                        (title, "update issue", updated)
+                    }
+                    Operation::CloseIssue {
+                        number,
+                        title,
+                        comment,
+                        feature,
+                    } => {
+                        let closed = forge.close_issue(*number, comment).await.map(|()| {
+                            Done::Closed(Synced {
+                                title: title.clone(),
+                                number: *number,
+                                created: false,
+                                feature: feature.clone(),
+                                renamed_from: None,
+                                parent: None,
+                            })
+                        });
+                        (title, "close issue", closed)
+                    }
+                    _ => unreachable!("only issue operations are left"),
+                };
+                match &result {
+                    Ok(_) => journaled(index),
+                    Err(_) => failed.store(true, Ordering::SeqCst),
+                }
+                Some((title, action, result))
+            }
+        })
+        .buffered(execution.jobs.max(1))
+        .collect()
+        .await;
+    for outcome in results {
+        let Some((title, action, result)) = outcome else {
+            report.skipped += 1;
+            continue;
+        };
+        match result {
+            Ok(Done::Issue(synced)) => report.issues.push(synced),
+            Ok(Done::Updated(updated)) => report.updated.push(updated),
+            Ok(Done::Closed(closed)) => report.closed.push(closed),
+            Err(error) => report.failures.push(Failure {
+                title: title.clone(),
+                action,
+                error,
+            }),
+        }
+    }
+    link(forge, &mut report, &bodies, &stop).await;
+    report
+}
+
+/// Link the task issues created in this run to their parents: as
+/// sub-issues where the forge has them, and by referring to them from the
+/// parent's task list.
+async fn link(
+    forge: &impl Forge,
+    report: &mut SyncReport,
+    bodies: &HashMap<String, String>,
+    stop: &impl Fn() -> bool,
+) {
+    let mut parents: Vec<&str> = Vec::new();
+    for synced in report.issues.iter().filter(|s| s.created) {
+        if let Some(parent) = synced.parent.as_deref() {
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+    }
+    let mut failures = Vec::new();
+    for parent in parents {
+        if stop() {
+            break;
+        }
+        let Some(issue) = report
+            .issues
+            .iter()
+            .find(|s| title_key(&s.title) == title_key(parent))
+        else {
+            continue;
+        };
+        let mut body = bodies.get(&title_key(parent)).cloned().unwrap_or_default();
+        let children = report
+            .issues
+            .iter()
+            .filter(|s| s.created && s.parent.as_deref() == Some(parent));
+        let mut result = Ok(());
+        for child in children {
+            result = forge.add_sub_issue(issue.number, child.number).await;
+            if result.is_err() {
+                break;
+            }
+            let name = child
+                .title
+                .strip_prefix(parent)
+                .and_then(|rest| rest.strip_prefix(" / "))
+                .unwrap_or(&child.title);
+            body = link_task(&body, name, child.number);
+        }
+        if let Some(marker) = Marker::find(&body) {
+            body = stamp(&body, &marker.id);
+        }
+        if result.is_ok() && bodies.get(&title_key(parent)) != Some(&body) {
+            let update = IssueUpdate {
+                body: Some(&body),
+                ..IssueUpdate::default()
+            };
+            result = forge.update_issue(issue.number, &update).await;
+        }
+        if let Err(error) = result {
+            failures.push(Failure {
+                title: parent.to_string(),
+                action: "link issue",
+                error,
+            });
+        }
+    }
+    report.failures.extend(failures);
+}
+
+/// `roadmap` split by the repository its features' issues belong in, to
+/// sync several repositories in one run. Features without a `Repo:` line,
+/// or naming `default`, make up the first part, keyed `None`, which also
+/// keeps the milestones no feature uses; every other part holds the
+/// features naming one repository and the milestones they use. A roadmap
+/// naming no other repository comes back whole.
+pub fn split_by_repo(roadmap: &Roadmap, default: Option<&str>) -> Vec<(Option<String>, Roadmap)> {
+    let target = |feature: &Feature| {
+        let repo = feature.repo.as_deref()?.trim();
+        let default = default.is_some_and(|default| default.eq_ignore_ascii_case(repo));
+        (!default).then(|| repo.to_string())
+    };
+    if roadmap
+        .features
+        .iter()
+        .all(|feature| target(feature).is_none())
+    {
+        return vec![(None, roadmap.clone())];
+    }
+    let empty = Roadmap {
+        milestones: Vec::new(),
+        features: Vec::new(),
+        ..roadmap.clone()
+    };
+    let mut parts: Vec<(Option<String>, Roadmap)> = vec![(None, empty.clone())];
+    for feature in &roadmap.features {
+        let index = match target(feature) {
+            None => 0,
+            Some(repo) => {
+                let found = parts.iter().position(|(name, _)| {
+                    name.as_deref()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&repo))
+                });
+                found.unwrap_or_else(|| {
+                    parts.push((Some(repo), empty.clone()));
+                    parts.len() - 1
+                })
+            }
+        };
+        parts[index].1.features.push(feature.clone());
+    }
+    let uses = |features: &[Feature], milestone: &Milestone| {
+        features
+            .iter()
+            .any(|feature| milestone_of(roadmap, feature) == Some(&milestone.name))
+    };
+    for (repo, part) in &mut parts {
+        part.milestones = roadmap
+            .milestones
+            .iter()
+            .filter(|milestone| {
+                uses(&part.features, milestone)
+                    || (repo.is_none() && !uses(&roadmap.features, milestone))
+            })
+            .cloned()
+            .collect();
+        graph::resolve(part);
+    }
+    parts.retain(|(repo, part)| {
+        repo.is_some() || !part.features.is_empty() || !part.milestones.is_empty()
+    });
+    parts
+}
+
+/// Leave the handles that cannot be assigned issues in the repository out of
+/// `plan`'s operations, with a warning for each, rather than have the forge
+/// reject the issues when the plan is applied. The forge is only asked when
+/// some operation assigns anyone.
+pub async fn check_assignees(plan: &mut Plan, forge: &impl Forge) -> Result<(), GitHubError> {
+    let assigns = plan.operations.iter().any(|operation| match operation {
+        Operation::CreateIssue { assignees, .. }
+        | Operation::UpdateIssue {
+            assignees: Some(assignees),
+            ..
+        } => !assignees.is_empty(),
+        _ => false,
+    });
+    if assigns {
+        let assignable = forge.list_assignees().await?;
+        drop_unassignable(plan, &assignable);
+    }
+    Ok(())
+}
+
+fn drop_unassignable(plan: &mut Plan, assignable: &[RemoteUser]) {
+    // Handles left out, with the issues they were left off, in order.
+    let mut dropped: Vec<(String, Vec<String>)> = Vec::new();
+    for operation in &mut plan.operations {
+        let title = match operation {
+            Operation::CreateIssue { title, .. } | Operation::UpdateIssue { title, .. } => {
+                title.clone()
+            }
+            _ => continue,
+        };
+        let Some(assignees) = assignees_mut(operation) else {
+            continue;
+        };
+        assignees.retain(|handle| {
+            let login = handle.trim_start_matches('@');
+            if assignable
+                .iter()
+                .any(|user| user.login.eq_ignore_ascii_case(login))
+            {
+                return true;
+            }
+            match dropped.iter_mut().find(|(dropped, _)| dropped == handle) {
+                Some((_, issues)) => issues.push(title.clone()),
+                None => dropped.push((handle.clone(), vec![title.clone()])),
+            }
+            false
+        });
+    }
+    for (handle, issues) in dropped {
+        plan.warnings.push(format!(
+            "'{}' cannot be assigned issues in {}; left unassigned on '{}'",
+            handle,
+            plan.repo,
+            issues.join("', '")
+        ));
+    }
+}
+
+/// The assignees an operation sets, if it sets any.
+fn assignees_mut(operation: &mut Operation) -> Option<&mut Vec<String>> {
+    match operation {
+        Operation::CreateIssue { assignees, .. } => Some(assignees),
+        Operation::UpdateIssue { assignees, .. } => assignees.as_mut(),
+        _ => None,
+    }
+}
+
+/// Plan and apply in one go; see [`plan`] for `matching` and `reconcile`, and
+/// [`apply`] for `execution` and `progress`, which here also receives the
+/// number of steps.
+pub async fn sync(
+    roadmap: &Roadmap,
+    texts: &[IssueText],
+    forge: &impl Forge,
+    matching: Matching<'_>,
+    reconcile: Reconcile<'_>,
+    execution: Execution<'_>,
+    mut progress: impl FnMut(usize, usize, &Operation),
+) -> Result<SyncReport, GitHubError> {
+    let milestones = forge.list_milestones().await?;
+    let issues = forge.list_issues().await?;
+    let plan = plan(
+        roadmap,
+        texts,
+        forge.repo(),
+        &milestones,
+        &issues,
+        matching,
+        reconcile,
+    );
+    let total = plan.operations.len();
+    let report = execute(
+        &plan,
+        forge,
+        &milestones,
+        &issues,
+        execution,
+        |step, operation| progress(step, total, operation),
+    )
+    .await;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::{LabelUpdate, NewLabel, RemoteComment, RemoteLabel};
+    use crate::template::IssueTemplates;
+    use gitscaffold_parser::Task;
+
+    #[test]
+    fn issue_body_lists_tasks_as_checklist() {
+        let feature = Feature {
+            title: "Login".into(),
+            description: "Let users sign in.\n".into(),
+            tasks: vec![
+                Task {
+                    title: "Form".into(),
+                    completed: true,
+                    ..Task::default()
+                },
+                Task {
+                    title: "Session".into(),
+                    ..Task::default()
+                },
+                Task {
+                    title: "Expiry".into(),
+                    depth: 1,
+                    ..Task::default()
+                },
+            ],
+            ..Feature::default()
+        };
+        assert_eq!(
+            issue_body(&feature),
+            "Let users sign in.\n\n## Tasks\n\n- [x] Form\n- [ ] Session\n  - [ ] Expiry"
+        );
+    }
+
+    #[test]
+    fn plans_only_what_is_missing() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Milestones\n- M1\n- M2 — 2025-06-30\n\n## Features\n\n\
+             ### Login\nMilestone: M2\n\n### Search\nMilestone: M1\n\n### Export !!!\nMilestone: M9\n\n\
+             ## Icebox\n\n### Themes\nMilestone: M1\n",
+        )
+        .unwrap();
+        let milestones = vec![RemoteMilestone {
+            number: 1,
+            title: "M1".into(),
+            state: "open".into(),
+            due_on: None,
+            description: None,
+        }];
+        let issues = vec![RemoteIssue {
+            number: 4,
+            title: "Search ".into(),
+            state: "closed".into(),
+            body: None,
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
+            html_url: None,
+            pull_request: None,
+            node_id: String::new(),
+            created_at: None,
+            closed_at: None,
+        }];
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let plan = plan(
+            &roadmap,
+            &texts,
+            "acme/app",
+            &milestones,
+            &issues,
+            Matching::default(),
+            Reconcile::default(),
+        );
+        assert_eq!(plan.existing_milestones[0].number, 1);
+        assert_eq!(plan.existing_issues[0].number, 4);
+        let summary: Vec<String> = plan.operations.iter().map(|op| op.to_string()).collect();
+        assert_eq!(
+            summary,
+            [
+                "create milestone 'M2' due 2025-06-30",
+                "create issue 'Export'",
+                "create issue 'Login' in milestone 'M2'",
+            ]
+        );
+
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(serde_json::from_str::<Plan>(&json).unwrap(), plan);
+
+        let with_deferred = super::plan(
+            &roadmap,
+            &texts,
+            "acme/app",
+            &milestones,
+            &issues,
+            Matching::default(),
+            Reconcile {
+                include_deferred: true,
+                ..Reconcile::default()
+            },
+        );
+        assert_eq!(
+            with_deferred.operations.last().unwrap().to_string(),
+            "create issue 'Themes' in milestone 'M1'"
+        );
+    }
+
+    #[test]
+    fn matches_milestones_ignoring_case() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Milestones\n- M1\n- M2\n\n## Features\n\n\
+             ### Login\nMilestone: m2\nStatus: done\n\n### Search\nMilestone: m1\n",
+        )
+        .unwrap();
+        let milestones = [RemoteMilestone {
+            number: 2,
+            title: "M2".into(),
+            state: "open".into(),
+            due_on: None,
+            description: None,
+        }];
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let plan = plan(
+            &roadmap,
+            &texts,
+            "acme/app",
+            &milestones,
+            &[],
+            Matching::default(),
+            Reconcile {
+                milestones: true,
+                ..Reconcile::default()
+            },
+        );
+        let summary: Vec<String> = plan.operations.iter().map(|op| op.to_string()).collect();
+        assert_eq!(
+            summary,
+            [
+                "create milestone 'M1'",
+                "create issue 'Login' in milestone 'M2'",
+                "create issue 'Search' in milestone 'M1'",
+                "close milestone #2 'M2'",
+            ]
+        );
+    }
+
+    #[test]
+    fn renames_the_issues_of_renamed_features() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Features\n\n### Add OAuth2 login\n\n### Full-text search\n\n### Dark mode\n",
+        )
+        .unwrap();
+        let issue = |number, title: &str| RemoteIssue {
+            number,
+            title: title.into(),
+            state: "open".into(),
+            body: None,
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            milestone: None,
+            html_url: None,
+            pull_request: None,
+            node_id: String::new(),
+            created_at: None,
+            closed_at: None,
+        };
+        let issues = [
+            issue(1, "Add OAuth login"),
+            issue(2, "Search"),
+            issue(3, "Export"),
+        ];
+        // Recorded by an earlier sync and renamed on the forge since.
+        let mut state = RepoState::default();
+        state.features.insert("Full-text search".into(), 2);
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let summary = |matching| -> Vec<String> {
+            plan(
+                &roadmap,
+                &texts,
+                "acme/app",
+                &[],
+                &issues,
+                matching,
+                Reconcile::default(),
+            )
+            .operations
+            .iter()
+            .map(Operation::to_string)
+            .collect()
+        };
+        assert_eq!(
+            summary(Matching {
+                state: Some(&state),
+                fuzzy_threshold: Some(0.8),
+            }),
+            [
+                "rename issue #1 'Add OAuth login' to 'Add OAuth2 login'",
+                "rename issue #2 'Search' to 'Full-text search'",
+                "create issue 'Dark mode'",
+            ]
+        );
+        // Without fuzzy matching only the recorded issue is renamed.
+        assert_eq!(
+            summary(Matching {
+                state: Some(&state),
+                fuzzy_threshold: None,
+            })[..2],
+            [
+                "create issue 'Add OAuth2 login'",
+                "rename issue #2 'Search' to 'Full-text search'",
+            ]
+        );
+    }
+
+    #[test]
+    fn updates_the_fields_that_drifted_unless_protected() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Milestones\n- M1\n\n## Features\n\n### Login\nLet users sign in.\n\
+             Milestone: M1\nLabels: auth, P1\n",
+        )
+        .unwrap();
+        let json = serde_json::json!({
+            "number": 7,
+            "title": "Login",
+            "state": "open",
+            "body": "Let users sign in.\r\n",
+            "labels": [{"name": "p1"}, {"name": "triaged"}],
+            "milestone": {"number": 1, "title": "M0", "state": "open"},
+        });
+        let issues = [serde_json::from_value::<RemoteIssue>(json).unwrap()];
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let summary = |update: &[Field]| -> Vec<String> {
+            plan(
+                &roadmap,
+                &texts,
+                "acme/app",
+                &[],
+                &issues,
+                Matching::default(),
+                Reconcile {
+                    update,
+                    ..Reconcile::default()
+                },
+            )
+            .operations
+            .iter()
+            .map(Operation::to_string)
+            .collect()
+        };
+        assert_eq!(
+            summary(&Field::ALL),
+            [
+                "create milestone 'M1'",
+                "update issue #7 'Login': labels [auth, P1], milestone 'M1'",
+            ]
+        );
+        assert_eq!(summary(&[Field::Body, Field::Assignees]).len(), 1);
+        assert_eq!(summary(&[]).len(), 1);
+
+        let plan = plan(
+            &roadmap,
+            &texts,
+            "acme/app",
+            &[],
+            &issues,
+            Matching::default(),
+            Reconcile {
+                update: &Field::ALL,
+                notify: Notify::Comment,
+                ..Reconcile::default()
+            },
+        );
+        let Operation::UpdateIssue {
+            comment: Some(comment),
+            ..
+        } = &plan.operations[1]
+        else {
+            panic!("expected an update with a comment");
+        };
+        assert!(comment.contains("- **Labels:** added `auth`; removed `triaged`\n"));
+        assert!(comment.contains("- **Milestone:** M0 → M1"));
+        assert!(plan.operations[1]
+            .to_string()
+            .ends_with(" (with a comment)"));
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(serde_json::from_str::<Plan>(&json).unwrap(), plan);
+    }
+
+    #[test]
+    fn manages_the_lifecycle_of_existing_milestones() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Milestones\n\n### M1: Alpha — 2025-07-01\nFirst usable build.\n\n\
+             ### M2 — 2025-09-01\n\n## Features\n\n### Login\nMilestone: M1: Alpha\nStatus: done\n\n\
+             ### Search\nMilestone: M2\n",
+        )
+        .unwrap();
+        let milestone = |number, title: &str, due_on: &str| RemoteMilestone {
+            number,
+            title: title.into(),
+            state: "open".into(),
+            due_on: Some(due_on.into()),
+            description: None,
+        };
+        let milestones = [
+            milestone(1, "M1 Alpha", "2025-06-01T00:00:00Z"),
+            milestone(2, "M2", "2025-09-01T07:00:00Z"),
+        ];
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let summary = |manage| -> Vec<String> {
+            plan(
+                &roadmap,
+                &texts,
+                "acme/app",
+                &milestones,
+                &[],
+                Matching {
+                    state: None,
+                    fuzzy_threshold: Some(0.8),
+                },
+                Reconcile {
+                    milestones: manage,
+                    ..Reconcile::default()
+                },
+            )
+            .operations
+            .iter()
+            .filter(|operation| operation.is_milestone())
+            .map(Operation::to_string)
+            .collect()
+        };
+        assert_eq!(
+            summary(true),
+            [
+                "rename milestone #1 'M1 Alpha' to 'M1: Alpha'",
+                "update milestone #1 'M1: Alpha': due 2025-07-01, description",
+                "close milestone #1 'M1: Alpha'",
+            ]
+        );
+        assert_eq!(
+            summary(false),
+            ["create milestone 'M1: Alpha' due 2025-07-01"]
+        );
+    }
+
+    #[test]
+    fn creates_task_issues_below_their_features() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Features\n\n### Login\nLabels: auth\n\n\
+             - [ ] Form\n  - [ ] Validation\n- [x] Session expiry\n",
+        )
+        .unwrap();
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let existing: RemoteIssue = serde_json::from_value(serde_json::json!({
+            "number": 5,
+            "title": "Login / Session expiry",
+            "state": "closed",
+        }))
+        .unwrap();
+        let plan = plan(
+            &roadmap,
+            &texts,
+            "acme/app",
+            &[],
+            &[existing],
+            Matching::default(),
+            Reconcile {
+                tasks_as_issues: Some(0),
+                ..Reconcile::default()
+            },
+        );
+        let created: Vec<(&str, &str, &[String], Option<&str>)> = plan
+            .operations
+            .iter()
+            .filter_map(|operation| match operation {
+                Operation::CreateIssue {
+                    title,
+                    body,
+                    labels,
+                    parent,
+                    ..
+                } => Some((
+                    title.as_str(),
+                    body.as_str(),
+                    &labels[..],
+                    parent.as_deref(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(created.len(), 2);
+        let (title, body, _, parent) = created[0];
+        assert_eq!((title, parent), ("Login", None));
+        assert!(body.contains("- [ ] Form\n  - [ ] Validation\n- [x] #5"));
+        let (title, body, labels, parent) = created[1];
+        assert_eq!((title, parent), ("Login / Form", Some("Login")));
+        assert!(body.contains("- [ ] Validation"));
+        assert_eq!(labels, ["auth"]);
+        assert_eq!(plan.existing_issues[0].parent.as_deref(), Some("Login"));
+
+        assert_eq!(
+            link_task("Intro\n\n- [ ] Form\n  - [ ] Form", "Form", 7),
+            "Intro\n\n- [ ] #7\n  - [ ] Form"
+        );
+    }
+
+    #[test]
+    fn prunes_the_issues_of_deleted_features() {
+        let roadmap =
+            gitscaffold_parser::parse_markdown("# P\n\n## Features\n\n### Login\n").unwrap();
+        let issue = |number, title: &str, state: &str, label: Option<&str>| {
+            let labels: Vec<_> = label
+                .map(|name| serde_json::json!({ "name": name }))
+                .into_iter()
+                .collect();
+            let json = serde_json::json!({
+                "number": number,
+                "title": title,
+                "state": state,
+                "labels": labels,
+            });
+            serde_json::from_value::<RemoteIssue>(json).unwrap()
+        };
+        let issues = [
+            issue(1, "Login", "open", Some("gitscaffold")),
+            issue(2, "[old] Export", "open", None),
+            issue(3, "Search", "open", Some("gitscaffold")),
+            issue(4, "Dark mode", "closed", Some("gitscaffold")),
+            issue(5, "Reported bug", "open", None),
+        ];
+        let mut state = RepoState::default();
+        state.features.insert("Login".into(), 1);
+        state.features.insert("Export".into(), 2);
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let plan = |prune| {
+            plan(
+                &roadmap,
+                &texts,
+                "acme/app",
+                &[],
+                &issues,
+                Matching {
+                    state: Some(&state),
+                    fuzzy_threshold: None,
+                },
+                Reconcile {
+                    prune,
+                    marker_label: Some("gitscaffold"),
+                    ..Reconcile::default()
+                },
+            )
+        };
+        assert!(plan(false).operations.is_empty());
+        let plan = plan(true);
+        let summary: Vec<String> = plan.operations.iter().map(Operation::to_string).collect();
+        assert_eq!(
+            summary,
+            ["close issue #2 '[old] Export'", "close issue #3 'Search'"]
+        );
+        let Operation::CloseIssue { comment, .. } = &plan.operations[0] else {
+            unreachable!();
+        };
+        assert!(comment.contains("'Export'"));
+    }
+
+    #[test]
+    fn prunes_only_the_marked_issues_of_this_roadmap() {
+        let roadmap =
+            gitscaffold_parser::parse_markdown("# P\n\n## Features\n\n### Login\n\n- [ ] Form\n")
+                .unwrap();
+        let issue = |number, title: &str, id: &str| {
+            let json = serde_json::json!({
+                "number": number,
+                "title": title,
+                "state": "open",
+                "body": stamp("Body", id),
+            });
+            serde_json::from_value::<RemoteIssue>(json).unwrap()
+        };
+        let issues = [
+            issue(1, "Login", "login"),
+            issue(2, "Login / Form", "login/form"),
+            issue(3, "Login / Captcha", "login/captcha"),
+            issue(4, "Exporting", "export"),
+            issue(5, "Billing", "billing"),
+        ];
+        let mut state = RepoState::default();
+        state.features.insert("Login".into(), 1);
+        state.features.insert("Export".into(), 9);
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let closed = |tasks_as_issues| -> Vec<String> {
+            plan(
+                &roadmap,
+                &texts,
+                "acme/app",
+                &[],
+                &issues,
+                Matching {
+                    state: Some(&state),
+                    fuzzy_threshold: None,
+                },
+                Reconcile {
+                    prune: true,
+                    tasks_as_issues,
+                    ..Reconcile::default()
+                },
+            )
+            .operations
+            .iter()
+            .filter(|operation| matches!(operation, Operation::CloseIssue { .. }))
+            .map(Operation::to_string)
+            .collect()
+        };
+        // Task issues are left alone unless tasks are synced, and another
+        // roadmap's issues always are.
+        assert_eq!(closed(None), ["close issue #4 'Exporting'"]);
+        assert_eq!(
+            closed(Some(0)),
+            [
+                "close issue #3 'Login / Captcha'",
+                "close issue #4 'Exporting'"
+            ]
+        );
+    }
+
+    #[test]
+    fn only_iso_due_dates_are_sent() {
+        assert_eq!(
+            due_on(Some("2025-06-30")).as_deref(),
+            Some("2025-06-30T00:00:00Z")
+        );
+        assert_eq!(due_on(Some("soon")), None);
+        assert_eq!(due_on(None), None);
+    }
+
+    /// A forge whose issue creation fails for titles containing "fail".
+    struct FlakyForge;
+
+    impl Forge for FlakyForge {
+        fn name(&self) -> &'static str {
+            "Flaky"
+        }
+
+        fn repo(&self) -> &str {
+            "acme/app"
+        }
+
+        async fn list_milestones(&self) -> Result<Vec<RemoteMilestone>, GitHubError> {
+            Ok(Vec::new())
+        }
+
+        async fn list_issues(&self) -> Result<Vec<RemoteIssue>, GitHubError> {
+            Ok(Vec::new())
+        }
+
+        async fn create_milestone(
+            &self,
+            _: &NewMilestone<'_>,
+        ) -> Result<RemoteMilestone, GitHubError> {
+            Err(GitHubError::Unsupported("milestones"))
+        }
+
+        async fn update_milestone(
+            &self,
+            _: u64,
+            _: &MilestoneUpdate<'_>,
+        ) -> Result<(), GitHubError> {
+            Err(GitHubError::Unsupported("milestones"))
+        }
+
+        async fn create_issue(&self, issue: &NewIssue<'_>) -> Result<RemoteIssue, GitHubError> {
+            if issue.title.contains("fail") {
+                return Err(GitHubError::Api {
+                    status: 422,
+                    message: "rejected".into(),
+                });
+            }
+            let json = serde_json::json!({"number": 1, "title": issue.title, "state": "open"});
+            Ok(serde_json::from_value(json).unwrap())
+        }
+
+        async fn rename_issue(&self, _: u64, _: &str) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn update_issue(&self, _: u64, _: &IssueUpdate<'_>) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn comment_on_issue(&self, _: u64, _: &str) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn list_comments(&self, _: u64) -> Result<Vec<RemoteComment>, GitHubError> {
+            Ok(Vec::new())
+        }
+
+        async fn update_comment(&self, _: u64, _: u64, _: &str) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn close_issue(&self, _: u64, _: &str) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn add_sub_issue(&self, _: u64, _: u64) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn delete_issue(&self, _: &RemoteIssue) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn lock_issue(&self, _: u64) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn upload_file(
+            &self,
+            _: &str,
+            _: &[u8],
+            _: Option<&str>,
+        ) -> Result<String, GitHubError> {
+            Err(GitHubError::Unsupported("uploads"))
+        }
+
+        async fn list_labels(&self) -> Result<Vec<RemoteLabel>, GitHubError> {
+            Ok(Vec::new())
+        }
+
+        async fn create_label(&self, _: &NewLabel<'_>) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn update_label(&self, _: &str, _: &LabelUpdate<'_>) -> Result<(), GitHubError> {
+            Ok(())
+        }
+
+        async fn list_assignees(&self) -> Result<Vec<RemoteUser>, GitHubError> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn stops_after_the_first_failure_unless_keeping_going() {
+        let roadmap = gitscaffold_parser::parse_markdown(
+            "# P\n\n## Features\n\n### Login\n\n### fail here\n\n### Search\n\n### Export\n",
+        )
+        .unwrap();
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        let run = |execution| {
+            futures::executor::block_on(sync(
+                &roadmap,
+                &texts,
+                &FlakyForge,
+                Matching::default(),
+                Reconcile::default(),
+                execution,
+                |_, _, _| {},
+            ))
+            .unwrap()
+        };
+        let report = run(Execution::default());
+        assert_eq!((report.issues.len(), report.failures.len()), (1, 1));
+        assert_eq!(report.skipped, 2);
+
+        let report = run(Execution {
+            keep_going: true,
+            ..Execution::default()
+        });
+        assert_eq!((report.issues.len(), report.failures.len()), (3, 1));
+        assert_eq!(report.skipped, 0);
+
+        let interrupted = AtomicBool::new(true);
+        let report = run(Execution {
+            keep_going: true,
+            interrupted: Some(&interrupted),
+            ..Execution::default()
+        });
+        assert_eq!((report.issues.len(), report.skipped), (0, 4));
+
+        // Batches stop the same way, and still report what they did.
+        let report = run(Execution {
+            batch: 2,
+            ..Execution::default()
+        });
+        assert_eq!((report.issues.len(), report.failures.len()), (1, 1));
+        assert_eq!(report.skipped, 2);
+        let report = run(Execution {
+            batch: 3,
+            keep_going: true,
+            ..Execution::default()
+        });
+        assert_eq!((report.issues.len(), report.failures.len()), (3, 1));
+
+        // The journal leaves the failed and skipped operations to resume.
+        let dir = std::env::temp_dir().join(format!("gitscaffold-sync-{}", std::process::id()));
+        let journal = Journal::new(&dir, "o/r");
+        let report = run(Execution {
+            journal: Some(&journal),
+            ..Execution::default()
+        });
+        let unfinished = journal.load().unwrap().unwrap();
+        assert_eq!(unfinished.done, [0].into());
+        assert_eq!(unfinished.remaining().operations.len(), 3);
+        assert!(journal.finish(&report).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn leaves_unassignable_handles_out_with_a_warning() {
+        let create = |title: &str, assignees: &[&str]| Operation::CreateIssue {
+            title: title.into(),
+            body: String::new(),
+            labels: vec![],
+            assignees: assignees.iter().map(|a| a.to_string()).collect(),
+            milestone: None,
+            feature: None,
+            parent: None,
+        };
+        let mut plan = Plan {
+            version: PLAN_VERSION,
+            repo: "acme/app".into(),
+            existing_milestones: vec![],
+            existing_issues: vec![],
+            operations: vec![
+                create("Login", &["@Alice", "bob"]),
+                create("Export", &["bob"]),
+            ],
+            warnings: vec![],
+        };
+        let assignable = [RemoteUser {
+            login: "alice".into(),
+        }];
+        drop_unassignable(&mut plan, &assignable);
+        assert_eq!(
+            plan.operations,
+            [create("Login", &["@Alice"]), create("Export", &[])]
+        );
+        assert_eq!(
+            plan.warnings,
+            ["'bob' cannot be assigned issues in acme/app; left unassigned on 'Login', 'Export'"]
+        );
+    }
+
+    #[test]
+    fn splits_features_by_their_repository() {
+        let milestone = |name: &str| Milestone {
+            name: name.into(),
+            ..Milestone::default()
+        };
+        let feature = |title: &str, milestone: &str, repo: Option<&str>| Feature {
+            title: title.into(),
+            milestone: Some(milestone.into()),
+            repo: repo.map(str::to_string),
+            ..Feature::default()
+        };
+        let roadmap = Roadmap {
+            milestones: vec![milestone("M1"), milestone("M2"), milestone("Later")],
+            features: vec![
+                feature("Login", "M1", None),
+                feature("API keys", "M2", Some("acme/api")),
+                feature("Tokens", "M1", Some("Acme/API")),
+                feature("Home", "M2", Some("acme/web")),
+            ],
+            ..Roadmap::default()
+        };
+        let parts = split_by_repo(&roadmap, Some("acme/web"));
+        let summary: Vec<(Option<&str>, Vec<&str>, Vec<&str>)> = parts
+            .iter()
+            .map(|(repo, part)| {
+                (
+                    repo.as_deref(),
+                    part.features.iter().map(|f| f.title.as_str()).collect(),
+                    part.milestones.iter().map(|m| m.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (None, vec!["Login", "Home"], vec!["M1", "M2", "Later"]),
+                (
+                    Some("acme/api"),
+                    vec!["API keys", "Tokens"],
+                    vec!["M1", "M2"]
+                ),
+            ]
+        );
+
+        let single = Roadmap {
+            features: vec![feature("Login", "M1", Some("acme/web"))],
+            ..roadmap.clone()
+        };
+        assert_eq!(split_by_repo(&single, Some("acme/web")), [(None, single)]);
+    }
+}
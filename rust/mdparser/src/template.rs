@@ -0,0 +1,401 @@
+//! Issue titles and bodies rendered from templates.
+//!
+//! Sync renders each feature through two [minijinja] templates,
+//! `issue_title.j2` and `issue_body.j2`. The defaults are built in; a
+//! template directory can replace either of them. Templates see:
+//!
+//! - `feature`: the feature, with the keys of the JSON output;
+//! - `milestone`: the feature's milestone (`name`, `due_date`), if the
+//!   roadmap defines it;
+//! - `roadmap`: the roadmap's `name`, `description` and `metadata`;
+//! - `checklist`: the feature's tasks as a Markdown checklist;
+//! - `provenance`: where the feature is in the repository (`file`, `line`,
+//!   `commit`, `short_commit`, `url`, `modified`), when enabled with
+//!   [`IssueTemplates::with_provenance`].
+//! - the values of custom fields under the `var` names `[fields]` gives
+//!   them, with [`IssueTemplates::with_fields`].
+//!
+//! With [`IssueTemplates::with_absolute_links`], relative links in the
+//! feature's description and `links` point at the files on the forge.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use minijinja::{context, Environment, Value};
+use pulldown_cmark::{Event, LinkType, Parser, Tag};
+
+use gitscaffold_parser::text::{title_key, truncate};
+use gitscaffold_parser::{extract, Feature, FieldMapping, Roadmap, TitleSettings};
+
+use crate::error::ScaffoldError;
+use crate::provenance::Provenance;
+
+pub use minijinja::Error as TemplateError;
+
+pub const TITLE_TEMPLATE: &str = "issue_title.j2";
+pub const BODY_TEMPLATE: &str = "issue_body.j2";
+
+/// The longest issue title every forge accepts: GitLab and Gitea allow
+/// 255 characters, GitHub 256. Longer titles are cut to fit.
+pub const MAX_TITLE_CHARS: usize = 255;
+
+const DEFAULT_TITLE: &str = include_str!("../templates/issue_title.j2");
+const DEFAULT_BODY: &str = include_str!("../templates/issue_body.j2");
+
+/// The title and body of the issue for one feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueText {
+    pub title: String,
+    pub body: String,
+}
+
+pub struct IssueTemplates {
+    env: Environment<'static>,
+    provenance: Option<Provenance>,
+    /// The repository and the ref relative links are made absolute at.
+    links: Option<(Provenance, String)>,
+    titles: TitleSettings,
+    /// Whether the title template came from a template directory.
+    custom_title: bool,
+    /// Template variables and the custom fields they hold, by key.
+    vars: Vec<(String, String)>,
+}
+
+impl Default for IssueTemplates {
+    fn default() -> Self {
+        let mut env = Environment::new();
+        // The built-in templates are checked by the tests below.
+        env.add_template(TITLE_TEMPLATE, DEFAULT_TITLE)
+            .expect("default title template");
+        env.add_template(BODY_TEMPLATE, DEFAULT_BODY)
+            .expect("default body template");
+        IssueTemplates {
+            env,
+            provenance: None,
+            links: None,
+            titles: TitleSettings::default(),
+            custom_title: false,
+            vars: Vec::new(),
+        }
+    }
+}
+
+impl IssueTemplates {
+    /// The default templates, overridden by the `issue_title.j2` and
+    /// `issue_body.j2` files in `dir` that exist.
+    pub fn from_dir(dir: &Path) -> Result<Self, ScaffoldError> {
+        let mut templates = IssueTemplates::default();
+        for name in [TITLE_TEMPLATE, BODY_TEMPLATE] {
+            let path = dir.join(name).display().to_string();
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(ScaffoldError::io(&path, e)),
+            };
+            templates
+                .env
+                .add_template_owned(name, source)
+                .map_err(|e| ScaffoldError::parse(Some(&path), e))?;
+            templates.custom_title |= name == TITLE_TEMPLATE;
+        }
+        Ok(templates)
+    }
+
+    /// Title issues as `[titles]` in the config says: with a milestone
+    /// prefix, a feature number or a template of their own. An
+    /// `issue_title.j2` from a template directory still wins.
+    pub fn with_titles(mut self, titles: &TitleSettings) -> Result<Self, ScaffoldError> {
+        self.titles = titles.clone();
+        if self.custom_title {
+            return Ok(self);
+        }
+        let source = match &titles.template {
+            Some(template) => template.clone(),
+            None if titles.milestone_prefix || titles.id_prefix.is_some() => {
+                let mut source = String::new();
+                if titles.milestone_prefix {
+                    source.push_str(
+                        "{% if feature.milestone %}[{{ feature.milestone }}] {% endif %}",
+                    );
+                }
+                if titles.id_prefix.is_some() {
+                    source.push_str("{{ id }} ");
+                }
+                source + DEFAULT_TITLE.trim_end()
+            }
+            None => return Ok(self),
+        };
+        self.env
+            .add_template_owned(TITLE_TEMPLATE, source)
+            .map_err(|e| ScaffoldError::parse(Some("[titles] template"), e))?;
+        Ok(self)
+    }
+
+    /// Give templates the values of the custom fields that `fields` maps
+    /// to a `var`, under that name.
+    pub fn with_fields(mut self, fields: &BTreeMap<String, FieldMapping>) -> Self {
+        self.vars = fields
+            .iter()
+            .filter_map(|(key, mapping)| Some((mapping.var.clone()?, key.clone())))
+            .collect();
+        self
+    }
+
+    /// Give templates the `provenance` of each feature, which the default
+    /// body renders as a footer.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Rewrite relative links in feature descriptions, like
+    /// `./docs/design.md`, to the files on the forge's website at
+    /// `reference`, a branch, tag or commit of the repository.
+    pub fn with_absolute_links(mut self, provenance: Provenance, reference: String) -> Self {
+        self.links = Some((provenance, reference));
+        self
+    }
+
+    /// Render the issue title and body for every feature of `roadmap`, in
+    /// order.
+    pub fn render(&self, roadmap: &Roadmap) -> Result<Vec<IssueText>, TemplateError> {
+        roadmap
+            .features
+            .iter()
+            .enumerate()
+            .map(|(index, feature)| self.render_feature(roadmap, index, feature))
+            .collect()
+    }
+
+    fn render_feature(
+        &self,
+        roadmap: &Roadmap,
+        index: usize,
+        feature: &Feature,
+    ) -> Result<IssueText, TemplateError> {
+        let number = format!("{:0width$}", index + 1, width = self.titles.id_digits);
+        let id = match &self.titles.id_prefix {
+            Some(prefix) => format!("{}-{}", prefix, number),
+            None => number,
+        };
+        let absolute;
+        let feature = match &self.links {
+            Some((provenance, reference)) => {
+                absolute = absolute_links(feature, |url| {
+                    provenance.absolute_link(feature, url, reference)
+                });
+                &absolute
+            }
+            None => feature,
+        };
+        let milestone = feature.milestone.as_deref().and_then(|name| {
+            roadmap
+                .milestones
+                .iter()
+                .find(|m| title_key(&m.name) == title_key(name))
+        });
+        // Fields the feature lacks stay undefined, rendering as nothing.
+        let vars: BTreeMap<&str, &str> = self
+            .vars
+            .iter()
+            .filter_map(|(var, key)| Some((var.as_str(), feature.fields.get(key)?.as_str())))
+            .collect();
+        let ctx = context! {
+            feature,
+            milestone,
+            roadmap => context! {
+                name => &roadmap.name,
+                description => &roadmap.description,
+                metadata => &roadmap.metadata,
+            },
+            checklist => checklist(feature),
+            id,
+            provenance => self.provenance.as_ref().map(|p| p.source(feature)),
+            ..Value::from_serialize(&vars)
+        };
+        let title = self.env.get_template(TITLE_TEMPLATE)?.render(&ctx)?;
+        let body = self.env.get_template(BODY_TEMPLATE)?.render(&ctx)?;
+        Ok(IssueText {
+            title: truncate(title.trim(), MAX_TITLE_CHARS).into_owned(),
+            body: body.trim().to_string(),
+        })
+    }
+}
+
+/// `feature` with the destinations `rewrite` maps replaced in its own
+/// and its tasks' descriptions, and in its `links`.
+fn absolute_links(feature: &Feature, rewrite: impl Fn(&str) -> Option<String>) -> Feature {
+    let mut feature = feature.clone();
+    feature.description = rewrite_links(&feature.description, &rewrite);
+    for task in &mut feature.tasks {
+        task.description = rewrite_links(&task.description, &rewrite);
+    }
+    for link in &mut feature.links {
+        if let Some(url) = rewrite(&link.url) {
+            link.url = url;
+        }
+    }
+    feature
+}
+
+/// `markdown` with the destinations of its inline links and images
+/// replaced where `rewrite` gives a new one; everything else is kept as
+/// written.
+pub(crate) fn rewrite_links(markdown: &str, rewrite: impl Fn(&str) -> Option<String>) -> String {
+    let mut edits: Vec<(usize, usize, String)> = Vec::new();
+    for (event, range) in Parser::new_ext(markdown, extract::options()).into_offset_iter() {
+        let (Event::Start(Tag::Link(LinkType::Inline, url, _))
+        | Event::Start(Tag::Image(LinkType::Inline, url, _))) = event
+        else {
+            continue;
+        };
+        let Some(new) = rewrite(&url) else {
+            continue;
+        };
+        // The destination follows the text's closing `](`.
+        let markup = &markdown[range.clone()];
+        let start = markup
+            .rfind("](")
+            .and_then(|open| markup[open..].find(&*url).map(|at| range.start + open + at));
+        if let Some(start) = start {
+            edits.push((start, start + url.len(), new));
+        }
+    }
+    edits.sort_by_key(|(start, ..)| *start);
+    let mut out = String::new();
+    let mut at = 0;
+    for (start, end, new) in edits {
+        if start >= at {
+            out.push_str(&markdown[at..start]);
+            out.push_str(&new);
+            at = end;
+        }
+    }
+    out.push_str(&markdown[at..]);
+    out
+}
+
+/// The feature's tasks as a Markdown checklist, nested by depth.
+fn checklist(feature: &Feature) -> String {
+    let lines: Vec<String> = feature
+        .tasks
+        .iter()
+        .map(|task| {
+            let mark = if task.completed { 'x' } else { ' ' };
+            format!("{}- [{}] {}", "  ".repeat(task.depth), mark, task.title)
+        })
+        .collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::issue_body;
+    use gitscaffold_parser::{Milestone, Task};
+
+    fn roadmap() -> Roadmap {
+        Roadmap {
+            name: "Demo".into(),
+            milestones: vec![Milestone {
+                name: "M1".into(),
+                due_date: Some("2025-06-30".into()),
+                ..Milestone::default()
+            }],
+            features: vec![Feature {
+                title: "Login".into(),
+                description: "Let users sign in.".into(),
+                milestone: Some("M1".into()),
+                tasks: vec![
+                    Task {
+                        title: "Form".into(),
+                        completed: true,
+                        ..Task::default()
+                    },
+                    Task {
+                        title: "Expiry".into(),
+                        depth: 1,
+                        ..Task::default()
+                    },
+                ],
+                ..Feature::default()
+            }],
+            ..Roadmap::default()
+        }
+    }
+
+    #[test]
+    fn defaults_match_the_plain_issue_body() {
+        let mut roadmap = roadmap();
+        roadmap.features.push(Feature {
+            title: "Bare".into(),
+            ..Feature::default()
+        });
+        let texts = IssueTemplates::default().render(&roadmap).unwrap();
+        for (feature, text) in roadmap.features.iter().zip(&texts) {
+            assert_eq!(text.title, feature.title);
+            assert_eq!(text.body, issue_body(feature));
+        }
+    }
+
+    #[test]
+    fn templates_see_milestone_and_roadmap_context() {
+        let mut templates = IssueTemplates::default();
+        templates
+            .env
+            .add_template(
+                TITLE_TEMPLATE,
+                "[{{ roadmap.name }}] {{ feature.title }}{% if milestone %} ({{ milestone.due_date }}){% endif %}",
+            )
+            .unwrap();
+        let texts = templates.render(&roadmap()).unwrap();
+        assert_eq!(texts[0].title, "[Demo] Login (2025-06-30)");
+
+        let fields = [(
+            "Team".to_string(),
+            FieldMapping {
+                var: Some("team".into()),
+                ..FieldMapping::default()
+            },
+        )];
+        let mut templates = IssueTemplates::default().with_fields(&fields.into_iter().collect());
+        templates
+            .env
+            .add_template(TITLE_TEMPLATE, "{{ feature.title }} [{{ team }}]")
+            .unwrap();
+        let mut roadmap = roadmap();
+        roadmap.features[0]
+            .fields
+            .insert("Team".into(), "infra".into());
+        let texts = templates.render(&roadmap).unwrap();
+        assert_eq!(texts[0].title, "Login [infra]");
+    }
+
+    #[test]
+    fn titles_follow_the_configured_prefixes() {
+        let mut roadmap = roadmap();
+        roadmap.features.push(Feature {
+            title: "Bare".into(),
+            ..Feature::default()
+        });
+        let titles = |settings: TitleSettings| -> Vec<String> {
+            let templates = IssueTemplates::default().with_titles(&settings).unwrap();
+            let texts = templates.render(&roadmap).unwrap();
+            texts.into_iter().map(|text| text.title).collect()
+        };
+        let prefixed = TitleSettings {
+            milestone_prefix: true,
+            id_prefix: Some("FEAT".into()),
+            ..TitleSettings::default()
+        };
+        assert_eq!(titles(prefixed), ["[M1] FEAT-001 Login", "FEAT-002 Bare"]);
+        let custom = TitleSettings {
+            id_digits: 2,
+            template: Some("#{{ id }}: {{ feature.title | upper }}".into()),
+            ..TitleSettings::default()
+        };
+        assert_eq!(titles(custom), ["#01: LOGIN", "#02: BARE"]);
+    }
+}
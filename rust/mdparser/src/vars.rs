@@ -0,0 +1,40 @@
+//! The flags that give `${NAME}` placeholders in roadmaps their values;
+//! see [`gitscaffold_parser::vars`].
+
+use std::path::PathBuf;
+
+use gitscaffold_parser::vars::{assignment, load_file};
+use gitscaffold_parser::Config;
+
+use crate::error::ScaffoldError;
+
+/// Variable flags shared by the binaries.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct VarArgs {
+    /// Value of the `${KEY}` placeholders in roadmaps (repeatable); wins
+    /// over `--vars-file`, `[vars]` in the config and the environment
+    #[arg(long = "var", global = true, value_name = "KEY=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+    /// File of `KEY=VALUE` lines giving placeholders their values; wins
+    /// over `[vars]` in the config and the environment
+    #[arg(long, global = true, value_name = "FILE")]
+    pub vars_file: Option<PathBuf>,
+}
+
+impl VarArgs {
+    /// Add the variables to those `config` defines, replacing any of the
+    /// same name.
+    pub fn apply(&self, config: &mut Config) -> Result<(), ScaffoldError> {
+        if let Some(path) = &self.vars_file {
+            let vars =
+                load_file(path).map_err(|e| ScaffoldError::load(&path.display().to_string(), e))?;
+            config.vars.extend(vars);
+        }
+        config.vars.extend(self.vars.iter().cloned());
+        Ok(())
+    }
+}
+
+fn parse_var(value: &str) -> Result<(String, String), String> {
+    assignment(value).ok_or_else(|| format!("expected KEY=VALUE, got '{}'", value))
+}
@@ -0,0 +1,104 @@
+//! GitHub push webhooks, as `scaffold serve` receives them.
+//!
+//! GitHub signs each delivery with the webhook's secret, as an HMAC-SHA256
+//! of the body in the `X-Hub-Signature-256` header; [`verify`] checks it
+//! before anything in the body is trusted. [`Push`] holds the parts of a
+//! push event that decide whether the roadmap changed.
+
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+/// Whether `signature`, the value of the `X-Hub-Signature-256` header, is
+/// `body` signed with `secret`. The comparison takes the same time however
+/// much of the signature matches.
+pub fn verify(secret: &[u8], body: &[u8], signature: Option<&str>) -> bool {
+    let Some(digest) = signature
+        .and_then(|signature| signature.strip_prefix("sha256="))
+        .and_then(|hex| hex::decode(hex.trim()).ok())
+    else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac.verify_slice(&digest).is_ok()
+}
+
+/// A `push` event.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Push {
+    /// The ref pushed to, e.g. `refs/heads/main`.
+    #[serde(rename = "ref")]
+    pub reference: String,
+    /// The push deleted the ref.
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+    pub repository: PushRepository,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushCommit {
+    #[serde(default)]
+    pub added: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+    #[serde(default)]
+    pub modified: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushRepository {
+    pub full_name: String,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+}
+
+impl Push {
+    /// The branch pushed to; `None` for tags.
+    pub fn branch(&self) -> Option<&str> {
+        self.reference.strip_prefix("refs/heads/")
+    }
+
+    /// The paths, relative to the repository root, that the pushed commits
+    /// added, removed or modified.
+    pub fn changed(&self) -> impl Iterator<Item = &str> {
+        self.commits.iter().flat_map(|commit| {
+            commit
+                .added
+                .iter()
+                .chain(&commit.removed)
+                .chain(&commit.modified)
+                .map(String::as_str)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_signatures_and_reads_pushes() {
+        // The example in GitHub's documentation on validating deliveries.
+        let secret = b"It's a Secret to Everybody";
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert!(verify(secret, b"Hello, World!", Some(signature)));
+        assert!(!verify(secret, b"Hello, World?", Some(signature)));
+        assert!(!verify(b"other", b"Hello, World!", Some(signature)));
+        assert!(!verify(secret, b"Hello, World!", Some("sha1=757107ea")));
+        assert!(!verify(secret, b"Hello, World!", None));
+
+        let push: Push = serde_json::from_str(
+            r#"{"ref": "refs/heads/main", "repository": {"full_name": "acme/app"},
+                "commits": [{"added": ["docs/a.md"], "modified": ["ROADMAP.md"]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(push.branch(), Some("main"));
+        assert_eq!(
+            push.changed().collect::<Vec<_>>(),
+            ["docs/a.md", "ROADMAP.md"]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::fs;
+use std::process::Command;
+
+fn status(bin: &str, dir: &std::path::Path, args: &[&str]) -> Option<i32> {
+    Command::new(bin)
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+#[test]
+fn exit_codes_follow_the_documented_contract() {
+    let dir = std::env::temp_dir().join(format!("gitscaffold-exit-codes-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("broken.yml"), "name: [\n").unwrap();
+    let scaffold = env!("CARGO_BIN_EXE_scaffold");
+    let mdparser = env!("CARGO_BIN_EXE_mdparser");
+
+    assert_eq!(status(mdparser, &dir, &["broken.yml"]), Some(2));
+    assert_eq!(status(mdparser, &dir, &["missing.md"]), Some(3));
+    assert_eq!(status(mdparser, &dir, &["--no-such-flag"]), Some(6));
+    assert_eq!(status(mdparser, &dir, &["--help"]), Some(0));
+    assert_eq!(
+        status(scaffold, &dir, &["--no-cache", "validate", "broken.yml"]),
+        Some(2)
+    );
+    assert_eq!(
+        status(scaffold, &dir, &["validate", "--no-such-flag"]),
+        Some(6)
+    );
+    assert_eq!(status(scaffold, &dir, &[]), Some(6));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,33 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn annotations_keep_json_reports_parseable() {
+    let dir =
+        std::env::temp_dir().join(format!("gitscaffold-github-output-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("ROADMAP.md"),
+        "# P\n\n## Features\n\n### Login\n\nDepends on: Nowhere\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_scaffold"))
+        .current_dir(&dir)
+        .env_remove("GITHUB_OUTPUT")
+        .args(["--github-output", "--no-cache", "validate", "ROADMAP.md"])
+        .args(["--report-format", "json"])
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let codes: Vec<&str> = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|diagnostic| diagnostic["code"].as_str())
+        .collect();
+    assert!(codes.contains(&"GS006"), "{:?}", codes);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("title=GS006::"), "{}", stderr);
+}
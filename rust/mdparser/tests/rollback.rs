@@ -0,0 +1,47 @@
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn dry_run_keeps_a_run_with_nothing_left_to_undo() {
+    let dir = std::env::temp_dir().join(format!("gitscaffold-rollback-{}", std::process::id()));
+    let runs = dir.join(".gitscaffold/runs");
+    fs::create_dir_all(&runs).unwrap();
+    let run = runs.join("20250101T000000Z.json");
+    fs::write(
+        &run,
+        r#"{"version":1,"id":"20250101T000000Z","repo":"acme/app",
+            "finished":"2025-01-01T00:00:00Z","milestones":[],
+            "issues":[{"title":"Login","number":7,"created":true}]}"#,
+    )
+    .unwrap();
+    let state = dir.join(".gitscaffold/state.json");
+    let recorded = r#"{"version":1,"repos":{"acme/app":{"features":{"Login":7}}}}"#;
+    fs::write(&state, recorded).unwrap();
+    // The issue was deleted by hand since, so nothing is left to undo.
+    fs::create_dir_all(dir.join("fixtures")).unwrap();
+    fs::write(
+        dir.join("fixtures/fixtures.json"),
+        r#"{"forge":"github","repo":"acme/app","api_url":"https://api.github.com",
+            "interactions":[
+              {"method":"GET","path":"/repos/acme/app/issues?state=all&per_page=100&page=1",
+               "status":200,"response":[]},
+              {"method":"GET","path":"/repos/acme/app/milestones?state=all&per_page=100&page=1",
+               "status":200,"response":[]}]}"#,
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_scaffold"))
+        .current_dir(&dir)
+        .args(["rollback", "20250101T000000Z", "--dry-run"])
+        .args(["--forge", "mock", "--fixtures", "fixtures"])
+        .output()
+        .unwrap();
+    let kept = (run.exists(), fs::read_to_string(&state).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(kept, (true, recorded.to_string()));
+}